@@ -0,0 +1,90 @@
+//! Parse, optimize, analyze and conflict detection time on synthetic files.
+//!
+//!   cargo bench --bench gix
+//!   cargo bench --bench gix -- optimize/aggressive    # one group
+//!   cargo bench --bench gix --features parallel -- conflicts
+//!
+//! The files come from `gix bench --size small|medium|huge`, which writes them for profiling.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use gix::cli::args::OptimizationMode;
+use gix::core::optimizer::{analyze_gitignore, optimize_gitignore_aggressive_tracked, optimize_gitignore_tracked, PassStats};
+use gix::core::pattern_analyzer::PatternAnalyzer;
+use gix::utils::synthetic::{synthetic_gitignore, FixtureSize};
+use gix::{parse_gitignore, EngineConfig, GitignoreFile, GixEngine};
+use std::hint::black_box;
+
+fn fixtures() -> Vec<(FixtureSize, String, GitignoreFile)> {
+    FixtureSize::ALL.into_iter()
+        .map(|size| {
+            let content = synthetic_gitignore(size.lines());
+            let file = parse_gitignore(&content).unwrap();
+            (size, content, file)
+        })
+        .collect()
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (size, content, _) in fixtures() {
+        group.bench_with_input(BenchmarkId::from_parameter(size.name()), &content, |b, content| {
+            b.iter(|| parse_gitignore(black_box(content)))
+        });
+    }
+    group.finish();
+}
+
+fn optimize(c: &mut Criterion) {
+    let fixtures = fixtures();
+    for mode in [OptimizationMode::Conservative, OptimizationMode::Standard, OptimizationMode::Aggressive, OptimizationMode::Advanced] {
+        let engine = GixEngine::new(EngineConfig { mode: mode.clone(), offline: true, ..EngineConfig::default() });
+        let mut group = c.benchmark_group(format!("optimize/{}", mode.name()));
+        group.sample_size(10);
+        for (size, _, file) in &fixtures {
+            group.bench_with_input(BenchmarkId::from_parameter(size.name()), file, |b, file| {
+                b.iter(|| engine.optimize(black_box(file)))
+            });
+        }
+        group.finish();
+    }
+}
+
+/// The deduplication passes alone, without the safety checks around them
+fn dedup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dedup");
+    for (size, _, file) in fixtures() {
+        group.bench_with_input(BenchmarkId::new("conservative", size.name()), &file, |b, file| {
+            b.iter(|| optimize_gitignore_tracked(black_box(file), &PatternAnalyzer::safe(), &mut PassStats::default()))
+        });
+        group.bench_with_input(BenchmarkId::new("aggressive", size.name()), &file, |b, file| {
+            b.iter(|| optimize_gitignore_aggressive_tracked(black_box(file), &PatternAnalyzer::heuristic(), &mut PassStats::default()))
+        });
+    }
+    group.finish();
+}
+
+fn analyze(c: &mut Criterion) {
+    let mut group = c.benchmark_group("analyze");
+    group.sample_size(10);
+    for (size, _, file) in fixtures() {
+        group.bench_with_input(BenchmarkId::from_parameter(size.name()), &file, |b, file| {
+            b.iter(|| analyze_gitignore(black_box(file)))
+        });
+    }
+    group.finish();
+}
+
+fn conflicts(c: &mut Criterion) {
+    let analyzer = PatternAnalyzer::default();
+    let mut group = c.benchmark_group("conflicts");
+    for (size, _, file) in fixtures() {
+        let patterns: Vec<String> = file.entries.iter().filter_map(|entry| entry.comparison_pattern()).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(size.name()), &patterns, |b, patterns| {
+            b.iter(|| analyzer.find_conflicts(black_box(patterns)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, optimize, dedup, analyze, conflicts);
+criterion_main!(benches);
@@ -2,7 +2,7 @@ use gix::{
     core::{
         parse_gitignore, optimize_gitignore, analyze_gitignore,
         PatternAnalyzer, PatternCategorizer, CommentGenerator,
-        PatternCategory, GitignoreAnalysis
+        PatternCategory
     },
     models::GitignoreFile,
 };
@@ -118,7 +118,7 @@ local/
     
     for pattern in &pattern_strings {
         let analysis = analyzer.analyze_pattern(pattern);
-        let category = categorizer.categorize_pattern(pattern);
+        let category = categorizer.primary_category(pattern);
         
         if let Some(comment) = comment_generator.generate_pattern_comment(pattern, &analysis) {
             println!("{} -> {}", pattern, comment);
@@ -57,8 +57,8 @@ build/
     let duplicates = original_file.find_duplicates();
     if !duplicates.is_empty() {
         println!("Found duplicate patterns:");
-        for (pattern, line_numbers) in &duplicates {
-            println!("  {} (lines: {:?})", pattern, line_numbers);
+        for group in &duplicates {
+            println!("  {} (lines: {:?})", group.pattern, group.line_numbers);
         }
         println!();
     }
@@ -66,7 +66,7 @@ build/
     // Optimize the file
     let optimized_file = optimize_gitignore(&original_file)?;
     println!("Optimized .gitignore content:");
-    println!("{}", optimized_file.to_string());
+    println!("{}", optimized_file);
     println!();
 
     println!("Optimization results:");
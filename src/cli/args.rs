@@ -1,7 +1,31 @@
-use clap::{Parser, ValueEnum};
-use std::path::PathBuf;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use crate::core::optimizer::{AnalysisFilter, AnalysisScope};
+use crate::core::pattern_analyzer::PatternAnalyzer;
+use crate::core::categorizer::PatternCategorizer;
+use crate::core::comment_generator::CommentGenerator;
+use crate::core::fleet::FleetFormat;
+use crate::core::parser::Dialect;
+use crate::core::plugins::PluginRegistry;
+use crate::core::lint::DEFAULT_EXAMPLE_BUDGET;
+use crate::core::remote_template::TemplateSource;
+use crate::core::risk::ChangeRisk;
+use crate::core::rules::{RuleSeverities, Severity};
+use crate::core::whitespace::{WhitespacePolicy, WhitespaceRule};
+use crate::utils::git::{core_ignore_case, MetadataLocation};
+use crate::utils::{BackupPolicy, DEFAULT_BACKUP_KEEP};
+use crate::utils::synthetic::FixtureSize;
+use crate::utils::walker::WalkOptions;
+use crate::engine::{EngineConfig, GixEngine};
+use crate::models::{Config, GixError};
+pub use crate::models::{KeepOccurrence, OptimizationMode, SortOrder};
+use crate::models::ReportFormat;
+#[cfg(feature = "dataset")]
+use crate::core::dataset::Dataset;
 
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 #[command(
     name = "gix",
     about = "Optimize .gitignore files by removing duplicates and normalizing patterns",
@@ -9,6 +33,9 @@ use std::path::PathBuf;
     long_about = "GIX is a command-line tool that optimizes .gitignore files by detecting and removing duplicate patterns, normalizing whitespace, and preserving comments and blank lines while maintaining the file's functionality."
 )]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Path to the .gitignore file (defaults to .gitignore in current directory)
     #[arg(value_name = "FILE")]
     pub file: Option<PathBuf>,
@@ -17,14 +44,86 @@ pub struct Args {
     #[arg(short, long, value_name = "OUTPUT")]
     pub output: Option<PathBuf>,
 
+    /// Optimize every .gitignore below the directory given as FILE (defaults to the current directory)
+    #[arg(short, long, conflicts_with_all = ["output", "check", "removals_log"])]
+    pub recursive: bool,
+
+    /// With --recursive, skip directories matching this gitignore-style glob (repeatable)
+    #[arg(long = "exclude-path", value_name = "GLOB", requires = "recursive")]
+    pub exclude_paths: Vec<String>,
+
+    /// With --recursive, descend into submodules and other nested repositories
+    #[arg(long, requires = "recursive")]
+    pub include_submodules: bool,
+
+    /// Ignore layers to process: repo (FILE), info (.git/info/exclude), global (core.excludesFile)
+    #[arg(long, value_enum, value_name = "SCOPES", value_delimiter = ',', default_value = "repo", conflicts_with = "recursive")]
+    pub scope: Vec<Scope>,
+
+    /// Create the file when it does not exist instead of failing
+    #[arg(long, env = "GIX_CREATE_IF_MISSING", conflicts_with = "recursive")]
+    pub create_if_missing: bool,
+
+    /// Seed a file created by --create-if-missing with the sections of this repository that fit the project
+    #[arg(long, value_name = "REPO", requires = "create_if_missing")]
+    pub like: Option<PathBuf>,
+
+    /// What to do when the file has no patterns or comments
+    #[arg(long, value_enum, env = "GIX_ON_EMPTY", default_value_t = EmptyFilePolicy::Warn)]
+    pub on_empty: EmptyFilePolicy,
+
+    /// Don't warn when the output path is ignored by the file being optimized
+    #[arg(long)]
+    pub no_output_checks: bool,
+
+    /// Also write the removed patterns, with their line numbers and section comments, to this file
+    #[arg(long, value_name = "FILE")]
+    pub removals_log: Option<PathBuf>,
+
     /// Create a backup of the original file before modifying
     #[arg(short, long)]
     pub backup: bool,
 
+    /// Exit with 1 when optimization changed a file, or on a dry run would change one, and 0 when it is already optimized
+    #[arg(long)]
+    pub exit_code: bool,
+
+    /// Don't create a backup, even if gix.toml asks for one
+    #[arg(long, conflicts_with = "backup")]
+    pub no_backup: bool,
+
+    /// Put backups in this directory instead of next to each file (or in .git/gix/backups with --metadata git)
+    #[arg(long, value_name = "DIR", global = true)]
+    pub backup_dir: Option<PathBuf>,
+
+    /// Keep this many timestamped backups of each file, deleting the oldest (0 keeps all; default 10)
+    #[arg(long, value_name = "N", global = true)]
+    pub backup_keep: Option<usize>,
+
     /// Optimization mode
     #[arg(short, long, value_enum, default_value_t = OptimizationMode::Standard)]
     pub mode: OptimizationMode,
 
+    /// Only perform changes that cannot alter which files are ignored (default)
+    #[arg(long, conflicts_with = "allow_heuristics")]
+    pub safe: bool,
+
+    /// Also merge patterns that are usually but not always equivalent (build/build/, /build, **/build)
+    #[arg(long)]
+    pub allow_heuristics: bool,
+
+    /// Treat patterns differing only in case (build/, BUILD/) as duplicates; defaults to git's core.ignorecase
+    #[arg(long)]
+    pub ignore_case: bool,
+
+    /// Keep patterns differing only in case apart, even if git's core.ignorecase is true
+    #[arg(long, conflicts_with = "ignore_case")]
+    pub no_ignore_case: bool,
+
+    /// Skip changes riskier than this (safe, likely-safe, behavior-may-change); no limit by default
+    #[arg(long, value_name = "RISK")]
+    pub max_risk: Option<ChangeRisk>,
+
     /// Show detailed statistics about the optimization
     #[arg(short, long)]
     pub stats: bool,
@@ -33,18 +132,110 @@ pub struct Args {
     #[arg(long)]
     pub dry_run: bool,
 
+    /// Write the edits to a JSON plan for `gix apply-plan` instead of modifying any file
+    #[arg(long, value_name = "PLAN", conflicts_with = "output")]
+    pub emit_plan: Option<PathBuf>,
+
+    /// Check the file against the whitespace policy without modifying it (exits non-zero on violations)
+    #[arg(long)]
+    pub check: bool,
+
+    /// Whitespace rules enforced by --check (trailing-spaces, blank-lines, indentation; defaults to all)
+    #[arg(long, value_name = "RULES", value_delimiter = ',', requires = "check")]
+    pub whitespace: Vec<WhitespaceRule>,
+
+    /// Report format for --check results (text, json, sarif, or CI annotations for github, gitlab, bitbucket) and --analyze (text or json)
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    pub format: ReportFormat,
+
     /// Verbose output
     #[arg(short, long)]
     pub verbose: bool,
 
+    /// Show how long optimization took and how many lines each pass removed
+    #[arg(long)]
+    pub timings: bool,
+
     /// Analyze patterns and show categorization
     #[arg(long)]
     pub analyze: bool,
 
+    /// Limit the analysis to these aspects (conflicts, negations, wildcards, globstar, absolute, directories, files)
+    #[arg(long, value_name = "SCOPES", value_delimiter = ',', requires = "analyze")]
+    pub only: Vec<AnalysisScope>,
+
+    /// Limit the analysis to patterns in this category (e.g. Python)
+    #[arg(long, value_name = "CATEGORY", requires = "analyze")]
+    pub category: Option<String>,
+
+    /// Remove literal patterns that match nothing in the working tree (build outputs and wildcards are kept)
+    #[arg(long, conflicts_with_all = ["recursive", "scope"])]
+    pub prune_unused: bool,
+
+    /// Use the embedded pattern dataset even if a newer one was downloaded
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Record every file gix writes in the audit log, .git/gix/audit.log (hashes and counts only)
+    #[arg(long, global = true, env = "GIX_AUDIT_LOG")]
+    pub audit_log: bool,
+
+    /// Don't record writes in the history `gix undo` restores from, even if gix.toml asks for it
+    #[arg(long, global = true, env = "GIX_NO_HISTORY")]
+    pub no_history: bool,
+
+    /// Where to keep backups and the audit log: `.git/gix/` so the tree stays clean (`.gix/` outside a repository), or `.gix/` in the working tree
+    #[arg(long, value_enum, global = true, env = "GIX_METADATA", default_value_t = MetadataLocation::Git)]
+    pub metadata: MetadataLocation,
+
+    /// Syntax of the ignore file: gitignore, dockerignore, npmignore, eslintignore or prettierignore (defaults to the file name's)
+    #[arg(long, value_enum, global = true)]
+    pub dialect: Option<Dialect>,
+
+    /// Never ask for confirmation; fail where gix would ask (implied when stdin is not a terminal)
+    #[arg(long, global = true, env = "GIX_NON_INTERACTIVE")]
+    pub non_interactive: bool,
+
+    /// Run this command as a plugin for categories, lint rules and suggestions (repeatable)
+    #[arg(long = "plugin", value_name = "CMD", global = true)]
+    pub plugins: Vec<PathBuf>,
+
+    /// Also run the plugins listed in the repository's gix.toml files (only for trusted checkouts)
+    #[arg(long, global = true)]
+    pub allow_plugins: bool,
+
     /// Detect and report pattern conflicts
     #[arg(long)]
     pub detect_conflicts: bool,
 
+    /// Exit with an error, without writing the file, when --detect-conflicts finds conflicts
+    #[arg(long, requires = "detect_conflicts")]
+    pub fail_on_conflicts: bool,
+
+    /// Optimize the result again and fail, without writing, if that changes a single byte
+    #[arg(long)]
+    pub check_idempotent: bool,
+
+    /// Write the file even if optimizing removed every pattern from it
+    #[arg(long)]
+    pub allow_empty: bool,
+
+    /// Move each negation next to the pattern it re-includes paths from, when that is order-safe
+    #[arg(long)]
+    pub group_negations: bool,
+
+    /// Trim trailing spaces git ignores, cut runs of blank lines to two, and write `#comment` as `# comment`
+    #[arg(long)]
+    pub normalize_whitespace: bool,
+
+    /// Reorder patterns within each section, keeping negations where they take effect
+    #[arg(long, value_enum, default_value_t = SortOrder::None)]
+    pub sort: SortOrder,
+
+    /// Which occurrence of a duplicated pattern deduplication keeps
+    #[arg(long, value_enum, default_value_t = KeepOccurrence::First)]
+    pub keep: KeepOccurrence,
+
     /// Generate comments for patterns
     #[arg(long)]
     pub generate_comments: bool,
@@ -52,44 +243,766 @@ pub struct Args {
     /// Show pattern categories
     #[arg(long)]
     pub show_categories: bool,
+
+    /// Settings from gix.toml and the user's config file
+    #[arg(skip)]
+    pub config: Config,
+
+    /// Category definitions from the user's config directory, merged over the dataset
+    #[cfg(feature = "dataset")]
+    #[arg(skip)]
+    pub definitions: Option<Dataset>,
+
+    /// Settings given on the command line, which no config file overrides
+    #[arg(skip)]
+    command_line: Vec<&'static str>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Rewrite the file grouped into sections by category, with generated headers
+    Organize {
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Output file (defaults to overwriting the input file)
+        #[arg(short, long, value_name = "OUTPUT")]
+        output: Option<PathBuf>,
+
+        /// Print the organized file instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Create a backup of the original file before modifying
+        #[arg(short, long)]
+        backup: bool,
+
+        /// Don't create a backup, even if gix.toml asks for one
+        #[arg(long, conflicts_with = "backup")]
+        no_backup: bool,
+
+        /// Keep each negation right after the pattern it re-includes paths from, where no path is decided differently
+        #[arg(long)]
+        pair_negations: bool,
+    },
+    /// Combine several .gitignore files into one, dropping patterns repeated across them
+    Merge {
+        /// Files to merge, in order; later files' duplicates are dropped
+        #[arg(value_name = "FILES", required = true, num_args = 2..)]
+        files: Vec<PathBuf>,
+
+        /// File to write (may be one of the inputs)
+        #[arg(short, long, value_name = "OUTPUT", default_value = ".gitignore")]
+        output: PathBuf,
+
+        /// Print the merged file instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Create a backup of the output file before overwriting it
+        #[arg(short, long)]
+        backup: bool,
+    },
+    /// Start a new .gitignore, from another repository's file or from the built-in pattern sets
+    Init {
+        /// Repository (or .gitignore file) to learn from; its sections for other stacks are dropped
+        #[arg(long, value_name = "REPO", required_unless_present_any = ["lang", "framework", "tool", "os"], conflicts_with_all = ["lang", "framework", "tool", "os"])]
+        like: Option<PathBuf>,
+
+        /// Languages to generate sections for (e.g. rust,node)
+        #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+        lang: Vec<String>,
+
+        /// Frameworks to generate sections for (e.g. react)
+        #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+        framework: Vec<String>,
+
+        /// Tools to generate sections for (e.g. vscode,vim)
+        #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+        tool: Vec<String>,
+
+        /// Operating systems to generate sections for (e.g. macos,windows)
+        #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+        os: Vec<String>,
+
+        /// File to create; the project is detected from its directory
+        #[arg(short, long, value_name = "OUTPUT", default_value = ".gitignore")]
+        output: PathBuf,
+
+        /// Print the generated file instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Overwrite the output file if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show whether paths are ignored and which line decides it, like `git check-ignore -v`
+    TestPath {
+        /// Paths to test, relative to the directory of the .gitignore file; a trailing / marks a directory
+        #[arg(value_name = "PATHS", required = true)]
+        paths: Vec<String>,
+
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// List every pattern that matches a path, in the order git applies them, and which one wins
+    Explain {
+        /// Path to explain, relative to the directory of the .gitignore file; a trailing / marks a directory
+        #[arg(value_name = "PATH")]
+        path: String,
+
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// Add patterns under the section of their category, creating the section if needed
+    ///
+    /// A pattern already in the file is not added again, and one an existing pattern already
+    /// covers is refused (skipped when adding several) unless --force is given.
+    Add {
+        /// The patterns to add, as they would be written in the file (after `--` if one starts with `-`)
+        #[arg(value_name = "PATTERN", required_unless_present = "from")]
+        patterns: Vec<String>,
+
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Also add the patterns of another ignore file, such as a template
+        #[arg(long, value_name = "FILE")]
+        from: Option<PathBuf>,
+
+        /// Only take the patterns of --from whose text matches this glob (repeatable)
+        #[arg(long, value_name = "GLOB", requires = "from")]
+        matching: Vec<String>,
+
+        /// Section to add the pattern to, by its header without the `#`, instead of its category's
+        #[arg(long, value_name = "NAME")]
+        section: Option<String>,
+
+        /// Add the pattern even if an existing pattern already covers it
+        #[arg(long)]
+        force: bool,
+
+        /// Show the changes instead of writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Remove a pattern by value or line number, warning about files that would stop being ignored
+    #[command(visible_alias = "rm")]
+    Remove {
+        /// The pattern to remove, as written in the file; every occurrence goes
+        #[arg(value_name = "PATTERN", allow_hyphen_values = true, required_unless_present_any = ["line", "matching"], conflicts_with_all = ["line", "matching"])]
+        pattern: Option<String>,
+
+        /// Line number of the pattern to remove instead
+        #[arg(long, value_name = "N", conflicts_with = "matching")]
+        line: Option<usize>,
+
+        /// Remove every pattern whose text matches this glob instead, e.g. '*.log*' (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        matching: Vec<String>,
+
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Show the changes and the files they expose instead of writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show what appending a pattern would do: files it would ignore, tracked files it would hide, lines it repeats or conflicts with
+    SimulateAdd {
+        /// The pattern to try, as it would be written in the file
+        #[arg(value_name = "PATTERN", allow_hyphen_values = true)]
+        pattern: String,
+
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// Report how far the file has drifted from a template: missing and extra patterns
+    Compare {
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Built-in pattern set to compare with (e.g. rust, node, macos), or a template file
+        #[arg(long, value_name = "TEMPLATE")]
+        template: String,
+    },
+    /// Suggest known patterns for the languages, frameworks and editors detected in the project
+    Suggest {
+        /// Path to the .gitignore file (defaults to .gitignore in current directory); the project is detected from its directory
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// List patterns that ignore files git already tracks (exits 1 if any)
+    Audit {
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// Check that a file ignores the same paths as another, such as its version before optimizing (exits 1 if not)
+    Verify {
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// The file to compare with
+        #[arg(long, value_name = "OLD")]
+        against: PathBuf,
+
+        /// Only check paths derived from the patterns, not the paths of the working tree
+        #[arg(long)]
+        no_tree: bool,
+    },
+    /// Check gix's matching against the installed git in a scratch repository (exits 1 on any difference)
+    SelfTest,
+    /// Make the edits of a plan written by --emit-plan, if every file is still as it was planned
+    ApplyPlan {
+        /// The plan to apply
+        #[arg(value_name = "PLAN")]
+        plan: PathBuf,
+    },
+    /// Generate the synthetic .gitignore files the benchmarks run on, for profiling
+    #[command(hide = true)]
+    Bench {
+        /// Size of the file: small (200 lines), medium (2,000) or huge (20,000)
+        #[arg(long, value_enum, default_value_t = FixtureSize::Medium)]
+        size: FixtureSize,
+
+        /// File to write (defaults to printing it)
+        #[arg(short, long, value_name = "OUTPUT")]
+        output: Option<PathBuf>,
+    },
+    /// Analyze the patterns of a file without modifying it: kinds, features, conflicts, unused patterns and groups
+    Analyze {
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Report format (text, or json for the analysis as one document)
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+
+        /// Limit the analysis to these aspects (conflicts, negations, wildcards, globstar, absolute, directories, files)
+        #[arg(long, value_name = "SCOPES", value_delimiter = ',')]
+        only: Vec<AnalysisScope>,
+
+        /// Limit the analysis to patterns in this category (e.g. Python)
+        #[arg(long, value_name = "CATEGORY")]
+        category: Option<String>,
+    },
+    /// Report duplicates, conflicts, redundant and invalid patterns without modifying the file (exits 1 on issues)
+    Check {
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Report format (text, json with stable finding ids, sarif, or CI annotations for github, gitlab, bitbucket)
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+
+        /// Show up to three paths from the working tree that each finding applies to
+        #[arg(long)]
+        examples: bool,
+
+        /// Most working-tree paths examined per finding when looking for examples
+        #[arg(long, value_name = "PATHS", default_value_t = DEFAULT_EXAMPLE_BUDGET, requires = "examples")]
+        example_budget: usize,
+
+        /// Don't report findings of these rules, by code or name (GIX001, duplicate, ...)
+        #[arg(long, value_name = "RULES", value_delimiter = ',')]
+        allow: Vec<String>,
+
+        /// Report findings of these rules without failing the check
+        #[arg(long, value_name = "RULES", value_delimiter = ',')]
+        warn: Vec<String>,
+
+        /// Fail the check on findings of these rules, even if gix.toml allows them
+        #[arg(long, value_name = "RULES", value_delimiter = ',')]
+        deny: Vec<String>,
+    },
+    /// List the rules of `gix check` with their codes and the severity gix.toml gives them
+    Rules,
+    /// Re-optimize a .gitignore (or check it) every time it is saved, until interrupted
+    Watch {
+        /// The .gitignore to watch, or with --recursive the directory to watch (defaults to .gitignore / the current directory)
+        #[arg(value_name = "PATH")]
+        path: Option<PathBuf>,
+
+        /// Watch every .gitignore below the directory, including new ones
+        #[arg(long)]
+        recursive: bool,
+
+        /// Report lint findings on each save instead of optimizing
+        #[arg(long)]
+        check: bool,
+
+        /// Milliseconds a file must go unchanged before it is processed
+        #[arg(long, value_name = "MS", default_value_t = 300)]
+        debounce: u64,
+    },
+    /// Summarize every ignore file in a repository: stats, cross-file duplicates and health scores
+    Scan {
+        /// Repository root to scan (defaults to the current directory)
+        #[arg(value_name = "DIR")]
+        dir: Option<PathBuf>,
+
+        /// Write the summary as Markdown to this file (e.g. .gix/summary.md) instead of printing it
+        #[arg(long, value_name = "FILE")]
+        write_summary: Option<PathBuf>,
+
+        /// Descend into submodules and other nested repositories (skipped by default)
+        #[arg(long)]
+        include_submodules: bool,
+
+        /// Skip directories matching this gitignore-style glob (repeatable, e.g. vendor, /third_party)
+        #[arg(long = "exclude-path", value_name = "GLOB")]
+        exclude_paths: Vec<String>,
+    },
+    /// Report build-output and secret patterns that .gitignore and .dockerignore don't share (exits 1 if any)
+    SyncCheck {
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// The .dockerignore file to compare with
+        #[arg(long, value_name = "DOCKERIGNORE")]
+        against: PathBuf,
+
+        /// Append the missing patterns to each file, translated to its dialect
+        #[arg(long)]
+        sync: bool,
+    },
+    /// Download a template from github/gitignore or gitignore.io and append the patterns the file lacks
+    AddTemplate {
+        /// Template name (e.g. python, node, rust)
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// Path to the .gitignore file (defaults to .gitignore in current directory)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Where to download the template from (github or gitignoreio)
+        #[arg(long, value_name = "SOURCE", default_value = "github")]
+        source: TemplateSource,
+
+        /// Download the template again even if it is cached
+        #[arg(long)]
+        refresh: bool,
+
+        /// Show the changes instead of writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Manage the pattern dataset used for categories and generated comments
+    Dataset {
+        #[command(subcommand)]
+        action: DatasetAction,
+    },
+    /// Restore files as they were before gix's last writes
+    Undo {
+        /// Number of writes to undo, newest first
+        #[arg(long, value_name = "N", default_value_t = 1)]
+        steps: usize,
+
+        /// Directory inside the repository (defaults to the current directory)
+        #[arg(value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+    /// List the writes `gix undo` can take back, oldest first (--verbose lists their changes)
+    History {
+        /// Directory inside the repository (defaults to the current directory)
+        #[arg(value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+    /// Inspect the log of writes recorded with --audit-log
+    AuditLog {
+        #[command(subcommand)]
+        action: AuditLogAction,
+    },
+    /// Assess many repositories at once, for teams tracking adoption across an organization
+    Fleet {
+        #[command(subcommand)]
+        action: FleetAction,
+    },
 }
 
-#[derive(Clone, Debug, ValueEnum)]
-pub enum OptimizationMode {
-    /// Standard optimization (remove duplicate patterns, preserve comments and blank lines)
-    Standard,
-    /// Aggressive optimization (also remove duplicate comments and limit blank lines)
-    Aggressive,
-    /// Conservative optimization (only remove exact duplicates)
-    Conservative,
-    /// Advanced optimization (use pattern analysis for better deduplication)
-    Advanced,
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum FleetAction {
+    /// Run check and the whitespace policy on every repository and report per-repository scores and failing rules
+    Report {
+        /// File listing one checkout path or clone URL per line (paths are relative to the file)
+        #[arg(long, value_name = "FILE", required_unless_present = "dir", conflicts_with = "dir")]
+        repos_from: Option<PathBuf>,
+
+        /// Directory whose subdirectories are the checkouts to assess
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+
+        /// Where repositories listed by URL are cloned, and read from on later runs
+        #[arg(long, value_name = "DIR")]
+        checkout_dir: Option<PathBuf>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = FleetFormat::Markdown)]
+        format: FleetFormat,
+
+        /// Write the report to this file instead of printing it
+        #[arg(short, long, value_name = "OUTPUT")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum AuditLogAction {
+    /// List the recorded writes, oldest first
+    Show {
+        /// Directory inside the repository (defaults to the current directory)
+        #[arg(value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+    /// Check that no record was altered or removed, and list files changed since gix last wrote them (exits 1 if altered)
+    Verify {
+        /// Directory inside the repository (defaults to the current directory)
+        #[arg(value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum DatasetAction {
+    /// Download a newer dataset into the cache directory and use it from then on
+    Update {
+        /// Dataset URL; its SHA-256 checksum is read from <URL>.sha256
+        #[arg(long)]
+        url: Option<String>,
+    },
+    /// Show which dataset is active
+    Status,
+}
+
+/// A layer of ignore rules, in order of decreasing precedence
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Scope {
+    /// The .gitignore given as FILE
+    Repo,
+    /// The repository's .git/info/exclude, next to FILE
+    Info,
+    /// The user's global excludes file
+    Global,
+}
+
+impl Scope {
+    /// Get the name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scope::Repo => "repo",
+            Scope::Info => "info",
+            Scope::Global => "global",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum EmptyFilePolicy {
+    /// Explain that there is nothing to optimize and suggest `gix init`
+    Warn,
+    /// Fail, for scripts that expect a populated file
+    Error,
 }
 
 impl Args {
+    /// Parse the command line, then fill in what it leaves unset from the config files that apply
+    pub fn parse_with_config() -> Result<Self, GixError> {
+        let matches = Self::command().get_matches();
+        let mut args = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        let config = Config::load(&args.config_dir())?;
+        args.apply_config(config, &matches);
+        #[cfg(feature = "dataset")]
+        {
+            args.definitions = Dataset::user_definitions()?;
+        }
+        Ok(args)
+    }
+
+    /// Directory whose repository's gix.toml applies: the one holding FILE, or the current directory
+    pub fn config_dir(&self) -> PathBuf {
+        match &self.file {
+            Some(file) if file.is_dir() => file.clone(),
+            Some(file) => file.parent().filter(|parent| !parent.as_os_str().is_empty()).map_or_else(|| PathBuf::from("."), PathBuf::from),
+            None => PathBuf::from("."),
+        }
+    }
+
+    /// Take settings from `config` wherever `matches` shows the command line left them at their defaults
+    pub fn apply_config(&mut self, config: Config, matches: &ArgMatches) {
+        let defaulted = |id: &str| matches!(matches.value_source(id), None | Some(ValueSource::DefaultValue));
+        self.command_line = ["mode", "format", "metadata", "backup"].into_iter().filter(|id| !defaulted(id)).collect();
+        self.use_config(config);
+    }
+
+    /// Replace the config applied so far, keeping the settings given on the command line
+    ///
+    /// `--recursive` uses this for files below a nested `gix.toml`.
+    pub fn use_config(&mut self, config: Config) {
+        let from_config = |id: &str| !self.command_line.contains(&id);
+        if let Some(mode) = config.mode.clone().filter(|_| from_config("mode")) {
+            self.mode = mode;
+        }
+        if let Some(format) = config.format.filter(|_| from_config("format")) {
+            self.format = format;
+        }
+        if let Some(metadata) = config.metadata.filter(|_| from_config("metadata")) {
+            self.metadata = metadata;
+        }
+        if from_config("backup") {
+            self.backup = config.backup == Some(true) && !self.no_backup;
+        }
+        self.config = config;
+    }
+
     /// Get the input file path, defaulting to .gitignore in current directory
     pub fn input_file(&self) -> PathBuf {
         self.file.clone().unwrap_or_else(|| PathBuf::from(".gitignore"))
     }
 
+    /// Get the dialect of the ignore file at `path`: the one given with --dialect, or else the one its name implies
+    pub fn dialect_of(&self, path: &Path) -> Dialect {
+        self.dialect.unwrap_or_else(|| Dialect::from_path(path))
+    }
+
     /// Get the output file path
     pub fn output_file(&self) -> PathBuf {
         self.output.clone().unwrap_or_else(|| self.input_file())
     }
 
+    /// Get the directory walked by --recursive, defaulting to the current directory
+    pub fn root_dir(&self) -> PathBuf {
+        self.file.clone().unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// Get the selected scopes without repeats, in order of decreasing precedence
+    pub fn scopes(&self) -> Vec<Scope> {
+        let mut scopes = self.scope.clone();
+        scopes.sort();
+        scopes.dedup();
+        scopes
+    }
+
+    /// Get the walk options for --recursive
+    pub fn walk_options(&self) -> WalkOptions {
+        WalkOptions {
+            include_submodules: self.include_submodules,
+            exclude_paths: self.exclude_paths.clone(),
+        }
+    }
+
     /// Check if we should create a backup
     pub fn should_backup(&self) -> bool {
         self.backup
     }
+
+    /// Get where backups go and how many are kept, from the command line or else the config
+    pub fn backup_policy(&self) -> BackupPolicy {
+        BackupPolicy {
+            location: self.metadata,
+            dir: self.backup_dir.clone().or_else(|| self.config.backup_dir.clone()),
+            keep: self.backup_keep.or(self.config.backup_keep).unwrap_or(DEFAULT_BACKUP_KEEP),
+        }
+    }
+
+    /// Check if writes are recorded in the history: unless --no-history is given or the config turns it off
+    pub fn records_history(&self) -> bool {
+        !self.no_history && self.config.history != Some(false)
+    }
+
+    /// Check if gix may ask for confirmation: stdin is a terminal and --non-interactive isn't set
+    pub fn interactive(&self) -> bool {
+        !self.non_interactive && std::io::stdin().is_terminal()
+    }
+
+    /// Check if heuristic merges are enabled; conservative mode and `--max-risk` below behavior-may-change never use them
+    pub fn heuristics_enabled(&self) -> bool {
+        self.allow_heuristics && self.mode != OptimizationMode::Conservative && self.allows(ChangeRisk::BehaviorMayChange)
+    }
+
+    /// Check if changes of this risk may be applied under `--max-risk`
+    pub fn allows(&self, risk: ChangeRisk) -> bool {
+        self.max_risk.is_none_or(|max| risk <= max)
+    }
+
+    /// Get the options that shape what gix writes, for the audit log
+    pub fn audit_options(&self) -> Vec<String> {
+        let flags = [
+            ("--allow-heuristics", self.allow_heuristics),
+            ("--prune-unused", self.prune_unused),
+            ("--group-negations", self.group_negations),
+            ("--normalize-whitespace", self.normalize_whitespace),
+            ("--generate-comments", self.generate_comments),
+            ("--check-idempotent", self.check_idempotent),
+            ("--allow-empty", self.allow_empty),
+            ("--recursive", self.recursive),
+            ("--create-if-missing", self.create_if_missing),
+        ];
+        let mut options: Vec<String> = flags.iter()
+            .filter(|(_, set)| *set)
+            .map(|(flag, _)| flag.to_string())
+            .collect();
+        if let Some(risk) = self.max_risk {
+            options.push(format!("--max-risk={}", risk));
+        }
+        if self.sort != SortOrder::None {
+            options.push(format!("--sort={}", self.sort.name()));
+        }
+        if self.keep != KeepOccurrence::First {
+            options.push(format!("--keep={}", self.keep.name()));
+        }
+        if self.scope != [Scope::Repo] {
+            let scopes: Vec<&str> = self.scope.iter().map(Scope::name).collect();
+            options.push(format!("--scope={}", scopes.join(",")));
+        }
+        options
+    }
+
+    /// Check if patterns differing only in case are duplicates
+    ///
+    /// `--ignore-case` and `--no-ignore-case` decide, then `ignore_case` in gix.toml, then the
+    /// `core.ignorecase` setting of the repository holding the file.
+    pub fn ignores_case(&self) -> bool {
+        if self.ignore_case || self.no_ignore_case {
+            return self.ignore_case;
+        }
+        self.config.ignore_case
+            .or_else(|| core_ignore_case(&self.config_dir()))
+            .unwrap_or(false)
+    }
+
+    /// Get the severities of `gix check` rules: gix.toml's, then `--allow`, `--warn` and `--deny` in that order
+    pub fn rule_severities(&self, allow: &[String], warn: &[String], deny: &[String]) -> Result<RuleSeverities, GixError> {
+        let mut severities = RuleSeverities::default();
+        for (rule, severity) in &self.config.rules {
+            severities.set(rule, *severity)?;
+        }
+        for (rules, severity) in [(allow, Severity::Allow), (warn, Severity::Warn), (deny, Severity::Deny)] {
+            for rule in rules {
+                severities.set(rule, severity)?;
+            }
+        }
+        Ok(severities)
+    }
+
+    /// Get the plugins from the user config, then the repository's with `--allow-plugins`, then those given with `--plugin`
+    pub fn plugins(&self) -> PluginRegistry {
+        let mut commands = self.config.plugins.clone();
+        let repository = if self.allow_plugins { self.config.repository_plugins.as_slice() } else { &[] };
+        for command in repository.iter().chain(&self.plugins) {
+            if !commands.contains(command) {
+                commands.push(command.clone());
+            }
+        }
+        PluginRegistry::from_commands(&commands)
+    }
+
+    /// Get the plugins the repository's gix.toml files list that aren't run, for lack of `--allow-plugins`
+    pub fn skipped_plugins(&self) -> &[PathBuf] {
+        if self.allow_plugins { &[] } else { &self.config.repository_plugins }
+    }
+
+    /// Get the engine settings selected on the command line
+    pub fn engine_config(&self) -> EngineConfig {
+        EngineConfig {
+            mode: self.mode.clone(),
+            allow_heuristics: self.allow_heuristics && self.allows(ChangeRisk::BehaviorMayChange),
+            offline: self.offline,
+            keep: self.config.keep.clone(),
+            category_order: self.config.category_order.clone(),
+            ignore_case: self.ignores_case(),
+            keep_occurrence: self.keep,
+            subsume: self.dialect_of(&self.input_file()).matches_like_gitignore(),
+        }
+    }
+
+    /// Get the pattern analyzer matching the requested safety level
+    pub fn pattern_analyzer(&self) -> PatternAnalyzer {
+        let mut analyzer = if self.heuristics_enabled() {
+            PatternAnalyzer::heuristic()
+        } else {
+            PatternAnalyzer::safe()
+        };
+        analyzer.case_sensitive = !self.ignores_case();
+        analyzer
+    }
+
+    /// Get the pattern dataset to use, honouring --offline, with the user's category definitions merged in
+    #[cfg(feature = "dataset")]
+    pub fn dataset(&self) -> Dataset {
+        let mut dataset = Dataset::load(self.offline);
+        if let Some(definitions) = &self.definitions {
+            dataset.merge(definitions.clone());
+        }
+        dataset
+    }
+
+    /// Build the engine for these arguments, backed by the active dataset when available and consulting the plugins
+    pub fn engine(&self) -> GixEngine {
+        #[cfg(feature = "dataset")]
+        let engine = GixEngine::from_dataset(self.dataset(), self.engine_config());
+        #[cfg(not(feature = "dataset"))]
+        let engine = GixEngine::new(self.engine_config());
+        engine.with_plugins(self.plugins())
+    }
+
+    /// Get the pattern categorizer, backed by the active dataset when available and consulting the plugins
+    pub fn pattern_categorizer(&self) -> PatternCategorizer {
+        #[cfg(feature = "dataset")]
+        let categorizer = PatternCategorizer::from_dataset(&self.dataset());
+        #[cfg(not(feature = "dataset"))]
+        let categorizer = PatternCategorizer::new();
+        categorizer.with_plugins(self.plugins())
+    }
+
+    /// Get the comment generator, backed by the active dataset when available
+    pub fn comment_generator(&self) -> CommentGenerator {
+        #[cfg(feature = "dataset")]
+        return CommentGenerator::from_dataset(&self.dataset());
+        #[cfg(not(feature = "dataset"))]
+        return CommentGenerator::new();
+    }
+
+    /// Get the whitespace policy enforced in check mode
+    pub fn whitespace_policy(&self) -> WhitespacePolicy {
+        if self.whitespace.is_empty() {
+            WhitespacePolicy::default()
+        } else {
+            WhitespacePolicy::from_rules(&self.whitespace)
+        }
+    }
+
+    /// Get the filter to apply to the analysis before it is printed
+    pub fn analysis_filter(&self) -> AnalysisFilter {
+        AnalysisFilter {
+            only: self.only.clone(),
+            category: self.category.clone(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::lint::LintKind;
+    use crate::core::validator::PatternProblem;
 
     #[test]
     fn test_default_args() {
-        let args = Args::parse_from(&["gix"]);
+        let args = Args::parse_from(["gix"]);
         assert_eq!(args.input_file(), PathBuf::from(".gitignore"));
         assert_eq!(args.output_file(), PathBuf::from(".gitignore"));
         assert!(!args.backup);
@@ -104,57 +1017,539 @@ mod tests {
 
     #[test]
     fn test_custom_file() {
-        let args = Args::parse_from(&["gix", "custom.gitignore"]);
+        let args = Args::parse_from(["gix", "custom.gitignore"]);
         assert_eq!(args.input_file(), PathBuf::from("custom.gitignore"));
         assert_eq!(args.output_file(), PathBuf::from("custom.gitignore"));
     }
 
     #[test]
     fn test_output_file() {
-        let args = Args::parse_from(&["gix", "--output", "output.gitignore"]);
+        let args = Args::parse_from(["gix", "--output", "output.gitignore"]);
         assert_eq!(args.input_file(), PathBuf::from(".gitignore"));
         assert_eq!(args.output_file(), PathBuf::from("output.gitignore"));
     }
 
+    #[test]
+    fn test_removals_log() {
+        let args = Args::parse_from(["gix", "--removals-log", "removed.gitignore"]);
+        assert_eq!(args.removals_log, Some(PathBuf::from("removed.gitignore")));
+    }
+
     #[test]
     fn test_backup_flag() {
-        let args = Args::parse_from(&["gix", "--backup"]);
+        let args = Args::parse_from(["gix", "--backup"]);
         assert!(args.should_backup());
     }
 
     #[test]
     fn test_dry_run() {
-        let args = Args::parse_from(&["gix", "--dry-run"]);
+        let args = Args::parse_from(["gix", "--dry-run"]);
         assert!(!args.should_backup());
     }
 
     #[test]
     fn test_backup_with_dry_run() {
-        let args = Args::parse_from(&["gix", "--backup", "--dry-run"]);
+        let args = Args::parse_from(["gix", "--backup", "--dry-run"]);
         assert!(args.should_backup());
     }
 
+    #[test]
+    fn test_fail_on_conflicts_requires_detect_conflicts() {
+        assert!(Args::try_parse_from(["gix", "--fail-on-conflicts"]).is_err());
+        let args = Args::parse_from(["gix", "--detect-conflicts", "--fail-on-conflicts"]);
+        assert!(args.detect_conflicts && args.fail_on_conflicts);
+    }
+
+    #[test]
+    fn test_organize_subcommand() {
+        let args = Args::parse_from(["gix", "organize", "--dry-run", "sub/.gitignore"]);
+        assert_eq!(args.command, Some(Command::Organize {
+            file: Some(PathBuf::from("sub/.gitignore")),
+            output: None,
+            dry_run: true,
+            backup: false,
+            no_backup: false,
+            pair_negations: false,
+        }));
+        assert!(Args::try_parse_from(["gix", "organize", "--backup", "--no-backup"]).is_err());
+    }
+
+    #[test]
+    fn test_init_subcommand() {
+        let args = Args::parse_from(["gix", "init", "--like", "../other-repo"]);
+        assert_eq!(args.command, Some(Command::Init {
+            like: Some(PathBuf::from("../other-repo")),
+            lang: Vec::new(),
+            framework: Vec::new(),
+            tool: Vec::new(),
+            os: Vec::new(),
+            output: PathBuf::from(".gitignore"),
+            dry_run: false,
+            force: false,
+        }));
+        assert!(Args::try_parse_from(["gix", "init"]).is_err());
+
+        let args = Args::parse_from(["gix", "init", "--lang", "rust,node", "--os", "macos"]);
+        let Some(Command::Init { like, lang, os, .. }) = args.command else {
+            panic!("expected init");
+        };
+        assert_eq!((like, lang, os), (None, vec!["rust".to_string(), "node".to_string()], vec!["macos".to_string()]));
+        assert!(Args::try_parse_from(["gix", "init", "--like", "../other-repo", "--lang", "rust"]).is_err());
+    }
+
+    #[test]
+    fn test_analyze_subcommand() {
+        let args = Args::parse_from(["gix", "analyze", "--only", "conflicts,negations", "--category", "Python", "sub/.gitignore"]);
+        assert_eq!(args.command, Some(Command::Analyze {
+            file: Some(PathBuf::from("sub/.gitignore")),
+            format: ReportFormat::Text,
+            only: vec![AnalysisScope::Conflicts, AnalysisScope::Negations],
+            category: Some("Python".to_string()),
+        }));
+        assert!(!args.analyze);
+    }
+
+    #[test]
+    fn test_check_subcommand() {
+        let args = Args::parse_from(["gix", "check", "sub/.gitignore"]);
+        assert_eq!(args.command, Some(Command::Check {
+            file: Some(PathBuf::from("sub/.gitignore")),
+            format: ReportFormat::Text,
+            examples: false,
+            example_budget: DEFAULT_EXAMPLE_BUDGET,
+            allow: Vec::new(),
+            warn: Vec::new(),
+            deny: Vec::new(),
+        }));
+        assert!(!args.check);
+    }
+
+    #[test]
+    fn test_rule_severities() {
+        let mut args = Args::parse_from(["gix", "check", "--allow", "GIX001,redundant", "--deny", "conflict"]);
+        args.config.rules = vec![("conflict".to_string(), Severity::Allow), ("GIX010".to_string(), Severity::Warn)];
+        let Some(Command::Check { allow, warn, deny, .. }) = &args.command else {
+            panic!("expected the check subcommand");
+        };
+        let severities = args.rule_severities(allow, warn, deny).unwrap();
+
+        assert_eq!(severities.severity(LintKind::Duplicate), Severity::Allow);
+        assert_eq!(severities.severity(LintKind::Redundant), Severity::Allow);
+        assert_eq!(severities.severity(LintKind::Conflict), Severity::Deny);
+        assert_eq!(severities.severity(LintKind::Invalid(PatternProblem::UnclosedClass)), Severity::Warn);
+        assert_eq!(severities.severity(LintKind::Empty), Severity::Deny);
+        assert!(args.rule_severities(&["typo".to_string()], &[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_audit_subcommand() {
+        let args = Args::parse_from(["gix", "audit", "sub/.gitignore"]);
+        assert_eq!(args.command, Some(Command::Audit { file: Some(PathBuf::from("sub/.gitignore")) }));
+    }
+
+    #[test]
+    fn test_self_test_subcommand() {
+        let args = Args::parse_from(["gix", "self-test"]);
+        assert_eq!(args.command, Some(Command::SelfTest));
+    }
+
+    #[test]
+    fn test_verify_subcommand() {
+        let args = Args::parse_from(["gix", "verify", "--against", "old.gitignore"]);
+        assert_eq!(args.command, Some(Command::Verify { file: None, against: PathBuf::from("old.gitignore"), no_tree: false }));
+    }
+
+    #[test]
+    fn test_scan_subcommand() {
+        let args = Args::parse_from(["gix", "scan", "--write-summary", ".gix/summary.md", "--exclude-path", "vendor", "--exclude-path", "third_party"]);
+        assert_eq!(args.command, Some(Command::Scan {
+            dir: None,
+            write_summary: Some(PathBuf::from(".gix/summary.md")),
+            include_submodules: false,
+            exclude_paths: vec!["vendor".to_string(), "third_party".to_string()],
+        }));
+    }
+
+    #[test]
+    fn test_missing_and_empty_file_flags() {
+        let args = Args::parse_from(["gix", "--create-if-missing", "--like", "../other-repo"]);
+        assert!(args.create_if_missing);
+        assert_eq!(args.like, Some(PathBuf::from("../other-repo")));
+        assert_eq!(args.on_empty, EmptyFilePolicy::Warn);
+
+        assert_eq!(Args::parse_from(["gix", "--on-empty", "error"]).on_empty, EmptyFilePolicy::Error);
+        assert!(Args::try_parse_from(["gix", "--like", "../other-repo"]).is_err());
+    }
+
+    #[test]
+    fn test_scope_flag() {
+        assert_eq!(Args::parse_from(["gix"]).scopes(), vec![Scope::Repo]);
+        let args = Args::parse_from(["gix", "--scope", "global,repo,info,repo"]);
+        assert_eq!(args.scopes(), vec![Scope::Repo, Scope::Info, Scope::Global]);
+    }
+
+    #[test]
+    fn test_recursive_flag() {
+        let args = Args::parse_from(["gix", "--recursive", "--exclude-path", "vendor", "repo"]);
+        assert!(args.recursive);
+        assert_eq!(args.root_dir(), PathBuf::from("repo"));
+        assert_eq!(args.walk_options().exclude_paths, vec!["vendor".to_string()]);
+        assert_eq!(Args::parse_from(["gix", "-r"]).root_dir(), PathBuf::from("."));
+
+        assert!(Args::try_parse_from(["gix", "--exclude-path", "vendor"]).is_err());
+        assert!(Args::try_parse_from(["gix", "--recursive", "--output", "out"]).is_err());
+    }
+
     #[test]
     fn test_analyze_flag() {
-        let args = Args::parse_from(&["gix", "--analyze"]);
+        let args = Args::parse_from(["gix", "--analyze"]);
         assert!(args.analyze);
     }
 
+    #[test]
+    fn test_analysis_filter_flags() {
+        let args = Args::parse_from(["gix", "--analyze", "--only", "conflicts,negations", "--category", "Python"]);
+        let filter = args.analysis_filter();
+        assert_eq!(filter.only, vec![AnalysisScope::Conflicts, AnalysisScope::Negations]);
+        assert_eq!(filter.category, Some("Python".to_string()));
+    }
+
+    #[test]
+    fn test_analysis_filter_requires_analyze() {
+        assert!(Args::try_parse_from(["gix", "--only", "conflicts"]).is_err());
+        assert!(Args::try_parse_from(["gix", "--only", "bogus", "--analyze"]).is_err());
+    }
+
+    #[test]
+    fn test_check_uses_full_whitespace_policy_by_default() {
+        let args = Args::parse_from(["gix", "--check"]);
+        assert!(args.check);
+        assert_eq!(args.whitespace_policy(), WhitespacePolicy::default());
+    }
+
+    #[test]
+    fn test_check_with_selected_whitespace_rules() {
+        let args = Args::parse_from(["gix", "--check", "--whitespace", "trailing-spaces,indentation"]);
+        let policy = args.whitespace_policy();
+        assert!(policy.no_trailing_spaces);
+        assert!(policy.no_leading_indentation);
+        assert!(!policy.single_blank_between_sections);
+    }
+
+    #[test]
+    fn test_safe_mode_is_default() {
+        let args = Args::parse_from(["gix"]);
+        assert!(!args.heuristics_enabled());
+        assert!(!args.pattern_analyzer().merge_equivalents);
+    }
+
+    #[test]
+    fn test_allow_heuristics() {
+        let args = Args::parse_from(["gix", "--allow-heuristics"]);
+        assert!(args.heuristics_enabled());
+
+        let args = Args::parse_from(["gix", "--allow-heuristics", "--mode", "conservative"]);
+        assert!(!args.heuristics_enabled());
+
+        assert!(Args::try_parse_from(["gix", "--safe", "--allow-heuristics"]).is_err());
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".gitignore");
+        let file = file.to_str().unwrap();
+        let mut args = Args::parse_from(["gix", file]);
+        assert!(!args.ignores_case());
+        assert!(args.pattern_analyzer().case_sensitive);
+
+        args.config.ignore_case = Some(true);
+        assert!(args.engine_config().ignore_case);
+        args.no_ignore_case = true;
+        assert!(!args.ignores_case());
+
+        let args = Args::parse_from(["gix", "--ignore-case", file]);
+        assert!(!args.pattern_analyzer().case_sensitive);
+        assert!(Args::try_parse_from(["gix", "--ignore-case", "--no-ignore-case"]).is_err());
+    }
+
+    #[test]
+    fn test_audit_log_options() {
+        let args = Args::parse_from(["gix", "--audit-log", "--prune-unused", "--max-risk", "safe", "-m", "advanced"]);
+        assert!(args.audit_log);
+        assert_eq!(args.mode.name(), "advanced");
+        assert_eq!(args.audit_options(), vec!["--prune-unused", "--max-risk=safe"]);
+
+        let args = Args::parse_from(["gix", "audit-log", "verify", "repo"]);
+        assert_eq!(args.command, Some(Command::AuditLog { action: AuditLogAction::Verify { dir: Some(PathBuf::from("repo")) } }));
+    }
+
+    #[test]
+    fn test_max_risk() {
+        let args = Args::parse_from(["gix"]);
+        assert!(args.allows(ChangeRisk::BehaviorMayChange));
+
+        let args = Args::parse_from(["gix", "--allow-heuristics", "--max-risk", "likely-safe"]);
+        assert_eq!(args.max_risk, Some(ChangeRisk::LikelySafe));
+        assert!(args.allows(ChangeRisk::Safe));
+        assert!(!args.allows(ChangeRisk::BehaviorMayChange));
+        assert!(!args.heuristics_enabled());
+        assert!(!args.engine_config().allow_heuristics);
+
+        assert!(Args::try_parse_from(["gix", "--max-risk", "yolo"]).is_err());
+    }
+
+    #[test]
+    fn test_report_format() {
+        assert_eq!(Args::parse_from(["gix"]).format, ReportFormat::Text);
+        let args = Args::parse_from(["gix", "--check", "--format", "gitlab"]);
+        assert_eq!(args.format, ReportFormat::Gitlab);
+    }
+
+    #[test]
+    fn test_config_precedence() {
+        let config = Config {
+            mode: Some(OptimizationMode::Advanced),
+            backup: Some(true),
+            format: Some(ReportFormat::Github),
+            keep: vec![".env".to_string()],
+            metadata: Some(MetadataLocation::Worktree),
+            history: Some(false),
+            backup_keep: Some(3),
+            ..Config::default()
+        };
+        let resolve = |argv: &[&str]| {
+            let matches = Args::command().get_matches_from(argv);
+            let mut args = Args::from_arg_matches(&matches).unwrap();
+            args.apply_config(config.clone(), &matches);
+            args
+        };
+
+        let args = resolve(&["gix"]);
+        assert_eq!(args.mode, OptimizationMode::Advanced);
+        assert_eq!(args.format, ReportFormat::Github);
+        assert!(args.should_backup());
+        assert_eq!(args.engine_config().keep, vec![".env"]);
+        assert_eq!(args.metadata, MetadataLocation::Worktree);
+        assert_eq!(args.backup_policy(), BackupPolicy { location: MetadataLocation::Worktree, dir: None, keep: 3 });
+        assert!(!args.records_history());
+
+        let args = resolve(&["gix", "--mode", "standard", "--format", "text", "--no-backup", "--metadata", "git", "--backup-dir", "b", "--backup-keep", "0"]);
+        assert_eq!(args.backup_policy(), BackupPolicy { location: MetadataLocation::Git, dir: Some(PathBuf::from("b")), keep: 0 });
+        assert_eq!(args.mode, OptimizationMode::Standard);
+        assert_eq!(args.metadata, MetadataLocation::Git);
+        assert_eq!(args.format, ReportFormat::Text);
+        assert!(!args.should_backup());
+        assert!(Args::try_parse_from(["gix", "--backup", "--no-backup"]).is_err());
+    }
+
+    #[test]
+    fn test_merge_subcommand() {
+        let args = Args::parse_from(["gix", "merge", "a.gitignore", "b.gitignore", "-o", "out.gitignore"]);
+        assert_eq!(args.command, Some(Command::Merge {
+            files: vec![PathBuf::from("a.gitignore"), PathBuf::from("b.gitignore")],
+            output: PathBuf::from("out.gitignore"),
+            dry_run: false,
+            backup: false,
+        }));
+        assert!(Args::try_parse_from(["gix", "merge", "a.gitignore"]).is_err());
+    }
+
+    #[test]
+    fn test_test_path_subcommand() {
+        let args = Args::parse_from(["gix", "test-path", "build/out.o", "logs/", "-f", "app/.gitignore"]);
+        assert_eq!(args.command, Some(Command::TestPath {
+            paths: vec!["build/out.o".to_string(), "logs/".to_string()],
+            file: Some(PathBuf::from("app/.gitignore")),
+        }));
+        assert!(Args::try_parse_from(["gix", "test-path"]).is_err());
+    }
+
+    #[test]
+    fn test_explain_subcommand() {
+        let args = Args::parse_from(["gix", "explain", "src/build/out.log"]);
+        assert_eq!(args.command, Some(Command::Explain { path: "src/build/out.log".to_string(), file: None }));
+    }
+
+    #[test]
+    fn test_compare_subcommand() {
+        let args = Args::parse_from(["gix", "compare", "--template", "rust"]);
+        assert_eq!(args.command, Some(Command::Compare { file: None, template: "rust".to_string() }));
+        assert!(Args::try_parse_from(["gix", "compare"]).is_err());
+    }
+
+    #[test]
+    fn test_suggest_subcommand() {
+        let args = Args::parse_from(["gix", "suggest", "app/.gitignore"]);
+        assert_eq!(args.command, Some(Command::Suggest { file: Some(PathBuf::from("app/.gitignore")) }));
+    }
+
+    #[test]
+    fn test_sync_check_subcommand() {
+        let args = Args::parse_from(["gix", "sync-check", "--against", ".dockerignore", "--sync"]);
+        assert_eq!(args.command, Some(Command::SyncCheck {
+            file: None,
+            against: PathBuf::from(".dockerignore"),
+            sync: true,
+        }));
+        assert!(Args::try_parse_from(["gix", "sync-check"]).is_err());
+    }
+
+    #[test]
+    fn test_add_template_subcommand() {
+        let args = Args::parse_from(["gix", "add-template", "python", "--source", "gitignoreio"]);
+        assert_eq!(args.command, Some(Command::AddTemplate {
+            name: "python".to_string(),
+            file: None,
+            source: TemplateSource::Gitignoreio,
+            refresh: false,
+            dry_run: false,
+        }));
+        assert!(Args::try_parse_from(["gix", "add-template", "python", "--source", "bogus"]).is_err());
+    }
+
+    #[test]
+    fn test_dataset_subcommand() {
+        let args = Args::parse_from(["gix", "dataset", "update", "--url", "https://example.com/data.txt"]);
+        assert_eq!(args.command, Some(Command::Dataset {
+            action: DatasetAction::Update { url: Some("https://example.com/data.txt".to_string()) },
+        }));
+
+        let args = Args::parse_from(["gix", "dataset", "status", "--offline", "--non-interactive"]);
+        assert!(args.offline);
+        assert!(!args.interactive());
+        assert_eq!(args.command, Some(Command::Dataset { action: DatasetAction::Status }));
+
+        let args = Args::parse_from(["gix", "my.gitignore"]);
+        assert_eq!(args.command, None);
+        assert_eq!(args.input_file(), PathBuf::from("my.gitignore"));
+    }
+
     #[test]
     fn test_detect_conflicts_flag() {
-        let args = Args::parse_from(&["gix", "--detect-conflicts"]);
+        let args = Args::parse_from(["gix", "--detect-conflicts"]);
         assert!(args.detect_conflicts);
     }
 
     #[test]
     fn test_generate_comments_flag() {
-        let args = Args::parse_from(&["gix", "--generate-comments"]);
+        let args = Args::parse_from(["gix", "--generate-comments"]);
         assert!(args.generate_comments);
     }
 
+    #[test]
+    fn test_check_idempotent_flag() {
+        let args = Args::parse_from(["gix", "--check-idempotent"]);
+        assert!(args.check_idempotent);
+    }
+
+    #[test]
+    fn test_sort_flag() {
+        assert_eq!(Args::parse_from(["gix"]).sort, SortOrder::None);
+        let args = Args::parse_from(["gix", "--sort", "category"]);
+        assert_eq!(args.sort, SortOrder::Category);
+        assert_eq!(args.audit_options(), vec!["--sort=category".to_string()]);
+    }
+
+    #[test]
+    fn test_plan_arguments() {
+        let args = Args::parse_from(["gix", "--recursive", "--emit-plan", "plan.json"]);
+        assert_eq!(args.emit_plan, Some(PathBuf::from("plan.json")));
+        assert!(Args::try_parse_from(["gix", "--emit-plan", "plan.json", "--output", "out"]).is_err());
+
+        let args = Args::parse_from(["gix", "apply-plan", "plan.json"]);
+        assert_eq!(args.command, Some(Command::ApplyPlan { plan: PathBuf::from("plan.json") }));
+    }
+
+    #[test]
+    fn test_bench_subcommand() {
+        let args = Args::parse_from(["gix", "bench", "--size", "huge", "-o", "huge.gitignore"]);
+        assert_eq!(args.command, Some(Command::Bench { size: FixtureSize::Huge, output: Some(PathBuf::from("huge.gitignore")) }));
+        assert!(!Args::command().render_help().to_string().contains("bench"));
+    }
+
+    #[test]
+    fn test_add_subcommand() {
+        let args = Args::parse_from(["gix", "add", "*.pyc", "--section", "Python", "--force"]);
+        assert_eq!(args.command, Some(Command::Add {
+            patterns: vec!["*.pyc".to_string()],
+            file: None,
+            from: None,
+            matching: Vec::new(),
+            section: Some("Python".to_string()),
+            force: true,
+            dry_run: false,
+        }));
+        assert!(Args::try_parse_from(["gix", "add"]).is_err());
+        assert!(Args::try_parse_from(["gix", "add", "--matching", "*.log"]).is_err());
+        assert!(Args::try_parse_from(["gix", "add", "--from", "Python.gitignore", "--matching", "*.py?"]).is_ok());
+    }
+
+    #[test]
+    fn test_remove_subcommand() {
+        let args = Args::parse_from(["gix", "rm", "--line", "4", "--dry-run"]);
+        assert_eq!(args.command, Some(Command::Remove { pattern: None, line: Some(4), matching: Vec::new(), file: None, dry_run: true }));
+        let args = Args::parse_from(["gix", "rm", "--matching", "*.log*", "--matching", ".idea*"]);
+        assert_eq!(args.command, Some(Command::Remove { pattern: None, line: None, matching: vec!["*.log*".to_string(), ".idea*".to_string()], file: None, dry_run: false }));
+        assert!(Args::try_parse_from(["gix", "remove"]).is_err());
+        assert!(Args::try_parse_from(["gix", "remove", "*.log", "--line", "4"]).is_err());
+    }
+
+    #[test]
+    fn test_simulate_add_subcommand() {
+        let args = Args::parse_from(["gix", "simulate-add", "!dist/", "-f", "app/.gitignore"]);
+        assert_eq!(args.command, Some(Command::SimulateAdd { pattern: "!dist/".to_string(), file: Some(PathBuf::from("app/.gitignore")) }));
+    }
+
+    #[test]
+    fn test_dialect_argument() {
+        let args = Args::parse_from(["gix", "web/.npmignore"]);
+        assert_eq!(args.dialect_of(&args.input_file()), Dialect::Npmignore);
+        let args = Args::parse_from(["gix", "check", "--dialect", "dockerignore", "ignore.txt"]);
+        assert_eq!(args.dialect_of(Path::new("ignore.txt")), Dialect::Dockerignore);
+    }
+
+    #[test]
+    fn test_watch_subcommand() {
+        assert_eq!(
+            Args::parse_from(["gix", "watch"]).command,
+            Some(Command::Watch { path: None, recursive: false, check: false, debounce: 300 })
+        );
+        let args = Args::parse_from(["gix", "watch", "--recursive", "--check", "--debounce", "50", "repo"]);
+        assert_eq!(args.command, Some(Command::Watch { path: Some(PathBuf::from("repo")), recursive: true, check: true, debounce: 50 }));
+    }
+
+    #[test]
+    fn test_undo_and_history_subcommands() {
+        assert_eq!(Args::parse_from(["gix", "undo"]).command, Some(Command::Undo { steps: 1, dir: None }));
+        let args = Args::parse_from(["gix", "undo", "--steps", "3", "repo"]);
+        assert_eq!(args.command, Some(Command::Undo { steps: 3, dir: Some(PathBuf::from("repo")) }));
+        assert_eq!(Args::parse_from(["gix", "history"]).command, Some(Command::History { dir: None }));
+    }
+
+    #[test]
+    fn test_fleet_report_subcommand() {
+        let args = Args::parse_from(["gix", "fleet", "report", "--repos-from", "repos.txt", "--format", "json"]);
+        assert_eq!(args.command, Some(Command::Fleet { action: FleetAction::Report {
+            repos_from: Some(PathBuf::from("repos.txt")),
+            dir: None,
+            checkout_dir: None,
+            format: FleetFormat::Json,
+            output: None,
+        } }));
+        assert!(Args::try_parse_from(["gix", "fleet", "report"]).is_err());
+        assert!(Args::try_parse_from(["gix", "fleet", "report", "--repos-from", "repos.txt", "--dir", "checkouts"]).is_err());
+    }
+
+    #[test]
+    fn test_group_negations_flag() {
+        let args = Args::parse_from(["gix", "--group-negations"]);
+        assert!(args.group_negations);
+    }
+
     #[test]
     fn test_show_categories_flag() {
-        let args = Args::parse_from(&["gix", "--show-categories"]);
+        let args = Args::parse_from(["gix", "--show-categories"]);
         assert!(args.show_categories);
     }
 } 
\ No newline at end of file
@@ -0,0 +1,779 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::{
+    cli::{
+        args::{Args, AuditLogAction, Command, DatasetAction, FleetAction},
+        output::{
+            print_addition_effect, print_analysis, print_audit_records, print_audit_verification,
+            print_backup, print_collapse_suggestions, print_diff, print_equivalence,
+            print_explanation, print_extension_groups, print_fixture_written,
+            print_fleet_report_written, print_generated, print_history, print_initialized,
+            print_lint_issues, print_merged, print_moved_negations, print_optimization_report,
+            print_organized, print_path_match, print_pattern_removed, print_patterns_added,
+            print_plan_applied, print_rules, print_self_test, print_suggestions,
+            print_summary_written, print_sync_report, print_template_added, print_template_drift,
+            print_tracked_matches, print_undone, print_watch_checked, print_watch_failed,
+            print_watch_optimized, print_watch_removed,
+        },
+        report::{render_analysis, render_lint_issues, ReportFormat},
+        run::{analysis_of, base_dir, record_engine_write, write_ignore_file},
+    },
+    core::{
+        assess_fleet, audit_log_path, checkouts_in, compare_with_template, compose_templates,
+        detect_stack, explain_path, fetch_template, files_equivalent, find_extension_groups,
+        find_ignored_tracked_files, generate_gitignore, history_dir, insert_pattern,
+        lint_attribute_conflicts, lint_gitignore, lint_gitignore_with_examples, merge_gitignores,
+        merge_template,
+        optimizer::{AnalysisFilter, PassStats},
+        parse_gitattributes, parse_gitignore, parse_ignore_file, read_audit_log, read_history,
+        read_repository_list, scan_repository, simulate_addition, simulate_removal,
+        suggest_collapses, suggest_patterns, sync_check, tailor_gitignore, undo_steps,
+        verify_audit_log, write_summary, Dialect, FleetFormat, LintKind, OptimizationReport,
+        PatternCategory, Plan, RuleSeverities, ScanOptions, Severity, TemplateSource,
+        ATTRIBUTES_FILE_NAME, COLLAPSE_MIN_PATTERNS, EXTENSION_GROUP_MIN,
+    },
+    engine::{BatchOptions, GixEngine},
+    models::{GitignoreEntry, GitignoreFile, GixError, WriteOptions},
+    utils::{
+        create_backup_in,
+        diff::{unified_diff, DEFAULT_CONTEXT},
+        git::{repository_root, tracked_files},
+        glob_selects, read_gitignore_file, stable_hash,
+        synthetic::{synthetic_gitignore, FixtureSize},
+        walker::{list_paths, WalkOptions},
+        write_gitignore_file,
+    },
+};
+
+pub(crate) fn run_command(command: &Command, args: &Args, engine: &GixEngine) -> Result<(), GixError> {
+    match command {
+        Command::Dataset { action } => run_dataset(action, args),
+        Command::AuditLog { action } => run_audit_log(args, action),
+        Command::Undo { steps, dir } => {
+            print_undone(&undo_steps(dir.as_deref().unwrap_or(Path::new(".")), args.metadata, *steps)?);
+            Ok(())
+        }
+        Command::History { dir } => {
+            let dir = dir.as_deref().unwrap_or(Path::new("."));
+            let history = history_dir(dir, args.metadata);
+            print_history(&history, &read_history(&history)?, args.verbose);
+            Ok(())
+        }
+        Command::Fleet { action: FleetAction::Report { repos_from, dir, checkout_dir, format, output } } => {
+            run_fleet_report(args, repos_from.as_deref(), dir.as_deref(), checkout_dir.as_deref(), *format, output.as_deref())
+        }
+        Command::SyncCheck { file, against, sync } => {
+            run_sync_check(args, file.as_deref().unwrap_or(Path::new(".gitignore")), against, *sync)
+        }
+        Command::AddTemplate { name, file, source, refresh, dry_run } => {
+            run_add_template(args, name, file.as_deref().unwrap_or(Path::new(".gitignore")), *source, *refresh, *dry_run || args.dry_run)
+        }
+        Command::TestPath { paths, file } => run_test_path(paths, file.as_deref().unwrap_or(Path::new(".gitignore"))),
+        Command::Explain { path, file } => run_explain(path, file.as_deref().unwrap_or(Path::new(".gitignore"))),
+        Command::Add { patterns, file, from, matching, section, force, dry_run } => {
+            let mut patterns = patterns.clone();
+            if let Some(from) = from {
+                let source = parse_ignore_file(&read_gitignore_file(from)?, args.dialect_of(from))?;
+                patterns.extend(source.entries.iter()
+                    .filter_map(GitignoreEntry::comparison_pattern)
+                    .filter(|pattern| matching.is_empty() || matching.iter().any(|glob| glob_selects(glob, pattern))));
+            }
+            run_add(args, engine, &patterns, file.as_deref().unwrap_or(Path::new(".gitignore")), section.as_deref(), *force, *dry_run || args.dry_run)
+        }
+        Command::Remove { pattern, line, matching, file, dry_run } => {
+            run_remove(args, pattern.as_deref(), *line, matching, file.as_deref().unwrap_or(Path::new(".gitignore")), *dry_run || args.dry_run)
+        }
+        Command::SimulateAdd { pattern, file } => run_simulate_add(pattern, file.as_deref().unwrap_or(Path::new(".gitignore"))),
+        Command::Compare { file, template } => run_compare(engine, file.as_deref().unwrap_or(Path::new(".gitignore")), template),
+        Command::Suggest { file } => run_suggest(engine, file.as_deref().unwrap_or(Path::new(".gitignore"))),
+        Command::Audit { file } => run_audit(file.as_deref().unwrap_or(Path::new(".gitignore"))),
+        Command::Verify { file, against, no_tree } => {
+            run_verify(args, file.as_deref().unwrap_or(Path::new(".gitignore")), against, *no_tree)
+        }
+        Command::SelfTest => run_self_test(),
+        Command::ApplyPlan { plan } => run_apply_plan(args, plan),
+        Command::Bench { size, output } => run_bench(*size, output.as_deref()),
+        Command::Check { file, format, examples, example_budget, allow, warn, deny } => {
+            let path = file.as_deref().unwrap_or(Path::new(".gitignore"));
+            let severities = args.rule_severities(allow, warn, deny)?;
+            run_check(engine, path, args.dialect_of(path), *format, examples.then_some(*example_budget), &severities)
+        }
+        Command::Analyze { file, format, only, category } => {
+            let path = file.as_deref().unwrap_or(Path::new(".gitignore"));
+            run_analyze(args, path, *format, &AnalysisFilter { only: only.clone(), category: category.clone() })
+        }
+        Command::Rules => {
+            print_rules(&args.rule_severities(&[], &[], &[])?);
+            Ok(())
+        }
+        Command::Watch { path, recursive, check, debounce } => run_watch(args, engine, path.as_deref(), *recursive, *check, *debounce),
+        Command::Scan { dir, write_summary, include_submodules, exclude_paths } => {
+            let options = ScanOptions {
+                include_submodules: *include_submodules,
+                exclude_paths: exclude_paths.clone(),
+            };
+            run_scan(dir.as_deref().unwrap_or(Path::new(".")), write_summary.as_deref(), &options)
+        }
+        Command::Merge { files, output, dry_run, backup } => run_merge(args, engine, files, output, *dry_run, *backup),
+        Command::Init { like: None, lang, framework, tool, os, output, dry_run, force } => {
+            let names = [
+                (PatternCategory::Language as fn(String) -> PatternCategory, lang),
+                (PatternCategory::Framework, framework),
+                (PatternCategory::Tool, tool),
+                (PatternCategory::OperatingSystem, os),
+            ];
+            run_generate(args, engine, &names, output, *dry_run, *force)
+        }
+        Command::Init { like, output, dry_run, force, .. } => run_init(args, engine, like.as_deref(), output, *dry_run, *force),
+        Command::Organize { file, output, dry_run, backup, no_backup, pair_negations } => {
+            let input_path = file.clone().unwrap_or_else(|| PathBuf::from(".gitignore"));
+            let output_path = output.clone().unwrap_or_else(|| input_path.clone());
+            // A backup configured in gix.toml applies here too, unless --no-backup says otherwise
+            let backup = (*backup || args.config.backup == Some(true)) && !*no_backup;
+            run_organize(args, engine, &input_path, &output_path, *dry_run, backup, *pair_negations)
+        }
+    }
+}
+
+fn run_verify(args: &Args, path: &Path, against: &Path, no_tree: bool) -> Result<(), GixError> {
+    let file = parse_gitignore(&read_gitignore_file(path)?)?;
+    let old = parse_gitignore(&read_gitignore_file(against)?)?;
+    let tree_paths = match no_tree {
+        true => Vec::new(),
+        false => list_paths(base_dir(path), &args.walk_options())?,
+    };
+    
+    let report = files_equivalent(&old, &file, &tree_paths);
+    print_equivalence(path, against, &report);
+    if !report.is_equivalent() {
+        return Err(GixError::NotEquivalent(report.changes.len()));
+    }
+    Ok(())
+}
+
+fn run_bench(size: FixtureSize, output: Option<&Path>) -> Result<(), GixError> {
+    let content = synthetic_gitignore(size.lines());
+    match output {
+        Some(path) => {
+            write_gitignore_file(path, &content)?;
+            print_fixture_written(path, size);
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+fn run_self_test() -> Result<(), GixError> {
+    let scratch = std::env::temp_dir().join(format!("gix-self-test-{}", process::id()));
+    std::fs::create_dir_all(&scratch)?;
+    let report = crate::core::run_self_test(&scratch);
+    let _ = std::fs::remove_dir_all(&scratch);
+    
+    let report = report?;
+    print_self_test(&report);
+    if !report.divergences.is_empty() {
+        return Err(GixError::SelfTestFailed(report.divergences.len()));
+    }
+    Ok(())
+}
+
+/// Print the analysis of an ignore file as text or JSON, leaving the file as it is
+fn run_analyze(args: &Args, path: &Path, format: ReportFormat, filter: &AnalysisFilter) -> Result<(), GixError> {
+    let dialect = args.dialect_of(path);
+    let file = parse_ignore_file(&read_gitignore_file(path)?, dialect)?;
+    // Unused patterns are looked for in the tree the file sits in
+    let tree_paths = match dialect.matches_like_gitignore() {
+        true => Some(list_paths(base_dir(path), &args.walk_options())?),
+        false => None,
+    };
+    let analysis = analysis_of(&file, tree_paths.as_deref(), filter)?;
+    match format {
+        ReportFormat::Text => print_analysis(path, &analysis, filter, args.verbose),
+        ReportFormat::Json => print!("{}", render_analysis(path, &analysis, filter)),
+        _ => return Err(GixError::UsageError("gix analyze reports as text or json".to_string())),
+    }
+    Ok(())
+}
+
+fn run_check(engine: &GixEngine, path: &Path, dialect: Dialect, format: ReportFormat, example_budget: Option<usize>, severities: &RuleSeverities) -> Result<(), GixError> {
+    let file = parse_ignore_file(&read_gitignore_file(path)?, dialect)?;
+    let mut issues = match example_budget {
+        Some(budget) if dialect.matches_like_gitignore() => lint_gitignore_with_examples(&file, &list_paths(base_dir(path), &WalkOptions::default())?, budget)?,
+        _ => lint_gitignore(&file)?,
+    };
+    // Subsumption assumes gitignore matching; in Docker `*.log` doesn't cover `logs/*.log`
+    if !dialect.matches_like_gitignore() {
+        issues.retain(|issue| issue.kind != LintKind::Redundant);
+    }
+    if !engine.plugins().is_empty() {
+        issues.extend(engine.plugins().lint(&file));
+        issues.sort_by_key(|issue| issue.line_number);
+    }
+    // The .gitattributes next to the file speaks of the same paths as its patterns
+    let attributes_path = base_dir(path).join(ATTRIBUTES_FILE_NAME);
+    if dialect == Dialect::Gitignore && attributes_path.is_file() {
+        issues.extend(lint_attribute_conflicts(&file, &parse_gitattributes(&read_gitignore_file(&attributes_path)?)));
+        issues.sort_by_key(|issue| issue.line_number);
+    }
+    let issues = severities.apply(issues);
+    match render_lint_issues(format, path, &issues) {
+        Some(report) => print!("{}", report),
+        None => print_lint_issues(path, &issues),
+    }
+    
+    // Warnings are reported without failing the check
+    let failing = issues.iter().filter(|issue| issue.severity == Severity::Deny).count();
+    if failing > 0 {
+        return Err(GixError::CheckFailed(failing));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "watch")]
+fn run_watch(args: &Args, engine: &GixEngine, path: Option<&Path>, recursive: bool, check: bool, debounce: u64) -> Result<(), GixError> {
+    use crate::cli::output::print_watching;
+    use crate::utils::path_relative_to;
+    use crate::utils::watch::IgnoreFileWatcher;
+    use std::time::Duration;
+    
+    let debounce = Duration::from_millis(debounce);
+    let (target, watcher) = match recursive {
+        true => {
+            let dir = path.unwrap_or(Path::new("."));
+            (dir, IgnoreFileWatcher::recursive(dir, debounce)?)
+        }
+        false => {
+            let file = path.unwrap_or(Path::new(".gitignore"));
+            (file, IgnoreFileWatcher::file(file, debounce)?)
+        }
+    };
+    print_watching(target, recursive, check);
+    
+    let mut written = HashMap::new();
+    loop {
+        for path in watcher.next_changes()? {
+            let label = path_relative_to(watcher.root(), &path).unwrap_or_else(|| path.display().to_string());
+            process_watched(args, engine, &path, &label, check, &mut written);
+        }
+    }
+}
+
+#[cfg(not(feature = "watch"))]
+fn run_watch(_args: &Args, _engine: &GixEngine, _path: Option<&Path>, _recursive: bool, _check: bool, _debounce: u64) -> Result<(), GixError> {
+    Err(GixError::UsageError("gix was built without the `watch` feature".to_string()))
+}
+
+/// Optimize or check a watched file that was saved, printing one summary line
+///
+/// `written` holds the hash of what gix last wrote to each file: gix's own writes are seen
+/// as saves too, and a file still holding what gix wrote is left alone. Failures are printed
+/// rather than returned, so one bad save doesn't end the watch.
+#[cfg_attr(not(feature = "watch"), allow(dead_code))]
+fn process_watched(args: &Args, engine: &GixEngine, path: &Path, label: &str, check: bool, written: &mut HashMap<PathBuf, String>) {
+    let content = match read_gitignore_file(path) {
+        Ok(content) => content,
+        Err(GixError::FileNotFound(_)) => {
+            written.remove(path);
+            print_watch_removed(label);
+            return;
+        }
+        Err(error) => return print_watch_failed(label, &error),
+    };
+    if written.get(path) == Some(&stable_hash(&content)) {
+        return;
+    }
+    
+    if check {
+        match parse_ignore_file(&content, args.dialect_of(path)).and_then(|file| engine.lint(&file)) {
+            Ok(issues) => print_watch_checked(label, &issues, args.verbose),
+            Err(error) => print_watch_failed(label, &error),
+        }
+        return;
+    }
+    
+    let options = BatchOptions {
+        dry_run: args.dry_run,
+        allow_empty: args.allow_empty,
+        backup: args.should_backup().then(|| args.backup_policy()),
+    };
+    let result = engine.optimize_many(&[path.to_path_buf()], &options, &mut PassStats::default()).remove(0);
+    let outcome = match result.outcome {
+        Ok(outcome) if outcome.written => record_engine_write(args, path, &outcome).map(|()| outcome),
+        outcome => outcome,
+    };
+    match outcome {
+        Ok(outcome) => {
+            if outcome.written {
+                written.insert(path.to_path_buf(), stable_hash(&outcome.optimized_content));
+            }
+            print_watch_optimized(label, &outcome, args.dry_run);
+            if args.verbose {
+                print_optimization_report(&OptimizationReport::describe(&outcome.original, &outcome.optimized, &[], &[], &[], &[]));
+            }
+        }
+        Err(error) => print_watch_failed(label, &error),
+    }
+}
+
+fn run_audit(path: &Path) -> Result<(), GixError> {
+    let file = parse_gitignore(&read_gitignore_file(path)?)?;
+    // Patterns are relative to the directory holding the file, and so are the listed paths
+    let matches = find_ignored_tracked_files(&file, &tracked_files(base_dir(path))?);
+    print_tracked_matches(path, &matches);
+    
+    if !matches.is_empty() {
+        return Err(GixError::CheckFailed(matches.len()));
+    }
+    Ok(())
+}
+
+fn run_audit_log(args: &Args, action: &AuditLogAction) -> Result<(), GixError> {
+    let (AuditLogAction::Show { dir } | AuditLogAction::Verify { dir }) = action;
+    let dir = dir.as_deref().unwrap_or(Path::new("."));
+    let log = audit_log_path(dir, args.metadata);
+    if !log.is_file() {
+        return Err(GixError::FileNotFound(format!("{} (writes are only logged with --audit-log)", log.display())));
+    }
+    
+    match action {
+        AuditLogAction::Show { .. } => print_audit_records(&log, &read_audit_log(&log)?),
+        AuditLogAction::Verify { .. } => {
+            // Logged paths are relative to the repository root
+            let root = repository_root(dir).unwrap_or_else(|| dir.to_path_buf());
+            let verification = verify_audit_log(&log, &root)?;
+            print_audit_verification(&log, &verification);
+            if let Some(line) = verification.broken_line {
+                return Err(GixError::AuditLogBroken(format!("{} line {}", log.display(), line)));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_scan(root: &Path, summary_path: Option<&Path>, options: &ScanOptions) -> Result<(), GixError> {
+    let summary = scan_repository(root, options)?;
+    match summary_path {
+        Some(path) => print_summary_written(path, write_summary(path, &summary)?),
+        None => print!("{}", summary.render_markdown()),
+    }
+    Ok(())
+}
+
+fn run_fleet_report(args: &Args, repos_from: Option<&Path>, dir: Option<&Path>, checkout_dir: Option<&Path>, format: FleetFormat, output: Option<&Path>) -> Result<(), GixError> {
+    let members = match (repos_from, dir) {
+        (Some(list), _) => read_repository_list(&read_gitignore_file(list)?, base_dir(list)),
+        (None, dir) => checkouts_in(dir.unwrap_or(Path::new(".")))?,
+    };
+    let checkout_dir = checkout_dir.map(Path::to_path_buf).unwrap_or_else(|| std::env::temp_dir().join("gix-fleet"));
+    let report = assess_fleet(&members, &checkout_dir, &args.whitespace_policy());
+    
+    match output {
+        Some(path) => {
+            write_gitignore_file(path, &report.render(format))?;
+            print_fleet_report_written(path, report.repositories.len());
+        }
+        None => print!("{}", report.render(format)),
+    }
+    Ok(())
+}
+
+pub(crate) fn run_init(args: &Args, engine: &GixEngine, like: Option<&Path>, output_path: &Path, dry_run: bool, force: bool) -> Result<(), GixError> {
+    let Some(like) = like else {
+        return Err(GixError::UsageError("gix init needs --like".to_string()));
+    };
+    if output_path.exists() && !force && !dry_run {
+        return Err(GixError::AlreadyExists(output_path.display().to_string()));
+    }
+    
+    // A repository is read through its root .gitignore
+    let source_path = if like.is_dir() { like.join(".gitignore") } else { like.to_path_buf() };
+    let source = parse_gitignore(&read_gitignore_file(&source_path)?)?;
+    let project_dir = match output_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let stack = detect_stack(project_dir);
+    let tailored = tailor_gitignore(&source, &stack, engine.categorizer());
+    
+    if dry_run {
+        println!("{}", tailored.file);
+        return Ok(());
+    }
+    
+    write_ignore_file(args, "init", output_path, &tailored.file.serialize(&WriteOptions { trailing_newline: true, ..WriteOptions::default() }))?;
+    print_initialized(output_path, &source_path, &stack, &tailored);
+    Ok(())
+}
+
+/// Category names given for one kind of category, with the constructor of that kind
+type CategoryNames<'a> = (fn(String) -> PatternCategory, &'a Vec<String>);
+
+/// Write a new file from the built-in pattern sets of the named categories
+fn run_generate(args: &Args, engine: &GixEngine, names: &[CategoryNames], output_path: &Path, dry_run: bool, force: bool) -> Result<(), GixError> {
+    if output_path.exists() && !force && !dry_run {
+        return Err(GixError::AlreadyExists(output_path.display().to_string()));
+    }
+    
+    let categorizer = engine.categorizer();
+    let mut categories = Vec::new();
+    for (kind, wanted) in names {
+        for name in wanted.iter() {
+            let Some(category) = categorizer.find_category(*kind, name) else {
+                let known = categorizer.category_names(*kind).join(", ");
+                return Err(GixError::UnknownCategory(format!("{} '{}' (known: {})", kind(String::new()).kind_name(), name, known)));
+            };
+            if !categories.contains(&category) {
+                categories.push(category);
+            }
+        }
+    }
+    let composed = compose_templates(&categories, categorizer, engine.comment_generator());
+    
+    if dry_run {
+        println!("{}", composed.file);
+        return Ok(());
+    }
+    
+    write_ignore_file(args, "init", output_path, &composed.file.serialize(&WriteOptions { trailing_newline: true, ..WriteOptions::default() }))?;
+    print_generated(output_path, &categories, &composed, args.verbose);
+    Ok(())
+}
+
+/// Make the edits of a plan, or show them with --dry-run, once every planned file is checked to be as it was
+fn run_apply_plan(args: &Args, plan_path: &Path) -> Result<(), GixError> {
+    let plan = Plan::parse(&read_gitignore_file(plan_path)?)?;
+    
+    // Nothing is written unless every file can be
+    let mut updates = Vec::new();
+    for planned in &plan.files {
+        let path = PathBuf::from(&planned.path);
+        let current = read_gitignore_file(&path)?;
+        let content = planned.apply(&current)?;
+        updates.push((path, planned, current, content));
+    }
+    
+    for (path, planned, current, content) in &updates {
+        if args.dry_run {
+            let name = path.display().to_string();
+            print_diff(&unified_diff(&name, &name, current, content, DEFAULT_CONTEXT));
+            continue;
+        }
+        if args.should_backup() {
+            create_backup_in(path, &args.backup_policy())?;
+        }
+        write_ignore_file(args, &planned.command, path, content)?;
+    }
+    print_plan_applied(plan_path, &plan, args.dry_run);
+    Ok(())
+}
+
+fn run_test_path(paths: &[String], path: &Path) -> Result<(), GixError> {
+    let file = parse_gitignore(&read_gitignore_file(path)?)?;
+    let base = base_dir(path);
+    for tested in paths {
+        // Paths that exist are checked as what they are; others are directories only with a trailing /
+        let is_dir = base.join(tested).is_dir();
+        print_path_match(path, tested, file.matches(tested, is_dir).as_ref());
+    }
+    Ok(())
+}
+
+fn run_explain(tested: &str, path: &Path) -> Result<(), GixError> {
+    let file = parse_gitignore(&read_gitignore_file(path)?)?;
+    let is_dir = base_dir(path).join(tested).is_dir();
+    print_explanation(path, &explain_path(&file, tested, is_dir));
+    Ok(())
+}
+
+fn run_add(args: &Args, engine: &GixEngine, patterns: &[String], path: &Path, section: Option<&str>, force: bool, dry_run: bool) -> Result<(), GixError> {
+    let content = if path.exists() { read_gitignore_file(path)? } else { String::new() };
+    let original_file = parse_ignore_file(&content, args.dialect_of(path))?;
+    let mut file = original_file.clone();
+    let (mut added, mut present, mut covered) = (Vec::new(), Vec::new(), Vec::new());
+    for pattern in patterns {
+        let insertion = insert_pattern(&file, pattern, engine.categorizer(), engine.comment_generator(), section)?;
+        if insertion.duplicate_of.is_some() {
+            present.push(insertion);
+        } else if insertion.covered_by.is_some() && !force {
+            covered.push(insertion);
+        } else {
+            file = insertion.file.clone();
+            added.push(insertion);
+        }
+    }
+    // Later patterns can push earlier ones down, so report where each one ends up
+    for insertion in &mut added {
+        let pattern = insertion.file.entries[insertion.line_number - 1].comparison_pattern();
+        if let Some(entry) = file.entries.iter().find(|entry| entry.is_pattern() && entry.comparison_pattern() == pattern) {
+            insertion.line_number = entry.line_number;
+        }
+    }
+    
+    if let ([single], [insertion]) = (patterns, covered.as_slice()) {
+        let (line_number, covering) = insertion.covered_by.as_ref().expect("covered insertions have a covering pattern");
+        return Err(GixError::AlreadyCovered(format!("'{}' by '{}' on line {} of {}", single, covering, line_number, path.display())));
+    }
+    
+    let added_content = file.serialize(&WriteOptions { trailing_newline: true, ..WriteOptions::preserving(&original_file) });
+    if dry_run {
+        let name = path.display().to_string();
+        print_diff(&unified_diff(&name, &name, &content, &added_content, DEFAULT_CONTEXT));
+        return Ok(());
+    }
+    
+    if !added.is_empty() {
+        write_ignore_file(args, "add", path, &added_content)?;
+    }
+    print_patterns_added(path, &added, &present, &covered);
+    Ok(())
+}
+
+fn run_remove(args: &Args, pattern: Option<&str>, line: Option<usize>, matching: &[String], path: &Path, dry_run: bool) -> Result<(), GixError> {
+    let content = read_gitignore_file(path)?;
+    let original_file = parse_ignore_file(&content, args.dialect_of(path))?;
+    let targets: Vec<&GitignoreEntry> = original_file.entries.iter()
+        .filter(|entry| match (pattern, line) {
+            (Some(pattern), _) => entry.comparison_pattern().as_deref() == Some(pattern),
+            (None, Some(line)) => entry.is_pattern() && entry.line_number == line,
+            (None, None) => entry.comparison_pattern().is_some_and(|text| matching.iter().any(|glob| glob_selects(glob, &text))),
+        })
+        .collect();
+    if targets.is_empty() {
+        return Err(GixError::UsageError(match (pattern, line) {
+            (Some(pattern), _) => format!("'{}' is not a pattern in {}", pattern, path.display()),
+            (None, Some(line)) => format!("line {} of {} holds no pattern", line, path.display()),
+            (None, None) => format!("no pattern in {} matches {}", path.display(), matching.join(", ")),
+        }));
+    }
+    if let Some(entry) = targets.iter().find(|entry| entry.preserve) {
+        return Err(GixError::UnsafeRewrite(format!("line {} is in a region marked `# gix:off`", entry.line_number)));
+    }
+    
+    let dir = base_dir(path);
+    let paths = list_paths(dir, &WalkOptions::default())?;
+    // Outside a repository nothing is tracked
+    let tracked = match repository_root(dir) {
+        Some(_) => tracked_files(dir)?,
+        None => Vec::new(),
+    };
+    let lines: Vec<usize> = targets.iter().map(|entry| entry.line_number).collect();
+    let (after, effect) = simulate_removal(&original_file, &lines, &paths, &tracked);
+    let removed_content = after.serialize(&WriteOptions::preserving(&original_file));
+    if dry_run {
+        let name = path.display().to_string();
+        print_diff(&unified_diff(&name, &name, &content, &removed_content, DEFAULT_CONTEXT));
+        print_pattern_removed(path, &effect, false);
+        return Ok(());
+    }
+    
+    write_ignore_file(args, "remove", path, &removed_content)?;
+    print_pattern_removed(path, &effect, true);
+    Ok(())
+}
+
+fn run_simulate_add(pattern: &str, path: &Path) -> Result<(), GixError> {
+    let file = parse_gitignore(&read_gitignore_file(path)?)?;
+    let dir = base_dir(path);
+    let paths = list_paths(dir, &WalkOptions::default())?;
+    // Outside a repository nothing is tracked
+    let tracked = match repository_root(dir) {
+        Some(_) => tracked_files(dir)?,
+        None => Vec::new(),
+    };
+    print_addition_effect(path, &simulate_addition(&file, pattern, &paths, &tracked)?);
+    Ok(())
+}
+
+fn run_compare(engine: &GixEngine, path: &Path, template: &str) -> Result<(), GixError> {
+    let file = parse_gitignore(&read_gitignore_file(path)?)?;
+    
+    // A template names a file when one exists, and a built-in pattern set otherwise
+    let standard = match Path::new(template).is_file() {
+        true => parse_gitignore(&read_gitignore_file(Path::new(template))?)?,
+        false => {
+            let categorizer = engine.categorizer();
+            let kinds: [fn(String) -> PatternCategory; 4] = [PatternCategory::Language, PatternCategory::Framework, PatternCategory::Tool, PatternCategory::OperatingSystem];
+            let Some(category) = kinds.iter().find_map(|kind| categorizer.find_category(*kind, template)) else {
+                let known: Vec<String> = kinds.iter().flat_map(|kind| categorizer.category_names(*kind)).collect();
+                return Err(GixError::UnknownCategory(format!("template '{}' (known: {})", template, known.join(", "))));
+            };
+            generate_gitignore(&[category], categorizer, engine.comment_generator())
+        }
+    };
+    
+    print_template_drift(path, template, &compare_with_template(&file, &standard));
+    Ok(())
+}
+
+fn run_suggest(engine: &GixEngine, path: &Path) -> Result<(), GixError> {
+    // A project without a .gitignore yet gets every known pattern suggested
+    let file = match path.exists() {
+        true => parse_gitignore(&read_gitignore_file(path)?)?,
+        false => GitignoreFile::new(),
+    };
+    let stack = detect_stack(base_dir(path));
+    let mut suggestions = suggest_patterns(&file, &stack, engine.categorizer());
+    suggestions.extend(engine.plugins().suggest_patterns(&file));
+    print_suggestions(&stack, &suggestions);
+    print_collapse_suggestions(&suggest_collapses(&file, COLLAPSE_MIN_PATTERNS));
+    Ok(())
+}
+
+fn run_sync_check(args: &Args, gitignore_path: &Path, dockerignore_path: &Path, sync: bool) -> Result<(), GixError> {
+    let gitignore = parse_gitignore(&read_gitignore_file(gitignore_path)?)?;
+    let dockerignore = parse_ignore_file(&read_gitignore_file(dockerignore_path)?, Dialect::Dockerignore)?;
+    let report = sync_check(&gitignore, &dockerignore);
+    print_sync_report(gitignore_path, dockerignore_path, &report, sync);
+    
+    if !sync {
+        if !report.is_empty() {
+            return Err(GixError::CheckFailed(report.len()));
+        }
+        return Ok(());
+    }
+    
+    // Each file gets the other's patterns in its own dialect
+    for (path, mut file, gaps, other) in [
+        (gitignore_path, gitignore, &report.missing_in_gitignore, dockerignore_path),
+        (dockerignore_path, dockerignore, &report.missing_in_dockerignore, gitignore_path),
+    ] {
+        if gaps.is_empty() {
+            continue;
+        }
+        let patterns: Vec<String> = gaps.iter().map(|gap| gap.translated.clone()).collect();
+        let options = WriteOptions { trailing_newline: true, ..WriteOptions::preserving(&file) };
+        file.append_section(&format!("# Synced from {} by gix", other.display()), &patterns);
+        write_ignore_file(args, "sync-check", path, &file.serialize(&options))?;
+    }
+    Ok(())
+}
+
+fn run_add_template(args: &Args, name: &str, path: &Path, source: TemplateSource, refresh: bool, dry_run: bool) -> Result<(), GixError> {
+    let content = if path.exists() { read_gitignore_file(path)? } else { String::new() };
+    let original_file = parse_gitignore(&content)?;
+    let template = fetch_template(source, name, refresh)?;
+    let header = format!("# {} (from {})", name, source.name());
+    let (merged, added) = merge_template(&original_file, &parse_gitignore(&template.content)?, &header);
+    
+    let merged_content = merged.serialize(&WriteOptions { trailing_newline: true, ..WriteOptions::preserving(&original_file) });
+    if dry_run {
+        let name = path.display().to_string();
+        print_diff(&unified_diff(&name, &name, &content, &merged_content, DEFAULT_CONTEXT));
+        return Ok(());
+    }
+    
+    if added > 0 {
+        write_ignore_file(args, "add-template", path, &merged_content)?;
+    }
+    print_template_added(path, name, &template, added);
+    Ok(())
+}
+
+fn run_merge(args: &Args, engine: &GixEngine, paths: &[PathBuf], output_path: &Path, dry_run: bool, backup: bool) -> Result<(), GixError> {
+    let mut sources = Vec::new();
+    for path in paths {
+        sources.push((path.display().to_string(), parse_gitignore(&read_gitignore_file(path)?)?));
+    }
+    let merged = merge_gitignores(&sources, engine.analyzer());
+    
+    if dry_run {
+        println!("{}", merged.file);
+        return Ok(());
+    }
+    
+    let created = if backup { create_backup_in(output_path, &args.backup_policy())? } else { None };
+    if let Some(created) = created {
+        print_backup(&created);
+    }
+    
+    write_ignore_file(args, "merge", output_path, &merged.file.serialize(&WriteOptions { trailing_newline: true, ..WriteOptions::preserving(&merged.file) }))?;
+    print_merged(output_path, sources.len(), &merged, args.verbose);
+    
+    Ok(())
+}
+
+fn run_organize(args: &Args, engine: &GixEngine, input_path: &Path, output_path: &Path, dry_run: bool, backup: bool, pair_negations: bool) -> Result<(), GixError> {
+    let original_file = parse_gitignore(&read_gitignore_file(input_path)?)?;
+    let (organized, paired) = match pair_negations {
+        true => engine.organize_paired(&original_file)?,
+        false => (engine.organize(&original_file)?, Vec::new()),
+    };
+    
+    if dry_run {
+        println!("{}", organized);
+        return Ok(());
+    }
+    
+    let created = if backup { create_backup_in(input_path, &args.backup_policy())? } else { None };
+    if let Some(created) = created {
+        print_backup(&created);
+    }
+    
+    write_ignore_file(args, "organize", output_path, &organized.serialize(&WriteOptions::preserving(&original_file)))?;
+    // Sections are separated by single blank lines
+    let sections = match organized.stats.pattern_lines {
+        0 => 0,
+        _ => organized.stats.blank_lines + 1,
+    };
+    print_organized(output_path, sections);
+    print_moved_negations(&paired, args.verbose);
+    print_extension_groups(&find_extension_groups(&organized, EXTENSION_GROUP_MIN));
+    
+    Ok(())
+}
+
+#[cfg(feature = "dataset")]
+fn run_dataset(action: &DatasetAction, args: &Args) -> Result<(), GixError> {
+    use crate::cli::output::{print_dataset_status, print_dataset_update};
+    use crate::core::dataset::{update_dataset, DEFAULT_DATASET_URL};
+    
+    match action {
+        DatasetAction::Update { url } => {
+            if args.offline {
+                return Err(GixError::NetworkError("cannot update the dataset with --offline".to_string()));
+            }
+            let update = update_dataset(url.as_deref().unwrap_or(DEFAULT_DATASET_URL))?;
+            print_dataset_update(&update);
+        }
+        DatasetAction::Status => print_dataset_status(&args.dataset()),
+    }
+    
+    Ok(())
+}
+
+#[cfg(not(feature = "dataset"))]
+fn run_dataset(_action: &DatasetAction, _args: &Args) -> Result<(), GixError> {
+    Err(GixError::UsageError("gix was built without the `dataset` feature".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_process_watched_skips_own_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n*.log\nbuild/\n").unwrap();
+        let args = Args::parse_from(["gix", "watch"]);
+        let engine = GixEngine::new(args.engine_config());
+        let mut written = HashMap::new();
+        
+        process_watched(&args, &engine, &input, ".gitignore", false, &mut written);
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\nbuild/\n");
+        assert_eq!(written.get(&input), Some(&stable_hash("*.log\nbuild/\n")));
+        assert_eq!(read_history(&dir.path().join(".git/gix/history")).unwrap().len(), 1);
+        
+        // The event for gix's own write changes nothing
+        process_watched(&args, &engine, &input, ".gitignore", false, &mut written);
+        assert_eq!(read_history(&dir.path().join(".git/gix/history")).unwrap().len(), 1);
+        
+        std::fs::remove_file(&input).unwrap();
+        process_watched(&args, &engine, &input, ".gitignore", true, &mut written);
+        assert!(written.is_empty());
+    }
+}
@@ -1,5 +1,9 @@
 pub mod args;
+mod commands;
 pub mod output;
+pub mod report;
+mod run;
 
 pub use args::Args;
-pub use output::print_results; 
\ No newline at end of file
+pub use output::print_results;
+pub use run::run; 
\ No newline at end of file
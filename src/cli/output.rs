@@ -1,31 +1,112 @@
-use crate::models::{GitignoreFile, GixError};
-use crate::cli::args::{Args, OptimizationMode};
-use std::path::Path;
+use crate::models::{GitignoreFile, GixError, DuplicateReport, PathMatch};
+use crate::cli::args::{Args, KeepOccurrence, OptimizationMode};
+use crate::engine::{FileOutcome, FileResult};
+use crate::core::whitespace::WhitespaceViolation;
+use crate::core::blocks::RepeatedBlock;
+use crate::core::optimizer::{AnalysisFilter, AnalysisScope, GitignoreAnalysis, KeptForSafety, PassStats, PatternConflict, RedundantPattern};
+use crate::core::categorizer::{CategorySummary, PatternCategory};
+use crate::core::changes::OptimizationReport;
+use crate::core::template::{ComposedGitignore, TailoredGitignore, TemplateDrift};
+use crate::core::audit::TrackedMatch;
+use crate::core::audit_log::{format_timestamp, AuditRecord, AuditVerification};
+use crate::core::history::HistoryStep;
+use crate::core::equivalence::{DecisionChange, EquivalenceReport};
+use crate::core::explain::{MatchOutcome, PathExplanation};
+use crate::core::folding::FoldedPatterns;
+use crate::core::lint::LintIssue;
+use crate::core::rules::{RuleSeverities, Severity, RULES};
+use crate::core::negations::MovedNegation;
+use crate::core::plan::Plan;
+use crate::core::unused::UnusedPattern;
+use crate::core::dockerignore::SyncReport;
+use crate::core::insertion::Insertion;
+use crate::core::extensions::ExtensionGroup;
+use crate::core::detector::{CollapseSuggestion, Suggestion};
+use crate::core::merge::MergedGitignore;
+use crate::core::risk::ChangeRisk;
+use crate::core::self_test::{describe_decision, SelfTestReport};
+use crate::core::simulate::{AdditionEffect, RemovalEffect};
+use crate::core::remote_template::RemoteTemplate;
+use crate::utils::synthetic::FixtureSize;
+#[cfg(feature = "dataset")]
+use crate::core::dataset::{Dataset, DatasetSource, DatasetUpdate};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Print optimization results to the user
+///
+/// Patterns removed as covered by broader ones are counted apart from duplicates. The files
+/// are only said to be ignored the same when every removal was an exact duplicate, comment,
+/// blank line or whitespace fix and `changes` (over the negation probes) is empty.
 pub fn print_results(
     args: &Args,
     original_file: &GitignoreFile,
     optimized_file: &GitignoreFile,
-    duplicates: &std::collections::HashMap<String, Vec<usize>>,
+    duplicates: &DuplicateReport,
+    blocks: &[RepeatedBlock],
+    redundant: &[RedundantPattern],
+    changes: &[DecisionChange],
 ) -> Result<(), GixError> {
     if args.verbose {
         println!("Optimizing .gitignore file...");
     }
 
     let removed_lines = original_file.entries.len() - optimized_file.entries.len();
+    let removed_patterns = original_file.stats.pattern_lines.saturating_sub(optimized_file.stats.pattern_lines);
+    let repeated: usize = duplicates.redundant_lines() + blocks.iter().map(|block| block.pattern_count).sum::<usize>();
+    let exact_only = redundant.is_empty() && removed_patterns <= repeated;
     
     if args.dry_run {
         println!("DRY RUN - No changes will be made");
     }
     
     if removed_lines > 0 {
-        println!("✅ Removed {} duplicate line(s)", removed_lines);
+        let covered = redundant.len().min(removed_lines);
+        if removed_lines > covered {
+            println!("✅ Removed {} duplicate line(s)", removed_lines - covered);
+        }
+        if covered > 0 {
+            println!("✅ Removed {} pattern(s) covered by broader patterns", covered);
+        }
+        if args.heuristics_enabled() {
+            println!("⚠️  Heuristic merges enabled: equivalent-looking patterns were merged, which may change what is ignored");
+        } else if let Some(change) = changes.first() {
+            println!("⚠️  {} checked path(s) are decided differently, e.g. {}; review the changes", changes.len(), change.describe());
+        } else if exact_only {
+            println!("🛡️  Safe mode: only exact duplicates, comments, blank lines and whitespace were removed; the same files are ignored");
+        } else {
+            println!("🛡️  Safe mode: removed patterns are covered by patterns that were kept; no checked path is decided differently");
+        }
+        
+        if args.verbose && !blocks.is_empty() {
+            println!("\nRepeated blocks found:");
+            for block in blocks {
+                println!(
+                    "  lines {}-{} repeat lines {}-{} ({} lines, {} patterns)",
+                    block.repeat_lines().start(),
+                    block.repeat_lines().end(),
+                    block.original_lines().start(),
+                    block.original_lines().end(),
+                    block.len,
+                    block.pattern_count
+                );
+            }
+        }
         
         if args.verbose && !duplicates.is_empty() {
             println!("\nDuplicate patterns found:");
-            for (pattern, line_numbers) in duplicates {
-                println!("  {} (lines: {:?})", pattern, line_numbers);
+            for group in duplicates {
+                let kept = match args.keep {
+                    KeepOccurrence::First => group.first_line(),
+                    KeepOccurrence::Last => group.line_numbers[group.count() - 1],
+                };
+                for line_number in group.line_numbers.iter().filter(|line| **line != kept) {
+                    println!("  {} at {}, duplicate of {}", group.pattern, group.describe_line(*line_number), group.describe_line(kept));
+                }
+                if group.variants.len() > 1 {
+                    println!("    spellings: {}", group.variants.join(" | "));
+                }
             }
         }
     } else {
@@ -71,6 +152,773 @@ fn print_statistics(original: &GitignoreFile, optimized: &GitignoreFile) {
     println!("    Size reduction: {:.1}%", reduction_percent);
 }
 
+/// Print the unified diff of a dry run, if anything would change
+pub fn print_diff(diff: &str) {
+    if diff.is_empty() {
+        return;
+    }
+    
+    println!();
+    print!("{}", diff);
+}
+
+/// Print whitespace policy violations found in check mode
+pub fn print_whitespace_violations(path: &Path, violations: &[WhitespaceViolation]) {
+    if violations.is_empty() {
+        println!("✅ {} follows the whitespace policy", path.display());
+        return;
+    }
+    
+    println!("❌ {} whitespace violation(s) in {}:", violations.len(), path.display());
+    for violation in violations {
+        println!("  line {}: {} [{}]", violation.line_number, violation.message, violation.rule);
+    }
+}
+
+/// Print hygiene issues found by `gix check`
+pub fn print_lint_issues(path: &Path, issues: &[LintIssue]) {
+    if issues.is_empty() {
+        println!("✅ {} has no duplicate, conflicting, redundant or invalid patterns", path.display());
+        return;
+    }
+    
+    println!("❌ {} issue(s) in {}:", issues.len(), path.display());
+    for issue in issues {
+        let warning = if issue.severity == Severity::Warn { ", warning" } else { "" };
+        println!(
+            "  line {}: {} [{} {}{}, fix: {}]",
+            issue.line_number, issue.message, issue.kind.code(), issue.kind, warning, issue.risk
+        );
+    }
+}
+
+/// Print the rules of `gix check` with the severity each has
+pub fn print_rules(severities: &RuleSeverities) {
+    for rule in RULES {
+        println!("  {}  {:<18}  {:<5}  {}", rule.code, rule.name, severities.rule_severity(rule.code), rule.summary);
+    }
+}
+
+/// Print the patterns removed because broader patterns cover them
+pub fn print_redundant_patterns(redundant: &[RedundantPattern]) {
+    if redundant.is_empty() {
+        return;
+    }
+    
+    println!("\nRedundant patterns removed:");
+    for removed in redundant {
+        println!(
+            "  line {}: {} (covered by '{}' on line {})",
+            removed.line_number, removed.pattern, removed.covered_by, removed.covered_by_line
+        );
+    }
+}
+
+/// Print every change optimization made, in line order, for --verbose
+pub fn print_optimization_report(report: &OptimizationReport) {
+    if report.is_empty() {
+        return;
+    }
+    
+    println!("\nChanges:");
+    for action in &report.actions {
+        println!("  {}", action.describe());
+    }
+}
+
+/// Print how many lines each optimization pass removed, and how long optimization took if timed
+///
+/// Each pass is labelled with the risk of its removals; `heuristics` says whether heuristic
+/// merges were enabled.
+pub fn print_pass_stats(stats: &PassStats, elapsed: Option<Duration>, heuristics: bool) {
+    println!("\nRemovals by pass:");
+    for (pass, count) in stats.passes() {
+        if count > 0 {
+            println!("  {}: {} ({})", pass, count, PassStats::risk(pass, heuristics));
+        }
+    }
+    println!("  total: {}", stats.total());
+    if let Some(elapsed) = elapsed {
+        println!("Optimized in {:.2?}", elapsed);
+    }
+}
+
+/// Print a change skipped because it is riskier than --max-risk allows
+pub fn print_risk_skipped(change: &str, risk: ChangeRisk) {
+    println!("⏭️  Skipped {} ({}, above --max-risk)", change, risk);
+}
+
+/// Print a table of the files optimized by --recursive, one row per file, then the totals
+///
+/// Each result is labelled with the file's path relative to the walked root.
+pub fn print_recursive_results(results: &[(&str, &FileResult)], dry_run: bool) {
+    if dry_run {
+        println!("DRY RUN - No changes will be made");
+    }
+    if results.is_empty() {
+        println!("No .gitignore files found");
+        return;
+    }
+    
+    let width = results.iter().map(|(path, _)| path.chars().count()).max().unwrap_or(0).max("FILE".len());
+    println!("  {:<width$}  {:<9}  LINES", "FILE", "STATUS", width = width);
+    for (path, result) in results {
+        match &result.outcome {
+            Ok(outcome) if outcome.removed() == 0 => {
+                println!("  {:<width$}  {:<9}  {}", path, "unchanged", outcome.original.entries.len(), width = width);
+            }
+            Ok(outcome) => println!(
+                "  {:<width$}  {:<9}  {} → {} (-{})",
+                path, "optimized", outcome.original.entries.len(), outcome.optimized.entries.len(), outcome.removed(), width = width
+            ),
+            Err(error) => println!("  {:<width$}  {:<9}  {}", path, "failed", error, width = width),
+        }
+    }
+    
+    let outcomes: Vec<&FileOutcome> = results.iter().filter_map(|(_, result)| result.outcome.as_ref().ok()).collect();
+    let before: usize = outcomes.iter().map(|outcome| outcome.original.entries.len()).sum();
+    let after: usize = outcomes.iter().map(|outcome| outcome.optimized.entries.len()).sum();
+    let changed = outcomes.iter().filter(|outcome| outcome.removed() > 0).count();
+    println!(
+        "✅ {} of {} file(s) optimized: {} → {} lines (-{})",
+        changed, results.len(), before, after, before - after
+    );
+}
+
+/// Print what `gix init --like` kept from the source file
+pub fn print_initialized(path: &Path, source: &Path, stack: &[PatternCategory], tailored: &TailoredGitignore) {
+    let stack: Vec<String> = stack.iter().map(PatternCategory::short_name).collect();
+    match stack.is_empty() {
+        true => println!("Detected stack: none"),
+        false => println!("Detected stack: {}", stack.join(", ")),
+    }
+    if !tailored.dropped.is_empty() {
+        println!("Dropped {} section(s) for other stacks: {}", tailored.dropped.len(), tailored.dropped.join(", "));
+    }
+    println!(
+        "✅ Created {} with {} section(s) and {} pattern(s) from {}",
+        path.display(), tailored.kept.len(), tailored.file.stats.pattern_lines, source.display()
+    );
+}
+
+/// Print the outcome of `gix init` from the built-in pattern sets
+pub fn print_generated(path: &Path, categories: &[PatternCategory], composed: &ComposedGitignore, verbose: bool) {
+    for negation in &composed.reordered {
+        println!(
+            "↕️  Moved {} ({} template) to the end so '{}' from {} doesn't override it",
+            negation.pattern, negation.source, negation.overridden_by, negation.overridden_source
+        );
+    }
+    if verbose {
+        for duplicate in &composed.duplicates {
+            println!("  dropped {} from {} (already in {})", duplicate.pattern, duplicate.source, duplicate.kept_from);
+        }
+    }
+    let names: Vec<String> = categories.iter().map(PatternCategory::short_name).collect();
+    println!("✅ Created {} with {} pattern(s) for {}", path.display(), composed.file.stats.pattern_lines, names.join(", "));
+}
+
+/// Print the verdict of `gix test-path` for one path
+pub fn print_path_match(file: &Path, path: &str, found: Option<&PathMatch>) {
+    let Some(found) = found else {
+        println!("{}: not ignored (no pattern matches)", path);
+        return;
+    };
+    
+    let verdict = if found.ignored { "ignored" } else { "not ignored" };
+    let via = found.via_directory.as_ref().map(|dir| format!(" via {}", dir)).unwrap_or_default();
+    println!("{}: {} by {}:{}: {}{}", path, verdict, file.display(), found.line_number, found.pattern, via);
+}
+
+/// Print every pattern matching a path and what became of it, then the decision
+pub fn print_explanation(file: &Path, explanation: &PathExplanation) {
+    if explanation.matches.is_empty() {
+        println!("{}: not ignored (no pattern in {} matches)", explanation.path, file.display());
+        return;
+    }
+    
+    println!("{}:", explanation.path);
+    for found in &explanation.matches {
+        let marker = if found.outcome == MatchOutcome::Decided { "→" } else { " " };
+        println!(
+            "  {} line {}: {} matches {} ({})",
+            marker, found.line_number, found.pattern, found.matched, found.outcome.describe()
+        );
+    }
+    
+    let verdict = if explanation.is_ignored() { "ignored" } else { "not ignored" };
+    match explanation.overridden_negations() {
+        0 => println!("Result: {}", verdict),
+        negations => println!("Result: {} ({} negation(s) had no effect)", verdict, negations),
+    }
+}
+
+/// Most paths listed per group by `print_addition_effect`
+const LISTED_PATHS: usize = 10;
+
+/// Print what appending a pattern would do, without having written it
+pub fn print_addition_effect(path: &Path, effect: &AdditionEffect) {
+    println!("🧪 Adding '{}' to {} (nothing was written):", effect.pattern, path.display());
+    if let Some((line_number, existing)) = &effect.duplicate_of {
+        println!("  ⚠️  Repeats line {}: {}", line_number, existing);
+    }
+    for (line_number, existing) in &effect.conflicts {
+        println!("  ⚠️  Conflicts with line {}: {}", line_number, existing);
+    }
+    
+    let groups = [
+        ("Untracked file(s) newly ignored", &effect.newly_ignored),
+        ("Tracked file(s) that would be ignored but stay tracked", &effect.tracked),
+        ("File(s) re-included", &effect.reincluded),
+    ];
+    print_path_groups(&groups);
+    
+    if effect.is_noop() {
+        println!("  No file in the working tree would change");
+    }
+    if !effect.tracked.is_empty() {
+        println!("\n💡 Git keeps tracking files it already tracks; run `git rm --cached <path>` for those meant to be ignored");
+    }
+}
+
+/// Print the patterns `gix rm` removed, or would remove, and the files that change with them
+pub fn print_pattern_removed(path: &Path, effect: &RemovalEffect, written: bool) {
+    let verb = if written { "Removed" } else { "Would remove" };
+    for (line_number, pattern) in &effect.removed {
+        println!("🗑️  {} line {} of {}: {}", verb, line_number, path.display(), pattern);
+    }
+    
+    let groups = [
+        ("⚠️  Untracked file(s) no longer ignored", &effect.exposed),
+        ("File(s) newly ignored", &effect.newly_ignored),
+    ];
+    print_path_groups(&groups);
+    
+    if effect.removed.len() > 1 {
+        println!("  📊 {} line(s) affected", effect.removed.len());
+    }
+    if effect.is_noop() {
+        println!("  No file in the working tree changes");
+    }
+    if !effect.exposed.is_empty() {
+        println!("\n💡 These files now show up in `git status`; make sure none of them is meant to stay out of the repository");
+    }
+}
+
+/// Print titled lists of paths, each cut short after `LISTED_PATHS`; empty lists are skipped
+fn print_path_groups(groups: &[(&str, &Vec<String>)]) {
+    for (title, paths) in groups.iter().filter(|(_, paths)| !paths.is_empty()) {
+        println!("  {}: {}", title, paths.len());
+        for listed in paths.iter().take(LISTED_PATHS) {
+            println!("    {}", listed);
+        }
+        if paths.len() > LISTED_PATHS {
+            println!("    ... and {} more", paths.len() - LISTED_PATHS);
+        }
+    }
+}
+
+/// Print where `gix add` put each pattern, the ones the file already had and the ones skipped as covered
+pub fn print_patterns_added(path: &Path, added: &[Insertion], present: &[Insertion], covered: &[Insertion]) {
+    for insertion in present {
+        if let Some((line_number, existing)) = &insertion.duplicate_of {
+            println!("✅ {} already has '{}' on line {}", path.display(), existing, line_number);
+        }
+    }
+    for insertion in added {
+        let place = match (&insertion.section, insertion.new_section) {
+            (Some(section), true) => format!("in a new '{}' section", section),
+            (Some(section), false) => format!("in the '{}' section", section),
+            // Only the end of the file gives the pattern the last word over the patterns below its section
+            (None, _) => "at the end of the file, after the patterns it must not be overridden by".to_string(),
+        };
+        println!("✅ Added '{}' to {} on line {} {}", insertion.pattern, path.display(), insertion.line_number, place);
+        if let Some((line_number, covering)) = &insertion.covered_by {
+            println!("  ⚠️  Already covered by line {}: {}", line_number, covering);
+        }
+    }
+    for insertion in covered {
+        if let Some((line_number, covering)) = &insertion.covered_by {
+            println!("⏭️  Skipped '{}': already covered by line {}: {}", insertion.pattern, line_number, covering);
+        }
+    }
+    
+    if added.len() + present.len() + covered.len() > 1 {
+        println!("\n📊 {} added, {} already present, {} skipped as covered (use --force to add those)", added.len(), present.len(), covered.len());
+    }
+}
+
+/// Print the patterns a file lacks and adds compared with a template, and the drift
+pub fn print_template_drift(path: &Path, template: &str, drift: &TemplateDrift) {
+    println!("{} compared with {}: {:.0}% drift", path.display(), template, drift.drift_percent());
+    println!("  {} template pattern(s) present", drift.shared);
+    
+    if !drift.missing.is_empty() {
+        println!("\nMissing from the file ({}):", drift.missing.len());
+        for pattern in &drift.missing {
+            println!("  {}", pattern);
+        }
+    }
+    if !drift.extra.is_empty() {
+        println!("\nNot in the template ({}):", drift.extra.len());
+        for (line_number, pattern) in &drift.extra {
+            println!("  line {}: {}", line_number, pattern);
+        }
+    }
+}
+
+/// Print the patterns `gix suggest` recommends, grouped by detected category
+pub fn print_suggestions(stack: &[PatternCategory], suggestions: &[Suggestion]) {
+    let stack: Vec<String> = stack.iter().map(PatternCategory::short_name).collect();
+    match stack.is_empty() {
+        true => println!("Detected stack: none"),
+        false => println!("Detected stack: {}", stack.join(", ")),
+    }
+    if suggestions.is_empty() {
+        println!("✅ No patterns to suggest");
+        return;
+    }
+    
+    for suggestion in suggestions {
+        println!("\n{} ({} pattern(s)):", suggestion.category.display_name(), suggestion.patterns.len());
+        for pattern in &suggestion.patterns {
+            println!("  {}", pattern);
+        }
+    }
+}
+
+/// Print the directory and extension patterns that could replace many patterns each
+pub fn print_collapse_suggestions(suggestions: &[CollapseSuggestion]) {
+    if suggestions.is_empty() {
+        return;
+    }
+    
+    println!("\n📦 Areas that could be collapsed (check nothing else there should be tracked):");
+    for suggestion in suggestions {
+        println!("  {} could replace {} pattern(s): {}", suggestion.pattern, suggestion.replaces.len(), suggestion.replaces.join(", "));
+    }
+}
+
+/// Print related extension patterns that would read better together under one comment
+pub fn print_extension_groups(groups: &[ExtensionGroup]) {
+    if groups.is_empty() {
+        return;
+    }
+    
+    println!("\n💡 Related extensions that could share a comment:");
+    for group in groups {
+        print_extension_group(group);
+    }
+}
+
+fn print_extension_group(group: &ExtensionGroup) {
+    let lines: Vec<String> = group.line_numbers.iter().map(usize::to_string).collect();
+    println!("  🧩 {} on lines {} could share one comment", group.family, lines.join(", "));
+    println!("     (gitignore has no brace expansion, so {} is not an option):", group.brace_pattern());
+    for line in group.block() {
+        println!("       {}", line);
+    }
+}
+
+/// Print the negations moved by --group-negations
+pub fn print_moved_negations(moved: &[MovedNegation], verbose: bool) {
+    if moved.is_empty() {
+        return;
+    }
+    
+    println!("↕️  Moved {} negation(s) next to the patterns they re-include from", moved.len());
+    if verbose {
+        for negation in moved {
+            println!(
+                "  line {}: {} (now follows '{}' from line {})",
+                negation.line_number, negation.pattern, negation.negates, negation.negates_line
+            );
+        }
+    }
+}
+
+/// Print the sibling patterns advanced mode folded into a character class
+pub fn print_folded_patterns(folded: &[FoldedPatterns], dry_run: bool) {
+    let verb = if dry_run { "Would fold" } else { "Folded" };
+    for fold in folded {
+        let lines: Vec<String> = fold.line_numbers.iter().map(usize::to_string).collect();
+        println!("🗜️  {} lines {}: {}", verb, lines.join(", "), fold.explain());
+    }
+}
+
+/// Print the patterns put back because removing them would change what a negation re-includes
+pub fn print_kept_for_safety(kept: &[KeptForSafety]) {
+    for pattern in kept {
+        println!("🛡️  Kept line {}: {} ({})", pattern.line_number, pattern.pattern, pattern.explain());
+    }
+}
+
+/// Print the patterns removed by --prune-unused
+pub fn print_pruned_patterns(unused: &[UnusedPattern], dry_run: bool) {
+    let verb = if dry_run { "Would prune" } else { "Pruned" };
+    for pattern in unused.iter().filter(|pattern| pattern.prunable) {
+        println!("✂️  {} line {}: {} (matches nothing in the working tree)", verb, pattern.line_number, pattern.pattern);
+    }
+}
+
+/// Print where the plan written by --emit-plan went
+pub fn print_plan_written(path: &Path, plan: &Plan) {
+    let edits: usize = plan.files.iter().map(|file| file.edits.len()).sum();
+    println!("📝 Wrote a plan of {} edit(s) to {} file(s) to {}", edits, plan.files.len(), path.display());
+}
+
+/// Print where `gix bench` wrote its synthetic file
+pub fn print_fixture_written(path: &Path, size: FixtureSize) {
+    println!("📝 Wrote a {} synthetic file of {} lines to {}", size.name(), size.lines(), path.display());
+}
+
+/// Print the outcome of `gix apply-plan`
+pub fn print_plan_applied(path: &Path, plan: &Plan, dry_run: bool) {
+    let verb = if dry_run { "Would apply" } else { "Applied" };
+    println!("✅ {} {} to {} file(s)", verb, path.display(), plan.files.len());
+}
+
+/// Print the outcome of `gix verify`: each path decided differently, or that there are none
+pub fn print_equivalence(path: &Path, against: &Path, report: &EquivalenceReport) {
+    if report.is_equivalent() {
+        println!(
+            "✅ {} ignores the same paths as {} ({} path(s) checked)",
+            path.display(), against.display(), report.checked_paths
+        );
+        return;
+    }
+    
+    println!(
+        "❌ {} decides {} of {} path(s) differently from {}:",
+        path.display(), report.changes.len(), report.checked_paths, against.display()
+    );
+    for change in &report.changes {
+        println!("  {}", change.describe());
+    }
+}
+
+/// Print the outcome of `gix self-test`, with a reproduction for each path decided differently
+pub fn print_self_test(report: &SelfTestReport) {
+    for divergence in &report.divergences {
+        println!("❌ {}", divergence.path);
+        for line in divergence.content.lines() {
+            println!("   | {}", line);
+        }
+        println!("   git: {}", describe_decision(&divergence.git));
+        println!("   gix: {}", describe_decision(&divergence.gix));
+        println!("   reproduce: {}", divergence.reproduce());
+    }
+    
+    let summary = format!("{} path(s) in {} file(s) against {}", report.paths, report.cases, report.git_version);
+    match report.divergences.len() {
+        0 => println!("✅ gix agrees with git on all {}", summary),
+        count => println!("⚠️  {} divergence(s) from git over {}", count, summary),
+    }
+}
+
+/// Print the patterns found by `gix audit`, with the tracked files each one ignores
+pub fn print_tracked_matches(path: &Path, matches: &[TrackedMatch]) {
+    if matches.is_empty() {
+        println!("✅ {}: no pattern ignores a tracked file", path.display());
+        return;
+    }
+    
+    for found in matches {
+        println!("{}:{}: '{}' ignores {} tracked file(s):", path.display(), found.line_number, found.pattern, found.paths.len());
+        for tracked in &found.paths {
+            println!("    {}", tracked);
+        }
+    }
+    println!("\n⚠️  Git keeps tracking these files; run `git rm --cached <path>` to stop, or narrow the pattern");
+}
+
+/// Print the pattern analysis report produced by --analyze
+///
+/// With `--only`, the report has the conflicts and the selected patterns when asked for, and none
+/// of the counts, unused patterns and groups, which belong to no scope.
+pub fn print_analysis(path: &Path, analysis: &GitignoreAnalysis, filter: &AnalysisFilter, verbose: bool) {
+    println!("🔍 Pattern analysis of {}:", path.display());
+    if !filter.only.is_empty() {
+        if filter.selects(AnalysisScope::Conflicts) {
+            print_analysis_conflicts(analysis);
+        }
+        if filter.selects_patterns() {
+            println!("  {} selected pattern(s):", analysis.pattern_analyses.len());
+            for pattern in &analysis.pattern_analyses {
+                println!("    {}", pattern.original);
+            }
+        }
+        return;
+    }
+    
+    println!("  Total patterns: {}", analysis.total_patterns);
+    
+    println!("  Pattern types:");
+    println!("    File patterns: {}", analysis.file_patterns);
+    println!("    Directory patterns: {}", analysis.directory_patterns);
+    println!("    File or directory patterns: {}", analysis.both_patterns);
+    
+    println!("  Pattern features:");
+    println!("    Negations: {}", analysis.negation_patterns);
+    println!("    Absolute paths: {}", analysis.absolute_patterns);
+    println!("    Wildcards: {}", analysis.wildcard_patterns);
+    println!("    Globstars: {}", analysis.globstar_patterns);
+    println!("    Case-sensitive: {}", analysis.case_sensitive_patterns);
+    println!("    Case-insensitive: {}", analysis.case_insensitive_patterns);
+    
+    print_analysis_conflicts(analysis);
+    
+    if !analysis.unused_patterns.is_empty() {
+        println!("  🕸️  {} pattern(s) match nothing in the working tree:", analysis.unused_patterns.len());
+        for unused in &analysis.unused_patterns {
+            let note = if unused.prunable { "" } else { " (kept by --prune-unused)" };
+            println!("    line {}: {}{}", unused.line_number, unused.pattern, note);
+        }
+    }
+    
+    let areas: Vec<_> = analysis.histogram.at_least(2).collect();
+    if !areas.is_empty() {
+        println!("  📊 Patterns by area:");
+        for bucket in areas {
+            println!("    {}: {} pattern(s)", bucket.key, bucket.patterns.len());
+        }
+    }
+    
+    for group in &analysis.extension_groups {
+        print_extension_group(group);
+    }
+    
+    if verbose && !analysis.pattern_analyses.is_empty() {
+        println!("\n  Patterns:");
+        for pattern in &analysis.pattern_analyses {
+            println!("    {} ({:?})", pattern.original, pattern.pattern_type);
+        }
+    }
+}
+
+/// Print the conflicts section of the analysis report
+fn print_analysis_conflicts(analysis: &GitignoreAnalysis) {
+    if analysis.has_conflicts() {
+        println!("  ⚠️  {} conflict(s):", analysis.conflict_count());
+        for (pattern, other) in &analysis.conflicts {
+            println!("    {} <-> {}", pattern, other);
+        }
+    } else {
+        println!("  ✅ No conflicts found");
+    }
+}
+
+/// Print conflicting patterns found by --detect-conflicts
+pub fn print_conflicts(conflicts: &[PatternConflict]) {
+    if conflicts.is_empty() {
+        println!("✅ No pattern conflicts found");
+        return;
+    }
+    
+    println!("⚠️  {} pattern conflict(s):", conflicts.len());
+    for conflict in conflicts {
+        println!(
+            "  line {}: {} conflicts with line {}: {}",
+            conflict.line_number, conflict.pattern, conflict.other_line, conflict.other
+        );
+    }
+}
+
+/// Print a table of pattern counts per category
+pub fn print_category_summary(summary: &CategorySummary) {
+    println!("\n🗂️  Pattern categories:");
+    if summary.total_patterns == 0 {
+        println!("  No patterns to categorize");
+        return;
+    }
+    
+    let mut rows: Vec<(String, usize)> = summary.category_counts.iter()
+        .map(|(category, count)| (category.display_name(), *count))
+        .collect();
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    
+    let width = rows.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0).max("Category".len());
+    println!("  {:<width$}  {:>8}  {:>6}", "Category", "Patterns", "Share", width = width);
+    for (name, count) in &rows {
+        let share = (*count as f64 / summary.total_patterns as f64) * 100.0;
+        println!("  {:<width$}  {:>8}  {:>5.1}%", name, count, share, width = width);
+    }
+    println!("  {:<width$}  {:>8}", "Total", summary.total_patterns, width = width);
+}
+
+/// Print the outcome of `gix dataset update`
+#[cfg(feature = "dataset")]
+pub fn print_dataset_update(update: &DatasetUpdate) {
+    match update {
+        DatasetUpdate::Updated { version, path } => {
+            println!("✅ Downloaded pattern dataset version {} to {}", version, path.display());
+        }
+        DatasetUpdate::UpToDate { version } => {
+            println!("✅ Pattern dataset is up to date (version {})", version);
+        }
+    }
+}
+
+/// Print the writes recorded in an audit log
+pub fn print_audit_records(log: &Path, records: &[AuditRecord]) {
+    if records.is_empty() {
+        println!("No writes recorded in {}", log.display());
+        return;
+    }
+    
+    for record in records {
+        let before = record.before.as_deref().unwrap_or("(new)");
+        println!(
+            "{}  {} {}  +{} -{}  {} -> {}  mode {}{}",
+            record.timestamp, record.command, record.file, record.added, record.removed, before, record.after,
+            record.mode,
+            if record.options.is_empty() { String::new() } else { format!(" {}", record.options.join(" ")) }
+        );
+    }
+}
+
+/// Print the writes `gix undo` can take back, with their changes when verbose
+pub fn print_history(history: &Path, steps: &[HistoryStep], verbose: bool) {
+    if steps.is_empty() {
+        println!("No writes recorded in {}", history.display());
+        return;
+    }
+    
+    for step in steps {
+        println!("#{}  {}  {} {}  {} change(s)", step.number, step.timestamp, step.command, step.file, step.changes.len());
+        if verbose {
+            for change in &step.changes {
+                println!("    {}", change.description);
+            }
+        }
+    }
+}
+
+/// Print the writes `gix undo` took back, newest first
+pub fn print_undone(steps: &[HistoryStep]) {
+    if steps.is_empty() {
+        println!("Nothing to undo");
+        return;
+    }
+    
+    for step in steps {
+        match step.original {
+            Some(_) => println!("↩️  Restored {} as it was before #{} ({})", step.file, step.number, step.command),
+            None => println!("↩️  Removed {}, created by #{} ({})", step.file, step.number, step.command),
+        }
+    }
+}
+
+/// Print what `gix watch` watches and what it does on each save
+pub fn print_watching(target: &Path, recursive: bool, check: bool) {
+    let action = if check { "checked" } else { "optimized" };
+    match recursive {
+        true => println!("👀 Watching every .gitignore below {}; each is {} when saved (Ctrl-C to stop)", target.display(), action),
+        false => println!("👀 Watching {}; it is {} when saved (Ctrl-C to stop)", target.display(), action),
+    }
+}
+
+/// Print the summary line for a watched file that was optimized
+pub fn print_watch_optimized(file: &str, outcome: &FileOutcome, dry_run: bool) {
+    let lines = outcome.original.entries.len();
+    match (outcome.removed(), dry_run) {
+        (0, _) => println!("[{}] {}: unchanged ({} lines)", clock_time(), file, lines),
+        (removed, true) => println!("[{}] {}: would optimize, {} → {} lines (-{})", clock_time(), file, lines, lines - removed, removed),
+        (removed, false) => println!("[{}] {}: optimized, {} → {} lines (-{})", clock_time(), file, lines, lines - removed, removed),
+    }
+}
+
+/// Print the summary line for a watched file that was checked, followed by its findings if `verbose`
+pub fn print_watch_checked(file: &str, issues: &[LintIssue], verbose: bool) {
+    match issues.len() {
+        0 => println!("[{}] {}: no issues", clock_time(), file),
+        count => println!("[{}] {}: {} issue(s)", clock_time(), file, count),
+    }
+    if verbose {
+        for issue in issues {
+            println!("  line {}: {} [{}, fix: {}]", issue.line_number, issue.message, issue.kind, issue.risk);
+        }
+    }
+}
+
+/// Print the summary line for a watched file that was removed
+pub fn print_watch_removed(file: &str) {
+    println!("[{}] {}: removed", clock_time(), file);
+}
+
+/// Print the summary line for a watched file that couldn't be processed; watching goes on
+pub fn print_watch_failed(file: &str, error: &GixError) {
+    println!("[{}] {}: failed: {}", clock_time(), file, error);
+}
+
+/// Get the current UTC time of day, as `HH:MM:SS`
+fn clock_time() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    format_timestamp(secs)[11..19].to_string()
+}
+
+/// Print the outcome of `gix audit-log verify`
+pub fn print_audit_verification(log: &Path, verification: &AuditVerification) {
+    match verification.broken_line {
+        Some(line) => println!("❌ {} was altered: record on line {} doesn't match the chain", log.display(), line),
+        None => println!("✅ {} is intact ({} record(s))", log.display(), verification.records),
+    }
+    for file in &verification.changed_since {
+        println!("⚠️  {} changed since gix last wrote it", file);
+    }
+}
+
+/// Print the patterns `gix sync-check` found in only one of the files
+pub fn print_sync_report(gitignore: &Path, dockerignore: &Path, report: &SyncReport, synced: bool) {
+    if report.is_empty() {
+        println!("✅ {} and {} agree on build outputs and secrets", gitignore.display(), dockerignore.display());
+        return;
+    }
+    
+    let verb = if synced { "Added" } else { "Missing" };
+    for (from, to, gaps) in [
+        (gitignore, dockerignore, &report.missing_in_dockerignore),
+        (dockerignore, gitignore, &report.missing_in_gitignore),
+    ] {
+        if gaps.is_empty() {
+            continue;
+        }
+        println!("{} in {} ({} pattern(s)):", verb, to.display(), gaps.len());
+        for gap in gaps {
+            println!("  {} ({}, {}:{})", gap.translated, gap.concern.name(), from.display(), gap.line_number);
+        }
+    }
+}
+
+/// Print the outcome of `gix add-template`
+pub fn print_template_added(path: &Path, name: &str, template: &RemoteTemplate, added: usize) {
+    let origin = if template.cached { "cached" } else { "downloaded" };
+    match added {
+        0 => println!("✅ {} already has every pattern of the {} template ({})", path.display(), name, origin),
+        _ => println!("✅ Added {} pattern(s) from the {} template ({}) to {}", added, name, origin, path.display()),
+    }
+}
+
+/// Print which pattern dataset is active
+#[cfg(feature = "dataset")]
+pub fn print_dataset_status(dataset: &Dataset) {
+    match &dataset.source {
+        DatasetSource::Embedded => println!("📚 Using embedded pattern dataset version {}", dataset.version),
+        DatasetSource::Cached(path) => println!("📚 Using downloaded pattern dataset version {} from {}", dataset.version, path.display()),
+    }
+    println!(
+        "  {} languages, {} frameworks, {} tools, {} operating systems, {} pattern descriptions",
+        dataset.language_patterns.len(),
+        dataset.framework_patterns.len(),
+        dataset.tool_patterns.len(),
+        dataset.os_patterns.len(),
+        dataset.pattern_comments.len()
+    );
+    if let Some(path) = &dataset.definitions {
+        println!("  with category definitions from {}", path.display());
+    }
+}
+
 /// Print error messages to the user
 pub fn print_error(error: &GixError) {
     eprintln!("❌ Error: {}", error);
@@ -81,9 +929,105 @@ pub fn print_success(path: &Path) {
     println!("✅ Successfully optimized {}", path.display());
 }
 
+/// Report the file created by --create-if-missing
+pub fn print_created_empty(path: &Path, dry_run: bool) {
+    match dry_run {
+        true => println!("DRY RUN - would create empty {}", path.display()),
+        false => println!("✅ Created empty {}", path.display()),
+    }
+    println!("💡 Add patterns, or run `gix init --like <repo>` to start from another repository");
+}
+
+/// Explain that an existing file has nothing to optimize
+pub fn print_empty_file_hint(path: &Path) {
+    println!("ℹ️  {} has no patterns or comments; nothing to optimize", path.display());
+    println!("💡 Run `gix init --like <repo>` to start from another repository");
+}
+
+/// Warn that a dry run would leave a file without patterns
+pub fn print_would_empty(path: &Path) {
+    println!("⚠️  Optimizing would leave {} without patterns; consider deleting it instead (or pass --allow-empty)", path.display());
+}
+
+/// Ask a yes/no question on stdin; anything but `y` or `yes` is no
+pub fn confirm(question: &str) -> bool {
+    print!("❓ {} [y/N] ", question);
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).is_ok() && matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+/// Introduce the ignore layer processed next by --scope
+pub fn print_scope_header(scope: &str, path: &Path) {
+    println!("── {}: {}", scope, path.display());
+}
+
+/// Report a selected ignore layer that has no file
+pub fn print_scope_missing(scope: &str) {
+    println!("── {}: no file found, skipping", scope);
+}
+
+/// Warn that the repository's plugins were not run
+pub fn print_plugins_skipped(commands: &[PathBuf]) {
+    let names: Vec<String> = commands.iter().map(|command| command.display().to_string()).collect();
+    eprintln!("⚠️  Not running plugins from gix.toml ({}); pass --allow-plugins if you trust this repository", names.join(", "));
+}
+
+/// Warn that the output path is ignored by the file being optimized
+pub fn print_output_ignored_warning(path: &Path, input: &Path) {
+    println!(
+        "⚠️  Output {} is ignored by {}; check the destination for typos (use --no-output-checks to silence)",
+        path.display(),
+        input.display()
+    );
+}
+
+/// Print organize success message
+pub fn print_organized(path: &Path, sections: usize) {
+    println!("✅ Organized {} into {} section(s)", path.display(), sections);
+}
+
+/// Print the outcome of `gix merge`
+pub fn print_merged(path: &Path, sources: usize, merged: &MergedGitignore, verbose: bool) {
+    for negation in &merged.reordered {
+        println!(
+            "↕️  Moved {} ({}:{}) to the end so '{}' from {} doesn't override it",
+            negation.pattern, negation.source, negation.line_number, negation.overridden_by, negation.overridden_source
+        );
+    }
+    if verbose {
+        for duplicate in &merged.duplicates {
+            println!("  dropped {}:{}: {} (already in {})", duplicate.source, duplicate.line_number, duplicate.pattern, duplicate.kept_from);
+        }
+    }
+    println!(
+        "✅ Merged {} file(s) into {}: {} pattern(s), {} duplicate(s) dropped",
+        sources, path.display(), merged.file.stats.pattern_lines, merged.duplicates.len()
+    );
+}
+
+/// Print where the repository summary went
+pub fn print_summary_written(path: &Path, written: bool) {
+    if written {
+        println!("📝 Wrote repository summary to {}", path.display());
+    } else {
+        println!("✅ Repository summary {} is up to date", path.display());
+    }
+}
+
+/// Print that a fleet report was written
+pub fn print_fleet_report_written(path: &Path, repositories: usize) {
+    println!("📝 Wrote fleet report for {} repositories to {}", repositories, path.display());
+}
+
+/// Print removals log message
+pub fn print_removals_log(path: &Path, count: usize) {
+    println!("📝 Logged {} removed pattern(s) to {}", count, path.display());
+}
+
 /// Print backup message
-pub fn print_backup(path: &Path) {
-    println!("💾 Created backup: {}", path.with_extension("backup").display());
+pub fn print_backup(backup: &Path) {
+    println!("💾 Created backup: {}", backup.display());
 }
 
 /// Print mode information
@@ -92,7 +1036,7 @@ pub fn print_mode(mode: &OptimizationMode) {
         OptimizationMode::Standard => println!("🔧 Using standard optimization mode"),
         OptimizationMode::Aggressive => println!("⚡ Using aggressive optimization mode"),
         OptimizationMode::Conservative => println!("🛡️ Using conservative optimization mode"),
-        OptimizationMode::Advanced => println!("🚀 Using advanced optimization mode with pattern analysis"),
+        OptimizationMode::Advanced => println!("🚀 Using advanced optimization mode (pattern analysis and redundancy removal)"),
     }
 }
 
@@ -127,6 +1071,26 @@ mod tests {
         print_statistics(&original, &optimized);
     }
 
+    #[test]
+    fn test_print_analysis() {
+        let file = crate::core::parse_gitignore("*.log\n!important.log\nbuild/").unwrap();
+        let analysis = crate::core::optimizer::analyze_gitignore(&file).unwrap();
+        // This test just ensures the function doesn't panic
+        print_analysis(Path::new(".gitignore"), &analysis, &AnalysisFilter::default(), true);
+    }
+
+    #[test]
+    fn test_print_conflicts_and_categories() {
+        let file = crate::core::parse_gitignore("*.log\n!debug.log\n.DS_Store").unwrap();
+        // These tests just ensure the functions don't panic
+        print_conflicts(&crate::core::find_pattern_conflicts(&file));
+        print_conflicts(&[]);
+        
+        let patterns: Vec<String> = vec!["*.log".to_string(), ".DS_Store".to_string()];
+        print_category_summary(&crate::core::PatternCategorizer::new().get_category_summary(&patterns));
+        print_category_summary(&CategorySummary::new());
+    }
+
     #[test]
     fn test_print_error() {
         let error = GixError::FileNotFound("test.gitignore".to_string());
@@ -0,0 +1,507 @@
+use std::path::Path;
+use crate::core::lint::{LintIssue, RelatedLocation};
+use crate::core::rules::Severity;
+use crate::core::optimizer::{AnalysisFilter, AnalysisScope, GitignoreAnalysis};
+use crate::core::risk::ChangeRisk;
+use crate::core::whitespace::WhitespaceViolation;
+pub use crate::models::ReportFormat;
+use crate::models::Span;
+use crate::utils::json::json_string;
+use crate::utils::stable_hash;
+
+/// A finding rendered by every report format
+struct Annotation {
+    line_number: usize,
+    rule: String,
+    /// What the rule reports, for formats that describe their rules
+    summary: &'static str,
+    message: String,
+    /// Identifier of the finding within its file
+    id: String,
+    /// Identifier that CI platforms use to track the finding across runs, unique across files
+    fingerprint: String,
+    /// Stable identifier of the entry the finding is about, if it is about a pattern
+    entry_id: Option<String>,
+    /// Risk of the change that fixes the finding, if gix knows one
+    risk: Option<ChangeRisk>,
+    /// Characters the finding covers, if it is about part of a line
+    span: Option<Span>,
+    /// The other line involved in the finding, if any
+    related: Option<RelatedLocation>,
+    /// Code of the rule that reported the finding, for `gix check` findings
+    code: Option<&'static str>,
+    /// Severity the finding's rule has, for `gix check` findings
+    severity: Option<Severity>,
+}
+
+/// Render whitespace violations for a CI platform; `None` for plain text output
+pub fn render_whitespace_violations(format: ReportFormat, path: &Path, violations: &[WhitespaceViolation]) -> Option<String> {
+    let path = path.display().to_string();
+    let annotations: Vec<Annotation> = violations.iter()
+        .map(|violation| Annotation {
+            line_number: violation.line_number,
+            rule: violation.rule.to_string(),
+            summary: violation.rule.summary(),
+            message: violation.message.clone(),
+            id: fingerprint(&path, violation),
+            fingerprint: fingerprint(&path, violation),
+            entry_id: None,
+            risk: None,
+            span: None,
+            related: None,
+            code: None,
+            severity: None,
+        })
+        .collect();
+    render(format, &path, "whitespace violation(s)", &annotations)
+}
+
+/// Render `gix check` issues for a CI platform or as JSON; `None` for plain text output
+pub fn render_lint_issues(format: ReportFormat, path: &Path, issues: &[LintIssue]) -> Option<String> {
+    let path = path.display().to_string();
+    let annotations: Vec<Annotation> = issues.iter()
+        .map(|issue| Annotation {
+            line_number: issue.line_number,
+            rule: issue.kind.to_string(),
+            summary: issue.kind.rule().summary,
+            message: issue.message.clone(),
+            // Issue ids don't depend on line numbers, so findings keep their identity when lines move
+            id: issue.id.clone(),
+            fingerprint: stable_hash(&format!("{}:{}", path, issue.id)),
+            entry_id: Some(issue.entry_id.clone()),
+            risk: Some(issue.risk),
+            span: Some(issue.span),
+            related: issue.related.clone(),
+            code: Some(issue.kind.code()),
+            severity: Some(issue.severity),
+        })
+        .collect();
+    render(format, &path, "issue(s)", &annotations)
+}
+
+/// Render the analysis of `--analyze` as a JSON document
+///
+/// With `--only`, the document has the conflicts and the selected patterns when asked for, like
+/// the text report.
+pub fn render_analysis(path: &Path, analysis: &GitignoreAnalysis, filter: &AnalysisFilter) -> String {
+    let conflicts: Vec<String> = analysis.conflicts.iter()
+        .map(|(pattern, other)| format!("    [{}, {}]", json_string(pattern), json_string(other)))
+        .collect();
+    let list = |items: &[String]| if items.is_empty() { "[]".to_string() } else { format!("[\n{}\n  ]", items.join(",\n")) };
+    if !filter.only.is_empty() {
+        let mut fields = vec![format!("  \"path\": {}", json_string(&path.display().to_string()))];
+        if filter.selects(AnalysisScope::Conflicts) {
+            fields.push(format!("  \"conflicts\": {}", list(&conflicts)));
+        }
+        if filter.selects_patterns() {
+            let patterns: Vec<String> = analysis.pattern_analyses.iter()
+                .map(|pattern| format!("    {}", json_string(&pattern.original)))
+                .collect();
+            fields.push(format!("  \"patterns\": {}", list(&patterns)));
+        }
+        return format!("{{\n{}\n}}\n", fields.join(",\n"));
+    }
+    
+    let unused: Vec<String> = analysis.unused_patterns.iter()
+        .map(|unused| format!(
+            "    {{\"line\": {}, \"pattern\": {}, \"prunable\": {}}}",
+            unused.line_number,
+            json_string(&unused.pattern),
+            unused.prunable
+        ))
+        .collect();
+    let histogram: Vec<String> = analysis.histogram.buckets.iter()
+        .map(|bucket| format!(
+            "    {{\"bucket\": {}, \"kind\": \"{}\", \"count\": {}, \"patterns\": [{}]}}",
+            json_string(&bucket.key.collapsed_pattern()),
+            bucket.key.kind(),
+            bucket.patterns.len(),
+            bucket.patterns.iter().map(|pattern| json_string(pattern)).collect::<Vec<_>>().join(", ")
+        ))
+        .collect();
+    let extension_groups: Vec<String> = analysis.extension_groups.iter()
+        .map(|group| format!(
+            "    {{\"family\": {}, \"lines\": [{}], \"patterns\": [{}], \"comment\": {}}}",
+            json_string(&group.family),
+            group.line_numbers.iter().map(usize::to_string).collect::<Vec<_>>().join(", "),
+            group.patterns.iter().map(|pattern| json_string(pattern)).collect::<Vec<_>>().join(", "),
+            json_string(&group.comment())
+        ))
+        .collect();
+
+    format!(
+        "{{\n  \"path\": {},\n  \"total_patterns\": {},\n  \"types\": {{\"file\": {}, \"directory\": {}, \"both\": {}}},\n  \"features\": {{\"negations\": {}, \"absolute\": {}, \"wildcards\": {}, \"globstars\": {}, \"case_sensitive\": {}, \"case_insensitive\": {}}},\n  \"conflicts\": {},\n  \"unused\": {},\n  \"histogram\": {},\n  \"extension_groups\": {}\n}}\n",
+        json_string(&path.display().to_string()),
+        analysis.total_patterns,
+        analysis.file_patterns,
+        analysis.directory_patterns,
+        analysis.both_patterns,
+        analysis.negation_patterns,
+        analysis.absolute_patterns,
+        analysis.wildcard_patterns,
+        analysis.globstar_patterns,
+        analysis.case_sensitive_patterns,
+        analysis.case_insensitive_patterns,
+        list(&conflicts),
+        list(&unused),
+        list(&histogram),
+        list(&extension_groups)
+    )
+}
+
+fn render(format: ReportFormat, path: &str, noun: &str, annotations: &[Annotation]) -> Option<String> {
+    match format {
+        ReportFormat::Text => None,
+        ReportFormat::Json => Some(render_json(path, annotations)),
+        ReportFormat::Github => Some(render_github(path, annotations)),
+        ReportFormat::Gitlab => Some(render_gitlab(path, annotations)),
+        ReportFormat::Bitbucket => Some(render_bitbucket(path, noun, annotations)),
+        ReportFormat::Sarif => Some(render_sarif(path, annotations)),
+    }
+}
+
+fn render_json(path: &str, annotations: &[Annotation]) -> String {
+    let findings: Vec<String> = annotations.iter()
+        .map(|annotation| format!(
+            "  {{\"id\": \"{}\", \"entry_id\": {}, \"path\": {}, \"line\": {}, \"rule\": {}, \"risk\": {}, \"code\": {}, \"severity\": {}, \"message\": {}, \"columns\": {}, \"related\": {}}}",
+            annotation.id,
+            annotation.entry_id.as_deref().map(json_string).unwrap_or_else(|| "null".to_string()),
+            json_string(path),
+            annotation.line_number,
+            json_string(&annotation.rule),
+            annotation.risk.map(|risk| json_string(risk.name())).unwrap_or_else(|| "null".to_string()),
+            annotation.code.map(json_string).unwrap_or_else(|| "null".to_string()),
+            annotation.severity.map(|severity| json_string(severity.name())).unwrap_or_else(|| "null".to_string()),
+            json_string(&annotation.message),
+            annotation.span.map(json_columns).unwrap_or_else(|| "null".to_string()),
+            annotation.related.as_ref()
+                .map(|related| format!(
+                    "{{\"line\": {}, \"columns\": {}, \"message\": {}}}",
+                    related.span.line,
+                    json_columns(related.span),
+                    json_string(&related.message)
+                ))
+                .unwrap_or_else(|| "null".to_string())
+        ))
+        .collect();
+
+    if findings.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", findings.join(",\n"))
+    }
+}
+
+fn render_github(path: &str, annotations: &[Annotation]) -> String {
+    annotations.iter()
+        .map(|annotation| format!(
+            "::warning file={},line={}{},title=gix {}{}::{}\n",
+            path,
+            annotation.line_number,
+            annotation.span.map(|span| format!(",col={},endColumn={}", span.start_column, span.end_column)).unwrap_or_default(),
+            annotation.rule,
+            risk_suffix(annotation.risk),
+            annotation.message
+        ))
+        .collect()
+}
+
+fn render_gitlab(path: &str, annotations: &[Annotation]) -> String {
+    let issues: Vec<String> = annotations.iter()
+        .map(|annotation| format!(
+            "  {{\"description\": {}, \"check_name\": {}, \"fingerprint\": \"{}\", \"severity\": \"{}\", \"location\": {{\"path\": {}, \"lines\": {{\"begin\": {}}}}}}}",
+            json_string(&format!("{}{}", annotation.message, risk_suffix(annotation.risk))),
+            json_string(&format!("gix/{}", annotation.rule)),
+            annotation.fingerprint,
+            match annotation.severity {
+                Some(Severity::Deny) => "major",
+                Some(Severity::Warn) | None => "minor",
+                Some(Severity::Allow) => "info",
+            },
+            json_string(path),
+            annotation.line_number
+        ))
+        .collect();
+
+    if issues.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", issues.join(",\n"))
+    }
+}
+
+fn render_bitbucket(path: &str, noun: &str, annotations: &[Annotation]) -> String {
+    let result = if annotations.is_empty() { "PASSED" } else { "FAILED" };
+    let items: Vec<String> = annotations.iter()
+        .map(|annotation| format!(
+            "    {{\"external_id\": \"{}\", \"annotation_type\": \"CODE_SMELL\", \"summary\": {}, \"severity\": \"{}\", \"path\": {}, \"line\": {}}}",
+            annotation.fingerprint,
+            json_string(&format!("{} [{}]{}", annotation.message, annotation.rule, risk_suffix(annotation.risk))),
+            match annotation.severity {
+                Some(Severity::Deny) => "HIGH",
+                Some(Severity::Warn) | None => "MEDIUM",
+                Some(Severity::Allow) => "LOW",
+            },
+            json_string(path),
+            annotation.line_number
+        ))
+        .collect();
+
+    format!(
+        "{{\n  \"report\": {{\"title\": \"gix check\", \"report_type\": \"TEST\", \"reporter\": \"gix\", \"result\": \"{}\", \"details\": {}}},\n  \"annotations\": [{}]\n}}\n",
+        result,
+        json_string(&format!("{} {} in {}", annotations.len(), noun, path)),
+        if items.is_empty() { String::new() } else { format!("\n{}\n  ", items.join(",\n")) }
+    )
+}
+
+fn render_sarif(path: &str, annotations: &[Annotation]) -> String {
+    // Rules are identified by their code where they have one, as `gix rules` lists them
+    let rule_id = |annotation: &Annotation| annotation.code.map_or_else(|| annotation.rule.clone(), str::to_string);
+    let mut described: Vec<&Annotation> = Vec::new();
+    for annotation in annotations {
+        if !described.iter().any(|known| rule_id(known) == rule_id(annotation)) {
+            described.push(annotation);
+        }
+    }
+    let rules: Vec<String> = described.iter()
+        .map(|annotation| format!(
+            "          {{\"id\": {}, \"name\": {}, \"shortDescription\": {{\"text\": {}}}, \"defaultConfiguration\": {{\"level\": \"{}\"}}}}",
+            json_string(&rule_id(annotation)),
+            json_string(&annotation.rule),
+            json_string(annotation.summary),
+            sarif_level(annotation.severity)
+        ))
+        .collect();
+
+    let uri = json_string(&path.replace('\\', "/"));
+    let region = |line: usize, span: Option<Span>| match span {
+        Some(span) => format!("{{\"startLine\": {}, \"startColumn\": {}, \"endColumn\": {}}}", line, span.start_column, span.end_column),
+        None => format!("{{\"startLine\": {}}}", line),
+    };
+    let results: Vec<String> = annotations.iter()
+        .map(|annotation| format!(
+            "        {{\"ruleId\": {}, \"level\": \"{}\", \"message\": {{\"text\": {}}}, \"locations\": [{{\"physicalLocation\": {{\"artifactLocation\": {{\"uri\": {}}}, \"region\": {}}}}}], \"partialFingerprints\": {{\"gix/v1\": \"{}\"}}{}}}",
+            json_string(&rule_id(annotation)),
+            sarif_level(annotation.severity),
+            json_string(&format!("{}{}", annotation.message, risk_suffix(annotation.risk))),
+            uri,
+            region(annotation.line_number, annotation.span),
+            annotation.fingerprint,
+            annotation.related.as_ref()
+                .map(|related| format!(
+                    ", \"relatedLocations\": [{{\"id\": 1, \"physicalLocation\": {{\"artifactLocation\": {{\"uri\": {}}}, \"region\": {}}}, \"message\": {{\"text\": {}}}}}]",
+                    uri,
+                    region(related.span.line, Some(related.span)),
+                    json_string(&related.message)
+                ))
+                .unwrap_or_default()
+        ))
+        .collect();
+    let list = |items: &[String], indent: &str| if items.is_empty() { "[]".to_string() } else { format!("[\n{}\n{}]", items.join(",\n"), indent) };
+
+    format!(
+        "{{\n  \"$schema\": \"https://json.schemastore.org/sarif-2.1.0.json\",\n  \"version\": \"2.1.0\",\n  \"runs\": [\n    {{\n      \"tool\": {{\"driver\": {{\"name\": \"gix\", \"version\": \"{}\", \"rules\": {}}}}},\n      \"results\": {}\n    }}\n  ]\n}}\n",
+        env!("CARGO_PKG_VERSION"),
+        list(&rules, "        "),
+        list(&results, "      ")
+    )
+}
+
+/// Get the SARIF level of findings with this severity; findings without one are warnings
+fn sarif_level(severity: Option<Severity>) -> &'static str {
+    match severity {
+        Some(Severity::Deny) => "error",
+        Some(Severity::Warn) | None => "warning",
+        Some(Severity::Allow) => "note",
+    }
+}
+
+/// Render the columns of a span as `{"start": .., "end": ..}`, the end exclusive
+fn json_columns(span: Span) -> String {
+    format!("{{\"start\": {}, \"end\": {}}}", span.start_column, span.end_column)
+}
+
+/// Describe the risk of fixing a finding, e.g. ` (fix: safe)`, or nothing if it is unknown
+fn risk_suffix(risk: Option<ChangeRisk>) -> String {
+    risk.map(|risk| format!(" (fix: {})", risk)).unwrap_or_default()
+}
+
+/// Stable identifier for a violation, so CI platforms can track it across runs
+fn fingerprint(path: &str, violation: &WhitespaceViolation) -> String {
+    stable_hash(&format!("{}:{}:{}", path, violation.line_number, violation.rule))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rules::RuleSeverities;
+    use crate::core::whitespace::WhitespaceRule;
+
+    fn violations() -> Vec<WhitespaceViolation> {
+        vec![WhitespaceViolation {
+            line_number: 3,
+            rule: WhitespaceRule::TrailingSpaces,
+            message: "1 trailing whitespace character(s)".to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_text_format_renders_nothing() {
+        assert!(render_whitespace_violations(ReportFormat::Text, Path::new(".gitignore"), &violations()).is_none());
+    }
+
+    #[test]
+    fn test_github_annotations() {
+        let output = render_whitespace_violations(ReportFormat::Github, Path::new(".gitignore"), &violations()).unwrap();
+        assert_eq!(output, "::warning file=.gitignore,line=3,title=gix trailing-spaces::1 trailing whitespace character(s)\n");
+    }
+
+    #[test]
+    fn test_gitlab_code_quality() {
+        let output = render_whitespace_violations(ReportFormat::Gitlab, Path::new(".gitignore"), &violations()).unwrap();
+        assert!(output.starts_with("[\n  {\"description\": \"1 trailing whitespace character(s)\""));
+        assert!(output.contains("\"check_name\": \"gix/trailing-spaces\""));
+        assert!(output.contains("\"location\": {\"path\": \".gitignore\", \"lines\": {\"begin\": 3}}"));
+
+        let empty = render_whitespace_violations(ReportFormat::Gitlab, Path::new(".gitignore"), &[]).unwrap();
+        assert_eq!(empty, "[]\n");
+    }
+
+    #[test]
+    fn test_bitbucket_code_insights() {
+        let output = render_whitespace_violations(ReportFormat::Bitbucket, Path::new(".gitignore"), &violations()).unwrap();
+        assert!(output.contains("\"result\": \"FAILED\""));
+        assert!(output.contains("\"annotation_type\": \"CODE_SMELL\""));
+        assert!(output.contains("\"path\": \".gitignore\", \"line\": 3"));
+    }
+
+    #[test]
+    fn test_json_findings() {
+        let output = render_whitespace_violations(ReportFormat::Json, Path::new(".gitignore"), &violations()).unwrap();
+        assert!(output.starts_with("[\n  {\"id\": \""));
+        assert!(output.contains("\"entry_id\": null, \"path\": \".gitignore\", \"line\": 3, \"rule\": \"trailing-spaces\", \"risk\": null"));
+        assert!(output.contains("\"columns\": null, \"related\": null}"));
+    }
+
+    #[test]
+    fn test_lint_issue_ids_survive_line_shifts() {
+        let render_ids = |content: &str| {
+            let file = crate::core::parse_gitignore(content).unwrap();
+            let issues = crate::core::lint_gitignore(&file).unwrap();
+            let output = render_lint_issues(ReportFormat::Json, Path::new(".gitignore"), &issues).unwrap();
+            output.lines()
+                .filter_map(|line| line.split("\"id\": \"").nth(1))
+                .map(|rest| rest[..16].to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let before = render_ids("*.log\n*.log");
+        assert_eq!(before.len(), 1);
+        assert_eq!(render_ids("build/\n\n*.log\n*.log"), before);
+    }
+
+    #[test]
+    fn test_lint_issues_carry_fix_risk() {
+        let file = crate::core::parse_gitignore("*.log\n*.log\n!*.log").unwrap();
+        let issues = crate::core::lint_gitignore(&file).unwrap();
+        
+        let json = render_lint_issues(ReportFormat::Json, Path::new(".gitignore"), &issues).unwrap();
+        assert!(json.contains("\"rule\": \"duplicate\", \"risk\": \"safe\""));
+        assert!(json.contains("\"risk\": \"safe\", \"code\": \"GIX001\", \"severity\": \"deny\""));
+        assert!(json.contains("\"rule\": \"conflict\", \"risk\": \"behavior-may-change\""));
+        
+        let github = render_lint_issues(ReportFormat::Github, Path::new(".gitignore"), &issues).unwrap();
+        assert!(github.contains("title=gix duplicate (fix: safe)::"));
+        let gitlab = render_lint_issues(ReportFormat::Gitlab, Path::new(".gitignore"), &issues).unwrap();
+        assert!(gitlab.contains("\"severity\": \"major\""));
+    }
+
+    #[test]
+    fn test_ci_severities_follow_rule_severities() {
+        let file = crate::core::parse_gitignore("*.log\n*.log\n!*.log").unwrap();
+        let mut severities = RuleSeverities::default();
+        severities.set("GIX001", Severity::Warn).unwrap();
+        let issues = severities.apply(crate::core::lint_gitignore(&file).unwrap());
+
+        let gitlab = render_lint_issues(ReportFormat::Gitlab, Path::new(".gitignore"), &issues).unwrap();
+        assert!(gitlab.contains("\"check_name\": \"gix/duplicate\", \"fingerprint\""));
+        assert_eq!(gitlab.matches("\"severity\": \"minor\"").count(), 1);
+        assert_eq!(gitlab.matches("\"severity\": \"major\"").count(), 2);
+        let bitbucket = render_lint_issues(ReportFormat::Bitbucket, Path::new(".gitignore"), &issues).unwrap();
+        assert_eq!(bitbucket.matches("\"severity\": \"MEDIUM\"").count(), 1);
+        assert_eq!(bitbucket.matches("\"severity\": \"HIGH\"").count(), 2);
+    }
+
+    #[test]
+    fn test_lint_issues_carry_spans_and_related_lines() {
+        let file = crate::core::parse_gitignore("*.log\n*.log  # again").unwrap();
+        let issues = crate::core::lint_gitignore(&file).unwrap();
+
+        let json = render_lint_issues(ReportFormat::Json, Path::new(".gitignore"), &issues).unwrap();
+        assert!(json.contains(concat!(
+            "\"columns\": {\"start\": 1, \"end\": 6}, ",
+            "\"related\": {\"line\": 1, \"columns\": {\"start\": 1, \"end\": 6}, \"message\": \"first occurrence of '*.log'\"}}"
+        )));
+        let github = render_lint_issues(ReportFormat::Github, Path::new(".gitignore"), &issues).unwrap();
+        assert!(github.starts_with("::warning file=.gitignore,line=2,col=1,endColumn=6,title=gix duplicate"));
+    }
+
+    #[test]
+    fn test_sarif_log() {
+        let file = crate::core::parse_gitignore("*.log\n*.log\n!*.log").unwrap();
+        let mut severities = RuleSeverities::default();
+        severities.set("conflict", Severity::Warn).unwrap();
+        let issues = severities.apply(crate::core::lint_gitignore(&file).unwrap());
+        let sarif = render_lint_issues(ReportFormat::Sarif, Path::new("app/.gitignore"), &issues).unwrap();
+
+        let document = crate::utils::json::parse_json(&sarif).unwrap();
+        assert_eq!(document.get("version").and_then(|version| version.as_str()), Some("2.1.0"));
+        let run = &document.get("runs").and_then(|runs| runs.as_array()).unwrap()[0];
+        let rules = run.get("tool").and_then(|tool| tool.get("driver")).and_then(|driver| driver.get("rules")).and_then(|rules| rules.as_array()).unwrap();
+        assert_eq!(rules.iter().filter_map(|rule| rule.get("id")?.as_str()).collect::<Vec<_>>(), vec!["GIX001", "GIX003"]);
+        assert!(sarif.contains(concat!(
+            "{\"id\": \"GIX003\", \"name\": \"conflict\", ",
+            "\"shortDescription\": {\"text\": \"a pattern of opposite polarity matches the same paths\"}, ",
+            "\"defaultConfiguration\": {\"level\": \"warning\"}}"
+        )));
+
+        let results = run.get("results").and_then(|results| results.as_array()).unwrap();
+        assert_eq!(results.len(), issues.len());
+        assert!(sarif.contains("\"ruleId\": \"GIX001\", \"level\": \"error\""));
+        assert!(sarif.contains("\"ruleId\": \"GIX003\", \"level\": \"warning\""));
+        assert!(sarif.contains("\"artifactLocation\": {\"uri\": \"app/.gitignore\"}, \"region\": {\"startLine\": 2, \"startColumn\": 1, \"endColumn\": 6}"));
+        assert!(sarif.contains("\"relatedLocations\": [{\"id\": 1,"));
+
+        let empty = render_whitespace_violations(ReportFormat::Sarif, Path::new(".gitignore"), &[]).unwrap();
+        assert!(empty.contains("\"rules\": []") && empty.contains("\"results\": []"));
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        let violation = &violations()[0];
+        assert_eq!(fingerprint(".gitignore", violation), fingerprint(".gitignore", violation));
+        assert_ne!(fingerprint(".gitignore", violation), fingerprint("other/.gitignore", violation));
+    }
+
+    #[test]
+    fn test_render_analysis() {
+        use crate::core::optimizer::analyze_gitignore;
+        use crate::utils::json::{parse_json, JsonValue};
+
+        let file = crate::core::parser::parse_gitignore("docs/a.md\ndocs/b.md\n*.log\n!debug.log\n").unwrap();
+        let json = parse_json(&render_analysis(Path::new(".gitignore"), &analyze_gitignore(&file).unwrap(), &AnalysisFilter::default())).unwrap();
+        assert_eq!(json.get("total_patterns").and_then(JsonValue::as_usize), Some(4));
+        assert_eq!(json.get("features").and_then(|features| features.get("negations")).and_then(JsonValue::as_usize), Some(1));
+
+        let histogram = json.get("histogram").and_then(JsonValue::as_array).unwrap();
+        assert_eq!(histogram[0].get("bucket").and_then(JsonValue::as_str), Some("docs/"));
+        assert_eq!(histogram[0].get("kind").and_then(JsonValue::as_str), Some("directory"));
+        assert_eq!(histogram[0].get("count").and_then(JsonValue::as_usize), Some(2));
+    }
+
+    #[test]
+    fn test_json_string_escapes() {
+        assert_eq!(json_string("a \"b\"\\\n"), "\"a \\\"b\\\"\\\\\\n\"");
+    }
+}
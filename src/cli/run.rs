@@ -0,0 +1,1550 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::{
+    cli::{
+        args::{Args, EmptyFilePolicy, OptimizationMode, Scope},
+        commands::{run_command, run_init},
+        output::{
+            confirm, print_analysis, print_backup, print_category_summary, print_conflicts,
+            print_created_empty, print_diff, print_empty_file_hint, print_folded_patterns,
+            print_kept_for_safety, print_mode, print_moved_negations, print_optimization_report,
+            print_output_ignored_warning, print_pass_stats, print_plan_written,
+            print_plugins_skipped, print_pruned_patterns, print_recursive_results,
+            print_removals_log, print_results, print_risk_skipped, print_scope_header,
+            print_scope_missing, print_success, print_whitespace_violations, print_would_empty,
+        },
+        report::{render_analysis, render_whitespace_violations, ReportFormat},
+    },
+    core::{
+        blocks::repeated_block_lines,
+        check_whitespace, collect_removed_entries, decision_changes, find_pattern_conflicts,
+        find_repeated_blocks, find_unused_patterns, log_write, negation_probes,
+        normalize_whitespace,
+        optimizer::{analyze_gitignore, AnalysisFilter, GitignoreAnalysis, PassStats},
+        parse_gitignore, parse_ignore_file, protected_lines, prune_unused_patterns, record_step,
+        remove_cross_layer_duplicates, render_removals_log, restore_kept_patterns, ChangeRisk,
+        Dialect, OptimizationReport, Plan, PlannedFile, UnusedPattern,
+    },
+    engine::{BatchOptions, FileOutcome, FileResult, GixEngine, OptimizedFile},
+    models::{EntryType, GitignoreFile, GixError, WriteOptions},
+    utils::{
+        create_backup_in,
+        diff::{unified_diff, DEFAULT_CONTEXT},
+        git::{exclude_file, global_excludes_file, repository_root},
+        is_ignored, path_relative_to, read_gitignore_file,
+        walker::{find_files, list_paths, WalkedFile},
+        write_gitignore_file,
+    },
+};
+
+/// Run gix, telling whether optimization changed a file (or, on a dry run, would change one)
+pub fn run(args: Args) -> Result<bool, GixError> {
+    if !args.skipped_plugins().is_empty() {
+        print_plugins_skipped(args.skipped_plugins());
+    }
+    let engine = args.engine();
+    if let Some(command) = &args.command {
+        return run_command(command, &args, &engine).map(|()| false);
+    }
+    
+    if args.recursive {
+        return emitting_plan(&args, |plan| run_recursive(&args, &engine, plan));
+    }
+    
+    // Print mode information
+    if args.verbose {
+        print_mode(&args.mode);
+    }
+    
+    let scopes = args.scopes();
+    if scopes == [Scope::Repo] {
+        return emitting_plan(&args, |plan| optimize_file(&args, &engine, args.input_file(), args.output_file(), &[], &[], plan));
+    }
+    emitting_plan(&args, |plan| run_scopes(&args, &engine, &scopes, plan))
+}
+
+/// Run an optimization, collecting its edits into the plan written to --emit-plan when one is asked for
+fn emitting_plan(args: &Args, optimize: impl FnOnce(Option<&mut Plan>) -> Result<bool, GixError>) -> Result<bool, GixError> {
+    let Some(plan_path) = &args.emit_plan else {
+        return optimize(None);
+    };
+    let mut plan = Plan::default();
+    let changed = optimize(Some(&mut plan))?;
+    write_gitignore_file(plan_path, &plan.to_json())?;
+    print_plan_written(plan_path, &plan);
+    Ok(changed)
+}
+
+/// Optimize one ignore file, dropping patterns that the `higher` and `lower` layers processed with it provide
+///
+/// With a `plan`, the edits are added to it and nothing is written.
+fn optimize_file(args: &Args, engine: &GixEngine, input_path: PathBuf, output_path: PathBuf, higher: &[String], lower: &[String], plan: Option<&mut Plan>) -> Result<bool, GixError> {
+    // A missing file is only created on request
+    if args.create_if_missing && !input_path.exists() {
+        if let Some(like) = &args.like {
+            return run_init(args, engine, Some(like), &input_path, args.dry_run, false).map(|()| true);
+        }
+        let dry_run = args.dry_run || plan.is_some();
+        if !dry_run {
+            write_ignore_file(args, "create", &input_path, "")?;
+        }
+        print_created_empty(&input_path, dry_run);
+        return Ok(true);
+    }
+    
+    // Read the .gitignore file
+    let content = read_gitignore_file(&input_path)?;
+    
+    // Parse the file in its dialect, from --dialect or its name
+    let dialect = args.dialect_of(&input_path);
+    let original_file = parse_ignore_file(&content, dialect)?;
+    
+    // Check mode never modifies the file
+    if args.check {
+        let violations = check_whitespace(&original_file, &args.whitespace_policy());
+        match render_whitespace_violations(args.format, &input_path, &violations) {
+            Some(report) => print!("{}", report),
+            None => print_whitespace_violations(&input_path, &violations),
+        }
+        if !violations.is_empty() {
+            return Err(GixError::CheckFailed(violations.len()));
+        }
+        return Ok(false);
+    }
+    
+    // A JSON analysis is a report on its own, so the file is left as it is
+    if args.analyze && args.format == ReportFormat::Json {
+        let tree_paths = if args.scopes() == [Scope::Repo] && dialect.matches_like_gitignore() {
+            Some(list_paths(base_dir(&input_path), &args.walk_options())?)
+        } else {
+            None
+        };
+        let filter = args.analysis_filter();
+        print!("{}", render_analysis(&input_path, &analysis_of(&original_file, tree_paths.as_deref(), &filter)?, &filter));
+        return Ok(false);
+    }
+    
+    // A file without patterns or comments has nothing to optimize
+    if original_file.stats.pattern_lines == 0 && original_file.stats.comment_lines == 0 {
+        return match args.on_empty {
+            EmptyFilePolicy::Warn => {
+                print_empty_file_hint(&input_path);
+                Ok(false)
+            }
+            EmptyFilePolicy::Error => Err(GixError::EmptyFile(input_path.display().to_string())),
+        };
+    }
+    
+    // Writing into an ignored directory is most likely a mistyped destination
+    if args.output.is_some() && !args.no_output_checks && output_is_ignored(&original_file, &input_path, &output_path) {
+        print_output_ignored_warning(&output_path, &input_path);
+    }
+    
+    // Find duplicates for reporting; in aggressive mode whole repeated blocks are reported once
+    let mut duplicates = original_file.find_duplicates();
+    let blocks = match args.mode {
+        OptimizationMode::Aggressive => find_repeated_blocks(&original_file),
+        _ => Vec::new(),
+    };
+    if !blocks.is_empty() {
+        duplicates = duplicates.excluding_lines(&repeated_block_lines(&blocks));
+    }
+    // Protected duplicates stay in the file, so they aren't reported as removed
+    duplicates = duplicates.excluding_lines(&protected_lines(&original_file, &args.config.keep));
+    
+    // Optimize the file based on mode; heuristic merges are opt-in
+    let mut pass_stats = PassStats::default();
+    let started = Instant::now();
+    if args.allow_heuristics && !args.heuristics_enabled() && args.mode != OptimizationMode::Conservative {
+        print_risk_skipped("heuristic merges", ChangeRisk::BehaviorMayChange);
+    }
+    
+    // Only the repository file is relative to the tree it sits in; advanced mode checks its folds on it
+    let wants_tree = args.analyze || args.prune_unused || args.mode == OptimizationMode::Advanced;
+    let tree_paths = if wants_tree && args.scopes() == [Scope::Repo] && dialect.matches_like_gitignore() {
+        Some(list_paths(base_dir(&input_path), &args.walk_options())?)
+    } else {
+        None
+    };
+    let OptimizedFile { file: optimized_file, redundant, folded, moved, kept_for_safety, .. } = optimize_passes(args, engine, &original_file, tree_paths.as_deref(), &mut pass_stats)?;
+    let kept_lines: HashSet<usize> = kept_for_safety.iter().map(|kept| kept.line_number).collect();
+    duplicates = duplicates.excluding_lines(&kept_lines);
+    // What the tool ignores whatever the file says is one more layer above it, what it ignores by default one below
+    let higher: Vec<String> = higher.iter().cloned().chain(dialect.always_ignored()).collect();
+    let lower: Vec<String> = lower.iter().cloned().chain(dialect.ignored_by_default()).collect();
+    let cross_layer = !higher.is_empty() || !lower.is_empty();
+    let optimized_file = if !cross_layer || args.allows(ChangeRisk::LikelySafe) {
+        let deduplicated = remove_cross_layer_duplicates(&optimized_file, &higher, &lower, &mut pass_stats);
+        restore_kept_patterns(&optimized_file, &deduplicated, &args.config.keep)
+    } else {
+        print_risk_skipped("cross-layer dedup", ChangeRisk::LikelySafe);
+        optimized_file
+    };
+    let optimized_file = match &tree_paths {
+        Some(_) if args.prune_unused && !args.allows(ChangeRisk::BehaviorMayChange) => {
+            print_risk_skipped("unused pruning", ChangeRisk::BehaviorMayChange);
+            optimized_file
+        }
+        Some(paths) if args.prune_unused => {
+            let unused = unused_patterns(args, &optimized_file, paths);
+            let pruned = prune_unused_patterns(&optimized_file, &unused, &mut pass_stats);
+            print_pruned_patterns(&unused, args.dry_run);
+            pruned
+        }
+        _ => optimized_file,
+    };
+    let (optimized_file, whitespace_fixes) = match args.normalize_whitespace {
+        true => normalize_whitespace(&optimized_file, &mut pass_stats),
+        false => (optimized_file, Vec::new()),
+    };
+    let elapsed = started.elapsed();
+    
+    // Print results, promising the same ignored files only if the paths negations decide agree
+    let changes = decision_changes(&original_file, &optimized_file, &negation_probes(&[&original_file, &optimized_file]));
+    print_results(args, &original_file, &optimized_file, &duplicates, &blocks, &redundant, &changes)?;
+    if args.verbose || args.timings {
+        print_pass_stats(&pass_stats, args.timings.then_some(elapsed), args.heuristics_enabled());
+    }
+    if args.analyze {
+        let filter = args.analysis_filter();
+        print_analysis(&input_path, &analysis_of(&original_file, tree_paths.as_deref(), &filter)?, &filter, args.verbose);
+    }
+    if args.show_categories {
+        let patterns: Vec<String> = original_file.entries.iter()
+            .filter_map(|entry| match &entry.entry_type {
+                EntryType::Pattern(pattern) => Some(pattern.clone()),
+                _ => None,
+            })
+            .collect();
+        print_category_summary(&engine.category_summary(&patterns));
+    }
+    if args.detect_conflicts {
+        let conflicts = find_pattern_conflicts(&original_file);
+        print_conflicts(&conflicts);
+        if args.fail_on_conflicts && !conflicts.is_empty() {
+            return Err(GixError::ConflictsFound(conflicts.len()));
+        }
+    }
+    
+    // Each exception was moved next to the rule it carves out of while optimizing
+    print_moved_negations(&moved, args.verbose);
+    print_folded_patterns(&folded, args.dry_run);
+    print_kept_for_safety(&kept_for_safety);
+    
+    // Sort within sections before comments are generated, which would split them up
+    let optimized_file = engine.sort(&optimized_file, args.sort);
+    let mut report = OptimizationReport::describe(&original_file, &optimized_file, &redundant, &folded, &moved, &kept_for_safety);
+    report.add_whitespace_fixes(&whitespace_fixes);
+    if args.verbose {
+        print_optimization_report(&report);
+    }
+    
+    // Document patterns that have no comment of their own
+    let optimized_file = if args.generate_comments {
+        engine.generate_comments(&optimized_file)
+    } else {
+        optimized_file
+    };
+    
+    // A file that ignores nothing is better deleted than kept
+    check_not_emptied(args, &input_path, &original_file, &optimized_file)?;
+    
+    // If this is a dry run, show what would change instead of modifying the file
+    let optimized_content = optimized_file.serialize(&WriteOptions::preserving(&original_file));
+    
+    // Optimizing the result again must give back the very same bytes
+    if args.check_idempotent {
+        let again = reoptimize(args, engine, &optimized_content, dialect, &higher, &lower, tree_paths.as_deref())?;
+        if again != optimized_content {
+            let name = output_path.display().to_string();
+            print_diff(&unified_diff(&name, &name, &optimized_content, &again, DEFAULT_CONTEXT));
+            return Err(GixError::NotIdempotent(input_path.display().to_string()));
+        }
+    }
+    
+    // A plan records the edits for review instead of making them
+    let changed = optimized_content != content;
+    if let Some(plan) = plan {
+        if changed {
+            plan.files.push(PlannedFile::new(&input_path.display().to_string(), "optimize", &content, &optimized_content));
+        }
+        return Ok(changed);
+    }
+    
+    if args.dry_run {
+        let input_name = input_path.display().to_string();
+        let output_name = output_path.display().to_string();
+        print_diff(&unified_diff(&input_name, &output_name, &content, &optimized_content, DEFAULT_CONTEXT));
+        return Ok(changed);
+    }
+    
+    // Create backup if requested
+    if args.should_backup() {
+        let backup = create_backup_in(&input_path, &args.backup_policy())?;
+        if let Some(backup) = backup.filter(|_| args.verbose) {
+            print_backup(&backup);
+        }
+    }
+    
+    // Write the optimized content
+    write_reported_file(args, "optimize", &output_path, &optimized_content, &report)?;
+    
+    // Keep an auditable record of everything that was removed
+    if let Some(log_path) = &args.removals_log {
+        let removed = collect_removed_entries(&original_file, &optimized_file);
+        let log = render_removals_log(&input_path.display().to_string(), &removed);
+        write_gitignore_file(log_path, &log)?;
+        if args.verbose {
+            print_removals_log(log_path, removed.len());
+        }
+    }
+    
+    // Print success message
+    print_success(&output_path);
+    
+    Ok(changed)
+}
+
+/// Run the engine's optimization, grouping negations along the way when asked to
+fn optimize_passes(args: &Args, engine: &GixEngine, file: &GitignoreFile, tree_paths: Option<&[String]>, stats: &mut PassStats) -> Result<OptimizedFile, GixError> {
+    let paths = tree_paths.unwrap_or_default();
+    match args.group_negations {
+        true => engine.optimize_grouped(file, paths, stats),
+        false => engine.optimize_in_tree(file, paths, stats),
+    }
+}
+
+/// Find the patterns of FILE that match nothing in the tree; protected patterns are never prunable
+fn unused_patterns(args: &Args, file: &GitignoreFile, paths: &[String]) -> Vec<UnusedPattern> {
+    let protected = protected_lines(file, &args.config.keep);
+    let mut unused = find_unused_patterns(file, paths);
+    for pattern in unused.iter_mut().filter(|pattern| protected.contains(&pattern.line_number)) {
+        pattern.prunable = false;
+    }
+    unused
+}
+
+/// Analyze a file for --analyze and `gix analyze`, with the patterns unused in `tree_paths` if given
+pub(crate) fn analysis_of(file: &GitignoreFile, tree_paths: Option<&[String]>, filter: &AnalysisFilter) -> Result<GitignoreAnalysis, GixError> {
+    let mut analysis = analyze_gitignore(file)?;
+    if let Some(paths) = tree_paths {
+        analysis.unused_patterns = find_unused_patterns(file, paths);
+    }
+    Ok(analysis.filter(filter))
+}
+
+/// Run the passes of `optimize_file` over its own output, without reporting, for --check-idempotent
+fn reoptimize(args: &Args, engine: &GixEngine, content: &str, dialect: Dialect, higher: &[String], lower: &[String], tree_paths: Option<&[String]>) -> Result<String, GixError> {
+    let file = parse_ignore_file(content, dialect)?;
+    let mut stats = PassStats::default();
+    let optimized = optimize_passes(args, engine, &file, tree_paths, &mut stats)?.file;
+    
+    let cross_layer = !higher.is_empty() || !lower.is_empty();
+    let optimized = match !cross_layer || args.allows(ChangeRisk::LikelySafe) {
+        true => restore_kept_patterns(&optimized, &remove_cross_layer_duplicates(&optimized, higher, lower, &mut stats), &args.config.keep),
+        false => optimized,
+    };
+    let optimized = match tree_paths {
+        Some(paths) if args.prune_unused && args.allows(ChangeRisk::BehaviorMayChange) => {
+            prune_unused_patterns(&optimized, &unused_patterns(args, &optimized, paths), &mut stats)
+        }
+        _ => optimized,
+    };
+    let optimized = match args.normalize_whitespace {
+        true => normalize_whitespace(&optimized, &mut stats).0,
+        false => optimized,
+    };
+    let optimized = engine.sort(&optimized, args.sort);
+    let optimized = match args.generate_comments {
+        true => engine.generate_comments(&optimized),
+        false => optimized,
+    };
+    Ok(optimized.serialize(&WriteOptions::preserving(&file)))
+}
+
+/// Optimize each selected ignore layer of the checkout holding FILE, in order of precedence
+///
+/// Only `info/exclude` is trimmed against the other layers: the `.gitignore` is shared with
+/// everyone and the global file with every repository, so neither can rely on the others.
+fn run_scopes(args: &Args, engine: &GixEngine, scopes: &[Scope], mut plan: Option<&mut Plan>) -> Result<bool, GixError> {
+    if args.output.is_some() && scopes.len() > 1 {
+        return Err(GixError::UsageError("--output can only be used with a single --scope".to_string()));
+    }
+    
+    let repo_path = args.input_file();
+    let worktree = match repo_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let layers: Vec<(Scope, Option<PathBuf>)> = scopes.iter()
+        .map(|scope| {
+            let path = match scope {
+                Scope::Repo => Some(repo_path.clone()),
+                Scope::Info => exclude_file(&worktree),
+                Scope::Global => global_excludes_file(),
+            };
+            (*scope, path)
+        })
+        .collect();
+    let patterns_of = |wanted: Scope| -> Result<Vec<String>, GixError> {
+        match layers.iter().find(|(scope, _)| *scope == wanted) {
+            Some((_, Some(path))) if path.is_file() => Ok(parse_gitignore(&read_gitignore_file(path)?)?
+                .entries.iter()
+                .filter_map(|entry| entry.comparison_pattern())
+                .collect()),
+            _ => Ok(Vec::new()),
+        }
+    };
+    
+    let mut changed = false;
+    for (scope, path) in &layers {
+        let Some(path) = path else {
+            print_scope_missing(scope.name());
+            continue;
+        };
+        print_scope_header(scope.name(), path);
+        let (higher, lower) = match scope {
+            Scope::Info => (patterns_of(Scope::Repo)?, patterns_of(Scope::Global)?),
+            _ => (Vec::new(), Vec::new()),
+        };
+        let output_path = args.output.clone().unwrap_or_else(|| path.clone());
+        changed |= optimize_file(args, engine, path.clone(), output_path, &higher, &lower, plan.as_deref_mut())?;
+    }
+    Ok(changed)
+}
+
+/// The settings for each file of a walk: `args` with the nested `gix.toml` files above the file layered over its config
+///
+/// Returns the distinct settings, `args` itself first, and the index of each file's own.
+fn nested_settings(args: &Args, files: &[WalkedFile]) -> Result<(Vec<Args>, Vec<usize>), GixError> {
+    let root = args.root_dir();
+    let config_root = repository_root(&root).unwrap_or(root);
+    let mut settings = vec![args.clone()];
+    let mut chosen = Vec::new();
+    for walked in files {
+        let dir = walked.location.parent().unwrap_or(Path::new("."));
+        let config = args.config.layered_below(&config_root, dir)?;
+        let index = match settings.iter().position(|known| known.config == config) {
+            Some(index) => index,
+            None => {
+                let mut nested = args.clone();
+                nested.use_config(config);
+                settings.push(nested);
+                settings.len() - 1
+            }
+        };
+        chosen.push(index);
+    }
+    Ok((settings, chosen))
+}
+
+/// Optimize every `.gitignore` below the root directory, printing a line per file and the totals
+///
+/// With a `plan`, the edits are added to it and nothing is written.
+fn run_recursive(args: &Args, engine: &GixEngine, mut plan: Option<&mut Plan>) -> Result<bool, GixError> {
+    let root = args.root_dir();
+    let found = find_files(&root, &[".gitignore"], &args.walk_options())?;
+    let mut pass_stats = PassStats::default();
+    let started = Instant::now();
+    
+    // Files below a nested gix.toml are optimized with its settings, a batch per distinct config
+    let (settings, chosen) = nested_settings(args, &found.files)?;
+    let mut batched: Vec<Option<FileResult>> = found.files.iter().map(|_| None).collect();
+    for (index, file_args) in settings.iter().enumerate() {
+        let members: Vec<usize> = (0..found.files.len()).filter(|&file| chosen[file] == index).collect();
+        if members.is_empty() {
+            continue;
+        }
+        let nested_engine;
+        let engine = match index {
+            0 => engine,
+            _ => {
+                nested_engine = file_args.engine();
+                &nested_engine
+            }
+        };
+        let locations: Vec<PathBuf> = members.iter().map(|&file| found.files[file].location.clone()).collect();
+        let options = BatchOptions {
+            dry_run: file_args.dry_run || plan.is_some(),
+            allow_empty: file_args.allow_empty,
+            backup: file_args.should_backup().then(|| file_args.backup_policy()),
+        };
+        for (file, result) in members.into_iter().zip(engine.optimize_many(&locations, &options, &mut pass_stats)) {
+            batched[file] = Some(result);
+        }
+    }
+    let mut results: Vec<FileResult> = batched.into_iter().map(|result| result.expect("every file is in a batch")).collect();
+    
+    for ((walked, result), &index) in found.files.iter().zip(results.iter_mut()).zip(&chosen) {
+        let args = &settings[index];
+        let Ok(outcome) = &result.outcome else {
+            continue;
+        };
+        if let Some(plan) = plan.as_deref_mut() {
+            if outcome.empties() && !args.allow_empty {
+                print_would_empty(&walked.location);
+            } else if outcome.removed() > 0 {
+                plan.files.push(PlannedFile::new(&walked.location.display().to_string(), "optimize", &outcome.content, &outcome.optimized_content));
+            }
+            continue;
+        }
+        if args.dry_run && outcome.removed() > 0 {
+            if outcome.empties() && !args.allow_empty {
+                print_would_empty(&walked.location);
+            }
+            print_diff(&unified_diff(&walked.path, &walked.path, &outcome.content, &outcome.optimized_content, DEFAULT_CONTEXT));
+        }
+        if outcome.written {
+            if let Err(error) = record_engine_write(args, &walked.location, outcome) {
+                result.outcome = Err(error);
+            }
+        }
+    }
+    
+    let labelled: Vec<(&str, &FileResult)> = found.files.iter().map(|walked| walked.path.as_str()).zip(results.iter()).collect();
+    print_recursive_results(&labelled, args.dry_run || plan.is_some());
+    if args.verbose || args.timings {
+        print_pass_stats(&pass_stats, args.timings.then_some(started.elapsed()), args.heuristics_enabled());
+    }
+    
+    // Every file is processed before failing, so one unreadable file doesn't hold up the rest
+    let failed = results.iter().filter(|result| result.outcome.is_err()).count();
+    if failed > 0 {
+        return Err(GixError::BatchFailed { failed, total: results.len() });
+    }
+    Ok(results.iter().any(|result| result.outcome.as_ref().is_ok_and(|outcome| outcome.removed() > 0)))
+}
+
+/// Record a file the engine optimized in the history and, when --audit-log is set, the audit log
+pub(crate) fn record_engine_write(args: &Args, path: &Path, outcome: &FileOutcome) -> Result<(), GixError> {
+    if args.records_history() {
+        let report = OptimizationReport::describe(&outcome.original, &outcome.optimized, &[], &[], &[], &[]);
+        record_step(path, args.metadata, "optimize", Some(&outcome.content), &outcome.optimized_content, &report)?;
+    }
+    if args.audit_log {
+        log_write(path, args.metadata, "optimize", args.mode.name(), &args.audit_options(), Some(&outcome.content), &outcome.optimized_content)?;
+    }
+    Ok(())
+}
+
+/// Refuse to write a file that optimization left without patterns, unless --allow-empty is set
+///
+/// A dry run only warns, so the diff can still be reviewed. On a terminal the user is asked
+/// instead, unless --non-interactive is set.
+fn check_not_emptied(args: &Args, path: &Path, original: &GitignoreFile, optimized: &GitignoreFile) -> Result<(), GixError> {
+    if args.allow_empty || original.stats.pattern_lines == 0 || optimized.stats.pattern_lines > 0 {
+        return Ok(());
+    }
+    if args.dry_run {
+        print_would_empty(path);
+        return Ok(());
+    }
+    if args.interactive() && confirm(&format!("Leave {} without patterns?", path.display())) {
+        return Ok(());
+    }
+    Err(GixError::WouldBeEmpty(path.display().to_string()))
+}
+
+/// Get the directory an ignore file's patterns are relative to
+pub(crate) fn base_dir(input_path: &Path) -> &Path {
+    match input_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Write an ignore file, recording the write in the history and, when --audit-log is set, the audit log
+pub(crate) fn write_ignore_file(args: &Args, command: &str, path: &Path, content: &str) -> Result<(), GixError> {
+    write_reported_file(args, command, path, content, &OptimizationReport::default())
+}
+
+/// Write an ignore file like `write_ignore_file`, keeping `report` in the history for `gix history`
+///
+/// A new file written with -o is left out of the history, since it replaced nothing.
+fn write_reported_file(args: &Args, command: &str, path: &Path, content: &str, report: &OptimizationReport) -> Result<(), GixError> {
+    let before = std::fs::read_to_string(path).ok();
+    write_gitignore_file(path, content)?;
+    let new_output = before.is_none() && args.output.as_deref() == Some(path);
+    if args.records_history() && !new_output && before.as_deref() != Some(content) {
+        record_step(path, args.metadata, command, before.as_deref(), content, report)?;
+    }
+    if args.audit_log {
+        log_write(path, args.metadata, command, args.mode.name(), &args.audit_options(), before.as_deref(), content)?;
+    }
+    Ok(())
+}
+
+/// Check if the output path is ignored by the patterns of the input file
+fn output_is_ignored(file: &GitignoreFile, input_path: &Path, output_path: &Path) -> bool {
+    let Some(relative) = path_relative_to(base_dir(input_path), output_path) else {
+        return false;
+    };
+    
+    let patterns: Vec<&str> = file.entries.iter()
+        .filter_map(|entry| match &entry.entry_type {
+            EntryType::Pattern(pattern) => Some(pattern.as_str()),
+            _ => None,
+        })
+        .collect();
+    is_ignored(&patterns, &relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::read_history;
+    use clap::Parser;
+    use tempfile::NamedTempFile;
+    use std::io::Write;
+
+    #[test]
+    fn test_run_with_dry_run() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let content = "*.log\n*.log\nbuild/";
+        writeln!(temp_file.as_file(), "{}", content).unwrap();
+        
+        let args = Args::parse_from(["gix", "--dry-run", temp_file.path().to_str().unwrap()]);
+        let result = run(args);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_run_check_reports_whitespace_violations() {
+        let temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file.as_file(), "*.log \n\n\nbuild/\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "--check", temp_file.path().to_str().unwrap()]);
+        let result = run(args);
+        assert!(matches!(result, Err(GixError::CheckFailed(2))));
+        
+        // Check mode must leave the file untouched
+        let content = std::fs::read_to_string(temp_file.path()).unwrap();
+        assert_eq!(content, "*.log \n\n\nbuild/\n");
+    }
+
+    #[test]
+    fn test_run_check_with_ci_format_still_fails() {
+        let temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file.as_file(), "*.log ").unwrap();
+        
+        let args = Args::parse_from(["gix", "--check", "--format", "bitbucket", temp_file.path().to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::CheckFailed(1))));
+    }
+
+    #[test]
+    fn test_run_check_passes_clean_file() {
+        let temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file.as_file(), "*.log\n\nbuild/\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "--check", temp_file.path().to_str().unwrap()]);
+        assert!(run(args).is_ok());
+    }
+
+    #[test]
+    fn test_run_with_analyze() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n*.log\n!debug.log\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "--analyze", "--only", "negations", input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n!debug.log\n");
+        
+        std::fs::write(&input, "*.log\n*.log\n").unwrap();
+        let args = Args::parse_from(["gix", "--analyze", "--format", "json", input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n*.log\n");
+    }
+
+    #[test]
+    fn test_run_analyze_subcommand() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n*.log\n!debug.log\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "analyze", "--only", "conflicts,negations", input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        let args = Args::parse_from(["gix", "analyze", "--format", "json", input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n*.log\n!debug.log\n");
+        
+        let args = Args::parse_from(["gix", "analyze", "--format", "sarif", input.to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::UsageError(_))));
+    }
+
+    #[test]
+    fn test_run_prune_unused() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\nold-tool/\nbuild/\n*.swp\n").unwrap();
+        std::fs::write(dir.path().join("app.log"), "").unwrap();
+        
+        let args = Args::parse_from(["gix", "--prune-unused", "--analyze", input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\nbuild/\n*.swp\n");
+    }
+
+    #[test]
+    fn test_run_max_risk_skips_riskier_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\nold-tool/\n*.log\nbuild\nbuild/\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "--prune-unused", "--allow-heuristics", "--max-risk", "safe", input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\nold-tool/\nbuild\nbuild/\n");
+    }
+
+    #[test]
+    fn test_run_refuses_to_empty_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "# Old tools\nold-tool/\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "--prune-unused", "--non-interactive", input.to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::WouldBeEmpty(_))));
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "# Old tools\nold-tool/\n");
+        
+        let args = Args::parse_from(["gix", "--prune-unused", "--allow-empty", input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "# Old tools\n");
+    }
+
+    #[test]
+    fn test_output_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        let file = parse_gitignore("build/\n*.log\n!keep.log").unwrap();
+        
+        assert!(output_is_ignored(&file, &input, &dir.path().join("build/.gitignore")));
+        assert!(output_is_ignored(&file, &input, &dir.path().join("out.log")));
+        assert!(!output_is_ignored(&file, &input, &dir.path().join("keep.log")));
+        assert!(!output_is_ignored(&file, &input, &dir.path().join("optimized.gitignore")));
+        assert!(!output_is_ignored(&file, &input, Path::new("/somewhere/else/build/x")));
+    }
+
+    #[test]
+    fn test_run_still_writes_ignored_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::create_dir(dir.path().join("build")).unwrap();
+        let output = dir.path().join("build/.gitignore");
+        std::fs::write(&input, "build/\nbuild/\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "--output", output.to_str().unwrap(), input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "build/\n");
+    }
+
+    #[test]
+    fn test_run_fails_on_conflicts_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n*.log\n!debug.log\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--detect-conflicts", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n!debug.log\n");
+        
+        std::fs::write(&input, "*.log\n*.log\n!debug.log\n").unwrap();
+        let args = Args::parse_from(["gix", "--detect-conflicts", "--fail-on-conflicts", input.to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::ConflictsFound(2))));
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n*.log\n!debug.log\n");
+    }
+
+    #[test]
+    fn test_run_generates_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "# Logs\n*.log\n\nbuild/\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--generate-comments", "--show-categories", input.to_str().unwrap()])).is_ok());
+        let lines: Vec<String> = std::fs::read_to_string(&input).unwrap().lines().map(str::to_string).collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(&lines[..3], &["# Logs", "*.log", ""]);
+        assert!(lines[3].starts_with("# "));
+        assert_eq!(lines[4], "build/");
+    }
+
+    #[test]
+    fn test_run_organize() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\ncustom/\n*.log\n!keep.log\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "organize", input.to_str().unwrap()])).is_ok());
+        let organized = std::fs::read_to_string(&input).unwrap();
+        assert_eq!(organized.matches("*.log").count(), 1);
+        assert!(organized.ends_with("# Exceptions\n!keep.log\n"));
+        
+        // Organizing an organized file changes nothing
+        assert!(run(Args::parse_from(["gix", "organize", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), organized);
+    }
+
+    #[test]
+    fn test_run_check_subcommand() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\nbuild/\n*.log\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "check", input.to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::CheckFailed(1))));
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\nbuild/\n*.log\n");
+        
+        std::fs::write(&input, "*.log\nbuild/\n").unwrap();
+        assert!(run(Args::parse_from(["gix", "check", input.to_str().unwrap()])).is_ok());
+        assert!(run(Args::parse_from(["gix", "check", "--format", "json", input.to_str().unwrap()])).is_ok());
+        
+        std::fs::write(&input, "build/\nbuild/*.o\n").unwrap();
+        std::fs::create_dir(dir.path().join("build")).unwrap();
+        std::fs::write(dir.path().join("build/a.o"), "").unwrap();
+        let args = Args::parse_from(["gix", "check", "--examples", "--example-budget", "100", input.to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::CheckFailed(1))));
+    }
+    
+    #[test]
+    fn test_run_check_rule_severities() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n*.log\n").unwrap();
+        let path = input.to_str().unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "check", "--allow", "GIX001", path])).is_ok());
+        assert!(run(Args::parse_from(["gix", "check", "--warn", "duplicate", path])).is_ok());
+        assert!(matches!(run(Args::parse_from(["gix", "check", "--warn", "duplicate", "--deny", "GIX001", path])), Err(GixError::CheckFailed(1))));
+        assert!(matches!(run(Args::parse_from(["gix", "check", "--allow", "typo", path])), Err(GixError::UsageError(_))));
+    }
+    
+    #[test]
+    fn test_run_check_reads_gitattributes() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.psd\n*.log\n").unwrap();
+        assert!(run(Args::parse_from(["gix", "check", input.to_str().unwrap()])).is_ok());
+        
+        std::fs::write(dir.path().join(".gitattributes"), "*.psd filter=lfs diff=lfs merge=lfs -text\n").unwrap();
+        assert!(matches!(run(Args::parse_from(["gix", "check", input.to_str().unwrap()])), Err(GixError::CheckFailed(1))));
+    }
+
+    #[test]
+    fn test_run_scan_writes_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "*.log\n*.log\n").unwrap();
+        let summary = dir.path().join(".gix/summary.md");
+        
+        let args = Args::parse_from(["gix", "scan", dir.path().to_str().unwrap(), "--write-summary", summary.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert!(std::fs::read_to_string(&summary).unwrap().contains("| `.gitignore` | 2 | 2 |"));
+    }
+
+    #[test]
+    fn test_run_simulate_add_leaves_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("dist")).unwrap();
+        std::fs::write(dir.path().join("dist/app.js"), "").unwrap();
+        
+        let args = Args::parse_from(["gix", "simulate-add", "dist/", "-f", input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n");
+        let args = Args::parse_from(["gix", "simulate-add", "", "-f", input.to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::InvalidPattern(_))));
+    }
+
+    #[test]
+    fn test_run_fleet_report() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in [("api", "*.log\n"), ("web", "*.log\n*.log\n")] {
+            std::fs::create_dir_all(dir.path().join(name).join(".git")).unwrap();
+            std::fs::write(dir.path().join(name).join(".gitignore"), content).unwrap();
+        }
+        let list = dir.path().join("repos.txt");
+        std::fs::write(&list, "api\nweb\nmissing\n").unwrap();
+        let report = dir.path().join("fleet.json");
+        
+        let args = Args::parse_from(["gix", "fleet", "report", "--repos-from", list.to_str().unwrap(), "--format", "json", "-o", report.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        let report = std::fs::read_to_string(&report).unwrap();
+        assert!(report.contains("\"repositories\": 3, \"assessed\": 2, \"compliant\": 1"));
+        assert!(report.contains("\"failing_rules\": {\"duplicate\": {\"findings\": 1, \"repositories\": 1}}"));
+    }
+
+    #[test]
+    fn test_run_writes_removals_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        let log = dir.path().join("removed.gitignore");
+        std::fs::write(&input, "# Logs\n*.log\nbuild/\n*.log\n").unwrap();
+        
+        let args = Args::parse_from([
+            "gix",
+            "--removals-log",
+            log.to_str().unwrap(),
+            input.to_str().unwrap(),
+        ]);
+        assert!(run(args).is_ok());
+        
+        let log_content = std::fs::read_to_string(&log).unwrap();
+        assert!(log_content.contains("# line 4 (under \"# Logs\")\n*.log\n"));
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "# Logs\n*.log\nbuild/\n");
+    }
+
+    #[test]
+    fn test_run_merges_equivalents_only_with_heuristics() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "build/\n/build\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "build/\n/build\n");
+        
+        assert!(run(Args::parse_from(["gix", "--allow-heuristics", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "build/\n");
+    }
+
+    #[test]
+    fn test_run_normalize_whitespace() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "#Logs\n*.log  \n\n\n\nbuild/\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "#Logs\n*.log  \n\n\n\nbuild/\n");
+        
+        assert!(run(Args::parse_from(["gix", "--normalize-whitespace", "--check-idempotent", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "# Logs\n*.log\n\n\nbuild/\n");
+    }
+
+    #[test]
+    fn test_run_ignore_case_dedups_case_variants() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "build/\nBUILD/\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "build/\nBUILD/\n");
+        
+        assert!(run(Args::parse_from(["gix", "--ignore-case", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "build/\n");
+    }
+
+    #[test]
+    fn test_run_keep_last_occurrence() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\nbuild/\n\n# Logs\n*.log\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--keep", "last", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "build/\n\n# Logs\n*.log\n");
+    }
+
+    #[test]
+    fn test_run_advanced_removes_covered_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "build/\nbuild/foo.txt\n*.pyc\n**/*.pyc\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--mode", "advanced", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "build/\n*.pyc\n");
+    }
+
+    #[test]
+    fn test_run_dry_run_skips_removals_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        let log = dir.path().join("removed.gitignore");
+        std::fs::write(&input, "*.log\n*.log\n").unwrap();
+        
+        let args = Args::parse_from([
+            "gix",
+            "--dry-run",
+            "--removals-log",
+            log.to_str().unwrap(),
+            input.to_str().unwrap(),
+        ]);
+        assert!(run(args).is_ok());
+        assert!(!log.exists());
+    }
+
+    #[test]
+    fn test_run_create_if_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        
+        assert!(matches!(run(Args::parse_from(["gix", input.to_str().unwrap()])), Err(GixError::FileNotFound(_))));
+        assert!(run(Args::parse_from(["gix", "--create-if-missing", "--dry-run", input.to_str().unwrap()])).is_ok());
+        assert!(!input.exists());
+        assert!(run(Args::parse_from(["gix", "--create-if-missing", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "");
+    }
+    
+    #[test]
+    fn test_run_empty_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "\n\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "\n\n");
+        let args = Args::parse_from(["gix", "--on-empty", "error", input.to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::EmptyFile(_))));
+    }
+
+    #[test]
+    fn test_run_repo_and_info_scopes() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        let exclude = dir.path().join(".git/info/exclude");
+        std::fs::create_dir_all(dir.path().join(".git/info")).unwrap();
+        std::fs::write(&input, "*.log\n*.log\n").unwrap();
+        std::fs::write(&exclude, "*.log\n.env\n.env\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "--scope", "repo,info", input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n");
+        assert_eq!(std::fs::read_to_string(&exclude).unwrap(), ".env\n");
+        
+        let args = Args::parse_from(["gix", "--scope", "repo,info", "--output", "out", input.to_str().unwrap()]);
+        assert!(run(args).is_err());
+    }
+
+    #[test]
+    fn test_run_group_negations() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\nbuild/\n*.log\n!debug.log\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--group-negations", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n!debug.log\nbuild/\n");
+    }
+
+    #[test]
+    fn test_run_check_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\r\n\r\n!debug.log\r\n\r\nlogs/\r\n*.log\r\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--check-idempotent", "--group-negations", "--mode", "advanced", input.to_str().unwrap()])).is_ok());
+        let optimized = std::fs::read_to_string(&input).unwrap();
+        // The last *.log ignores debug.log again, so it stays
+        assert_eq!(optimized, "*.log\r\n!debug.log\r\n\r\nlogs/\r\n*.log\r\n");
+        
+        assert!(run(Args::parse_from(["gix", "--check-idempotent", "--group-negations", "--mode", "advanced", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), optimized);
+    }
+
+    #[test]
+    fn test_run_advanced_folds_sibling_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(dir.path().join("app.pyc"), "").unwrap();
+        std::fs::write(&input, "# Python\n*.pyc\n*.pyo\n*.pyd\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--mode", "advanced", "--check-idempotent", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "# Python\n*.py[cod]\n");
+    }
+
+    #[test]
+    fn test_run_keeps_configured_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, ".env\n*.log\n.env\n*.log\n").unwrap();
+        
+        let mut args = Args::parse_from(["gix", input.to_str().unwrap()]);
+        args.config.keep = vec![".env".to_string()];
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), ".env\n*.log\n.env\n");
+    }
+
+    #[test]
+    fn test_run_keeps_marked_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "build/\n# gix:keep\nbuild/cache/\n# gix:keep\nmissing.txt\nunused.txt\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--mode", "advanced", "--prune-unused", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "build/\n# gix:keep\nbuild/cache/\n# gix:keep\nmissing.txt\n");
+    }
+
+    #[test]
+    fn test_run_keeps_duplicates_that_override_negations() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n!debug.log\n*.log\n.env\n.env\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--mode", "aggressive", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n!debug.log\n*.log\n.env\n");
+    }
+
+    #[test]
+    fn test_run_leaves_preserved_region_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        let region = "# gix:off\nbuild/\n!build/keep.txt\nbuild/\n*.pyo\n# gix:on\n";
+        std::fs::write(&input, format!("*.log\n*.pyc\n{}*.log\n!debug.log\n", region)).unwrap();
+        
+        let args = Args::parse_from(["gix", "--mode", "advanced", "--group-negations", "--generate-comments", "--check-idempotent", input.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        let optimized = std::fs::read_to_string(&input).unwrap();
+        assert!(optimized.contains(&format!("\n{}", region)), "{}", optimized);
+        assert!(!optimized.contains("*.py[co]"));
+    }
+
+    #[test]
+    fn test_run_audit() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| std::process::Command::new("git").arg("-C").arg(dir.path()).args(args).output().unwrap();
+        git(&["init", "-q"]);
+        std::fs::write(dir.path().join("app.log"), "").unwrap();
+        git(&["add", "app.log"]);
+        
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.tmp\n").unwrap();
+        assert!(run(Args::parse_from(["gix", "audit", input.to_str().unwrap()])).is_ok());
+        std::fs::write(&input, "*.log\n").unwrap();
+        assert!(matches!(run(Args::parse_from(["gix", "audit", input.to_str().unwrap()])), Err(GixError::CheckFailed(1))));
+    }
+
+    #[test]
+    fn test_run_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        let old = dir.path().join("old.gitignore");
+        std::fs::write(&input, "*.log\n*.log\n!debug.log\n").unwrap();
+        std::fs::write(&old, "*.log\n*.log\n!debug.log\n").unwrap();
+        std::fs::write(dir.path().join("debug.log"), "").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", input.to_str().unwrap()])).is_ok());
+        let verify = || run(Args::parse_from(["gix", "verify", input.to_str().unwrap(), "--against", old.to_str().unwrap()]));
+        assert!(verify().is_ok());
+        
+        std::fs::write(&input, "!debug.log\n*.log\n").unwrap();
+        assert!(matches!(verify(), Err(GixError::NotEquivalent(_))));
+    }
+
+    #[test]
+    fn test_run_sort() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "# Editors\n.vscode/\n.idea/\n\n# Logs\n*.log\n!debug.log\naudit.log\n*.log\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--sort", "alpha", "--check-idempotent", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "# Editors\n.idea/\n.vscode/\n\n# Logs\n*.log\naudit.log\n!debug.log\n*.log\n");
+    }
+
+    #[test]
+    fn test_run_emit_and_apply_plan() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("app")).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "*.log\n*.log\n").unwrap();
+        std::fs::write(dir.path().join("app/.gitignore"), "build/\n").unwrap();
+        let plan = dir.path().join("plan.json");
+        
+        let emit = ["gix", "--recursive", "--emit-plan", plan.to_str().unwrap(), dir.path().to_str().unwrap()];
+        assert!(run(Args::parse_from(emit)).is_ok());
+        assert_eq!(std::fs::read_to_string(dir.path().join(".gitignore")).unwrap(), "*.log\n*.log\n");
+        assert_eq!(Plan::parse(&std::fs::read_to_string(&plan).unwrap()).unwrap().files.len(), 1);
+        
+        assert!(run(Args::parse_from(["gix", "apply-plan", plan.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(dir.path().join(".gitignore")).unwrap(), "*.log\n");
+        
+        // The file no longer matches the plan
+        let result = run(Args::parse_from(["gix", "apply-plan", plan.to_str().unwrap()]));
+        assert!(matches!(result, Err(GixError::StalePlan(_))));
+    }
+
+    #[test]
+    fn test_run_self_test() {
+        assert!(run(Args::parse_from(["gix", "self-test"])).is_ok());
+    }
+
+    #[test]
+    fn test_run_keeps_bom() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "\u{feff}# Logs\n*.log\n*.log").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "\u{feff}# Logs\n*.log");
+    }
+
+    #[test]
+    fn test_run_recursive() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("app")).unwrap();
+        std::fs::create_dir_all(dir.path().join("vendor")).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "*.log\n*.log\n").unwrap();
+        std::fs::write(dir.path().join("app/.gitignore"), "build/\n").unwrap();
+        std::fs::write(dir.path().join("vendor/.gitignore"), "*.o\n*.o\n").unwrap();
+        
+        let root = dir.path().to_str().unwrap();
+        assert!(run(Args::parse_from(["gix", "--recursive", "--dry-run", root])).is_ok());
+        assert_eq!(std::fs::read_to_string(dir.path().join(".gitignore")).unwrap(), "*.log\n*.log\n");
+        
+        assert!(run(Args::parse_from(["gix", "--recursive", "--exclude-path", "vendor", root])).is_ok());
+        assert_eq!(std::fs::read_to_string(dir.path().join(".gitignore")).unwrap(), "*.log\n");
+        assert_eq!(std::fs::read_to_string(dir.path().join("app/.gitignore")).unwrap(), "build/\n");
+        assert_eq!(std::fs::read_to_string(dir.path().join("vendor/.gitignore")).unwrap(), "*.o\n*.o\n");
+    }
+
+    #[test]
+    fn test_run_recursive_applies_nested_config() {
+        let dir = tempfile::tempdir().unwrap();
+        for sub in ["services", "legacy"] {
+            std::fs::create_dir_all(dir.path().join(sub)).unwrap();
+            std::fs::write(dir.path().join(sub).join(".gitignore"), "# Build\n*.o\n\n\n\n# Build\n*.o\n").unwrap();
+        }
+        std::fs::write(dir.path().join("services/gix.toml"), "mode = \"aggressive\"\n").unwrap();
+        std::fs::write(dir.path().join("legacy/gix.toml"), "keep = [\"*.o\"]\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--recursive", dir.path().to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(dir.path().join("services/.gitignore")).unwrap(), "# Build\n*.o\n");
+        assert_eq!(std::fs::read_to_string(dir.path().join("legacy/.gitignore")).unwrap(), "# Build\n*.o\n\n\n\n# Build\n*.o\n");
+    }
+
+    #[test]
+    fn test_run_recursive_continues_past_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("broken")).unwrap();
+        std::fs::create_dir_all(dir.path().join("zzz")).unwrap();
+        std::fs::write(dir.path().join("broken/.gitignore"), b"*.log\n\xff\n").unwrap();
+        std::fs::write(dir.path().join("zzz/.gitignore"), "*.o\n*.o\n").unwrap();
+        
+        let result = run(Args::parse_from(["gix", "--recursive", dir.path().to_str().unwrap()]));
+        assert!(matches!(result, Err(GixError::BatchFailed { failed: 1, total: 2 })));
+        assert_eq!(std::fs::read_to_string(dir.path().join("zzz/.gitignore")).unwrap(), "*.o\n");
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_run_init_like_sibling_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let sibling = dir.path().join("sibling");
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(&sibling).unwrap();
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(sibling.join(".gitignore"), "# Rust\ntarget/\n\n# Java\n*.class\n\n# Local\nlocal/\n").unwrap();
+        std::fs::write(project.join("Cargo.toml"), "[package]\n").unwrap();
+        
+        let output = project.join(".gitignore");
+        let args = Args::parse_from(["gix", "init", "--like", sibling.to_str().unwrap(), "--output", output.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "# Rust\ntarget/\n\n# Local\nlocal/\n");
+        
+        // An existing file is only replaced on request
+        let args = Args::parse_from(["gix", "init", "--like", sibling.to_str().unwrap(), "--output", output.to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::AlreadyExists(_))));
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_run_init_from_builtin_sets() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join(".gitignore");
+        
+        let args = Args::parse_from(["gix", "init", "--lang", "rust,node", "--os", "macos", "--output", output.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        let content = std::fs::read_to_string(&output).unwrap();
+        assert!(content.starts_with("# Rust\n"));
+        assert!(content.contains("\n# Node.js\n"));
+        assert!(content.contains(".DS_Store\n"));
+        
+        let args = Args::parse_from(["gix", "init", "--lang", "cobol", "--force", "--output", output.to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::UnknownCategory(_))));
+    }
+
+    #[test]
+    #[cfg(not(feature = "templates-remote"))]
+    fn test_run_add_template_needs_feature() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "add-template", "python", input.to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::NetworkError(_))));
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n");
+    }
+
+    #[test]
+    fn test_run_audit_log() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n*.log\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--audit-log", input.to_str().unwrap()])).is_ok());
+        assert!(run(Args::parse_from(["gix", "organize", input.to_str().unwrap()])).is_ok());
+        
+        let log = std::fs::read_to_string(dir.path().join(".git/gix/audit.log")).unwrap();
+        assert_eq!(log.lines().count(), 2);
+        assert!(log.contains("\t.gitignore\toptimize\tstandard\t"));
+        
+        let dir_arg = dir.path().to_str().unwrap();
+        assert!(run(Args::parse_from(["gix", "audit-log", "show", dir_arg])).is_ok());
+        assert!(run(Args::parse_from(["gix", "audit-log", "verify", dir_arg])).is_ok());
+        
+        std::fs::write(dir.path().join(".git/gix/audit.log"), log.replace("optimize", "organize")).unwrap();
+        assert!(matches!(run(Args::parse_from(["gix", "audit-log", "verify", dir_arg])), Err(GixError::AuditLogBroken(_))));
+    }
+
+    #[test]
+    fn test_run_undo() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n*.log\nbuild/\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", input.to_str().unwrap()])).is_ok());
+        assert!(run(Args::parse_from(["gix", "--sort", "alpha", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\nbuild/\n");
+        
+        let dir_arg = dir.path().to_str().unwrap();
+        assert!(run(Args::parse_from(["gix", "history", dir_arg])).is_ok());
+        let history = read_history(&dir.path().join(".git/gix/history")).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].changes[0].description, "line 2: *.log (duplicate of line 1)");
+        assert!(!dir.path().join(".gix").exists());
+        
+        assert!(run(Args::parse_from(["gix", "undo", dir_arg])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n*.log\nbuild/\n");
+        assert!(run(Args::parse_from(["gix", "undo", "--steps", "2", dir_arg])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n*.log\nbuild/\n");
+        
+        // Neither a new -o output nor a run with --no-history is recorded
+        let output = dir.path().join("optimized.gitignore");
+        assert!(run(Args::parse_from(["gix", input.to_str().unwrap(), "-o", output.to_str().unwrap()])).is_ok());
+        assert!(run(Args::parse_from(["gix", "--no-history", input.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\nbuild/\n");
+        assert!(read_history(&dir.path().join(".git/gix/history")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_run_other_dialects() {
+        let dir = tempfile::tempdir().unwrap();
+        let npmignore = dir.path().join(".npmignore");
+        std::fs::write(&npmignore, "*.test.js\nnode_modules/\n.DS_Store\n*.test.js\n").unwrap();
+        assert!(run(Args::parse_from(["gix", npmignore.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&npmignore).unwrap(), "*.test.js\n");
+        
+        let dockerignore = dir.path().join(".dockerignore");
+        std::fs::write(&dockerignore, "/build/\n./build\n*.log # debug\n").unwrap();
+        assert!(run(Args::parse_from(["gix", dockerignore.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&dockerignore).unwrap(), "/build/\n*.log # debug\n");
+        
+        // --dialect overrides the file name
+        let renamed = dir.path().join("ignore.txt");
+        std::fs::write(&renamed, "/build/\n./build\n").unwrap();
+        assert!(run(Args::parse_from(["gix", "--dialect", "dockerignore", renamed.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&renamed).unwrap(), "/build/\n");
+    }
+    
+    #[test]
+    fn test_dockerignore_patterns_are_not_subsumed() {
+        // Docker matches `*.log` at the context root only, so it leaves `logs/*.log` to its own line
+        let dir = tempfile::tempdir().unwrap();
+        let dockerignore = dir.path().join(".dockerignore");
+        let content = "*.log\nlogs/*.log\n**/*.tmp\nsub/x.tmp\n";
+        std::fs::write(&dockerignore, content).unwrap();
+        assert!(run(Args::parse_from(["gix", "--mode", "advanced", dockerignore.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&dockerignore).unwrap(), content);
+        assert!(run(Args::parse_from(["gix", "check", dockerignore.to_str().unwrap()])).is_ok());
+    }
+    
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_run_add() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "# Node.js\nnode_modules/\n\n# Build\nbuild/\n").unwrap();
+        let path = input.to_str().unwrap();
+        
+        // Only the temp dir's file may change, never the .gitignore of the working directory
+        let args = Args::parse_from(["gix", "add", "*.tgz", "-f", path]);
+        assert!(matches!(&args.command, Some(crate::cli::args::Command::Add { patterns, file: Some(file), .. }) if patterns == &["*.tgz"] && file == &input));
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "# Node.js\nnode_modules/\n*.tgz\n\n# Build\nbuild/\n");
+        
+        // Already present: nothing changes
+        assert!(run(Args::parse_from(["gix", "add", "build/", "-f", path])).is_ok());
+        
+        let covered = run(Args::parse_from(["gix", "add", "build/app.js", "-f", path]));
+        assert!(matches!(covered, Err(GixError::AlreadyCovered(_))));
+        assert!(run(Args::parse_from(["gix", "add", "build/app.js", "--section", "Build", "--force", "-f", path])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "# Node.js\nnode_modules/\n*.tgz\n\n# Build\nbuild/\nbuild/app.js\n");
+        
+        // Several at once: present and covered ones are skipped rather than failing the batch
+        let template = dir.path().join("Node.gitignore");
+        std::fs::write(&template, "node_modules/\nnpm-debug.log*\nyarn-error.log*\nbuild/Release\n.next/\n").unwrap();
+        assert!(run(Args::parse_from(["gix", "add", "--from", template.to_str().unwrap(), "--matching", "*.log*", "--matching", "build*", "-f", path])).is_ok());
+        let content = std::fs::read_to_string(&input).unwrap();
+        assert!(content.contains("npm-debug.log*\nyarn-error.log*\n"));
+        assert!(!content.contains("build/Release") && !content.contains(".next/"));
+    }
+    
+    #[test]
+    fn test_run_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n\n# Build\nbuild/\ndist/\n").unwrap();
+        std::fs::create_dir(dir.path().join("build")).unwrap();
+        std::fs::write(dir.path().join("build/app.js"), "").unwrap();
+        let path = input.to_str().unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "rm", "build/", "--dry-run", "-f", path])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n\n# Build\nbuild/\ndist/\n");
+        
+        assert!(run(Args::parse_from(["gix", "rm", "--line", "4", "-f", path])).is_ok());
+        assert!(run(Args::parse_from(["gix", "rm", "*.log", "-f", path])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "# Build\ndist/\n");
+        
+        assert!(matches!(run(Args::parse_from(["gix", "rm", "--line", "1", "-f", path])), Err(GixError::UsageError(_))));
+        assert!(matches!(run(Args::parse_from(["gix", "rm", "missing/", "-f", path])), Err(GixError::UsageError(_))));
+        
+        std::fs::write(&input, "# Logs\n*.log\nnpm-debug.log*\n\n# Editors\n.idea/\n.vscode/\n*.swp\n").unwrap();
+        assert!(run(Args::parse_from(["gix", "rm", "--matching", "*.log*", "--matching", ".idea*", "-f", path])).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "# Logs\n\n# Editors\n.vscode/\n*.swp\n");
+        assert!(matches!(run(Args::parse_from(["gix", "rm", "--matching", "*.tmp", "-f", path])), Err(GixError::UsageError(_))));
+    }
+    
+    #[test]
+    fn test_run_keeps_metadata_in_git_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n*.log\n").unwrap();
+        
+        assert!(run(Args::parse_from(["gix", "--metadata", "git", "--audit-log", "--backup", input.to_str().unwrap()])).is_ok());
+        
+        let backups = crate::utils::backups_of(&input, &Args::parse_from(["gix", "--metadata", "git"]).backup_policy()).unwrap();
+        assert!(backups[0].starts_with(dir.path().canonicalize().unwrap().join(".git/gix/backups")));
+        assert_eq!(std::fs::read_to_string(&backups[0]).unwrap(), "*.log\n*.log\n");
+        assert!(dir.path().join(".git/gix/audit.log").is_file());
+        assert!(!dir.path().join(".gix").exists());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+        
+        let dir_arg = dir.path().to_str().unwrap();
+        assert!(run(Args::parse_from(["gix", "--metadata", "git", "audit-log", "verify", dir_arg])).is_ok());
+    }
+
+    #[test]
+    fn test_run_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("a.gitignore");
+        let second = dir.path().join("b.gitignore");
+        let output = dir.path().join(".gitignore");
+        std::fs::write(&first, "*.log\n!keep.log\n").unwrap();
+        std::fs::write(&second, "# Build\n*.log\ntarget/\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "merge", first.to_str().unwrap(), second.to_str().unwrap(), "-o", output.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        assert_eq!(
+            std::fs::read_to_string(&output).unwrap(),
+            format!("# From {}\n*.log\n!keep.log\n\n# From {}\n# Build\ntarget/\n", first.display(), second.display())
+        );
+    }
+
+    #[test]
+    fn test_run_test_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".gitignore");
+        std::fs::write(&path, "build/\n").unwrap();
+        std::fs::create_dir(dir.path().join("build")).unwrap();
+        
+        let args = Args::parse_from(["gix", "test-path", "build", "src/main.rs", "-f", path.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        
+        let args = Args::parse_from(["gix", "test-path", "a", "-f", dir.path().join("missing").to_str().unwrap()]);
+        assert!(matches!(run(args), Err(GixError::FileNotFound(_))));
+    }
+
+    #[test]
+    fn test_run_explain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".gitignore");
+        std::fs::write(&path, "build/\n!build/keep.txt\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "explain", "build/keep.txt", "-f", path.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+    }
+
+    #[test]
+    fn test_run_compare() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".gitignore");
+        let template = dir.path().join("Rust.gitignore");
+        std::fs::write(&path, "target/\n*.log\n").unwrap();
+        std::fs::write(&template, "target/\n**/*.rs.bk\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "compare", path.to_str().unwrap(), "--template", template.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+        let args = Args::parse_from(["gix", "compare", path.to_str().unwrap(), "--template", "no-such-stack"]);
+        assert!(matches!(run(args), Err(GixError::UnknownCategory(_))));
+    }
+
+    #[test]
+    fn test_run_suggest() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".vscode")).unwrap();
+        let path = dir.path().join(".gitignore");
+        
+        assert!(run(Args::parse_from(["gix", "suggest", path.to_str().unwrap()])).is_ok());
+        std::fs::write(&path, ".vscode/\n").unwrap();
+        assert!(run(Args::parse_from(["gix", "suggest", path.to_str().unwrap()])).is_ok());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), ".vscode/\n");
+    }
+
+    #[test]
+    fn test_run_sync_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let gitignore = dir.path().join(".gitignore");
+        let dockerignore = dir.path().join(".dockerignore");
+        std::fs::write(&gitignore, "target/\n.env\n").unwrap();
+        std::fs::write(&dockerignore, "target\n*.pem\n").unwrap();
+        
+        let check = |sync: bool| {
+            let mut argv = vec!["gix", "sync-check", gitignore.to_str().unwrap(), "--against", dockerignore.to_str().unwrap()];
+            if sync {
+                argv.push("--sync");
+            }
+            run(Args::parse_from(argv))
+        };
+        assert!(matches!(check(false), Err(GixError::CheckFailed(2))));
+        
+        assert!(check(true).is_ok());
+        let synced = std::fs::read_to_string(&dockerignore).unwrap();
+        assert!(synced.starts_with("target\n*.pem\n\n# Synced from "));
+        assert!(synced.ends_with("by gix\n**/.env\n"));
+        assert!(std::fs::read_to_string(&gitignore).unwrap().ends_with("by gix\n/*.pem\n"));
+        assert!(check(false).is_ok());
+    }
+
+    #[test]
+    fn test_run_dry_run_with_timings() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join(".gitignore");
+        std::fs::write(&input, "*.log\n*.log\n\n\nbuild/\n").unwrap();
+        
+        let args = Args::parse_from(["gix", "--dry-run", "--timings", "--mode", "aggressive", input.to_str().unwrap()]);
+        assert!(args.timings);
+        assert!(run(args).is_ok());
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "*.log\n*.log\n\n\nbuild/\n");
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_run_dataset_update_refuses_offline() {
+        let args = Args::parse_from(["gix", "dataset", "update", "--offline"]);
+        assert!(matches!(run(args), Err(GixError::NetworkError(_))));
+    }
+
+    #[test]
+    fn test_run_with_nonexistent_file() {
+        let args = Args::parse_from(["gix", "nonexistent.gitignore"]);
+        let result = run(args);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,72 @@
+/// Name of the attributes file read next to an ignore file
+pub const ATTRIBUTES_FILE_NAME: &str = ".gitattributes";
+
+/// One line of a `.gitattributes` file: a pattern and the attributes it assigns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeRule {
+    /// Line number (1-indexed)
+    pub line_number: usize,
+    /// The pattern, in gitignore syntax without negation
+    pub pattern: String,
+    /// The attributes as written: `text`, `-diff`, `!eol`, `filter=lfs`, ...
+    pub attributes: Vec<String>,
+}
+
+impl AttributeRule {
+    /// Check if the rule sets `name`, either alone or with a value
+    pub fn sets(&self, name: &str) -> bool {
+        self.attributes.iter().any(|attribute| attribute == name || self.value_of(attribute, name).is_some())
+    }
+
+    /// Get the value the rule gives `name` with `name=value`
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().rev().find_map(|attribute| self.value_of(attribute, name))
+    }
+
+    /// Check if files the rule matches are stored in Git LFS
+    pub fn is_lfs(&self) -> bool {
+        self.value("filter") == Some("lfs")
+    }
+
+    fn value_of<'a>(&self, attribute: &'a str, name: &str) -> Option<&'a str> {
+        attribute.strip_prefix(name)?.strip_prefix('=')
+    }
+}
+
+/// Parse the rules of a `.gitattributes` file
+///
+/// Comments, blank lines and macro definitions (`[attr]name ...`) are skipped, and so are
+/// quoted patterns, which gix doesn't unquote.
+pub fn parse_gitattributes(content: &str) -> Vec<AttributeRule> {
+    content.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next()?;
+            if pattern.starts_with('#') || pattern.starts_with("[attr]") || pattern.starts_with('"') {
+                return None;
+            }
+            Some(AttributeRule {
+                line_number: index + 1,
+                pattern: pattern.to_string(),
+                attributes: fields.map(str::to_string).collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gitattributes() {
+        let rules = parse_gitattributes("# LFS\n*.psd filter=lfs diff=lfs merge=lfs -text\n\n[attr]binary -diff -text\n/docs export-ignore\n");
+        assert_eq!(rules.len(), 2);
+        assert_eq!((rules[0].line_number, rules[0].pattern.as_str()), (2, "*.psd"));
+        assert!(rules[0].is_lfs());
+        assert!(!rules[0].sets("text"));
+        assert!(rules[1].sets("export-ignore"));
+        assert!(!rules[1].is_lfs());
+    }
+}
@@ -0,0 +1,71 @@
+use crate::models::GitignoreFile;
+use crate::utils::patterns::ignoring_pattern;
+use std::collections::BTreeMap;
+
+/// A pattern that ignores files git already tracks
+///
+/// Git keeps tracking such files, so the pattern silently has no effect on them until they
+/// are removed from the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedMatch {
+    /// Line number of the pattern (1-indexed)
+    pub line_number: usize,
+    /// The pattern
+    pub pattern: String,
+    /// Tracked paths the pattern ignores, relative to the file's directory
+    pub paths: Vec<String>,
+}
+
+/// Find the patterns that ignore tracked files, in line order
+///
+/// Each path is attributed to the pattern that decides it is ignored, so a path re-included by
+/// a later negation is not reported.
+pub fn find_ignored_tracked_files(file: &GitignoreFile, tracked: &[String]) -> Vec<TrackedMatch> {
+    let patterns: Vec<(usize, String)> = file.entries.iter()
+        .filter_map(|entry| Some((entry.line_number, entry.comparison_pattern()?)))
+        .collect();
+    let globs: Vec<&str> = patterns.iter().map(|(_, pattern)| pattern.as_str()).collect();
+
+    let mut matches: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for path in tracked {
+        if let Some(index) = ignoring_pattern(&globs, path) {
+            matches.entry(index).or_default().push(path.clone());
+        }
+    }
+
+    matches.into_iter()
+        .map(|(index, paths)| TrackedMatch {
+            line_number: patterns[index].0,
+            pattern: patterns[index].1.clone(),
+            paths,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_patterns_ignoring_tracked_files() {
+        let file = parse_gitignore("# Build\nbuild/\n*.log\n!keep.log\n*.tmp").unwrap();
+        let tracked: Vec<String> = ["build/out.bin", "build/sub/a.txt", "keep.log", "app.log", "src/main.rs"]
+            .iter()
+            .map(|path| path.to_string())
+            .collect();
+
+        assert_eq!(find_ignored_tracked_files(&file, &tracked), vec![
+            TrackedMatch {
+                line_number: 2,
+                pattern: "build/".to_string(),
+                paths: vec!["build/out.bin".to_string(), "build/sub/a.txt".to_string()],
+            },
+            TrackedMatch {
+                line_number: 3,
+                pattern: "*.log".to_string(),
+                paths: vec!["app.log".to_string()],
+            },
+        ]);
+    }
+}
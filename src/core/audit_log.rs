@@ -0,0 +1,312 @@
+use crate::models::GixError;
+use crate::utils::git::{metadata_dir, repository_root, MetadataLocation};
+use crate::utils::{path_relative_to, read_gitignore_file, stable_hash};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the audit log in the metadata directory (see `utils::git::metadata_dir`)
+pub const AUDIT_LOG_NAME: &str = "audit.log";
+
+/// First line of a new audit log
+const HEADER: &str = "# gix audit log: one tab-separated record per write, chained by hash; do not edit";
+
+/// Chain value preceding the first record
+const CHAIN_START: &str = "0000000000000000";
+
+/// One write performed by gix
+///
+/// Only hashes and line counts of the contents are kept, never the contents themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// When the file was written, in UTC (RFC 3339)
+    pub timestamp: String,
+    /// The written file, relative to the repository root when inside it
+    pub file: String,
+    /// The gix command that wrote it (`optimize`, `organize`, `merge`, ...)
+    pub command: String,
+    /// Optimization mode in effect
+    pub mode: String,
+    /// Options that influenced the write, as given on the command line
+    pub options: Vec<String>,
+    /// Hash of the contents before the write, `None` if the file was created
+    pub before: Option<String>,
+    /// Hash of the contents after the write
+    pub after: String,
+    /// Lines added by the write
+    pub added: usize,
+    /// Lines removed by the write
+    pub removed: usize,
+}
+
+impl AuditRecord {
+    /// Describe a write of `after` over `before` (`None` for a new file), timestamped now
+    pub fn new(file: &str, command: &str, mode: &str, options: &[String], before: Option<&str>, after: &str) -> Self {
+        let (added, removed) = count_changes(before.unwrap_or(""), after);
+        Self {
+            timestamp: format_timestamp(SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())),
+            file: file.to_string(),
+            command: command.to_string(),
+            mode: mode.to_string(),
+            options: options.to_vec(),
+            before: before.map(stable_hash),
+            after: stable_hash(after),
+            added,
+            removed,
+        }
+    }
+
+    /// Render the record's fields, tab-separated, without its chain value
+    fn fields(&self) -> String {
+        let clean = |value: &str| value.replace(['\t', '\n', '\r'], " ");
+        [
+            clean(&self.timestamp),
+            clean(&self.file),
+            clean(&self.command),
+            clean(&self.mode),
+            clean(&self.options.join(" ")),
+            self.before.clone().unwrap_or_else(|| "-".to_string()),
+            self.after.clone(),
+            format!("+{}", self.added),
+            format!("-{}", self.removed),
+        ].join("\t")
+    }
+
+    /// Parse a record line into the record and its chain value
+    fn parse(line: &str) -> Option<(Self, String)> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [timestamp, file, command, mode, options, before, after, added, removed, chain] = fields[..] else {
+            return None;
+        };
+        let record = Self {
+            timestamp: timestamp.to_string(),
+            file: file.to_string(),
+            command: command.to_string(),
+            mode: mode.to_string(),
+            options: options.split_whitespace().map(str::to_string).collect(),
+            before: (before != "-").then(|| before.to_string()),
+            after: after.to_string(),
+            added: added.strip_prefix('+')?.parse().ok()?,
+            removed: removed.strip_prefix('-')?.parse().ok()?,
+        };
+        Some((record, chain.to_string()))
+    }
+}
+
+/// The outcome of `gix audit-log verify`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditVerification {
+    /// Records in the log
+    pub records: usize,
+    /// Line of the first record that doesn't parse or whose chain value doesn't match, if any
+    pub broken_line: Option<usize>,
+    /// Files whose contents no longer match the last write gix logged for them
+    pub changed_since: Vec<String>,
+}
+
+impl AuditVerification {
+    /// Check if every record is intact
+    pub fn is_intact(&self) -> bool {
+        self.broken_line.is_none()
+    }
+}
+
+/// Path of the audit log of the repository containing `dir`, or of `dir` itself outside a repository
+pub fn audit_log_path(dir: &Path, location: MetadataLocation) -> PathBuf {
+    metadata_dir(dir, location).join(AUDIT_LOG_NAME)
+}
+
+/// Record a write of `path` in the audit log of its repository
+///
+/// The log is only ever appended to; each record carries a hash chained from the one before,
+/// so edits and deletions of earlier records are caught by `verify_audit_log`.
+pub fn log_write(path: &Path, location: MetadataLocation, command: &str, mode: &str, options: &[String], before: Option<&str>, after: &str) -> Result<PathBuf, GixError> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let log = audit_log_path(dir, location);
+    let root = repository_root(dir).unwrap_or_else(|| dir.to_path_buf());
+    let file = path.canonicalize().ok()
+        .and_then(|path| path_relative_to(&root, &path))
+        .unwrap_or_else(|| path.display().to_string());
+
+    append_record(&log, &AuditRecord::new(&file, command, mode, options, before, after))?;
+    Ok(log)
+}
+
+/// Append a record to the log at `log`, creating it if needed
+pub fn append_record(log: &Path, record: &AuditRecord) -> Result<(), GixError> {
+    let previous = match log.is_file() {
+        true => last_chain(&read_gitignore_file(log)?),
+        false => None,
+    };
+    if let Some(parent) = log.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(log)?;
+    if previous.is_none() && file.metadata()?.len() == 0 {
+        writeln!(file, "{}", HEADER)?;
+    }
+    let fields = record.fields();
+    let chain = chain(previous.as_deref().unwrap_or(CHAIN_START), &fields);
+    writeln!(file, "{}\t{}", fields, chain)?;
+    Ok(())
+}
+
+/// Read the records of an audit log, skipping lines that don't parse
+pub fn read_audit_log(log: &Path) -> Result<Vec<AuditRecord>, GixError> {
+    Ok(read_gitignore_file(log)?
+        .lines()
+        .filter_map(AuditRecord::parse)
+        .map(|(record, _)| record)
+        .collect())
+}
+
+/// Check the hash chain of an audit log and compare the logged files with their current contents
+///
+/// Paths of logged files are resolved against `root`, the repository the log belongs to.
+pub fn verify_audit_log(log: &Path, root: &Path) -> Result<AuditVerification, GixError> {
+    let mut verification = AuditVerification::default();
+    let mut previous = CHAIN_START.to_string();
+    let mut latest: HashMap<String, String> = HashMap::new();
+
+    for (index, line) in read_gitignore_file(log)?.lines().enumerate() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        let Some((record, recorded)) = AuditRecord::parse(line) else {
+            verification.broken_line.get_or_insert(index + 1);
+            continue;
+        };
+        verification.records += 1;
+        let expected = chain(&previous, &record.fields());
+        if recorded != expected {
+            verification.broken_line.get_or_insert(index + 1);
+        }
+        previous = recorded;
+        latest.insert(record.file, record.after);
+    }
+
+    let mut files: Vec<(String, String)> = latest.into_iter().collect();
+    files.sort();
+    for (file, after) in files {
+        let current = fs::read_to_string(root.join(&file)).ok().map(|content| stable_hash(&content));
+        if current.as_deref() != Some(after.as_str()) {
+            verification.changed_since.push(file);
+        }
+    }
+    Ok(verification)
+}
+
+fn chain(previous: &str, fields: &str) -> String {
+    stable_hash(&format!("{}\t{}", previous, fields))
+}
+
+fn last_chain(content: &str) -> Option<String> {
+    content.lines().rev().find_map(AuditRecord::parse).map(|(_, chain)| chain)
+}
+
+/// Count the lines `after` adds to and removes from `before`, ignoring their order
+fn count_changes(before: &str, after: &str) -> (usize, usize) {
+    let mut counts: HashMap<&str, isize> = HashMap::new();
+    for line in before.lines() {
+        *counts.entry(line).or_default() -= 1;
+    }
+    for line in after.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+    let added = counts.values().filter(|count| **count > 0).map(|count| count.unsigned_abs()).sum();
+    let removed = counts.values().filter(|count| **count < 0).map(|count| count.unsigned_abs()).sum();
+    (added, removed)
+}
+
+/// Format seconds since the Unix epoch as an RFC 3339 UTC timestamp
+pub(crate) fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rest = secs % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, rest / 3_600, rest % 3_600 / 60, rest % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_790_000_000), "2026-09-21T14:13:20Z");
+    }
+
+    #[test]
+    fn test_count_changes() {
+        assert_eq!(count_changes("*.log\n*.log\ntarget/\n", "*.log\ntarget/\nbuild/\n"), (1, 1));
+        assert_eq!(count_changes("", "a\nb\n"), (2, 0));
+    }
+
+    #[test]
+    fn test_log_and_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        let path = dir.path().join(".gitignore");
+        fs::write(&path, "*.log\n").unwrap();
+
+        let options = vec!["--prune-unused".to_string()];
+        let log = log_write(&path, MetadataLocation::Worktree, "optimize", "standard", &options, Some("*.log\n*.log\n"), "*.log\n").unwrap();
+        assert_eq!(log, dir.path().canonicalize().unwrap().join(".gix").join(AUDIT_LOG_NAME));
+        log_write(&path, MetadataLocation::Worktree, "organize", "standard", &[], Some("*.log\n"), "*.log\n").unwrap();
+
+        let records = read_audit_log(&log).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].file, ".gitignore");
+        assert_eq!(records[0].options, options);
+        assert_eq!((records[0].added, records[0].removed), (0, 1));
+        assert!(!fs::read_to_string(&log).unwrap().contains("*.log"));
+
+        let root = dir.path().canonicalize().unwrap();
+        let verification = verify_audit_log(&log, &root).unwrap();
+        assert!(verification.is_intact());
+        assert_eq!(verification.records, 2);
+        assert!(verification.changed_since.is_empty());
+
+        fs::write(&path, "*.tmp\n").unwrap();
+        let tampered = fs::read_to_string(&log).unwrap().replace("\toptimize\t", "\torganize\t");
+        fs::write(&log, tampered).unwrap();
+        let verification = verify_audit_log(&log, &root).unwrap();
+        assert_eq!(verification.broken_line, Some(2));
+        assert_eq!(verification.changed_since, vec![".gitignore"]);
+    }
+
+    #[test]
+    fn test_log_in_git_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        let path = dir.path().join("src/.gitignore");
+        fs::write(&path, "*.log\n").unwrap();
+
+        let log = log_write(&path, MetadataLocation::Git, "optimize", "standard", &[], None, "*.log\n").unwrap();
+        assert_eq!(log, dir.path().canonicalize().unwrap().join(".git/gix").join(AUDIT_LOG_NAME));
+        assert_eq!(read_audit_log(&log).unwrap()[0].file, "src/.gitignore");
+        assert!(!dir.path().join(".gix").exists());
+    }
+}
@@ -0,0 +1,138 @@
+use crate::models::GitignoreFile;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+
+/// Minimum number of lines for a repeated run to count as a block
+pub const MIN_BLOCK_LINES: usize = 3;
+
+/// A run of consecutive lines that appears a second time, e.g. a template pasted twice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepeatedBlock {
+    /// Line number where the first copy starts
+    pub original_start: usize,
+    /// Line number where the repeated copy starts
+    pub repeat_start: usize,
+    /// Number of lines in the block
+    pub len: usize,
+    /// Number of pattern lines in the block
+    pub pattern_count: usize,
+}
+
+impl RepeatedBlock {
+    /// Line numbers of the first copy
+    pub fn original_lines(&self) -> RangeInclusive<usize> {
+        self.original_start..=self.original_start + self.len - 1
+    }
+
+    /// Line numbers of the repeated copy
+    pub fn repeat_lines(&self) -> RangeInclusive<usize> {
+        self.repeat_start..=self.repeat_start + self.len - 1
+    }
+}
+
+/// Find blocks of at least `MIN_BLOCK_LINES` identical consecutive lines that occur twice
+pub fn find_repeated_blocks(file: &GitignoreFile) -> Vec<RepeatedBlock> {
+    let entries = &file.entries;
+    let mut blocks = Vec::new();
+    let mut start = 0;
+
+    // A repeat can only start where an earlier line reads the same, and never on a blank line
+    let mut positions: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, entry) in entries.iter().enumerate().filter(|(_, entry)| !entry.is_blank()) {
+        positions.entry(entry.original.as_str()).or_default().push(index);
+    }
+
+    while start < entries.len() {
+        if entries[start].is_blank() {
+            start += 1;
+            continue;
+        }
+
+        // Find the longest earlier run that this position repeats, without overlapping it
+        let mut best: Option<(usize, usize)> = None;
+        for &earlier in positions[entries[start].original.as_str()].iter().take_while(|&&earlier| earlier < start) {
+            let mut len = 0;
+            while start + len < entries.len()
+                && earlier + len < start
+                && entries[earlier + len].original == entries[start + len].original
+            {
+                len += 1;
+            }
+
+            // Blank lines at the edges belong to the surrounding layout, not the block
+            while len > 0 && entries[start + len - 1].is_blank() {
+                len -= 1;
+            }
+
+            if len > best.map(|(_, best_len)| best_len).unwrap_or(0) {
+                best = Some((earlier, len));
+            }
+        }
+
+        match best {
+            Some((earlier, len)) if len >= MIN_BLOCK_LINES => {
+                let pattern_count = entries[start..start + len].iter().filter(|e| e.is_pattern()).count();
+                if pattern_count > 0 {
+                    blocks.push(RepeatedBlock {
+                        original_start: entries[earlier].line_number,
+                        repeat_start: entries[start].line_number,
+                        len,
+                        pattern_count,
+                    });
+                    start += len;
+                    continue;
+                }
+                start += 1;
+            }
+            _ => start += 1,
+        }
+    }
+
+    blocks
+}
+
+/// Line numbers covered by the repeated copies of the given blocks
+pub fn repeated_block_lines(blocks: &[RepeatedBlock]) -> HashSet<usize> {
+    blocks.iter().flat_map(|block| block.repeat_lines()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_find_double_pasted_template() {
+        let template = "# Python\n*.pyc\n__pycache__/\nvenv/\n";
+        let content = format!("{}\n{}", template, template);
+        let file = parse_gitignore(&content).unwrap();
+        let blocks = find_repeated_blocks(&file);
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].original_lines(), 1..=4);
+        assert_eq!(blocks[0].repeat_lines(), 6..=9);
+        assert_eq!(blocks[0].pattern_count, 3);
+    }
+
+    #[test]
+    fn test_short_runs_are_not_blocks() {
+        let file = parse_gitignore("*.log\nbuild/\n*.log\nbuild/").unwrap();
+        assert!(find_repeated_blocks(&file).is_empty());
+    }
+
+    #[test]
+    fn test_comment_only_runs_are_not_blocks() {
+        let file = parse_gitignore("# a\n# b\n# c\n*.log\n# a\n# b\n# c").unwrap();
+        assert!(find_repeated_blocks(&file).is_empty());
+    }
+
+    #[test]
+    fn test_block_not_adjacent_to_original() {
+        let file = parse_gitignore("*.log\n*.tmp\n*.bak\nbuild/\n*.log\n*.tmp\n*.bak").unwrap();
+        let blocks = find_repeated_blocks(&file);
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].repeat_lines(), 5..=7);
+        assert_eq!(repeated_block_lines(&blocks), (5..=7).collect());
+    }
+}
@@ -1,4 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "dataset")]
+use std::path::Path;
+#[cfg(feature = "dataset")]
+use crate::core::dataset::Dataset;
+#[cfg(feature = "dataset")]
+use crate::models::GixError;
+use crate::core::plugins::PluginRegistry;
+use crate::utils::patterns::GlobPattern;
+use crate::utils::tokens::Token;
 
 /// Represents a category of gitignore patterns
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -30,6 +39,30 @@ impl PatternCategory {
         }
     }
     
+    /// Get the name of the kind of category, as used in messages
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            PatternCategory::Language(_) => "language",
+            PatternCategory::Framework(_) => "framework",
+            PatternCategory::Tool(_) => "tool",
+            PatternCategory::OperatingSystem(_) => "operating system",
+            PatternCategory::Custom(_) => "custom category",
+            PatternCategory::Uncategorized => "category",
+        }
+    }
+    
+    /// Get the kind of category as written in `category_order` of `gix.toml`
+    pub fn kind_key(&self) -> &'static str {
+        match self {
+            PatternCategory::Language(_) => "language",
+            PatternCategory::Framework(_) => "framework",
+            PatternCategory::Tool(_) => "tool",
+            PatternCategory::OperatingSystem(_) => "os",
+            PatternCategory::Custom(_) => "custom",
+            PatternCategory::Uncategorized => "uncategorized",
+        }
+    }
+    
     /// Get the short name for the category
     pub fn short_name(&self) -> String {
         match self {
@@ -43,25 +76,51 @@ impl PatternCategory {
     }
 }
 
+/// Confidence of a pattern written exactly as a known one
+pub const EXACT_MATCH: f32 = 1.0;
+/// Confidence of a pattern matching the same paths as a known one, anchored or not (`/target`, `target/`)
+pub const EQUIVALENT_MATCH: f32 = 0.9;
+/// Highest confidence of a pattern a known glob selects (`*.pyc` by `*.py[cod]`)
+///
+/// Globs spelling out less of the pattern score less, down to halfway to `DIRECTORY_MATCH`,
+/// so `npm-debug.log` goes with `npm-debug.log*` rather than `*.log`.
+pub const GLOB_MATCH: f32 = 0.8;
+/// Confidence of a pattern inside a known directory (`.vscode/settings.json` under `.vscode/`)
+pub const DIRECTORY_MATCH: f32 = 0.7;
+/// Confidence of a pattern that only looks project-specific
+pub const PROJECT_MATCH: f32 = 0.5;
+
+/// Name of the custom category of patterns several stacks share, which `gix organize` groups together
+pub const COMMON_CATEGORY: &str = "Common";
+
+/// Directories whose contents are usually specific to the project
+const PROJECT_DIRECTORIES: &[&str] = &["custom", "project", "local", "temp", "tmp"];
+
 /// Categorizer for gitignore patterns
+///
+/// Known patterns are kept in sorted maps so a pattern matching several categories is always
+/// assigned the same one.
 pub struct PatternCategorizer {
     /// Language-specific patterns
-    language_patterns: HashMap<String, Vec<String>>,
+    language_patterns: BTreeMap<String, Vec<String>>,
     /// Framework-specific patterns
-    framework_patterns: HashMap<String, Vec<String>>,
+    framework_patterns: BTreeMap<String, Vec<String>>,
     /// Tool-specific patterns
-    tool_patterns: HashMap<String, Vec<String>>,
+    tool_patterns: BTreeMap<String, Vec<String>>,
     /// OS-specific patterns
-    os_patterns: HashMap<String, Vec<String>>,
+    os_patterns: BTreeMap<String, Vec<String>>,
+    /// Plugins asked before the known patterns
+    plugins: PluginRegistry,
 }
 
 impl Default for PatternCategorizer {
     fn default() -> Self {
         let mut categorizer = Self {
-            language_patterns: HashMap::new(),
-            framework_patterns: HashMap::new(),
-            tool_patterns: HashMap::new(),
-            os_patterns: HashMap::new(),
+            language_patterns: BTreeMap::new(),
+            framework_patterns: BTreeMap::new(),
+            tool_patterns: BTreeMap::new(),
+            os_patterns: BTreeMap::new(),
+            plugins: PluginRegistry::new(),
         };
         
         // Initialize with common patterns
@@ -76,231 +135,206 @@ impl PatternCategorizer {
         Self::default()
     }
     
-    /// Initialize with common gitignore patterns
-    fn initialize_common_patterns(&mut self) {
-        // Language patterns
-        self.add_language_patterns("Python", &[
-            "*.py[cod]", "*.so", "__pycache__/", "*.egg", "*.egg-info/",
-            "dist/", "build/", "eggs/", "parts/", "bin/", "var/",
-            "sdist/", "develop-eggs/", "*.egg-info/", ".installed.cfg",
-            "*.manifest", "*.spec", "pip-log.txt", "pip-delete-this-directory.txt",
-            ".Python", "env/", "venv/", "ENV/", "env.bak/", "venv.bak/",
-            ".pytest_cache/", ".coverage", "htmlcov/", ".tox/", ".nox/",
-            ".cache", ".mypy_cache/", ".dmypy.json", "dmypy.json",
-        ]);
-        
-        self.add_language_patterns("Node.js", &[
-            "node_modules/", "npm-debug.log*", "yarn-debug.log*", "yarn-error.log*",
-            "lerna-debug.log*", ".npm", ".eslintcache", ".node_repl_history",
-            "*.tgz", ".yarn-integrity", ".env.local", ".env.development.local",
-            ".env.test.local", ".env.production.local", "coverage/", ".nyc_output",
-            ".grunt", "bower_components/", ".lock-wscript", "build/Release",
-            ".node_repl_history", "*.tgz", ".yarn-integrity", ".next/", "out/",
-        ]);
-        
-        self.add_language_patterns("Java", &[
-            "*.class", "*.log", "*.ctxt", ".mtj.tmp/", "*.jar", "*.war",
-            "*.nar", "*.ear", "*.zip", "*.tar.gz", "*.rar", "hs_err_pid*",
-            "replay_pid*", "target/", "!.mvn/wrapper/maven-wrapper.jar",
-            "!**/src/main/**/target/", "!**/src/test/**/target/", ".idea/",
-            "*.iws", "*.iml", "*.ipr", ".gradle/", "build/", "!gradle/wrapper/gradle-wrapper.jar",
-        ]);
-        
-        self.add_language_patterns("Rust", &[
-            "target/", "Cargo.lock", "*.pdb", "*.exe", "*.dll", "*.so", "*.dylib",
-            "*.rlib", "*.rmeta", "*.rbc", "*.dSYM/", "*.su", "*.idb", "*.pdb",
-            "*.ilk", "*.exp", "*.lib", "*.a", "*.o", "*.so", "*.dylib",
-        ]);
-        
-        self.add_language_patterns("Go", &[
-            "*.exe", "*.exe~", "*.dll", "*.so", "*.dylib", "*.test", "*.out",
-            "go.work", "vendor/", ".go-version",
-        ]);
-        
-        // Framework patterns
-        self.add_framework_patterns("React", &[
-            "node_modules/", ".pnp", ".pnp.js", "coverage/", "build/",
-            ".DS_Store", ".env.local", ".env.development.local",
-            ".env.test.local", ".env.production.local", "npm-debug.log*",
-            "yarn-debug.log*", "yarn-error.log*", ".next/", "out/",
-        ]);
-        
-        self.add_framework_patterns("Django", &[
-            "*.log", "local_settings.py", "db.sqlite3", "db.sqlite3-journal",
-            "media/", "staticfiles/", ".env", ".venv", "env/", "venv/",
-            "ENV/", "env.bak/", "venv.bak/", ".pytest_cache/",
-        ]);
-        
-        self.add_framework_patterns("Spring", &[
-            "*.class", "*.log", "*.ctxt", ".mtj.tmp/", "*.jar", "*.war",
-            "*.nar", "*.ear", "*.zip", "*.tar.gz", "*.rar", "hs_err_pid*",
-            "replay_pid*", "target/", ".idea/", "*.iws", "*.iml", "*.ipr",
-        ]);
-        
-        // Tool patterns
-        self.add_tool_patterns("VSCode", &[
-            ".vscode/", "*.code-workspace", ".vscode/settings.json",
-            ".vscode/tasks.json", ".vscode/launch.json", ".vscode/extensions.json",
-        ]);
-        
-        self.add_tool_patterns("IntelliJ", &[
-            ".idea/", "*.iws", "*.iml", "*.ipr", ".idea_modules/",
-        ]);
-        
-        self.add_tool_patterns("Eclipse", &[
-            ".metadata", "bin/", "tmp/", "*.tmp", "*.bak", "*.swp", "*~.nib",
-            "local.properties", ".settings/", ".loadpath", ".recommenders",
-        ]);
-        
-        self.add_tool_patterns("Vim", &[
-            "*.swp", "*.swo", "*~", ".vim/", ".viminfo", ".vimrc",
-        ]);
-        
-        self.add_tool_patterns("Emacs", &[
-            "*~", "#*#", ".#*", ".emacs.desktop", ".emacs.desktop.lock",
-            "*.elc", "auto-save-list", "tramp", ".emacs.desktop.lock",
-        ]);
-        
-        // OS patterns
-        self.add_os_patterns("macOS", &[
-            ".DS_Store", ".AppleDouble", ".LSOverride", "Icon", "._*",
-            ".DocumentRevisions-V100", ".fseventsd", ".Spotlight-V100",
-            ".TemporaryItems", ".Trashes", ".VolumeIcon.icns", ".com.apple.timemachine.donotpresent",
-            ".AppleDB", ".AppleDesktop", "Network Trash Folder", "Temporary Items",
-            ".apdisk", ".VolumeIcon.icns", ".fseventsd", ".Spotlight-V100",
-        ]);
-        
-        self.add_os_patterns("Windows", &[
-            "Thumbs.db", "Thumbs.db:encryptable", "ehthumbs.db", "ehthumbs_vista.db",
-            "*.tmp", "*.temp", "Desktop.ini", "$RECYCLE.BIN/", "*.cab",
-            "*.msi", "*.msix", "*.msm", "*.msp", "*.lnk", "*.stackdump",
-        ]);
+    /// Create a categorizer that knows the patterns of the given dataset
+    #[cfg(feature = "dataset")]
+    pub fn from_dataset(dataset: &Dataset) -> Self {
+        let mut categorizer = Self {
+            language_patterns: BTreeMap::new(),
+            framework_patterns: BTreeMap::new(),
+            tool_patterns: BTreeMap::new(),
+            os_patterns: BTreeMap::new(),
+            plugins: PluginRegistry::new(),
+        };
         
-        self.add_os_patterns("Linux", &[
-            "*~", "*.swp", "*.swo", "*~", ".nfs*", ".fuse_hidden*",
-            ".directory", ".Trash-*", ".nfs*", ".fuse_hidden*",
-        ]);
+        categorizer.load_dataset(dataset);
+        categorizer
+    }
+    
+    /// Create a categorizer that knows the embedded patterns and the category definitions in `path`
+    ///
+    /// The file is TOML (see `Dataset::parse_definitions`); its categories extend the embedded
+    /// ones of the same name.
+    #[cfg(feature = "dataset")]
+    pub fn from_file(path: &Path) -> Result<Self, GixError> {
+        let mut dataset = Dataset::embedded();
+        dataset.merge(Dataset::read_definitions(path)?);
+        Ok(Self::from_dataset(&dataset))
     }
     
-    /// Add language-specific patterns
-    fn add_language_patterns(&mut self, language: &str, patterns: &[&str]) {
-        self.language_patterns.insert(
-            language.to_string(),
-            patterns.iter().map(|s| s.to_string()).collect()
-        );
+    /// Let plugins categorize patterns first, falling back to the known patterns
+    pub fn with_plugins(mut self, plugins: PluginRegistry) -> Self {
+        self.plugins = plugins;
+        self
     }
     
-    /// Add framework-specific patterns
-    fn add_framework_patterns(&mut self, framework: &str, patterns: &[&str]) {
-        self.framework_patterns.insert(
-            framework.to_string(),
-            patterns.iter().map(|s| s.to_string()).collect()
-        );
+    /// Ask the plugins about `patterns` at once, so categorizing them one by one afterwards
+    /// doesn't run a plugin command per pattern
+    pub fn prefetch<S: AsRef<str>>(&self, patterns: impl IntoIterator<Item = S>) {
+        if !self.plugins.is_empty() {
+            let patterns: Vec<String> = patterns.into_iter()
+                .map(|pattern| pattern.as_ref().trim().trim_start_matches('!').to_string())
+                .collect();
+            self.plugins.categorize_patterns(&patterns);
+        }
     }
     
-    /// Add tool-specific patterns
-    fn add_tool_patterns(&mut self, tool: &str, patterns: &[&str]) {
-        self.tool_patterns.insert(
-            tool.to_string(),
-            patterns.iter().map(|s| s.to_string()).collect()
-        );
+    /// Initialize with common gitignore patterns from the built-in dataset
+    #[cfg(feature = "dataset")]
+    fn initialize_common_patterns(&mut self) {
+        self.load_dataset(&Dataset::embedded());
     }
     
-    /// Add OS-specific patterns
-    fn add_os_patterns(&mut self, os: &str, patterns: &[&str]) {
-        self.os_patterns.insert(
-            os.to_string(),
-            patterns.iter().map(|s| s.to_string()).collect()
-        );
+    /// Without the dataset the categorizer only recognizes project-specific patterns
+    #[cfg(not(feature = "dataset"))]
+    fn initialize_common_patterns(&mut self) {}
+    
+    /// Add the language, framework, tool and OS patterns of a dataset
+    #[cfg(feature = "dataset")]
+    fn load_dataset(&mut self, dataset: &Dataset) {
+        self.language_patterns.extend(dataset.language_patterns.iter().cloned());
+        self.framework_patterns.extend(dataset.framework_patterns.iter().cloned());
+        self.tool_patterns.extend(dataset.tool_patterns.iter().cloned());
+        self.os_patterns.extend(dataset.os_patterns.iter().cloned());
     }
     
-    /// Categorize a single pattern
-    pub fn categorize_pattern(&self, pattern: &str) -> PatternCategory {
+    /// Categorize a single pattern: every category it may belong to, best first, with how sure
+    /// the categorizer is of each, from 0 to 1
+    ///
+    /// A plugin's answer comes alone, with full confidence. Otherwise the known categories are
+    /// ranked (see `matching_categories`), and failing those a pattern that only looks
+    /// project-specific gets `PROJECT_MATCH`. The list is never empty: an uncategorized pattern
+    /// gets `(Uncategorized, 0.0)`.
+    pub fn categorize_pattern(&self, pattern: &str) -> Vec<(PatternCategory, f32)> {
         let normalized_pattern = pattern.trim();
         
-        // Check language patterns
-        for (language, patterns) in &self.language_patterns {
-            if patterns.iter().any(|p| self.pattern_matches(normalized_pattern, p)) {
-                return PatternCategory::Language(language.clone());
-            }
+        // A plugin's taxonomy wins over the known patterns
+        if let Some(category) = self.plugins.categorize_pattern(normalized_pattern) {
+            return vec![(category, EXACT_MATCH)];
         }
         
-        // Check framework patterns
-        for (framework, patterns) in &self.framework_patterns {
-            if patterns.iter().any(|p| self.pattern_matches(normalized_pattern, p)) {
-                return PatternCategory::Framework(framework.clone());
-            }
+        let ranked = self.ranked_categories(normalized_pattern);
+        if !ranked.is_empty() {
+            return ranked;
         }
         
-        // Check tool patterns
-        for (tool, patterns) in &self.tool_patterns {
-            if patterns.iter().any(|p| self.pattern_matches(normalized_pattern, p)) {
-                return PatternCategory::Tool(tool.clone());
-            }
-        }
-        
-        // Check OS patterns
-        for (os, patterns) in &self.os_patterns {
-            if patterns.iter().any(|p| self.pattern_matches(normalized_pattern, p)) {
-                return PatternCategory::OperatingSystem(os.clone());
-            }
-        }
-        
-        // Check for common custom patterns
         if self.is_custom_pattern(normalized_pattern) {
-            return PatternCategory::Custom("Project-specific".to_string());
+            return vec![(PatternCategory::Custom("Project-specific".to_string()), PROJECT_MATCH)];
         }
         
-        PatternCategory::Uncategorized
+        vec![(PatternCategory::Uncategorized, 0.0)]
     }
     
-    /// Check if a pattern matches a known pattern (with wildcard support)
-    fn pattern_matches(&self, pattern: &str, known_pattern: &str) -> bool {
-        // Exact match
-        if pattern == known_pattern {
-            return true;
+    /// Get the best category of a pattern, the first `categorize_pattern` ranks
+    pub fn primary_category(&self, pattern: &str) -> PatternCategory {
+        self.categorize_pattern(pattern).swap_remove(0).0
+    }
+    
+    /// Get the category of the section a pattern belongs in
+    ///
+    /// That is its best category, unless several categories of the same kind claim it equally
+    /// (`build/` is Python's as much as Java's): such a pattern goes to `COMMON_CATEGORY`
+    /// rather than to whichever sorts first.
+    pub fn section_category(&self, pattern: &str) -> PatternCategory {
+        let mut ranked = self.categorize_pattern(pattern);
+        let (best, confidence) = ranked.swap_remove(0);
+        let shared = ranked.iter().any(|(other, other_confidence)| *other_confidence == confidence && other.kind_key() == best.kind_key());
+        match shared {
+            true => PatternCategory::Custom(COMMON_CATEGORY.to_string()),
+            false => best,
         }
-        
-        // Check for wildcard matches first
-        if known_pattern.contains('*') {
-            // Simple wildcard matching
-            let parts: Vec<&str> = known_pattern.split('*').collect();
-            if parts.len() == 2 {
-                let prefix = parts[0];
-                let suffix = parts[1];
-                if pattern.starts_with(prefix) && pattern.ends_with(suffix) {
-                    return true;
+    }
+    
+    /// Get every known category a pattern belongs to, best first
+    ///
+    /// Some patterns are shared by several stacks (`target/` is used by both Java and Rust).
+    /// Unlike `categorize_pattern`, this leaves out plugins and project-specific guesses.
+    pub fn matching_categories(&self, pattern: &str) -> Vec<PatternCategory> {
+        self.ranked_categories(pattern.trim()).into_iter().map(|(category, _)| category).collect()
+    }
+    
+    /// Known categories whose patterns match, with the confidence of their best match, best first
+    ///
+    /// Equally good matches go to tools, then operating systems, languages and frameworks:
+    /// editor and OS files are copied into every project template, and framework templates
+    /// repeat their language's patterns.
+    fn ranked_categories(&self, pattern: &str) -> Vec<(PatternCategory, f32)> {
+        let text = pattern.strip_prefix('!').unwrap_or(pattern);
+        let glob = GlobPattern::parse(text);
+        let mut ranked: Vec<(PatternCategory, f32)> = Vec::new();
+        for (table, category) in [
+            (&self.tool_patterns, PatternCategory::Tool as fn(String) -> PatternCategory),
+            (&self.os_patterns, PatternCategory::OperatingSystem),
+            (&self.language_patterns, PatternCategory::Language),
+            (&self.framework_patterns, PatternCategory::Framework),
+        ] {
+            for (name, patterns) in table {
+                let best = patterns.iter()
+                    .map(|known| match_confidence(text, &glob, known))
+                    .fold(0.0, f32::max);
+                if best > 0.0 {
+                    ranked.push((category(name.clone()), best));
                 }
             }
         }
-        
-        // Check if pattern contains the known pattern as a substring
-        if pattern.contains(known_pattern) {
-            return true;
-        }
-        
-        // Check if known pattern contains the pattern as a substring
-        if known_pattern.contains(pattern) {
-            return true;
+        // A stable sort keeps the tie order above
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+    }
+    
+    /// Find a known category of the given kind by name, ignoring case and punctuation
+    ///
+    /// The first word of a name is enough, so `node` finds `Node.js`.
+    pub fn find_category(&self, kind: fn(String) -> PatternCategory, name: &str) -> Option<PatternCategory> {
+        let simplify = |name: &str| name.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+        let wanted = simplify(name);
+        self.table(&kind(String::new()))?
+            .keys()
+            .find(|known| {
+                let first_word = known.split(|c: char| !c.is_alphanumeric()).next().unwrap_or(known);
+                simplify(known) == wanted || simplify(first_word) == wanted
+            })
+            .map(|known| kind(known.clone()))
+    }
+    
+    /// Get the names of the known categories of the given kind, sorted
+    pub fn category_names(&self, kind: fn(String) -> PatternCategory) -> Vec<String> {
+        self.table(&kind(String::new())).map(|table| table.keys().cloned().collect()).unwrap_or_default()
+    }
+    
+    /// Get the known patterns of a language, framework, tool or operating system
+    pub fn patterns_of(&self, category: &PatternCategory) -> &[String] {
+        self.table(category)
+            .and_then(|table| table.get(&category.short_name()))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+    
+    /// The table of known patterns holding categories of this kind
+    fn table(&self, category: &PatternCategory) -> Option<&BTreeMap<String, Vec<String>>> {
+        match category {
+            PatternCategory::Language(_) => Some(&self.language_patterns),
+            PatternCategory::Framework(_) => Some(&self.framework_patterns),
+            PatternCategory::Tool(_) => Some(&self.tool_patterns),
+            PatternCategory::OperatingSystem(_) => Some(&self.os_patterns),
+            PatternCategory::Custom(_) | PatternCategory::Uncategorized => None,
         }
-        
-        false
+    }
+    
+    /// Check if a pattern matches a known pattern, as a glob rather than as text
+    pub fn pattern_matches(&self, pattern: &str, known_pattern: &str) -> bool {
+        match_confidence(pattern, &GlobPattern::parse(pattern), known_pattern) > 0.0
     }
     
     /// Check if a pattern looks like a custom/project-specific pattern
+    ///
+    /// That is a pattern under a project directory such as `tmp/`, or a name marked local,
+    /// such as `config.local.json`.
     fn is_custom_pattern(&self, pattern: &str) -> bool {
-        // Patterns that are likely custom
-        pattern.starts_with("custom/") ||
-        pattern.starts_with("project/") ||
-        pattern.starts_with("local/") ||
-        pattern.starts_with("temp/") ||
-        pattern.starts_with("tmp/") ||
-        pattern.contains("config") ||
-        pattern.contains("settings") ||
-        pattern.contains("local") ||
-        pattern.contains("dev") ||
-        pattern.contains("prod") ||
-        pattern.contains("test")
+        let glob = GlobPattern::parse(pattern.strip_prefix('!').unwrap_or(pattern));
+        let names: Vec<String> = glob.segments.iter().map(ToString::to_string).collect();
+        let directories = if glob.directory_only { &names[..] } else { &names[..names.len().saturating_sub(1)] };
+        directories.iter().any(|name| PROJECT_DIRECTORIES.contains(&name.as_str()))
+            || names.last().is_some_and(|name| name.split(['.', '_', '-']).any(|word| word == "local"))
     }
     
     /// Categorize multiple patterns and return grouped results
@@ -308,8 +342,8 @@ impl PatternCategorizer {
         let mut categorized: HashMap<PatternCategory, Vec<String>> = HashMap::new();
         
         for pattern in patterns {
-            let category = self.categorize_pattern(pattern);
-            categorized.entry(category).or_insert_with(Vec::new).push(pattern.clone());
+            let category = self.primary_category(pattern);
+            categorized.entry(category).or_default().push(pattern.clone());
         }
         
         categorized
@@ -328,6 +362,44 @@ impl PatternCategorizer {
     }
 }
 
+/// How well `pattern` (compiled as `glob`, without its `!`) matches a known pattern, 0 when it doesn't
+///
+/// The known pattern is compiled as a gitignore glob and the pattern's text matched against it as
+/// a path, so `log/` no longer matches `*.log` for sharing its letters.
+fn match_confidence(pattern: &str, glob: &GlobPattern, known: &str) -> f32 {
+    let known = known.strip_prefix('!').unwrap_or(known);
+    if pattern == known {
+        return EXACT_MATCH;
+    }
+    let known_glob = GlobPattern::parse(known);
+    if glob.body.is_empty() || known_glob.body.is_empty() {
+        return 0.0;
+    }
+    if glob.segments == known_glob.segments {
+        return EQUIVALENT_MATCH;
+    }
+    
+    // The pattern's own text, as a path relative to the directory of the file
+    let text = pattern.strip_prefix('/').unwrap_or(pattern);
+    let text = text.strip_prefix("**/").unwrap_or(text);
+    let (path, is_dir) = match text.strip_suffix('/') {
+        Some(path) => (path, true),
+        None => (text, glob.directory_only),
+    };
+    if known_glob.matches_path(path, is_dir) {
+        let literal = known_glob.segments.iter()
+            .flat_map(|segment| &segment.tokens)
+            .filter(|token| matches!(token, Token::Literal(_) | Token::Escaped(_)))
+            .count();
+        let spelled_out = (literal as f32 / path.chars().count().max(1) as f32).min(1.0);
+        DIRECTORY_MATCH + (GLOB_MATCH - DIRECTORY_MATCH) * (1.0 + spelled_out) / 2.0
+    } else if known_glob.matches(text) {
+        DIRECTORY_MATCH
+    } else {
+        0.0
+    }
+}
+
 /// Summary of pattern categories
 #[derive(Debug, Clone)]
 pub struct CategorySummary {
@@ -368,48 +440,53 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "dataset")]
     fn test_categorize_python_pattern() {
         let categorizer = PatternCategorizer::new();
-        let category = categorizer.categorize_pattern("*.pyc");
+        let category = categorizer.primary_category("*.pyc");
         assert_eq!(category, PatternCategory::Language("Python".to_string()));
     }
 
     #[test]
+    #[cfg(feature = "dataset")]
     fn test_categorize_node_pattern() {
         let categorizer = PatternCategorizer::new();
-        let category = categorizer.categorize_pattern("node_modules/");
+        let category = categorizer.primary_category("node_modules/");
         assert_eq!(category, PatternCategory::Language("Node.js".to_string()));
     }
 
     #[test]
+    #[cfg(feature = "dataset")]
     fn test_categorize_vscode_pattern() {
         let categorizer = PatternCategorizer::new();
-        let category = categorizer.categorize_pattern(".vscode/");
+        let category = categorizer.primary_category(".vscode/");
         assert_eq!(category, PatternCategory::Tool("VSCode".to_string()));
     }
 
     #[test]
+    #[cfg(feature = "dataset")]
     fn test_categorize_macos_pattern() {
         let categorizer = PatternCategorizer::new();
-        let category = categorizer.categorize_pattern(".DS_Store");
+        let category = categorizer.primary_category(".DS_Store");
         assert_eq!(category, PatternCategory::OperatingSystem("macOS".to_string()));
     }
 
     #[test]
     fn test_categorize_custom_pattern() {
         let categorizer = PatternCategorizer::new();
-        let category = categorizer.categorize_pattern("custom/");
+        let category = categorizer.primary_category("custom/");
         assert_eq!(category, PatternCategory::Custom("Project-specific".to_string()));
     }
 
     #[test]
     fn test_categorize_uncategorized_pattern() {
         let categorizer = PatternCategorizer::new();
-        let category = categorizer.categorize_pattern("random_file.txt");
+        let category = categorizer.primary_category("random_file.txt");
         assert_eq!(category, PatternCategory::Uncategorized);
     }
 
     #[test]
+    #[cfg(feature = "dataset")]
     fn test_categorize_multiple_patterns() {
         let categorizer = PatternCategorizer::new();
         let patterns = vec![
@@ -429,6 +506,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "dataset")]
     fn test_category_summary() {
         let categorizer = PatternCategorizer::new();
         let patterns = vec![
@@ -446,6 +524,68 @@ mod tests {
         assert_eq!(summary.category_counts.get(&PatternCategory::Tool("VSCode".to_string())), Some(&1));
     }
 
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_matching_categories_lists_every_stack() {
+        let categorizer = PatternCategorizer::new();
+        let categories = categorizer.matching_categories("target/");
+        
+        assert!(categories.contains(&PatternCategory::Language("Java".to_string())));
+        assert!(categories.contains(&PatternCategory::Language("Rust".to_string())));
+        assert_eq!(categorizer.primary_category("target/"), categories[0]);
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_categorize_pattern_ranks_every_category() {
+        let categorizer = PatternCategorizer::new();
+        let ranked = categorizer.categorize_pattern("node_modules/");
+        
+        assert_eq!(ranked[0], (PatternCategory::Language("Node.js".to_string()), EXACT_MATCH));
+        assert!(ranked.contains(&(PatternCategory::Framework("React".to_string()), EXACT_MATCH)));
+        assert_eq!(categorizer.categorize_pattern("random_file.txt"), vec![(PatternCategory::Uncategorized, 0.0)]);
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_section_category_of_shared_patterns() {
+        let categorizer = PatternCategorizer::new();
+        let common = PatternCategory::Custom(COMMON_CATEGORY.to_string());
+        
+        assert_eq!(categorizer.section_category("build/"), common);
+        assert_eq!(categorizer.section_category("*.so"), common);
+        // React lists node_modules/ too, but a framework doesn't share it with a language
+        assert_eq!(categorizer.section_category("node_modules/"), PatternCategory::Language("Node.js".to_string()));
+        assert_eq!(categorizer.section_category("*.pyc"), PatternCategory::Language("Python".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_find_category_by_loose_name() {
+        let categorizer = PatternCategorizer::new();
+        
+        assert_eq!(categorizer.find_category(PatternCategory::Language, "node"), Some(PatternCategory::Language("Node.js".to_string())));
+        assert_eq!(categorizer.find_category(PatternCategory::OperatingSystem, "MacOS"), Some(PatternCategory::OperatingSystem("macOS".to_string())));
+        assert_eq!(categorizer.find_category(PatternCategory::Language, "macos"), None);
+        assert!(categorizer.patterns_of(&PatternCategory::Language("Rust".to_string())).contains(&"target/".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_categorizer_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("categories.toml");
+        std::fs::write(&path, "[language]\nRust = [\"*.profraw\"]\n\n[tool]\nBazel = [\".bazelrc.user\", \"bazel-*/\"]\n").unwrap();
+        let categorizer = PatternCategorizer::from_file(&path).unwrap();
+        
+        assert_eq!(categorizer.primary_category("*.profraw"), PatternCategory::Language("Rust".to_string()));
+        assert_eq!(categorizer.primary_category(".bazelrc.user"), PatternCategory::Tool("Bazel".to_string()));
+        assert_eq!(categorizer.primary_category("__pycache__/"), PatternCategory::Language("Python".to_string()));
+        
+        std::fs::write(&path, "[tool]\nBazel = [\"bazel-[out\"]\n").unwrap();
+        assert!(matches!(PatternCategorizer::from_file(&path), Err(GixError::ConfigError(message)) if message.contains("categories.toml: line 2:")));
+    }
+
     #[test]
     fn test_pattern_matching() {
         let categorizer = PatternCategorizer::new();
@@ -0,0 +1,248 @@
+use crate::core::folding::FoldedPatterns;
+use crate::core::negations::MovedNegation;
+use crate::core::optimizer::{KeptForSafety, RedundantPattern};
+use crate::core::whitespace::WhitespaceFix;
+use crate::models::{EntryType, GitignoreFile};
+use std::collections::HashSet;
+
+/// One change optimization made to a file; line numbers are those of the original file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptimizationAction {
+    /// A pattern repeating an earlier one was removed
+    RemovedDuplicate { line: usize, pattern: String, kept_line: usize },
+    /// A pattern covered by a broader one was removed
+    RemovedRedundant { line: usize, pattern: String, covered_by_line: usize, covered_by: String },
+    /// Sibling patterns were folded into one with a character class, which takes the first line
+    FoldedPatterns { lines: Vec<usize>, folded: String },
+    /// A comment repeating an earlier one was removed, so its section joins the earlier one
+    MergedComment { line: usize, comment: String, kept_line: usize },
+    /// A run of blank lines was removed
+    CollapsedBlankLines { lines: Vec<usize> },
+    /// A negation was moved to follow the pattern it re-includes paths from
+    MovedNegation { line: usize, pattern: String, after_line: usize },
+    /// A pattern was kept because removing it changes what a negation re-includes
+    KeptForSafety { line: usize, pattern: String },
+    /// A line was removed for another reason, such as another ignore layer providing it
+    RemovedLine { line: usize, text: String },
+    /// Whitespace on a line was normalized; `fixes` names what changed
+    NormalizedWhitespace { line: usize, before: String, after: String, fixes: Vec<&'static str> },
+}
+
+impl OptimizationAction {
+    /// The first original line the action concerns
+    pub fn line(&self) -> usize {
+        match self {
+            OptimizationAction::RemovedDuplicate { line, .. }
+            | OptimizationAction::RemovedRedundant { line, .. }
+            | OptimizationAction::MergedComment { line, .. }
+            | OptimizationAction::MovedNegation { line, .. }
+            | OptimizationAction::KeptForSafety { line, .. }
+            | OptimizationAction::RemovedLine { line, .. }
+            | OptimizationAction::NormalizedWhitespace { line, .. } => *line,
+            OptimizationAction::FoldedPatterns { lines, .. } | OptimizationAction::CollapsedBlankLines { lines } => lines[0],
+        }
+    }
+
+    /// Get the name used in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            OptimizationAction::RemovedDuplicate { .. } => "removed-duplicate",
+            OptimizationAction::RemovedRedundant { .. } => "removed-redundant",
+            OptimizationAction::FoldedPatterns { .. } => "folded-patterns",
+            OptimizationAction::MergedComment { .. } => "merged-comment",
+            OptimizationAction::CollapsedBlankLines { .. } => "collapsed-blank-lines",
+            OptimizationAction::MovedNegation { .. } => "moved-negation",
+            OptimizationAction::KeptForSafety { .. } => "kept-for-safety",
+            OptimizationAction::RemovedLine { .. } => "removed-line",
+            OptimizationAction::NormalizedWhitespace { .. } => "normalized-whitespace",
+        }
+    }
+
+    /// Describe the action for people
+    pub fn describe(&self) -> String {
+        let lines = |lines: &[usize]| lines.iter().map(usize::to_string).collect::<Vec<_>>().join(", ");
+        match self {
+            OptimizationAction::RemovedDuplicate { line, pattern, kept_line } => {
+                format!("line {}: {} (duplicate of line {})", line, pattern, kept_line)
+            }
+            OptimizationAction::RemovedRedundant { line, pattern, covered_by_line, covered_by } => {
+                format!("line {}: {} (covered by '{}' on line {})", line, pattern, covered_by, covered_by_line)
+            }
+            OptimizationAction::FoldedPatterns { lines: folded_lines, folded } => {
+                format!("lines {}: folded into {}", lines(folded_lines), folded)
+            }
+            OptimizationAction::MergedComment { line, comment, kept_line } => {
+                format!("line {}: {} (merged into the same comment on line {})", line, comment, kept_line)
+            }
+            OptimizationAction::CollapsedBlankLines { lines: blank_lines } => {
+                format!("lines {}: blank (collapsed)", lines(blank_lines))
+            }
+            OptimizationAction::MovedNegation { line, pattern, after_line } => {
+                format!("line {}: {} (moved to follow line {})", line, pattern, after_line)
+            }
+            OptimizationAction::KeptForSafety { line, pattern } => {
+                format!("line {}: {} (kept, a negation depends on it)", line, pattern)
+            }
+            OptimizationAction::RemovedLine { line, text } => format!("line {}: {} (removed)", line, text),
+            OptimizationAction::NormalizedWhitespace { line, before, after, fixes } => {
+                format!("line {}: {:?} -> {:?} ({})", line, before, after, fixes.join(", "))
+            }
+        }
+    }
+}
+
+/// Every change optimization made to a file, in line order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptimizationReport {
+    pub actions: Vec<OptimizationAction>,
+}
+
+impl OptimizationReport {
+    /// Work out what turned `original` into `optimized`
+    ///
+    /// Removals the passes explain themselves (`redundant`, `folded`) are reported as such; any
+    /// other removed pattern or comment is a duplicate when an identical line was kept, and
+    /// removed blank lines are grouped into runs.
+    pub fn describe(
+        original: &GitignoreFile,
+        optimized: &GitignoreFile,
+        redundant: &[RedundantPattern],
+        folded: &[FoldedPatterns],
+        moved: &[MovedNegation],
+        kept_for_safety: &[KeptForSafety],
+    ) -> Self {
+        let kept: HashSet<usize> = optimized.entries.iter().map(|entry| entry.line_number).collect();
+        let mut explained: HashSet<usize> = HashSet::new();
+        let mut actions = Vec::new();
+
+        for fold in folded.iter().filter(|fold| kept.contains(&fold.line_numbers[0])) {
+            explained.extend(&fold.line_numbers);
+            actions.push(OptimizationAction::FoldedPatterns { lines: fold.line_numbers.clone(), folded: fold.folded.clone() });
+        }
+        for pattern in redundant.iter().filter(|pattern| !kept.contains(&pattern.line_number)) {
+            explained.insert(pattern.line_number);
+            actions.push(OptimizationAction::RemovedRedundant {
+                line: pattern.line_number,
+                pattern: pattern.pattern.clone(),
+                covered_by_line: pattern.covered_by_line,
+                covered_by: pattern.covered_by.clone(),
+            });
+        }
+
+        let mut blank_run: Vec<usize> = Vec::new();
+        for entry in original.entries.iter().filter(|entry| !kept.contains(&entry.line_number) && !explained.contains(&entry.line_number)) {
+            if entry.is_blank() && blank_run.last().is_none_or(|&last| last + 1 == entry.line_number) {
+                blank_run.push(entry.line_number);
+                continue;
+            }
+            if !blank_run.is_empty() {
+                actions.push(OptimizationAction::CollapsedBlankLines { lines: std::mem::take(&mut blank_run) });
+                if entry.is_blank() {
+                    blank_run.push(entry.line_number);
+                    continue;
+                }
+            }
+
+            let same_line = optimized.entries.iter().find(|kept_entry| match (&kept_entry.entry_type, &entry.entry_type) {
+                (EntryType::Pattern(_), EntryType::Pattern(_)) => kept_entry.comparison_pattern() == entry.comparison_pattern(),
+                (EntryType::Comment(kept_comment), EntryType::Comment(comment)) => kept_comment.trim() == comment.trim(),
+                _ => false,
+            });
+            actions.push(match (&entry.entry_type, same_line) {
+                (EntryType::Pattern(_), Some(kept_entry)) => OptimizationAction::RemovedDuplicate {
+                    line: entry.line_number,
+                    pattern: entry.comparison_pattern().unwrap_or_default(),
+                    kept_line: kept_entry.line_number,
+                },
+                (EntryType::Comment(comment), Some(kept_entry)) => OptimizationAction::MergedComment {
+                    line: entry.line_number,
+                    comment: comment.clone(),
+                    kept_line: kept_entry.line_number,
+                },
+                _ => OptimizationAction::RemovedLine { line: entry.line_number, text: entry.original.clone() },
+            });
+        }
+        if !blank_run.is_empty() {
+            actions.push(OptimizationAction::CollapsedBlankLines { lines: blank_run });
+        }
+
+        actions.extend(moved.iter().map(|negation| OptimizationAction::MovedNegation {
+            line: negation.line_number,
+            pattern: negation.pattern.clone(),
+            after_line: negation.negates_line,
+        }));
+        actions.extend(kept_for_safety.iter().map(|pattern| OptimizationAction::KeptForSafety {
+            line: pattern.line_number,
+            pattern: pattern.pattern.clone(),
+        }));
+        actions.sort_by_key(OptimizationAction::line);
+        Self { actions }
+    }
+
+    /// Add the lines whitespace normalization rewrote, keeping line order
+    pub fn add_whitespace_fixes(&mut self, fixes: &[WhitespaceFix]) {
+        self.actions.extend(fixes.iter().map(|fix| OptimizationAction::NormalizedWhitespace {
+            line: fix.line_number,
+            before: fix.before.clone(),
+            after: fix.after.clone(),
+            fixes: fix.kinds.iter().map(|kind| kind.name()).collect(),
+        }));
+        self.actions.sort_by_key(OptimizationAction::line);
+    }
+
+    /// Check if optimization changed nothing
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::optimizer::{optimize_gitignore_advanced, optimize_gitignore_aggressive, PassStats};
+    use crate::core::whitespace::normalize_whitespace;
+    use crate::core::pattern_analyzer::PatternAnalyzer;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_describe_aggressive_optimization() {
+        let original = parse_gitignore("# Logs\n*.log\n\n\n\n# Logs\n*.log\ndebug.log\n").unwrap();
+        let optimized = optimize_gitignore_aggressive(&original).unwrap();
+        let report = OptimizationReport::describe(&original, &optimized, &[], &[], &[], &[]);
+
+        assert_eq!(report.actions, vec![
+            OptimizationAction::CollapsedBlankLines { lines: vec![3, 4, 5] },
+            OptimizationAction::MergedComment { line: 6, comment: "# Logs".to_string(), kept_line: 1 },
+            OptimizationAction::RemovedDuplicate { line: 7, pattern: "*.log".to_string(), kept_line: 2 },
+        ]);
+        assert_eq!(report.actions[2].describe(), "line 7: *.log (duplicate of line 2)");
+        assert!(OptimizationReport::describe(&original, &original, &[], &[], &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_describe_redundant_patterns() {
+        let original = parse_gitignore("build/\nbuild/out\nbuild/\n").unwrap();
+        let (optimized, redundant) = optimize_gitignore_advanced(&original, &PatternAnalyzer::safe()).unwrap();
+        let report = OptimizationReport::describe(&original, &optimized, &redundant, &[], &[], &[]);
+
+        assert_eq!(report.actions.len(), 2);
+        assert_eq!(report.actions[0].name(), "removed-redundant");
+        assert_eq!(report.actions[0].describe(), "line 2: build/out (covered by 'build/' on line 1)");
+        assert_eq!(report.actions[1].name(), "removed-duplicate");
+    }
+
+    #[test]
+    fn test_report_whitespace_fixes() {
+        let original = parse_gitignore("#Logs\n*.log \n\n\n\nbuild/\n").unwrap();
+        let (normalized, fixes) = normalize_whitespace(&original, &mut PassStats::default());
+        let mut report = OptimizationReport::describe(&original, &normalized, &[], &[], &[], &[]);
+        report.add_whitespace_fixes(&fixes);
+
+        assert_eq!(report.actions.iter().map(OptimizationAction::name).collect::<Vec<_>>(), vec![
+            "normalized-whitespace",
+            "normalized-whitespace",
+            "collapsed-blank-lines",
+        ]);
+        assert_eq!(report.actions[1].describe(), "line 2: \"*.log \" -> \"*.log\" (trailing spaces)");
+    }
+}
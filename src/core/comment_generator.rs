@@ -1,6 +1,10 @@
 use std::collections::HashMap;
-use crate::core::pattern_analyzer::PatternAnalysis;
+use crate::core::pattern_analyzer::{PatternAnalysis, PatternAnalyzer};
+use crate::models::{EntryType, GitignoreEntry, GitignoreFile};
 use crate::core::categorizer::PatternCategory;
+#[cfg(feature = "dataset")]
+use crate::core::dataset::Dataset;
+use crate::utils::patterns::GlobPattern;
 
 /// Generator for automatic comments on gitignore patterns
 pub struct CommentGenerator {
@@ -28,110 +32,35 @@ impl CommentGenerator {
         Self::default()
     }
     
-    /// Initialize predefined comments
-    fn initialize_comments(&mut self) {
-        // Language-specific pattern comments
-        self.pattern_comments.insert("*.pyc".to_string(), "Python bytecode files".to_string());
-        self.pattern_comments.insert("__pycache__/".to_string(), "Python cache directory".to_string());
-        self.pattern_comments.insert("*.pyo".to_string(), "Python optimized bytecode files".to_string());
-        self.pattern_comments.insert("*.pyd".to_string(), "Python dynamic modules".to_string());
-        self.pattern_comments.insert("*.so".to_string(), "Shared object files".to_string());
-        self.pattern_comments.insert("*.egg".to_string(), "Python egg packages".to_string());
-        self.pattern_comments.insert("*.egg-info/".to_string(), "Python egg metadata".to_string());
-        self.pattern_comments.insert("dist/".to_string(), "Distribution/packaging directory".to_string());
-        self.pattern_comments.insert("build/".to_string(), "Build output directory".to_string());
-        self.pattern_comments.insert("venv/".to_string(), "Python virtual environment".to_string());
-        self.pattern_comments.insert("env/".to_string(), "Python virtual environment".to_string());
-        self.pattern_comments.insert(".env".to_string(), "Environment variables file".to_string());
-        self.pattern_comments.insert(".coverage".to_string(), "Python coverage data".to_string());
-        self.pattern_comments.insert(".pytest_cache/".to_string(), "Pytest cache directory".to_string());
-        
-        // Node.js pattern comments
-        self.pattern_comments.insert("node_modules/".to_string(), "Node.js dependencies".to_string());
-        self.pattern_comments.insert("npm-debug.log*".to_string(), "NPM debug logs".to_string());
-        self.pattern_comments.insert("yarn-debug.log*".to_string(), "Yarn debug logs".to_string());
-        self.pattern_comments.insert("yarn-error.log*".to_string(), "Yarn error logs".to_string());
-        self.pattern_comments.insert("coverage/".to_string(), "Test coverage reports".to_string());
-        self.pattern_comments.insert(".nyc_output".to_string(), "NYC coverage output".to_string());
-        self.pattern_comments.insert(".next/".to_string(), "Next.js build output".to_string());
-        self.pattern_comments.insert("out/".to_string(), "Build output directory".to_string());
-        
-        // Java pattern comments
-        self.pattern_comments.insert("*.class".to_string(), "Java compiled classes".to_string());
-        self.pattern_comments.insert("*.jar".to_string(), "Java archive files".to_string());
-        self.pattern_comments.insert("*.war".to_string(), "Web application archive".to_string());
-        self.pattern_comments.insert("target/".to_string(), "Maven build output".to_string());
-        self.pattern_comments.insert(".gradle/".to_string(), "Gradle cache directory".to_string());
-        
-        // Rust pattern comments
-        self.pattern_comments.insert("Cargo.lock".to_string(), "Cargo lock file".to_string());
-        self.pattern_comments.insert("target/".to_string(), "Rust build output".to_string());
-        self.pattern_comments.insert("*.pdb".to_string(), "Program database files".to_string());
-        self.pattern_comments.insert("*.exe".to_string(), "Executable files".to_string());
-        self.pattern_comments.insert("*.dll".to_string(), "Dynamic link libraries".to_string());
-        self.pattern_comments.insert("*.so".to_string(), "Shared object files".to_string());
-        self.pattern_comments.insert("*.dylib".to_string(), "Dynamic libraries (macOS)".to_string());
-        
-        // IDE pattern comments
-        self.pattern_comments.insert(".vscode/".to_string(), "VSCode workspace settings".to_string());
-        self.pattern_comments.insert(".idea/".to_string(), "IntelliJ IDEA settings".to_string());
-        self.pattern_comments.insert("*.swp".to_string(), "Vim swap files".to_string());
-        self.pattern_comments.insert("*.swo".to_string(), "Vim swap files".to_string());
-        self.pattern_comments.insert("*~".to_string(), "Backup files".to_string());
-        
-        // OS pattern comments
-        self.pattern_comments.insert(".DS_Store".to_string(), "macOS system files".to_string());
-        self.pattern_comments.insert("Thumbs.db".to_string(), "Windows thumbnail cache".to_string());
-        self.pattern_comments.insert("Desktop.ini".to_string(), "Windows desktop configuration".to_string());
-        
-        // Common pattern comments
-        self.pattern_comments.insert("*.log".to_string(), "Log files".to_string());
-        self.pattern_comments.insert("*.tmp".to_string(), "Temporary files".to_string());
-        self.pattern_comments.insert("*.temp".to_string(), "Temporary files".to_string());
-        self.pattern_comments.insert("*.bak".to_string(), "Backup files".to_string());
-        self.pattern_comments.insert("*.cache".to_string(), "Cache files".to_string());
-        self.pattern_comments.insert("*.pid".to_string(), "Process ID files".to_string());
-        self.pattern_comments.insert("*.lock".to_string(), "Lock files".to_string());
+    /// Create a comment generator using the descriptions of the given dataset
+    #[cfg(feature = "dataset")]
+    pub fn from_dataset(dataset: &Dataset) -> Self {
+        let mut generator = Self {
+            pattern_comments: HashMap::new(),
+            category_comments: HashMap::new(),
+        };
         
-        // Category comments
-        self.category_comments.insert(
-            PatternCategory::Language("Python".to_string()),
-            "Python language files".to_string()
-        );
-        self.category_comments.insert(
-            PatternCategory::Language("Node.js".to_string()),
-            "Node.js language files".to_string()
-        );
-        self.category_comments.insert(
-            PatternCategory::Language("Java".to_string()),
-            "Java language files".to_string()
-        );
-        self.category_comments.insert(
-            PatternCategory::Language("Rust".to_string()),
-            "Rust language files".to_string()
-        );
-        self.category_comments.insert(
-            PatternCategory::Tool("VSCode".to_string()),
-            "VSCode editor files".to_string()
-        );
-        self.category_comments.insert(
-            PatternCategory::Tool("IntelliJ".to_string()),
-            "IntelliJ IDEA files".to_string()
-        );
-        self.category_comments.insert(
-            PatternCategory::OperatingSystem("macOS".to_string()),
-            "macOS system files".to_string()
-        );
-        self.category_comments.insert(
-            PatternCategory::OperatingSystem("Windows".to_string()),
-            "Windows system files".to_string()
-        );
-        self.category_comments.insert(
-            PatternCategory::OperatingSystem("Linux".to_string()),
-            "Linux system files".to_string()
-        );
+        generator.load_dataset(dataset);
+        generator
+    }
+    
+    /// Initialize predefined comments from the built-in dataset
+    #[cfg(feature = "dataset")]
+    fn initialize_comments(&mut self) {
+        self.load_dataset(&Dataset::embedded());
+    }
+    
+    /// Add the pattern and category descriptions of a dataset
+    #[cfg(feature = "dataset")]
+    fn load_dataset(&mut self, dataset: &Dataset) {
+        self.pattern_comments.extend(dataset.pattern_comments.iter().cloned());
+        self.category_comments.extend(dataset.category_comments.iter().cloned());
     }
     
+    /// Without the dataset comments are derived from pattern analysis alone
+    #[cfg(not(feature = "dataset"))]
+    fn initialize_comments(&mut self) {}
+    
     /// Generate a comment for a specific pattern
     pub fn generate_pattern_comment(&self, pattern: &str, analysis: &PatternAnalysis) -> Option<String> {
         // Check for exact pattern match
@@ -191,6 +120,33 @@ impl CommentGenerator {
         }
     }
     
+    /// Describe what a negation re-includes, relative to the nearest pattern above it that it carves out of
+    ///
+    /// `preceding` holds the patterns above the negation, in file order. A negation whose parent
+    /// directory is ignored by that pattern can't re-include anything, which the comment says.
+    /// `None` when no pattern above matches what the negation does.
+    pub fn generate_negation_comment(&self, negation: &str, preceding: &[String]) -> Option<String> {
+        let glob = GlobPattern::parse(negation);
+        if !glob.negated {
+            return None;
+        }
+        let target = negation.trim_start_matches('!');
+        
+        preceding.iter().rev().find_map(|pattern| {
+            let broad = GlobPattern::parse(pattern);
+            if broad.negated {
+                return None;
+            }
+            if broad.covers_parent_of(&glob) {
+                Some(format!("No effect: {} above ignores the directory holding {}", pattern, target))
+            } else if broad.may_overlap(&glob) {
+                Some(format!("Re-include {} despite {} above", target, pattern))
+            } else {
+                None
+            }
+        })
+    }
+    
     /// Generate a section header comment for a category
     pub fn generate_section_header(&self, category: &PatternCategory) -> String {
         match category {
@@ -233,6 +189,39 @@ impl CommentGenerator {
             .collect()
     }
     
+    /// Insert a generated comment above every pattern that has no comment in its paragraph
+    ///
+    /// A paragraph is a run of lines between blank lines; a comment anywhere above a pattern
+    /// within its paragraph counts as documenting it. A negation is described by what it
+    /// re-includes from the pattern above it (see `generate_negation_comment`).
+    pub fn add_generated_comments(&self, file: &GitignoreFile) -> GitignoreFile {
+        let analyzer = PatternAnalyzer::default();
+        let mut commented = GitignoreFile::new();
+        let mut paragraph_has_comment = false;
+        let mut preceding = Vec::new();
+        
+        for entry in &file.entries {
+            match &entry.entry_type {
+                EntryType::Blank => paragraph_has_comment = false,
+                EntryType::Comment(_) => paragraph_has_comment = true,
+                EntryType::Pattern(pattern) if !paragraph_has_comment && !entry.preserve => {
+                    let analysis = analyzer.analyze_pattern(pattern);
+                    let comment = self.generate_negation_comment(pattern, &preceding)
+                        .or_else(|| self.generate_pattern_comment(pattern, &analysis));
+                    if let Some(comment) = comment {
+                        let line = format!("# {}", comment);
+                        commented.add_entry(GitignoreEntry::new(line.clone(), EntryType::Comment(line), entry.line_number));
+                    }
+                }
+                EntryType::Pattern(_) => {}
+            }
+            preceding.extend(entry.comparison_pattern());
+            commented.add_entry(entry.clone());
+        }
+        
+        commented
+    }
+    
     /// Generate a comprehensive comment for a pattern with context
     pub fn generate_detailed_comment(&self, pattern: &str, analysis: &PatternAnalysis, category: &PatternCategory) -> String {
         let mut comment_parts = Vec::new();
@@ -273,6 +262,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "dataset")]
     fn test_generate_pattern_comment_exact_match() {
         let generator = CommentGenerator::new();
         let analysis = PatternAnalysis::new("*.pyc".to_string(), "*.pyc".to_string());
@@ -282,6 +272,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "dataset")]
     fn test_generate_pattern_comment_wildcard_match() {
         let generator = CommentGenerator::new();
         let analysis = PatternAnalysis::new("file.pyc".to_string(), "file.pyc".to_string());
@@ -319,6 +310,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "dataset")]
     fn test_generate_category_comment() {
         let generator = CommentGenerator::new();
         let comment = generator.generate_category_comment(&PatternCategory::Language("Python".to_string()));
@@ -327,6 +319,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "dataset")]
     fn test_generate_detailed_comment() {
         let generator = CommentGenerator::new();
         let analysis = PatternAnalysis::new("*.pyc".to_string(), "*.pyc".to_string());
@@ -348,4 +341,37 @@ mod tests {
         assert!(generator.pattern_matches_wildcard("*.pyc", "*.pyc"));
         assert!(!generator.pattern_matches_wildcard("file.txt", "*.pyc"));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_generate_negation_intent_comment() {
+        let generator = CommentGenerator::new();
+        let preceding = vec!["*.log".to_string(), "build/".to_string(), "*.tmp".to_string()];
+        
+        assert_eq!(generator.generate_negation_comment("!debug.log", &preceding), Some("Re-include debug.log despite *.log above".to_string()));
+        assert_eq!(
+            generator.generate_negation_comment("!build/keep.txt", &preceding),
+            Some("No effect: build/ above ignores the directory holding build/keep.txt".to_string())
+        );
+        assert_eq!(generator.generate_negation_comment("!README.md", &preceding), None);
+        assert_eq!(generator.generate_negation_comment("*.log", &preceding), None);
+        
+        let file = crate::core::parser::parse_gitignore("*.log\n!debug.log").unwrap();
+        let commented = CommentGenerator::new().add_generated_comments(&file);
+        assert_eq!(commented.entries[2].original, "# Re-include debug.log despite *.log above");
+    }
+
+    #[test]
+    fn test_add_generated_comments_only_to_uncommented_patterns() {
+        let file = crate::core::parser::parse_gitignore("# Logs\n*.log\ntmp/\n\nbuild/\ndist/").unwrap();
+        let commented = CommentGenerator::new().add_generated_comments(&file);
+        let lines: Vec<&str> = commented.entries.iter().map(|entry| entry.original.as_str()).collect();
+        
+        assert_eq!(lines.len(), 8);
+        assert_eq!(&lines[..4], &["# Logs", "*.log", "tmp/", ""]);
+        assert!(lines[4].starts_with("# "));
+        assert_eq!(lines[5], "build/");
+        assert!(lines[6].starts_with("# "));
+        assert_eq!(lines[7], "dist/");
+        assert_eq!(commented.stats.pattern_lines, 4);
+    }
+}
@@ -0,0 +1,613 @@
+//! Built-in knowledge base of well-known gitignore patterns, compiled in with the `dataset` feature
+
+use crate::core::categorizer::PatternCategory;
+use crate::core::validator::find_pattern_problems;
+use crate::models::config::{parse_array, parse_settings, parse_string, user_config_path, TomlSetting};
+use crate::models::GixError;
+use std::path::{Path, PathBuf};
+
+/// Version of the embedded dataset; a downloaded dataset is only preferred when it is newer
+pub const EMBEDDED_VERSION: u32 = 1;
+
+/// Where `gix dataset update` downloads from; the checksum lives next to it with a `.sha256` suffix
+pub const DEFAULT_DATASET_URL: &str = "https://raw.githubusercontent.com/smirnoffmg/gix/main/dataset/gix-dataset.txt";
+
+/// Patterns commonly ignored for each programming language
+pub const LANGUAGE_PATTERNS: &[(&str, &[&str])] = &[
+    ("Python", &[
+        "*.py[cod]", "*.so", "__pycache__/", "*.egg", "*.egg-info/", "dist/", "build/", "eggs/",
+        "parts/", "bin/", "var/", "sdist/", "develop-eggs/", "*.egg-info/", ".installed.cfg",
+        "*.manifest", "*.spec", "pip-log.txt", "pip-delete-this-directory.txt", ".Python",
+        "env/", "venv/", ".venv/", "ENV/", "env.bak/", "venv.bak/", ".pytest_cache/", ".coverage",
+        "htmlcov/", ".tox/", ".nox/", ".cache", ".mypy_cache/", ".dmypy.json", "dmypy.json",
+    ]),
+    ("Node.js", &[
+        "node_modules/", "npm-debug.log*", "yarn-debug.log*", "yarn-error.log*",
+        "lerna-debug.log*", ".npm", ".eslintcache", ".node_repl_history", "*.tgz",
+        ".yarn-integrity", ".env.local", ".env.development.local", ".env.test.local",
+        ".env.production.local", "coverage/", ".nyc_output", ".grunt", "bower_components/",
+        ".lock-wscript", "build/Release", ".node_repl_history", "*.tgz", ".yarn-integrity",
+        ".next/", "out/",
+    ]),
+    ("Java", &[
+        "*.class", "*.log", "*.ctxt", ".mtj.tmp/", "*.jar", "*.war", "*.nar", "*.ear", "*.zip",
+        "*.tar.gz", "*.rar", "hs_err_pid*", "replay_pid*", "target/",
+        "!.mvn/wrapper/maven-wrapper.jar", "!**/src/main/**/target/", "!**/src/test/**/target/",
+        ".idea/", "*.iws", "*.iml", "*.ipr", ".gradle/", "build/",
+        "!gradle/wrapper/gradle-wrapper.jar",
+    ]),
+    ("Rust", &[
+        "target/", "Cargo.lock", "*.pdb", "*.exe", "*.dll", "*.so", "*.dylib", "*.rlib",
+        "*.rmeta", "*.rbc", "*.dSYM/", "*.su", "*.idb", "*.pdb", "*.ilk", "*.exp", "*.lib",
+        "*.a", "*.o", "*.so", "*.dylib",
+    ]),
+    ("Go", &[
+        "*.exe", "*.exe~", "*.dll", "*.so", "*.dylib", "*.test", "*.out", "go.work", "vendor/",
+        ".go-version",
+    ]),
+];
+
+/// Patterns commonly ignored for each framework
+pub const FRAMEWORK_PATTERNS: &[(&str, &[&str])] = &[
+    ("React", &[
+        "node_modules/", ".pnp", ".pnp.js", "coverage/", "build/", ".DS_Store", ".env.local",
+        ".env.development.local", ".env.test.local", ".env.production.local", "npm-debug.log*",
+        "yarn-debug.log*", "yarn-error.log*", ".next/", "out/",
+    ]),
+    ("Django", &[
+        "*.log", "local_settings.py", "db.sqlite3", "db.sqlite3-journal", "media/",
+        "staticfiles/", ".env", ".venv", "env/", "venv/", "ENV/", "env.bak/", "venv.bak/",
+        ".pytest_cache/",
+    ]),
+    ("Spring", &[
+        "*.class", "*.log", "*.ctxt", ".mtj.tmp/", "*.jar", "*.war", "*.nar", "*.ear", "*.zip",
+        "*.tar.gz", "*.rar", "hs_err_pid*", "replay_pid*", "target/", ".idea/", "*.iws",
+        "*.iml", "*.ipr",
+    ]),
+];
+
+/// Patterns commonly ignored for each tool (IDEs, editors)
+pub const TOOL_PATTERNS: &[(&str, &[&str])] = &[
+    ("VSCode", &[
+        ".vscode/", "*.code-workspace", ".vscode/settings.json", ".vscode/tasks.json",
+        ".vscode/launch.json", ".vscode/extensions.json",
+    ]),
+    ("IntelliJ", &[
+        ".idea/", "*.iws", "*.iml", "*.ipr", ".idea_modules/",
+    ]),
+    ("Eclipse", &[
+        ".metadata", "bin/", "tmp/", "*.tmp", "*.bak", "*.swp", "*~.nib", "local.properties",
+        ".settings/", ".loadpath", ".recommenders",
+    ]),
+    ("Vim", &[
+        "*.swp", "*.swo", "*~", ".vim/", ".viminfo", ".vimrc",
+    ]),
+    ("Emacs", &[
+        "*~", "#*#", ".#*", ".emacs.desktop", ".emacs.desktop.lock", "*.elc", "auto-save-list",
+        "tramp", ".emacs.desktop.lock",
+    ]),
+];
+
+/// Patterns commonly ignored for each operating system
+pub const OS_PATTERNS: &[(&str, &[&str])] = &[
+    ("macOS", &[
+        ".DS_Store", ".AppleDouble", ".LSOverride", "Icon", "._*", ".DocumentRevisions-V100",
+        ".fseventsd", ".Spotlight-V100", ".TemporaryItems", ".Trashes", ".VolumeIcon.icns",
+        ".com.apple.timemachine.donotpresent", ".AppleDB", ".AppleDesktop",
+        "Network Trash Folder", "Temporary Items", ".apdisk", ".VolumeIcon.icns", ".fseventsd",
+        ".Spotlight-V100",
+    ]),
+    ("Windows", &[
+        "Thumbs.db", "Thumbs.db:encryptable", "ehthumbs.db", "ehthumbs_vista.db", "*.tmp",
+        "*.temp", "Desktop.ini", "$RECYCLE.BIN/", "*.cab", "*.msi", "*.msix", "*.msm", "*.msp",
+        "*.lnk", "*.stackdump",
+    ]),
+    ("Linux", &[
+        "*~", "*.swp", "*.swo", "*~", ".nfs*", ".fuse_hidden*", ".directory", ".Trash-*",
+        ".nfs*", ".fuse_hidden*",
+    ]),
+];
+
+/// Descriptions of well-known patterns; later entries override earlier ones
+pub const PATTERN_COMMENTS: &[(&str, &str)] = &[
+    ("*.pyc", "Python bytecode files"),
+    ("__pycache__/", "Python cache directory"),
+    ("*.pyo", "Python optimized bytecode files"),
+    ("*.pyd", "Python dynamic modules"),
+    ("*.so", "Shared object files"),
+    ("*.egg", "Python egg packages"),
+    ("*.egg-info/", "Python egg metadata"),
+    ("dist/", "Distribution/packaging directory"),
+    ("build/", "Build output directory"),
+    ("venv/", "Python virtual environment"),
+    ("env/", "Python virtual environment"),
+    (".env", "Environment variables file"),
+    (".coverage", "Python coverage data"),
+    (".pytest_cache/", "Pytest cache directory"),
+    ("node_modules/", "Node.js dependencies"),
+    ("npm-debug.log*", "NPM debug logs"),
+    ("yarn-debug.log*", "Yarn debug logs"),
+    ("yarn-error.log*", "Yarn error logs"),
+    ("coverage/", "Test coverage reports"),
+    (".nyc_output", "NYC coverage output"),
+    (".next/", "Next.js build output"),
+    ("out/", "Build output directory"),
+    ("*.class", "Java compiled classes"),
+    ("*.jar", "Java archive files"),
+    ("*.war", "Web application archive"),
+    ("target/", "Maven build output"),
+    (".gradle/", "Gradle cache directory"),
+    ("Cargo.lock", "Cargo lock file"),
+    ("target/", "Rust build output"),
+    ("*.pdb", "Program database files"),
+    ("*.exe", "Executable files"),
+    ("*.dll", "Dynamic link libraries"),
+    ("*.so", "Shared object files"),
+    ("*.dylib", "Dynamic libraries (macOS)"),
+    (".vscode/", "VSCode workspace settings"),
+    (".idea/", "IntelliJ IDEA settings"),
+    ("*.swp", "Vim swap files"),
+    ("*.swo", "Vim swap files"),
+    ("*~", "Backup files"),
+    (".DS_Store", "macOS system files"),
+    ("Thumbs.db", "Windows thumbnail cache"),
+    ("Desktop.ini", "Windows desktop configuration"),
+    ("*.log", "Log files"),
+    ("*.tmp", "Temporary files"),
+    ("*.temp", "Temporary files"),
+    ("*.bak", "Backup files"),
+    ("*.cache", "Cache files"),
+    ("*.pid", "Process ID files"),
+    ("*.lock", "Lock files"),
+];
+
+/// Descriptions of pattern categories
+pub fn category_comments() -> Vec<(PatternCategory, &'static str)> {
+    vec![
+        (PatternCategory::Language("Python".to_string()), "Python language files"),
+        (PatternCategory::Language("Node.js".to_string()), "Node.js language files"),
+        (PatternCategory::Language("Java".to_string()), "Java language files"),
+        (PatternCategory::Language("Rust".to_string()), "Rust language files"),
+        (PatternCategory::Tool("VSCode".to_string()), "VSCode editor files"),
+        (PatternCategory::Tool("IntelliJ".to_string()), "IntelliJ IDEA files"),
+        (PatternCategory::OperatingSystem("macOS".to_string()), "macOS system files"),
+        (PatternCategory::OperatingSystem("Windows".to_string()), "Windows system files"),
+        (PatternCategory::OperatingSystem("Linux".to_string()), "Linux system files"),
+    ]
+}
+
+/// Where the active dataset came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatasetSource {
+    /// Compiled into the binary
+    Embedded,
+    /// Downloaded by `gix dataset update`
+    Cached(PathBuf),
+}
+
+/// A knowledge base of well-known patterns, either embedded or downloaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dataset {
+    /// Dataset version, increasing with every published update
+    pub version: u32,
+    /// Patterns per programming language
+    pub language_patterns: Vec<(String, Vec<String>)>,
+    /// Patterns per framework
+    pub framework_patterns: Vec<(String, Vec<String>)>,
+    /// Patterns per tool
+    pub tool_patterns: Vec<(String, Vec<String>)>,
+    /// Patterns per operating system
+    pub os_patterns: Vec<(String, Vec<String>)>,
+    /// Descriptions of well-known patterns
+    pub pattern_comments: Vec<(String, String)>,
+    /// Descriptions of pattern categories
+    pub category_comments: Vec<(PatternCategory, String)>,
+    /// Where this dataset was loaded from
+    pub source: DatasetSource,
+    /// File of category definitions merged in, from the user's config directory or `from_file`
+    pub definitions: Option<PathBuf>,
+}
+
+impl Dataset {
+    /// The dataset compiled into the binary
+    pub fn embedded() -> Self {
+        let owned = |table: &[(&str, &[&str])]| -> Vec<(String, Vec<String>)> {
+            table.iter()
+                .map(|(name, patterns)| (name.to_string(), patterns.iter().map(|p| p.to_string()).collect()))
+                .collect()
+        };
+        
+        Self {
+            version: EMBEDDED_VERSION,
+            language_patterns: owned(LANGUAGE_PATTERNS),
+            framework_patterns: owned(FRAMEWORK_PATTERNS),
+            tool_patterns: owned(TOOL_PATTERNS),
+            os_patterns: owned(OS_PATTERNS),
+            pattern_comments: PATTERN_COMMENTS.iter().map(|(p, c)| (p.to_string(), c.to_string())).collect(),
+            category_comments: category_comments().into_iter().map(|(category, c)| (category, c.to_string())).collect(),
+            source: DatasetSource::Embedded,
+            definitions: None,
+        }
+    }
+    
+    /// Load the dataset to use: the downloaded one when it is newer, unless `offline` is set
+    ///
+    /// The user's category definitions are left out; merge in `Dataset::user_definitions` to
+    /// categorize as the command line does.
+    pub fn load(offline: bool) -> Self {
+        match offline {
+            true => Self::embedded(),
+            false => cache_path()
+                .and_then(|path| {
+                    let text = std::fs::read_to_string(&path).ok()?;
+                    let mut dataset = Self::parse(&text).ok()?;
+                    dataset.source = DatasetSource::Cached(path);
+                    Some(dataset)
+                })
+                .filter(|dataset| dataset.version > EMBEDDED_VERSION)
+                .unwrap_or_else(Self::embedded),
+        }
+    }
+    
+    /// Read the category definitions in the user's config directory, if there are any
+    pub fn user_definitions() -> Result<Option<Self>, GixError> {
+        match user_definitions_path().filter(|path| path.is_file()) {
+            Some(path) => Self::read_definitions(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+    
+    /// Read a file of category definitions (see `Dataset::parse_definitions`), naming it in errors
+    pub fn read_definitions(path: &Path) -> Result<Self, GixError> {
+        let text = std::fs::read_to_string(path)?;
+        let mut definitions = Self::parse_definitions(&text).map_err(|e| match e {
+            GixError::ConfigError(message) => GixError::ConfigError(format!("{}: {}", path.display(), message)),
+            other => other,
+        })?;
+        definitions.definitions = Some(path.to_path_buf());
+        Ok(definitions)
+    }
+    
+    /// Add the categories and descriptions of `other` to this dataset
+    ///
+    /// A category this dataset already has gains the patterns it doesn't list yet; new categories
+    /// follow the known ones. Descriptions of `other` win, as later entries override earlier ones.
+    pub fn merge(&mut self, other: Dataset) {
+        for (table, added) in [
+            (&mut self.language_patterns, other.language_patterns),
+            (&mut self.framework_patterns, other.framework_patterns),
+            (&mut self.tool_patterns, other.tool_patterns),
+            (&mut self.os_patterns, other.os_patterns),
+        ] {
+            for (name, patterns) in added {
+                match table.iter_mut().find(|(known, _)| *known == name) {
+                    Some((_, known)) => {
+                        for pattern in patterns {
+                            if !known.contains(&pattern) {
+                                known.push(pattern);
+                            }
+                        }
+                    }
+                    None => table.push((name, patterns)),
+                }
+            }
+        }
+        self.pattern_comments.extend(other.pattern_comments);
+        self.category_comments.extend(other.category_comments);
+        self.definitions = other.definitions.or(self.definitions.take());
+    }
+    
+    /// Parse category definitions written in TOML, like `gix.toml`
+    ///
+    /// The `[language]`, `[framework]`, `[tool]` and `[os]` tables map category names to arrays
+    /// of patterns, and `[comments]` maps patterns to descriptions. Every pattern is validated,
+    /// so a typo is reported with its line rather than never matching.
+    pub fn parse_definitions(text: &str) -> Result<Self, GixError> {
+        let mut definitions = Self::empty();
+        
+        for TomlSetting { line, table, key, value } in parse_settings(text)? {
+            let error = |message: String| GixError::ConfigError(format!("line {}: {}", line, message));
+            let table = table.ok_or_else(|| error(format!("'{}' must be in a table, such as [language]", key)))?;
+            if table == "comments" {
+                let comment = parse_string(&value).ok_or_else(|| error(format!("the comment on '{}' must be a string", key)))?;
+                definitions.pattern_comments.push((key, comment));
+                continue;
+            }
+            
+            let categories = match table.as_str() {
+                "language" => &mut definitions.language_patterns,
+                "framework" => &mut definitions.framework_patterns,
+                "tool" => &mut definitions.tool_patterns,
+                "os" => &mut definitions.os_patterns,
+                _ => return Err(error(format!("unknown table '[{}]' (expected language, framework, tool, os or comments)", table))),
+            };
+            let patterns = parse_array(&value).ok_or_else(|| error(format!("{} must be an array of patterns", key)))?;
+            for pattern in &patterns {
+                if let Some(problem) = find_pattern_problems(pattern).first() {
+                    return Err(error(problem.message(pattern)));
+                }
+            }
+            categories.push((key, patterns));
+        }
+        
+        Ok(definitions)
+    }
+    
+    /// Parse the text format written by [`Dataset::render`]
+    ///
+    /// Every pattern is validated, so a typo is reported with its line rather than never matching.
+    pub fn parse(text: &str) -> Result<Self, GixError> {
+        let mut dataset = Self::empty();
+        let mut section: Option<String> = None;
+        
+        for (index, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            let error = |message: &str| GixError::ParseError(format!("dataset line {}: {}", index + 1, message));
+            
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            
+            if let Some(header) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                let (kind, name) = header.split_once(' ').unwrap_or((header, ""));
+                let table = match kind {
+                    "language" => Some(&mut dataset.language_patterns),
+                    "framework" => Some(&mut dataset.framework_patterns),
+                    "tool" => Some(&mut dataset.tool_patterns),
+                    "os" => Some(&mut dataset.os_patterns),
+                    "comments" | "category-comments" => None,
+                    _ => return Err(error(&format!("unknown section '{}'", kind))),
+                };
+                if let Some(table) = table {
+                    if name.is_empty() {
+                        return Err(error("section needs a name"));
+                    }
+                    table.push((name.to_string(), Vec::new()));
+                }
+                section = Some(kind.to_string());
+                continue;
+            }
+            
+            // Entries starting with '#' or '[' are written with a protecting backslash
+            let line = line.strip_prefix('\\').unwrap_or(line);
+            
+            match section.as_deref() {
+                None => {
+                    let version = line.strip_prefix("version")
+                        .and_then(|rest| rest.trim().strip_prefix('='))
+                        .and_then(|value| value.trim().parse().ok())
+                        .ok_or_else(|| error("expected 'version = N'"))?;
+                    dataset.version = version;
+                }
+                Some("comments") => {
+                    let (pattern, comment) = line.split_once(" = ").ok_or_else(|| error("expected 'pattern = comment'"))?;
+                    dataset.pattern_comments.push((pattern.to_string(), comment.to_string()));
+                }
+                Some("category-comments") => {
+                    let (category, comment) = line.split_once(" = ").ok_or_else(|| error("expected 'kind Name = comment'"))?;
+                    let category = parse_category(category).ok_or_else(|| error(&format!("unknown category '{}'", category)))?;
+                    dataset.category_comments.push((category, comment.to_string()));
+                }
+                Some(kind) => {
+                    let table = match kind {
+                        "language" => &mut dataset.language_patterns,
+                        "framework" => &mut dataset.framework_patterns,
+                        "tool" => &mut dataset.tool_patterns,
+                        _ => &mut dataset.os_patterns,
+                    };
+                    if let Some(problem) = find_pattern_problems(line).first() {
+                        return Err(error(&problem.message(line)));
+                    }
+                    if let Some((_, patterns)) = table.last_mut() {
+                        patterns.push(line.to_string());
+                    }
+                }
+            }
+        }
+        
+        if dataset.version == 0 {
+            return Err(GixError::ParseError("dataset is missing its version".to_string()));
+        }
+        Ok(dataset)
+    }
+    
+    /// A dataset without any categories, to be filled by parsing
+    fn empty() -> Self {
+        Self {
+            version: 0,
+            language_patterns: Vec::new(),
+            framework_patterns: Vec::new(),
+            tool_patterns: Vec::new(),
+            os_patterns: Vec::new(),
+            pattern_comments: Vec::new(),
+            category_comments: Vec::new(),
+            source: DatasetSource::Embedded,
+            definitions: None,
+        }
+    }
+    
+    /// Render the dataset in its text format
+    pub fn render(&self) -> String {
+        let mut lines = vec!["# gix pattern dataset".to_string(), format!("version = {}", self.version)];
+        
+        for (kind, table) in [
+            ("language", &self.language_patterns),
+            ("framework", &self.framework_patterns),
+            ("tool", &self.tool_patterns),
+            ("os", &self.os_patterns),
+        ] {
+            for (name, patterns) in table {
+                lines.push(String::new());
+                lines.push(format!("[{} {}]", kind, name));
+                lines.extend(patterns.iter().map(|pattern| escape_entry(pattern)));
+            }
+        }
+        
+        lines.push(String::new());
+        lines.push("[comments]".to_string());
+        lines.extend(self.pattern_comments.iter().map(|(pattern, comment)| escape_entry(&format!("{} = {}", pattern, comment))));
+        
+        lines.push(String::new());
+        lines.push("[category-comments]".to_string());
+        lines.extend(self.category_comments.iter().filter_map(|(category, comment)| {
+            category_key(category).map(|key| format!("{} = {}", key, comment))
+        }));
+        
+        let mut text = lines.join("\n");
+        text.push('\n');
+        text
+    }
+}
+
+/// Path of the downloaded dataset in the user's cache directory
+pub fn cache_path() -> Option<PathBuf> {
+    Some(crate::utils::file::cache_dir()?.join("dataset.txt"))
+}
+
+/// Path of the user's category definitions, `categories.toml` next to the user's config file
+pub fn user_definitions_path() -> Option<PathBuf> {
+    Some(user_config_path()?.with_file_name("categories.toml"))
+}
+
+/// Result of a dataset update
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatasetUpdate {
+    /// A newer dataset was stored at the given path
+    Updated { version: u32, path: PathBuf },
+    /// The published dataset is not newer than the active one
+    UpToDate { version: u32 },
+}
+
+/// Download the dataset at `url`, verify it against `<url>.sha256` and store it in the cache directory
+#[cfg(feature = "network")]
+pub fn update_dataset(url: &str) -> Result<DatasetUpdate, GixError> {
+    use crate::utils::download::{fetch_text, sha256_hex};
+    
+    let text = fetch_text(url)?;
+    let published = fetch_text(&format!("{}.sha256", url))?;
+    let expected = published.split_whitespace().next().unwrap_or_default().to_lowercase();
+    let actual = sha256_hex(text.as_bytes());
+    if expected != actual {
+        return Err(GixError::ChecksumMismatch { expected, actual });
+    }
+    
+    let dataset = Dataset::parse(&text)?;
+    let active = Dataset::load(false);
+    if dataset.version <= active.version {
+        return Ok(DatasetUpdate::UpToDate { version: active.version });
+    }
+    
+    let path = cache_path().ok_or_else(|| GixError::NetworkError("no cache directory available".to_string()))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    crate::utils::write_gitignore_file(&path, &text)?;
+    
+    Ok(DatasetUpdate::Updated { version: dataset.version, path })
+}
+
+/// Datasets can only be downloaded when gix is built with the `network` feature
+#[cfg(not(feature = "network"))]
+pub fn update_dataset(_url: &str) -> Result<DatasetUpdate, GixError> {
+    Err(GixError::NetworkError("gix was built without the `network` feature".to_string()))
+}
+
+/// Protect entries that would otherwise read as comments or section headers
+fn escape_entry(entry: &str) -> String {
+    if entry.starts_with(['#', '[', '\\']) {
+        format!("\\{}", entry)
+    } else {
+        entry.to_string()
+    }
+}
+
+fn category_key(category: &PatternCategory) -> Option<String> {
+    match category {
+        PatternCategory::Language(name) => Some(format!("language {}", name)),
+        PatternCategory::Framework(name) => Some(format!("framework {}", name)),
+        PatternCategory::Tool(name) => Some(format!("tool {}", name)),
+        PatternCategory::OperatingSystem(name) => Some(format!("os {}", name)),
+        PatternCategory::Custom(_) | PatternCategory::Uncategorized => None,
+    }
+}
+
+fn parse_category(key: &str) -> Option<PatternCategory> {
+    let (kind, name) = key.split_once(' ')?;
+    let name = name.to_string();
+    match kind {
+        "language" => Some(PatternCategory::Language(name)),
+        "framework" => Some(PatternCategory::Framework(name)),
+        "tool" => Some(PatternCategory::Tool(name)),
+        "os" => Some(PatternCategory::OperatingSystem(name)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_and_parse_round_trip() {
+        let embedded = Dataset::embedded();
+        let parsed = Dataset::parse(&embedded.render()).unwrap();
+        assert_eq!(parsed, embedded);
+    }
+
+    #[test]
+    fn test_parse_dataset() {
+        let text = "version = 7\n\n[language Zig]\nzig-cache/\nzig-out/\n\n[comments]\nzig-out/ = Zig build output\n\n[category-comments]\nlanguage Zig = Zig language files\n";
+        let dataset = Dataset::parse(text).unwrap();
+        
+        assert_eq!(dataset.version, 7);
+        assert_eq!(dataset.language_patterns, vec![("Zig".to_string(), vec!["zig-cache/".to_string(), "zig-out/".to_string()])]);
+        assert_eq!(dataset.pattern_comments[0].1, "Zig build output");
+        assert_eq!(dataset.category_comments[0].0, PatternCategory::Language("Zig".to_string()));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_dataset() {
+        assert!(Dataset::parse("[language Zig]\nzig-out/\n").is_err());
+        assert!(Dataset::parse("version = 2\n[plugins]\n").is_err());
+        assert!(Dataset::parse("version = 2\n[comments]\nno separator\n").is_err());
+        assert!(Dataset::parse("version = 2\n[tool Bazel]\nbazel-*/\n*.[ch\n").unwrap_err().to_string().contains("line 4"));
+    }
+
+    #[test]
+    fn test_merge_definitions() {
+        let definitions = Dataset::parse_definitions("[language]\nRust = [\"target/\", \"*.profraw\"]\n\n[tool]\nBazel = [\"bazel-*/\"]\n").unwrap();
+        let mut dataset = Dataset::embedded();
+        dataset.merge(definitions);
+        
+        let rust = &dataset.language_patterns.iter().find(|(name, _)| name == "Rust").unwrap().1;
+        assert_eq!(rust.iter().filter(|pattern| *pattern == "target/").count(), 1);
+        assert_eq!(rust.last().map(String::as_str), Some("*.profraw"));
+        assert_eq!(dataset.tool_patterns.last(), Some(&("Bazel".to_string(), vec!["bazel-*/".to_string()])));
+        assert_eq!(dataset.version, EMBEDDED_VERSION);
+    }
+
+    #[test]
+    fn test_parse_definitions() {
+        let definitions = Dataset::parse_definitions(concat!(
+            "# Categories of our own\n",
+            "[language]\n",
+            "\"Node.js\" = [\n  \".turbo/\",  # build cache\n  \"*.tsbuildinfo\",\n]\n",
+            "\n[comments]\n",
+            "\".turbo/\" = \"Turborepo cache\"\n",
+        )).unwrap();
+        assert_eq!(definitions.language_patterns, vec![("Node.js".to_string(), vec![".turbo/".to_string(), "*.tsbuildinfo".to_string()])]);
+        assert_eq!(definitions.pattern_comments, vec![(".turbo/".to_string(), "Turborepo cache".to_string())]);
+        
+        let message = |text: &str| Dataset::parse_definitions(text).unwrap_err().to_string();
+        assert!(message("Rust = [\"target/\"]").contains("line 1: 'Rust' must be in a table"));
+        assert!(message("[editor]\nVim = [\"*.swp\"]").contains("line 2: unknown table '[editor]'"));
+        assert!(message("[tool]\nBazel = \"bazel-*/\"").contains("Bazel must be an array of patterns"));
+        assert!(message("[tool]\nBazel = [\"bazel-*/\", \"*.[ch\"]").contains("line 2:"));
+        assert!(message("[language]\nNode.js = [\"node_modules/\"]").contains("quote the key 'Node.js'"));
+    }
+
+    #[test]
+    fn test_offline_uses_embedded_dataset() {
+        assert_eq!(Dataset::load(true).source, DatasetSource::Embedded);
+    }
+}
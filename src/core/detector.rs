@@ -0,0 +1,219 @@
+use crate::core::categorizer::{PatternCategorizer, PatternCategory};
+use crate::core::histogram::pattern_histogram;
+use crate::models::GitignoreFile;
+use crate::utils::patterns::GlobPattern;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Files whose presence in the project root reveals its language
+const LANGUAGE_MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Rust"),
+    ("package.json", "Node.js"),
+    ("pyproject.toml", "Python"),
+    ("requirements.txt", "Python"),
+    ("setup.py", "Python"),
+    ("Pipfile", "Python"),
+    ("go.mod", "Go"),
+    ("pom.xml", "Java"),
+    ("build.gradle", "Java"),
+    ("build.gradle.kts", "Java"),
+];
+
+/// Manifests mentioning a dependency that reveals a framework, matched case-insensitively
+const FRAMEWORK_MARKERS: &[(&str, &str, &str)] = &[
+    ("package.json", "\"react\"", "React"),
+    ("requirements.txt", "django", "Django"),
+    ("pyproject.toml", "django", "Django"),
+    ("Pipfile", "django", "Django"),
+    ("pom.xml", "spring", "Spring"),
+    ("build.gradle", "spring", "Spring"),
+    ("build.gradle.kts", "spring", "Spring"),
+];
+
+/// Directories whose presence in the project root reveals an editor or IDE
+const TOOL_MARKERS: &[(&str, &str)] = &[
+    (".idea", "IntelliJ"),
+    (".vscode", "VSCode"),
+    (".settings", "Eclipse"),
+];
+
+/// Known patterns of a detected category that a file doesn't ignore yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The detected language, framework or tool
+    pub category: PatternCategory,
+    /// Its known patterns that no pattern of the file covers, in dataset order
+    pub patterns: Vec<String>,
+}
+
+/// Minimum number of patterns in one area before `gix suggest` proposes collapsing them
+pub const COLLAPSE_MIN_PATTERNS: usize = 5;
+
+/// A directory or extension pattern that could replace patterns listing paths one by one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollapseSuggestion {
+    /// The broader pattern, such as `docs/` or `*.log`
+    pub pattern: String,
+    /// The patterns it would replace, in file order
+    pub replaces: Vec<String>,
+}
+
+/// Detect the languages, frameworks and tools of the project at `dir` from its manifests and editor directories
+///
+/// Languages come first, then frameworks, then tools, each without duplicates and in the
+/// order of the marker tables, so the result is the same on every run.
+pub fn detect_stack(dir: &Path) -> Vec<PatternCategory> {
+    let mut stack = Vec::new();
+    for (file, language) in LANGUAGE_MARKERS {
+        let category = PatternCategory::Language(language.to_string());
+        if dir.join(file).is_file() && !stack.contains(&category) {
+            stack.push(category);
+        }
+    }
+
+    for (file, dependency, framework) in FRAMEWORK_MARKERS {
+        let category = PatternCategory::Framework(framework.to_string());
+        if stack.contains(&category) {
+            continue;
+        }
+        let mentions = fs::read_to_string(dir.join(file))
+            .map(|content| content.to_lowercase().contains(dependency))
+            .unwrap_or(false);
+        if mentions {
+            stack.push(category);
+        }
+    }
+
+    for (marker, tool) in TOOL_MARKERS {
+        let category = PatternCategory::Tool(tool.to_string());
+        if dir.join(marker).is_dir() && !stack.contains(&category) {
+            stack.push(category);
+        }
+    }
+
+    stack
+}
+
+/// Suggest the known patterns of each category in `stack` that `file` lacks
+///
+/// A known pattern is missing when no pattern of the file covers it or a directory above it. Patterns shared by
+/// several categories are only suggested for the first, and categories with nothing missing
+/// are left out.
+pub fn suggest_patterns(file: &GitignoreFile, stack: &[PatternCategory], categorizer: &PatternCategorizer) -> Vec<Suggestion> {
+    let existing: Vec<GlobPattern> = file.entries.iter()
+        .filter_map(|entry| entry.comparison_pattern())
+        .map(|pattern| GlobPattern::parse(&pattern))
+        .filter(|glob| !glob.negated)
+        .collect();
+    let mut seen: HashSet<&str> = HashSet::new();
+
+    stack.iter()
+        .map(|category| {
+            let patterns = categorizer.patterns_of(category).iter()
+                .filter(|pattern| seen.insert(pattern.as_str()))
+                .filter(|pattern| {
+                    let wanted = GlobPattern::parse(pattern);
+                    !existing.iter().any(|glob| glob.covers(&wanted) || glob.covers_parent_of(&wanted))
+                })
+                .cloned()
+                .collect();
+            Suggestion { category: category.clone(), patterns }
+        })
+        .filter(|suggestion| !suggestion.patterns.is_empty())
+        .collect()
+}
+
+/// Suggest a directory or extension pattern for each area with at least `min_patterns` patterns
+///
+/// Areas come from the pattern histogram (see `pattern_histogram`). The broader pattern also
+/// ignores paths the file doesn't list, so it is only a suggestion; none is made where the file
+/// already has it or a negation re-includes something it would cover.
+pub fn suggest_collapses(file: &GitignoreFile, min_patterns: usize) -> Vec<CollapseSuggestion> {
+    let patterns: Vec<String> = file.entries.iter().filter_map(|entry| entry.comparison_pattern()).collect();
+    let negations: Vec<GlobPattern> = patterns.iter().map(|pattern| GlobPattern::parse(pattern)).filter(|glob| glob.negated).collect();
+
+    pattern_histogram(patterns.iter().map(String::as_str))
+        .at_least(min_patterns)
+        .filter_map(|bucket| {
+            let pattern = bucket.key.collapsed_pattern();
+            let glob = GlobPattern::parse(&pattern);
+            let overrides_negation = negations.iter().any(|negation| glob.may_overlap(negation) || glob.covers_parent_of(negation));
+            (!patterns.contains(&pattern) && !overrides_negation).then(|| CollapseSuggestion { pattern, replaces: bucket.patterns.clone() })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_languages_and_frameworks() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("package.json"), r#"{"dependencies": {"react": "^18.0.0"}}"#).unwrap();
+        fs::write(dir.path().join("requirements.txt"), "Django==4.2\n").unwrap();
+        fs::write(dir.path().join("pyproject.toml"), "[project]\n").unwrap();
+
+        assert_eq!(detect_stack(dir.path()), vec![
+            PatternCategory::Language("Node.js".to_string()),
+            PatternCategory::Language("Python".to_string()),
+            PatternCategory::Framework("React".to_string()),
+            PatternCategory::Framework("Django".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_detect_editor_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        fs::create_dir(dir.path().join(".vscode")).unwrap();
+        fs::create_dir(dir.path().join(".idea")).unwrap();
+
+        assert_eq!(detect_stack(dir.path()), vec![
+            PatternCategory::Language("Rust".to_string()),
+            PatternCategory::Tool("IntelliJ".to_string()),
+            PatternCategory::Tool("VSCode".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_suggest_missing_patterns() {
+        let categorizer = PatternCategorizer::new();
+        let vscode = PatternCategory::Tool("VSCode".to_string());
+        let intellij = PatternCategory::Tool("IntelliJ".to_string());
+        let file = crate::core::parser::parse_gitignore(".vscode/\n*.iml\n").unwrap();
+
+        let suggestions = suggest_patterns(&file, &[vscode.clone(), intellij.clone()], &categorizer);
+        let found: Vec<(&PatternCategory, Vec<&str>)> = suggestions.iter()
+            .map(|suggestion| (&suggestion.category, suggestion.patterns.iter().map(String::as_str).collect()))
+            .collect();
+        #[cfg(feature = "dataset")]
+        assert_eq!(found, vec![
+            (&vscode, vec!["*.code-workspace"]),
+            (&intellij, vec![".idea/", "*.iws", "*.ipr", ".idea_modules/"]),
+        ]);
+        #[cfg(not(feature = "dataset"))]
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_suggest_collapses() {
+        let parse = crate::core::parser::parse_gitignore;
+        let file = parse("docs/a.md\ndocs/b.md\ndocs/c/\na.log\nb.log\nc.log\n").unwrap();
+        assert_eq!(suggest_collapses(&file, 3), vec![
+            CollapseSuggestion { pattern: "docs/".to_string(), replaces: vec!["docs/a.md".to_string(), "docs/b.md".to_string(), "docs/c/".to_string()] },
+            CollapseSuggestion { pattern: "*.log".to_string(), replaces: vec!["a.log".to_string(), "b.log".to_string(), "c.log".to_string()] },
+        ]);
+        assert!(suggest_collapses(&file, 4).is_empty());
+
+        let file = parse("docs/a.md\ndocs/b.md\ndocs/c.md\n!docs/README.md\n").unwrap();
+        assert!(suggest_collapses(&file, 3).is_empty());
+    }
+
+    #[test]
+    fn test_detect_nothing_in_empty_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(detect_stack(dir.path()).is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+use crate::core::unused::BUILD_OUTPUT_NAMES;
+use crate::models::GitignoreFile;
+use crate::utils::patterns::{pattern_matches, GlobPattern};
+use crate::utils::tokens::{render, tokenize, Segment};
+
+/// Files that commonly hold credentials and should stay out of both commits and build contexts
+pub const SECRET_PATTERNS: &[&str] = &[
+    ".env", ".env.*", "*.pem", "*.key", "*.p12", "*.pfx", "id_rsa", "id_ed25519",
+    ".npmrc", ".pypirc", "credentials.json", "secrets.*", "*.secret",
+];
+
+/// Why a pattern should be in both ignore files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncConcern {
+    /// Build output or cache: bloats the build context and the repository
+    BuildOutput,
+    /// Credentials: must not leak into images or commits
+    Secret,
+}
+
+impl SyncConcern {
+    /// Get the name used in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            SyncConcern::BuildOutput => "build output",
+            SyncConcern::Secret => "secret",
+        }
+    }
+}
+
+/// A pattern one file has and the other lacks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncGap {
+    /// Line number of the pattern in the file that has it (1-indexed)
+    pub line_number: usize,
+    /// The pattern, as written in the file that has it
+    pub pattern: String,
+    /// The same pattern in the other file's dialect
+    pub translated: String,
+    /// Why it matters
+    pub concern: SyncConcern,
+}
+
+/// Build-output and secret patterns missing from either file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// `.gitignore` patterns the `.dockerignore` lacks
+    pub missing_in_dockerignore: Vec<SyncGap>,
+    /// `.dockerignore` patterns the `.gitignore` lacks
+    pub missing_in_gitignore: Vec<SyncGap>,
+}
+
+impl SyncReport {
+    /// Count the gaps in both directions
+    pub fn len(&self) -> usize {
+        self.missing_in_dockerignore.len() + self.missing_in_gitignore.len()
+    }
+
+    /// Check if both files agree
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Translate a gitignore pattern to `.dockerignore` syntax
+///
+/// Docker matches every pattern from the context root and has no directory-only patterns, so
+/// an unanchored `*.log` becomes `**/*.log` and `build/` becomes `**/build`.
+pub fn to_dockerignore(pattern: &str) -> String {
+    let glob = GlobPattern::parse(pattern);
+    let negation = if glob.negated { "!" } else { "" };
+    if glob.anchored {
+        format!("{}{}", negation, glob.body)
+    } else {
+        format!("{}**/{}", negation, glob.body)
+    }
+}
+
+/// Translate a `.dockerignore` pattern to gitignore syntax
+///
+/// `**/name` matches at any depth, which is what an unanchored gitignore pattern does; any
+/// other pattern is anchored to the root.
+pub fn from_dockerignore(pattern: &str) -> String {
+    let (negation, body) = match pattern.strip_prefix('!') {
+        Some(body) => ("!", body),
+        None => ("", pattern),
+    };
+    let segments = tokenize(body);
+    let start = segments.iter().position(|segment| !segment.is_empty()).unwrap_or(segments.len());
+    match &segments[start..] {
+        [globstar, name] if globstar.is_globstar() => format!("{}{}", negation, name),
+        [name] => format!("{}/{}", negation, name),
+        segments => format!("{}{}", negation, render(segments)),
+    }
+}
+
+/// Classify a pattern as build output or secret by its last path component
+pub fn sync_concern(pattern: &str) -> Option<SyncConcern> {
+    let glob = GlobPattern::parse(pattern);
+    let name = glob.name();
+    let known = |list: &[&str]| list.contains(&name.as_str()) || list.iter().any(|known| pattern_matches(known, &name));
+    if glob.negated || name.is_empty() {
+        None
+    } else if known(SECRET_PATTERNS) {
+        Some(SyncConcern::Secret)
+    } else if known(BUILD_OUTPUT_NAMES) {
+        Some(SyncConcern::BuildOutput)
+    } else {
+        None
+    }
+}
+
+/// Compare the build-output and secret patterns of a `.gitignore` and a `.dockerignore`
+///
+/// A `.gitignore` pattern counts as present when the `.dockerignore` covers it at the context
+/// root, since build contexts rarely hold nested outputs; a `.dockerignore` entry counts as
+/// present when the `.gitignore` covers it as a file or as a directory.
+pub fn sync_check(gitignore: &GitignoreFile, dockerignore: &GitignoreFile) -> SyncReport {
+    let git_globs: Vec<GlobPattern> = patterns(gitignore).map(|(_, pattern)| GlobPattern::parse(&pattern)).collect();
+    let docker_globs: Vec<GlobPattern> = patterns(dockerignore)
+        .map(|(_, pattern)| GlobPattern::parse(&from_dockerignore(&pattern)))
+        .collect();
+    let covered_by = |globs: &[GlobPattern], wanted: &[GlobPattern]| {
+        globs.iter().any(|glob| !glob.negated && wanted.iter().any(|wanted| glob.covers(wanted)))
+    };
+
+    let mut report = SyncReport::default();
+    for (line_number, pattern) in patterns(gitignore) {
+        let Some(concern) = sync_concern(&pattern) else {
+            continue;
+        };
+        let glob = GlobPattern::parse(&pattern);
+        let at_root = GlobPattern { anchored: true, directory_only: false, body: glob.name(), segments: last_segment(&glob), ..glob.clone() };
+        if !covered_by(&docker_globs, &[glob, at_root]) {
+            report.missing_in_dockerignore.push(SyncGap { line_number, translated: to_dockerignore(&pattern), pattern, concern });
+        }
+    }
+    for (line_number, pattern) in patterns(dockerignore) {
+        let translated = from_dockerignore(&pattern);
+        let Some(concern) = sync_concern(&translated) else {
+            continue;
+        };
+        let glob = GlobPattern::parse(&translated);
+        let as_dir = GlobPattern { directory_only: true, ..glob.clone() };
+        if !covered_by(&git_globs, &[glob, as_dir]) {
+            report.missing_in_gitignore.push(SyncGap { line_number, pattern, translated, concern });
+        }
+    }
+    report
+}
+
+fn patterns(file: &GitignoreFile) -> impl Iterator<Item = (usize, String)> + '_ {
+    file.entries.iter().filter_map(|entry| Some((entry.line_number, entry.comparison_pattern()?)))
+}
+
+fn last_segment(glob: &GlobPattern) -> Vec<Segment> {
+    glob.segments.last().cloned().into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_translate_between_dialects() {
+        assert_eq!(to_dockerignore("*.log"), "**/*.log");
+        assert_eq!(to_dockerignore("build/"), "**/build");
+        assert_eq!(to_dockerignore("/dist/"), "dist");
+        assert_eq!(to_dockerignore("!keep.env"), "!**/keep.env");
+
+        assert_eq!(from_dockerignore("**/*.pem"), "*.pem");
+        assert_eq!(from_dockerignore("node_modules"), "/node_modules");
+        assert_eq!(from_dockerignore("/docs/build"), "docs/build");
+    }
+
+    #[test]
+    fn test_sync_check_reports_gaps_both_ways() {
+        let gitignore = parse_gitignore("target/\n*.log\n.env\nnode_modules/\n").unwrap();
+        let dockerignore = parse_gitignore("# Context\ntarget\n.git\n*.pem\n**/node_modules\n").unwrap();
+        let report = sync_check(&gitignore, &dockerignore);
+
+        let missing: Vec<(&str, &str, SyncConcern)> = report.missing_in_dockerignore.iter()
+            .map(|gap| (gap.pattern.as_str(), gap.translated.as_str(), gap.concern))
+            .collect();
+        assert_eq!(missing, vec![(".env", "**/.env", SyncConcern::Secret)]);
+
+        assert_eq!(report.missing_in_gitignore.len(), 1);
+        assert_eq!(report.missing_in_gitignore[0].line_number, 4);
+        assert_eq!(report.missing_in_gitignore[0].translated, "/*.pem");
+        assert_eq!(report.len(), 2);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::models::{GitignoreFile, GitignoreMatcher};
+use crate::utils::patterns::GlobPattern;
+use std::collections::BTreeSet;
+
+/// A path two gitignore files decide differently
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecisionChange {
+    /// The path, directories with a trailing `/`
+    pub path: String,
+    /// Whether the first file ignores it
+    pub ignored_before: bool,
+    /// Whether the second file ignores it
+    pub ignored_after: bool,
+}
+
+impl DecisionChange {
+    /// Describe the change for reports
+    pub fn describe(&self) -> String {
+        match (self.ignored_before, self.ignored_after) {
+            (true, false) => format!("'{}' would no longer be ignored", self.path),
+            _ => format!("'{}' would become ignored", self.path),
+        }
+    }
+}
+
+/// The outcome of comparing two gitignore files path by path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EquivalenceReport {
+    /// Number of paths both files were asked about
+    pub checked_paths: usize,
+    /// Paths the files decide differently
+    pub changes: Vec<DecisionChange>,
+}
+
+impl EquivalenceReport {
+    /// Check if every checked path is decided the same way
+    pub fn is_equivalent(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Compare which paths two gitignore files ignore
+///
+/// Both files are asked about the probe paths of every pattern in either of them (see
+/// `probe_paths`), which exercises each pair of patterns that can meet on a path, and about
+/// `sample_paths`, such as the paths of the working tree. Equivalence is therefore proven for
+/// those paths only, but a pattern whose removal or reordering matters shows up in them.
+pub fn files_equivalent(a: &GitignoreFile, b: &GitignoreFile, sample_paths: &[String]) -> EquivalenceReport {
+    let globs: Vec<GlobPattern> = [a, b].iter()
+        .flat_map(|file| file.entries.iter().filter_map(|entry| entry.comparison_pattern()))
+        .map(|pattern| GlobPattern::parse(&pattern))
+        .collect();
+    let mut paths: BTreeSet<String> = probe_paths(&globs).into_iter().collect();
+    paths.extend(sample_paths.iter().cloned());
+    let paths: Vec<String> = paths.into_iter().collect();
+
+    EquivalenceReport { checked_paths: paths.len(), changes: decision_changes(a, b, &paths) }
+}
+
+/// Sample paths whose decision depends on the negations of the given files
+///
+/// Each negation contributes its probe paths (see `probe_paths`), and so does every pattern
+/// that may overlap one or ignore a parent of what it re-includes. An unanchored negation's
+/// witnesses are also tried in the directories anchored patterns name (`logs/b.txt` for
+/// `!b.txt` and `logs/*.txt`). Files without negations give no paths.
+pub fn negation_probes(files: &[&GitignoreFile]) -> Vec<String> {
+    let globs: Vec<GlobPattern> = files.iter()
+        .flat_map(|file| file.entries.iter().filter_map(|entry| entry.comparison_pattern()))
+        .map(|pattern| GlobPattern::parse(&pattern))
+        .collect();
+    let negations: Vec<&GlobPattern> = globs.iter().filter(|glob| glob.negated).collect();
+
+    let relevant: Vec<GlobPattern> = globs.iter()
+        .filter(|glob| glob.negated || negations.iter().any(|negation| glob.may_overlap(negation) || glob.covers_parent_of(negation)))
+        .cloned()
+        .collect();
+    let mut probes = probe_paths(&relevant);
+
+    let directories: BTreeSet<String> = globs.iter()
+        .filter(|glob| glob.anchored)
+        .flat_map(GlobPattern::witnesses)
+        .filter_map(|witness| witness.trim_end_matches('/').rsplit_once('/').map(|(directory, _)| directory.to_string()))
+        .collect();
+    for negation in negations.iter().filter(|negation| !negation.anchored) {
+        for witness in negation.witnesses() {
+            probes.extend(directories.iter().map(|directory| format!("{}/{}", directory, witness)));
+        }
+    }
+    probes.sort();
+    probes.dedup();
+    probes
+}
+
+/// Sample paths the given patterns apply to, sorted and without repeats
+///
+/// These are the witnesses of each pattern; unanchored witnesses are also tried one directory
+/// down, and directory witnesses (with a trailing `/`) with a file inside.
+pub fn probe_paths(globs: &[GlobPattern]) -> Vec<String> {
+    let mut probes = BTreeSet::new();
+    for glob in globs {
+        for witness in glob.witnesses() {
+            let nested = (!glob.anchored).then(|| format!("sub/{}", witness));
+            for path in std::iter::once(witness).chain(nested) {
+                if path.ends_with('/') {
+                    probes.insert(format!("{}x", path));
+                }
+                probes.insert(path);
+            }
+        }
+    }
+    probes.into_iter().collect()
+}
+
+/// Find the paths among `paths` that `before` and `after` decide differently
+pub fn decision_changes(before: &GitignoreFile, after: &GitignoreFile, paths: &[String]) -> Vec<DecisionChange> {
+    let (before, after) = (before.matcher(), after.matcher());
+    let ignored = |matcher: &GitignoreMatcher, path: &str| matcher.matches(path, false).is_some_and(|decision| decision.ignored);
+    paths.iter()
+        .filter_map(|path| {
+            let (ignored_before, ignored_after) = (ignored(&before, path), ignored(&after, path));
+            (ignored_before != ignored_after).then(|| DecisionChange { path: path.clone(), ignored_before, ignored_after })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_negation_probes() {
+        let file = parse_gitignore("*.log\n!debug.log\nbuild/\n").unwrap();
+        let probes = negation_probes(&[&file]);
+
+        assert!(probes.contains(&"debug.log".to_string()));
+        assert!(probes.contains(&"sub/debug.log".to_string()));
+        assert!(probes.contains(&"x.log/x".to_string()));
+        assert!(!probes.iter().any(|path| path.starts_with("build")));
+        assert!(negation_probes(&[&parse_gitignore("*.log\nbuild/\n").unwrap()]).is_empty());
+
+        // The negated name inside a directory an anchored pattern names
+        let before = parse_gitignore("*.txt\n!b.txt\nlogs/*.txt\n").unwrap();
+        let after = parse_gitignore("*.txt\n!b.txt\n").unwrap();
+        let probes = negation_probes(&[&before, &after]);
+        assert!(probes.contains(&"logs/b.txt".to_string()));
+        assert!(decision_changes(&before, &after, &probes).iter().any(|change| change.path == "logs/b.txt"));
+    }
+
+    #[test]
+    fn test_decision_changes() {
+        let before = parse_gitignore("*.log\n!debug.log\n*.log\n").unwrap();
+        let after = parse_gitignore("*.log\n!debug.log\n").unwrap();
+        let probes = negation_probes(&[&before, &after]);
+
+        let changes = decision_changes(&before, &after, &probes);
+        assert!(changes.contains(&DecisionChange { path: "debug.log".to_string(), ignored_before: true, ignored_after: false }));
+        assert_eq!(changes[0].describe(), format!("'{}' would no longer be ignored", changes[0].path));
+        assert!(decision_changes(&before, &before, &probes).is_empty());
+    }
+
+    #[test]
+    fn test_files_equivalent() {
+        let original = parse_gitignore("*.log\n*.log\nbuild/\nbuild/cache/\n!debug.log\n").unwrap();
+        let optimized = parse_gitignore("*.log\nbuild/\n!debug.log\n").unwrap();
+        let report = files_equivalent(&original, &optimized, &["src/main.rs".to_string()]);
+        assert!(report.is_equivalent());
+        assert!(report.checked_paths > 1);
+
+        let reordered = parse_gitignore("!debug.log\n*.log\nbuild/\n").unwrap();
+        let report = files_equivalent(&original, &reordered, &[]);
+        assert!(!report.is_equivalent());
+        assert!(report.changes.iter().any(|change| change.path == "debug.log" && !change.ignored_before));
+    }
+}
@@ -0,0 +1,186 @@
+use crate::models::{GitignoreFile, PathMatch};
+use crate::utils::patterns::GlobPattern;
+
+/// What became of a pattern that matched the path or one of its parent directories
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchOutcome {
+    /// The pattern decided whether the path is ignored
+    Decided,
+    /// A later pattern matched the same path or directory, so this one lost
+    Overridden {
+        /// Line number of the later pattern
+        by_line: usize,
+    },
+    /// A parent directory is ignored, so nothing below it is looked at; a negation here has no effect
+    ParentExcluded,
+    /// The pattern won for its parent directory, which is not ignored, and the path itself was decided below it
+    Superseded,
+}
+
+impl MatchOutcome {
+    /// Describe the outcome for reports
+    pub fn describe(&self) -> String {
+        match self {
+            MatchOutcome::Decided => "decides".to_string(),
+            MatchOutcome::Overridden { by_line } => format!("overridden by line {}", by_line),
+            MatchOutcome::ParentExcluded => "has no effect: a parent directory is ignored".to_string(),
+            MatchOutcome::Superseded => "superseded: the path itself matched a later rule".to_string(),
+        }
+    }
+}
+
+/// A pattern that matched the path or one of its parent directories
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedPattern {
+    /// Line number of the pattern (1-indexed)
+    pub line_number: usize,
+    /// The pattern
+    pub pattern: String,
+    /// Whether the pattern is a negation
+    pub negated: bool,
+    /// What it matched: the path, or a parent directory with a trailing `/`
+    pub matched: String,
+    /// What became of it
+    pub outcome: MatchOutcome,
+}
+
+/// Every pattern that matched a path, in the order git considers them, and the decision
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathExplanation {
+    /// The explained path
+    pub path: String,
+    /// Matching patterns, parent directories outermost first and the path itself last
+    pub matches: Vec<MatchedPattern>,
+    /// The deciding pattern, `None` when nothing matches
+    pub decision: Option<PathMatch>,
+}
+
+impl PathExplanation {
+    /// Check if the path is ignored
+    pub fn is_ignored(&self) -> bool {
+        self.decision.as_ref().is_some_and(|decision| decision.ignored)
+    }
+
+    /// Count the negations that matched but didn't re-include the path
+    pub fn overridden_negations(&self) -> usize {
+        self.matches.iter().filter(|found| found.negated && found.outcome != MatchOutcome::Decided).count()
+    }
+}
+
+/// Explain how `file` decides whether `path` is ignored
+///
+/// Git checks each parent directory, outermost first, then the path itself; for each the last
+/// matching pattern wins, and an ignored directory hides everything below it.
+pub fn explain_path(file: &GitignoreFile, path: &str, is_dir: bool) -> PathExplanation {
+    let decision = file.matches(path, is_dir);
+    let trimmed = path.trim_start_matches("./").trim_start_matches('/');
+    let (trimmed, is_dir) = match trimmed.strip_suffix('/') {
+        Some(dir) => (dir, true),
+        None => (trimmed, is_dir),
+    };
+
+    let globs: Vec<(usize, String, GlobPattern)> = file.entries.iter()
+        .filter_map(|entry| {
+            let pattern = entry.comparison_pattern()?;
+            let glob = GlobPattern::parse(&pattern);
+            Some((entry.line_number, pattern, glob))
+        })
+        .collect();
+
+    let mut candidates: Vec<(&str, bool)> = trimmed.match_indices('/').map(|(end, _)| (&trimmed[..end], true)).collect();
+    candidates.push((trimmed, is_dir));
+
+    // The directory whose exclusion hides everything below it, if any
+    let excluded_dir = decision.as_ref()
+        .filter(|decision| decision.ignored)
+        .and_then(|decision| decision.via_directory.as_deref())
+        .map(|dir| dir.trim_end_matches('/'));
+
+    let mut matches = Vec::new();
+    for (candidate, candidate_is_dir) in candidates {
+        let matching: Vec<&(usize, String, GlobPattern)> = globs.iter()
+            .filter(|(_, _, glob)| glob.matches_path(candidate, candidate_is_dir))
+            .collect();
+        let Some(last) = matching.last().map(|(line_number, _, _)| *line_number) else {
+            continue;
+        };
+        let matched = if candidate_is_dir && candidate != trimmed { format!("{}/", candidate) } else { candidate.to_string() };
+        let below_excluded = excluded_dir.is_some_and(|dir| candidate.len() > dir.len() && candidate.starts_with(dir));
+
+        for (line_number, pattern, glob) in matching {
+            let decided = decision.as_ref().is_some_and(|decision| {
+                decision.line_number == *line_number
+                    && decision.via_directory.as_deref().map_or(candidate == trimmed, |dir| dir.trim_end_matches('/') == candidate)
+            });
+            let outcome = if decided {
+                MatchOutcome::Decided
+            } else if below_excluded {
+                MatchOutcome::ParentExcluded
+            } else if *line_number != last {
+                MatchOutcome::Overridden { by_line: last }
+            } else {
+                MatchOutcome::Superseded
+            };
+            matches.push(MatchedPattern {
+                line_number: *line_number,
+                pattern: pattern.clone(),
+                negated: glob.negated,
+                matched: matched.clone(),
+                outcome,
+            });
+        }
+    }
+
+    PathExplanation { path: path.to_string(), matches, decision }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    fn outcomes(explanation: &PathExplanation) -> Vec<(usize, &str, MatchOutcome)> {
+        explanation.matches.iter().map(|found| (found.line_number, found.matched.as_str(), found.outcome)).collect()
+    }
+
+    #[test]
+    fn test_explain_last_match_wins() {
+        let file = parse_gitignore("*.log\n!important.log\nlogs/\n*.log").unwrap();
+        let explanation = explain_path(&file, "important.log", false);
+
+        assert!(explanation.is_ignored());
+        assert_eq!(outcomes(&explanation), vec![
+            (1, "important.log", MatchOutcome::Overridden { by_line: 4 }),
+            (2, "important.log", MatchOutcome::Overridden { by_line: 4 }),
+            (4, "important.log", MatchOutcome::Decided),
+        ]);
+        assert_eq!(explanation.overridden_negations(), 1);
+    }
+
+    #[test]
+    fn test_explain_negation_below_ignored_directory() {
+        let file = parse_gitignore("build/\n!build/keep.txt\n*.txt").unwrap();
+        let explanation = explain_path(&file, "build/keep.txt", false);
+
+        assert!(explanation.is_ignored());
+        assert_eq!(outcomes(&explanation), vec![
+            (1, "build/", MatchOutcome::Decided),
+            (2, "build/keep.txt", MatchOutcome::ParentExcluded),
+            (3, "build/keep.txt", MatchOutcome::ParentExcluded),
+        ]);
+    }
+
+    #[test]
+    fn test_explain_reincluded_directory() {
+        let file = parse_gitignore("vendor/*\n!vendor/lib/\n*.o").unwrap();
+        let explanation = explain_path(&file, "vendor/lib/a.o", false);
+
+        assert!(explanation.is_ignored());
+        assert_eq!(outcomes(&explanation), vec![
+            (1, "vendor/lib/", MatchOutcome::Overridden { by_line: 2 }),
+            (2, "vendor/lib/", MatchOutcome::Superseded),
+            (3, "vendor/lib/a.o", MatchOutcome::Decided),
+        ]);
+        assert!(explain_path(&file, "src/main.rs", false).matches.is_empty());
+    }
+}
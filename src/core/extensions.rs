@@ -0,0 +1,138 @@
+use crate::models::GitignoreFile;
+
+/// Fewest extension patterns of one family worth describing under one comment
+pub const EXTENSION_GROUP_MIN: usize = 3;
+
+/// Families of related file extensions, by the name their comment gives them
+pub const EXTENSION_FAMILIES: &[(&str, &[&str])] = &[
+    ("Images", &["png", "jpg", "jpeg", "gif", "bmp", "tif", "tiff", "webp", "ico", "svg", "psd", "heic"]),
+    ("Archives", &["zip", "tar", "gz", "tgz", "bz2", "xz", "7z", "rar", "zst"]),
+    ("Audio", &["mp3", "wav", "flac", "ogg", "aac", "m4a"]),
+    ("Video", &["mp4", "mov", "avi", "mkv", "webm", "wmv"]),
+    ("Documents", &["pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "rtf"]),
+    ("Fonts", &["ttf", "otf", "woff", "woff2", "eot"]),
+    ("Binaries", &["o", "obj", "a", "lib", "so", "dll", "dylib", "exe"]),
+];
+
+/// Extension patterns of one family with the same directory part, which one comment could describe
+///
+/// Gitignore has no brace expansion, so `*.{png,jpg,gif}` can't replace them; the group is
+/// reported with the commented block that would keep them together instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionGroup {
+    /// Name of the family (`Images`, `Archives`, ...)
+    pub family: String,
+    /// Text before `*.`, such as `assets/`; empty for patterns matching anywhere
+    pub prefix: String,
+    /// Extensions in line order
+    pub extensions: Vec<String>,
+    /// Line numbers of the patterns (1-indexed)
+    pub line_numbers: Vec<usize>,
+    /// The patterns, without inline comments
+    pub patterns: Vec<String>,
+}
+
+impl ExtensionGroup {
+    /// The group in brace syntax, which other tools expand but gitignore matches literally
+    pub fn brace_pattern(&self) -> String {
+        format!("{}*.{{{}}}", self.prefix, self.extensions.join(","))
+    }
+
+    /// Comment describing the group, to go above its patterns
+    pub fn comment(&self) -> String {
+        format!("# {}: {}", self.family, self.extensions.join(", "))
+    }
+
+    /// The comment followed by the patterns, one per line
+    pub fn block(&self) -> Vec<String> {
+        std::iter::once(self.comment()).chain(self.patterns.iter().cloned()).collect()
+    }
+}
+
+/// Find extension patterns of one family spread over the file, or not yet under a comment of their own
+///
+/// Patterns are `*.ext` with an optional directory part in front; groups need at least
+/// `min_patterns` of them with the same directory part. A group already written as one run
+/// of lines right below a comment is left out.
+pub fn find_extension_groups(file: &GitignoreFile, min_patterns: usize) -> Vec<ExtensionGroup> {
+    let mut groups: Vec<ExtensionGroup> = Vec::new();
+    for entry in &file.entries {
+        let Some(pattern) = entry.comparison_pattern() else {
+            continue;
+        };
+        let Some((prefix, extension)) = split_extension(&pattern) else {
+            continue;
+        };
+        let Some((family, _)) = EXTENSION_FAMILIES.iter().find(|(_, extensions)| extensions.contains(&extension.to_ascii_lowercase().as_str())) else {
+            continue;
+        };
+
+        let group = match groups.iter_mut().position(|group| group.family == *family && group.prefix == prefix) {
+            Some(index) => &mut groups[index],
+            None => {
+                groups.push(ExtensionGroup {
+                    family: family.to_string(),
+                    prefix: prefix.to_string(),
+                    extensions: Vec::new(),
+                    line_numbers: Vec::new(),
+                    patterns: Vec::new(),
+                });
+                groups.last_mut().expect("just pushed")
+            }
+        };
+        if !group.extensions.iter().any(|known| known == extension) {
+            group.extensions.push(extension.to_string());
+        }
+        group.line_numbers.push(entry.line_number);
+        group.patterns.push(pattern);
+    }
+
+    groups.retain(|group| group.extensions.len() >= min_patterns && !already_grouped(file, group));
+    groups
+}
+
+/// Split `dir/*.ext` into `dir/` and `ext`, for a plain extension without wildcards
+fn split_extension(pattern: &str) -> Option<(&str, &str)> {
+    let star = pattern.rfind("*.")?;
+    let (prefix, extension) = (&pattern[..star], &pattern[star + 2..]);
+    let plain = !extension.is_empty() && extension.chars().all(|ch| ch.is_ascii_alphanumeric());
+    let whole_name = prefix.is_empty() || prefix.ends_with('/');
+    (plain && whole_name && !prefix.contains(['*', '?', '[', '!', '\\'])).then_some((prefix, extension))
+}
+
+/// Check if a group's patterns are consecutive lines following a comment
+fn already_grouped(file: &GitignoreFile, group: &ExtensionGroup) -> bool {
+    let (Some(&first), Some(&last)) = (group.line_numbers.first(), group.line_numbers.last()) else {
+        return false;
+    };
+    let index_of = |line_number: usize| file.entries.iter().position(|entry| entry.line_number == line_number);
+    let (Some(first), Some(last)) = (index_of(first), index_of(last)) else {
+        return false;
+    };
+    last - first + 1 == group.line_numbers.len() && first > 0 && file.entries[first - 1].is_comment()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_find_extension_groups() {
+        let file = parse_gitignore("*.png\n*.log\n*.jpg\nassets/*.zip\n*.gif\n*.PNG\nassets/*.tar\nassets/*.gz\n").unwrap();
+        let groups = find_extension_groups(&file, EXTENSION_GROUP_MIN);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].line_numbers, vec![1, 3, 5, 6]);
+        assert_eq!(groups[0].brace_pattern(), "*.{png,jpg,gif,PNG}");
+        assert_eq!(groups[0].block(), vec!["# Images: png, jpg, gif, PNG", "*.png", "*.jpg", "*.gif", "*.PNG"]);
+        assert_eq!(groups[1].brace_pattern(), "assets/*.{zip,tar,gz}");
+    }
+
+    #[test]
+    fn test_grouped_extensions_are_not_reported() {
+        let file = parse_gitignore("# Images\n*.png\n*.jpg\n*.gif\n\n*.mp3\n*.wav\n").unwrap();
+        assert!(find_extension_groups(&file, EXTENSION_GROUP_MIN).is_empty());
+        assert_eq!(find_extension_groups(&parse_gitignore("*.png\n*.jpg\n*.gif\n").unwrap(), EXTENSION_GROUP_MIN).len(), 1);
+    }
+}
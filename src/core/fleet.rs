@@ -0,0 +1,364 @@
+use crate::core::lint::lint_gitignore;
+use crate::core::parser::parse_gitignore;
+use crate::core::summary::{scan_repository, ScanOptions, IGNORE_FILE_NAMES};
+use crate::core::whitespace::{check_whitespace, WhitespacePolicy};
+use crate::models::GixError;
+use crate::utils::git::{clone_repository, resolve_git_dir};
+use crate::utils::json::json_string;
+use crate::utils::read_gitignore_file;
+use crate::utils::walker::find_files;
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Format of a fleet report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum FleetFormat {
+    /// A Markdown document with a table per repository and rule
+    #[default]
+    Markdown,
+    /// A JSON document for dashboards
+    Json,
+}
+
+/// Where a repository of the fleet is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FleetLocation {
+    /// A checkout on disk
+    Local(PathBuf),
+    /// A repository to clone, by URL
+    Remote(String),
+}
+
+/// A repository to assess
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FleetMember {
+    /// Name shown in the report: the line of the repository list, or the checkout's directory name
+    pub name: String,
+    pub location: FleetLocation,
+}
+
+/// How one repository fares against the policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FleetRepository {
+    pub name: String,
+    /// Number of ignore files assessed
+    pub files: usize,
+    /// Health of the ignore files from 0 to 100, as reported by `gix scan`
+    pub score: usize,
+    /// Findings per failing rule, by the rule's name in `gix check` and whitespace reports
+    pub failing_rules: BTreeMap<String, usize>,
+    /// Why the repository could not be assessed; it then has no files, score or findings
+    pub error: Option<String>,
+}
+
+impl FleetRepository {
+    /// A repository that could not be cloned, read or parsed
+    pub fn unavailable(name: &str, error: &GixError) -> Self {
+        Self {
+            name: name.to_string(),
+            files: 0,
+            score: 0,
+            failing_rules: BTreeMap::new(),
+            error: Some(error.to_string()),
+        }
+    }
+
+    /// Check if the repository was assessed and breaks no rule
+    pub fn is_compliant(&self) -> bool {
+        self.error.is_none() && self.failing_rules.is_empty()
+    }
+}
+
+/// Compliance of every repository of a fleet, in the order they were listed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FleetReport {
+    pub repositories: Vec<FleetRepository>,
+}
+
+impl FleetReport {
+    /// Number of repositories that could be assessed
+    pub fn assessed(&self) -> usize {
+        self.repositories.iter().filter(|repository| repository.error.is_none()).count()
+    }
+
+    /// Number of repositories breaking no rule
+    pub fn compliant(&self) -> usize {
+        self.repositories.iter().filter(|repository| repository.is_compliant()).count()
+    }
+
+    /// Average score of the assessed repositories, or 100 when there are none
+    pub fn average_score(&self) -> usize {
+        match self.assessed() {
+            0 => 100,
+            assessed => self.repositories.iter().filter(|repository| repository.error.is_none()).map(|repository| repository.score).sum::<usize>() / assessed,
+        }
+    }
+
+    /// For each failing rule, the number of findings and of repositories with findings
+    pub fn rule_totals(&self) -> BTreeMap<&str, (usize, usize)> {
+        let mut totals: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        for (rule, &count) in self.repositories.iter().flat_map(|repository| &repository.failing_rules) {
+            let total = totals.entry(rule.as_str()).or_default();
+            total.0 += count;
+            total.1 += 1;
+        }
+        totals
+    }
+
+    /// Render the report in the given format
+    pub fn render(&self, format: FleetFormat) -> String {
+        match format {
+            FleetFormat::Markdown => self.render_markdown(),
+            FleetFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Render the report as Markdown
+    pub fn render_markdown(&self) -> String {
+        let mut lines = vec![
+            "# gix fleet report".to_string(),
+            String::new(),
+            format!("Repositories: {} ({} assessed, {} compliant)", self.repositories.len(), self.assessed(), self.compliant()),
+            format!("Average score: {}/100", self.average_score()),
+            String::new(),
+            "## Repositories".to_string(),
+            String::new(),
+        ];
+
+        if self.repositories.is_empty() {
+            lines.push("No repositories listed.".to_string());
+        } else {
+            lines.push("| Repository | Files | Score | Failing rules |".to_string());
+            lines.push("|------------|------:|------:|---------------|".to_string());
+            for repository in &self.repositories {
+                let row = match &repository.error {
+                    Some(error) => format!("| `{}` | - | - | could not assess: {} |", repository.name, error.replace('|', "\\|").replace('\n', " ")),
+                    None if repository.failing_rules.is_empty() => format!("| `{}` | {} | {} | none |", repository.name, repository.files, repository.score),
+                    None => {
+                        let rules: Vec<String> = repository.failing_rules.iter().map(|(rule, count)| format!("{} ({})", rule, count)).collect();
+                        format!("| `{}` | {} | {} | {} |", repository.name, repository.files, repository.score, rules.join(", "))
+                    }
+                };
+                lines.push(row);
+            }
+        }
+
+        lines.push(String::new());
+        lines.push("## Failing rules".to_string());
+        lines.push(String::new());
+        let totals = self.rule_totals();
+        if totals.is_empty() {
+            lines.push("None.".to_string());
+        } else {
+            lines.push("| Rule | Findings | Repositories |".to_string());
+            lines.push("|------|---------:|-------------:|".to_string());
+            for (rule, (findings, repositories)) in totals {
+                lines.push(format!("| `{}` | {} | {} |", rule, findings, repositories));
+            }
+        }
+
+        let mut markdown = lines.join("\n");
+        markdown.push('\n');
+        markdown
+    }
+
+    /// Render the report as a JSON document
+    pub fn to_json(&self) -> String {
+        let object = |members: Vec<String>| format!("{{{}}}", members.join(", "));
+        let repositories: Vec<String> = self.repositories.iter()
+            .map(|repository| format!(
+                "    {{\"name\": {}, \"files\": {}, \"score\": {}, \"compliant\": {}, \"failing_rules\": {}, \"error\": {}}}",
+                json_string(&repository.name),
+                repository.files,
+                repository.score,
+                repository.is_compliant(),
+                object(repository.failing_rules.iter().map(|(rule, count)| format!("{}: {}", json_string(rule), count)).collect()),
+                repository.error.as_deref().map_or_else(|| "null".to_string(), json_string)
+            ))
+            .collect();
+        let totals = object(self.rule_totals().into_iter()
+            .map(|(rule, (findings, repositories))| format!("{}: {{\"findings\": {}, \"repositories\": {}}}", json_string(rule), findings, repositories))
+            .collect());
+
+        format!(
+            "{{\n  \"repositories\": {},\n  \"totals\": {{\"repositories\": {}, \"assessed\": {}, \"compliant\": {}, \"average_score\": {}, \"failing_rules\": {}}}\n}}\n",
+            if repositories.is_empty() { "[]".to_string() } else { format!("[\n{}\n  ]", repositories.join(",\n")) },
+            self.repositories.len(),
+            self.assessed(),
+            self.compliant(),
+            self.average_score(),
+            totals
+        )
+    }
+}
+
+/// Read a repository list: one checkout path or clone URL per line, `#` comments and blank lines skipped
+///
+/// Relative checkout paths are resolved against `base`, the directory holding the list.
+pub fn read_repository_list(text: &str, base: &Path) -> Vec<FleetMember> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| FleetMember {
+            name: line.to_string(),
+            location: if is_remote(line) { FleetLocation::Remote(line.to_string()) } else { FleetLocation::Local(base.join(line)) },
+        })
+        .collect()
+}
+
+/// The repositories checked out directly below `dir`, sorted by directory name
+pub fn checkouts_in(dir: &Path) -> Result<Vec<FleetMember>, GixError> {
+    let mut members = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && resolve_git_dir(&path).is_some() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            members.push(FleetMember { name, location: FleetLocation::Local(path) });
+        }
+    }
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(members)
+}
+
+/// Assess every member of the fleet against the policy
+///
+/// Remote repositories are cloned into `checkout_dir`, or read from there when an earlier run
+/// cloned them. A repository that can't be cloned or read is reported as such instead of
+/// stopping the report.
+pub fn assess_fleet(members: &[FleetMember], checkout_dir: &Path, policy: &WhitespacePolicy) -> FleetReport {
+    let repositories = members.iter()
+        .map(|member| {
+            checkout(&member.location, checkout_dir)
+                .and_then(|root| assess_repository(&member.name, &root, policy))
+                .unwrap_or_else(|error| FleetRepository::unavailable(&member.name, &error))
+        })
+        .collect();
+    FleetReport { repositories }
+}
+
+/// Run `gix check` and the whitespace policy over every ignore file of a checkout
+///
+/// Only files in the working tree count: `.git/info/exclude` is local to each clone, and a
+/// fresh clone's holds nothing but git's sample comments.
+pub fn assess_repository(name: &str, root: &Path, policy: &WhitespacePolicy) -> Result<FleetRepository, GixError> {
+    let walked = find_files(root, IGNORE_FILE_NAMES, &ScanOptions::default())?;
+    let mut summary = scan_repository(root, &ScanOptions::default())?;
+    summary.files.retain(|scanned| walked.files.iter().any(|walked_file| walked_file.path == scanned.path));
+
+    let mut failing_rules: BTreeMap<String, usize> = BTreeMap::new();
+    for walked_file in &walked.files {
+        let file = parse_gitignore(&read_gitignore_file(&walked_file.location)?)?;
+        let lint_rules = lint_gitignore(&file)?.into_iter().map(|issue| issue.kind.name());
+        let whitespace_rules = check_whitespace(&file, policy).into_iter().map(|violation| violation.rule.name());
+        for rule in lint_rules.chain(whitespace_rules) {
+            *failing_rules.entry(rule.to_string()).or_default() += 1;
+        }
+    }
+
+    Ok(FleetRepository {
+        name: name.to_string(),
+        files: summary.files.len(),
+        score: summary.health(),
+        failing_rules,
+        error: None,
+    })
+}
+
+/// Find the checkout of a repository, cloning it first if it is remote and not cloned yet
+fn checkout(location: &FleetLocation, checkout_dir: &Path) -> Result<PathBuf, GixError> {
+    match location {
+        FleetLocation::Local(path) if path.is_dir() => Ok(path.clone()),
+        FleetLocation::Local(path) => Err(GixError::FileNotFound(path.display().to_string())),
+        FleetLocation::Remote(url) => {
+            let dest = checkout_dir.join(checkout_name(url));
+            if resolve_git_dir(&dest).is_none() {
+                fs::create_dir_all(checkout_dir)?;
+                clone_repository(url, &dest)?;
+            }
+            Ok(dest)
+        }
+    }
+}
+
+fn is_remote(entry: &str) -> bool {
+    entry.contains("://") || entry.starts_with("git@")
+}
+
+/// Directory name for the clone of `url`: `github.com-org-app` for `https://github.com/org/app.git`
+fn checkout_name(url: &str) -> String {
+    let address = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let address = address.strip_prefix("git@").unwrap_or(address).trim_end_matches('/');
+    let address = address.strip_suffix(".git").unwrap_or(address);
+    let name: String = address.chars().map(|ch| if ch.is_ascii_alphanumeric() || ch == '.' || ch == '_' { ch } else { '-' }).collect();
+    name.trim_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkout_with(dir: &Path, name: &str, gitignore: &str) -> PathBuf {
+        let root = dir.join(name);
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join(".gitignore"), gitignore).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_read_repository_list() {
+        let members = read_repository_list("# platform\napi\n\n  https://github.com/org/web.git\ngit@github.com:org/cli.git\n", Path::new("/srv"));
+        assert_eq!(members.len(), 3);
+        assert_eq!(members[0].location, FleetLocation::Local(PathBuf::from("/srv/api")));
+        assert_eq!(members[1].location, FleetLocation::Remote("https://github.com/org/web.git".to_string()));
+        assert_eq!(members[2].name, "git@github.com:org/cli.git");
+        assert_eq!(checkout_name("https://github.com/org/web.git"), "github.com-org-web");
+        assert_eq!(checkout_name("git@github.com:org/cli.git"), "github.com-org-cli");
+        assert_eq!(checkout_name("file:///srv/git/api/"), "srv-git-api");
+    }
+
+    #[test]
+    fn test_assess_fleet() {
+        let dir = tempfile::tempdir().unwrap();
+        checkout_with(dir.path(), "api", "*.log\nbuild/\n");
+        checkout_with(dir.path(), "web", "*.log\n*.log\nnode_modules/ \n");
+        fs::create_dir_all(dir.path().join("notes")).unwrap();
+
+        let mut members = checkouts_in(dir.path()).unwrap();
+        assert_eq!(members.iter().map(|member| member.name.as_str()).collect::<Vec<_>>(), vec!["api", "web"]);
+        members.push(FleetMember { name: "gone".to_string(), location: FleetLocation::Local(dir.path().join("gone")) });
+
+        let report = assess_fleet(&members, &dir.path().join("clones"), &WhitespacePolicy::default());
+        assert!(report.repositories[0].is_compliant());
+        assert_eq!(report.repositories[0].score, 100);
+        assert_eq!(report.repositories[1].failing_rules.get("duplicate"), Some(&1));
+        assert_eq!(report.repositories[1].failing_rules.get("trailing-spaces"), Some(&1));
+        assert!(report.repositories[2].error.is_some());
+        assert_eq!((report.assessed(), report.compliant()), (2, 1));
+        assert_eq!(report.rule_totals().get("duplicate"), Some(&(1, 1)));
+    }
+
+    #[test]
+    fn test_render_fleet_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = checkout_with(dir.path(), "web", "*.log\n*.log\n");
+        fs::create_dir_all(root.join(".git/info")).unwrap();
+        fs::write(root.join(".git/info/exclude"), "# git ls-files --others --exclude-from=.git/info/exclude\n").unwrap();
+        let mut report = FleetReport { repositories: vec![assess_repository("web", &root, &WhitespacePolicy::default()).unwrap()] };
+        report.repositories.push(FleetRepository::unavailable("gone", &GixError::FileNotFound("gone".to_string())));
+
+        let markdown = report.render(FleetFormat::Markdown);
+        assert!(markdown.contains("Repositories: 2 (1 assessed, 0 compliant)"));
+        assert!(markdown.contains("| `web` | 1 | 95 | duplicate (1) |"));
+        assert!(markdown.contains("| `gone` | - | - | could not assess: File not found: gone |"));
+        assert!(markdown.contains("| `duplicate` | 1 | 1 |"));
+
+        let json = crate::utils::json::parse_json(&report.render(FleetFormat::Json)).unwrap();
+        let repositories = json.get("repositories").and_then(|value| value.as_array()).unwrap();
+        assert_eq!(repositories[0].get("score").and_then(|value| value.as_usize()), Some(95));
+        assert_eq!(repositories[1].get("error").and_then(|value| value.as_str()), Some("File not found: gone"));
+        assert_eq!(json.get("totals").and_then(|totals| totals.get("average_score")).and_then(|value| value.as_usize()), Some(95));
+        assert!(FleetReport::default().render_markdown().contains("No repositories listed."));
+    }
+}
@@ -0,0 +1,464 @@
+use crate::core::optimizer::PassStats;
+use crate::models::{GitignoreEntry, GitignoreFile};
+use crate::utils::patterns::{trim_unescaped_trailing_whitespace, GlobPattern};
+use crate::utils::tokens::{render, tokenize, ClassItem, Segment, Token};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap};
+
+/// Characters never folded into a class, since inside brackets they would need escaping
+const CLASS_SPECIAL: &[char] = &['[', ']', '\\', '-', '!', '^', '/'];
+
+/// Sibling patterns folded into one pattern with a character class
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldedPatterns {
+    /// Line numbers of the folded patterns (1-indexed); the folded pattern takes the first
+    pub line_numbers: Vec<usize>,
+    /// The folded patterns, in line order
+    pub patterns: Vec<String>,
+    /// The pattern replacing them
+    pub folded: String,
+    /// Paths of the working tree the folded pattern was checked against
+    pub verified_paths: usize,
+}
+
+impl FoldedPatterns {
+    /// Explain the fold for the change report
+    pub fn explain(&self) -> String {
+        let checked = match self.verified_paths {
+            0 => String::new(),
+            paths => format!("; same result for all {} path(s) in the working tree", paths),
+        };
+        format!(
+            "{} → {} (they differ in one character only, which the class lists{})",
+            self.patterns.join(", "), self.folded, checked
+        )
+    }
+}
+
+/// A pattern split into tokens, as a candidate for folding
+struct Candidate {
+    negated: bool,
+    segments: Vec<Segment>,
+    glob: GlobPattern,
+}
+
+impl Candidate {
+    fn of(entry: &GitignoreEntry) -> Option<Self> {
+        let pattern = entry.comparison_pattern().filter(|_| entry.inline_comment.is_none() && !entry.preserve)?;
+        let text = trim_unescaped_trailing_whitespace(&pattern);
+        let (negated, body) = match text.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let segments = tokenize(body);
+        Some(Self { negated, glob: GlobPattern::from_segments(negated, segments.clone()), segments })
+    }
+}
+
+/// Patterns of one run of pattern lines sharing everything but one foldable token
+struct Family {
+    /// Run of consecutive pattern lines the members are in, by its first entry
+    run: usize,
+    negated: bool,
+    /// Segment and position of the token the members differ in
+    varying: (usize, usize),
+    /// Entry indices of the members, in file order
+    members: Vec<usize>,
+    /// Set when the family couldn't fold, until a fold may have changed that
+    failed: bool,
+}
+
+impl Family {
+    fn queued(&self, id: usize) -> (usize, Reverse<usize>, Reverse<usize>) {
+        (self.members.len(), Reverse(self.members.first().copied().unwrap_or_default()), Reverse(id))
+    }
+}
+
+type FamilyKey = (usize, bool, usize, usize, Vec<Segment>);
+
+/// The families of a file's patterns, kept up to date as folds replace and remove entries
+///
+/// Families are built once; a fold only touches the families of the entries it changes, and
+/// retries failed families only where the fold may have unblocked them.
+struct Families {
+    /// Candidate at each entry index; `None` for other entries and those folded away
+    candidates: Vec<Option<Candidate>>,
+    /// Run of each entry
+    runs: Vec<usize>,
+    /// Entry indices of the negated and plain candidates, which may block a fold of the other kind
+    by_kind: [BTreeSet<usize>; 2],
+    families: Vec<Family>,
+    keys: HashMap<FamilyKey, usize>,
+    /// Families each entry belongs to
+    of_entry: Vec<Vec<usize>>,
+    /// Failed families by run, retried after a fold of the other kind in that run
+    failed: HashMap<usize, Vec<usize>>,
+    /// Families by size, largest first, then by first member and age; entries outdated by a
+    /// later change are skipped when popped
+    queue: BinaryHeap<(usize, Reverse<usize>, Reverse<usize>)>,
+}
+
+impl Families {
+    fn new(entries: &[GitignoreEntry]) -> Self {
+        let mut families = Self {
+            candidates: entries.iter().map(Candidate::of).collect(),
+            runs: Vec::with_capacity(entries.len()),
+            by_kind: [BTreeSet::new(), BTreeSet::new()],
+            families: Vec::new(),
+            keys: HashMap::new(),
+            of_entry: vec![Vec::new(); entries.len()],
+            failed: HashMap::new(),
+            queue: BinaryHeap::new(),
+        };
+        let mut run = 0;
+        for index in 0..entries.len() {
+            if families.candidates[index].is_none() {
+                run = index + 1;
+            }
+            families.runs.push(run);
+        }
+        for index in 0..entries.len() {
+            families.add(index);
+        }
+        families
+    }
+
+    /// Add the candidate at `index` to the families it belongs to
+    fn add(&mut self, index: usize) {
+        let Some(candidate) = &self.candidates[index] else {
+            return;
+        };
+        let negated = candidate.negated;
+        self.by_kind[negated as usize].insert(index);
+        let mut keys = Vec::new();
+        for (segment, tokens) in candidate.segments.iter().enumerate() {
+            for (position, token) in tokens.tokens.iter().enumerate() {
+                if class_items(token).is_none() {
+                    continue;
+                }
+                let mut rest = candidate.segments.clone();
+                rest[segment].tokens.remove(position);
+                keys.push((self.runs[index], negated, segment, position, rest));
+            }
+        }
+
+        for key in keys {
+            let (run, _, segment, position, _) = key;
+            let id = *self.keys.entry(key).or_insert_with(|| {
+                self.families.push(Family { run, negated, varying: (segment, position), members: Vec::new(), failed: false });
+                self.families.len() - 1
+            });
+            let family = &mut self.families[id];
+            let at = family.members.partition_point(|&member| member < index);
+            family.members.insert(at, index);
+            family.failed = false;
+            self.of_entry[index].push(id);
+            self.queue.push(family.queued(id));
+        }
+    }
+
+    /// Take the candidate at `index` out of its families
+    fn remove(&mut self, index: usize) {
+        if let Some(candidate) = self.candidates[index].take() {
+            self.by_kind[candidate.negated as usize].remove(&index);
+        }
+        for id in std::mem::take(&mut self.of_entry[index]) {
+            let family = &mut self.families[id];
+            family.members.retain(|&member| member != index);
+            family.failed = false;
+            self.queue.push(family.queued(id));
+        }
+    }
+
+    /// Take the largest family left to try
+    fn next(&mut self) -> Option<usize> {
+        while let Some(queued) = self.queue.pop() {
+            let (len, _, Reverse(id)) = queued;
+            let family = &self.families[id];
+            if len > 1 && queued == family.queued(id) && !family.failed {
+                return Some(id);
+            }
+        }
+        None
+    }
+
+    /// Set a family aside until a fold may let it fold after all
+    fn fail(&mut self, id: usize) {
+        let family = &mut self.families[id];
+        family.failed = true;
+        self.failed.entry(family.run).or_default().push(id);
+    }
+
+    /// Retry the failed families a fold of patterns of `negated` kind in `run` may have unblocked
+    fn retry(&mut self, run: usize, negated: bool) {
+        let Some(failed) = self.failed.remove(&run) else {
+            return;
+        };
+        let (retried, still_failed): (Vec<usize>, Vec<usize>) = failed.into_iter()
+            .filter(|&id| self.families[id].failed)
+            .partition(|&id| self.families[id].negated != negated);
+        for id in retried {
+            let family = &mut self.families[id];
+            family.failed = false;
+            self.queue.push(family.queued(id));
+        }
+        if !still_failed.is_empty() {
+            self.failed.insert(run, still_failed);
+        }
+    }
+
+    fn candidate(&self, index: usize) -> &Candidate {
+        self.candidates[index].as_ref().expect("family members are candidates")
+    }
+}
+
+/// Fold families of sibling patterns that differ in a single character into one pattern
+///
+/// `*.pyc`, `*.pyo` and `*.pyd` become `*.py[cod]`, which matches exactly the same paths. Only
+/// patterns of the same kind within one run of pattern lines fold, and a pattern only moves
+/// up to the first of its family when no pattern of the other kind in between can match what
+/// it matches. Each fold is also checked with the matcher against `paths` (relative to the
+/// directory holding the file, directories with a trailing `/`), and skipped if any of them
+/// would be decided differently. New folds are appended to `folded`; one absorbing an earlier
+/// fold replaces it.
+pub fn fold_character_classes(file: &GitignoreFile, paths: &[String], folded: &mut Vec<FoldedPatterns>, stats: &mut PassStats) -> GitignoreFile {
+    let paths: Vec<(&str, bool)> = paths.iter()
+        .map(|path| match path.strip_suffix('/') {
+            Some(dir) => (dir, true),
+            None => (path.as_str(), false),
+        })
+        .collect();
+
+    // Folds by the line of their first pattern, so one absorbing an earlier fold finds it directly
+    let mut folds: Vec<Option<FoldedPatterns>> = std::mem::take(folded).into_iter().map(Some).collect();
+    let mut fold_at: HashMap<usize, usize> = folds.iter().flatten().enumerate()
+        .map(|(position, fold)| (fold.line_numbers[0], position))
+        .collect();
+
+    let mut entries: Vec<Option<GitignoreEntry>> = file.entries.iter().cloned().map(Some).collect();
+    let mut families = Families::new(&file.entries);
+    while let Some(id) = families.next() {
+        let Some((members, pattern)) = fold_family(&families, id, &paths) else {
+            families.fail(id);
+            continue;
+        };
+        let mut fold = FoldedPatterns {
+            line_numbers: Vec::new(),
+            patterns: Vec::new(),
+            folded: pattern.clone(),
+            verified_paths: paths.len(),
+        };
+        for &index in &members {
+            let entry = entries[index].as_ref().expect("family members are entries");
+            match fold_at.remove(&entry.line_number).and_then(|position| folds[position].take()) {
+                Some(earlier) => {
+                    fold.line_numbers.extend(earlier.line_numbers);
+                    fold.patterns.extend(earlier.patterns);
+                }
+                None => {
+                    fold.line_numbers.push(entry.line_number);
+                    fold.patterns.push(entry.original.clone());
+                }
+            }
+        }
+
+        for &index in &members {
+            families.remove(index);
+        }
+        for &index in &members[1..] {
+            entries[index] = None;
+        }
+        let first = members[0];
+        let line_number = file.entries[first].line_number;
+        let mut entry = file.entries[first].clone();
+        entry.rewrite_pattern(pattern.trim_start());
+        families.candidates[first] = Candidate::of(&entry);
+        entries[first] = Some(entry);
+        families.add(first);
+        let (run, negated) = (families.families[id].run, families.families[id].negated);
+        families.retry(run, negated);
+
+        stats.folded_patterns += members.len() - 1;
+        fold_at.insert(line_number, folds.len());
+        folds.push(Some(fold));
+    }
+    folded.extend(folds.into_iter().flatten());
+
+    let mut result = GitignoreFile::new();
+    result.keep_format_of(file);
+    for entry in entries.into_iter().flatten() {
+        result.add_entry(entry);
+    }
+    result
+}
+
+/// Fold the members of a family that may move up to its first member, if at least two remain,
+/// giving the entry indices folded and the folded pattern
+fn fold_family(families: &Families, id: usize, paths: &[(&str, bool)]) -> Option<(Vec<usize>, String)> {
+    let family = &families.families[id];
+    let members = &family.members;
+    let first = members[0];
+    let last = *members.last()?;
+
+    // A member jumping over a pattern of the other kind that can match what it matches would
+    // change which of the two wins
+    let others: Vec<(usize, &GlobPattern)> = families.by_kind[!family.negated as usize]
+        .range(first + 1..last)
+        .map(|&index| (index, &families.candidate(index).glob))
+        .collect();
+    let movable: Vec<usize> = std::iter::once(first)
+        .chain(members[1..].iter().copied().filter(|&member| {
+            let glob = &families.candidate(member).glob;
+            others.iter().take_while(|(index, _)| *index < member).all(|(_, other)| {
+                !(other.may_overlap(glob) || other.covers_parent_of(glob) || glob.covers_parent_of(other))
+            })
+        }))
+        .collect();
+    if movable.len() < 2 {
+        return None;
+    }
+
+    let (segment, position) = family.varying;
+    let mut items: Vec<ClassItem> = Vec::new();
+    for &member in &movable {
+        for item in class_items(&families.candidate(member).segments[segment].tokens[position])? {
+            if !items.contains(&item) {
+                items.push(item);
+            }
+        }
+    }
+    if items.len() < 2 {
+        return None;
+    }
+
+    let mut segments = families.candidate(first).segments.clone();
+    segments[segment].tokens[position] = class_token(&items);
+    let folded = GlobPattern::from_segments(family.negated, segments.clone());
+
+    // The class matches exactly what the members match; confirm it on the tree at hand
+    let same_everywhere = paths.iter().all(|(path, is_dir)| {
+        let before = movable.iter().any(|&member| families.candidate(member).glob.matches_path(path, *is_dir));
+        before == folded.matches_path(path, *is_dir)
+    });
+    if !same_everywhere {
+        return None;
+    }
+
+    let prefix = if family.negated { "!" } else { "" };
+    let pattern = format!("{}{}", prefix, render(&segments));
+    Some((movable, pattern))
+}
+
+/// Get the characters a token stands for when it can join a class: a literal or a plain class
+fn class_items(token: &Token) -> Option<Vec<ClassItem>> {
+    let plain = |ch: &char| !CLASS_SPECIAL.contains(ch);
+    match token {
+        Token::Literal(ch) | Token::Escaped(ch) if plain(ch) => Some(vec![ClassItem::Char(*ch)]),
+        Token::Class(class) if !class.negated => class.items.iter()
+            .all(|item| match item {
+                ClassItem::Char(ch) => plain(ch),
+                ClassItem::Range(low, high) => plain(low) && plain(high),
+                ClassItem::Posix(_) => false,
+            })
+            .then(|| class.items.clone()),
+        _ => None,
+    }
+}
+
+/// Build a class token listing `items`, writing runs of three or more consecutive characters as a range
+fn class_token(items: &[ClassItem]) -> Token {
+    let mut source = String::from("[");
+    let mut i = 0;
+    while i < items.len() {
+        match &items[i] {
+            ClassItem::Char(low) => {
+                let mut end = i;
+                while let (ClassItem::Char(current), Some(ClassItem::Char(next))) = (&items[end], items.get(end + 1)) {
+                    if *current as u32 + 1 != *next as u32 {
+                        break;
+                    }
+                    end += 1;
+                }
+                match (end - i, &items[end]) {
+                    (2.., ClassItem::Char(high)) => source.push_str(&format!("{}-{}", low, high)),
+                    _ => (i..=end).for_each(|j| if let ClassItem::Char(ch) = items[j] { source.push(ch) }),
+                }
+                i = end + 1;
+            }
+            ClassItem::Range(low, high) => {
+                source.push_str(&format!("{}-{}", low, high));
+                i += 1;
+            }
+            ClassItem::Posix(name) => {
+                source.push_str(&format!("[:{}:]", name));
+                i += 1;
+            }
+        }
+    }
+    source.push(']');
+
+    tokenize(&source).remove(0).tokens.remove(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    fn fold(content: &str, paths: &[&str]) -> (String, Vec<FoldedPatterns>, PassStats) {
+        let file = parse_gitignore(content).unwrap();
+        let paths: Vec<String> = paths.iter().map(|path| path.to_string()).collect();
+        let mut folded = Vec::new();
+        let mut stats = PassStats::default();
+        let result = fold_character_classes(&file, &paths, &mut folded, &mut stats);
+        (format!("{}", result), folded, stats)
+    }
+
+    #[test]
+    fn test_fold_sibling_patterns() {
+        let (content, folded, stats) = fold("# Python\n*.pyc\n*.pyo\n*.pyd\n\nlog1/\nlog2/\nlog3/", &["a.pyc", "a.py", "log2/"]);
+
+        assert_eq!(content, "# Python\n*.py[cod]\n\nlog[1-3]/");
+        assert_eq!(stats.folded_patterns, 4);
+        assert_eq!(folded[0], FoldedPatterns {
+            line_numbers: vec![2, 3, 4],
+            patterns: vec!["*.pyc".to_string(), "*.pyo".to_string(), "*.pyd".to_string()],
+            folded: "*.py[cod]".to_string(),
+            verified_paths: 3,
+        });
+        assert!(folded[0].explain().starts_with("*.pyc, *.pyo, *.pyd → *.py[cod]"));
+    }
+
+    #[test]
+    fn test_fold_keeps_indentation() {
+        let (content, _, _) = fold("  *.pyc\n  *.pyo\nlog/", &[]);
+
+        assert_eq!(content, "  *.py[co]\nlog/");
+    }
+
+    #[test]
+    fn test_fold_extends_classes_and_nests() {
+        let (content, folded, _) = fold("a1\na2\nb1\nb2\n*.py[co]\n*.pyd", &[]);
+
+        assert_eq!(content, "[ab][12]\n*.py[cod]");
+        assert_eq!(folded.len(), 2);
+        assert_eq!(folded[0].line_numbers, vec![1, 3, 2, 4]);
+    }
+
+    #[test]
+    fn test_fold_keeps_order_against_negations() {
+        // Moving `b.log` above `!b.log` would re-include it
+        let (content, _, _) = fold("a.log\n!b.log\nb.log\nc.log", &[]);
+        assert_eq!(content, "[ac].log\n!b.log\nb.log");
+
+        let (content, _, _) = fold("*.pyc\n!keep.pyc\n*.pyo\n!*.txt\n*.tx[!t]", &[]);
+        assert_eq!(content, "*.py[co]\n!keep.pyc\n!*.txt\n*.tx[!t]");
+    }
+
+    #[test]
+    fn test_fold_leaves_unrelated_patterns() {
+        let (content, folded, _) = fold("*.py\n*.pyc\nfoo/a\nbar/b\n*.a-\n*.a]\n!x1\nx2", &[]);
+        assert_eq!(content, "*.py\n*.pyc\nfoo/a\nbar/b\n*.a-\n*.a]\n!x1\nx2");
+        assert!(folded.is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::utils::patterns::GlobPattern;
+use std::fmt;
+
+/// What a histogram bucket groups patterns by
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BucketKey {
+    /// Patterns for paths below a directory, by its name (`docs` for `docs/api/index.md`)
+    Directory(String),
+    /// Patterns for files with an extension (`log` for `*.log` or `debug.log`)
+    Extension(String),
+}
+
+impl BucketKey {
+    /// The pattern that would cover every path of the bucket: `docs/` or `*.log`
+    pub fn collapsed_pattern(&self) -> String {
+        match self {
+            BucketKey::Directory(dir) => format!("{}/", dir),
+            BucketKey::Extension(extension) => format!("*.{}", extension),
+        }
+    }
+
+    /// Name of the kind of bucket, as used in JSON reports
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BucketKey::Directory(_) => "directory",
+            BucketKey::Extension(_) => "extension",
+        }
+    }
+}
+
+impl fmt::Display for BucketKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.collapsed_pattern())
+    }
+}
+
+/// The patterns sharing a first path segment or an extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistogramBucket {
+    pub key: BucketKey,
+    /// The patterns, in file order
+    pub patterns: Vec<String>,
+}
+
+/// Patterns bucketed by first path segment or extension, largest bucket first
+///
+/// A large bucket is an area of the file that lists many paths one by one, which a single
+/// directory or extension pattern might replace.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatternHistogram {
+    pub buckets: Vec<HistogramBucket>,
+}
+
+impl PatternHistogram {
+    /// Buckets holding at least `min_patterns` patterns
+    pub fn at_least(&self, min_patterns: usize) -> impl Iterator<Item = &HistogramBucket> {
+        self.buckets.iter().filter(move |bucket| bucket.patterns.len() >= min_patterns)
+    }
+}
+
+/// Bucket patterns by the directory they reach below, or else by the extension they match
+///
+/// `docs/a.md` and `/docs/**/*.pdf` go under `docs/`, `*.log` and `logs.old/debug.log` under
+/// `*.log` only when no directory applies. Negations, patterns starting with a wildcard segment
+/// and patterns naming a directory itself (`build/`) are left out, as are wildcard extensions.
+pub fn pattern_histogram<'a>(patterns: impl IntoIterator<Item = &'a str>) -> PatternHistogram {
+    let mut buckets: Vec<HistogramBucket> = Vec::new();
+    for pattern in patterns {
+        let Some(key) = bucket_key(pattern) else {
+            continue;
+        };
+        match buckets.iter_mut().find(|bucket| bucket.key == key) {
+            Some(bucket) => bucket.patterns.push(pattern.to_string()),
+            None => buckets.push(HistogramBucket { key, patterns: vec![pattern.to_string()] }),
+        }
+    }
+    buckets.sort_by(|a, b| b.patterns.len().cmp(&a.patterns.len()).then_with(|| a.key.cmp(&b.key)));
+    PatternHistogram { buckets }
+}
+
+fn bucket_key(pattern: &str) -> Option<BucketKey> {
+    if GlobPattern::parse(pattern).negated {
+        return None;
+    }
+    let path = pattern.trim_start_matches('/').trim_end_matches('/');
+    let has_wildcard = |text: &str| text.contains(['*', '?', '[', '\\']);
+
+    if let Some((first, rest)) = path.split_once('/') {
+        if !first.is_empty() && !rest.is_empty() && !has_wildcard(first) {
+            return Some(BucketKey::Directory(first.to_string()));
+        }
+    }
+    let name = path.rsplit('/').next()?;
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() && !extension.is_empty() && !has_wildcard(extension) => {
+            Some(BucketKey::Extension(extension.to_string()))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_histogram() {
+        let patterns = ["docs/a.md", "/docs/b/", "docs/**/*.pdf", "*.log", "old/debug.log", "error.log", "build/", "!docs/keep.md", "*.py[co]", "**/cache/x"];
+        let histogram = pattern_histogram(patterns);
+
+        assert_eq!(histogram.buckets.len(), 3);
+        assert_eq!(histogram.buckets[0].key, BucketKey::Directory("docs".to_string()));
+        assert_eq!(histogram.buckets[0].patterns, vec!["docs/a.md", "/docs/b/", "docs/**/*.pdf"]);
+        assert_eq!(histogram.buckets[1].key.collapsed_pattern(), "*.log");
+        assert_eq!(histogram.buckets[1].patterns, vec!["*.log", "error.log"]);
+        assert_eq!(histogram.buckets[2].key, BucketKey::Directory("old".to_string()));
+        assert_eq!(histogram.at_least(2).count(), 2);
+    }
+}
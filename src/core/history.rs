@@ -0,0 +1,270 @@
+use crate::core::audit_log::format_timestamp;
+use crate::core::changes::OptimizationReport;
+use crate::models::GixError;
+use crate::utils::git::{metadata_dir, repository_root, MetadataLocation};
+use crate::utils::json::{json_string, parse_json, JsonValue};
+use crate::utils::{path_relative_to, read_gitignore_file, stable_hash, write_gitignore_file};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the history directory in the metadata directory (see `utils::git::metadata_dir`)
+pub const HISTORY_DIR_NAME: &str = "history";
+
+/// Version of the step format written to the history directory
+pub const HISTORY_VERSION: usize = 1;
+
+/// One change of a recorded step, as `OptimizationReport` described it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryChange {
+    /// Name of the action (`removed-duplicate`, ...)
+    pub action: String,
+    /// First original line the action concerns
+    pub line: usize,
+    /// The action described for people
+    pub description: String,
+}
+
+/// One write gix can undo
+///
+/// Unlike the audit log, a step keeps the contents the write replaced, so they can be restored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryStep {
+    /// Position in the history, counting from 1; later writes have higher numbers
+    pub number: usize,
+    /// When the file was written, in UTC (RFC 3339)
+    pub timestamp: String,
+    /// The written file, relative to the repository root when inside it
+    pub file: String,
+    /// The gix command that wrote it (`optimize`, `organize`, `merge`, ...)
+    pub command: String,
+    /// Hash of the contents before the write, `None` if the file was created
+    pub before: Option<String>,
+    /// Hash of the contents after the write
+    pub after: String,
+    /// The contents before the write, `None` if the file was created
+    pub original: Option<String>,
+    /// What the write changed, in line order; empty for commands that don't describe their changes
+    pub changes: Vec<HistoryChange>,
+}
+
+impl HistoryStep {
+    /// Render the step as a JSON document
+    pub fn to_json(&self) -> String {
+        let optional = |value: &Option<String>| value.as_deref().map_or_else(|| "null".to_string(), json_string);
+        let changes: Vec<String> = self.changes.iter()
+            .map(|change| format!(
+                "    {{\"action\": {}, \"line\": {}, \"description\": {}}}",
+                json_string(&change.action), change.line, json_string(&change.description)
+            ))
+            .collect();
+        format!(
+            "{{\n  \"version\": {},\n  \"timestamp\": {},\n  \"file\": {},\n  \"command\": {},\n  \"before\": {},\n  \"after\": {},\n  \"original\": {},\n  \"changes\": [{}]\n}}\n",
+            HISTORY_VERSION,
+            json_string(&self.timestamp),
+            json_string(&self.file),
+            json_string(&self.command),
+            optional(&self.before),
+            json_string(&self.after),
+            optional(&self.original),
+            if changes.is_empty() { String::new() } else { format!("\n{}\n  ", changes.join(",\n")) }
+        )
+    }
+
+    /// Parse a step written by `to_json` as step `number`
+    pub fn parse(number: usize, text: &str) -> Result<Self, String> {
+        let document = parse_json(text)?;
+        match document.get("version").and_then(JsonValue::as_usize) {
+            Some(HISTORY_VERSION) => {}
+            Some(version) => return Err(format!("unsupported version {} (expected {})", version, HISTORY_VERSION)),
+            None => return Err("missing version".to_string()),
+        }
+
+        let string = |value: &JsonValue, key: &str| -> Result<String, String> {
+            value.get(key).and_then(JsonValue::as_str).map(str::to_string).ok_or_else(|| format!("missing string '{}'", key))
+        };
+        let optional = |key: &str| -> Result<Option<String>, String> {
+            match document.get(key) {
+                Some(JsonValue::Null) => Ok(None),
+                _ => string(&document, key).map(Some),
+            }
+        };
+        let mut changes = Vec::new();
+        for change in document.get("changes").and_then(JsonValue::as_array).ok_or("missing array 'changes'")? {
+            changes.push(HistoryChange {
+                action: string(change, "action")?,
+                line: change.get("line").and_then(JsonValue::as_usize).ok_or("missing line number 'line'")?,
+                description: string(change, "description")?,
+            });
+        }
+        Ok(Self {
+            number,
+            timestamp: string(&document, "timestamp")?,
+            file: string(&document, "file")?,
+            command: string(&document, "command")?,
+            before: optional("before")?,
+            after: string(&document, "after")?,
+            original: optional("original")?,
+            changes,
+        })
+    }
+}
+
+/// Path of the history directory of the repository containing `dir`, or of `dir` itself outside a repository
+pub fn history_dir(dir: &Path, location: MetadataLocation) -> PathBuf {
+    metadata_dir(dir, location).join(HISTORY_DIR_NAME)
+}
+
+/// Record a write of `after` over `before` (`None` for a new file) to `path` as the next step of its history
+///
+/// Call it once the file is written. Returns the path of the recorded step.
+pub fn record_step(
+    path: &Path,
+    location: MetadataLocation,
+    command: &str,
+    before: Option<&str>,
+    after: &str,
+    report: &OptimizationReport,
+) -> Result<PathBuf, GixError> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let history = history_dir(dir, location);
+    let root = repository_root(dir).unwrap_or_else(|| dir.to_path_buf());
+    let file = path.canonicalize().ok()
+        .and_then(|path| path_relative_to(&root, &path))
+        .unwrap_or_else(|| path.display().to_string());
+
+    let step = HistoryStep {
+        number: read_history(&history)?.last().map_or(0, |step| step.number) + 1,
+        timestamp: format_timestamp(SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())),
+        file,
+        command: command.to_string(),
+        before: before.map(stable_hash),
+        after: stable_hash(after),
+        original: before.map(str::to_string),
+        changes: report.actions.iter()
+            .map(|action| HistoryChange { action: action.name().to_string(), line: action.line(), description: action.describe() })
+            .collect(),
+    };
+    fs::create_dir_all(&history)?;
+    let step_path = step_path(&history, step.number);
+    fs::write(&step_path, step.to_json())?;
+    Ok(step_path)
+}
+
+/// Read the steps of a history directory, oldest first; a missing directory has none
+pub fn read_history(history: &Path) -> Result<Vec<HistoryStep>, GixError> {
+    if !history.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut steps = Vec::new();
+    for entry in fs::read_dir(history)? {
+        let path = entry?.path();
+        let number = path.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".json"))
+            .and_then(|number| number.parse().ok());
+        let Some(number) = number else {
+            continue;
+        };
+        let step = HistoryStep::parse(number, &read_gitignore_file(&path)?)
+            .map_err(|message| GixError::ParseError(format!("{}: {}", path.display(), message)))?;
+        steps.push(step);
+    }
+    steps.sort_by_key(|step| step.number);
+    Ok(steps)
+}
+
+/// Undo the last `steps` recorded writes in the repository containing `dir`, newest first
+///
+/// Each file gets back the contents it had before the write, or is deleted if the write
+/// created it, and the step leaves the history. Undoing stops with `ChangedSinceWrite`
+/// at a file edited since gix wrote it, keeping the steps undone so far undone.
+pub fn undo_steps(dir: &Path, location: MetadataLocation, steps: usize) -> Result<Vec<HistoryStep>, GixError> {
+    let history = history_dir(dir, location);
+    let root = repository_root(dir).unwrap_or_else(|| dir.to_path_buf());
+    let mut undone = Vec::new();
+
+    for step in read_history(&history)?.into_iter().rev().take(steps) {
+        let path = root.join(&step.file);
+        let current = fs::read_to_string(&path).ok();
+        if current.as_deref().map(stable_hash).as_ref() != Some(&step.after) {
+            return Err(GixError::ChangedSinceWrite(step.file));
+        }
+        match &step.original {
+            Some(original) => write_gitignore_file(&path, original)?,
+            None => fs::remove_file(&path)?,
+        }
+        fs::remove_file(step_path(&history, step.number))?;
+        undone.push(step);
+    }
+    Ok(undone)
+}
+
+fn step_path(history: &Path, number: usize) -> PathBuf {
+    history.join(format!("{:04}.json", number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::changes::OptimizationAction;
+
+    #[test]
+    fn test_step_round_trip() {
+        let step = HistoryStep {
+            number: 3,
+            timestamp: "2026-09-21T14:13:20Z".to_string(),
+            file: "src/.gitignore".to_string(),
+            command: "optimize".to_string(),
+            before: Some(stable_hash("*.log\n*.log\n")),
+            after: stable_hash("*.log\n"),
+            original: Some("*.log\n*.log\n".to_string()),
+            changes: vec![HistoryChange {
+                action: "removed-duplicate".to_string(),
+                line: 2,
+                description: "line 2: *.log (duplicate of line 1)".to_string(),
+            }],
+        };
+        assert_eq!(HistoryStep::parse(3, &step.to_json()).unwrap(), step);
+
+        let created = HistoryStep { before: None, original: None, changes: Vec::new(), ..step };
+        assert_eq!(HistoryStep::parse(3, &created.to_json()).unwrap(), created);
+        assert!(HistoryStep::parse(1, "{\"version\": 2}").unwrap_err().contains("unsupported version 2"));
+    }
+
+    #[test]
+    fn test_record_and_undo() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        let path = dir.path().join(".gitignore");
+        let report = OptimizationReport {
+            actions: vec![OptimizationAction::RemovedDuplicate { line: 2, pattern: "*.log".to_string(), kept_line: 1 }],
+        };
+
+        fs::write(&path, "*.log\n").unwrap();
+        let recorded = record_step(&path, MetadataLocation::Worktree, "optimize", Some("*.log\n*.log\n"), "*.log\n", &report).unwrap();
+        assert_eq!(recorded, dir.path().canonicalize().unwrap().join(".gix/history/0001.json"));
+        fs::write(&path, "*.log\nbuild/\n").unwrap();
+        record_step(&path, MetadataLocation::Worktree, "add-template", Some("*.log\n"), "*.log\nbuild/\n", &OptimizationReport::default()).unwrap();
+
+        let history = read_history(&history_dir(dir.path(), MetadataLocation::Worktree)).unwrap();
+        assert_eq!(history.iter().map(|step| step.number).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(history[0].file, ".gitignore");
+        assert_eq!(history[0].changes[0].description, "line 2: *.log (duplicate of line 1)");
+
+        let undone = undo_steps(dir.path(), MetadataLocation::Worktree, 1).unwrap();
+        assert_eq!(undone[0].command, "add-template");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "*.log\n");
+
+        fs::write(&path, "*.tmp\n").unwrap();
+        assert!(matches!(undo_steps(dir.path(), MetadataLocation::Worktree, 1), Err(GixError::ChangedSinceWrite(file)) if file == ".gitignore"));
+        fs::write(&path, "*.log\n").unwrap();
+        assert_eq!(undo_steps(dir.path(), MetadataLocation::Worktree, 5).unwrap().len(), 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "*.log\n*.log\n");
+        assert!(read_history(&history_dir(dir.path(), MetadataLocation::Worktree)).unwrap().is_empty());
+    }
+}
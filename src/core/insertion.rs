@@ -0,0 +1,174 @@
+use crate::core::categorizer::{PatternCategorizer, PatternCategory};
+use crate::core::comment_generator::CommentGenerator;
+use crate::core::optimizer::optimize_gitignore_redundant;
+use crate::core::organizer::{is_exceptions_section, section_category, EXCEPTIONS_HEADER};
+use crate::core::sorting::order_matters;
+use crate::core::validator::validate_pattern;
+use crate::models::{is_keep_marker, EntryType, GitignoreEntry, GitignoreFile, GixError};
+use crate::utils::patterns::GlobPattern;
+
+/// Where a new pattern goes in a file, and what the file already says about it
+#[derive(Debug, Clone)]
+pub struct Insertion {
+    /// The pattern to add
+    pub pattern: String,
+    /// Category the pattern falls into
+    pub category: PatternCategory,
+    /// Name of the section the pattern goes into; `None` when it goes at the end of the file
+    pub section: Option<String>,
+    /// The section did not exist and is appended to the file
+    pub new_section: bool,
+    /// Line number of the pattern in the file once added, or of the line it repeats
+    pub line_number: usize,
+    /// Line number and text of an existing pattern the new one repeats; nothing is added then
+    pub duplicate_of: Option<(usize, String)>,
+    /// Line number and text of an existing pattern that already matches everything the new one does
+    pub covered_by: Option<(usize, String)>,
+    /// The file with the pattern added
+    pub file: GitignoreFile,
+}
+
+/// Add a pattern to the section of the file its category belongs in
+///
+/// The section is `section` when given, and otherwise the first one named after the pattern's
+/// category or holding mostly patterns of that category; negations go to the exceptions
+/// section. A missing section is appended. When a later pattern could decide the same paths
+/// the other way, or the section lies in a `# gix:off` region, the pattern goes at the end of
+/// the file instead, where it has the last word as an appended pattern would. Line numbers in
+/// `duplicate_of` and `covered_by` are those of `file`.
+pub fn insert_pattern(
+    file: &GitignoreFile,
+    pattern: &str,
+    categorizer: &PatternCategorizer,
+    generator: &CommentGenerator,
+    section: Option<&str>,
+) -> Result<Insertion, GixError> {
+    validate_pattern(pattern)?;
+    let compared = GitignoreEntry::new(pattern.to_string(), EntryType::Pattern(pattern.to_string()), 0)
+        .comparison_pattern()
+        .unwrap_or_else(|| pattern.to_string());
+    let category = categorizer.section_category(compared.trim_start_matches('!'));
+    let mut insertion = Insertion {
+        pattern: pattern.to_string(),
+        category,
+        section: None,
+        new_section: false,
+        line_number: 0,
+        duplicate_of: None,
+        covered_by: None,
+        file: file.clone(),
+    };
+
+    if let Some(existing) = file.entries.iter().find(|entry| entry.comparison_pattern().as_deref() == Some(compared.as_str())) {
+        insertion.line_number = existing.line_number;
+        insertion.duplicate_of = Some((existing.line_number, existing.original.clone()));
+        return Ok(insertion);
+    }
+
+    let name = match section {
+        Some(name) => name.to_string(),
+        None => section_for(file, &compared, &insertion.category, categorizer, generator),
+    };
+    let mut added = file.clone();
+    let line_number = added.add_pattern(pattern, Some(&name));
+    let glob = GlobPattern::parse(&compared);
+    let decided_later = added.entries[line_number..].iter()
+        .filter_map(GitignoreEntry::comparison_pattern)
+        .any(|later| order_matters(&glob, &GlobPattern::parse(&later)));
+    if decided_later || in_preserved_region(&added, line_number) {
+        insertion.file = file.clone();
+        insertion.line_number = insertion.file.add_pattern(pattern, None);
+        if in_preserved_region(&insertion.file, insertion.line_number) {
+            return Err(GixError::UnsafeRewrite("the file ends inside a region marked `# gix:off`".to_string()));
+        }
+    } else {
+        insertion.new_section = !file.sections().iter().any(|section| section.name() == Some(name.as_str()));
+        insertion.section = Some(name);
+        insertion.line_number = line_number;
+        insertion.file = added;
+    }
+
+    let (_, redundant) = optimize_gitignore_redundant(&insertion.file)?;
+    insertion.covered_by = redundant.into_iter()
+        .find(|removed| removed.line_number == insertion.line_number)
+        .map(|removed| {
+            let line = removed.covered_by_line - usize::from(removed.covered_by_line > insertion.line_number);
+            (line, removed.covered_by)
+        });
+    Ok(insertion)
+}
+
+/// Name of the section a pattern of `category` belongs in, existing or to be created
+fn section_for(file: &GitignoreFile, pattern: &str, category: &PatternCategory, categorizer: &PatternCategorizer, generator: &CommentGenerator) -> String {
+    let header = generator.generate_section_header(category);
+    let header_name = header.trim_start_matches('#').trim();
+    let sections = file.sections();
+    let named: Vec<(&str, _)> = sections.iter()
+        .filter(|section| !section.header.iter().any(|entry| is_keep_marker(&entry.original)))
+        .filter_map(|section| Some((section.name()?, section)))
+        .collect();
+
+    let found = if pattern.starts_with('!') {
+        let exceptions = EXCEPTIONS_HEADER.trim_start_matches('#').trim();
+        named.iter().find(|(name, section)| name.eq_ignore_ascii_case(exceptions) || is_exceptions_section(section))
+    } else {
+        named.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(header_name) || name.eq_ignore_ascii_case(&category.display_name()))
+            .or_else(|| named.iter().find(|(_, section)| section_category(section, categorizer).as_ref() == Some(category)))
+    };
+    match (found, pattern.starts_with('!')) {
+        (Some((name, _)), _) => name.to_string(),
+        (None, true) => EXCEPTIONS_HEADER.trim_start_matches('#').trim().to_string(),
+        (None, false) => header_name.to_string(),
+    }
+}
+
+/// Check if the line at `line_number` sits between two lines of a `# gix:off` region
+///
+/// At the end of the file, a preserved line before it is enough, since the region may be open.
+fn in_preserved_region(file: &GitignoreFile, line_number: usize) -> bool {
+    let before = line_number.checked_sub(2).and_then(|index| file.entries.get(index));
+    before.is_some_and(|entry| entry.preserve) && file.entries.get(line_number).is_none_or(|entry| entry.preserve)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parse_gitignore;
+
+    fn insert(content: &str, pattern: &str) -> Insertion {
+        let file = parse_gitignore(content).unwrap();
+        insert_pattern(&file, pattern, &PatternCategorizer::new(), &CommentGenerator::new(), None).unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_insert_into_category_section() {
+        let insertion = insert("# Node.js\nnode_modules/\n\n# Editors\n.vscode/\n", "*.tgz");
+        assert_eq!(insertion.section.as_deref(), Some("Node.js"));
+        assert_eq!(insertion.line_number, 3);
+        assert_eq!(format!("{}", insertion.file), "# Node.js\nnode_modules/\n*.tgz\n\n# Editors\n.vscode/");
+
+        let insertion = insert("# Node.js\nnode_modules/\n", "!keep.tgz");
+        assert!(insertion.new_section);
+        assert_eq!(format!("{}", insertion.file), "# Node.js\nnode_modules/\n\n# Exceptions\n!keep.tgz");
+    }
+
+    #[test]
+    fn test_insert_reports_duplicates_and_covering_patterns() {
+        let insertion = insert("*.log\nbuild/\n", "build/");
+        assert_eq!(insertion.duplicate_of, Some((2, "build/".to_string())));
+        assert_eq!(format!("{}", insertion.file), "*.log\nbuild/");
+
+        let insertion = insert("build/\n", "build/app.js");
+        assert_eq!(insertion.covered_by, Some((1, "build/".to_string())));
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_insert_keeps_later_negations_deciding() {
+        let insertion = insert("# Node.js\nnode_modules/\n\n# Exceptions\n!keep.tgz\n", "*.tgz");
+        assert_eq!(insertion.section, None);
+        assert_eq!(insertion.line_number, 6);
+    }
+}
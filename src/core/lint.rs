@@ -0,0 +1,438 @@
+use crate::core::attributes::{AttributeRule, ATTRIBUTES_FILE_NAME};
+use crate::core::optimizer::{find_pattern_conflicts, optimize_gitignore_advanced};
+use crate::core::pattern_analyzer::PatternAnalyzer;
+use crate::core::risk::ChangeRisk;
+use crate::core::rules::{find_rule, Rule, Severity};
+use crate::core::validator::{find_pattern_problems, PatternProblem};
+use crate::models::{EntryType, GitignoreFile, GixError, Span};
+use crate::utils::patterns::GlobPattern;
+use crate::utils::stable_hash;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A kind of hygiene problem reported by `gix check`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// A pattern repeats an earlier pattern
+    Duplicate,
+    /// A pattern of opposite polarity can match the same paths as an earlier one
+    Conflict,
+    /// A pattern is fully covered by a broader pattern
+    Redundant,
+    /// A pattern is invalid or can never match, for the reason given
+    Invalid(PatternProblem),
+    /// The file has no patterns at all, only comments or blank lines
+    Empty,
+    /// A pattern ignores files `.gitattributes` stores in Git LFS, so they are never committed
+    LfsTracked,
+    /// A pattern ignores files `.gitattributes` marks `export-ignore`, which only affects tracked files
+    ExportIgnored,
+    /// A plugin reported a problem with the entry (see `PluginRegistry::lint`)
+    Plugin,
+}
+
+impl LintKind {
+    /// Get the name used in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintKind::Duplicate => "duplicate",
+            LintKind::Conflict => "conflict",
+            LintKind::Redundant => "redundant",
+            LintKind::Invalid(problem) => problem.code(),
+            LintKind::Empty => "empty",
+            LintKind::LfsTracked => "lfs-tracked",
+            LintKind::ExportIgnored => "export-ignored",
+            LintKind::Plugin => "plugin",
+        }
+    }
+
+    /// Get the rule reporting issues of this kind
+    pub fn rule(&self) -> &'static Rule {
+        find_rule(self.name()).expect("every lint kind has a rule")
+    }
+
+    /// Get the code of the rule reporting issues of this kind (`GIX001`)
+    pub fn code(&self) -> &'static str {
+        self.rule().code
+    }
+
+    /// Get the risk of fixing an issue of this kind
+    ///
+    /// Duplicates and redundant patterns can be removed without changing what is ignored,
+    /// unless a negation sits between the two lines (see `LintIssue::risk`);
+    /// conflicts, invalid patterns and disagreements with `.gitattributes` need a decision about
+    /// what was meant, and an empty file is fixed by deleting it. Plugins don't say what their
+    /// fixes would do.
+    pub fn risk(&self) -> ChangeRisk {
+        match self {
+            LintKind::Duplicate | LintKind::Redundant => ChangeRisk::Safe,
+            LintKind::Conflict | LintKind::Invalid(_) | LintKind::Empty | LintKind::LfsTracked | LintKind::ExportIgnored | LintKind::Plugin => {
+                ChangeRisk::BehaviorMayChange
+            }
+        }
+    }
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A single problem found in a gitignore file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    /// Line number (1-indexed)
+    pub line_number: usize,
+    /// The kind of problem
+    pub kind: LintKind,
+    /// Human-readable description
+    pub message: String,
+    /// Stable identifier of the entry on this line (see `GitignoreFile::entry_ids`)
+    pub entry_id: String,
+    /// Stable identifier of the issue, the same across runs as long as the entries involved are unchanged
+    pub id: String,
+    /// Working-tree paths that both patterns involved match, when examples were requested
+    pub examples: Vec<String>,
+    /// Where the issue is: the pattern on its line, or the whole line
+    pub span: Span,
+    /// The other line involved, such as the first occurrence of a duplicate
+    pub related: Option<RelatedLocation>,
+    /// What the check does with the issue; `Deny` until `RuleSeverities::apply` says otherwise
+    pub severity: Severity,
+    /// Risk of the change fixing the issue: that of its kind, or `BehaviorMayChange` when a
+    /// negation sits between the two lines involved, since it may decide paths either way
+    pub risk: ChangeRisk,
+}
+
+/// Another line involved in an issue, for editors to link to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedLocation {
+    /// The pattern on that line
+    pub span: Span,
+    /// What the line is to the issue, e.g. "first occurrence of '*.log'"
+    pub message: String,
+}
+
+/// Most example paths attached to an issue
+pub const MAX_EXAMPLES: usize = 3;
+
+/// Default number of working-tree paths examined per issue when looking for examples
+pub const DEFAULT_EXAMPLE_BUDGET: usize = 10_000;
+
+/// Report duplicates, conflicts, redundant and invalid patterns without modifying the file
+///
+/// Issues are returned in line order; issues on the same line keep the order of the checks.
+pub fn lint_gitignore(file: &GitignoreFile) -> Result<Vec<LintIssue>, GixError> {
+    lint(file, &[], 0)
+}
+
+/// Like `lint_gitignore`, with up to `MAX_EXAMPLES` paths that both patterns of an issue match
+///
+/// `paths` are relative to the directory holding the file, with a trailing `/` on directories,
+/// as listed by `utils::walker::list_paths`. Only the first `budget` paths are examined per
+/// issue, so large trees stay fast at the cost of missing some examples. Examples are appended
+/// to the message as well, so every report format shows them.
+pub fn lint_gitignore_with_examples(file: &GitignoreFile, paths: &[String], budget: usize) -> Result<Vec<LintIssue>, GixError> {
+    lint(file, paths, budget)
+}
+
+/// Report patterns that can ignore files the attribute `rules` store in Git LFS or mark `export-ignore`
+///
+/// The rules should come from the `.gitattributes` next to the file, so both sets of patterns are
+/// relative to the same directory. Negations are skipped. A pattern is reported when it ignores
+/// the paths a rule is written for, or only paths the rule covers: `*.psd` and `art/cover.psd`
+/// against `*.psd filter=lfs`, but not `build/`, which merely may hold such files.
+pub fn lint_attribute_conflicts(file: &GitignoreFile, rules: &[AttributeRule]) -> Vec<LintIssue> {
+    let ids = entry_ids_by_line(file);
+    let mut issues = Vec::new();
+    for entry in &file.entries {
+        let Some(pattern) = entry.comparison_pattern() else {
+            continue;
+        };
+        let glob = GlobPattern::parse(&pattern);
+        if glob.negated {
+            continue;
+        }
+        let concerns = |rule: &&AttributeRule| {
+            let rule_glob = GlobPattern::parse(&rule.pattern);
+            rule_glob.covers(&glob) || rule_glob.witnesses().iter().any(|path| glob.matches(path))
+        };
+        for rule in rules.iter().filter(concerns) {
+            let rule_text = format!("'{} {}' on {} line {}", rule.pattern, rule.attributes.join(" "), ATTRIBUTES_FILE_NAME, rule.line_number);
+            let mut found = Vec::new();
+            if rule.is_lfs() {
+                found.push((LintKind::LfsTracked, format!("'{}' ignores files Git LFS tracks ({}); they would never be committed", pattern, rule_text)));
+            }
+            if rule.sets("export-ignore") {
+                found.push((LintKind::ExportIgnored, format!("'{}' ignores files marked export-ignore ({}), which only matters for tracked files", pattern, rule_text)));
+            }
+            for (kind, message) in found {
+                let entry_id = ids.get(&entry.line_number).cloned().unwrap_or_default();
+                issues.push(LintIssue {
+                    line_number: entry.line_number,
+                    kind,
+                    message,
+                    id: stable_hash(&format!("{}:{}:{}", kind, entry_id, rule.pattern)),
+                    entry_id,
+                    examples: Vec::new(),
+                    span: file.span_of(entry.line_number),
+                    related: None,
+                    severity: Severity::Deny,
+                    risk: kind.risk(),
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// Find up to `MAX_EXAMPLES` of the first `budget` paths that both patterns apply to
+pub fn example_paths(pattern: &str, other: &str, paths: &[String], budget: usize) -> Vec<String> {
+    let (pattern, other) = (GlobPattern::parse(pattern), GlobPattern::parse(other));
+    paths.iter()
+        .take(budget)
+        .filter(|path| pattern.matches(path) && other.matches(path))
+        .take(MAX_EXAMPLES)
+        .cloned()
+        .collect()
+}
+
+pub(crate) fn entry_ids_by_line(file: &GitignoreFile) -> HashMap<usize, String> {
+    file.entries.iter()
+        .zip(file.entry_ids())
+        .filter_map(|(entry, id)| Some((entry.line_number, id?)))
+        .collect()
+}
+
+fn lint(file: &GitignoreFile, paths: &[String], budget: usize) -> Result<Vec<LintIssue>, GixError> {
+    let ids = entry_ids_by_line(file);
+    let patterns: HashMap<usize, String> = file.entries.iter()
+        .filter_map(|entry| Some((entry.line_number, entry.comparison_pattern()?)))
+        .collect();
+    let negation_lines: Vec<usize> = patterns.iter()
+        .filter(|(_, pattern)| pattern.starts_with('!'))
+        .map(|(line, _)| *line)
+        .collect();
+    // Each issue is identified by its kind, its entry and the entry it relates to, if any
+    let issue = |line_number: usize, kind: LintKind, message: String, related: Option<(usize, String)>| {
+        let related_line = related.as_ref().map(|(line, _)| *line);
+        let entry_id = ids.get(&line_number).cloned().unwrap_or_default();
+        let related_id = related_line.and_then(|line| ids.get(&line)).map(String::as_str).unwrap_or("");
+        let examples = match (patterns.get(&line_number), related_line.and_then(|line| patterns.get(&line))) {
+            (Some(pattern), Some(other)) => example_paths(pattern, other, paths, budget),
+            _ => Vec::new(),
+        };
+        let message = if examples.is_empty() { message } else { format!("{} (e.g. {})", message, examples.join(", ")) };
+        LintIssue {
+            line_number,
+            kind,
+            message,
+            id: stable_hash(&format!("{}:{}:{}", kind, entry_id, related_id)),
+            entry_id,
+            examples,
+            span: file.span_of(line_number),
+            related: related.map(|(line, message)| RelatedLocation { span: file.span_of(line), message }),
+            severity: Severity::Deny,
+            risk: match related_line {
+                Some(other) if negation_lines.iter().any(|line| (line_number.min(other) + 1..line_number.max(other)).contains(line)) => {
+                    ChangeRisk::BehaviorMayChange
+                }
+                _ => kind.risk(),
+            },
+        }
+    };
+    let mut issues = Vec::new();
+
+    if file.stats.pattern_lines == 0 {
+        let message = "file has no patterns and ignores nothing; remove it from the repository (`git rm`)".to_string();
+        issues.push(issue(1, LintKind::Empty, message, None));
+    }
+
+    for entry in &file.entries {
+        if let EntryType::Pattern(pattern) = &entry.entry_type {
+            for problem in find_pattern_problems(pattern) {
+                issues.push(issue(entry.line_number, LintKind::Invalid(problem), problem.message(pattern), None));
+            }
+        }
+    }
+
+    for group in file.find_duplicates().iter() {
+        for line_number in &group.line_numbers[1..] {
+            let message = match group.section_of(*line_number) {
+                Some(section) => format!("'{}' under '{}' duplicates {}", group.pattern, section, group.describe_line(group.first_line())),
+                None => format!("'{}' duplicates {}", group.pattern, group.describe_line(group.first_line())),
+            };
+            let related = format!("first occurrence of '{}'", group.pattern);
+            issues.push(issue(*line_number, LintKind::Duplicate, message, Some((group.first_line(), related))));
+        }
+    }
+
+    // Duplicates are already reported, so redundancy is checked after safe deduplication
+    let (_, redundant) = optimize_gitignore_advanced(file, &PatternAnalyzer::safe())?;
+    for removed in redundant {
+        let message = format!("'{}' is covered by '{}' on line {}", removed.pattern, removed.covered_by, removed.covered_by_line);
+        let related = format!("'{}' covers '{}'", removed.covered_by, removed.pattern);
+        issues.push(issue(removed.line_number, LintKind::Redundant, message, Some((removed.covered_by_line, related))));
+    }
+
+    for conflict in find_pattern_conflicts(file) {
+        let message = format!("'{}' conflicts with '{}' on line {}", conflict.other, conflict.pattern, conflict.line_number);
+        let related = format!("'{}' matches the same paths", conflict.pattern);
+        issues.push(issue(conflict.other_line, LintKind::Conflict, message, Some((conflict.line_number, related))));
+    }
+
+    issues.sort_by_key(|issue| issue.line_number);
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::attributes::parse_gitattributes;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_clean_file_has_no_issues() {
+        let file = parse_gitignore("# Logs\n*.log\n\n# Build\nbuild/").unwrap();
+        assert!(lint_gitignore(&file).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lint_reports_each_kind() {
+        let file = parse_gitignore("*.log\nbuild/\n*.log\nbuild/out.txt\n!debug.log").unwrap();
+        let issues = lint_gitignore(&file).unwrap();
+
+        let found: Vec<(usize, LintKind)> = issues.iter().map(|issue| (issue.line_number, issue.kind)).collect();
+        assert_eq!(found, vec![
+            (3, LintKind::Duplicate),
+            (4, LintKind::Redundant),
+            (5, LintKind::Conflict),
+            (5, LintKind::Conflict),
+        ]);
+        assert_eq!(issues[0].message, "'*.log' duplicates line 1");
+        assert_eq!(issues[1].message, "'build/out.txt' is covered by 'build/' on line 2");
+    }
+
+    #[test]
+    fn test_negated_name_conflicts_with_anchored_wildcard() {
+        // `!b` re-includes `logs/b`, which `logs/*` ignores
+        let file = parse_gitignore("logs/*\n!b").unwrap();
+        let issues = lint_gitignore(&file).unwrap();
+
+        let found: Vec<(usize, LintKind)> = issues.iter().map(|issue| (issue.line_number, issue.kind)).collect();
+        assert_eq!(found, vec![(2, LintKind::Conflict)]);
+        assert_eq!(issues[0].message, "'!b' conflicts with 'logs/*' on line 1");
+    }
+
+    #[test]
+    fn test_findings_across_a_negation_may_change_behavior() {
+        // The negation may decide paths of the later line either way, so removing it isn't safe
+        let file = parse_gitignore("*.txt\n!a.log\nlogs/*.txt\n*.txt").unwrap();
+        let issues = lint_gitignore(&file).unwrap();
+
+        let found: Vec<(usize, LintKind, ChangeRisk)> = issues.iter().map(|issue| (issue.line_number, issue.kind, issue.risk)).collect();
+        assert_eq!(found, vec![
+            (3, LintKind::Redundant, ChangeRisk::BehaviorMayChange),
+            (4, LintKind::Duplicate, ChangeRisk::BehaviorMayChange),
+        ]);
+
+        let file = parse_gitignore("*.txt\nlogs/*.txt\n!a.log").unwrap();
+        assert_eq!(lint_gitignore(&file).unwrap()[0].risk, ChangeRisk::Safe);
+    }
+
+    #[test]
+    fn test_duplicates_name_their_sections() {
+        let file = parse_gitignore("# Logs\n*.log\n\n# Debugging\n*.log\n").unwrap();
+        let issues = lint_gitignore(&file).unwrap();
+        assert_eq!(issues[0].message, "'*.log' under '# Debugging' duplicates line 2 under '# Logs'");
+    }
+
+    #[test]
+    fn test_lint_reports_invalid_patterns() {
+        let file = parse_gitignore("*.log\n*.[ch\nsrc/../build\nlogs\\\n").unwrap();
+        let issues = lint_gitignore(&file).unwrap();
+
+        let found: Vec<(usize, &str)> = issues.iter().map(|issue| (issue.line_number, issue.kind.name())).collect();
+        assert_eq!(found, vec![(2, "invalid-class"), (3, "never-matches"), (4, "trailing-backslash")]);
+        assert_eq!(issues[1].message, "paths git matches never contain '.' or '..' segments, so 'src/../build' matches nothing");
+        assert_eq!(issues[0].kind.risk(), ChangeRisk::BehaviorMayChange);
+    }
+
+    #[test]
+    fn test_lint_with_examples() {
+        let file = parse_gitignore("build/\nbuild/*.o\n*.log").unwrap();
+        let paths: Vec<String> = ["app.log", "build/", "build/a.o", "build/b.o", "build/c.o", "build/d.o", "build/e.txt"]
+            .iter()
+            .map(|path| path.to_string())
+            .collect();
+        let issues = lint_gitignore_with_examples(&file, &paths, DEFAULT_EXAMPLE_BUDGET).unwrap();
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].examples, vec!["build/a.o", "build/b.o", "build/c.o"]);
+        assert_eq!(issues[0].message, "'build/*.o' is covered by 'build/' on line 1 (e.g. build/a.o, build/b.o, build/c.o)");
+        assert_eq!(issues[0].id, lint_gitignore(&file).unwrap()[0].id);
+
+        // Only the budgeted paths are examined
+        let issues = lint_gitignore_with_examples(&file, &paths, 3).unwrap();
+        assert_eq!(issues[0].examples, vec!["build/a.o"]);
+    }
+
+    #[test]
+    fn test_issue_ids_are_stable_and_distinct() {
+        let before = lint_gitignore(&parse_gitignore("*.log\n*.log\n!debug.log").unwrap()).unwrap();
+        let after = lint_gitignore(&parse_gitignore("build/\n*.log\n*.log\n!debug.log").unwrap()).unwrap();
+
+        let before_ids: Vec<&str> = before.iter().map(|issue| issue.id.as_str()).collect();
+        let after_ids: Vec<&str> = after.iter().map(|issue| issue.id.as_str()).collect();
+        assert_eq!(before_ids, after_ids);
+
+        // Both conflicts are on the negation but relate to different entries
+        assert_eq!(before[1].entry_id, before[2].entry_id);
+        assert_ne!(before[1].id, before[2].id);
+    }
+
+    #[test]
+    fn test_issues_link_related_lines() {
+        let file = parse_gitignore("*.log\nbuild/  # output\n*.log\n!debug.log").unwrap();
+        let issues = lint_gitignore(&file).unwrap();
+
+        assert_eq!(issues[0].span, Span { line: 3, start_column: 1, end_column: 6 });
+        assert_eq!(issues[0].related, Some(RelatedLocation {
+            span: Span { line: 1, start_column: 1, end_column: 6 },
+            message: "first occurrence of '*.log'".to_string(),
+        }));
+        assert_eq!(issues[1].span, Span { line: 4, start_column: 1, end_column: 11 });
+        assert_eq!(issues[1].related.as_ref().map(|related| related.span.line), Some(1));
+        assert!(lint_gitignore(&parse_gitignore("# empty").unwrap()).unwrap()[0].related.is_none());
+    }
+
+    #[test]
+    fn test_lint_reports_file_without_patterns() {
+        let issues = lint_gitignore(&parse_gitignore("# Nothing here yet\n\n").unwrap()).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].line_number, issues[0].kind), (1, LintKind::Empty));
+    }
+
+    #[test]
+    fn test_lint_attribute_conflicts() {
+        let file = parse_gitignore("*.psd\nbuild/\n!keep.psd\n*.log\nart/cover.psd\n").unwrap();
+        let rules = parse_gitattributes("*.psd filter=lfs diff=lfs merge=lfs -text\nbuild/** export-ignore\n");
+        let issues = lint_attribute_conflicts(&file, &rules);
+
+        let found: Vec<(usize, LintKind)> = issues.iter().map(|issue| (issue.line_number, issue.kind)).collect();
+        assert_eq!(found, vec![(1, LintKind::LfsTracked), (2, LintKind::ExportIgnored), (5, LintKind::LfsTracked)]);
+        assert_eq!(
+            issues[0].message,
+            "'*.psd' ignores files Git LFS tracks ('*.psd filter=lfs diff=lfs merge=lfs -text' on .gitattributes line 1); they would never be committed"
+        );
+        assert_eq!(issues[0].kind.risk(), ChangeRisk::BehaviorMayChange);
+    }
+
+    #[test]
+    fn test_lint_kind_names() {
+        assert_eq!(LintKind::Duplicate.to_string(), "duplicate");
+        assert_eq!(LintKind::Conflict.code(), "GIX003");
+        assert_eq!(LintKind::Invalid(PatternProblem::UnclosedClass).to_string(), "invalid-class");
+    }
+}
@@ -0,0 +1,191 @@
+use crate::core::pattern_analyzer::PatternAnalyzer;
+use crate::models::{EntryType, GitignoreEntry, GitignoreFile};
+use crate::utils::patterns::GlobPattern;
+use std::collections::HashMap;
+
+/// Header of the section holding negations moved below patterns from later files
+pub const REORDERED_NEGATIONS_HEADER: &str = "# Re-included after patterns from later files (gix merge)";
+
+/// A pattern left out of the merged file because an earlier source already has it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedDuplicate {
+    /// Label of the source that had the duplicate
+    pub source: String,
+    /// Line number of the duplicate in that source (1-indexed)
+    pub line_number: usize,
+    /// The duplicate pattern
+    pub pattern: String,
+    /// Label of the source whose pattern was kept
+    pub kept_from: String,
+}
+
+/// A negation moved to the end of the merged file so a later source can't override it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReorderedNegation {
+    /// Label of the source the negation came from
+    pub source: String,
+    /// Line number of the negation in that source (1-indexed)
+    pub line_number: usize,
+    /// The negation (`!pattern`)
+    pub pattern: String,
+    /// The later pattern that would have ignored the re-included paths again
+    pub overridden_by: String,
+    /// Label of the source of that pattern
+    pub overridden_source: String,
+}
+
+/// The result of merging several gitignore files
+#[derive(Debug, Clone)]
+pub struct MergedGitignore {
+    /// One section per source, headed by `# From <label>`, then any reordered negations
+    pub file: GitignoreFile,
+    /// Patterns dropped as duplicates of earlier sources
+    pub duplicates: Vec<MergedDuplicate>,
+    /// Negations moved below the later patterns they re-include paths from
+    pub reordered: Vec<ReorderedNegation>,
+}
+
+/// Merge labelled gitignore files into one, in the order given
+///
+/// Each source keeps its comments and blank lines in a section headed by its label. A pattern
+/// `analyzer` considers a duplicate of one from an earlier source is dropped. In a single file
+/// a later pattern wins, so a negation followed by a pattern from a later source that matches
+/// the same paths would stop re-including them; such negations move to a final section.
+pub fn merge_gitignores(sources: &[(String, GitignoreFile)], analyzer: &PatternAnalyzer) -> MergedGitignore {
+    let mut seen: HashMap<String, &str> = HashMap::new();
+    let mut duplicates = Vec::new();
+    // Each entry with the index of its source
+    let mut merged: Vec<(GitignoreEntry, usize)> = Vec::new();
+
+    for (index, (label, file)) in sources.iter().enumerate() {
+        let start = file.entries.iter().position(|entry| !entry.is_blank()).unwrap_or(file.entries.len());
+        let end = file.entries.iter().rposition(|entry| !entry.is_blank()).map_or(start, |last| last + 1);
+        if start == end {
+            continue;
+        }
+
+        if !merged.is_empty() {
+            merged.push((GitignoreEntry::new(String::new(), EntryType::Blank, 0), index));
+        }
+        let header = format!("# From {}", label);
+        merged.push((GitignoreEntry::new(header.clone(), EntryType::Comment(header), 0), index));
+
+        for entry in &file.entries[start..end] {
+            if let Some(pattern) = entry.comparison_pattern() {
+                if let Some(kept_from) = seen.get(&analyzer.dedup_key(&pattern)) {
+                    duplicates.push(MergedDuplicate {
+                        source: label.clone(),
+                        line_number: entry.line_number,
+                        pattern,
+                        kept_from: kept_from.to_string(),
+                    });
+                    continue;
+                }
+                seen.insert(analyzer.dedup_key(&pattern), label);
+            }
+            merged.push((entry.clone(), index));
+        }
+    }
+
+    let labels: Vec<String> = sources.iter().map(|(label, _)| label.clone()).collect();
+    let (merged, reordered) = reorder_negations(merged, &labels);
+
+    let mut file = GitignoreFile::new();
+    if let Some((_, first)) = sources.first() {
+        file.keep_format_of(first);
+    }
+    for (index, mut entry) in merged.into_iter().enumerate() {
+        entry.line_number = index + 1;
+        file.add_entry(entry);
+    }
+    MergedGitignore { file, duplicates, reordered }
+}
+
+/// Move negations followed by an overlapping pattern from a later source to a final section
+///
+/// Each entry comes with the index of its source in `labels`; line numbers are left as they are.
+pub fn reorder_negations(merged: Vec<(GitignoreEntry, usize)>, labels: &[String]) -> (Vec<GitignoreEntry>, Vec<ReorderedNegation>) {
+    let globs: Vec<Option<GlobPattern>> = merged.iter()
+        .map(|(entry, _)| entry.comparison_pattern().map(|pattern| GlobPattern::parse(&pattern)))
+        .collect();
+
+    let mut kept = Vec::new();
+    let mut moved = Vec::new();
+    let mut reordered = Vec::new();
+    for (position, (entry, source)) in merged.iter().enumerate() {
+        let Some(negation) = globs[position].as_ref().filter(|glob| glob.negated) else {
+            kept.push(entry.clone());
+            continue;
+        };
+
+        let overriding = (position + 1..merged.len()).find(|&later| {
+            merged[later].1 > *source && globs[later].as_ref().is_some_and(|glob| {
+                !glob.negated && (glob.may_overlap(negation) || glob.covers_parent_of(negation))
+            })
+        });
+        match overriding {
+            Some(later) => {
+                reordered.push(ReorderedNegation {
+                    source: labels[*source].clone(),
+                    line_number: entry.line_number,
+                    pattern: entry.comparison_pattern().unwrap_or_default(),
+                    overridden_by: merged[later].0.comparison_pattern().unwrap_or_default(),
+                    overridden_source: labels[merged[later].1].clone(),
+                });
+                moved.push(entry.clone());
+            }
+            None => kept.push(entry.clone()),
+        }
+    }
+
+    if !moved.is_empty() {
+        let header = REORDERED_NEGATIONS_HEADER.to_string();
+        kept.push(GitignoreEntry::new(String::new(), EntryType::Blank, 0));
+        kept.push(GitignoreEntry::new(header.clone(), EntryType::Comment(header), 0));
+        kept.extend(moved);
+    }
+    (kept, reordered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    fn sources(files: &[(&str, &str)]) -> Vec<(String, GitignoreFile)> {
+        files.iter().map(|(label, content)| (label.to_string(), parse_gitignore(content).unwrap())).collect()
+    }
+
+    #[test]
+    fn test_merge_deduplicates_across_files() {
+        let merged = merge_gitignores(
+            &sources(&[("a.gitignore", "# Logs\n*.log\ntarget/\n\n"), ("b.gitignore", "\n*.log\n# Editors\n.idea/\n")]),
+            &PatternAnalyzer::safe(),
+        );
+
+        assert_eq!(format!("{}", merged.file), "# From a.gitignore\n# Logs\n*.log\ntarget/\n\n# From b.gitignore\n# Editors\n.idea/");
+        assert_eq!(merged.duplicates, vec![MergedDuplicate {
+            source: "b.gitignore".to_string(),
+            line_number: 2,
+            pattern: "*.log".to_string(),
+            kept_from: "a.gitignore".to_string(),
+        }]);
+        assert!(merged.reordered.is_empty());
+    }
+
+    #[test]
+    fn test_merge_moves_negations_below_later_patterns() {
+        let merged = merge_gitignores(
+            &sources(&[("a", "*.log\n!keep.log\n!docs/\n"), ("b", "keep.*\n!other.txt\n")]),
+            &PatternAnalyzer::safe(),
+        );
+
+        assert_eq!(
+            format!("{}", merged.file),
+            format!("# From a\n*.log\n!docs/\n\n# From b\nkeep.*\n!other.txt\n\n{}\n!keep.log", REORDERED_NEGATIONS_HEADER)
+        );
+        assert_eq!(merged.reordered.len(), 1);
+        assert_eq!(merged.reordered[0].overridden_by, "keep.*");
+        assert_eq!(merged.reordered[0].line_number, 2);
+    }
+}
@@ -1,15 +1,85 @@
+pub mod attributes;
+pub mod audit;
+pub mod audit_log;
+pub mod blocks;
 pub mod categorizer;
+pub mod changes;
 pub mod comment_generator;
+#[cfg(feature = "dataset")]
+pub mod dataset;
+pub mod detector;
+pub mod dockerignore;
+pub mod equivalence;
+pub mod explain;
+pub mod extensions;
+pub mod fleet;
+pub mod folding;
+pub mod histogram;
+pub mod history;
+pub mod insertion;
+pub mod lint;
+pub mod merge;
+pub mod negations;
 pub mod normalizer;
 pub mod optimizer;
+pub mod organizer;
 pub mod parser;
 pub mod pattern_analyzer;
+pub mod pipeline;
+pub mod plugins;
+pub mod plan;
+pub mod remote_template;
+pub mod removals;
+pub mod risk;
+pub mod rules;
+pub mod sections;
+pub mod self_test;
+pub mod simulate;
+pub mod sorting;
+pub mod summary;
+pub mod template;
+pub mod unused;
 pub mod validator;
+pub mod whitespace;
 
+pub use attributes::{parse_gitattributes, AttributeRule, ATTRIBUTES_FILE_NAME};
+pub use audit::{find_ignored_tracked_files, TrackedMatch};
+pub use audit_log::{audit_log_path, log_write, read_audit_log, verify_audit_log, AuditRecord, AuditVerification};
+pub use blocks::{find_repeated_blocks, RepeatedBlock};
 pub use categorizer::{PatternCategorizer, PatternCategory, CategorySummary};
+pub use changes::{OptimizationAction, OptimizationReport};
 pub use comment_generator::CommentGenerator;
+pub use detector::{detect_stack, suggest_collapses, suggest_patterns, CollapseSuggestion, Suggestion, COLLAPSE_MIN_PATTERNS};
+pub use dockerignore::{sync_check, SyncConcern, SyncGap, SyncReport};
+pub use equivalence::{decision_changes, files_equivalent, negation_probes, probe_paths, DecisionChange, EquivalenceReport};
+pub use explain::{explain_path, MatchOutcome, MatchedPattern, PathExplanation};
+pub use extensions::{find_extension_groups, ExtensionGroup, EXTENSION_GROUP_MIN};
+pub use fleet::{assess_fleet, assess_repository, checkouts_in, read_repository_list, FleetFormat, FleetLocation, FleetMember, FleetReport, FleetRepository};
+pub use folding::{fold_character_classes, FoldedPatterns};
+pub use histogram::{pattern_histogram, BucketKey, HistogramBucket, PatternHistogram};
+pub use history::{history_dir, read_history, record_step, undo_steps, HistoryChange, HistoryStep, HISTORY_DIR_NAME};
+pub use insertion::{insert_pattern, Insertion};
+pub use lint::{lint_attribute_conflicts, lint_gitignore, lint_gitignore_with_examples, LintIssue, LintKind, RelatedLocation};
+pub use merge::{merge_gitignores, MergedDuplicate, MergedGitignore, ReorderedNegation};
+pub use negations::{group_negations, MovedNegation};
 pub use normalizer::{normalize_pattern, patterns_equivalent, patterns_equivalent_case_sensitive};
-pub use optimizer::{optimize_gitignore, optimize_gitignore_aggressive, analyze_gitignore, GitignoreAnalysis};
-pub use parser::parse_gitignore;
+pub use optimizer::{optimize_gitignore, optimize_gitignore_aggressive, optimize_gitignore_redundant, optimize_gitignore_advanced, remove_cross_layer_duplicates, restore_kept_patterns, restore_negation_semantics, protected_lines, KeptForSafety, PassStats, RedundantPattern, find_pattern_conflicts, PatternConflict, analyze_gitignore, GitignoreAnalysis, AnalysisFilter, AnalysisScope};
+pub use organizer::organize_gitignore;
+pub use parser::{parse_gitignore, parse_ignore_file, Dialect};
 pub use pattern_analyzer::{PatternAnalyzer, PatternAnalysis, PatternType};
-pub use validator::{validate_pattern, is_valid_pattern}; 
\ No newline at end of file
+pub use pipeline::{OptimizeResult, Optimizer, OptimizerBuilder};
+pub use plan::{Plan, PlanEdit, PlannedFile, PLAN_VERSION};
+pub use plugins::{CommandPlugin, GixPlugin, PluginRegistry};
+pub use remote_template::{fetch_template, merge_template, RemoteTemplate, TemplateSource};
+pub use removals::{collect_removed_entries, render_removals_log, RemovedEntry};
+pub use risk::ChangeRisk;
+pub use rules::{find_rule, Rule, RuleSeverities, Severity, RULES};
+pub use sections::{merge_repeated_sections, remove_emptied_sections};
+pub use self_test::{describe_decision, run_self_test, self_test_corpus, Divergence, SelfTestCase, SelfTestReport};
+pub use simulate::{simulate_addition, simulate_removal, AdditionEffect, RemovalEffect};
+pub use sorting::sort_sections;
+pub use summary::{scan_repository, write_summary, RepositorySummary, ScanOptions, ScannedFile};
+pub use template::{compare_with_template, compose_templates, generate_gitignore, tailor_gitignore, ComposedGitignore, TailoredGitignore, TemplateDrift};
+pub use unused::{find_unused_patterns, prune_unused_patterns, UnusedPattern};
+pub use validator::{validate_pattern, is_valid_pattern, find_pattern_problems, PatternProblem};
+pub use whitespace::{check_whitespace, normalize_whitespace, WhitespaceFix, WhitespaceFixKind, WhitespacePolicy, WhitespaceRule, WhitespaceViolation}; 
\ No newline at end of file
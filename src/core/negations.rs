@@ -0,0 +1,154 @@
+use crate::models::{GitignoreEntry, GitignoreFile};
+use crate::utils::patterns::GlobPattern;
+
+/// A negation moved next to the pattern it carves an exception from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovedNegation {
+    /// The negation (`!pattern`)
+    pub pattern: String,
+    /// Line number of the negation before the move (1-indexed)
+    pub line_number: usize,
+    /// The pattern the negation now follows
+    pub negates: String,
+    /// Line number of that pattern before the move (1-indexed)
+    pub negates_line: usize,
+}
+
+/// Move each negation up to directly follow the latest earlier pattern it re-includes paths from
+///
+/// A negation is only moved when none of the patterns it would jump over can match a path it
+/// matches, so every path is decided exactly as before. Negations grouped under the same
+/// pattern keep their relative order, a negation standing alone between blank lines takes one
+/// of them along, and line numbers are renumbered afterwards. Negations inside a `# gix:off`
+/// region stay put, and none is moved into one.
+pub fn group_negations(file: &GitignoreFile) -> (GitignoreFile, Vec<MovedNegation>) {
+    let mut entries: Vec<(GitignoreEntry, Option<GlobPattern>)> = file.entries.iter()
+        .map(|entry| (entry.clone(), entry.comparison_pattern().map(|pattern| GlobPattern::parse(&pattern))))
+        .collect();
+    let mut moved = Vec::new();
+
+    let negation_lines: Vec<usize> = entries.iter()
+        .filter(|(entry, glob)| !entry.preserve && glob.as_ref().is_some_and(|glob| glob.negated))
+        .map(|(entry, _)| entry.line_number)
+        .collect();
+    for line_number in negation_lines {
+        let Some((from, negation)) = entries.iter().enumerate().find_map(|(index, (entry, glob))| match glob {
+            Some(glob) if glob.negated && entry.line_number == line_number => Some((index, glob.clone())),
+            _ => None,
+        }) else {
+            continue;
+        };
+
+        // The pattern this negation carves an exception from
+        let Some(target) = entries[..from].iter().rposition(|(_, glob)| {
+            glob.as_ref().is_some_and(|glob| !glob.negated && glob.may_overlap(&negation))
+        }) else {
+            continue;
+        };
+
+        // Land after the target and any negations already grouped with it
+        let mut to = target + 1;
+        while to < from && entries[to].1.as_ref().is_some_and(|glob| glob.negated) {
+            to += 1;
+        }
+        // Only comments in between (such as one describing the negation) count as grouped already
+        let grouped = entries[to..from].iter().all(|(entry, _)| entry.is_comment());
+        if grouped || (entries[to - 1].0.preserve && entries[to].0.preserve) {
+            continue;
+        }
+
+        let jumped_matters = entries[to..from].iter().any(|(_, glob)| {
+            glob.as_ref().is_some_and(|glob| !glob.negated && (glob.may_overlap(&negation) || glob.covers_parent_of(&negation)))
+        });
+        if jumped_matters {
+            continue;
+        }
+
+        let entry = entries.remove(from);
+        // Don't leave a doubled blank line where the negation stood between two
+        if from > 0 && from < entries.len() && entries[from - 1].0.is_blank() && entries[from].0.is_blank() && !entries[from].0.preserve {
+            entries.remove(from);
+        }
+        moved.push(MovedNegation {
+            pattern: entry.0.comparison_pattern().unwrap_or_default(),
+            line_number,
+            negates: entries[target].0.comparison_pattern().unwrap_or_default(),
+            negates_line: entries[target].0.line_number,
+        });
+        entries.insert(to, entry);
+    }
+
+    let mut grouped = GitignoreFile::new();
+    grouped.keep_format_of(file);
+    for (index, (mut entry, _)) in entries.into_iter().enumerate() {
+        entry.line_number = index + 1;
+        grouped.add_entry(entry);
+    }
+    (grouped, moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_negation_moves_next_to_its_pattern() {
+        let file = parse_gitignore("*.log\n!*.keep\nbuild/\n\n# Docs\ndocs/\n!debug.log\n!trace.log").unwrap();
+        let (grouped, moved) = group_negations(&file);
+
+        assert_eq!(format!("{}", grouped), "*.log\n!*.keep\n!debug.log\n!trace.log\nbuild/\n\n# Docs\ndocs/");
+        assert_eq!(moved.len(), 2);
+        assert_eq!(moved[0], MovedNegation {
+            pattern: "!debug.log".to_string(),
+            line_number: 7,
+            negates: "*.log".to_string(),
+            negates_line: 1,
+        });
+        let lines: Vec<usize> = grouped.entries.iter().map(|entry| entry.line_number).collect();
+        assert_eq!(lines, (1..=8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_moved_negation_leaves_no_doubled_blank_line() {
+        let file = parse_gitignore("a/**/b\n\n!a/x/b\n\n/build").unwrap();
+        let (grouped, moved) = group_negations(&file);
+
+        assert_eq!(moved.len(), 1);
+        assert_eq!(format!("{}", grouped), "a/**/b\n!a/x/b\n\n/build");
+        assert_eq!(group_negations(&grouped).0.entries.len(), grouped.entries.len());
+    }
+
+    #[test]
+    fn test_negation_stays_when_a_later_pattern_matches_it() {
+        let file = parse_gitignore("*.log\ndebug.*\n!debug.log").unwrap();
+        let (grouped, moved) = group_negations(&file);
+
+        assert!(moved.is_empty());
+        assert_eq!(format!("{}", grouped), "*.log\ndebug.*\n!debug.log");
+    }
+
+    #[test]
+    fn test_negation_without_target_stays() {
+        let file = parse_gitignore("!keep.txt\n*.log").unwrap();
+        assert!(group_negations(&file).1.is_empty());
+    }
+
+    #[test]
+    fn test_negation_below_its_own_comment_stays() {
+        let file = parse_gitignore("*.log\n# Keep the debug log\n!debug.log").unwrap();
+        assert!(group_negations(&file).1.is_empty());
+    }
+
+    #[test]
+    fn test_preserved_region_is_left_alone() {
+        let file = parse_gitignore("*.log\nbuild/\n# gix:off\n!a.log\n# gix:on\n!b.log").unwrap();
+        let (grouped, moved) = group_negations(&file);
+
+        assert_eq!(moved.len(), 1);
+        assert_eq!(format!("{}", grouped), "*.log\n!b.log\nbuild/\n# gix:off\n!a.log\n# gix:on");
+
+        let file = parse_gitignore("# gix:off\n*.log\nbuild/\n# gix:on\n!b.log").unwrap();
+        assert!(group_negations(&file).1.is_empty());
+    }
+}
@@ -1,45 +1,64 @@
-use crate::models::{GitignoreFile, GixError};
+use crate::models::{is_keep_marker, EntryType, GitignoreFile, GixError};
 use crate::core::pattern_analyzer::{PatternAnalyzer, PatternAnalysis};
-use std::collections::{HashSet, HashMap};
+use crate::core::categorizer::PatternCategorizer;
+use crate::core::blocks::{find_repeated_blocks, repeated_block_lines};
+use crate::core::sections::{merge_repeated_sections, remove_emptied_sections};
+use crate::core::unused::UnusedPattern;
+use crate::core::folding::{fold_character_classes, FoldedPatterns};
+use crate::core::risk::ChangeRisk;
+use crate::core::histogram::{pattern_histogram, PatternHistogram};
+use crate::core::extensions::{find_extension_groups, ExtensionGroup, EXTENSION_GROUP_MIN};
+use crate::core::equivalence::{decision_changes, negation_probes, DecisionChange};
+use crate::utils::patterns::GlobPattern;
+use crate::utils::tokens::{Segment, Token};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 
 /// Optimize a gitignore file by removing duplicate patterns while preserving structure
+///
+/// Only exact duplicates are removed, so the set of ignored files never changes.
 pub fn optimize_gitignore(file: &GitignoreFile) -> Result<GitignoreFile, GixError> {
-    let analyzer = PatternAnalyzer::default();
+    let analyzer = PatternAnalyzer::safe();
     optimize_gitignore_with_analyzer(file, &analyzer)
 }
 
 /// Optimize a gitignore file with more aggressive deduplication
+///
+/// Besides exact duplicate patterns this removes duplicate comments, repeated blocks
+/// and consecutive blank lines, none of which affect which files are ignored. Sections
+/// repeating an earlier header are merged into it, and sections left without patterns go.
 pub fn optimize_gitignore_aggressive(file: &GitignoreFile) -> Result<GitignoreFile, GixError> {
-    let analyzer = PatternAnalyzer::default();
+    let analyzer = PatternAnalyzer::safe();
     optimize_gitignore_aggressive_with_analyzer(file, &analyzer)
 }
 
 /// Optimize a gitignore file using a specific pattern analyzer
 pub fn optimize_gitignore_with_analyzer(file: &GitignoreFile, analyzer: &PatternAnalyzer) -> Result<GitignoreFile, GixError> {
+    optimize_gitignore_tracked(file, analyzer, &mut PassStats::default())
+}
+
+/// Optimize a gitignore file using a specific pattern analyzer, counting what each pass removed
+pub fn optimize_gitignore_tracked(file: &GitignoreFile, analyzer: &PatternAnalyzer, stats: &mut PassStats) -> Result<GitignoreFile, GixError> {
     let mut optimized = GitignoreFile::new();
     let mut seen_patterns: HashSet<String> = HashSet::new();
-    let mut pattern_analyses: HashMap<String, PatternAnalysis> = HashMap::new();
-    
-    // First pass: collect all patterns and their analyses
-    for entry in &file.entries {
-        if let crate::models::EntryType::Pattern(pattern) = &entry.entry_type {
-            let analysis = analyzer.analyze_pattern(pattern);
-            pattern_analyses.insert(pattern.clone(), analysis);
-        }
-    }
+    let mut seen_exact: HashSet<String> = HashSet::new();
     
-    // Second pass: deduplicate patterns using analysis
+    // Deduplicate patterns using their normalized form
     for entry in &file.entries {
         match &entry.entry_type {
             crate::models::EntryType::Pattern(pattern) => {
-                let analysis = &pattern_analyses[pattern];
-                let normalized = &analysis.normalized;
+                // Whitespace separating an inline comment is not part of the pattern
+                let comparison = entry.comparison_pattern().unwrap_or_else(|| pattern.clone());
+                let normalized = analyzer.dedup_key(&comparison);
                 
                 // Use normalized pattern for deduplication to improve performance
-                if !seen_patterns.contains(normalized) {
-                    seen_patterns.insert(normalized.clone());
+                if !seen_patterns.contains(&normalized) {
+                    seen_patterns.insert(normalized);
                     optimized.add_entry(entry.clone());
+                } else {
+                    stats.record_duplicate(&seen_exact, &comparison);
                 }
+                seen_exact.insert(comparison);
             }
             crate::models::EntryType::Comment(_) | crate::models::EntryType::Blank => {
                 // Always preserve comments and blank lines
@@ -51,33 +70,76 @@ pub fn optimize_gitignore_with_analyzer(file: &GitignoreFile, analyzer: &Pattern
     Ok(optimized)
 }
 
+/// Remove every occurrence of a duplicated pattern but the last, counting them in `stats`
+///
+/// Git lets the last matching pattern decide, so dropping the earlier copies of a pattern never
+/// changes what is ignored. Comments and blank lines stay where they are.
+pub fn remove_earlier_duplicates(file: &GitignoreFile, analyzer: &PatternAnalyzer, stats: &mut PassStats) -> GitignoreFile {
+    let mut seen_patterns: HashSet<String> = HashSet::new();
+    let mut seen_exact: HashSet<String> = HashSet::new();
+    let mut removed: HashSet<usize> = HashSet::new();
+    
+    for entry in file.entries.iter().rev() {
+        let Some(comparison) = entry.comparison_pattern() else {
+            continue;
+        };
+        if !seen_patterns.insert(analyzer.dedup_key(&comparison)) {
+            stats.record_duplicate(&seen_exact, &comparison);
+            removed.insert(entry.line_number);
+        }
+        seen_exact.insert(comparison);
+    }
+    
+    let mut optimized = GitignoreFile::new();
+    for entry in file.entries.iter().filter(|entry| !removed.contains(&entry.line_number)) {
+        optimized.add_entry(entry.clone());
+    }
+    optimized
+}
+
 /// Optimize a gitignore file with aggressive deduplication using a specific analyzer
 pub fn optimize_gitignore_aggressive_with_analyzer(file: &GitignoreFile, analyzer: &PatternAnalyzer) -> Result<GitignoreFile, GixError> {
+    optimize_gitignore_aggressive_tracked(file, analyzer, &mut PassStats::default())
+}
+
+/// Optimize a gitignore file with aggressive deduplication, counting what each pass removed
+pub fn optimize_gitignore_aggressive_tracked(file: &GitignoreFile, analyzer: &PatternAnalyzer, stats: &mut PassStats) -> Result<GitignoreFile, GixError> {
     let mut optimized = GitignoreFile::new();
     let mut seen_patterns: HashSet<String> = HashSet::new();
+    let mut seen_exact: HashSet<String> = HashSet::new();
     let mut seen_comments: HashSet<String> = HashSet::new();
-    let mut pattern_analyses: HashMap<String, PatternAnalysis> = HashMap::new();
     
-    // First pass: collect all patterns and their analyses
+    // Whole blocks pasted twice are dropped as a unit, comments and all
+    let repeated_lines = repeated_block_lines(&find_repeated_blocks(file));
+    let mut unrepeated = GitignoreFile::new();
     for entry in &file.entries {
-        if let crate::models::EntryType::Pattern(pattern) = &entry.entry_type {
-            let analysis = analyzer.analyze_pattern(pattern);
-            pattern_analyses.insert(pattern.clone(), analysis);
+        if repeated_lines.contains(&entry.line_number) {
+            stats.repeated_blocks += 1;
+        } else {
+            unrepeated.add_entry(entry.clone());
         }
     }
     
-    // Second pass: aggressive deduplication
-    for entry in &file.entries {
+    // Then sections under a header seen before join the first one
+    let (merged, merged_lines) = merge_repeated_sections(&unrepeated);
+    stats.section_lines += merged_lines;
+    
+    // Aggressive deduplication
+    for entry in &merged.entries {
         match &entry.entry_type {
             crate::models::EntryType::Pattern(pattern) => {
-                let analysis = &pattern_analyses[pattern];
-                let normalized = &analysis.normalized;
+                // Whitespace separating an inline comment is not part of the pattern
+                let comparison = entry.comparison_pattern().unwrap_or_else(|| pattern.clone());
+                let normalized = analyzer.dedup_key(&comparison);
                 
                 // Use normalized pattern for deduplication to improve performance
-                if !seen_patterns.contains(normalized) {
-                    seen_patterns.insert(normalized.clone());
+                if !seen_patterns.contains(&normalized) {
+                    seen_patterns.insert(normalized);
                     optimized.add_entry(entry.clone());
+                } else {
+                    stats.record_duplicate(&seen_exact, &comparison);
                 }
+                seen_exact.insert(comparison);
             }
             crate::models::EntryType::Comment(comment) => {
                 let normalized = comment.trim();
@@ -86,6 +148,8 @@ pub fn optimize_gitignore_aggressive_with_analyzer(file: &GitignoreFile, analyze
                 if !seen_comments.contains(normalized) {
                     seen_comments.insert(normalized.to_string());
                     optimized.add_entry(entry.clone());
+                } else {
+                    stats.duplicate_comments += 1;
                 }
             }
             crate::models::EntryType::Blank => {
@@ -93,11 +157,15 @@ pub fn optimize_gitignore_aggressive_with_analyzer(file: &GitignoreFile, analyze
                 if optimized.entries.is_empty() || 
                    !matches!(optimized.entries.last().unwrap().entry_type, crate::models::EntryType::Blank) {
                     optimized.add_entry(entry.clone());
+                } else {
+                    stats.blank_lines += 1;
                 }
             }
         }
     }
     
+    let (optimized, emptied_lines) = remove_emptied_sections(file, &optimized);
+    stats.section_lines += emptied_lines;
     Ok(optimized)
 }
 
@@ -106,15 +174,6 @@ pub fn optimize_gitignore_with_conflicts(file: &GitignoreFile) -> Result<(Gitign
     let analyzer = PatternAnalyzer::default();
     let mut optimized = GitignoreFile::new();
     let mut seen_patterns: HashSet<String> = HashSet::new();
-    let mut pattern_analyses: HashMap<String, PatternAnalysis> = HashMap::new();
-    
-    // First pass: collect all patterns and their analyses
-    for entry in &file.entries {
-        if let crate::models::EntryType::Pattern(pattern) = &entry.entry_type {
-            let analysis = analyzer.analyze_pattern(pattern);
-            pattern_analyses.insert(pattern.clone(), analysis);
-        }
-    }
     
     // Find conflicts
     let pattern_strings: Vec<String> = file.entries.iter()
@@ -128,17 +187,20 @@ pub fn optimize_gitignore_with_conflicts(file: &GitignoreFile) -> Result<(Gitign
         .collect();
     
     let conflicts = analyzer.find_conflicts(&pattern_strings);
+    let overridden = overridden_lines(file);
     
-    // Second pass: deduplicate patterns using analysis
+    // Deduplicate patterns using their normalized form, dropping patterns a later negation fully undoes
     for entry in &file.entries {
         match &entry.entry_type {
+            crate::models::EntryType::Pattern(_) if overridden.contains(&entry.line_number) => {}
             crate::models::EntryType::Pattern(pattern) => {
-                let analysis = &pattern_analyses[pattern];
-                let normalized = &analysis.normalized;
+                // Whitespace separating an inline comment is not part of the pattern
+                let comparison = entry.comparison_pattern().unwrap_or_else(|| pattern.clone());
+                let normalized = analyzer.dedup_key(&comparison);
                 
                 // Use normalized pattern for deduplication to improve performance
-                if !seen_patterns.contains(normalized) {
-                    seen_patterns.insert(normalized.clone());
+                if !seen_patterns.contains(&normalized) {
+                    seen_patterns.insert(normalized);
                     optimized.add_entry(entry.clone());
                 }
             }
@@ -152,6 +214,456 @@ pub fn optimize_gitignore_with_conflicts(file: &GitignoreFile) -> Result<(Gitign
     Ok((optimized, conflicts))
 }
 
+/// Line numbers of patterns whose every match is later matched by a pattern of opposite polarity
+///
+/// Git uses the last matching pattern, so such a pattern never decides anything.
+fn overridden_lines(file: &GitignoreFile) -> HashSet<usize> {
+    let patterns: Vec<(usize, GlobPattern)> = file.entries.iter()
+        .filter_map(|entry| match &entry.entry_type {
+            crate::models::EntryType::Pattern(pattern) => {
+                let comparison = entry.comparison_pattern().unwrap_or_else(|| pattern.clone());
+                Some((entry.line_number, GlobPattern::parse(&comparison)))
+            }
+            _ => None,
+        })
+        .collect();
+    
+    patterns.iter()
+        .enumerate()
+        .filter(|(i, (_, earlier))| {
+            patterns[i + 1..].iter().any(|(_, later)| later.negated != earlier.negated && later.covers(earlier))
+        })
+        .map(|(_, (line_number, _))| *line_number)
+        .collect()
+}
+
+/// Optimize a gitignore file using pattern analysis followed by redundancy elimination
+///
+/// Duplicates are removed using the analyzer's notion of equality, then patterns covered by
+/// broader patterns are dropped and returned for reporting, and sibling patterns differing in
+/// one character are folded into a character class.
+pub fn optimize_gitignore_advanced(file: &GitignoreFile, analyzer: &PatternAnalyzer) -> Result<(GitignoreFile, Vec<RedundantPattern>), GixError> {
+    optimize_gitignore_advanced_tracked(file, analyzer, &mut PassStats::default())
+}
+
+/// Optimize a gitignore file in advanced mode, counting what each pass removed
+pub fn optimize_gitignore_advanced_tracked(file: &GitignoreFile, analyzer: &PatternAnalyzer, stats: &mut PassStats) -> Result<(GitignoreFile, Vec<RedundantPattern>), GixError> {
+    optimize_gitignore_advanced_in_tree(file, analyzer, &[], stats).map(|(optimized, redundant, _)| (optimized, redundant))
+}
+
+/// Optimize a gitignore file in advanced mode, checking each fold against the working tree `paths`
+///
+/// Also returns the folds made, for reporting.
+pub fn optimize_gitignore_advanced_in_tree(file: &GitignoreFile, analyzer: &PatternAnalyzer, paths: &[String], stats: &mut PassStats) -> Result<(GitignoreFile, Vec<RedundantPattern>, Vec<FoldedPatterns>), GixError> {
+    advanced_passes(file, analyzer, paths, true, stats)
+}
+
+/// Run the advanced passes, dropping covered patterns only if `subsume` is set
+pub(crate) fn advanced_passes(file: &GitignoreFile, analyzer: &PatternAnalyzer, paths: &[String], subsume: bool, stats: &mut PassStats) -> Result<(GitignoreFile, Vec<RedundantPattern>, Vec<FoldedPatterns>), GixError> {
+    let mut optimized = optimize_gitignore_tracked(file, analyzer, stats)?;
+    let mut redundant = Vec::new();
+    let mut folded = Vec::new();
+    
+    // Dropping a pattern can unblock another, and so can a fold, so repeat until nothing
+    // changes; a second run over the result then has nothing left to do
+    loop {
+        let (pruned, removed) = match subsume {
+            true => optimize_gitignore_redundant(&optimized)?,
+            false => (optimized.clone(), Vec::new()),
+        };
+        stats.subsumed_patterns += removed.len();
+        let folds_before = stats.folded_patterns;
+        let pruned = fold_character_classes(&pruned, paths, &mut folded, stats);
+        if removed.is_empty() && stats.folded_patterns == folds_before {
+            break;
+        }
+        redundant.extend(removed);
+        optimized = pruned;
+    }
+    redundant.sort_by_key(|pattern| pattern.line_number);
+    folded.sort_by_key(|fold| fold.line_numbers[0]);
+    Ok((optimized, redundant, folded))
+}
+
+/// Find the lines of the patterns optimization must never remove
+///
+/// A pattern is protected when its exact text is listed in `keep`, the line right above it
+/// is a `# gix:keep` comment, or it is inside a `# gix:off` region.
+pub fn protected_lines(file: &GitignoreFile, keep: &[String]) -> HashSet<usize> {
+    let mut marked = false;
+    let mut lines = HashSet::new();
+    for entry in &file.entries {
+        if let Some(pattern) = entry.comparison_pattern() {
+            if marked || entry.preserve || keep.contains(&pattern) {
+                lines.insert(entry.line_number);
+            }
+        }
+        marked = matches!(&entry.entry_type, EntryType::Comment(comment) if is_keep_marker(comment));
+    }
+    lines
+}
+
+/// Put back the protected patterns (see `protected_lines`) that optimizing `original` into `optimized` removed or rewrote
+///
+/// Each one returns right after the entries that preceded it in `original`, which relies on
+/// the passes keeping line numbers and order.
+pub fn restore_kept_patterns(original: &GitignoreFile, optimized: &GitignoreFile, keep: &[String]) -> GitignoreFile {
+    restore_lines(original, optimized, &protected_lines(original, keep))
+}
+
+/// A pattern put back because removing it changed how a negation plays out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeptForSafety {
+    /// Line number of the pattern (1-indexed)
+    pub line_number: usize,
+    /// The pattern
+    pub pattern: String,
+    /// A path its removal decided differently
+    pub change: DecisionChange,
+}
+
+impl KeptForSafety {
+    /// Explain why the pattern was kept
+    pub fn explain(&self) -> String {
+        format!("without it {}", self.change.describe())
+    }
+}
+
+/// Put back patterns whose removal changes what the negations of `original` re-include
+///
+/// Order matters once `!` rules are involved, so `optimized` is compared with `original` on
+/// paths sampled from the negations and the patterns around them (see `negation_probes`).
+/// Removed patterns are tried one at a time, in line order, and each one that brings the
+/// decisions closer to the original's is kept, with a path it decides.
+pub fn restore_negation_semantics(original: &GitignoreFile, optimized: &GitignoreFile) -> (GitignoreFile, Vec<KeptForSafety>) {
+    let probes = negation_probes(&[original, optimized]);
+    let mut changes = decision_changes(original, optimized, &probes);
+    let mut restored = optimized.clone();
+    let mut kept = Vec::new();
+    if changes.is_empty() {
+        return (restored, kept);
+    }
+    
+    let present: HashSet<(usize, String)> = optimized.entries.iter()
+        .filter_map(|entry| Some((entry.line_number, entry.comparison_pattern()?)))
+        .collect();
+    let mut lines = HashSet::new();
+    for entry in &original.entries {
+        let Some(pattern) = entry.comparison_pattern() else {
+            continue;
+        };
+        if changes.is_empty() || present.contains(&(entry.line_number, pattern.clone())) {
+            continue;
+        }
+        // A pattern that applies to none of the changed paths can't bring any of them back
+        let glob = GlobPattern::parse(&pattern);
+        if !changes.iter().any(|change| glob.matches(&change.path)) {
+            continue;
+        }
+        
+        lines.insert(entry.line_number);
+        let candidate = restore_lines(original, optimized, &lines);
+        let remaining = decision_changes(original, &candidate, &probes);
+        match changes.iter().find(|change| !remaining.contains(change)) {
+            Some(fixed) if remaining.len() < changes.len() => {
+                kept.push(KeptForSafety { line_number: entry.line_number, pattern, change: fixed.clone() });
+                restored = candidate;
+                changes = remaining;
+            }
+            _ => {
+                lines.remove(&entry.line_number);
+            }
+        }
+    }
+    (restored, kept)
+}
+
+/// Put back the patterns of `original` on `lines` that are missing from `optimized`, in place
+fn restore_lines(original: &GitignoreFile, optimized: &GitignoreFile, lines: &HashSet<usize>) -> GitignoreFile {
+    let present: HashSet<(usize, String)> = optimized.entries.iter()
+        .filter_map(|entry| Some((entry.line_number, entry.comparison_pattern()?)))
+        .collect();
+    let mut missing = original.entries.iter()
+        .filter(|entry| lines.contains(&entry.line_number) && entry.comparison_pattern().is_some_and(|pattern| {
+            !present.contains(&(entry.line_number, pattern))
+        }))
+        .peekable();
+    
+    let mut restored = GitignoreFile::new();
+    restored.keep_format_of(optimized);
+    for entry in &optimized.entries {
+        while let Some(kept) = missing.next_if(|kept| kept.line_number < entry.line_number) {
+            restored.add_entry(kept.clone());
+        }
+        restored.add_entry(entry.clone());
+    }
+    for kept in missing {
+        restored.add_entry(kept.clone());
+    }
+    restored
+}
+
+/// Drop patterns that another ignore layer of the same checkout already provides
+///
+/// `higher` holds the patterns of layers that take precedence over this file (the root
+/// `.gitignore` over `info/exclude`): every path one of them matches is decided there, so a
+/// copy here never matters. `lower` holds the patterns of layers this file overrides (the
+/// global excludes file); a copy here only stops mattering when neither side has negations
+/// that could flip the outcome, so lower layers are skipped otherwise.
+pub fn remove_cross_layer_duplicates(file: &GitignoreFile, higher: &[String], lower: &[String], stats: &mut PassStats) -> GitignoreFile {
+    let use_lower = !lower.iter().any(|pattern| pattern.starts_with('!'))
+        && !file.entries.iter().filter_map(|entry| entry.comparison_pattern()).any(|pattern| pattern.starts_with('!'));
+    let provided: HashSet<&str> = higher.iter()
+        .chain(lower.iter().filter(|_| use_lower))
+        .map(String::as_str)
+        .collect();
+    
+    let mut optimized = GitignoreFile::new();
+    optimized.keep_format_of(file);
+    for entry in &file.entries {
+        match entry.comparison_pattern() {
+            Some(pattern) if provided.contains(pattern.as_str()) => stats.cross_layer_duplicates += 1,
+            _ => optimized.add_entry(entry.clone()),
+        }
+    }
+    optimized
+}
+
+/// Number of lines removed by each optimization pass
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PassStats {
+    /// Patterns identical to an earlier pattern
+    pub exact_duplicates: usize,
+    /// Patterns merged with an equivalent-looking pattern (heuristics only)
+    pub equivalent_patterns: usize,
+    /// Patterns covered by a broader pattern
+    pub subsumed_patterns: usize,
+    /// Patterns folded with siblings into one pattern with a character class
+    pub folded_patterns: usize,
+    /// Lines of blocks pasted a second time
+    pub repeated_blocks: usize,
+    /// Comments identical to an earlier comment
+    pub duplicate_comments: usize,
+    /// Blank lines collapsed into the one before them
+    pub blank_lines: usize,
+    /// Patterns another ignore layer already provides
+    pub cross_layer_duplicates: usize,
+    /// Patterns pruned because they match nothing in the working tree
+    pub unused_patterns: usize,
+    /// Headers and blank lines of sections merged into an earlier one or left without patterns
+    pub section_lines: usize,
+    /// Blank lines beyond the run whitespace normalization keeps
+    pub whitespace_lines: usize,
+}
+
+impl PassStats {
+    /// Removals per pass, in pipeline order, with the names used in reports
+    pub fn passes(&self) -> [(&'static str, usize); 11] {
+        [
+            ("repeated blocks", self.repeated_blocks),
+            ("exact dedup", self.exact_duplicates),
+            ("equivalence", self.equivalent_patterns),
+            ("subsumption", self.subsumed_patterns),
+            ("class folding", self.folded_patterns),
+            ("cross-layer dedup", self.cross_layer_duplicates),
+            ("unused pruning", self.unused_patterns),
+            ("duplicate comments", self.duplicate_comments),
+            ("blank collapse", self.blank_lines),
+            ("empty sections", self.section_lines),
+            ("whitespace", self.whitespace_lines),
+        ]
+    }
+    
+    /// Get the risk of the removals made by a pass, by its name in `passes`
+    ///
+    /// Equivalence merges are only safe when made by the safe analyzer, which merges spellings
+    /// git treats identically; `heuristics` says whether heuristic merges were enabled.
+    pub fn risk(pass: &str, heuristics: bool) -> ChangeRisk {
+        match pass {
+            "equivalence" if heuristics => ChangeRisk::BehaviorMayChange,
+            "cross-layer dedup" => ChangeRisk::LikelySafe,
+            "unused pruning" => ChangeRisk::BehaviorMayChange,
+            _ => ChangeRisk::Safe,
+        }
+    }
+    
+    /// Total number of removed lines
+    pub fn total(&self) -> usize {
+        self.passes().iter().map(|(_, count)| count).sum()
+    }
+    
+    /// Attribute a dropped pattern to exact dedup if its exact text was seen before, else to equivalence
+    fn record_duplicate(&mut self, seen_exact: &HashSet<String>, comparison: &str) {
+        if seen_exact.contains(comparison) {
+            self.exact_duplicates += 1;
+        } else {
+            self.equivalent_patterns += 1;
+        }
+    }
+}
+
+/// A pattern removed because another pattern already matches everything it matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedundantPattern {
+    /// Line number of the removed pattern
+    pub line_number: usize,
+    /// The removed pattern
+    pub pattern: String,
+    /// Line number of the pattern that covers it
+    pub covered_by_line: usize,
+    /// The covering pattern
+    pub covered_by: String,
+}
+
+/// Optimize a gitignore file by removing patterns that broader patterns already cover
+///
+/// `build/foo.txt` is dropped when `build/` is present, `*.pyc` when `**/*.pyc` is present, and so on.
+/// A removal only happens when no negation could make the narrower pattern matter.
+pub fn optimize_gitignore_redundant(file: &GitignoreFile) -> Result<(GitignoreFile, Vec<RedundantPattern>), GixError> {
+    let patterns: Vec<(&crate::models::GitignoreEntry, GlobPattern)> = file.entries.iter()
+        .filter_map(|entry| match &entry.entry_type {
+            crate::models::EntryType::Pattern(pattern) => {
+                let comparison = entry.comparison_pattern().unwrap_or_else(|| pattern.clone());
+                Some((entry, GlobPattern::parse(&comparison)))
+            }
+            _ => None,
+        })
+        .collect();
+    
+    // A literal pattern covers only its own text, directly or as a parent directory, so it is
+    // looked up by that text. A pattern with wildcards can only cover text starting with the
+    // literal characters its last component starts with, so it is looked up by those.
+    let leading_literal = |segment: &Segment| -> String {
+        segment.tokens.iter().map_while(|token| match token {
+            Token::Literal(ch) => Some(*ch),
+            _ => None,
+        }).collect()
+    };
+    let mut literal: HashMap<(bool, &[Segment]), Vec<usize>> = HashMap::new();
+    let mut wildcard: HashMap<String, Vec<usize>> = HashMap::new();
+    for (j, (_, glob)) in patterns.iter().enumerate() {
+        match (glob.is_literal(), glob.segments.last()) {
+            (true, _) => literal.entry((glob.anchored, &glob.segments[..])).or_default().push(j),
+            (false, Some(last)) => wildcard.entry(leading_literal(last)).or_default().push(j),
+            (false, None) => {}
+        }
+    }
+    let candidates = |narrow: &GlobPattern| -> Vec<usize> {
+        let mut targets: Vec<&[Segment]> = vec![&narrow.segments[..]];
+        if narrow.anchored {
+            targets.extend((1..narrow.segments.len())
+                .map(|end| &narrow.segments[..end])
+                .take_while(|prefix| !prefix.iter().any(Segment::is_globstar)));
+        }
+        let mut found: Vec<usize> = Vec::new();
+        for target in targets {
+            let last = &target[target.len() - 1..];
+            let text = leading_literal(&last[0]);
+            let prefixes = text.char_indices().map(|(at, _)| &text[..at]).chain([text.as_str()]);
+            found.extend(prefixes.filter_map(|prefix| wildcard.get(prefix)).flatten());
+            found.extend(literal.get(&(false, last)).into_iter().flatten());
+            if narrow.anchored {
+                found.extend(literal.get(&(true, target)).into_iter().flatten());
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+    };
+    // Only a pattern of the other kind can block a removal
+    let by_kind: [Vec<usize>; 2] = [false, true].map(|negated| (0..patterns.len()).filter(|&k| patterns[k].1.negated == negated).collect());
+    
+    // Decide from the bottom up, so a pattern is only ever removed in favour of one that is kept
+    let mut covering: Vec<Option<usize>> = vec![None; patterns.len()];
+    for i in (0..patterns.len()).rev() {
+        let narrow = &patterns[i].1;
+        covering[i] = candidates(narrow).into_iter()
+            .filter(|&j| j != i && covering[j].is_none())
+            .find(|&j| {
+                let broad = &patterns[j].1;
+                if broad.negated != narrow.negated {
+                    return false;
+                }
+                
+                // A later pattern matching the same paths always has the last word; otherwise a
+                // negation in between (or anywhere after, for a covering parent directory) could
+                // make the narrower pattern decide a path
+                let blockers = if broad.covers(narrow) {
+                    if j > i { return true; }
+                    j + 1..i
+                } else if broad.covers_parent_of(narrow) {
+                    i.min(j) + 1..patterns.len()
+                } else {
+                    return false;
+                };
+                
+                let others = &by_kind[!narrow.negated as usize];
+                let start = others.partition_point(|&k| k < blockers.start);
+                others[start..].iter()
+                    .take_while(|&&k| k < blockers.end)
+                    .all(|&k| !patterns[k].1.may_overlap(narrow))
+            });
+    }
+    
+    let mut optimized = GitignoreFile::new();
+    let mut redundant = Vec::new();
+    let mut index = 0;
+    for entry in &file.entries {
+        if !entry.is_pattern() {
+            optimized.add_entry(entry.clone());
+            continue;
+        }
+        
+        match covering[index] {
+            Some(j) => redundant.push(RedundantPattern {
+                line_number: entry.line_number,
+                pattern: entry.original.clone(),
+                covered_by_line: patterns[j].0.line_number,
+                covered_by: patterns[j].0.original.clone(),
+            }),
+            None => optimized.add_entry(entry.clone()),
+        }
+        index += 1;
+    }
+    
+    Ok((optimized, redundant))
+}
+
+/// Two patterns of opposite polarity that can match the same path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternConflict {
+    /// Line number of the earlier pattern
+    pub line_number: usize,
+    /// The earlier pattern
+    pub pattern: String,
+    /// Line number of the later pattern
+    pub other_line: usize,
+    /// The later pattern
+    pub other: String,
+}
+
+/// Find conflicting patterns along with the lines they appear on
+pub fn find_pattern_conflicts(file: &GitignoreFile) -> Vec<PatternConflict> {
+    let analyzer = PatternAnalyzer::default();
+    let patterns: Vec<(usize, &str)> = file.entries.iter()
+        .filter_map(|entry| match &entry.entry_type {
+            crate::models::EntryType::Pattern(pattern) => Some((entry.line_number, pattern.as_str())),
+            _ => None,
+        })
+        .collect();
+    
+    let texts: Vec<&str> = patterns.iter().map(|(_, pattern)| *pattern).collect();
+    analyzer.conflicting_pairs(&texts)
+        .into_iter()
+        .map(|(i, j)| PatternConflict {
+            line_number: patterns[i].0,
+            pattern: patterns[i].1.to_string(),
+            other_line: patterns[j].0,
+            other: patterns[j].1.to_string(),
+        })
+        .collect()
+}
+
 /// Get detailed analysis of a gitignore file
 pub fn analyze_gitignore(file: &GitignoreFile) -> Result<GitignoreAnalysis, GixError> {
     let analyzer = PatternAnalyzer::default();
@@ -176,6 +688,8 @@ pub fn analyze_gitignore(file: &GitignoreFile) -> Result<GitignoreAnalysis, GixE
         .collect();
     
     analysis.conflicts = analyzer.find_conflicts(&pattern_strings);
+    analysis.histogram = pattern_histogram(pattern_strings.iter().map(String::as_str));
+    analysis.extension_groups = find_extension_groups(file, EXTENSION_GROUP_MIN);
     
     Ok(analysis)
 }
@@ -207,6 +721,12 @@ pub struct GitignoreAnalysis {
     pub conflicts: Vec<(String, String)>,
     /// Pattern analyses
     pub pattern_analyses: Vec<PatternAnalysis>,
+    /// Patterns that match nothing in the working tree, when it was walked
+    pub unused_patterns: Vec<UnusedPattern>,
+    /// Patterns bucketed by first path segment or extension
+    pub histogram: PatternHistogram,
+    /// Related extension patterns that one comment could describe
+    pub extension_groups: Vec<ExtensionGroup>,
 }
 
 impl GitignoreAnalysis {
@@ -224,6 +744,9 @@ impl GitignoreAnalysis {
             case_insensitive_patterns: 0,
             conflicts: Vec::new(),
             pattern_analyses: Vec::new(),
+            unused_patterns: Vec::new(),
+            histogram: PatternHistogram::default(),
+            extension_groups: Vec::new(),
         }
     }
     
@@ -268,6 +791,51 @@ impl GitignoreAnalysis {
     pub fn conflict_count(&self) -> usize {
         self.conflicts.len()
     }
+    
+    /// Build a new analysis containing only the patterns selected by the filter
+    pub fn filter(&self, filter: &AnalysisFilter) -> GitignoreAnalysis {
+        if filter.is_empty() {
+            return self.clone();
+        }
+        
+        let categorizer = PatternCategorizer::default();
+        let conflicting: HashSet<&str> = self.conflicts.iter()
+            .flat_map(|(a, b)| [a.as_str(), b.as_str()])
+            .collect();
+        
+        let mut filtered = GitignoreAnalysis::new();
+        for analysis in &self.pattern_analyses {
+            if filter.matches(analysis, &conflicting, &categorizer) {
+                filtered.add_pattern_analysis(analysis.clone());
+            }
+        }
+        
+        // Conflicts are only reported when selected, and only for retained patterns
+        if filter.selects(AnalysisScope::Conflicts) {
+            let retained: HashSet<&str> = filtered.pattern_analyses.iter()
+                .map(|analysis| analysis.original.as_str())
+                .collect();
+            filtered.conflicts = self.conflicts.iter()
+                .filter(|(a, b)| retained.contains(a.as_str()) || retained.contains(b.as_str()))
+                .cloned()
+                .collect();
+        }
+        
+        let retained: HashSet<&str> = filtered.pattern_analyses.iter()
+            .map(|analysis| analysis.original.as_str())
+            .collect();
+        filtered.unused_patterns = self.unused_patterns.iter()
+            .filter(|unused| retained.contains(unused.pattern.as_str()))
+            .cloned()
+            .collect();
+        filtered.histogram = pattern_histogram(filtered.pattern_analyses.iter().map(|analysis| analysis.original.as_str()));
+        filtered.extension_groups = self.extension_groups.iter()
+            .filter(|group| group.patterns.iter().all(|pattern| retained.contains(pattern.as_str())))
+            .cloned()
+            .collect();
+        
+        filtered
+    }
 }
 
 impl Default for GitignoreAnalysis {
@@ -276,10 +844,106 @@ impl Default for GitignoreAnalysis {
     }
 }
 
+/// A part of the analysis that can be selected with `--only`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisScope {
+    /// Patterns involved in a conflict
+    Conflicts,
+    /// Negation patterns
+    Negations,
+    /// Patterns with wildcards
+    Wildcards,
+    /// Patterns with globstar
+    Globstar,
+    /// Absolute path patterns
+    Absolute,
+    /// Directory patterns
+    Directories,
+    /// File patterns
+    Files,
+}
+
+impl AnalysisScope {
+    /// Check if a pattern analysis falls within this scope
+    fn matches(&self, analysis: &PatternAnalysis, conflicting: &HashSet<&str>) -> bool {
+        match self {
+            AnalysisScope::Conflicts => conflicting.contains(analysis.original.as_str()),
+            AnalysisScope::Negations => analysis.is_negation,
+            AnalysisScope::Wildcards => analysis.has_wildcards,
+            AnalysisScope::Globstar => analysis.has_globstar,
+            AnalysisScope::Absolute => analysis.is_absolute,
+            AnalysisScope::Directories => analysis.matches_directories,
+            AnalysisScope::Files => analysis.matches_files,
+        }
+    }
+}
+
+impl FromStr for AnalysisScope {
+    type Err = GixError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "conflicts" => Ok(AnalysisScope::Conflicts),
+            "negations" => Ok(AnalysisScope::Negations),
+            "wildcards" => Ok(AnalysisScope::Wildcards),
+            "globstar" => Ok(AnalysisScope::Globstar),
+            "absolute" => Ok(AnalysisScope::Absolute),
+            "directories" => Ok(AnalysisScope::Directories),
+            "files" => Ok(AnalysisScope::Files),
+            other => Err(GixError::ParseError(format!(
+                "unknown analysis scope '{}' (expected conflicts, negations, wildcards, globstar, absolute, directories or files)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Filter used to scope a GitignoreAnalysis before it is rendered
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisFilter {
+    /// Keep only patterns within at least one of these scopes (empty keeps all)
+    pub only: Vec<AnalysisScope>,
+    /// Keep only patterns in this category, matched against its name case-insensitively
+    pub category: Option<String>,
+}
+
+impl AnalysisFilter {
+    /// Check if the filter selects everything
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.category.is_none()
+    }
+    
+    /// Check if a scope is selected; without `--only` every scope is
+    pub fn selects(&self, scope: AnalysisScope) -> bool {
+        self.only.is_empty() || self.only.contains(&scope)
+    }
+    
+    /// Check if scopes other than conflicts are selected, whose patterns are then listed
+    pub fn selects_patterns(&self) -> bool {
+        self.only.iter().any(|scope| *scope != AnalysisScope::Conflicts)
+    }
+    
+    fn matches(&self, analysis: &PatternAnalysis, conflicting: &HashSet<&str>, categorizer: &PatternCategorizer) -> bool {
+        if !self.only.is_empty() && !self.only.iter().any(|scope| scope.matches(analysis, conflicting)) {
+            return false;
+        }
+        
+        match &self.category {
+            Some(wanted) => {
+                let category = categorizer.primary_category(&analysis.original);
+                category.short_name().eq_ignore_ascii_case(wanted) ||
+                    category.display_name().eq_ignore_ascii_case(wanted)
+            }
+            None => true,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::core::parser::parse_gitignore;
+    use crate::models::WriteOptions;
 
     #[test]
     fn test_basic_optimization() {
@@ -291,6 +955,19 @@ mod tests {
         assert_eq!(optimized.stats.pattern_lines, 2);
     }
 
+    #[test]
+    fn test_remove_earlier_duplicates() {
+        let file = parse_gitignore("*.log\n# Logs\n!debug.log\n*.log\nbuild/\n").unwrap();
+        let mut stats = PassStats::default();
+        let optimized = remove_earlier_duplicates(&file, &PatternAnalyzer::safe(), &mut stats);
+        
+        assert_eq!(format!("{}", optimized), "# Logs\n!debug.log\n*.log\nbuild/");
+        assert_eq!(optimized.entries[2].line_number, 4);
+        assert_eq!(stats.exact_duplicates, 1);
+        // The last `*.log` still decides, so debug.log stays ignored
+        assert_eq!(optimized.matches("debug.log", false).map(|found| found.ignored), file.matches("debug.log", false).map(|found| found.ignored));
+    }
+
     #[test]
     fn test_preserve_comments() {
         let content = "*.log\n# Logs\n*.log\nbuild/";
@@ -335,6 +1012,35 @@ mod tests {
         assert_eq!(optimized.stats.pattern_lines, 2);
     }
 
+    #[test]
+    fn test_inline_comment_separator_whitespace_is_ignored() {
+        let content = "*.log # logs\n*.log\t# again\n*.log  #third\n*.log";
+        let file = parse_gitignore(content).unwrap();
+        let optimized = optimize_gitignore(&file).unwrap();
+        
+        assert_eq!(optimized.entries.len(), 1);
+        assert_eq!(optimized.entries[0].original, "*.log # logs");
+    }
+
+    #[test]
+    fn test_safe_optimization_keeps_heuristic_equivalents() {
+        let content = "build\nbuild/\n/build\nbuild//out\nbuild/out";
+        let file = parse_gitignore(content).unwrap();
+        let optimized = optimize_gitignore(&file).unwrap();
+        
+        assert_eq!(optimized.stats.pattern_lines, 5);
+    }
+
+    #[test]
+    fn test_heuristic_optimization_merges_equivalents() {
+        let content = "build\nbuild/\n/build\n**/build\n!build";
+        let file = parse_gitignore(content).unwrap();
+        let optimized = optimize_gitignore_with_analyzer(&file, &PatternAnalyzer::heuristic()).unwrap();
+        
+        let lines: Vec<&str> = optimized.entries.iter().map(|e| e.original.as_str()).collect();
+        assert_eq!(lines, vec!["build", "!build"]);
+    }
+
     #[test]
     fn test_negation_patterns() {
         let content = "*.log\n!debug.log\n*.log";
@@ -357,6 +1063,27 @@ mod tests {
         assert_eq!(optimized.stats.pattern_lines, 2);
     }
 
+    #[test]
+    fn test_aggressive_removes_repeated_block() {
+        let content = "# Python\n*.pyc\nvenv/\n# keep\n\n# Python\n*.pyc\nvenv/\n# keep\n\nbuild/";
+        let file = parse_gitignore(content).unwrap();
+        let optimized = optimize_gitignore_aggressive(&file).unwrap();
+        
+        let lines: Vec<&str> = optimized.entries.iter().map(|e| e.original.as_str()).collect();
+        assert_eq!(lines, vec!["# Python", "*.pyc", "venv/", "# keep", "", "build/"]);
+    }
+
+    #[test]
+    fn test_aggressive_cleans_up_sections() {
+        let file = parse_gitignore("# Build\ntarget/\n\n# Logs\n*.log\n\n# Old logs\n*.log\n\n# Build\ndist/\ntarget/\n").unwrap();
+        let mut stats = PassStats::default();
+        let optimized = optimize_gitignore_aggressive_tracked(&file, &PatternAnalyzer::safe(), &mut stats).unwrap();
+        
+        assert_eq!(format!("{}", optimized), "# Build\ntarget/\ndist/\n\n# Logs\n*.log");
+        assert_eq!(stats.section_lines, 4);
+        assert_eq!(stats.total(), 6);
+    }
+
     #[test]
     fn test_optimization_with_conflicts() {
         let content = "*.log\n!*.log\nbuild/";
@@ -369,6 +1096,173 @@ mod tests {
                 (conflicts[0].0 == "!*.log" && conflicts[0].1 == "*.log"));
     }
 
+    #[test]
+    fn test_pass_stats_attribute_removals() {
+        let file = parse_gitignore("*.log\n*.log\n\n\n# Build\nbuild/\n/build\n# Build\nbuild/out.txt").unwrap();
+        
+        let mut stats = PassStats::default();
+        let optimized = optimize_gitignore_aggressive_tracked(&file, &PatternAnalyzer::heuristic(), &mut stats).unwrap();
+        assert_eq!(stats, PassStats {
+            exact_duplicates: 1,
+            equivalent_patterns: 1,
+            duplicate_comments: 1,
+            blank_lines: 1,
+            ..PassStats::default()
+        });
+        assert_eq!(stats.total(), file.entries.len() - optimized.entries.len());
+        
+        let mut stats = PassStats::default();
+        optimize_gitignore_advanced_tracked(&file, &PatternAnalyzer::safe(), &mut stats).unwrap();
+        assert_eq!(stats.exact_duplicates, 1);
+        assert_eq!(stats.equivalent_patterns, 0);
+        assert_eq!(stats.subsumed_patterns, 1);
+    }
+
+    #[test]
+    fn test_restore_kept_patterns() {
+        let file = parse_gitignore("*.log\n.env\n*.log\n*.pyc\n*.pyo\n.env").unwrap();
+        let (optimized, _) = optimize_gitignore_advanced(&file, &PatternAnalyzer::safe()).unwrap();
+        assert_eq!(format!("{}", optimized), "*.log\n.env\n*.py[co]");
+        
+        let keep = vec!["*.pyc".to_string(), ".env".to_string()];
+        let restored = restore_kept_patterns(&file, &optimized, &keep);
+        assert_eq!(format!("{}", restored), "*.log\n.env\n*.py[co]\n*.pyc\n.env");
+    }
+
+    #[test]
+    fn test_preserved_region_survives_every_pass() {
+        let file = parse_gitignore("*.pyc\n*.log\n# gix:off\n*.pyo\n*.log\nbuild/\nbuild/out\n# gix:on\n*.log").unwrap();
+        let (optimized, _) = optimize_gitignore_advanced(&file, &PatternAnalyzer::safe()).unwrap();
+        let restored = restore_kept_patterns(&file, &optimized, &[]);
+        
+        assert_eq!(format!("{}", restored), "*.pyc\n*.log\n# gix:off\n*.pyo\n*.log\nbuild/\nbuild/out\n# gix:on");
+    }
+
+    #[test]
+    fn test_keep_marker_protects_next_pattern() {
+        let file = parse_gitignore("*.log\n# gix:keep\n*.log\nbuild/\n#gix:keep\n\nbuild/out").unwrap();
+        assert_eq!(protected_lines(&file, &[]), HashSet::from([3]));
+        
+        let (optimized, _) = optimize_gitignore_advanced(&file, &PatternAnalyzer::safe()).unwrap();
+        let restored = restore_kept_patterns(&file, &optimized, &[]);
+        assert_eq!(format!("{}", restored), "*.log\n# gix:keep\n*.log\nbuild/\n#gix:keep\n");
+    }
+
+    #[test]
+    fn test_restore_negation_semantics() {
+        let file = parse_gitignore("*.log\n!debug.log\n*.log\n*.tmp\n*.tmp").unwrap();
+        let optimized = optimize_gitignore(&file).unwrap();
+        assert_eq!(format!("{}", optimized), "*.log\n!debug.log\n*.tmp");
+        
+        let (restored, kept) = restore_negation_semantics(&file, &optimized);
+        assert_eq!(format!("{}", restored), "*.log\n!debug.log\n*.log\n*.tmp");
+        assert_eq!(kept.len(), 1);
+        assert_eq!((kept[0].line_number, kept[0].pattern.as_str()), (3, "*.log"));
+        assert!(kept[0].change.ignored_before && !kept[0].change.ignored_after);
+        assert!(kept[0].explain().starts_with("without it '"));
+    }
+
+    #[test]
+    fn test_remove_cross_layer_duplicates() {
+        let file = parse_gitignore("*.log\n.env\n*.swp").unwrap();
+        let higher = vec!["*.log".to_string()];
+        let lower = vec!["*.swp".to_string()];
+        
+        let mut stats = PassStats::default();
+        let optimized = remove_cross_layer_duplicates(&file, &higher, &lower, &mut stats);
+        assert_eq!(format!("{}", optimized), ".env");
+        assert_eq!(stats.cross_layer_duplicates, 2);
+        
+        // A negation in the lower layer could be overridden by the copy here
+        let lower = vec!["*.swp".to_string(), "!keep.swp".to_string()];
+        let optimized = remove_cross_layer_duplicates(&file, &higher, &lower, &mut PassStats::default());
+        assert_eq!(format!("{}", optimized), ".env\n*.swp");
+    }
+
+    #[test]
+    fn test_find_pattern_conflicts_with_lines() {
+        let file = parse_gitignore("# Logs\n*.log\nbuild/\n!debug.log").unwrap();
+        let conflicts = find_pattern_conflicts(&file);
+        
+        assert_eq!(conflicts, vec![PatternConflict {
+            line_number: 2,
+            pattern: "*.log".to_string(),
+            other_line: 4,
+            other: "!debug.log".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_optimization_with_conflicts_keeps_partial_overrides() {
+        let content = "*.log\n!debug.log\nbuild/\n!build/keep/";
+        let file = parse_gitignore(content).unwrap();
+        let (optimized, conflicts) = optimize_gitignore_with_conflicts(&file).unwrap();
+        
+        assert_eq!(optimized.entries.len(), 4);
+        assert_eq!(conflicts.len(), 2);
+    }
+
+    #[test]
+    fn test_redundant_file_under_ignored_directory() {
+        let file = parse_gitignore("build/\nbuild/foo.txt\n*.log").unwrap();
+        let (optimized, redundant) = optimize_gitignore_redundant(&file).unwrap();
+        
+        assert_eq!(format!("{}", optimized), "build/\n*.log");
+        assert_eq!(redundant, vec![RedundantPattern {
+            line_number: 2,
+            pattern: "build/foo.txt".to_string(),
+            covered_by_line: 1,
+            covered_by: "build/".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_redundant_keeps_first_of_equivalent_patterns() {
+        let file = parse_gitignore("# Python\n*.pyc\n**/*.pyc\nlogs/*.log\n*.log").unwrap();
+        let (optimized, redundant) = optimize_gitignore_redundant(&file).unwrap();
+        
+        assert_eq!(format!("{}", optimized), "# Python\n*.pyc\n*.log");
+        assert_eq!(redundant.len(), 2);
+        assert_eq!(redundant[0].covered_by, "*.pyc");
+        assert_eq!(redundant[1].covered_by, "*.log");
+    }
+
+    #[test]
+    fn test_redundant_respects_negations() {
+        // debug.log is re-included by the negation and ignored again by the last line
+        let file = parse_gitignore("*.log\n!debug.log\ndebug.log").unwrap();
+        let (optimized, redundant) = optimize_gitignore_redundant(&file).unwrap();
+        
+        assert_eq!(optimized.entries.len(), 3);
+        assert!(redundant.is_empty());
+        
+        let file = parse_gitignore("build/foo.txt\nbuild/\n!build/").unwrap();
+        let (_, redundant) = optimize_gitignore_redundant(&file).unwrap();
+        assert!(redundant.is_empty());
+    }
+
+    #[test]
+    fn test_redundant_keeps_pattern_re_ignoring_a_negated_name() {
+        // logs/b.txt is re-included by the negation and ignored again by the last line
+        let file = parse_gitignore("*.txt\n!b.txt\nlogs/*.txt").unwrap();
+        let (optimized, redundant) = optimize_gitignore_redundant(&file).unwrap();
+        assert_eq!(format!("{}", optimized), "*.txt\n!b.txt\nlogs/*.txt");
+        assert!(redundant.is_empty());
+        
+        let (optimized, _) = optimize_gitignore_advanced(&file, &PatternAnalyzer::safe()).unwrap();
+        assert_eq!(optimized.entries.len(), 3);
+    }
+
+    #[test]
+    fn test_advanced_optimization() {
+        let file = parse_gitignore("*.log\nbuild/\n*.log\nbuild/cache/\n/build").unwrap();
+        let (optimized, redundant) = optimize_gitignore_advanced(&file, &PatternAnalyzer::safe()).unwrap();
+        
+        assert_eq!(format!("{}", optimized), "*.log\nbuild/\n/build");
+        assert_eq!(redundant.len(), 1);
+        assert_eq!(redundant[0].line_number, 4);
+    }
+
     #[test]
     fn test_analyze_gitignore() {
         let content = "*.log\nbuild/\n!debug.log\n# comment";
@@ -381,6 +1275,60 @@ mod tests {
         assert!(analysis.has_conflicts());
     }
 
+    #[test]
+    fn test_filter_analysis_by_scope() {
+        let content = "*.log\n!*.log\nbuild/\n!keep/\n**/cache";
+        let file = parse_gitignore(content).unwrap();
+        let analysis = analyze_gitignore(&file).unwrap();
+        
+        let negations = analysis.filter(&AnalysisFilter {
+            only: vec![AnalysisScope::Negations],
+            category: None,
+        });
+        assert_eq!(negations.total_patterns, 2);
+        assert_eq!(negations.negation_patterns, 2);
+        assert!(!negations.has_conflicts());
+        
+        let conflicts = analysis.filter(&AnalysisFilter {
+            only: vec![AnalysisScope::Conflicts, AnalysisScope::Globstar],
+            category: None,
+        });
+        assert_eq!(conflicts.total_patterns, 3);
+        assert_eq!(conflicts.conflict_count(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_filter_analysis_by_category() {
+        let content = "node_modules/\n.vscode/\nrandom_file.txt";
+        let file = parse_gitignore(content).unwrap();
+        let analysis = analyze_gitignore(&file).unwrap();
+        
+        let filtered = analysis.filter(&AnalysisFilter {
+            only: Vec::new(),
+            category: Some("vscode".to_string()),
+        });
+        assert_eq!(filtered.total_patterns, 1);
+        assert_eq!(filtered.pattern_analyses[0].original, ".vscode/");
+    }
+
+    #[test]
+    fn test_empty_filter_keeps_everything() {
+        let file = parse_gitignore("*.log\n!*.log").unwrap();
+        let analysis = analyze_gitignore(&file).unwrap();
+        let filtered = analysis.filter(&AnalysisFilter::default());
+        
+        assert_eq!(filtered.total_patterns, analysis.total_patterns);
+        assert_eq!(filtered.conflict_count(), analysis.conflict_count());
+    }
+
+    #[test]
+    fn test_parse_analysis_scope() {
+        assert_eq!("conflicts".parse::<AnalysisScope>().unwrap(), AnalysisScope::Conflicts);
+        assert_eq!("Negations".parse::<AnalysisScope>().unwrap(), AnalysisScope::Negations);
+        assert!("bogus".parse::<AnalysisScope>().is_err());
+    }
+
     // Test cases from TEST_MATRIX.md
     #[test]
     fn test_tc01_exact_deduplication_optimization() {
@@ -550,4 +1498,19 @@ mod tests {
         assert_eq!(optimized.entries[0].original, "foo");
         assert_eq!(optimized.entries[1].original, "!foo");
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_optimizing_twice_changes_nothing() {
+        let content = "# Build\r\n*.log\r\n\r\nlogs/*.log\r\n\r\n\r\n!keep.log\r\nlogs/\r\n*.log\r\n";
+        let analyzer = PatternAnalyzer::safe();
+        let file = parse_gitignore(content).unwrap();
+
+        let (once, _) = optimize_gitignore_advanced(&file, &analyzer).unwrap();
+        let written = once.serialize(&WriteOptions::preserving(&file));
+        let reparsed = parse_gitignore(&written).unwrap();
+        let (twice, redundant) = optimize_gitignore_advanced(&reparsed, &analyzer).unwrap();
+
+        assert!(redundant.is_empty());
+        assert_eq!(twice.serialize(&WriteOptions::preserving(&reparsed)), written);
+    }
+}
@@ -0,0 +1,409 @@
+use crate::core::categorizer::{PatternCategorizer, PatternCategory};
+use crate::core::comment_generator::CommentGenerator;
+use crate::core::equivalence::{decision_changes, negation_probes};
+use crate::core::negations::MovedNegation;
+use crate::models::{is_keep_marker, EntryType, GitignoreEntry, GitignoreFile, GixError, Section};
+use crate::utils::patterns::GlobPattern;
+use std::collections::{HashMap, HashSet};
+
+/// Header of the trailing section holding negation patterns
+pub const EXCEPTIONS_HEADER: &str = "# Exceptions";
+
+/// Default order of sections by category kind, as named by `PatternCategory::kind_key`
+pub const DEFAULT_CATEGORY_ORDER: [&str; 6] = ["language", "framework", "tool", "os", "custom", "uncategorized"];
+
+/// Rewrite a gitignore file grouped into one section per category
+///
+/// Sections are ordered by category kind (languages, frameworks, tools, operating systems,
+/// custom, uncategorized) and then by name, and patterns keep their original relative order,
+/// so the output is the same on every run. A section keeps the header the file already gives
+/// its category (see `existing_headers`), and otherwise gets a generated one. Negations are moved to a final section, which is
+/// refused when a later pattern re-ignores what a negation re-included. Files with a
+/// `# gix:off` region are refused too, since regrouping would move its lines.
+pub fn organize_gitignore(
+    file: &GitignoreFile,
+    categorizer: &PatternCategorizer,
+    generator: &CommentGenerator,
+) -> Result<GitignoreFile, GixError> {
+    organize_gitignore_ordered(file, categorizer, generator, &[])
+}
+
+/// Rewrite a gitignore file grouped into one section per category, kinds listed in `order` first
+///
+/// `order` names category kinds as `PatternCategory::kind_key` does; kinds it leaves out follow
+/// in the default order.
+pub fn organize_gitignore_ordered(
+    file: &GitignoreFile,
+    categorizer: &PatternCategorizer,
+    generator: &CommentGenerator,
+    order: &[String],
+) -> Result<GitignoreFile, GixError> {
+    check_preserved_regions(file)?;
+    check_negations_can_move(file, &HashSet::new())?;
+    categorizer.prefetch(file.entries.iter().filter_map(GitignoreEntry::comparison_pattern));
+    let organized = organize_with_partners(file, categorizer, generator, order, &[]);
+
+    // The overlap check is conservative only one way, so confirm on the paths negations decide
+    let probes = negation_probes(&[file, &organized]);
+    if let Some(change) = decision_changes(file, &organized, &probes).first() {
+        return Err(GixError::UnsafeRewrite(format!("organizing would change whether '{}' is ignored", change.path)));
+    }
+    Ok(organized)
+}
+
+/// Rewrite a gitignore file grouped by category, each negation right after the pattern it re-includes paths from
+///
+/// Like `organize_gitignore_ordered`, except that a negation follows its partner, the latest
+/// earlier pattern it may re-include paths from, in that pattern's section. A pairing is kept
+/// only if it decides no path probing the file's negations (see `negation_probes`) differently
+/// from the original; negations left unpaired go to the exceptions section as usual.
+pub fn organize_gitignore_paired(
+    file: &GitignoreFile,
+    categorizer: &PatternCategorizer,
+    generator: &CommentGenerator,
+    order: &[String],
+) -> Result<(GitignoreFile, Vec<MovedNegation>), GixError> {
+    check_preserved_regions(file)?;
+    categorizer.prefetch(file.entries.iter().filter_map(GitignoreEntry::comparison_pattern));
+    let probes = negation_probes(&[file]);
+    let changed_paths = |paired: &[(&GitignoreEntry, &GitignoreEntry)]| -> HashSet<String> {
+        let organized = organize_with_partners(file, categorizer, generator, order, paired);
+        decision_changes(file, &organized, &probes).into_iter().map(|change| change.path).collect()
+    };
+
+    // Each pairing is tried on top of those kept so far, and kept if it changes no new path
+    let mut paired: Vec<(&GitignoreEntry, &GitignoreEntry)> = Vec::new();
+    let mut changed = changed_paths(&paired);
+    for candidate in negation_partners(file) {
+        paired.push(candidate);
+        let with_candidate = changed_paths(&paired);
+        if with_candidate.is_subset(&changed) {
+            changed = with_candidate;
+        } else {
+            paired.pop();
+        }
+    }
+
+    let paired_lines: HashSet<usize> = paired.iter().map(|(negation, _)| negation.line_number).collect();
+    check_negations_can_move(file, &paired_lines)?;
+    if let Some(path) = changed.iter().min() {
+        return Err(GixError::UnsafeRewrite(format!("organizing would change whether '{}' is ignored", path)));
+    }
+
+    let moved = paired.iter()
+        .map(|(negation, partner)| MovedNegation {
+            pattern: negation.comparison_pattern().unwrap_or_default(),
+            line_number: negation.line_number,
+            negates: partner.comparison_pattern().unwrap_or_default(),
+            negates_line: partner.line_number,
+        })
+        .collect();
+    Ok((organize_with_partners(file, categorizer, generator, order, &paired), moved))
+}
+
+/// Organize a file whose negations go after their paired pattern, or else to the exceptions section
+///
+/// `paired` holds negations with the first occurrence of the pattern each follows.
+fn organize_with_partners(
+    file: &GitignoreFile,
+    categorizer: &PatternCategorizer,
+    generator: &CommentGenerator,
+    order: &[String],
+    paired: &[(&GitignoreEntry, &GitignoreEntry)],
+) -> GitignoreFile {
+    let mut sections: HashMap<PatternCategory, Vec<&GitignoreEntry>> = HashMap::new();
+    let mut exceptions: Vec<&GitignoreEntry> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+
+    for entry in &file.entries {
+        let Some(pattern) = entry.comparison_pattern() else {
+            continue;
+        };
+        if !seen.insert(pattern.clone()) {
+            continue;
+        }
+
+        if pattern.starts_with('!') {
+            if !paired.iter().any(|(negation, _)| negation.line_number == entry.line_number) {
+                exceptions.push(entry);
+            }
+        } else {
+            let section = sections.entry(categorizer.section_category(&pattern)).or_default();
+            section.push(entry);
+            section.extend(paired.iter().filter(|(_, partner)| partner.line_number == entry.line_number).map(|(negation, _)| *negation));
+        }
+    }
+
+    let mut categories: Vec<PatternCategory> = sections.keys().cloned().collect();
+    categories.sort_by(|a, b| category_rank(a, order).cmp(&category_rank(b, order)).then_with(|| a.short_name().cmp(&b.short_name())));
+
+    let (mut headers, exceptions_header) = existing_headers(file, categorizer);
+    let mut lines: Vec<String> = Vec::new();
+    for category in &categories {
+        let header = headers.remove(category).unwrap_or_else(|| {
+            let mut header = vec![generator.generate_section_header(category)];
+            if let Some(description) = generator.generate_category_comment(category) {
+                header.push(format!("# {}", description));
+            }
+            header
+        });
+        push_section(&mut lines, header, &sections[category]);
+    }
+    if !exceptions.is_empty() {
+        let header = exceptions_header.unwrap_or_else(|| vec![EXCEPTIONS_HEADER.to_string()]);
+        push_section(&mut lines, header, &exceptions);
+    }
+
+    let mut organized = GitignoreFile::new();
+    for (index, line) in lines.into_iter().enumerate() {
+        let entry_type = if line.is_empty() {
+            EntryType::Blank
+        } else if line.starts_with('#') {
+            EntryType::Comment(line.clone())
+        } else {
+            EntryType::Pattern(line.clone())
+        };
+        organized.add_entry(GitignoreEntry::new(line, entry_type, index + 1));
+    }
+    organized
+}
+
+/// Refuse files with a `# gix:off` region, since regrouping would move its lines
+fn check_preserved_regions(file: &GitignoreFile) -> Result<(), GixError> {
+    match file.entries.iter().find(|entry| entry.preserve) {
+        Some(entry) => Err(GixError::UnsafeRewrite(format!(
+            "line {} starts a region marked `# gix:off`; organizing would move its lines",
+            entry.line_number
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Each negation with the first occurrence of the latest earlier pattern it may re-include paths from
+///
+/// Repeated negations are only listed once, as organizing keeps only their first occurrence.
+fn negation_partners(file: &GitignoreFile) -> Vec<(&GitignoreEntry, &GitignoreEntry)> {
+    let patterns: Vec<(&GitignoreEntry, String, GlobPattern)> = file.entries.iter()
+        .filter_map(|entry| {
+            let pattern = entry.comparison_pattern()?;
+            let glob = GlobPattern::parse(&pattern);
+            Some((entry, pattern, glob))
+        })
+        .collect();
+    let first_of = |pattern: &str| patterns.iter().find(|(_, kept, _)| kept == pattern).map(|(entry, _, _)| *entry);
+    let mut seen: HashSet<&str> = HashSet::new();
+
+    patterns.iter()
+        .enumerate()
+        .filter(|(_, (_, pattern, glob))| glob.negated && seen.insert(pattern))
+        .filter_map(|(index, (negation, _, glob))| {
+            let (_, partner, _) = patterns[..index].iter().rev().find(|(_, _, earlier)| !earlier.negated && earlier.may_overlap(glob))?;
+            Some((*negation, first_of(partner)?))
+        })
+        .collect()
+}
+
+/// Header lines of the file's sections, by the category most of their patterns fall into
+///
+/// The first section to claim a category keeps it, ties going to the category seen first. A
+/// section of nothing but negations lends its header to the exceptions, returned separately.
+/// Sections without a header, or headed by a `# gix:keep` marker, lend nothing.
+fn existing_headers(file: &GitignoreFile, categorizer: &PatternCategorizer) -> (HashMap<PatternCategory, Vec<String>>, Option<Vec<String>>) {
+    let mut headers: HashMap<PatternCategory, Vec<String>> = HashMap::new();
+    let mut exceptions_header = None;
+    for section in file.sections() {
+        if section.header.is_empty() || section.header.iter().any(|entry| is_keep_marker(&entry.original)) {
+            continue;
+        }
+        let header = || section.header.iter().map(|entry| entry.original.clone()).collect::<Vec<_>>();
+        if is_exceptions_section(&section) {
+            exceptions_header.get_or_insert_with(header);
+            continue;
+        }
+        if let Some(category) = section_category(&section, categorizer) {
+            headers.entry(category).or_insert_with(header);
+        }
+    }
+    (headers, exceptions_header)
+}
+
+/// Check if a section holds patterns, all of them negations
+pub(crate) fn is_exceptions_section(section: &Section) -> bool {
+    let mut patterns = section.patterns().filter_map(GitignoreEntry::comparison_pattern).peekable();
+    patterns.peek().is_some() && patterns.all(|pattern| pattern.starts_with('!'))
+}
+
+/// The category most of a section's patterns fall into, ties going to the category seen first
+///
+/// Negations are not counted; `None` means the section has no other pattern.
+pub(crate) fn section_category(section: &Section, categorizer: &PatternCategorizer) -> Option<PatternCategory> {
+    let mut counts: Vec<(PatternCategory, usize)> = Vec::new();
+    for pattern in section.patterns().filter_map(GitignoreEntry::comparison_pattern).filter(|pattern| !pattern.starts_with('!')) {
+        let category = categorizer.section_category(&pattern);
+        match counts.iter_mut().find(|(counted, _)| *counted == category) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((category, 1)),
+        }
+    }
+    // `max_by_key` keeps the last of equal counts, so look from the end to prefer the first
+    counts.into_iter().rev().max_by_key(|(_, count)| *count).map(|(category, _)| category)
+}
+
+/// Append a section, separated from the previous one by a blank line
+fn push_section(lines: &mut Vec<String>, header: Vec<String>, entries: &[&GitignoreEntry]) {
+    if !lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines.extend(header);
+    lines.extend(entries.iter().map(|entry| entry.original.clone()));
+}
+
+/// Position of a category kind in the organized file
+pub fn category_rank(category: &PatternCategory, order: &[String]) -> usize {
+    let kind = category.kind_key();
+    let default_rank = DEFAULT_CATEGORY_ORDER.iter().position(|default| *default == kind).unwrap_or(DEFAULT_CATEGORY_ORDER.len());
+    order.iter().position(|listed| listed == kind).unwrap_or(order.len() + default_rank)
+}
+
+/// Moving a negation below later patterns is only safe if none of them can match what it re-includes
+///
+/// Negations on the lines in `skip` are not checked.
+fn check_negations_can_move(file: &GitignoreFile, skip: &HashSet<usize>) -> Result<(), GixError> {
+    let patterns: Vec<(usize, String, GlobPattern)> = file.entries.iter()
+        .filter_map(|entry| {
+            let pattern = entry.comparison_pattern()?;
+            let glob = GlobPattern::parse(&pattern);
+            Some((entry.line_number, pattern, glob))
+        })
+        .collect();
+
+    for (i, (negation_line, negation, negation_glob)) in patterns.iter().enumerate() {
+        if !negation_glob.negated || skip.contains(negation_line) {
+            continue;
+        }
+
+        let blocker = patterns[i + 1..].iter().find(|(_, _, later)| {
+            !later.negated && (later.may_overlap(negation_glob) || later.covers_parent_of(negation_glob))
+        });
+        if let Some((line_number, pattern, _)) = blocker {
+            return Err(GixError::UnsafeRewrite(format!(
+                "line {} ({}) re-ignores paths re-included by line {} ({}); grouping would change what is ignored",
+                line_number, pattern, negation_line, negation
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    fn organize(content: &str) -> Result<String, GixError> {
+        let file = parse_gitignore(content)?;
+        let organized = organize_gitignore(&file, &PatternCategorizer::new(), &CommentGenerator::new())?;
+        Ok(format!("{}", organized))
+    }
+
+    #[test]
+    fn test_organize_groups_by_category() {
+        let output = organize("custom/\n*.log\n!keep.log\ncustom/\nlocal/").unwrap();
+
+        assert!(output.starts_with("# "));
+        assert!(output.ends_with("\n\n# Exceptions\n!keep.log"));
+        assert_eq!(output.matches("custom/").count(), 1);
+        assert!(output.find("custom/").unwrap() < output.find("local/").unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_organize_keeps_existing_headers() {
+        let output = organize("# Python caches, do not commit\n__pycache__/\n\n# Editors\n.vscode/\n.venv/\n.pytest_cache/\n\n# Keep\n!keep.log").unwrap();
+
+        assert!(output.contains("# Python caches, do not commit\n__pycache__/\n.venv/\n.pytest_cache/"), "{}", output);
+        assert!(output.ends_with("\n\n# Keep\n!keep.log"));
+        assert!(!output.contains("# Editors"));
+        assert_eq!(organize(&output).unwrap(), output);
+    }
+
+    #[test]
+    fn test_organize_refuses_negations_re_ignored_in_directories() {
+        // `logs/b` is re-included by the negation and ignored again by the last line
+        for content in ["logs/*\n!b\nlogs/*", "**/*/?\n!b\n**/*/?"] {
+            assert!(matches!(organize(content), Err(GixError::UnsafeRewrite(_))), "{}", content);
+        }
+    }
+
+    #[test]
+    fn test_organize_is_deterministic() {
+        let content = ".DS_Store\n*.pyc\nnode_modules/\n.vscode/\nbuild/\nThumbs.db\n*.log";
+        let first = organize(content).unwrap();
+        for _ in 0..5 {
+            assert_eq!(organize(content).unwrap(), first);
+        }
+        assert_eq!(organize(&first).unwrap(), first);
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_organize_in_configured_order() {
+        let file = parse_gitignore("node_modules/\n.vscode/\nThumbs.db").unwrap();
+        let order = |kinds: &[&str]| {
+            let order: Vec<String> = kinds.iter().map(|kind| kind.to_string()).collect();
+            let organized = organize_gitignore_ordered(&file, &PatternCategorizer::new(), &CommentGenerator::new(), &order).unwrap();
+            organized.entries.iter().filter(|entry| entry.is_pattern()).map(|entry| entry.original.clone()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(order(&[]), vec!["node_modules/", ".vscode/", "Thumbs.db"]);
+        assert_eq!(order(&["os", "tool"]), vec!["Thumbs.db", ".vscode/", "node_modules/"]);
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_organize_groups_shared_patterns() {
+        let output = organize("*.pyc\nbuild/\nnode_modules/\ntarget/\n").unwrap();
+
+        assert!(output.contains("# Common\nbuild/\ntarget/"), "{}", output);
+        assert!(output.contains("# Python\n# Python language files\n*.pyc"), "{}", output);
+        assert!(!output.contains("# Java"));
+    }
+
+    #[test]
+    fn test_organize_refuses_to_move_overridden_negation() {
+        let result = organize("!keep.log\n*.log");
+        assert!(matches!(result, Err(GixError::UnsafeRewrite(_))));
+    }
+
+    #[test]
+    fn test_organize_pairs_negations_with_their_patterns() {
+        let file = parse_gitignore("notes.txt\n*.pyc\nbuild/\n!debug.pyc\n").unwrap();
+        let (organized, moved) = organize_gitignore_paired(&file, &PatternCategorizer::new(), &CommentGenerator::new(), &[]).unwrap();
+        let output = format!("{}", organized);
+
+        assert!(output.contains("*.pyc\n!debug.pyc"), "{}", output);
+        assert!(!output.contains(EXCEPTIONS_HEADER));
+        assert_eq!((moved[0].line_number, moved[0].negates_line), (4, 2));
+        assert!(matches!(organize("notes.txt\n*.pyc\nbuild/\n!debug.pyc\n"), Ok(output) if output.ends_with("# Exceptions\n!debug.pyc")));
+    }
+
+    #[test]
+    fn test_organize_leaves_unsafe_pairs_apart() {
+        // Pairing `!debug.log` with the first `*.log` would stop the second from re-ignoring debug.log
+        let file = parse_gitignore("*.log\n!debug.log\n*.log\n").unwrap();
+        let result = organize_gitignore_paired(&file, &PatternCategorizer::new(), &CommentGenerator::new(), &[]);
+        assert!(matches!(result, Err(GixError::UnsafeRewrite(_))));
+    }
+
+    #[test]
+    fn test_organize_refuses_preserved_region() {
+        let result = organize("*.log\n# gix:off\nbuild/\n# gix:on");
+        assert!(matches!(result, Err(GixError::UnsafeRewrite(message)) if message.starts_with("line 2")));
+    }
+
+    #[test]
+    fn test_organize_empty_file() {
+        assert_eq!(organize("# nothing here\n").unwrap(), "");
+    }
+}
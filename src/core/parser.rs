@@ -1,19 +1,144 @@
-use crate::models::{GitignoreEntry, GitignoreFile, EntryType, GixError};
+use crate::models::{GitignoreEntry, GitignoreFile, EntryType, GixError, LineEnding, UTF8_BOM};
+use clap::ValueEnum;
+use std::path::Path;
+
+/// Directive starting a region gix leaves exactly as written
+pub const PRESERVE_OFF: &str = "# gix:off";
+
+/// Directive ending a region started by `PRESERVE_OFF`
+pub const PRESERVE_ON: &str = "# gix:on";
+
+/// Files npm never packs, whatever `.npmignore` says
+const NPM_ALWAYS_IGNORED: &[&str] = &[
+    ".git", "CVS", ".svn", ".hg", ".lock-wscript", ".wafpickle-*", ".*.swp", ".DS_Store", "._*",
+    "npm-debug.log", ".npmrc", "node_modules", "config.gypi", "*.orig", "package-lock.json",
+];
+
+/// The syntax of an ignore file
+///
+/// npm, ESLint and Prettier read their ignore files with gitignore rules; Docker matches every
+/// pattern from the build context root, ignores whitespace around patterns and cleans their
+/// paths (`/build/` and `./build` are both `build`), and has no inline comments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Dialect {
+    /// .gitignore, .git/info/exclude and the global excludes file
+    #[default]
+    Gitignore,
+    /// .dockerignore
+    Dockerignore,
+    /// .npmignore
+    Npmignore,
+    /// .eslintignore
+    Eslintignore,
+    /// .prettierignore
+    Prettierignore,
+}
+
+impl Dialect {
+    /// Pick the dialect from a file's name, falling back to gitignore
+    ///
+    /// Besides the usual names, `Dockerfile.dockerignore` style names are dockerignore.
+    pub fn from_path(path: &Path) -> Self {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        match name {
+            ".npmignore" => Dialect::Npmignore,
+            ".eslintignore" => Dialect::Eslintignore,
+            ".prettierignore" => Dialect::Prettierignore,
+            _ if name.ends_with(".dockerignore") => Dialect::Dockerignore,
+            _ => Dialect::Gitignore,
+        }
+    }
+
+    /// Get the name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dialect::Gitignore => "gitignore",
+            Dialect::Dockerignore => "dockerignore",
+            Dialect::Npmignore => "npmignore",
+            Dialect::Eslintignore => "eslintignore",
+            Dialect::Prettierignore => "prettierignore",
+        }
+    }
+
+    /// Check if patterns match the way gitignore patterns do, relative to the file and at any depth
+    pub fn matches_like_gitignore(&self) -> bool {
+        *self != Dialect::Dockerignore
+    }
+
+    /// Patterns the tool ignores whatever the file says, so copies in the file never matter
+    pub fn always_ignored(&self) -> Vec<String> {
+        match self {
+            Dialect::Npmignore => spellings(NPM_ALWAYS_IGNORED),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Patterns the tool ignores unless the file re-includes them with a negation
+    pub fn ignored_by_default(&self) -> Vec<String> {
+        match self {
+            Dialect::Eslintignore | Dialect::Prettierignore => spellings(&["node_modules"]),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Write each unanchored name the ways a file may spell it: `name`, `name/`, `/name` and `/name/`
+fn spellings(names: &[&str]) -> Vec<String> {
+    names.iter()
+        .flat_map(|name| [name.to_string(), format!("{}/", name), format!("/{}", name), format!("/{}/", name)])
+        .collect()
+}
 
 /// Parse a .gitignore file content into a structured representation
+///
+/// A leading UTF-8 byte order mark is not part of the first line; it is recorded in `has_bom`,
+/// and the line ending and trailing newline in `line_ending` and `trailing_newline`. Entries
+/// from a `# gix:off` line through the next `# gix:on` line (or the end of the file) are
+/// marked `preserve`.
 pub fn parse_gitignore(content: &str) -> Result<GitignoreFile, GixError> {
+    parse_ignore_file(content, Dialect::Gitignore)
+}
+
+/// Parse the content of an ignore file written in `dialect`
+///
+/// Entries keep the lines as written in `original`; for dockerignore, the pattern compared
+/// with others is the cleaned one Docker matches.
+pub fn parse_ignore_file(content: &str, dialect: Dialect) -> Result<GitignoreFile, GixError> {
     let mut file = GitignoreFile::new();
+    let content = match content.strip_prefix(UTF8_BOM) {
+        Some(rest) => {
+            file.has_bom = true;
+            rest
+        }
+        None => content,
+    };
+    file.line_ending = LineEnding::detect(content);
+    file.trailing_newline = content.ends_with('\n');
     
+    let mut preserving = false;
     for (line_number, line) in content.lines().enumerate() {
-        let entry = parse_line(line, line_number + 1)?;
+        let mut entry = parse_line(line, line_number + 1, dialect)?;
+        if is_directive(&entry, PRESERVE_OFF) {
+            preserving = true;
+        }
+        entry.preserve = preserving;
+        if is_directive(&entry, PRESERVE_ON) {
+            preserving = false;
+        }
         file.add_entry(entry);
     }
     
     Ok(file)
 }
 
-/// Parse a single line from a .gitignore file
-fn parse_line(line: &str, line_number: usize) -> Result<GitignoreEntry, GixError> {
+/// Check if an entry is the given directive comment, ignoring spacing after the `#`
+fn is_directive(entry: &GitignoreEntry, directive: &str) -> bool {
+    let name = |comment: &str| comment.trim_start_matches('#').trim().to_string();
+    matches!(&entry.entry_type, EntryType::Comment(comment) if name(comment) == name(directive))
+}
+
+/// Parse a single line from an ignore file
+fn parse_line(line: &str, line_number: usize, dialect: Dialect) -> Result<GitignoreEntry, GixError> {
     let original = line.to_string();
     
     // Handle blank lines
@@ -26,20 +151,41 @@ fn parse_line(line: &str, line_number: usize) -> Result<GitignoreEntry, GixError
         return Ok(GitignoreEntry::new(original.clone(), EntryType::Comment(original.clone()), line_number));
     }
     
+    // Docker has no inline comments and matches the cleaned path
+    if dialect == Dialect::Dockerignore {
+        return Ok(GitignoreEntry::new(original, EntryType::Pattern(clean_docker_pattern(line)), line_number));
+    }
+    
     // Handle patterns (everything else)
     // Remove inline comments (everything after # that's not escaped)
     let pattern = remove_inline_comment(line);
+    let inline_comment = (pattern.len() < line.len()).then(|| line[pattern.len()..].to_string());
     
-    Ok(GitignoreEntry::new(original, EntryType::Pattern(pattern), line_number))
+    let mut entry = GitignoreEntry::new(original, EntryType::Pattern(pattern), line_number);
+    entry.inline_comment = inline_comment;
+    Ok(entry)
+}
+
+/// Clean a `.dockerignore` pattern as Docker does: trim it and drop empty and `.` path segments
+fn clean_docker_pattern(line: &str) -> String {
+    let line = line.trim();
+    let (negation, body) = match line.strip_prefix('!') {
+        Some(body) => ("!", body.trim()),
+        None => ("", line),
+    };
+    let segments: Vec<&str> = body.split('/').filter(|segment| !segment.is_empty() && *segment != ".").collect();
+    match segments.is_empty() {
+        true => line.to_string(),
+        false => format!("{}{}", negation, segments.join("/")),
+    }
 }
 
 /// Remove inline comments from a pattern line
 fn remove_inline_comment(line: &str) -> String {
     let mut result = String::new();
-    let mut chars = line.chars().peekable();
     let mut escaped = false;
     
-    while let Some(ch) = chars.next() {
+    for ch in line.chars() {
         if escaped {
             result.push(ch);
             escaped = false;
@@ -63,28 +209,28 @@ mod tests {
 
     #[test]
     fn test_parse_blank_line() {
-        let entry = parse_line("", 1).unwrap();
+        let entry = parse_line("", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_blank());
         assert_eq!(entry.original, "");
     }
 
     #[test]
     fn test_parse_whitespace_line() {
-        let entry = parse_line("   ", 1).unwrap();
+        let entry = parse_line("   ", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_blank());
         assert_eq!(entry.original, "   ");
     }
 
     #[test]
     fn test_parse_comment_line() {
-        let entry = parse_line("# This is a comment", 1).unwrap();
+        let entry = parse_line("# This is a comment", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_comment());
         assert_eq!(entry.original, "# This is a comment");
     }
 
     #[test]
     fn test_parse_pattern_line() {
-        let entry = parse_line("*.log", 1).unwrap();
+        let entry = parse_line("*.log", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_pattern());
         assert_eq!(entry.original, "*.log");
         assert_eq!(entry.normalized_pattern(), Some("*.log".to_string()));
@@ -92,7 +238,7 @@ mod tests {
 
     #[test]
     fn test_parse_negation_pattern() {
-        let entry = parse_line("!debug.log", 1).unwrap();
+        let entry = parse_line("!debug.log", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_pattern());
         assert_eq!(entry.original, "!debug.log");
         assert_eq!(entry.normalized_pattern(), Some("!debug.log".to_string()));
@@ -100,7 +246,7 @@ mod tests {
 
     #[test]
     fn test_parse_escaped_hash() {
-        let entry = parse_line("\\#notacomment", 1).unwrap();
+        let entry = parse_line("\\#notacomment", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_pattern());
         assert_eq!(entry.original, "\\#notacomment");
         assert_eq!(entry.normalized_pattern(), Some("\\#notacomment".to_string()));
@@ -108,7 +254,7 @@ mod tests {
 
     #[test]
     fn test_parse_escaped_negation() {
-        let entry = parse_line("\\!notnegation", 1).unwrap();
+        let entry = parse_line("\\!notnegation", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_pattern());
         assert_eq!(entry.original, "\\!notnegation");
         assert_eq!(entry.normalized_pattern(), Some("\\!notnegation".to_string()));
@@ -116,23 +262,34 @@ mod tests {
 
     #[test]
     fn test_parse_inline_comment() {
-        let entry = parse_line("*.log # inline comment", 1).unwrap();
+        let entry = parse_line("*.log # inline comment", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_pattern());
         assert_eq!(entry.original, "*.log # inline comment");
         assert_eq!(entry.normalized_pattern(), Some("*.log ".to_string()));
+        assert_eq!(entry.inline_comment, Some("# inline comment".to_string()));
+        assert_eq!(entry.comparison_pattern(), Some("*.log".to_string()));
+    }
+
+    #[test]
+    fn test_parse_inline_comment_after_tab() {
+        let entry = parse_line("*.log\t# inline", 1, Dialect::Gitignore).unwrap();
+        assert_eq!(entry.normalized_pattern(), Some("*.log\t".to_string()));
+        assert_eq!(entry.inline_comment, Some("# inline".to_string()));
+        assert_eq!(entry.comparison_pattern(), Some("*.log".to_string()));
     }
 
     #[test]
     fn test_parse_escaped_inline_comment() {
-        let entry = parse_line("*.log \\# not a comment", 1).unwrap();
+        let entry = parse_line("*.log \\# not a comment", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_pattern());
         assert_eq!(entry.original, "*.log \\# not a comment");
         assert_eq!(entry.normalized_pattern(), Some("*.log \\# not a comment".to_string()));
+        assert_eq!(entry.inline_comment, None);
     }
 
     #[test]
     fn test_parse_unicode_pattern() {
-        let entry = parse_line("Данные/", 1).unwrap();
+        let entry = parse_line("Данные/", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_pattern());
         assert_eq!(entry.original, "Данные/");
         assert_eq!(entry.normalized_pattern(), Some("Данные/".to_string()));
@@ -140,11 +297,30 @@ mod tests {
 
     #[test]
     fn test_parse_emoji_comment() {
-        let entry = parse_line("# 📝", 1).unwrap();
+        let entry = parse_line("# 📝", 1, Dialect::Gitignore).unwrap();
         assert!(entry.is_comment());
         assert_eq!(entry.original, "# 📝");
     }
 
+    #[test]
+    fn test_dialect_from_path() {
+        assert_eq!(Dialect::from_path(Path::new("web/.npmignore")), Dialect::Npmignore);
+        assert_eq!(Dialect::from_path(Path::new("Dockerfile.dockerignore")), Dialect::Dockerignore);
+        assert_eq!(Dialect::from_path(Path::new(".prettierignore")), Dialect::Prettierignore);
+        assert_eq!(Dialect::from_path(Path::new(".git/info/exclude")), Dialect::Gitignore);
+        assert!(Dialect::Npmignore.always_ignored().contains(&"/node_modules/".to_string()));
+    }
+
+    #[test]
+    fn test_parse_dockerignore() {
+        let file = parse_ignore_file("/build/\n./build\n  *.log # not a comment\n!/keep\n# comment\n", Dialect::Dockerignore).unwrap();
+        let patterns: Vec<String> = file.entries.iter().filter_map(|entry| entry.comparison_pattern()).collect();
+        assert_eq!(patterns, vec!["build", "build", "*.log # not a comment", "!keep"]);
+        assert_eq!(file.entries[2].original, "  *.log # not a comment");
+        assert_eq!(file.find_duplicates().get("build").unwrap().line_numbers, vec![1, 2]);
+        assert_eq!(file.stats.comment_lines, 1);
+    }
+
     #[test]
     fn test_parse_complete_file() {
         let content = "*.log\n# Logs\n*.log\n\nbuild/";
@@ -164,7 +340,7 @@ mod tests {
         
         let duplicates = file.find_duplicates();
         assert_eq!(duplicates.len(), 1);
-        assert_eq!(duplicates["*.log"], vec![1, 2]);
+        assert_eq!(duplicates.get("*.log").unwrap().line_numbers, vec![1, 2]);
     }
 
     #[test]
@@ -250,4 +426,42 @@ mod tests {
         assert_eq!(comments[0].original, "# 📝");
         assert_eq!(patterns.len(), 2);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_bom_before_comment() {
+        let file = parse_gitignore("\u{feff}# Logs\n*.log\n*.log").unwrap();
+        
+        assert!(file.has_bom);
+        assert!(file.entries[0].is_comment());
+        assert_eq!(file.entries[0].original, "# Logs");
+        assert_eq!(file.stats.comment_lines, 1);
+        assert_eq!(file.stats.pattern_lines, 2);
+        assert_eq!(file.find_duplicates().redundant_lines(), 1);
+    }
+
+    #[test]
+    fn test_bom_before_pattern() {
+        let file = parse_gitignore("\u{feff}*.log\n*.log").unwrap();
+        
+        assert!(file.has_bom);
+        assert_eq!(file.entries[0].entry_type, EntryType::Pattern("*.log".to_string()));
+        assert_eq!(file.find_duplicates().redundant_lines(), 1);
+    }
+
+    #[test]
+    fn test_bom_only_file() {
+        let file = parse_gitignore("\u{feff}").unwrap();
+        
+        assert!(file.has_bom);
+        assert!(file.entries.is_empty());
+        assert!(!parse_gitignore("*.log").unwrap().has_bom);
+    }
+
+    #[test]
+    fn test_preserved_regions() {
+        let file = parse_gitignore("*.log\n# gix:off\n!a.log\n*.log\n#gix:on\nbuild/\n# gix:off\ntmp/").unwrap();
+        let preserved: Vec<bool> = file.entries.iter().map(|entry| entry.preserve).collect();
+        
+        assert_eq!(preserved, vec![false, true, true, true, true, false, true, true]);
+    }
+}
@@ -1,5 +1,8 @@
+use crate::utils::patterns::{trim_unescaped_trailing_whitespace, GlobPattern};
+use crate::utils::tokens::{render, tokenize, Segment, Token};
 use std::collections::HashMap;
 
+
 /// Represents the type of a gitignore pattern
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PatternType {
@@ -34,6 +37,9 @@ pub struct PatternAnalysis {
     pub matches_directories: bool,
     /// Whether this pattern is case sensitive
     pub is_case_sensitive: bool,
+    /// Tokens of the base pattern, one segment per path component; a leading or
+    /// trailing slash shows up as an empty first or last segment
+    pub segments: Vec<Segment>,
 }
 
 impl PatternAnalysis {
@@ -42,16 +48,19 @@ impl PatternAnalysis {
         let is_negation = normalized.starts_with('!');
         let pattern = if is_negation { &normalized[1..] } else { &normalized };
         
-        let is_absolute = pattern.starts_with('/');
-        let has_wildcards = pattern.contains('*') || pattern.contains('?') || pattern.contains('[');
-        let has_globstar = pattern.contains("**");
+        let segments = tokenize(trim_unescaped_trailing_whitespace(pattern));
+        let tokens = || segments.iter().flat_map(|segment| &segment.tokens);
+        
+        let is_absolute = segments.len() > 1 && segments[0].is_empty();
+        let has_wildcards = tokens().any(Token::is_wildcard);
+        let has_globstar = segments.iter().any(Segment::is_globstar);
         // Gitignore patterns are case-sensitive by default
         let is_case_sensitive = true;
         
         // Determine pattern type
-        let pattern_type = if pattern.ends_with('/') {
+        let pattern_type = if segments.len() > 1 && segments.last().is_some_and(Segment::is_empty) {
             PatternType::Directory
-        } else if has_wildcards || pattern.contains('.') {
+        } else if has_wildcards || tokens().any(|token| token.literal() == Some('.')) {
             PatternType::Both
         } else {
             PatternType::File
@@ -71,6 +80,7 @@ impl PatternAnalysis {
             matches_files,
             matches_directories,
             is_case_sensitive,
+            segments,
         }
     }
     
@@ -83,44 +93,37 @@ impl PatternAnalysis {
         }
     }
     
+    /// Compile the pattern for matching, without re-reading its text
+    pub fn glob(&self) -> GlobPattern {
+        GlobPattern::from_segments(self.is_negation, self.segments.clone())
+    }
+    
     /// Check if this pattern could potentially conflict with another
     pub fn could_conflict_with(&self, other: &PatternAnalysis) -> bool {
         // Negation patterns can conflict with non-negation patterns
         if self.is_negation != other.is_negation {
-            let base1 = self.base_pattern();
-            let base2 = other.base_pattern();
-            
-            // Check if base patterns are equivalent
-            self.are_base_patterns_equivalent(base1, base2)
+            // Check if the base patterns can match a common path
+            self.has_equivalent_base(other) || self.glob().may_overlap(&other.glob())
         } else {
             false
         }
     }
     
-    /// Check if two base patterns are functionally equivalent
-    fn are_base_patterns_equivalent(&self, pattern1: &str, pattern2: &str) -> bool {
-        // Exact match
-        if pattern1 == pattern2 {
-            return true;
-        }
-        
-        // Handle trailing slash differences
-        if pattern1.ends_with('/') && pattern2 == &pattern1[..pattern1.len()-1] {
-            return true;
-        }
-        if pattern2.ends_with('/') && pattern1 == &pattern2[..pattern2.len()-1] {
+    /// Check if the base patterns are the same up to one leading or one trailing slash
+    fn has_equivalent_base(&self, other: &PatternAnalysis) -> bool {
+        let (first, second) = (&self.segments[..], &other.segments[..]);
+        if first == second {
             return true;
         }
         
-        // Handle leading slash differences for relative patterns
-        if pattern1.starts_with('/') && pattern2 == &pattern1[1..] {
-            return true;
-        }
-        if pattern2.starts_with('/') && pattern1 == &pattern2[1..] {
-            return true;
+        let (longer, shorter) = if first.len() > second.len() { (first, second) } else { (second, first) };
+        if longer.len() != shorter.len() + 1 || shorter.iter().all(Segment::is_empty) {
+            return false;
         }
         
-        false
+        // Handle trailing slash differences, then leading slash differences for relative patterns
+        (longer.last().is_some_and(Segment::is_empty) && &longer[..shorter.len()] == shorter)
+            || (longer[0].is_empty() && &longer[1..] == shorter)
     }
 }
 
@@ -130,6 +133,9 @@ pub struct PatternAnalyzer {
     pub normalize_patterns: bool,
     /// Whether to detect case-insensitive patterns
     pub case_sensitive: bool,
+    /// Whether deduplication may merge heuristically equivalent spellings
+    /// (`build`/`build/`, `/build`/`build`, `**/build`/`build`)
+    pub merge_equivalents: bool,
 }
 
 impl Default for PatternAnalyzer {
@@ -137,6 +143,7 @@ impl Default for PatternAnalyzer {
         Self {
             normalize_patterns: true,
             case_sensitive: true,
+            merge_equivalents: false,
         }
     }
 }
@@ -147,10 +154,25 @@ impl PatternAnalyzer {
         Self {
             normalize_patterns,
             case_sensitive,
+            merge_equivalents: false,
+        }
+    }
+    
+    /// Create an analyzer that only treats byte-identical patterns as duplicates,
+    /// so deduplication can never change which files are ignored
+    pub fn safe() -> Self {
+        Self::new(false, true)
+    }
+    
+    /// Create an analyzer that also merges heuristically equivalent patterns
+    pub fn heuristic() -> Self {
+        Self {
+            merge_equivalents: true,
+            ..Self::default()
         }
     }
     
-    /// Normalize a pattern by removing trailing spaces and handling separators
+    /// Normalize a pattern by removing unescaped trailing spaces and handling separators
     pub fn normalize_pattern(&self, pattern: &str) -> String {
         if !self.normalize_patterns {
             return pattern.to_string();
@@ -158,8 +180,8 @@ impl PatternAnalyzer {
         
         let mut normalized = pattern.to_string();
         
-        // Remove trailing spaces
-        normalized = normalized.trim_end().to_string();
+        // Remove trailing spaces, which git ignores unless escaped with a backslash
+        normalized = trim_unescaped_trailing_whitespace(&normalized).to_string();
         
         // Normalize path separators (convert backslashes to forward slashes)
         if cfg!(windows) {
@@ -189,6 +211,47 @@ impl PatternAnalyzer {
         result
     }
     
+    /// Normalize a pattern for deduplication, keeping trailing whitespace significant
+    ///
+    /// A case-insensitive analyzer lowercases the key, so `build/` and `BUILD/` are duplicates.
+    pub fn dedup_key(&self, pattern: &str) -> String {
+        let trimmed = pattern.trim_end();
+        let mut key = if self.merge_equivalents {
+            self.equivalence_key(trimmed)
+        } else {
+            self.normalize_pattern(trimmed)
+        };
+        if !self.case_sensitive {
+            key = key.to_lowercase();
+        }
+        key.push_str(&pattern[trimmed.len()..]);
+        key
+    }
+    
+    /// Reduce a pattern to its heuristic equivalence class
+    ///
+    /// Spellings that usually mean the same thing share a key even though git does not
+    /// always treat them identically: `build` and `build/`, `/build` and `build`, and
+    /// `**/build` and `build`. Negation is preserved.
+    pub fn equivalence_key(&self, pattern: &str) -> String {
+        let analysis = self.analyze_pattern(pattern);
+        let negation = if analysis.is_negation { "!" } else { "" };
+        
+        let mut segments = &analysis.segments[..];
+        let has_rest = |rest: &[Segment]| !rest.iter().all(Segment::is_empty);
+        if segments.len() > 1 && segments[0].is_globstar() && has_rest(&segments[1..]) {
+            segments = &segments[1..];
+        }
+        if segments.len() > 1 && segments[0].is_empty() && has_rest(&segments[1..]) {
+            segments = &segments[1..];
+        }
+        if segments.len() > 1 && segments[segments.len() - 1].is_empty() && has_rest(&segments[..segments.len() - 1]) {
+            segments = &segments[..segments.len() - 1];
+        }
+        
+        format!("{}{}", negation, render(segments))
+    }
+    
     /// Analyze a pattern and return detailed analysis
     pub fn analyze_pattern(&self, pattern: &str) -> PatternAnalysis {
         let normalized = self.normalize_pattern(pattern);
@@ -206,7 +269,7 @@ impl PatternAnalyzer {
         }
         
         // Check if they're functionally equivalent
-        analysis1.are_base_patterns_equivalent(analysis1.base_pattern(), analysis2.base_pattern())
+        analysis1.has_equivalent_base(&analysis2)
     }
     
     /// Check if two patterns conflict (one negates the other)
@@ -219,17 +282,49 @@ impl PatternAnalyzer {
     
     /// Find all conflicts in a set of patterns
     pub fn find_conflicts(&self, patterns: &[String]) -> Vec<(String, String)> {
-        let mut conflicts = Vec::new();
-        
-        for (i, pattern1) in patterns.iter().enumerate() {
-            for pattern2 in patterns.iter().skip(i + 1) {
-                if self.are_conflicting(pattern1, pattern2) {
-                    conflicts.push((pattern1.clone(), pattern2.clone()));
+        self.conflicting_pairs(patterns)
+            .into_iter()
+            .map(|(i, j)| (patterns[i].clone(), patterns[j].clone()))
+            .collect()
+    }
+    
+    /// Find the index pairs `(i, j)`, `i < j`, of conflicting patterns, sorted
+    ///
+    /// Only a negation and a plain pattern can conflict, and patterns with the same normalized
+    /// text conflict with the same others, so each distinct negation is compared with each
+    /// distinct plain pattern once. With the `parallel` feature the negations are spread
+    /// over threads.
+    pub fn conflicting_pairs<S: AsRef<str> + Sync>(&self, patterns: &[S]) -> Vec<(usize, usize)> {
+        let mut buckets: Vec<(PatternAnalysis, GlobPattern, Vec<usize>)> = Vec::new();
+        let mut bucket_of: HashMap<String, usize> = HashMap::new();
+        for (index, pattern) in patterns.iter().enumerate() {
+            let analysis = self.analyze_pattern(pattern.as_ref());
+            match bucket_of.get(&analysis.normalized) {
+                Some(&bucket) => buckets[bucket].2.push(index),
+                None => {
+                    bucket_of.insert(analysis.normalized.clone(), buckets.len());
+                    let glob = analysis.glob();
+                    buckets.push((analysis, glob, vec![index]));
                 }
             }
         }
+        let (negations, plain): (Vec<_>, Vec<_>) = buckets.iter().partition(|(analysis, _, _)| analysis.is_negation);
         
-        conflicts
+        let conflicts_of = |(negation, negation_glob, negation_lines): &&(PatternAnalysis, GlobPattern, Vec<usize>)| {
+            plain.iter()
+                .filter(|(analysis, glob, _)| negation.has_equivalent_base(analysis) || negation_glob.may_overlap(glob))
+                .flat_map(|(_, _, lines)| lines.iter().flat_map(|&line| negation_lines.iter().map(move |&other| (line.min(other), line.max(other)))))
+                .collect::<Vec<_>>()
+        };
+        #[cfg(feature = "parallel")]
+        let mut pairs: Vec<(usize, usize)> = {
+            use rayon::prelude::*;
+            negations.par_iter().flat_map_iter(conflicts_of).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let mut pairs: Vec<(usize, usize)> = negations.iter().flat_map(conflicts_of).collect();
+        pairs.sort_unstable();
+        pairs
     }
     
     /// Group patterns by their base pattern (for deduplication)
@@ -239,7 +334,7 @@ impl PatternAnalyzer {
         for pattern in patterns {
             let analysis = self.analyze_pattern(pattern);
             let base = analysis.base_pattern().to_string();
-            let normalized_base = self.normalize_pattern(&base);
+            let normalized_base = self.equivalence_key(&base);
             groups.entry(normalized_base).or_insert_with(Vec::new).push(pattern.clone());
         }
         
@@ -275,6 +370,14 @@ mod tests {
         assert_eq!(normalized, "*.log");
     }
 
+    #[test]
+    fn test_normalize_pattern_keeps_escaped_trailing_space() {
+        let analyzer = PatternAnalyzer::default();
+        assert_eq!(analyzer.normalize_pattern("foo\\ "), "foo\\ ");
+        assert_eq!(analyzer.normalize_pattern("foo\\   "), "foo\\ ");
+        assert_eq!(analyzer.normalize_pattern("foo\\\\ "), "foo\\\\");
+    }
+
     #[test]
     fn test_normalize_pattern_preserves_leading_spaces() {
         let analyzer = PatternAnalyzer::default();
@@ -296,6 +399,38 @@ mod tests {
         assert_eq!(normalized, "**/node_modules");
     }
 
+    #[test]
+    fn test_dedup_key_keeps_trailing_whitespace() {
+        let analyzer = PatternAnalyzer::default();
+        assert_eq!(analyzer.dedup_key("build//output"), "build/output");
+        assert_eq!(analyzer.dedup_key("*.log "), "*.log ");
+        assert_ne!(analyzer.dedup_key("*.log "), analyzer.dedup_key("*.log"));
+    }
+
+    #[test]
+    fn test_safe_dedup_key_is_exact() {
+        let analyzer = PatternAnalyzer::safe();
+        assert_eq!(analyzer.dedup_key("build//output"), "build//output");
+        assert_ne!(analyzer.dedup_key("build"), analyzer.dedup_key("build/"));
+    }
+
+    #[test]
+    fn test_case_insensitive_dedup_key() {
+        let analyzer = PatternAnalyzer::new(false, false);
+        assert_eq!(analyzer.dedup_key("BUILD/"), analyzer.dedup_key("build/"));
+        assert_ne!(PatternAnalyzer::safe().dedup_key("BUILD/"), PatternAnalyzer::safe().dedup_key("build/"));
+    }
+
+    #[test]
+    fn test_heuristic_dedup_key_merges_equivalents() {
+        let analyzer = PatternAnalyzer::heuristic();
+        assert_eq!(analyzer.dedup_key("build/"), analyzer.dedup_key("build"));
+        assert_eq!(analyzer.dedup_key("/build"), analyzer.dedup_key("build"));
+        assert_eq!(analyzer.dedup_key("**/node_modules/"), analyzer.dedup_key("node_modules"));
+        assert_ne!(analyzer.dedup_key("!build"), analyzer.dedup_key("build"));
+        assert_eq!(analyzer.equivalence_key("/"), "/");
+    }
+
     #[test]
     fn test_analyze_pattern_file_type() {
         let analyzer = PatternAnalyzer::default();
@@ -342,6 +477,22 @@ mod tests {
         assert!(!analysis.is_negation);
     }
 
+    #[test]
+    fn test_analysis_keeps_token_stream() {
+        let analyzer = PatternAnalyzer::default();
+        let analysis = analyzer.analyze_pattern("!/logs/**/debug-[0-9].log");
+        
+        assert_eq!(analysis.segments.len(), 4);
+        assert!(analysis.segments[2].is_globstar());
+        assert!(analysis.is_absolute && analysis.has_globstar && analysis.has_wildcards);
+        assert!(analysis.glob().matches("logs/a/b/debug-1.log"));
+        
+        // Escaped wildcards are literals, so the pattern has none
+        let escaped = analyzer.analyze_pattern("\\*important\\?");
+        assert!(!escaped.has_wildcards);
+        assert_eq!(escaped.pattern_type, PatternType::File);
+    }
+
     #[test]
     fn test_are_equivalent_exact_match() {
         let analyzer = PatternAnalyzer::default();
@@ -405,6 +556,20 @@ mod tests {
                 (conflicts[0].0 == "!*.log" && conflicts[0].1 == "*.log"));
     }
 
+    #[test]
+    fn test_conflicting_pairs() {
+        let analyzer = PatternAnalyzer::default();
+        let patterns = ["*.log", "build/", "!debug.log", "*.log", "!build", "*.tmp", "!debug.log"];
+        
+        let pairs = analyzer.conflicting_pairs(&patterns);
+        assert_eq!(pairs, vec![(0, 2), (0, 6), (1, 4), (2, 3), (3, 6)]);
+        let slow: Vec<(usize, usize)> = (0..patterns.len())
+            .flat_map(|i| (i + 1..patterns.len()).map(move |j| (i, j)))
+            .filter(|&(i, j)| analyzer.are_conflicting(patterns[i], patterns[j]))
+            .collect();
+        assert_eq!(pairs, slow);
+    }
+
     #[test]
     fn test_group_by_base_pattern() {
         let analyzer = PatternAnalyzer::default();
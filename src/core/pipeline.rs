@@ -0,0 +1,245 @@
+//! An optimizer configured pass by pass, for library users who need more than the fixed modes
+
+use crate::core::categorizer::PatternCategorizer;
+use crate::core::changes::OptimizationReport;
+use crate::core::folding::FoldedPatterns;
+use crate::core::optimizer::{
+    optimize_gitignore_advanced_in_tree, optimize_gitignore_tracked, remove_earlier_duplicates, restore_kept_patterns, restore_negation_semantics,
+    KeptForSafety, PassStats, RedundantPattern,
+};
+use crate::core::organizer::category_rank;
+use crate::core::pattern_analyzer::PatternAnalyzer;
+use crate::core::removals::{collect_removed_entries, RemovedEntry};
+use crate::core::sorting::sort_sections;
+use crate::models::{EntryType, GitignoreFile, GixError, KeepOccurrence, SortOrder};
+use std::collections::HashSet;
+
+/// An optimization pipeline; build one with `Optimizer::builder()`
+///
+/// Duplicate patterns are always removed, keeping the first occurrence unless `keep` says
+/// otherwise. Every other pass is
+/// off unless the builder turns it on, so the default optimizer matches standard mode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Optimizer {
+    case_insensitive: bool,
+    keep: KeepOccurrence,
+    dedup_comments: bool,
+    max_blank_lines: Option<usize>,
+    remove_redundant: bool,
+    protected: Vec<String>,
+    sort: SortOrder,
+}
+
+/// Builder for `Optimizer`
+#[derive(Debug, Clone, Default)]
+pub struct OptimizerBuilder {
+    optimizer: Optimizer,
+}
+
+impl OptimizerBuilder {
+    /// Treat patterns differing only in case as duplicates, as git does with `core.ignoreCase`
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.optimizer.case_insensitive = !case_sensitive;
+        self
+    }
+
+    /// Keep this occurrence of each duplicated pattern (the first by default)
+    pub fn keep(mut self, keep: KeepOccurrence) -> Self {
+        self.optimizer.keep = keep;
+        self
+    }
+
+    /// Remove comments identical to an earlier comment
+    pub fn dedup_comments(mut self, dedup: bool) -> Self {
+        self.optimizer.dedup_comments = dedup;
+        self
+    }
+
+    /// Collapse runs of blank lines longer than `max` (aggressive mode keeps 1)
+    pub fn max_blank_lines(mut self, max: usize) -> Self {
+        self.optimizer.max_blank_lines = Some(max);
+        self
+    }
+
+    /// Remove patterns covered by broader ones and fold siblings into character classes, as advanced mode does
+    pub fn remove_redundant(mut self, remove: bool) -> Self {
+        self.optimizer.remove_redundant = remove;
+        self
+    }
+
+    /// Never remove these patterns, compared by their exact text (like `keep` in gix.toml)
+    pub fn protect<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.optimizer.protected.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sort the patterns of each section, keeping negations where they take effect
+    pub fn sort(mut self, order: SortOrder) -> Self {
+        self.optimizer.sort = order;
+        self
+    }
+
+    /// Finish the optimizer
+    pub fn build(self) -> Optimizer {
+        self.optimizer
+    }
+}
+
+/// What `Optimizer::optimize` did to a file
+#[derive(Debug, Clone)]
+pub struct OptimizeResult {
+    /// The optimized file; entries keep the line numbers they had in the original
+    pub file: GitignoreFile,
+    /// Lines removed by each pass
+    pub stats: PassStats,
+    /// Removed patterns, with the comment above each in the original
+    pub removed: Vec<RemovedEntry>,
+    /// Patterns dropped because broader ones cover them
+    pub redundant: Vec<RedundantPattern>,
+    /// Sibling patterns folded into a character class
+    pub folded: Vec<FoldedPatterns>,
+    /// Patterns put back because removing them changed what a negation re-includes
+    pub kept_for_safety: Vec<KeptForSafety>,
+    /// Every change made, line by line
+    pub report: OptimizationReport,
+}
+
+impl Optimizer {
+    /// Start building an optimizer with every optional pass off
+    pub fn builder() -> OptimizerBuilder {
+        OptimizerBuilder::default()
+    }
+
+    /// Optimize a file, leaving it untouched
+    ///
+    /// Protected patterns and patterns marked `# gix:keep` are put back wherever a pass removed
+    /// them, and so are patterns whose removal would change what a negation re-includes.
+    pub fn optimize(&self, file: &GitignoreFile) -> Result<OptimizeResult, GixError> {
+        let analyzer = PatternAnalyzer::new(false, !self.case_insensitive);
+        let mut stats = PassStats::default();
+        let deduplicated;
+        let file_to_optimize = match self.keep {
+            KeepOccurrence::First => file,
+            KeepOccurrence::Last => {
+                deduplicated = remove_earlier_duplicates(file, &analyzer, &mut stats);
+                &deduplicated
+            }
+        };
+        let (optimized, redundant, folded) = if self.remove_redundant {
+            optimize_gitignore_advanced_in_tree(file_to_optimize, &analyzer, &[], &mut stats)?
+        } else {
+            (optimize_gitignore_tracked(file_to_optimize, &analyzer, &mut stats)?, Vec::new(), Vec::new())
+        };
+
+        let mut seen_comments = HashSet::new();
+        let mut blank_run = 0;
+        let mut cleaned = GitignoreFile::new();
+        cleaned.keep_format_of(file);
+        for entry in optimized.entries {
+            let keep = match &entry.entry_type {
+                EntryType::Comment(comment) if self.dedup_comments && !entry.preserve => seen_comments.insert(comment.trim().to_string()),
+                EntryType::Blank => {
+                    blank_run += 1;
+                    entry.preserve || self.max_blank_lines.is_none_or(|max| blank_run <= max)
+                }
+                _ => true,
+            };
+            if !entry.is_blank() {
+                blank_run = 0;
+            }
+            match (keep, &entry.entry_type) {
+                (true, _) => cleaned.add_entry(entry),
+                (false, EntryType::Blank) => stats.blank_lines += 1,
+                (false, _) => stats.duplicate_comments += 1,
+            }
+        }
+
+        let restored = restore_kept_patterns(file, &cleaned, &self.protected);
+        let (restored, kept_for_safety) = restore_negation_semantics(file, &restored);
+        let sorted = self.sorted(&restored);
+        Ok(OptimizeResult {
+            report: OptimizationReport::describe(file, &sorted, &redundant, &folded, &[], &kept_for_safety),
+            removed: collect_removed_entries(file, &sorted),
+            file: sorted,
+            stats,
+            redundant,
+            folded,
+            kept_for_safety,
+        })
+    }
+
+    fn sorted(&self, file: &GitignoreFile) -> GitignoreFile {
+        let alpha = |pattern: &str| pattern.trim_start_matches('!').to_string();
+        match self.sort {
+            SortOrder::None => file.clone(),
+            SortOrder::Alpha => sort_sections(file, alpha),
+            SortOrder::Category => {
+                let categorizer = PatternCategorizer::new();
+                sort_sections(file, |pattern| {
+                    let category = categorizer.primary_category(pattern.trim_start_matches('!'));
+                    (category_rank(&category, &[]), category.short_name(), alpha(pattern))
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_default_optimizer_only_removes_duplicates() {
+        let file = parse_gitignore("# Logs\n*.log\n\n\n\n# Logs\n*.log\nbuild/\nbuild/out\n").unwrap();
+        let result = Optimizer::builder().build().optimize(&file).unwrap();
+
+        assert_eq!(format!("{}", result.file), "# Logs\n*.log\n\n\n\n# Logs\nbuild/\nbuild/out");
+        assert_eq!(result.stats.exact_duplicates, 1);
+        assert_eq!(result.removed.len(), 1);
+        assert_eq!(result.removed[0].entry.line_number, 7);
+        assert_eq!(result.removed[0].comment.as_deref(), Some("# Logs"));
+        assert_eq!(result.report.actions[0].describe(), "line 7: *.log (duplicate of line 2)");
+    }
+
+    #[test]
+    fn test_builder_enables_passes() {
+        let file = parse_gitignore("# Logs\n*.log\n\n\n\n# Logs\n*.LOG\nzz/\nbuild/\nbuild/out\n").unwrap();
+        let optimizer = Optimizer::builder()
+            .case_sensitive(false)
+            .dedup_comments(true)
+            .max_blank_lines(2)
+            .remove_redundant(true)
+            .sort(SortOrder::Alpha)
+            .build();
+        let result = optimizer.optimize(&file).unwrap();
+
+        assert_eq!(format!("{}", result.file), "# Logs\n*.log\n\n\nbuild/\nzz/");
+        assert_eq!(result.stats.equivalent_patterns, 1);
+        assert_eq!(result.stats.duplicate_comments, 1);
+        assert_eq!(result.stats.blank_lines, 1);
+        assert_eq!(result.redundant[0].pattern, "build/out");
+        assert_eq!(result.removed.len(), 2);
+    }
+
+    #[test]
+    fn test_keep_last_occurrence() {
+        let file = parse_gitignore("*.log\n\n# Logs\n*.log\n").unwrap();
+        let result = Optimizer::builder().keep(KeepOccurrence::Last).build().optimize(&file).unwrap();
+
+        assert_eq!(format!("{}", result.file), "\n# Logs\n*.log");
+        assert_eq!(result.report.actions[0].describe(), "line 1: *.log (duplicate of line 4)");
+    }
+
+    #[test]
+    fn test_protected_patterns_survive() {
+        let file = parse_gitignore("*.log\n*.log\nbuild/\nbuild/out\n").unwrap();
+        let result = Optimizer::builder().remove_redundant(true).protect(["build/out"]).build().optimize(&file).unwrap();
+        assert_eq!(format!("{}", result.file), "*.log\nbuild/\nbuild/out");
+        assert_eq!(result.removed.len(), 1);
+    }
+}
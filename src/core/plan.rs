@@ -0,0 +1,203 @@
+use crate::models::GixError;
+use crate::utils::diff::{line_changes, LineChange};
+use crate::utils::json::{json_string, parse_json, JsonValue};
+use crate::utils::stable_hash;
+
+/// Version of the plan format written by `--emit-plan`
+pub const PLAN_VERSION: usize = 1;
+
+/// One edit of a planned file, with line numbers of the file as it was when planned (1-indexed)
+///
+/// Line text includes the line ending, if the line has one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanEdit {
+    /// Remove line `line`, which must read `text`
+    Delete { line: usize, text: String },
+    /// Add `text` after line `after` (0 for the top of the file)
+    Insert { after: usize, text: String },
+}
+
+/// The edits planned for one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedFile {
+    /// The file, as given on the command line when planning
+    pub path: String,
+    /// The gix command that planned the edits (`optimize`, ...)
+    pub command: String,
+    /// Hash of the contents the edits apply to (see `utils::stable_hash`)
+    pub before: String,
+    /// Hash of the contents after the edits
+    pub after: String,
+    /// The edits, in file order
+    pub edits: Vec<PlanEdit>,
+}
+
+impl PlannedFile {
+    /// Plan the edits turning `before` into `after`
+    pub fn new(path: &str, command: &str, before: &str, after: &str) -> Self {
+        let edits = line_changes(before, after).into_iter()
+            .map(|change| match change {
+                LineChange::Delete { line, text } => PlanEdit::Delete { line, text: text.to_string() },
+                LineChange::Insert { after, text } => PlanEdit::Insert { after, text: text.to_string() },
+            })
+            .collect();
+        Self {
+            path: path.to_string(),
+            command: command.to_string(),
+            before: stable_hash(before),
+            after: stable_hash(after),
+            edits,
+        }
+    }
+
+    /// Apply the edits to the current contents of the file
+    ///
+    /// Fails with `StalePlan` if the file changed since the plan was made, and with
+    /// `InvalidPlan` if the edits don't fit it or don't give the planned result.
+    pub fn apply(&self, current: &str) -> Result<String, GixError> {
+        if stable_hash(current) != self.before {
+            return Err(GixError::StalePlan(self.path.clone()));
+        }
+        let invalid = |message: String| GixError::InvalidPlan(format!("{}: {}", self.path, message));
+
+        let lines: Vec<&str> = current.split_inclusive('\n').collect();
+        let mut edits = self.edits.iter().peekable();
+        let mut result = String::with_capacity(current.len());
+        for number in 0..=lines.len() {
+            if number > 0 {
+                match edits.peek() {
+                    Some(PlanEdit::Delete { line, text }) if *line == number => {
+                        if text != lines[number - 1] {
+                            return Err(invalid(format!("line {} is not {}", number, json_string(text))));
+                        }
+                        edits.next();
+                    }
+                    _ => result.push_str(lines[number - 1]),
+                }
+            }
+            while let Some(PlanEdit::Insert { after, text }) = edits.peek() {
+                if *after != number {
+                    break;
+                }
+                result.push_str(text);
+                edits.next();
+            }
+        }
+
+        if let Some(edit) = edits.next() {
+            return Err(invalid(format!("edit {:?} is out of order or past the end of the file", edit)));
+        }
+        if stable_hash(&result) != self.after {
+            return Err(invalid("the edits don't give the planned contents".to_string()));
+        }
+        Ok(result)
+    }
+}
+
+/// Edits gix intends to make across files, to review before `gix apply-plan` makes them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    /// The files to change, in the order they are changed
+    pub files: Vec<PlannedFile>,
+}
+
+impl Plan {
+    /// Render the plan as a JSON document
+    pub fn to_json(&self) -> String {
+        let files: Vec<String> = self.files.iter()
+            .map(|file| {
+                let edits: Vec<String> = file.edits.iter()
+                    .map(|edit| match edit {
+                        PlanEdit::Delete { line, text } => format!("        {{\"op\": \"delete\", \"line\": {}, \"text\": {}}}", line, json_string(text)),
+                        PlanEdit::Insert { after, text } => format!("        {{\"op\": \"insert\", \"after\": {}, \"text\": {}}}", after, json_string(text)),
+                    })
+                    .collect();
+                format!(
+                    "    {{\n      \"path\": {},\n      \"command\": {},\n      \"before\": {},\n      \"after\": {},\n      \"edits\": [{}]\n    }}",
+                    json_string(&file.path),
+                    json_string(&file.command),
+                    json_string(&file.before),
+                    json_string(&file.after),
+                    if edits.is_empty() { String::new() } else { format!("\n{}\n      ", edits.join(",\n")) }
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"version\": {},\n  \"files\": [{}]\n}}\n",
+            PLAN_VERSION,
+            if files.is_empty() { String::new() } else { format!("\n{}\n  ", files.join(",\n")) }
+        )
+    }
+
+    /// Parse a plan written by `to_json`
+    pub fn parse(text: &str) -> Result<Self, GixError> {
+        let document = parse_json(text).map_err(GixError::InvalidPlan)?;
+        let invalid = |message: &str| GixError::InvalidPlan(message.to_string());
+        match document.get("version").and_then(JsonValue::as_usize) {
+            Some(PLAN_VERSION) => {}
+            Some(version) => return Err(GixError::InvalidPlan(format!("unsupported version {} (expected {})", version, PLAN_VERSION))),
+            None => return Err(invalid("missing version")),
+        }
+
+        let string = |value: &JsonValue, key: &str| -> Result<String, GixError> {
+            value.get(key).and_then(JsonValue::as_str).map(str::to_string).ok_or_else(|| GixError::InvalidPlan(format!("missing string '{}'", key)))
+        };
+        let number = |value: &JsonValue, key: &str| -> Result<usize, GixError> {
+            value.get(key).and_then(JsonValue::as_usize).ok_or_else(|| GixError::InvalidPlan(format!("missing line number '{}'", key)))
+        };
+        let mut files = Vec::new();
+        for file in document.get("files").and_then(JsonValue::as_array).ok_or_else(|| invalid("missing array 'files'"))? {
+            let mut edits = Vec::new();
+            for edit in file.get("edits").and_then(JsonValue::as_array).ok_or_else(|| invalid("missing array 'edits'"))? {
+                edits.push(match string(edit, "op")?.as_str() {
+                    "delete" => PlanEdit::Delete { line: number(edit, "line")?, text: string(edit, "text")? },
+                    "insert" => PlanEdit::Insert { after: number(edit, "after")?, text: string(edit, "text")? },
+                    op => return Err(GixError::InvalidPlan(format!("unknown op '{}'", op))),
+                });
+            }
+            files.push(PlannedFile {
+                path: string(file, "path")?,
+                command: string(file, "command")?,
+                before: string(file, "before")?,
+                after: string(file, "after")?,
+                edits,
+            });
+        }
+        Ok(Plan { files })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_round_trip() {
+        let before = "# Logs\n*.log\n*.log\nbuild/";
+        let after = "# Logs\n*.log\nbuild/\n";
+        let plan = Plan { files: vec![PlannedFile::new("app/.gitignore", "optimize", before, after)] };
+
+        let parsed = Plan::parse(&plan.to_json()).unwrap();
+        assert_eq!(parsed, plan);
+        assert_eq!(parsed.files[0].apply(before).unwrap(), after);
+        assert!(plan.to_json().contains("{\"op\": \"delete\", \"line\": 3, \"text\": \"*.log\\n\"}"));
+        assert_eq!(Plan::parse(&Plan::default().to_json()).unwrap(), Plan::default());
+    }
+
+    #[test]
+    fn test_apply_refuses_changed_file() {
+        let planned = PlannedFile::new(".gitignore", "optimize", "*.log\n*.log\n", "*.log\n");
+        assert!(matches!(planned.apply("*.log\n*.log\n*.tmp\n"), Err(GixError::StalePlan(path)) if path == ".gitignore"));
+
+        let mut tampered = planned.clone();
+        tampered.edits = vec![PlanEdit::Delete { line: 1, text: "*.tmp\n".to_string() }];
+        assert!(matches!(tampered.apply("*.log\n*.log\n"), Err(GixError::InvalidPlan(_))));
+    }
+
+    #[test]
+    fn test_parse_plan_errors() {
+        assert!(matches!(Plan::parse("{\"version\": 2, \"files\": []}"), Err(GixError::InvalidPlan(message)) if message.contains("unsupported version 2")));
+        assert!(matches!(Plan::parse("{\"version\": 1}"), Err(GixError::InvalidPlan(message)) if message.contains("'files'")));
+        assert!(matches!(Plan::parse("not json"), Err(GixError::InvalidPlan(_))));
+    }
+}
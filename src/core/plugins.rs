@@ -0,0 +1,380 @@
+//! Extension points for an organization's own section taxonomy, lint rules and suggestions
+//!
+//! Plugins are either Rust types implementing `GixPlugin`, registered by programs embedding
+//! gix, or external commands speaking the protocol of `CommandPlugin`.
+
+use crate::core::categorizer::PatternCategory;
+use crate::core::detector::Suggestion;
+use crate::core::lint::{entry_ids_by_line, LintIssue, LintKind};
+use crate::core::rules::Severity;
+use crate::models::{GitignoreEntry, GitignoreFile};
+use crate::utils::stable_hash;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A source of categories, lint findings and pattern suggestions beyond gix's own
+///
+/// Every method but `name` does nothing by default, so a plugin implements only what it adds.
+pub trait GixPlugin: Send + Sync {
+    /// Name shown with the plugin's findings and suggestions
+    fn name(&self) -> &str;
+
+    /// Category of a pattern, taking precedence over gix's own; `None` leaves it to gix
+    fn categorize_pattern(&self, _pattern: &str) -> Option<PatternCategory> {
+        None
+    }
+
+    /// Categories of several patterns, in order; override when answering a batch is cheaper
+    fn categorize_patterns(&self, patterns: &[String]) -> Vec<Option<PatternCategory>> {
+        patterns.iter().map(|pattern| self.categorize_pattern(pattern)).collect()
+    }
+
+    /// Problems with one entry of a file, as messages
+    fn lint_entry(&self, _entry: &GitignoreEntry) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Problems with the entries of a file, by line number; override to look at the file as a whole
+    fn lint_file(&self, file: &GitignoreFile) -> Vec<(usize, String)> {
+        file.entries.iter()
+            .flat_map(|entry| self.lint_entry(entry).into_iter().map(|message| (entry.line_number, message)))
+            .collect()
+    }
+
+    /// Patterns the file should have
+    fn suggest_patterns(&self, _file: &GitignoreFile) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// The plugins gix consults, in registration order
+#[derive(Clone, Default)]
+pub struct PluginRegistry {
+    plugins: Vec<Arc<dyn GixPlugin>>,
+}
+
+impl fmt::Debug for PluginRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl PluginRegistry {
+    /// Create a registry without plugins
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry of external commands (see `CommandPlugin`)
+    pub fn from_commands<P: AsRef<Path>>(commands: &[P]) -> Self {
+        let mut registry = Self::new();
+        for command in commands {
+            registry.register(CommandPlugin::new(command.as_ref()));
+        }
+        registry
+    }
+
+    /// Add a plugin, consulted after those registered before it
+    pub fn register(&mut self, plugin: impl GixPlugin + 'static) {
+        self.plugins.push(Arc::new(plugin));
+    }
+
+    /// Check if no plugin is registered
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Get the names of the plugins, in registration order
+    pub fn names(&self) -> Vec<&str> {
+        self.plugins.iter().map(|plugin| plugin.name()).collect()
+    }
+
+    /// Get the category the first plugin with an answer gives a pattern
+    pub fn categorize_pattern(&self, pattern: &str) -> Option<PatternCategory> {
+        self.plugins.iter().find_map(|plugin| plugin.categorize_pattern(pattern))
+    }
+
+    /// Get the category the first plugin with an answer gives each pattern, asking each plugin once
+    pub fn categorize_patterns(&self, patterns: &[String]) -> Vec<Option<PatternCategory>> {
+        let mut categories = vec![None; patterns.len()];
+        for plugin in &self.plugins {
+            let unanswered: Vec<usize> = (0..patterns.len()).filter(|&index| categories[index].is_none()).collect();
+            if unanswered.is_empty() {
+                break;
+            }
+            let questions: Vec<String> = unanswered.iter().map(|&index| patterns[index].clone()).collect();
+            for (index, category) in unanswered.into_iter().zip(plugin.categorize_patterns(&questions)) {
+                categories[index] = category;
+            }
+        }
+        categories
+    }
+
+    /// Collect every plugin's findings as `plugin` issues, in line order
+    ///
+    /// Each message names the plugin that reported it.
+    pub fn lint(&self, file: &GitignoreFile) -> Vec<LintIssue> {
+        let ids = entry_ids_by_line(file);
+        let mut issues: Vec<LintIssue> = self.plugins.iter()
+            .flat_map(|plugin| {
+                let ids = &ids;
+                plugin.lint_file(file).into_iter().map(move |(line_number, message)| {
+                    let entry_id = ids.get(&line_number).cloned().unwrap_or_default();
+                    LintIssue {
+                        line_number,
+                        kind: LintKind::Plugin,
+                        id: stable_hash(&format!("{}:{}:{}:{}", LintKind::Plugin, entry_id, plugin.name(), message)),
+                        message: format!("{} (plugin {})", message, plugin.name()),
+                        entry_id,
+                        examples: Vec::new(),
+                        span: file.span_of(line_number),
+                        related: None,
+                        severity: Severity::Deny,
+                        risk: LintKind::Plugin.risk(),
+                    }
+                })
+            })
+            .collect();
+        issues.sort_by_key(|issue| issue.line_number);
+        issues
+    }
+
+    /// Collect the patterns each plugin suggests that the file doesn't have yet, one suggestion per plugin
+    pub fn suggest_patterns(&self, file: &GitignoreFile) -> Vec<Suggestion> {
+        let existing: Vec<String> = file.entries.iter().filter_map(GitignoreEntry::comparison_pattern).collect();
+        self.plugins.iter()
+            .map(|plugin| {
+                let mut patterns: Vec<String> = Vec::new();
+                for pattern in plugin.suggest_patterns(file) {
+                    if !existing.contains(&pattern) && !patterns.contains(&pattern) {
+                        patterns.push(pattern);
+                    }
+                }
+                Suggestion { category: PatternCategory::Custom(plugin.name().to_string()), patterns }
+            })
+            .filter(|suggestion| !suggestion.patterns.is_empty())
+            .collect()
+    }
+}
+
+/// A plugin run as an external command, once per question
+///
+/// - `COMMAND categorize` reads patterns on stdin, one per line, and prints a line for each:
+///   its category, such as `tool:Terraform` (kinds as in `category_order`; a bare name is a
+///   custom category), or an empty line.
+/// - `COMMAND lint` reads the file on stdin and prints a `LINE<TAB>MESSAGE` line per finding.
+/// - `COMMAND suggest` reads the file on stdin and prints one pattern per line.
+///
+/// A command that can't be run or exits with an error answers nothing, except that a failed
+/// `lint` is reported as a finding on line 1, so a broken plugin doesn't pass a check.
+pub struct CommandPlugin {
+    name: String,
+    command: PathBuf,
+    categories: Mutex<HashMap<String, Option<PatternCategory>>>,
+}
+
+impl CommandPlugin {
+    /// Create a plugin for a command, named after its file name
+    pub fn new(command: &Path) -> Self {
+        let name = command.file_stem().map_or_else(|| command.display().to_string(), |stem| stem.to_string_lossy().into_owned());
+        Self { name, command: command.to_path_buf(), categories: Mutex::new(HashMap::new()) }
+    }
+
+    /// Run the command with `args` and `input` on stdin, returning its output if it succeeded
+    fn run(&self, args: &[&str], input: Option<&str>) -> Result<String, String> {
+        let mut child = Command::new(&self.command)
+            .args(args)
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("cannot run {}: {}", self.command.display(), e))?;
+        // Written from another thread, so a plugin answering before it has read all its input can't block on a full pipe
+        let writer = child.stdin.take().zip(input).map(|(mut stdin, input)| {
+            let input = input.to_string();
+            thread::spawn(move || {
+                // A plugin that exits without reading its input still answers
+                let _ = stdin.write_all(input.as_bytes());
+            })
+        });
+        let output = child.wait_with_output().map_err(|e| format!("cannot run {}: {}", self.command.display(), e))?;
+        if let Some(writer) = writer {
+            let _ = writer.join();
+        }
+        if !output.status.success() {
+            return Err(format!("{} {} failed ({})", self.command.display(), args.join(" "), output.status));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl GixPlugin for CommandPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn categorize_pattern(&self, pattern: &str) -> Option<PatternCategory> {
+        self.categorize_patterns(&[pattern.to_string()]).pop().flatten()
+    }
+
+    fn categorize_patterns(&self, patterns: &[String]) -> Vec<Option<PatternCategory>> {
+        let Ok(mut cache) = self.categories.lock() else {
+            return vec![None; patterns.len()];
+        };
+        let mut questions: Vec<&str> = Vec::new();
+        for pattern in patterns {
+            // A line break would shift every later answer
+            if !cache.contains_key(pattern) && !pattern.contains('\n') && !questions.contains(&pattern.as_str()) {
+                questions.push(pattern);
+            }
+        }
+        if !questions.is_empty() {
+            let output = self.run(&["categorize"], Some(&format!("{}\n", questions.join("\n")))).unwrap_or_default();
+            let mut answers = output.lines();
+            for question in questions {
+                cache.insert(question.to_string(), answers.next().and_then(parse_category));
+            }
+        }
+        patterns.iter().map(|pattern| cache.get(pattern).cloned().flatten()).collect()
+    }
+
+    fn lint_file(&self, file: &GitignoreFile) -> Vec<(usize, String)> {
+        match self.run(&["lint"], Some(&format!("{}", file))) {
+            Ok(output) => output.lines()
+                .filter_map(|line| {
+                    let (line_number, message) = line.split_once('\t')?;
+                    Some((line_number.trim().parse().ok()?, message.trim().to_string()))
+                })
+                .collect(),
+            Err(message) => vec![(1, message)],
+        }
+    }
+
+    fn suggest_patterns(&self, file: &GitignoreFile) -> Vec<String> {
+        self.run(&["suggest"], Some(&format!("{}", file)))
+            .map(|output| output.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Parse a category as `kind:name`, kinds named as `PatternCategory::kind_key` does, or a bare custom name
+fn parse_category(text: &str) -> Option<PatternCategory> {
+    let text = text.trim();
+    let (kind, name) = text.split_once(':').unwrap_or(("custom", text));
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return (kind == "uncategorized").then_some(PatternCategory::Uncategorized);
+    }
+    match kind.trim() {
+        "language" => Some(PatternCategory::Language(name)),
+        "framework" => Some(PatternCategory::Framework(name)),
+        "tool" => Some(PatternCategory::Tool(name)),
+        "os" => Some(PatternCategory::OperatingSystem(name)),
+        "custom" => Some(PatternCategory::Custom(name)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    struct Terraform;
+
+    impl GixPlugin for Terraform {
+        fn name(&self) -> &str {
+            "terraform"
+        }
+
+        fn categorize_pattern(&self, pattern: &str) -> Option<PatternCategory> {
+            pattern.contains(".terraform").then(|| PatternCategory::Tool("Terraform".to_string()))
+        }
+
+        fn lint_entry(&self, entry: &GitignoreEntry) -> Vec<String> {
+            match entry.comparison_pattern().as_deref() {
+                Some("*.tfstate") => vec!["state files hold secrets; keep them in a remote backend".to_string()],
+                _ => Vec::new(),
+            }
+        }
+
+        fn suggest_patterns(&self, _file: &GitignoreFile) -> Vec<String> {
+            vec![".terraform/".to_string(), "*.tfvars".to_string()]
+        }
+    }
+
+    #[test]
+    fn test_registry_consults_plugins() {
+        let mut registry = PluginRegistry::new();
+        registry.register(Terraform);
+        let file = parse_gitignore(".terraform/\n*.tfstate\n").unwrap();
+
+        assert_eq!(registry.categorize_pattern(".terraform/"), Some(PatternCategory::Tool("Terraform".to_string())));
+        assert_eq!(registry.categorize_pattern("*.log"), None);
+
+        let issues = registry.lint(&file);
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].line_number, issues[0].kind.code()), (2, "GIX100"));
+        assert!(issues[0].message.ends_with("(plugin terraform)"));
+
+        let suggestions = registry.suggest_patterns(&file);
+        assert_eq!(suggestions[0].patterns, vec!["*.tfvars".to_string()]);
+        assert_eq!(format!("{:?}", registry), "[\"terraform\"]");
+    }
+
+    #[test]
+    fn test_parse_category() {
+        assert_eq!(parse_category("tool:Terraform\n"), Some(PatternCategory::Tool("Terraform".to_string())));
+        assert_eq!(parse_category("Secrets"), Some(PatternCategory::Custom("Secrets".to_string())));
+        assert_eq!(parse_category(""), None);
+        assert_eq!(parse_category("editor:Vim"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_command_plugin() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let command = dir.path().join("acme-rules");
+        std::fs::write(&command, concat!(
+            "#!/bin/sh\n",
+            "case \"$1\" in\n",
+            "  categorize) echo \"$@\" >> \"$(dirname \"$0\")/calls\"; while read -r pattern; do case \"$pattern\" in *.secret) echo 'custom:Secrets' ;; *) echo ;; esac; done ;;\n",
+            "  lint) grep -n 'tmp' | cut -d: -f1 | sed 's/$/\\tuse the shared tmp pattern/' ;;\n",
+            "  suggest) echo '*.secret' ;;\n",
+            "esac\n",
+        )).unwrap();
+        std::fs::set_permissions(&command, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let registry = PluginRegistry::from_commands(&[&command]);
+        let file = parse_gitignore("*.log\ntmp/\n").unwrap();
+        assert_eq!(registry.names(), vec!["acme-rules"]);
+        let patterns: Vec<String> = ["*.log", "api.secret", "*.tmp"].iter().map(|pattern| pattern.to_string()).collect();
+        assert_eq!(registry.categorize_patterns(&patterns), vec![None, Some(PatternCategory::Custom("Secrets".to_string())), None]);
+        assert_eq!(registry.categorize_pattern("api.secret"), Some(PatternCategory::Custom("Secrets".to_string())));
+        assert_eq!(registry.categorize_pattern("*.log"), None);
+        // One run answered the batch, and the single questions came from the cache
+        assert_eq!(std::fs::read_to_string(dir.path().join("calls")).unwrap(), "categorize\n");
+        let issues = registry.lint(&file);
+        assert_eq!((issues[0].line_number, issues[0].message.as_str()), (2, "use the shared tmp pattern (plugin acme-rules)"));
+        assert_eq!(registry.suggest_patterns(&file)[0].patterns, vec!["*.secret".to_string()]);
+
+        // A plugin echoing more than a pipe holds before its input ends must not deadlock
+        let echo = dir.path().join("echo");
+        std::fs::write(&echo, "#!/bin/sh\ncat\n").unwrap();
+        std::fs::set_permissions(&echo, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let large: String = (0..50_000).map(|index| format!("build-{}/\n", index)).collect();
+        let large = parse_gitignore(&large).unwrap();
+        assert_eq!(PluginRegistry::from_commands(&[&echo]).suggest_patterns(&large).len(), 0);
+
+        let broken = PluginRegistry::from_commands(&[dir.path().join("missing")]);
+        assert_eq!(broken.lint(&file).len(), 1);
+        assert_eq!(broken.categorize_pattern("*.log"), None);
+    }
+}
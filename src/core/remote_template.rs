@@ -0,0 +1,145 @@
+use crate::models::{GitignoreFile, GixError};
+use crate::utils::file::cache_dir;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A site publishing gitignore templates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateSource {
+    /// The github/gitignore repository (`Python.gitignore`, `Node.gitignore`, ...)
+    Github,
+    /// Toptal's gitignore.io API (`python`, `node`, ...)
+    Gitignoreio,
+}
+
+impl TemplateSource {
+    /// Get the name used on the command line and in cache paths
+    pub fn name(&self) -> &'static str {
+        match self {
+            TemplateSource::Github => "github",
+            TemplateSource::Gitignoreio => "gitignoreio",
+        }
+    }
+
+    /// Get the URL of a template
+    ///
+    /// github/gitignore names its files in title case, so `python` asks for `Python.gitignore`;
+    /// names already containing capitals are used as given (`VisualStudio`).
+    pub fn url(&self, name: &str) -> String {
+        match self {
+            TemplateSource::Github => {
+                let file = if name.chars().any(char::is_uppercase) { name.to_string() } else { title_case(name) };
+                format!("https://raw.githubusercontent.com/github/gitignore/main/{}.gitignore", file)
+            }
+            TemplateSource::Gitignoreio => format!("https://www.toptal.com/developers/gitignore/api/{}", name.to_lowercase()),
+        }
+    }
+}
+
+impl FromStr for TemplateSource {
+    type Err = GixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "github" => Ok(TemplateSource::Github),
+            "gitignoreio" | "gitignore.io" | "toptal" => Ok(TemplateSource::Gitignoreio),
+            other => Err(GixError::ParseError(format!("unknown template source '{}' (expected github or gitignoreio)", other))),
+        }
+    }
+}
+
+/// A downloaded template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteTemplate {
+    /// Template contents
+    pub content: String,
+    /// Where the template is cached
+    pub path: PathBuf,
+    /// Whether it was read from the cache instead of downloaded
+    pub cached: bool,
+}
+
+/// Path of a cached template in the user's cache directory
+pub fn template_cache_path(source: TemplateSource, name: &str) -> Option<PathBuf> {
+    Some(cache_dir()?.join("templates").join(source.name()).join(format!("{}.gitignore", name.to_lowercase())))
+}
+
+/// Get a template from the cache, downloading it first if it is missing or `refresh` is set
+#[cfg(feature = "templates-remote")]
+pub fn fetch_template(source: TemplateSource, name: &str, refresh: bool) -> Result<RemoteTemplate, GixError> {
+    use crate::utils::download::fetch_text;
+
+    let path = template_cache_path(source, name)
+        .ok_or_else(|| GixError::NetworkError("no cache directory available".to_string()))?;
+    if !refresh && path.is_file() {
+        let content = crate::utils::read_gitignore_file(&path)?;
+        return Ok(RemoteTemplate { content, path, cached: true });
+    }
+
+    let content = fetch_text(&source.url(name))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    crate::utils::write_gitignore_file(&path, &content)?;
+    Ok(RemoteTemplate { content, path, cached: false })
+}
+
+/// Templates can only be downloaded when gix is built with the `templates-remote` feature
+#[cfg(not(feature = "templates-remote"))]
+pub fn fetch_template(_source: TemplateSource, _name: &str, _refresh: bool) -> Result<RemoteTemplate, GixError> {
+    Err(GixError::NetworkError("gix was built without the `templates-remote` feature".to_string()))
+}
+
+/// Append the patterns of a template that the file lacks, in a section headed by `header`
+///
+/// Returns the merged file and the number of patterns added; the file is unchanged when it
+/// already has every pattern.
+pub fn merge_template(file: &GitignoreFile, template: &GitignoreFile, header: &str) -> (GitignoreFile, usize) {
+    let mut seen: HashSet<String> = file.entries.iter().filter_map(|entry| entry.comparison_pattern()).collect();
+    let missing: Vec<String> = template.entries.iter()
+        .filter_map(|entry| entry.comparison_pattern())
+        .filter(|pattern| seen.insert(pattern.clone()))
+        .collect();
+
+    let mut merged = file.clone();
+    merged.append_section(header, &missing);
+    (merged, missing.len())
+}
+
+fn title_case(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_template_urls() {
+        assert_eq!(TemplateSource::Github.url("python"), "https://raw.githubusercontent.com/github/gitignore/main/Python.gitignore");
+        assert_eq!(TemplateSource::Github.url("VisualStudio"), "https://raw.githubusercontent.com/github/gitignore/main/VisualStudio.gitignore");
+        assert_eq!(TemplateSource::Gitignoreio.url("Node"), "https://www.toptal.com/developers/gitignore/api/node");
+        assert_eq!("gitignore.io".parse::<TemplateSource>().unwrap(), TemplateSource::Gitignoreio);
+        assert!("bogus".parse::<TemplateSource>().is_err());
+    }
+
+    #[test]
+    fn test_merge_template_adds_missing_patterns() {
+        let file = parse_gitignore("# Local\n.env\n__pycache__/").unwrap();
+        let template = parse_gitignore("# Byte-compiled\n__pycache__/\n*.py[cod]\n\n# Environments\n.env\n.venv").unwrap();
+        let (merged, added) = merge_template(&file, &template, "# Python (github)");
+
+        assert_eq!(added, 2);
+        assert_eq!(format!("{}", merged), "# Local\n.env\n__pycache__/\n\n# Python (github)\n*.py[cod]\n.venv");
+
+        let (unchanged, added) = merge_template(&merged, &template, "# Python (github)");
+        assert_eq!(added, 0);
+        assert_eq!(unchanged.entries.len(), merged.entries.len());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::models::{GitignoreEntry, GitignoreFile};
+use std::collections::HashSet;
+
+/// A pattern that was dropped during optimization
+#[derive(Debug, Clone)]
+pub struct RemovedEntry {
+    /// The removed entry, carrying its original line and line number
+    pub entry: GitignoreEntry,
+    /// The nearest comment above the entry in the original file
+    pub comment: Option<String>,
+}
+
+/// Collect the pattern entries of `original` that are missing from `optimized`
+pub fn collect_removed_entries(original: &GitignoreFile, optimized: &GitignoreFile) -> Vec<RemovedEntry> {
+    let kept: HashSet<usize> = optimized.entries.iter().map(|entry| entry.line_number).collect();
+    let mut removed = Vec::new();
+    let mut last_comment: Option<&str> = None;
+
+    for entry in &original.entries {
+        if entry.is_comment() {
+            last_comment = Some(entry.original.as_str());
+        } else if entry.is_pattern() && !kept.contains(&entry.line_number) {
+            removed.push(RemovedEntry {
+                entry: entry.clone(),
+                comment: last_comment.map(str::to_string),
+            });
+        }
+    }
+
+    removed
+}
+
+/// Render removed entries as a gitignore-formatted log that can be copied back
+pub fn render_removals_log(source: &str, removed: &[RemovedEntry]) -> String {
+    let mut lines = vec![
+        format!("# Patterns removed by gix from {}", source),
+        "# Copy a line back into the original file to restore it.".to_string(),
+    ];
+
+    if removed.is_empty() {
+        lines.push("# No patterns were removed.".to_string());
+    }
+
+    for removed_entry in removed {
+        lines.push(String::new());
+        match &removed_entry.comment {
+            Some(comment) => lines.push(format!(
+                "# line {} (under \"{}\")",
+                removed_entry.entry.line_number,
+                comment.trim()
+            )),
+            None => lines.push(format!("# line {}", removed_entry.entry.line_number)),
+        }
+        lines.push(removed_entry.entry.original.clone());
+    }
+
+    let mut log = lines.join("\n");
+    log.push('\n');
+    log
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::optimizer::optimize_gitignore;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_collect_removed_entries() {
+        let original = parse_gitignore("# Logs\n*.log\n\n# Build\nbuild/\n*.log").unwrap();
+        let optimized = optimize_gitignore(&original).unwrap();
+        let removed = collect_removed_entries(&original, &optimized);
+
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].entry.line_number, 6);
+        assert_eq!(removed[0].entry.original, "*.log");
+        assert_eq!(removed[0].comment, Some("# Build".to_string()));
+    }
+
+    #[test]
+    fn test_collect_removed_entries_without_comment() {
+        let original = parse_gitignore("*.log\n*.log").unwrap();
+        let optimized = optimize_gitignore(&original).unwrap();
+        let removed = collect_removed_entries(&original, &optimized);
+
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].comment, None);
+    }
+
+    #[test]
+    fn test_render_removals_log() {
+        let original = parse_gitignore("# Logs\n*.log\n*.log # again").unwrap();
+        let optimized = optimize_gitignore(&original).unwrap();
+        let log = render_removals_log(".gitignore", &collect_removed_entries(&original, &optimized));
+
+        assert!(log.starts_with("# Patterns removed by gix from .gitignore\n"));
+        assert!(log.contains("# line 3 (under \"# Logs\")\n*.log # again\n"));
+
+        // The log itself must parse as a gitignore file holding only the removed patterns
+        let reparsed = parse_gitignore(&log).unwrap();
+        assert_eq!(reparsed.stats.pattern_lines, 1);
+    }
+
+    #[test]
+    fn test_render_empty_removals_log() {
+        let log = render_removals_log(".gitignore", &[]);
+        assert!(log.contains("No patterns were removed"));
+    }
+}
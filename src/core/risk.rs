@@ -0,0 +1,64 @@
+use crate::models::GixError;
+use std::fmt;
+use std::str::FromStr;
+
+/// How likely a change is to alter which files are ignored, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChangeRisk {
+    /// Provably ignores the same files: exact duplicates, covered patterns, comments, blank lines
+    Safe,
+    /// Ignores the same files as long as other files stay as they are (another ignore layer)
+    LikelySafe,
+    /// May ignore different files: heuristic merges, pruning, rewriting conflicting patterns
+    BehaviorMayChange,
+}
+
+impl ChangeRisk {
+    /// Every risk level, from least to most risky
+    pub const ALL: [ChangeRisk; 3] = [ChangeRisk::Safe, ChangeRisk::LikelySafe, ChangeRisk::BehaviorMayChange];
+
+    /// Get the name used on the command line and in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChangeRisk::Safe => "safe",
+            ChangeRisk::LikelySafe => "likely-safe",
+            ChangeRisk::BehaviorMayChange => "behavior-may-change",
+        }
+    }
+}
+
+impl fmt::Display for ChangeRisk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ChangeRisk {
+    type Err = GixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wanted = s.trim().to_ascii_lowercase().replace('_', "-");
+        ChangeRisk::ALL.into_iter()
+            .find(|risk| risk.name() == wanted)
+            .ok_or_else(|| GixError::ParseError(format!(
+                "unknown risk '{}' (expected safe, likely-safe or behavior-may-change)",
+                s.trim()
+            )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risk_order_and_names() {
+        assert!(ChangeRisk::Safe < ChangeRisk::LikelySafe);
+        assert!(ChangeRisk::LikelySafe < ChangeRisk::BehaviorMayChange);
+        for risk in ChangeRisk::ALL {
+            assert_eq!(risk.name().parse::<ChangeRisk>().unwrap(), risk);
+        }
+        assert_eq!("Likely_Safe".parse::<ChangeRisk>().unwrap(), ChangeRisk::LikelySafe);
+        assert!("risky".parse::<ChangeRisk>().is_err());
+    }
+}
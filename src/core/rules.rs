@@ -0,0 +1,148 @@
+use crate::core::lint::{LintIssue, LintKind};
+use crate::models::GixError;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A check `gix check` runs, under a stable code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    /// Stable code, `GIX` and three digits
+    pub code: &'static str,
+    /// Name of the finding kind in reports
+    pub name: &'static str,
+    /// What the rule reports
+    pub summary: &'static str,
+}
+
+/// Every rule, by code; codes from GIX010 are invalid patterns, and GIX100 holds plugin findings
+pub const RULES: &[Rule] = &[
+    Rule { code: "GIX001", name: "duplicate", summary: "a pattern repeats an earlier pattern" },
+    Rule { code: "GIX002", name: "redundant", summary: "a broader pattern already covers the pattern" },
+    Rule { code: "GIX003", name: "conflict", summary: "a pattern of opposite polarity matches the same paths" },
+    Rule { code: "GIX004", name: "empty", summary: "the file has no patterns" },
+    Rule { code: "GIX005", name: "lfs-tracked", summary: "a pattern ignores files Git LFS tracks" },
+    Rule { code: "GIX006", name: "export-ignored", summary: "a pattern ignores files marked export-ignore" },
+    Rule { code: "GIX010", name: "invalid-class", summary: "a character class is never closed" },
+    Rule { code: "GIX011", name: "only-slashes", summary: "a pattern is only slashes" },
+    Rule { code: "GIX012", name: "misplaced-globstar", summary: "`**` appears inside a name" },
+    Rule { code: "GIX013", name: "trailing-backslash", summary: "a pattern ends with a backslash that escapes nothing" },
+    Rule { code: "GIX014", name: "never-matches", summary: "a pattern has a `.` or `..` segment" },
+    Rule { code: "GIX015", name: "empty-pattern", summary: "a pattern is empty" },
+    Rule { code: "GIX100", name: "plugin", summary: "a plugin reported a problem" },
+];
+
+/// Find a rule by its code (`GIX001`, in any case) or its name (`duplicate`)
+pub fn find_rule(rule: &str) -> Option<&'static Rule> {
+    let rule = rule.trim();
+    RULES.iter().find(|known| known.code.eq_ignore_ascii_case(rule) || known.name == rule)
+}
+
+/// What `gix check` does with the findings of a rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// Not reported
+    Allow,
+    /// Reported, without failing the check
+    Warn,
+    /// Reported, failing the check (the default)
+    Deny,
+}
+
+impl Severity {
+    /// Get the name used on the command line and in config files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Allow => "allow",
+            Severity::Warn => "warn",
+            Severity::Deny => "deny",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = GixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "allow" => Ok(Severity::Allow),
+            "warn" => Ok(Severity::Warn),
+            "deny" => Ok(Severity::Deny),
+            other => Err(GixError::ParseError(format!("unknown severity '{}' (expected allow, warn or deny)", other))),
+        }
+    }
+}
+
+/// Severities of the rules that don't have the default, `Severity::Deny`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleSeverities {
+    overrides: HashMap<&'static str, Severity>,
+}
+
+impl RuleSeverities {
+    /// Give `rule` (a code or a name) the severity `severity`, replacing what it had
+    pub fn set(&mut self, rule: &str, severity: Severity) -> Result<(), GixError> {
+        let found = find_rule(rule).ok_or_else(|| GixError::UsageError(format!("unknown rule '{}' (see `gix rules`)", rule)))?;
+        self.overrides.insert(found.code, severity);
+        Ok(())
+    }
+
+    /// Get the severity of the rule with this code
+    pub fn rule_severity(&self, code: &str) -> Severity {
+        self.overrides.get(code).copied().unwrap_or(Severity::Deny)
+    }
+
+    /// Get the severity of issues of this kind
+    pub fn severity(&self, kind: LintKind) -> Severity {
+        self.rule_severity(kind.code())
+    }
+
+    /// Give each issue the severity of its rule, dropping those of allowed rules
+    pub fn apply(&self, issues: Vec<LintIssue>) -> Vec<LintIssue> {
+        issues.into_iter()
+            .map(|issue| LintIssue { severity: self.severity(issue.kind), ..issue })
+            .filter(|issue| issue.severity != Severity::Allow)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::lint::lint_gitignore;
+    use crate::core::parser::parse_gitignore;
+    use crate::core::validator::PatternProblem;
+
+    #[test]
+    fn test_every_kind_has_a_rule() {
+        assert_eq!(LintKind::Duplicate.code(), "GIX001");
+        assert_eq!(LintKind::Invalid(PatternProblem::UnclosedClass).code(), "GIX010");
+        assert!(PatternProblem::ALL.iter().all(|problem| LintKind::Invalid(*problem).code() >= "GIX010"));
+        assert_eq!(find_rule("gix002").map(|rule| rule.name), Some("redundant"));
+        assert!(find_rule("GIX999").is_none());
+    }
+
+    #[test]
+    fn test_rule_severities() {
+        let file = parse_gitignore("*.log\n*.log\nbuild/\nbuild/out\n!debug.log\n").unwrap();
+        let issues = lint_gitignore(&file).unwrap();
+        let mut severities = RuleSeverities::default();
+        assert_eq!(severities.apply(issues.clone()), issues);
+
+        severities.set("duplicate", Severity::Allow).unwrap();
+        severities.set("GIX003", Severity::Warn).unwrap();
+        let reported: Vec<(&str, Severity)> = severities.apply(issues).iter()
+            .map(|issue| (issue.kind.name(), issue.severity))
+            .collect();
+        assert_eq!(reported, vec![("redundant", Severity::Deny), ("conflict", Severity::Warn), ("conflict", Severity::Warn)]);
+
+        assert!(severities.set("typo", Severity::Deny).is_err());
+        assert_eq!("warn".parse::<Severity>().unwrap(), Severity::Warn);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::core::sorting::order_matters;
+use crate::models::{is_keep_marker, GitignoreEntry, GitignoreFile, Section};
+use crate::utils::patterns::GlobPattern;
+use std::collections::{HashMap, HashSet};
+
+/// Move the patterns of each section whose header repeats an earlier one under that earlier header
+///
+/// The repeated header goes, along with the blank lines ending its section. A section stays
+/// where it is when moving its patterns past the ones in between could change what is ignored,
+/// or when it has `# gix:off` lines or a `# gix:keep` marker in its header. Returns the file and
+/// the number of lines removed; line numbers are left as they were.
+pub fn merge_repeated_sections(file: &GitignoreFile) -> (GitignoreFile, usize) {
+    let sections = file.sections();
+    let keys: Vec<Option<String>> = sections.iter().map(header_key).collect();
+    let mut blocks: Vec<Vec<&GitignoreEntry>> = sections.iter().map(|section| section.header.iter().chain(section.entries).collect()).collect();
+    let mut first_with_key: HashMap<&str, usize> = HashMap::new();
+    let mut removed = 0;
+
+    // Only a negation and a plain pattern can depend on their order, and negations are few,
+    // so they are kept apart with the section they are in now
+    let glob_of = |entry: &GitignoreEntry| entry.comparison_pattern().map(|pattern| GlobPattern::parse(&pattern));
+    let mut negations: Vec<(usize, GlobPattern)> = blocks.iter()
+        .enumerate()
+        .flat_map(|(index, block)| block.iter().filter_map(|entry| glob_of(entry)).filter(|glob| glob.negated).map(move |glob| (index, glob)))
+        .collect();
+
+    for later in 0..sections.len() {
+        let Some(key) = keys[later].as_deref() else {
+            continue;
+        };
+        let earlier = *first_with_key.entry(key).or_insert(later);
+        if earlier == later {
+            continue;
+        }
+        let moving: Vec<&GitignoreEntry> = sections[later].entries.iter().filter(|entry| !entry.is_blank()).collect();
+        let moving_globs: Vec<GlobPattern> = moving.iter().filter_map(|entry| glob_of(entry)).collect();
+        let passes = |glob: &GlobPattern, passed: &GlobPattern| !order_matters(glob, passed);
+        let safe = if moving_globs.iter().any(|glob| glob.negated) {
+            let between: Vec<GlobPattern> = blocks[earlier + 1..later].iter().flatten().filter_map(|entry| glob_of(entry)).collect();
+            moving_globs.iter().all(|glob| between.iter().all(|passed| passes(glob, passed)))
+        } else {
+            negations.iter()
+                .filter(|(index, _)| (earlier + 1..later).contains(index))
+                .all(|(_, passed)| moving_globs.iter().all(|glob| passes(glob, passed)))
+        };
+        if !safe {
+            continue;
+        }
+
+        let insert_at = blocks[earlier].iter().rposition(|entry| !entry.is_blank()).map_or(0, |index| index + 1);
+        blocks[earlier].splice(insert_at..insert_at, moving.iter().copied());
+        removed += blocks[later].len() - moving.len();
+        blocks[later].clear();
+        for (index, _) in negations.iter_mut().filter(|(index, _)| *index == later) {
+            *index = earlier;
+        }
+    }
+
+    let mut merged = GitignoreFile::new();
+    merged.keep_format_of(file);
+    for entry in blocks.into_iter().flatten() {
+        merged.add_entry(entry.clone());
+    }
+    (merged, removed)
+}
+
+/// Remove the sections that deduplication left without patterns, with their blank lines
+///
+/// Only sections that had patterns in `original` go, so a comment block standing on its own
+/// (a license notice, say) stays. Blank lines left at the end of the file go too, unless
+/// `original` ended with one. Returns the file and the number of lines removed.
+pub fn remove_emptied_sections(original: &GitignoreFile, optimized: &GitignoreFile) -> (GitignoreFile, usize) {
+    let had_patterns: HashSet<usize> = original.sections().iter()
+        .filter(|section| section.patterns().next().is_some())
+        .flat_map(|section| section.header.iter().map(|entry| entry.line_number))
+        .collect();
+    let mut kept: Vec<&GitignoreEntry> = Vec::new();
+    let mut removed = 0;
+
+    for section in optimized.sections() {
+        let lines = section.header.iter().chain(section.entries);
+        let emptied = section.header.iter().any(|entry| had_patterns.contains(&entry.line_number))
+            && section.patterns().next().is_none()
+            && !lines.clone().any(|entry| entry.preserve);
+        if emptied {
+            removed += section.header.len() + section.entries.len();
+        } else {
+            kept.extend(lines);
+        }
+    }
+    if !original.entries.last().is_some_and(GitignoreEntry::is_blank) {
+        while kept.last().is_some_and(|entry| entry.is_blank()) {
+            kept.pop();
+            removed += 1;
+        }
+    }
+
+    let mut cleaned = GitignoreFile::new();
+    cleaned.keep_format_of(optimized);
+    for entry in kept {
+        cleaned.add_entry(entry.clone());
+    }
+    (cleaned, removed)
+}
+
+/// What makes two section headers the same: their lines without surrounding whitespace
+///
+/// Sections without a header, with a `# gix:keep` marker in it, or with `# gix:off` lines have none.
+fn header_key(section: &Section) -> Option<String> {
+    let lines = section.header.iter().chain(section.entries);
+    if section.header.is_empty() || section.header.iter().any(|entry| is_keep_marker(&entry.original)) || lines.clone().any(|entry| entry.preserve) {
+        return None;
+    }
+    Some(section.header.iter().map(|entry| entry.original.trim()).collect::<Vec<_>>().join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_merge_repeated_sections() {
+        let file = parse_gitignore("# Build\ntarget/\n\n# Logs\n*.log\n\n# Build\ndist/\n").unwrap();
+        let (merged, removed) = merge_repeated_sections(&file);
+        assert_eq!(format!("{}", merged), "# Build\ntarget/\ndist/\n\n# Logs\n*.log\n");
+        assert_eq!(removed, 1);
+
+        // dist/ can't move above the negation it would otherwise be overridden by
+        let file = parse_gitignore("# Build\ntarget/\n\n# Keep\n!dist/keep\n\n# Build\ndist/\n").unwrap();
+        assert_eq!(merge_repeated_sections(&file).1, 0);
+    }
+
+    #[test]
+    fn test_remove_emptied_sections() {
+        let original = parse_gitignore("# License notice\n\n# Logs\n*.log\n\n# More logs\n*.log\n\n# Build\nbuild/").unwrap();
+        let optimized = parse_gitignore("# License notice\n\n# Logs\n*.log\n\n# More logs\n\n# Build\nbuild/").unwrap();
+        let (cleaned, removed) = remove_emptied_sections(&original, &optimized);
+        assert_eq!(format!("{}", cleaned), "# License notice\n\n# Logs\n*.log\n\n# Build\nbuild/");
+        assert_eq!(removed, 2);
+
+        let optimized = parse_gitignore("# License notice\n\n# Logs\n*.log\n\n# More logs\n").unwrap();
+        assert_eq!(format!("{}", remove_emptied_sections(&original, &optimized).0), "# License notice\n\n# Logs\n*.log");
+    }
+}
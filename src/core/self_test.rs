@@ -0,0 +1,180 @@
+use crate::core::equivalence::probe_paths;
+use crate::core::parser::parse_gitignore;
+use crate::models::{GixError, PathMatch};
+use crate::utils::git::{check_ignore, git_version, init_scratch_repository};
+use crate::utils::patterns::GlobPattern;
+use std::fs;
+use std::path::Path;
+
+/// Patterns the corpus is built from, one for each feature of the syntax
+const PATTERNS: &[&str] = &[
+    "*.log", "debug.log", "/root.txt", "build/", "build", "logs/*", "logs/**", "**/cache",
+    "doc/**/*.md", "a?c", "*.py[co]", "[!a]*.tmp", "[[:digit:]]*.bak", "foo/bar", "foo/bar/",
+    "\\#notes", "\\!important", "trailing\\ ", "*",
+];
+
+/// Negations tried after each pattern, and before it
+const NEGATIONS: &[&str] = &["!debug.log", "!build/keep.txt", "!logs/keep/", "!*.md", "!/root.txt"];
+
+/// Paths no pattern of the corpus is about, checked in every case
+const BYSTANDERS: &[&str] = &["README.md", "src/main.rs", "sub/"];
+
+/// One gitignore file of the corpus and the paths checked against it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestCase {
+    /// Content of the file
+    pub content: String,
+    /// Paths relative to the file, directories with a trailing `/`
+    pub paths: Vec<String>,
+}
+
+/// A path git and gix decide differently
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Content of the gitignore file
+    pub content: String,
+    /// The path
+    pub path: String,
+    /// What `git check-ignore` decided
+    pub git: Option<PathMatch>,
+    /// What gix decided
+    pub gix: Option<PathMatch>,
+}
+
+impl Divergence {
+    /// Shell commands that reproduce git's decision in an empty repository
+    pub fn reproduce(&self) -> String {
+        let quote = |text: &str| format!("'{}'", text.replace('\'', "'\\''"));
+        let write = format!("printf '%s\\n' {} > .gitignore", self.content.lines().map(quote).collect::<Vec<_>>().join(" "));
+        match self.path.strip_suffix('/') {
+            Some(dir) => format!("{} && mkdir -p {} && git check-ignore --no-index -v -n {}", write, quote(dir), quote(dir)),
+            None => format!("{} && git check-ignore --no-index -v -n {}", write, quote(&self.path)),
+        }
+    }
+}
+
+/// Describe a decision for reports
+pub fn describe_decision(decision: &Option<PathMatch>) -> String {
+    match decision {
+        Some(decision) if decision.ignored => format!("ignored by line {}: {}", decision.line_number, decision.pattern),
+        Some(decision) => format!("re-included by line {}: {}", decision.line_number, decision.pattern),
+        None => "not ignored".to_string(),
+    }
+}
+
+/// The outcome of `gix self-test`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// The git that was compared against, as `git --version` prints it
+    pub git_version: String,
+    /// Number of gitignore files checked
+    pub cases: usize,
+    /// Number of path decisions compared
+    pub paths: usize,
+    /// Every path decided differently
+    pub divergences: Vec<Divergence>,
+}
+
+/// Build the corpus: each pattern alone, then with each negation after and before it
+pub fn self_test_corpus() -> Vec<SelfTestCase> {
+    let mut files: Vec<Vec<&str>> = PATTERNS.iter().map(|pattern| vec![*pattern]).collect();
+    for pattern in PATTERNS {
+        for negation in NEGATIONS {
+            files.push(vec![pattern, negation]);
+            files.push(vec![negation, pattern]);
+        }
+    }
+
+    files.into_iter()
+        .map(|patterns| {
+            let globs: Vec<GlobPattern> = patterns.iter().map(|pattern| GlobPattern::parse(pattern)).collect();
+            let mut paths = probe_paths(&globs);
+            paths.extend(BYSTANDERS.iter().map(|path| path.to_string()));
+            SelfTestCase { content: patterns.join("\n") + "\n", paths }
+        })
+        .collect()
+}
+
+/// Compare gix's decisions with the installed git's on every case of the corpus
+///
+/// `dir` must be an empty scratch directory; a repository is created in it, with each case
+/// in a directory of its own. Git runs without user or system configuration. It takes a
+/// trailing `/` on a path as part of the name, so files are checked first and directories
+/// after they are created, by their names alone.
+pub fn run_self_test(dir: &Path) -> Result<SelfTestReport, GixError> {
+    init_scratch_repository(dir)?;
+    let corpus = self_test_corpus();
+
+    // Every checked path as (case, path relative to its file, path relative to the repository)
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
+    for (index, case) in corpus.iter().enumerate() {
+        let case_dir = format!("case-{}", index + 1);
+        fs::create_dir_all(dir.join(&case_dir))?;
+        fs::write(dir.join(&case_dir).join(".gitignore"), &case.content)?;
+        for path in &case.paths {
+            match path.strip_suffix('/') {
+                Some(name) => dirs.push((index, path, format!("{}/{}", case_dir, name))),
+                None => files.push((index, path, format!("{}/{}", case_dir, path))),
+            }
+        }
+    }
+
+    let file_decisions = check_ignore(dir, &files.iter().map(|(_, _, path)| path.clone()).collect::<Vec<_>>())?;
+    for (_, _, path) in &dirs {
+        fs::create_dir_all(dir.join(path))?;
+    }
+    let dir_decisions = check_ignore(dir, &dirs.iter().map(|(_, _, path)| path.clone()).collect::<Vec<_>>())?;
+
+    let parsed = corpus.iter().map(|case| parse_gitignore(&case.content)).collect::<Result<Vec<_>, _>>()?;
+    let ignored = |decision: &Option<PathMatch>| decision.as_ref().is_some_and(|decision| decision.ignored);
+    let mut report = SelfTestReport {
+        git_version: git_version()?,
+        cases: corpus.len(),
+        paths: files.len() + dirs.len(),
+        divergences: Vec::new(),
+    };
+    for ((index, path, _), git) in files.into_iter().chain(dirs).zip(file_decisions.into_iter().chain(dir_decisions)) {
+        let gix = parsed[index].matches(path, false);
+        if ignored(&git) != ignored(&gix) {
+            report.divergences.push(Divergence { content: corpus[index].content.clone(), path: path.clone(), git, gix });
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_corpus() {
+        let corpus = self_test_corpus();
+        assert_eq!(corpus.len(), PATTERNS.len() * (1 + 2 * NEGATIONS.len()));
+        assert_eq!(corpus[0].content, "*.log\n");
+        assert!(corpus[0].paths.contains(&"sub/x.log".to_string()));
+        assert!(corpus[0].paths.contains(&"README.md".to_string()));
+    }
+
+    #[test]
+    fn test_run_self_test_agrees_with_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = run_self_test(dir.path()).unwrap();
+
+        assert!(report.git_version.starts_with("git version"));
+        assert!(report.paths > report.cases);
+        assert_eq!(report.divergences, Vec::new(), "{:?}", report.divergences.first().map(Divergence::reproduce));
+    }
+
+    #[test]
+    fn test_divergence_reproduce() {
+        let divergence = Divergence { content: "*.log\n!it's.log\n".to_string(), path: "it's.log".to_string(), git: None, gix: None };
+        assert_eq!(
+            divergence.reproduce(),
+            "printf '%s\\n' '*.log' '!it'\\''s.log' > .gitignore && git check-ignore --no-index -v -n 'it'\\''s.log'"
+        );
+        let divergence = Divergence { path: "logs/".to_string(), ..divergence };
+        assert!(divergence.reproduce().ends_with("&& mkdir -p 'logs' && git check-ignore --no-index -v -n 'logs'"));
+        assert_eq!(describe_decision(&None), "not ignored");
+    }
+}
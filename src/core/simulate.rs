@@ -0,0 +1,175 @@
+use crate::core::equivalence::decision_changes;
+use crate::core::pattern_analyzer::PatternAnalyzer;
+use crate::core::validator::validate_pattern;
+use crate::models::{EntryType, GitignoreEntry, GitignoreFile, GixError};
+use std::collections::HashSet;
+
+/// What appending a pattern to a gitignore file would do, worked out without writing anything
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdditionEffect {
+    /// The pattern that would be added
+    pub pattern: String,
+    /// Tracked files the pattern would ignore; git keeps tracking them regardless
+    pub tracked: Vec<String>,
+    /// Untracked files not ignored today that the pattern would ignore
+    pub newly_ignored: Vec<String>,
+    /// Files ignored today that the pattern, a negation, would re-include
+    pub reincluded: Vec<String>,
+    /// Line number and text of an existing pattern the new one repeats
+    pub duplicate_of: Option<(usize, String)>,
+    /// Line numbers and text of existing patterns of opposite polarity that can match the same paths
+    pub conflicts: Vec<(usize, String)>,
+}
+
+impl AdditionEffect {
+    /// Check if adding the pattern would change nothing about which files are ignored
+    pub fn is_noop(&self) -> bool {
+        self.tracked.is_empty() && self.newly_ignored.is_empty() && self.reincluded.is_empty()
+    }
+}
+
+/// Work out what appending `pattern` to the end of `file` would do
+///
+/// `paths` are the files of the working tree and `tracked` the files git tracks, both relative
+/// to the directory holding the file; directories (with a trailing `/`) in `paths` are skipped.
+pub fn simulate_addition(file: &GitignoreFile, pattern: &str, paths: &[String], tracked: &[String]) -> Result<AdditionEffect, GixError> {
+    validate_pattern(pattern)?;
+    let mut after = file.clone();
+    let line_number = file.entries.last().map_or(0, |entry| entry.line_number) + 1;
+    after.add_entry(GitignoreEntry::new(pattern.to_string(), EntryType::Pattern(pattern.to_string()), line_number));
+
+    let tracked_set: HashSet<&str> = tracked.iter().map(String::as_str).collect();
+    let mut files: Vec<String> = paths.iter().filter(|path| !path.ends_with('/')).cloned().collect();
+    files.extend(tracked.iter().filter(|path| !paths.contains(path)).cloned());
+    files.sort();
+    let mut effect = AdditionEffect {
+        pattern: pattern.to_string(),
+        tracked: Vec::new(),
+        newly_ignored: Vec::new(),
+        reincluded: Vec::new(),
+        duplicate_of: None,
+        conflicts: Vec::new(),
+    };
+    for change in decision_changes(file, &after, &files) {
+        match (change.ignored_after, tracked_set.contains(change.path.as_str())) {
+            (true, true) => effect.tracked.push(change.path),
+            (true, false) => effect.newly_ignored.push(change.path),
+            (false, _) => effect.reincluded.push(change.path),
+        }
+    }
+
+    let existing: Vec<(usize, String)> = file.entries.iter()
+        .filter_map(|entry| Some((entry.line_number, entry.comparison_pattern()?)))
+        .collect();
+    effect.duplicate_of = existing.iter().find(|(_, existing)| existing == pattern).cloned();
+    let mut patterns: Vec<&str> = existing.iter().map(|(_, pattern)| pattern.as_str()).collect();
+    patterns.push(pattern);
+    effect.conflicts = PatternAnalyzer::safe().conflicting_pairs(&patterns).into_iter()
+        .filter_map(|(a, b)| match (a == existing.len(), b == existing.len()) {
+            (true, false) => Some(existing[b].clone()),
+            (false, true) => Some(existing[a].clone()),
+            _ => None,
+        })
+        .collect();
+    Ok(effect)
+}
+
+/// What removing patterns from a gitignore file would do, worked out without writing anything
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovalEffect {
+    /// Line numbers and text of the patterns removed
+    pub removed: Vec<(usize, String)>,
+    /// Untracked files ignored today that would show up in `git status` once the patterns are gone
+    pub exposed: Vec<String>,
+    /// Untracked files not ignored today that would be, once a negation among the patterns is gone
+    pub newly_ignored: Vec<String>,
+}
+
+impl RemovalEffect {
+    /// Check if removing the patterns would change nothing about which files are ignored
+    pub fn is_noop(&self) -> bool {
+        self.exposed.is_empty() && self.newly_ignored.is_empty()
+    }
+}
+
+/// Work out what removing the patterns on `line_numbers` from `file` would do
+///
+/// `paths` and `tracked` are as for `simulate_addition`; tracked files are left out, since git
+/// shows them whatever the file says. The file without the patterns is returned with the effect.
+pub fn simulate_removal(file: &GitignoreFile, line_numbers: &[usize], paths: &[String], tracked: &[String]) -> (GitignoreFile, RemovalEffect) {
+    let mut after = file.clone();
+    after.remove_lines(line_numbers);
+
+    let tracked_set: HashSet<&str> = tracked.iter().map(String::as_str).collect();
+    let files: Vec<String> = paths.iter().filter(|path| !path.ends_with('/') && !tracked_set.contains(path.as_str())).cloned().collect();
+    let mut effect = RemovalEffect {
+        removed: file.entries.iter()
+            .filter(|entry| entry.is_pattern() && line_numbers.contains(&entry.line_number))
+            .map(|entry| (entry.line_number, entry.original.clone()))
+            .collect(),
+        exposed: Vec::new(),
+        newly_ignored: Vec::new(),
+    };
+    for change in decision_changes(file, &after, &files) {
+        match change.ignored_after {
+            true => effect.newly_ignored.push(change.path),
+            false => effect.exposed.push(change.path),
+        }
+    }
+    (after, effect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    fn strings(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|path| path.to_string()).collect()
+    }
+
+    #[test]
+    fn test_simulate_addition() {
+        let file = parse_gitignore("*.log\n!keep/\n").unwrap();
+        let paths = strings(&["dist/", "dist/app.js", "dist/map.js", "keep/", "keep/dist/x", "src/main.rs", "debug.log"]);
+        let tracked = strings(&["dist/app.js", "src/main.rs"]);
+
+        let effect = simulate_addition(&file, "dist/", &paths, &tracked).unwrap();
+        assert_eq!(effect.tracked, vec!["dist/app.js"]);
+        assert_eq!(effect.newly_ignored, vec!["dist/map.js", "keep/dist/x"]);
+        assert!(effect.duplicate_of.is_none());
+        assert!(effect.conflicts.is_empty());
+        assert!(!effect.is_noop());
+    }
+
+    #[test]
+    fn test_simulate_duplicate_and_conflict() {
+        let file = parse_gitignore("*.log\nbuild/\n").unwrap();
+        let paths = strings(&["debug.log", "app.log"]);
+
+        let duplicate = simulate_addition(&file, "*.log", &paths, &[]).unwrap();
+        assert_eq!(duplicate.duplicate_of, Some((1, "*.log".to_string())));
+        assert!(duplicate.is_noop());
+
+        let negation = simulate_addition(&file, "!debug.log", &paths, &[]).unwrap();
+        assert_eq!(negation.conflicts, vec![(1, "*.log".to_string())]);
+        assert_eq!(negation.reincluded, vec!["debug.log"]);
+        assert!(simulate_addition(&file, "  ", &paths, &[]).is_err());
+    }
+
+    #[test]
+    fn test_simulate_removal() {
+        let file = parse_gitignore("*.log\n!keep.log\n\nbuild/\n").unwrap();
+        let paths = strings(&["build/", "build/app.js", "build/tracked.js", "debug.log", "keep.log"]);
+        let tracked = strings(&["build/tracked.js"]);
+
+        let (after, effect) = simulate_removal(&file, &[4], &paths, &tracked);
+        assert_eq!(format!("{}", after), "*.log\n!keep.log");
+        assert_eq!(effect.removed, vec![(4, "build/".to_string())]);
+        assert_eq!(effect.exposed, vec!["build/app.js"]);
+
+        let (_, effect) = simulate_removal(&file, &[2], &paths, &tracked);
+        assert_eq!(effect.newly_ignored, vec!["keep.log"]);
+        assert!(simulate_removal(&file, &[3], &paths, &tracked).1.removed.is_empty());
+    }
+}
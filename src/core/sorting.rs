@@ -0,0 +1,94 @@
+use crate::models::{is_keep_marker, EntryType, GitignoreEntry, GitignoreFile};
+use crate::utils::patterns::GlobPattern;
+
+/// Sort the patterns of each section by `key`, without changing which paths are ignored
+///
+/// A section is a run of patterns between comments and blank lines; nothing moves across
+/// them. Within a section a negation and a pattern it could override, or be overridden by,
+/// keep their relative order, and otherwise ties keep the original order. Patterns in a
+/// `# gix:off` region and patterns marked `# gix:keep` stay where they are, and line numbers
+/// are left as they were.
+pub fn sort_sections<K: Ord>(file: &GitignoreFile, key: impl Fn(&str) -> K) -> GitignoreFile {
+    let mut sorted = GitignoreFile::new();
+    sorted.keep_format_of(file);
+
+    let mut section: Vec<&GitignoreEntry> = Vec::new();
+    let mut after_marker = false;
+    for entry in &file.entries {
+        let movable = entry.is_pattern() && !entry.preserve && !after_marker;
+        after_marker = matches!(&entry.entry_type, EntryType::Comment(comment) if is_keep_marker(comment));
+        if movable {
+            section.push(entry);
+            continue;
+        }
+
+        for sorted_entry in sort_section(&section, &key) {
+            sorted.add_entry(sorted_entry.clone());
+        }
+        section.clear();
+        sorted.add_entry(entry.clone());
+    }
+    for sorted_entry in sort_section(&section, &key) {
+        sorted.add_entry(sorted_entry.clone());
+    }
+    sorted
+}
+
+/// Check if swapping two patterns could change what is ignored
+///
+/// Only a negation and a plain pattern that can meet on a path depend on their order.
+pub fn order_matters(a: &GlobPattern, b: &GlobPattern) -> bool {
+    a.negated != b.negated && (a.may_overlap(b) || a.covers_parent_of(b) || b.covers_parent_of(a))
+}
+
+/// Order one section: repeatedly take the smallest pattern that no unplaced pattern must precede
+fn sort_section<'a, K: Ord>(section: &[&'a GitignoreEntry], key: &impl Fn(&str) -> K) -> Vec<&'a GitignoreEntry> {
+    let patterns: Vec<String> = section.iter().map(|entry| entry.comparison_pattern().unwrap_or_default()).collect();
+    let globs: Vec<GlobPattern> = patterns.iter().map(|pattern| GlobPattern::parse(pattern)).collect();
+    let keys: Vec<K> = patterns.iter().map(|pattern| key(pattern)).collect();
+
+    let must_precede = |earlier: usize, later: usize| order_matters(&globs[earlier], &globs[later]);
+
+    let mut placed = vec![false; section.len()];
+    let mut order = Vec::with_capacity(section.len());
+    while order.len() < section.len() {
+        let next = (0..section.len())
+            .filter(|&candidate| !placed[candidate])
+            .filter(|&candidate| (0..candidate).all(|earlier| placed[earlier] || !must_precede(earlier, candidate)))
+            .min_by(|&a, &b| keys[a].cmp(&keys[b]).then(a.cmp(&b)))
+            .expect("the earliest unplaced pattern is always free to go");
+        placed[next] = true;
+        order.push(section[next]);
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::equivalence::files_equivalent;
+    use crate::core::parser::parse_gitignore;
+
+    fn alpha(pattern: &str) -> String {
+        pattern.trim_start_matches('!').to_string()
+    }
+
+    #[test]
+    fn test_sort_within_sections() {
+        let file = parse_gitignore("# Build\ntarget/\ndist/\n\n*.tmp\n*.bak\n# gix:keep\nzz\naa").unwrap();
+        let sorted = sort_sections(&file, alpha);
+        assert_eq!(format!("{}", sorted), "# Build\ndist/\ntarget/\n\n*.bak\n*.tmp\n# gix:keep\nzz\naa");
+        assert_eq!(sorted.entries[1].line_number, 3);
+    }
+
+    #[test]
+    fn test_sort_keeps_negations_after_what_they_override() {
+        let file = parse_gitignore("*.log\n!debug.log\nbuild/\naudit.log\n").unwrap();
+        let sorted = sort_sections(&file, alpha);
+        assert_eq!(format!("{}", sorted), "*.log\naudit.log\nbuild/\n!debug.log");
+        assert!(files_equivalent(&file, &sorted, &[]).is_equivalent());
+
+        let file = parse_gitignore("!keep.tmp\nz.txt\n*.tmp\n").unwrap();
+        assert_eq!(format!("{}", sort_sections(&file, alpha)), "!keep.tmp\n*.tmp\nz.txt");
+    }
+}
@@ -0,0 +1,277 @@
+use crate::core::optimizer::{find_pattern_conflicts, optimize_gitignore_advanced};
+use crate::core::pattern_analyzer::PatternAnalyzer;
+use crate::core::parser::parse_gitignore;
+use crate::core::validator::is_valid_pattern;
+use crate::models::{FileStats, GixError};
+use crate::utils::read_gitignore_file;
+use crate::utils::walker::{find_files, WalkOptions, WalkedFile};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Names of the ignore files collected by a scan; all use gitignore syntax
+pub const IGNORE_FILE_NAMES: &[&str] = &[".gitignore", ".dockerignore", ".npmignore"];
+
+/// Options controlling which parts of a checkout a scan visits
+pub type ScanOptions = WalkOptions;
+
+/// Findings for one ignore file in the repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedFile {
+    /// Path relative to the scanned root, with `/` separators
+    pub path: String,
+    /// Line counts of the file
+    pub stats: FileStats,
+    /// Pattern lines that repeat an earlier pattern
+    pub duplicate_lines: usize,
+    /// Patterns fully covered by a broader pattern
+    pub redundant_patterns: usize,
+    /// Pairs of patterns of opposite polarity that can match the same path
+    pub conflicts: usize,
+    /// Patterns that fail validation
+    pub invalid_patterns: usize,
+}
+
+impl ScannedFile {
+    /// Score from 0 to 100; every finding costs points, weighted by how likely it is a mistake
+    pub fn health(&self) -> usize {
+        let penalty = self.invalid_patterns * 10
+            + self.duplicate_lines * 5
+            + self.redundant_patterns * 2
+            + self.conflicts;
+        100usize.saturating_sub(penalty)
+    }
+}
+
+/// Summary of every ignore file below a directory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepositorySummary {
+    /// Scanned files, sorted by path
+    pub files: Vec<ScannedFile>,
+    /// Patterns repeated across files of the same kind, with the files they appear in
+    pub cross_file_duplicates: Vec<(String, Vec<String>)>,
+}
+
+impl RepositorySummary {
+    /// Average health of all files, or 100 when there are none
+    pub fn health(&self) -> usize {
+        if self.files.is_empty() {
+            return 100;
+        }
+        self.files.iter().map(ScannedFile::health).sum::<usize>() / self.files.len()
+    }
+
+    /// Render the summary as Markdown; the output only depends on the files' contents
+    pub fn render_markdown(&self) -> String {
+        let mut lines = vec![
+            "# gix summary".to_string(),
+            String::new(),
+            "Generated by `gix scan --write-summary`; regenerate it instead of editing by hand.".to_string(),
+            String::new(),
+            format!("Ignore files: {}", self.files.len()),
+            format!("Overall health: {}/100", self.health()),
+            String::new(),
+            "## Files".to_string(),
+            String::new(),
+        ];
+
+        if self.files.is_empty() {
+            lines.push("No ignore files found.".to_string());
+        } else {
+            lines.push("| File | Lines | Patterns | Comments | Blank | Duplicates | Redundant | Conflicts | Invalid | Health |".to_string());
+            lines.push("|------|------:|---------:|---------:|------:|-----------:|----------:|----------:|--------:|-------:|".to_string());
+            for file in &self.files {
+                lines.push(format!(
+                    "| `{}` | {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+                    file.path,
+                    file.stats.total_lines,
+                    file.stats.pattern_lines,
+                    file.stats.comment_lines,
+                    file.stats.blank_lines,
+                    file.duplicate_lines,
+                    file.redundant_patterns,
+                    file.conflicts,
+                    file.invalid_patterns,
+                    file.health()
+                ));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push("## Cross-file duplicates".to_string());
+        lines.push(String::new());
+        if self.cross_file_duplicates.is_empty() {
+            lines.push("None.".to_string());
+        } else {
+            for (pattern, files) in &self.cross_file_duplicates {
+                let files: Vec<String> = files.iter().map(|file| format!("`{}`", file)).collect();
+                lines.push(format!("- `{}` in {}", pattern, files.join(", ")));
+            }
+        }
+
+        let mut markdown = lines.join("\n");
+        markdown.push('\n');
+        markdown
+    }
+}
+
+/// Scan `root` for ignore files, skipping `.git` and directories ignored by a `.gitignore` above them
+///
+/// The repository's `info/exclude` is included, resolved through the common git dir so linked
+/// worktrees report the file shared by all worktrees. Nested repositories such as submodules
+/// have their own ignore rules and are skipped unless `include_submodules` is set.
+pub fn scan_repository(root: &Path, options: &ScanOptions) -> Result<RepositorySummary, GixError> {
+    let walked = find_files(root, IGNORE_FILE_NAMES, options)?;
+    let mut found = walked.files;
+    found.extend(walked.exclude_files);
+    found.sort();
+
+    let mut summary = RepositorySummary::default();
+    // Keyed by file name and pattern, so sorted output falls out of the map
+    let mut occurrences: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+
+    for WalkedFile { path, location } in found {
+        let file = parse_gitignore(&read_gitignore_file(&location)?)?;
+        let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+        let mut seen = Vec::new();
+        let mut invalid_patterns = 0;
+        for entry in &file.entries {
+            let Some(pattern) = entry.comparison_pattern() else {
+                continue;
+            };
+            if !is_valid_pattern(&pattern) {
+                invalid_patterns += 1;
+            }
+            if !seen.contains(&pattern) {
+                occurrences.entry((name.clone(), pattern.clone())).or_default().push(path.clone());
+                seen.push(pattern);
+            }
+        }
+
+        // Exact duplicates are counted separately, so redundancy is measured after deduplication
+        let (_, redundant) = optimize_gitignore_advanced(&file, &PatternAnalyzer::safe())?;
+        summary.files.push(ScannedFile {
+            path,
+            duplicate_lines: file.find_duplicates().redundant_lines(),
+            redundant_patterns: redundant.len(),
+            conflicts: find_pattern_conflicts(&file).len(),
+            invalid_patterns,
+            stats: file.stats,
+        });
+    }
+
+    summary.cross_file_duplicates = occurrences.into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|((_, pattern), files)| (pattern, files))
+        .collect();
+
+    Ok(summary)
+}
+
+/// Write the summary, leaving the file untouched when nothing changed; returns whether it was written
+pub fn write_summary(path: &Path, summary: &RepositorySummary) -> Result<bool, GixError> {
+    let content = summary.render_markdown();
+    if fs::read_to_string(path).ok().as_deref() == Some(content.as_str()) {
+        return Ok(false);
+    }
+
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    crate::utils::write_gitignore_file(path, &content)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.log\n*.log\nbuild/\n").unwrap();
+        fs::create_dir_all(dir.path().join("app")).unwrap();
+        fs::write(dir.path().join("app/.gitignore"), "*.log\n*.tmp\n!keep.tmp\n").unwrap();
+        fs::write(dir.path().join("app/.dockerignore"), "*.log\n").unwrap();
+        fs::create_dir_all(dir.path().join("build/gen")).unwrap();
+        fs::write(dir.path().join("build/gen/.gitignore"), "*\n").unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".git/.gitignore"), "*\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_scan_repository() {
+        let dir = repository();
+        let summary = scan_repository(dir.path(), &ScanOptions::default()).unwrap();
+
+        let paths: Vec<&str> = summary.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec![".gitignore", "app/.dockerignore", "app/.gitignore"]);
+
+        assert_eq!(summary.files[0].duplicate_lines, 1);
+        assert_eq!(summary.files[0].health(), 95);
+        assert_eq!(summary.files[2].conflicts, 1);
+
+        // Only files of the same kind count as cross-file duplicates
+        assert_eq!(summary.cross_file_duplicates, vec![(
+            "*.log".to_string(),
+            vec![".gitignore".to_string(), "app/.gitignore".to_string()],
+        )]);
+    }
+
+    #[test]
+    fn test_scan_skips_submodules_unless_included() {
+        let dir = repository();
+        fs::create_dir_all(dir.path().join(".git/info")).unwrap();
+        fs::write(dir.path().join(".git/info/exclude"), "*.local\n").unwrap();
+        fs::create_dir_all(dir.path().join("vendor/lib")).unwrap();
+        fs::write(dir.path().join("vendor/lib/.git"), "gitdir: ../../.git/modules/lib\n").unwrap();
+        fs::write(dir.path().join("vendor/lib/.gitignore"), "*.log\n").unwrap();
+
+        let summary = scan_repository(dir.path(), &ScanOptions::default()).unwrap();
+        let paths: Vec<&str> = summary.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec![".git/info/exclude", ".gitignore", "app/.dockerignore", "app/.gitignore"]);
+
+        let options = ScanOptions { include_submodules: true, ..ScanOptions::default() };
+        let summary = scan_repository(dir.path(), &options).unwrap();
+        assert!(summary.files.iter().any(|file| file.path == "vendor/lib/.gitignore"));
+    }
+
+    #[test]
+    fn test_scan_excludes_paths() {
+        let dir = repository();
+        fs::create_dir_all(dir.path().join("vendor/pkg")).unwrap();
+        fs::write(dir.path().join("vendor/pkg/.gitignore"), "*.o\n").unwrap();
+        fs::create_dir_all(dir.path().join("libs/third_party")).unwrap();
+        fs::write(dir.path().join("libs/third_party/.gitignore"), "*.a\n").unwrap();
+
+        let options = ScanOptions {
+            exclude_paths: vec!["vendor".to_string(), "libs/third_*".to_string(), "/app".to_string()],
+            ..ScanOptions::default()
+        };
+        let summary = scan_repository(dir.path(), &options).unwrap();
+        let paths: Vec<&str> = summary.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec![".gitignore"]);
+    }
+
+    #[test]
+    fn test_write_summary_is_idempotent() {
+        let dir = repository();
+        let path = dir.path().join(".gix/summary.md");
+        let summary = scan_repository(dir.path(), &ScanOptions::default()).unwrap();
+
+        assert!(write_summary(&path, &summary).unwrap());
+        let first = fs::read_to_string(&path).unwrap();
+        assert!(first.contains("| `app/.gitignore` |"));
+
+        assert!(!write_summary(&path, &scan_repository(dir.path(), &ScanOptions::default()).unwrap()).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), first);
+    }
+
+    #[test]
+    fn test_empty_repository_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let summary = scan_repository(dir.path(), &ScanOptions::default()).unwrap();
+        assert_eq!(summary.health(), 100);
+        assert!(summary.render_markdown().contains("No ignore files found."));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::core::categorizer::{PatternCategorizer, PatternCategory};
+use crate::core::comment_generator::CommentGenerator;
+use crate::core::merge::{reorder_negations, MergedDuplicate, ReorderedNegation};
+use crate::core::pattern_analyzer::PatternAnalyzer;
+use crate::models::{EntryType, GitignoreEntry, GitignoreFile};
+use crate::utils::patterns::GlobPattern;
+use std::collections::HashMap;
+
+/// A gitignore file tailored from another repository's file
+#[derive(Debug, Clone)]
+pub struct TailoredGitignore {
+    /// The sections relevant to the target project
+    pub file: GitignoreFile,
+    /// Names of the sections that were kept
+    pub kept: Vec<String>,
+    /// Names of the sections that were dropped as belonging to another stack
+    pub dropped: Vec<String>,
+}
+
+/// Keep the sections of `source` that apply to a project built with `stack`
+///
+/// Sections are runs of lines separated by blank lines. A section whose patterns belong to a
+/// language or framework is kept only if one of its patterns is known to a category in `stack`,
+/// even when another stack claims it first. Sections of tools, operating systems and
+/// project-specific patterns apply to any project and are always kept. Sections with no
+/// patterns at all are dropped, since their comments describe nothing in the new file.
+pub fn tailor_gitignore(source: &GitignoreFile, stack: &[PatternCategory], categorizer: &PatternCategorizer) -> TailoredGitignore {
+    let mut tailored = TailoredGitignore { file: GitignoreFile::new(), kept: Vec::new(), dropped: Vec::new() };
+
+    for section in source.entries.split(|entry| entry.is_blank()) {
+        let patterns: Vec<String> = section.iter()
+            .filter_map(|entry| entry.comparison_pattern())
+            .map(|pattern| pattern.trim_start_matches('!').to_string())
+            .collect();
+        let Some(first) = patterns.first() else {
+            continue;
+        };
+
+        // Patterns shared by several stacks count for each of them
+        let stack_specific = patterns.iter()
+            .map(|pattern| categorizer.primary_category(pattern))
+            .find(|category| matches!(category, PatternCategory::Language(_) | PatternCategory::Framework(_)));
+        let relevant = patterns.iter()
+            .any(|pattern| categorizer.matching_categories(pattern).iter().any(|category| stack.contains(category)));
+        let name = section_name(section, stack_specific.as_ref().unwrap_or(&categorizer.primary_category(first)));
+        if stack_specific.is_some() && !relevant {
+            tailored.dropped.push(name);
+            continue;
+        }
+
+        if !tailored.file.entries.is_empty() {
+            let line_number = tailored.file.entries.len() + 1;
+            tailored.file.add_entry(GitignoreEntry::new(String::new(), EntryType::Blank, line_number));
+        }
+        for entry in section {
+            let mut entry = entry.clone();
+            entry.line_number = tailored.file.entries.len() + 1;
+            tailored.file.add_entry(entry);
+        }
+        tailored.kept.push(name);
+    }
+
+    tailored
+}
+
+/// A gitignore file composed from the pattern sets of several categories
+#[derive(Debug, Clone)]
+pub struct ComposedGitignore {
+    /// One section per category that has patterns, then any reordered negations
+    pub file: GitignoreFile,
+    /// Patterns left out because an earlier category's section already has them
+    pub duplicates: Vec<MergedDuplicate>,
+    /// Negations moved below the later sections they re-include paths from
+    pub reordered: Vec<ReorderedNegation>,
+}
+
+/// Generate a gitignore file with a section of known patterns for each category, in the order given
+///
+/// See `compose_templates`; this keeps only the file.
+pub fn generate_gitignore(categories: &[PatternCategory], categorizer: &PatternCategorizer, generator: &CommentGenerator) -> GitignoreFile {
+    compose_templates(categories, categorizer, generator).file
+}
+
+/// Compose the pattern sets of several categories into one file, in the order given
+///
+/// Each section starts with the generator's header and category description. Patterns shared
+/// by several categories (`target/` for Java and Rust), or spelled differently but matching the
+/// same paths, are only written in the first section that has them, and a section left
+/// without patterns is omitted. As in `gix merge`, a negation that a later section's pattern
+/// would override moves to a final section, so it still re-includes what it was written for.
+pub fn compose_templates(categories: &[PatternCategory], categorizer: &PatternCategorizer, generator: &CommentGenerator) -> ComposedGitignore {
+    let analyzer = PatternAnalyzer::safe();
+    let mut seen: HashMap<String, String> = HashMap::new();
+    let mut duplicates = Vec::new();
+    let mut composed: Vec<(GitignoreEntry, usize)> = Vec::new();
+    let labels: Vec<String> = categories.iter().map(PatternCategory::short_name).collect();
+    let entry = |line: String, entry_type: EntryType| GitignoreEntry::new(line, entry_type, 0);
+
+    for (index, category) in categories.iter().enumerate() {
+        let mut patterns = Vec::new();
+        for (position, pattern) in categorizer.patterns_of(category).iter().enumerate() {
+            match seen.get(&analyzer.dedup_key(pattern)) {
+                Some(kept_from) if *kept_from != labels[index] => duplicates.push(MergedDuplicate {
+                    source: labels[index].clone(),
+                    line_number: position + 1,
+                    pattern: pattern.clone(),
+                    kept_from: kept_from.clone(),
+                }),
+                Some(_) => {}
+                None => {
+                    seen.insert(analyzer.dedup_key(pattern), labels[index].clone());
+                    patterns.push(pattern);
+                }
+            }
+        }
+        if patterns.is_empty() {
+            continue;
+        }
+
+        if !composed.is_empty() {
+            composed.push((entry(String::new(), EntryType::Blank), index));
+        }
+        let mut header = vec![generator.generate_section_header(category)];
+        if let Some(description) = generator.generate_category_comment(category) {
+            header.push(format!("# {}", description));
+        }
+        for comment in header {
+            composed.push((entry(comment.clone(), EntryType::Comment(comment)), index));
+        }
+        for pattern in patterns {
+            composed.push((entry(pattern.clone(), EntryType::Pattern(pattern.clone())), index));
+        }
+    }
+
+    let (entries, reordered) = reorder_negations(composed, &labels);
+    let mut file = GitignoreFile::new();
+    for (index, mut entry) in entries.into_iter().enumerate() {
+        entry.line_number = index + 1;
+        file.add_entry(entry);
+    }
+    ComposedGitignore { file, duplicates, reordered }
+}
+
+/// How far a file has drifted from a template
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateDrift {
+    /// Template patterns the file lacks
+    pub missing: Vec<String>,
+    /// Patterns of the file the template doesn't have, with their line numbers
+    pub extra: Vec<(usize, String)>,
+    /// Template patterns the file has
+    pub shared: usize,
+}
+
+impl TemplateDrift {
+    /// Share of the patterns in either file that only one of them has, from 0 to 100
+    pub fn drift_percent(&self) -> f64 {
+        let total = self.shared + self.missing.len() + self.extra.len();
+        if total == 0 {
+            return 0.0;
+        }
+        (self.missing.len() + self.extra.len()) as f64 * 100.0 / total as f64
+    }
+}
+
+/// Compare the patterns of `file` with those of `template`
+///
+/// A template pattern is present when a file pattern with the same negation covers it or a
+/// directory above it, so `.vscode/` in the file accounts for the template's `.vscode/*`. A
+/// file pattern is extra when it is unrelated to every template pattern that way, in either
+/// direction: a broader `target` is not extra next to the template's `/target/`.
+pub fn compare_with_template(file: &GitignoreFile, template: &GitignoreFile) -> TemplateDrift {
+    let globs = |file: &GitignoreFile| -> Vec<(usize, String, GlobPattern)> {
+        file.entries.iter()
+            .filter_map(|entry| entry.comparison_pattern().map(|pattern| (entry.line_number, pattern)))
+            .map(|(line_number, pattern)| {
+                let glob = GlobPattern::parse(&pattern);
+                (line_number, pattern, glob)
+            })
+            .collect()
+    };
+    let covers = |glob: &GlobPattern, wanted: &GlobPattern| {
+        glob.negated == wanted.negated && (glob.covers(wanted) || glob.covers_parent_of(wanted))
+    };
+    let local = globs(file);
+    let standard = globs(template);
+
+    let mut drift = TemplateDrift::default();
+    for (_, pattern, wanted) in &standard {
+        match local.iter().any(|(_, _, glob)| covers(glob, wanted)) {
+            true => drift.shared += 1,
+            false => drift.missing.push(pattern.clone()),
+        }
+    }
+    drift.extra = local.into_iter()
+        .filter(|(_, _, glob)| !standard.iter().any(|(_, _, wanted)| covers(wanted, glob) || covers(glob, wanted)))
+        .map(|(line_number, pattern, _)| (line_number, pattern))
+        .collect();
+    drift
+}
+
+/// Name a section after its first comment, or after its category when it has none
+fn section_name(section: &[GitignoreEntry], category: &PatternCategory) -> String {
+    section.iter()
+        .find_map(|entry| match &entry.entry_type {
+            EntryType::Comment(comment) => Some(comment.trim_start_matches('#').trim().to_string()),
+            _ => None,
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| category.display_name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_tailor_keeps_sections_of_the_detected_stack() {
+        let source = parse_gitignore("# Rust\ntarget/\nCargo.lock\n\n# Java\n*.class\n\n# Editors\n.vscode/\n\n# Local\nlocal/\n").unwrap();
+        let stack = vec![PatternCategory::Language("Rust".to_string())];
+        let tailored = tailor_gitignore(&source, &stack, &PatternCategorizer::new());
+
+        assert_eq!(tailored.kept, vec!["Rust", "Editors", "Local"]);
+        assert_eq!(tailored.dropped, vec!["Java"]);
+        assert_eq!(format!("{}", tailored.file), "# Rust\ntarget/\nCargo.lock\n\n# Editors\n.vscode/\n\n# Local\nlocal/");
+        let lines: Vec<usize> = tailored.file.entries.iter().map(|entry| entry.line_number).collect();
+        assert_eq!(lines, (1..=9).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_generate_sections_for_each_category() {
+        let categories = vec![
+            PatternCategory::Language("Java".to_string()),
+            PatternCategory::Language("Rust".to_string()),
+            PatternCategory::OperatingSystem("macOS".to_string()),
+        ];
+        let file = generate_gitignore(&categories, &PatternCategorizer::new(), &CommentGenerator::new());
+        let content = format!("{}", file);
+
+        assert!(content.starts_with("# Java\n"));
+        assert!(content.contains("\n\n# Rust\n"));
+        assert!(content.contains("\n\n# macOS\n"));
+        assert!(content.contains(".DS_Store"));
+        // Shared patterns are only written once
+        assert_eq!(content.lines().filter(|line| *line == "target/").count(), 1);
+        let lines: Vec<usize> = file.entries.iter().map(|entry| entry.line_number).collect();
+        assert_eq!(lines, (1..=file.entries.len()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg(feature = "dataset")]
+    fn test_compose_reorders_cross_template_negations() {
+        use crate::core::dataset::Dataset;
+        let dataset = Dataset::parse("version = 1\n\n[language Java]\n*.jar\n!gradle-wrapper.jar\nbuild/\n\n[tool Packaging]\n/build/\n*.jar\n*.[jw]ar\n").unwrap();
+        let categorizer = PatternCategorizer::from_dataset(&dataset);
+        let categories = vec![PatternCategory::Language("Java".to_string()), PatternCategory::Tool("Packaging".to_string())];
+        let composed = compose_templates(&categories, &categorizer, &CommentGenerator::new());
+        let patterns: Vec<String> = composed.file.entries.iter().filter_map(|entry| entry.comparison_pattern()).collect();
+
+        assert_eq!(patterns, vec!["*.jar", "build/", "/build/", "*.[jw]ar", "!gradle-wrapper.jar"]);
+        assert_eq!(composed.duplicates.len(), 1);
+        assert_eq!((composed.duplicates[0].pattern.as_str(), composed.duplicates[0].kept_from.as_str()), ("*.jar", "Java"));
+        assert_eq!(composed.reordered.len(), 1);
+        assert_eq!(composed.reordered[0].overridden_by, "*.[jw]ar");
+        assert!(composed.file.matches("gradle-wrapper.jar", false).is_some_and(|decision| !decision.ignored));
+    }
+
+    #[test]
+    fn test_compare_with_template() {
+        let file = parse_gitignore("# Rust\ntarget\n*.log\n.vscode/\n").unwrap();
+        let template = parse_gitignore("/target/\n**/*.rs.bk\n.vscode/*\n!.vscode/settings.json\n").unwrap();
+        let drift = compare_with_template(&file, &template);
+
+        assert_eq!(drift.shared, 2);
+        assert_eq!(drift.missing, vec!["**/*.rs.bk", "!.vscode/settings.json"]);
+        assert_eq!(drift.extra, vec![(3, "*.log".to_string())]);
+        assert_eq!(drift.drift_percent(), 60.0);
+        assert_eq!(compare_with_template(&file, &file).drift_percent(), 0.0);
+    }
+
+    #[test]
+    fn test_tailor_drops_sections_without_patterns() {
+        let source = parse_gitignore("# Generated by hand\n\nlocal/\n").unwrap();
+        let tailored = tailor_gitignore(&source, &[], &PatternCategorizer::new());
+
+        assert_eq!(format!("{}", tailored.file), "local/");
+        assert!(tailored.dropped.is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::core::optimizer::PassStats;
+use crate::models::GitignoreFile;
+use crate::utils::patterns::GlobPattern;
+use std::collections::HashSet;
+
+/// Names of build outputs and caches that are often absent from a fresh checkout
+///
+/// Patterns for them are kept by `--prune-unused` even when nothing matches yet.
+pub const BUILD_OUTPUT_NAMES: &[&str] = &[
+    "build", "dist", "out", "target", "bin", "obj", "node_modules", "vendor", "coverage",
+    "__pycache__", ".cache", ".tox", ".venv", "venv", "env", ".env", "tmp", "temp", "logs",
+];
+
+/// A pattern that matches nothing in the working tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedPattern {
+    /// Line number of the pattern (1-indexed)
+    pub line_number: usize,
+    /// The pattern
+    pub pattern: String,
+    /// Whether `--prune-unused` may remove it
+    ///
+    /// Only literal paths are pruned: wildcards describe files that come and go, and build
+    /// outputs only exist after a build.
+    pub prunable: bool,
+}
+
+/// Find the patterns that match none of `paths`, in line order
+///
+/// `paths` are relative to the directory holding the file, with a trailing `/` on directories,
+/// as listed by `utils::walker::list_paths`.
+pub fn find_unused_patterns(file: &GitignoreFile, paths: &[String]) -> Vec<UnusedPattern> {
+    let split: Vec<(&str, bool)> = paths.iter()
+        .map(|path| match path.strip_suffix('/') {
+            Some(dir) => (dir, true),
+            None => (path.as_str(), false),
+        })
+        .collect();
+
+    file.entries.iter()
+        .filter_map(|entry| {
+            let pattern = entry.comparison_pattern()?;
+            let glob = GlobPattern::parse(&pattern);
+            if split.iter().any(|(path, is_dir)| glob.matches_path(path, *is_dir)) {
+                return None;
+            }
+            Some(UnusedPattern {
+                line_number: entry.line_number,
+                prunable: is_prunable(&glob),
+                pattern,
+            })
+        })
+        .collect()
+}
+
+/// Remove the prunable unused patterns, counting them in `stats`
+pub fn prune_unused_patterns(file: &GitignoreFile, unused: &[UnusedPattern], stats: &mut PassStats) -> GitignoreFile {
+    let lines: HashSet<usize> = unused.iter()
+        .filter(|pattern| pattern.prunable)
+        .map(|pattern| pattern.line_number)
+        .collect();
+
+    let mut pruned = GitignoreFile::new();
+    pruned.keep_format_of(file);
+    for entry in &file.entries {
+        if entry.is_pattern() && lines.contains(&entry.line_number) {
+            stats.unused_patterns += 1;
+        } else {
+            pruned.add_entry(entry.clone());
+        }
+    }
+    pruned
+}
+
+/// Literal, non-negated patterns that don't name a build output
+fn is_prunable(glob: &GlobPattern) -> bool {
+    !glob.negated && glob.is_literal() && !BUILD_OUTPUT_NAMES.contains(&glob.name().as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    fn paths(list: &[&str]) -> Vec<String> {
+        list.iter().map(|path| path.to_string()).collect()
+    }
+
+    #[test]
+    fn test_find_unused_patterns() {
+        let file = parse_gitignore("*.log\nold-tool/\nbuild/\n*.swp\nsrc/generated.rs\n!keep.log").unwrap();
+        let unused = find_unused_patterns(&file, &paths(&["app.log", "src/", "src/main.rs"]));
+
+        let found: Vec<(&str, bool)> = unused.iter().map(|unused| (unused.pattern.as_str(), unused.prunable)).collect();
+        assert_eq!(found, vec![
+            ("old-tool/", true),
+            ("build/", false),
+            ("*.swp", false),
+            ("src/generated.rs", true),
+            ("!keep.log", false),
+        ]);
+    }
+
+    #[test]
+    fn test_prune_unused_patterns() {
+        let file = parse_gitignore("*.log\nold-tool/\nbuild/").unwrap();
+        let unused = find_unused_patterns(&file, &paths(&["app.log"]));
+
+        let mut stats = PassStats::default();
+        let pruned = prune_unused_patterns(&file, &unused, &mut stats);
+        assert_eq!(format!("{}", pruned), "*.log\nbuild/");
+        assert_eq!(stats.unused_patterns, 1);
+    }
+}
@@ -1,16 +1,105 @@
 use crate::models::GixError;
+use crate::utils::patterns::trim_unescaped_trailing_whitespace;
+use std::fmt;
 
-/// Validate a gitignore pattern
+/// A reason a pattern is invalid, or can never match anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternProblem {
+    /// Nothing is left once trailing whitespace and negation are removed
+    Empty,
+    /// A `[` opens a character class that is never closed, so the pattern matches nothing
+    UnclosedClass,
+    /// The pattern is only slashes, which matches nothing
+    OnlySlashes,
+    /// `**` inside a name (`a**b`), where it acts as a single `*`
+    MisplacedGlobstar,
+    /// The pattern ends with a backslash that escapes nothing, so it matches nothing
+    TrailingBackslash,
+    /// A `.` or `..` path segment, which the paths git matches never contain
+    NeverMatches,
+}
+
+impl PatternProblem {
+    /// All problems, in reporting order
+    pub const ALL: [PatternProblem; 6] = [
+        PatternProblem::Empty,
+        PatternProblem::UnclosedClass,
+        PatternProblem::OnlySlashes,
+        PatternProblem::MisplacedGlobstar,
+        PatternProblem::TrailingBackslash,
+        PatternProblem::NeverMatches,
+    ];
+
+    /// Get the diagnostic code used in reports
+    pub fn code(&self) -> &'static str {
+        match self {
+            PatternProblem::Empty => "empty-pattern",
+            PatternProblem::UnclosedClass => "invalid-class",
+            PatternProblem::OnlySlashes => "only-slashes",
+            PatternProblem::MisplacedGlobstar => "misplaced-globstar",
+            PatternProblem::TrailingBackslash => "trailing-backslash",
+            PatternProblem::NeverMatches => "never-matches",
+        }
+    }
+
+    /// Describe the problem with `pattern` for people
+    pub fn message(&self, pattern: &str) -> String {
+        let pattern = trim_unescaped_trailing_whitespace(pattern);
+        match self {
+            PatternProblem::Empty => "Pattern cannot be empty".to_string(),
+            PatternProblem::UnclosedClass => format!("'{}' opens a character class with '[' that is never closed, so it matches nothing", pattern),
+            PatternProblem::OnlySlashes => format!("'{}' is only slashes and matches nothing", pattern),
+            PatternProblem::MisplacedGlobstar => format!("'**' only spans directories between slashes; in '{}' it acts as a single '*'", pattern),
+            PatternProblem::TrailingBackslash => format!("'{}' ends with a backslash that escapes nothing, so it matches nothing", pattern),
+            PatternProblem::NeverMatches => format!("paths git matches never contain '.' or '..' segments, so '{}' matches nothing", pattern),
+        }
+    }
+}
+
+impl fmt::Display for PatternProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Find everything wrong with a pattern, in the order of `PatternProblem::ALL`
+///
+/// Unescaped trailing whitespace and a leading `!` are not part of what is checked. An empty
+/// pattern has no other problems.
+pub fn find_pattern_problems(pattern: &str) -> Vec<PatternProblem> {
+    let text = trim_unescaped_trailing_whitespace(pattern);
+    let body = text.strip_prefix('!').unwrap_or(text);
+    if body.trim().is_empty() {
+        return vec![PatternProblem::Empty];
+    }
+
+    let mut problems = Vec::new();
+    let (unclosed_class, trailing_backslash) = scan_escapes(body);
+    if unclosed_class {
+        problems.push(PatternProblem::UnclosedClass);
+    }
+    if body.chars().all(|ch| ch == '/') {
+        problems.push(PatternProblem::OnlySlashes);
+    }
+    let segments: Vec<&str> = body.split('/').filter(|segment| !segment.is_empty()).collect();
+    if segments.iter().any(|segment| *segment != "**" && has_unescaped_globstar(segment)) {
+        problems.push(PatternProblem::MisplacedGlobstar);
+    }
+    if trailing_backslash {
+        problems.push(PatternProblem::TrailingBackslash);
+    }
+    if segments.iter().any(|segment| *segment == "." || *segment == "..") {
+        problems.push(PatternProblem::NeverMatches);
+    }
+    problems
+}
+
+/// Validate a gitignore pattern, failing with the message of its first problem
 pub fn validate_pattern(pattern: &str) -> Result<(), GixError> {
-    // Basic validation - ensure pattern is not empty after trimming
-    if pattern.trim().is_empty() {
-        return Err(GixError::InvalidPattern("Pattern cannot be empty".to_string()));
+    match find_pattern_problems(pattern).first() {
+        Some(problem) => Err(GixError::InvalidPattern(problem.message(pattern))),
+        None => Ok(()),
     }
-    
-    // Check for invalid characters or patterns
-    // This is a basic implementation - could be expanded for more complex validation
-    
-    Ok(())
 }
 
 /// Check if a pattern is valid for gitignore
@@ -18,6 +107,59 @@ pub fn is_valid_pattern(pattern: &str) -> bool {
     validate_pattern(pattern).is_ok()
 }
 
+/// Walk a pattern's escapes, returning whether a `[` is left unclosed and whether it ends with a lone backslash
+///
+/// In a class, a `!` or `^` right after the `[` negates it and a `]` right after that is literal.
+fn scan_escapes(pattern: &str) -> (bool, bool) {
+    let mut chars = pattern.chars().peekable();
+    let mut unclosed = false;
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' if chars.next().is_none() => return (unclosed, true),
+            '[' => {
+                if matches!(chars.peek(), Some('!' | '^')) {
+                    chars.next();
+                }
+                if chars.peek() == Some(&']') {
+                    chars.next();
+                }
+                let mut closed = false;
+                while let Some(inner) = chars.next() {
+                    match inner {
+                        ']' => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' if chars.next().is_none() => return (true, true),
+                        _ => {}
+                    }
+                }
+                unclosed = !closed;
+            }
+            _ => {}
+        }
+    }
+    (unclosed, false)
+}
+
+/// Check if a path segment holds two unescaped stars in a row
+fn has_unescaped_globstar(segment: &str) -> bool {
+    let mut chars = segment.chars();
+    let mut previous_star = false;
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+                previous_star = false;
+            }
+            '*' if previous_star => return true,
+            '*' => previous_star = true,
+            _ => previous_star = false,
+        }
+    }
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -28,12 +170,33 @@ mod tests {
         assert!(validate_pattern("build/").is_ok());
         assert!(validate_pattern("!debug.log").is_ok());
         assert!(validate_pattern("\\#notacomment").is_ok());
+        assert!(validate_pattern("**/logs/**").is_ok());
+        assert!(validate_pattern("*.[oa]").is_ok());
+        assert!(validate_pattern("[]]x").is_ok());
+        assert!(validate_pattern("foo\\\\").is_ok());
+        assert!(validate_pattern("foo\\ ").is_ok());
     }
 
     #[test]
     fn test_validate_empty_pattern() {
         assert!(validate_pattern("").is_err());
         assert!(validate_pattern("   ").is_err());
+        assert_eq!(find_pattern_problems("!"), vec![PatternProblem::Empty]);
+    }
+
+    #[test]
+    fn test_find_pattern_problems() {
+        assert_eq!(find_pattern_problems("*.[ch"), vec![PatternProblem::UnclosedClass]);
+        assert_eq!(find_pattern_problems("\\[draft"), vec![]);
+        assert_eq!(find_pattern_problems("/"), vec![PatternProblem::OnlySlashes]);
+        assert_eq!(find_pattern_problems("!//"), vec![PatternProblem::OnlySlashes]);
+        assert_eq!(find_pattern_problems("a**b/"), vec![PatternProblem::MisplacedGlobstar]);
+        assert_eq!(find_pattern_problems("a\\*\\*b"), vec![]);
+        assert_eq!(find_pattern_problems("foo\\"), vec![PatternProblem::TrailingBackslash]);
+        assert_eq!(find_pattern_problems("foo/../bar"), vec![PatternProblem::NeverMatches]);
+        assert_eq!(find_pattern_problems("./build"), vec![PatternProblem::NeverMatches]);
+        assert_eq!(find_pattern_problems("[a\\"), vec![PatternProblem::UnclosedClass, PatternProblem::TrailingBackslash]);
+        assert_eq!(PatternProblem::UnclosedClass.message("*.[ch  "), "'*.[ch' opens a character class with '[' that is never closed, so it matches nothing");
     }
 
     #[test]
@@ -42,5 +205,6 @@ mod tests {
         assert!(is_valid_pattern("build/"));
         assert!(!is_valid_pattern(""));
         assert!(!is_valid_pattern("   "));
+        assert!(!is_valid_pattern("foo/../bar"));
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,356 @@
+use crate::core::optimizer::PassStats;
+use crate::models::{EntryType, GitignoreFile, GixError};
+use crate::utils::patterns::trim_unescaped_trailing_whitespace;
+use std::fmt;
+use std::str::FromStr;
+
+/// A whitespace rule that can be enforced on a gitignore file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WhitespaceRule {
+    /// No unescaped trailing whitespace on any line
+    TrailingSpaces,
+    /// At most one blank line between sections
+    BlankLines,
+    /// No leading indentation before patterns or comments
+    Indentation,
+}
+
+impl WhitespaceRule {
+    /// All rules, in reporting order
+    pub const ALL: [WhitespaceRule; 3] = [
+        WhitespaceRule::TrailingSpaces,
+        WhitespaceRule::BlankLines,
+        WhitespaceRule::Indentation,
+    ];
+
+    /// Get the name used on the command line and in config files
+    pub fn name(&self) -> &'static str {
+        match self {
+            WhitespaceRule::TrailingSpaces => "trailing-spaces",
+            WhitespaceRule::BlankLines => "blank-lines",
+            WhitespaceRule::Indentation => "indentation",
+        }
+    }
+
+    /// Describe what the rule reports
+    pub fn summary(&self) -> &'static str {
+        match self {
+            WhitespaceRule::TrailingSpaces => "a line ends with unescaped whitespace",
+            WhitespaceRule::BlankLines => "sections are separated by more than one blank line",
+            WhitespaceRule::Indentation => "a pattern or comment is indented",
+        }
+    }
+}
+
+impl fmt::Display for WhitespaceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for WhitespaceRule {
+    type Err = GixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        WhitespaceRule::ALL
+            .iter()
+            .find(|rule| rule.name() == s.trim())
+            .copied()
+            .ok_or_else(|| GixError::ParseError(format!(
+                "unknown whitespace rule '{}' (expected trailing-spaces, blank-lines or indentation)",
+                s
+            )))
+    }
+}
+
+/// The set of whitespace rules a team has agreed on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhitespacePolicy {
+    /// Forbid unescaped trailing whitespace
+    pub no_trailing_spaces: bool,
+    /// Allow only a single blank line between sections
+    pub single_blank_between_sections: bool,
+    /// Forbid leading indentation
+    pub no_leading_indentation: bool,
+}
+
+impl Default for WhitespacePolicy {
+    fn default() -> Self {
+        Self {
+            no_trailing_spaces: true,
+            single_blank_between_sections: true,
+            no_leading_indentation: true,
+        }
+    }
+}
+
+impl WhitespacePolicy {
+    /// Create a policy enforcing exactly the given rules
+    pub fn from_rules(rules: &[WhitespaceRule]) -> Self {
+        Self {
+            no_trailing_spaces: rules.contains(&WhitespaceRule::TrailingSpaces),
+            single_blank_between_sections: rules.contains(&WhitespaceRule::BlankLines),
+            no_leading_indentation: rules.contains(&WhitespaceRule::Indentation),
+        }
+    }
+
+    /// Check if a rule is enforced by this policy
+    pub fn enforces(&self, rule: WhitespaceRule) -> bool {
+        match rule {
+            WhitespaceRule::TrailingSpaces => self.no_trailing_spaces,
+            WhitespaceRule::BlankLines => self.single_blank_between_sections,
+            WhitespaceRule::Indentation => self.no_leading_indentation,
+        }
+    }
+}
+
+/// A single breach of the whitespace policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhitespaceViolation {
+    /// Line number (1-indexed)
+    pub line_number: usize,
+    /// The rule that was violated
+    pub rule: WhitespaceRule,
+    /// Human-readable description
+    pub message: String,
+}
+
+/// Check a gitignore file against a whitespace policy, returning violations in line order
+pub fn check_whitespace(file: &GitignoreFile, policy: &WhitespacePolicy) -> Vec<WhitespaceViolation> {
+    let mut violations = Vec::new();
+    let mut consecutive_blanks = 0;
+
+    for entry in &file.entries {
+        let line = entry.original.as_str();
+
+        if entry.is_blank() {
+            consecutive_blanks += 1;
+            if policy.single_blank_between_sections && consecutive_blanks > 1 {
+                violations.push(WhitespaceViolation {
+                    line_number: entry.line_number,
+                    rule: WhitespaceRule::BlankLines,
+                    message: "more than one consecutive blank line".to_string(),
+                });
+            }
+        } else {
+            consecutive_blanks = 0;
+            if policy.no_leading_indentation && line.starts_with(char::is_whitespace) {
+                violations.push(WhitespaceViolation {
+                    line_number: entry.line_number,
+                    rule: WhitespaceRule::Indentation,
+                    message: "line is indented".to_string(),
+                });
+            }
+        }
+
+        if policy.no_trailing_spaces {
+            let count = unescaped_trailing_whitespace(line);
+            if count > 0 {
+                violations.push(WhitespaceViolation {
+                    line_number: entry.line_number,
+                    rule: WhitespaceRule::TrailingSpaces,
+                    message: format!("{} trailing whitespace character(s)", count),
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+/// Longest run of blank lines whitespace normalization leaves alone
+pub const MAX_BLANK_RUN: usize = 2;
+
+/// What whitespace normalization changed on a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhitespaceFixKind {
+    /// Unescaped trailing whitespace was trimmed
+    TrailingSpaces,
+    /// A space was put between `#` and the comment text
+    CommentSpacing,
+}
+
+impl WhitespaceFixKind {
+    /// Get the name used in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            WhitespaceFixKind::TrailingSpaces => "trailing spaces",
+            WhitespaceFixKind::CommentSpacing => "comment spacing",
+        }
+    }
+}
+
+/// A line whitespace normalization rewrote; removed blank lines are counted in `PassStats`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhitespaceFix {
+    /// Line number (1-indexed)
+    pub line_number: usize,
+    /// The fixes applied, in the order above
+    pub kinds: Vec<WhitespaceFixKind>,
+    /// The line before the fix
+    pub before: String,
+    /// The line after the fix
+    pub after: String,
+}
+
+/// Normalize whitespace: trim trailing spaces, cut blank runs to `MAX_BLANK_RUN`, and write `#comment` as `# comment`
+///
+/// Git drops unescaped trailing spaces from patterns, so trimming them never changes what is
+/// ignored; an escaped one (`foo\ `) is kept. Only comments whose text starts right after the
+/// `#` with a letter or digit get a space, so rulers like `#####` stay as they are. Lines in a
+/// `# gix:off` region are left alone.
+pub fn normalize_whitespace(file: &GitignoreFile, stats: &mut PassStats) -> (GitignoreFile, Vec<WhitespaceFix>) {
+    let mut normalized = GitignoreFile::new();
+    normalized.keep_format_of(file);
+    let mut fixes = Vec::new();
+    let mut blank_run = 0;
+
+    for entry in &file.entries {
+        if entry.preserve {
+            blank_run = 0;
+            normalized.add_entry(entry.clone());
+            continue;
+        }
+        if entry.is_blank() {
+            blank_run += 1;
+            if blank_run > MAX_BLANK_RUN {
+                stats.whitespace_lines += 1;
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+
+        let before = entry.original.clone();
+        let mut entry = entry.clone();
+        let mut kinds = Vec::new();
+        let trailing = unescaped_trailing_whitespace(&entry.original);
+        if trailing > 0 {
+            let trimmed = entry.original.chars().rev().take(trailing).map(char::len_utf8).sum::<usize>();
+            let truncate = |text: &mut String| text.truncate(text.len() - trimmed);
+            match (&mut entry.entry_type, &mut entry.inline_comment) {
+                (EntryType::Pattern(pattern), None) => {
+                    let pattern = trim_unescaped_trailing_whitespace(pattern.trim_start()).to_string();
+                    entry.rewrite_pattern(&pattern);
+                }
+                (EntryType::Pattern(_), Some(comment)) => {
+                    truncate(comment);
+                    truncate(&mut entry.original);
+                }
+                (EntryType::Comment(text), _) => {
+                    truncate(text);
+                    truncate(&mut entry.original);
+                }
+                (EntryType::Blank, _) => truncate(&mut entry.original),
+            }
+            kinds.push(WhitespaceFixKind::TrailingSpaces);
+        }
+        if let EntryType::Comment(comment) = &mut entry.entry_type {
+            if comment[1..].starts_with(|ch: char| ch.is_alphanumeric()) {
+                comment.insert(1, ' ');
+                entry.original = comment.clone();
+                kinds.push(WhitespaceFixKind::CommentSpacing);
+            }
+        }
+        if !kinds.is_empty() {
+            fixes.push(WhitespaceFix {
+                line_number: entry.line_number,
+                kinds,
+                before,
+                after: entry.original.clone(),
+            });
+        }
+        normalized.add_entry(entry);
+    }
+    (normalized, fixes)
+}
+
+/// Count trailing whitespace characters that are not protected by a backslash escape
+fn unescaped_trailing_whitespace(line: &str) -> usize {
+    line[trim_unescaped_trailing_whitespace(line).len()..].chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_gitignore;
+
+    #[test]
+    fn test_clean_file_has_no_violations() {
+        let file = parse_gitignore("# Logs\n*.log\n\n# Build\nbuild/").unwrap();
+        assert!(check_whitespace(&file, &WhitespacePolicy::default()).is_empty());
+    }
+
+    #[test]
+    fn test_trailing_spaces_are_reported() {
+        let file = parse_gitignore("*.log  \nbuild/\n# comment ").unwrap();
+        let violations = check_whitespace(&file, &WhitespacePolicy::default());
+
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].line_number, 1);
+        assert_eq!(violations[0].rule, WhitespaceRule::TrailingSpaces);
+        assert_eq!(violations[1].line_number, 3);
+    }
+
+    #[test]
+    fn test_escaped_trailing_space_is_allowed() {
+        let file = parse_gitignore("foo\\ \nbar\\  \nbaz\\\\ ").unwrap();
+        let violations = check_whitespace(&file, &WhitespacePolicy::default());
+
+        let lines: Vec<usize> = violations.iter().map(|v| v.line_number).collect();
+        assert_eq!(lines, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_multiple_blank_lines_are_reported() {
+        let file = parse_gitignore("*.log\n\n\n\nbuild/").unwrap();
+        let violations = check_whitespace(&file, &WhitespacePolicy::default());
+
+        let lines: Vec<usize> = violations.iter().map(|v| v.line_number).collect();
+        assert_eq!(lines, vec![3, 4]);
+        assert!(violations.iter().all(|v| v.rule == WhitespaceRule::BlankLines));
+    }
+
+    #[test]
+    fn test_indentation_is_reported() {
+        let file = parse_gitignore("  *.log\n\t# comment\nbuild/").unwrap();
+        let violations = check_whitespace(&file, &WhitespacePolicy::default());
+
+        assert_eq!(violations.len(), 2);
+        assert!(violations.iter().all(|v| v.rule == WhitespaceRule::Indentation));
+    }
+
+    #[test]
+    fn test_policy_only_enforces_selected_rules() {
+        let file = parse_gitignore("  *.log \n\n\nbuild/").unwrap();
+        let policy = WhitespacePolicy::from_rules(&[WhitespaceRule::Indentation]);
+        let violations = check_whitespace(&file, &policy);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, WhitespaceRule::Indentation);
+        assert!(!policy.enforces(WhitespaceRule::TrailingSpaces));
+    }
+
+    #[test]
+    fn test_normalize_whitespace() {
+        let file = parse_gitignore("#Logs\n*.log  \nfoo\\  \n\n\n\n\n#####\nbuild/ # out \n  tmp/ \n").unwrap();
+        let mut stats = PassStats::default();
+        let (normalized, fixes) = normalize_whitespace(&file, &mut stats);
+
+        assert_eq!(format!("{}", normalized), "# Logs\n*.log\nfoo\\ \n\n\n#####\nbuild/ # out\n  tmp/");
+        assert_eq!(stats.whitespace_lines, 2);
+        assert_eq!(fixes.iter().map(|fix| fix.line_number).collect::<Vec<_>>(), vec![1, 2, 3, 9, 10]);
+        assert_eq!(fixes[0].kinds, vec![WhitespaceFixKind::CommentSpacing]);
+        assert_eq!((fixes[1].before.as_str(), fixes[1].after.as_str()), ("*.log  ", "*.log"));
+        assert_eq!(normalized.entries[2].comparison_pattern().as_deref(), Some("foo\\ "));
+        assert_eq!(normalized.entries[6].inline_comment.as_deref(), Some("# out"));
+        assert!(check_whitespace(&normalized, &WhitespacePolicy::from_rules(&[WhitespaceRule::TrailingSpaces])).is_empty());
+    }
+
+    #[test]
+    fn test_parse_whitespace_rule() {
+        assert_eq!("trailing-spaces".parse::<WhitespaceRule>().unwrap(), WhitespaceRule::TrailingSpaces);
+        assert_eq!("blank-lines".parse::<WhitespaceRule>().unwrap(), WhitespaceRule::BlankLines);
+        assert!("tabs".parse::<WhitespaceRule>().is_err());
+    }
+}
@@ -0,0 +1,499 @@
+//! A long-lived handle bundling everything gix needs to analyze and optimize files
+//!
+//! Building categorizers and comment generators means loading the pattern dataset, so
+//! embedders such as language servers or watchers should build one engine and share it.
+
+use crate::core::categorizer::{CategorySummary, PatternCategorizer, PatternCategory};
+use crate::core::changes::OptimizationReport;
+use crate::core::comment_generator::CommentGenerator;
+#[cfg(feature = "dataset")]
+use crate::core::dataset::Dataset;
+use crate::core::folding::FoldedPatterns;
+use crate::core::lint::{lint_gitignore, LintIssue};
+use crate::core::negations::{group_negations, MovedNegation};
+use crate::core::optimizer::{
+    advanced_passes, analyze_gitignore, optimize_gitignore_aggressive_tracked,
+    optimize_gitignore_tracked, remove_earlier_duplicates, restore_kept_patterns, restore_negation_semantics, GitignoreAnalysis, KeptForSafety,
+    PassStats, RedundantPattern,
+};
+use crate::core::organizer::{category_rank, organize_gitignore_ordered, organize_gitignore_paired};
+use crate::core::parser::parse_gitignore;
+use crate::core::sorting::sort_sections;
+use crate::core::pattern_analyzer::PatternAnalyzer;
+use crate::core::plugins::PluginRegistry;
+use crate::models::{GitignoreEntry, GitignoreFile, GixError, KeepOccurrence, OptimizationMode, SortOrder, WriteOptions};
+use crate::utils::{create_backup_in, read_gitignore_file, write_gitignore_file, BackupPolicy};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Settings an engine is built with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineConfig {
+    /// Optimization mode used by `GixEngine::optimize`
+    pub mode: OptimizationMode,
+    /// Allow heuristic merges of patterns that only look equivalent (ignored in conservative mode)
+    pub allow_heuristics: bool,
+    /// Use the embedded pattern dataset even if a newer one was downloaded
+    pub offline: bool,
+    /// Patterns optimization never removes, compared by their exact text
+    pub keep: Vec<String>,
+    /// Order of organized sections by category kind; unlisted kinds follow in the default order
+    pub category_order: Vec<String>,
+    /// Treat patterns differing only in case as duplicates, as git does with `core.ignoreCase`
+    pub ignore_case: bool,
+    /// Which occurrence of a duplicated pattern deduplication keeps
+    pub keep_occurrence: KeepOccurrence,
+    /// Drop patterns broader ones cover in advanced mode; off for `.dockerignore`, whose patterns
+    /// match from the context root rather than at any depth
+    pub subsume: bool,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            mode: OptimizationMode::Standard,
+            allow_heuristics: false,
+            offline: false,
+            keep: Vec::new(),
+            category_order: Vec::new(),
+            ignore_case: false,
+            keep_occurrence: KeepOccurrence::First,
+            subsume: true,
+        }
+    }
+}
+
+impl EngineConfig {
+    /// Check if heuristic merges apply
+    pub fn heuristics_enabled(&self) -> bool {
+        self.allow_heuristics && self.mode != OptimizationMode::Conservative
+    }
+}
+
+/// A file optimized by the engine, with what each rewrite did
+#[derive(Debug, Clone)]
+pub struct OptimizedFile {
+    /// The optimized file
+    pub file: GitignoreFile,
+    /// Patterns dropped because broader ones cover them (advanced mode)
+    pub redundant: Vec<RedundantPattern>,
+    /// Sibling patterns folded into a character class (advanced mode)
+    pub folded: Vec<FoldedPatterns>,
+    /// Negations moved next to the patterns they re-include paths from
+    pub moved: Vec<MovedNegation>,
+    /// Patterns put back because removing them changed what a negation re-includes
+    pub kept_for_safety: Vec<KeptForSafety>,
+    /// Every change made, line by line
+    pub report: OptimizationReport,
+}
+
+/// Settings for `GixEngine::optimize_many`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// Optimize without writing any file
+    pub dry_run: bool,
+    /// Write files that optimization leaves without patterns instead of failing them
+    pub allow_empty: bool,
+    /// Back up each file before writing it, as this policy says
+    pub backup: Option<BackupPolicy>,
+}
+
+/// What optimizing one file of a batch did
+#[derive(Debug, Clone)]
+pub struct FileOutcome {
+    /// The file as read
+    pub original: GitignoreFile,
+    /// The optimized file
+    pub optimized: GitignoreFile,
+    /// Content as read
+    pub content: String,
+    /// Optimized content, the same as `content` when nothing was removed
+    pub optimized_content: String,
+    /// Whether the file was written; false on a dry run or when nothing was removed
+    pub written: bool,
+}
+
+impl FileOutcome {
+    /// Count the lines optimization removed
+    pub fn removed(&self) -> usize {
+        self.original.entries.len() - self.optimized.entries.len()
+    }
+
+    /// Check if optimization left a file that had patterns without any
+    pub fn empties(&self) -> bool {
+        self.original.stats.pattern_lines > 0 && self.optimized.stats.pattern_lines == 0
+    }
+}
+
+/// The result for one file of a batch
+#[derive(Debug)]
+pub struct FileResult {
+    /// The file, as given
+    pub path: PathBuf,
+    /// What was done, or why the file was skipped
+    pub outcome: Result<FileOutcome, GixError>,
+}
+
+/// Shared, thread-safe entry point for analyzing and optimizing gitignore files
+///
+/// The engine is `Send + Sync`: every method takes `&self`, so one engine can serve many
+/// threads behind an `Arc`. Categories of patterns seen before are cached.
+pub struct GixEngine {
+    config: EngineConfig,
+    #[cfg(feature = "dataset")]
+    dataset: Dataset,
+    analyzer: PatternAnalyzer,
+    categorizer: PatternCategorizer,
+    comment_generator: CommentGenerator,
+    categories: RwLock<HashMap<String, PatternCategory>>,
+    plugins: PluginRegistry,
+}
+
+impl GixEngine {
+    /// Build an engine, loading the pattern dataset once
+    ///
+    /// The user's category definitions aren't read; build with `from_dataset` to use them.
+    pub fn new(config: EngineConfig) -> Self {
+        #[cfg(feature = "dataset")]
+        return Self::from_dataset(Dataset::load(config.offline), config);
+        #[cfg(not(feature = "dataset"))]
+        return Self {
+            analyzer: Self::analyzer_for(&config),
+            categorizer: PatternCategorizer::new(),
+            comment_generator: CommentGenerator::new(),
+            categories: RwLock::new(HashMap::new()),
+            plugins: PluginRegistry::new(),
+            config,
+        };
+    }
+
+    /// Build an engine backed by the given dataset
+    #[cfg(feature = "dataset")]
+    pub fn from_dataset(dataset: Dataset, config: EngineConfig) -> Self {
+        Self {
+            analyzer: Self::analyzer_for(&config),
+            categorizer: PatternCategorizer::from_dataset(&dataset),
+            comment_generator: CommentGenerator::from_dataset(&dataset),
+            categories: RwLock::new(HashMap::new()),
+            plugins: PluginRegistry::new(),
+            dataset,
+            config,
+        }
+    }
+
+    fn analyzer_for(config: &EngineConfig) -> PatternAnalyzer {
+        let mut analyzer = if config.heuristics_enabled() {
+            PatternAnalyzer::heuristic()
+        } else {
+            PatternAnalyzer::safe()
+        };
+        analyzer.case_sensitive = !config.ignore_case;
+        analyzer
+    }
+
+    /// Consult `plugins` when categorizing, linting and suggesting patterns
+    ///
+    /// Plugin categories take precedence over the dataset's, so organizing and sorting follow them.
+    pub fn with_plugins(self, plugins: PluginRegistry) -> Self {
+        Self {
+            categorizer: self.categorizer.with_plugins(plugins.clone()),
+            categories: RwLock::new(HashMap::new()),
+            plugins,
+            ..self
+        }
+    }
+
+    /// Get the plugins the engine consults
+    pub fn plugins(&self) -> &PluginRegistry {
+        &self.plugins
+    }
+
+    /// Get the settings the engine was built with
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    /// Get the pattern dataset the engine loaded
+    #[cfg(feature = "dataset")]
+    pub fn dataset(&self) -> &Dataset {
+        &self.dataset
+    }
+
+    /// Get the pattern analyzer matching the configured safety level
+    pub fn analyzer(&self) -> &PatternAnalyzer {
+        &self.analyzer
+    }
+
+    /// Get the pattern categorizer, backed by the engine's dataset when available
+    pub fn categorizer(&self) -> &PatternCategorizer {
+        &self.categorizer
+    }
+
+    /// Get the comment generator, backed by the engine's dataset when available
+    pub fn comment_generator(&self) -> &CommentGenerator {
+        &self.comment_generator
+    }
+
+    /// Parse gitignore content
+    pub fn parse(&self, content: &str) -> Result<GitignoreFile, GixError> {
+        parse_gitignore(content)
+    }
+
+    /// Optimize a file with the configured mode
+    pub fn optimize(&self, file: &GitignoreFile) -> Result<(GitignoreFile, Vec<RedundantPattern>), GixError> {
+        self.optimize_tracked(file, &mut PassStats::default())
+    }
+
+    /// Optimize a file with the configured mode, counting removals per pass in `stats`
+    ///
+    /// Advanced mode also returns the patterns it dropped because broader ones cover them.
+    pub fn optimize_tracked(&self, file: &GitignoreFile, stats: &mut PassStats) -> Result<(GitignoreFile, Vec<RedundantPattern>), GixError> {
+        let optimized = self.optimize_in_tree(file, &[], stats)?;
+        Ok((optimized.file, optimized.redundant))
+    }
+
+    /// Optimize a file with the configured mode, checking rewrites against the working tree
+    ///
+    /// `paths` are relative to the directory holding the file, directories with a trailing `/`;
+    /// advanced mode skips any fold that would decide one of them differently. Patterns the
+    /// config says to keep, or marked `# gix:keep`, are put back wherever a pass removed them,
+    /// and so are patterns whose removal would change what a negation re-includes.
+    pub fn optimize_in_tree(&self, file: &GitignoreFile, paths: &[String], stats: &mut PassStats) -> Result<OptimizedFile, GixError> {
+        let original = file;
+        let deduplicated;
+        let file = match self.config.keep_occurrence {
+            KeepOccurrence::First => file,
+            KeepOccurrence::Last => {
+                deduplicated = remove_earlier_duplicates(file, &self.analyzer, stats);
+                &deduplicated
+            }
+        };
+        let (file, redundant, folded) = match self.config.mode {
+            // Conservative mode only removes exact duplicates, which the analyzer guarantees
+            OptimizationMode::Standard | OptimizationMode::Conservative => {
+                (optimize_gitignore_tracked(file, &self.analyzer, stats)?, Vec::new(), Vec::new())
+            }
+            OptimizationMode::Aggressive => {
+                (optimize_gitignore_aggressive_tracked(file, &self.analyzer, stats)?, Vec::new(), Vec::new())
+            }
+            OptimizationMode::Advanced => advanced_passes(file, &self.analyzer, paths, self.config.subsume, stats)?,
+        };
+        let file = restore_kept_patterns(original, &file, &self.config.keep);
+        let (file, kept_for_safety) = restore_negation_semantics(original, &file);
+        let report = OptimizationReport::describe(original, &file, &redundant, &folded, &[], &kept_for_safety);
+        Ok(OptimizedFile { file, redundant, folded, moved: Vec::new(), kept_for_safety, report })
+    }
+
+    /// Optimize and write several files, in the order given
+    ///
+    /// A file that can't be read, parsed, optimized or written fails alone: its error is in its
+    /// result and the rest of the batch carries on. Files with nothing to remove are left
+    /// byte-for-byte untouched, and a file optimization would empty fails unless
+    /// `allow_empty` is set (a dry run reports it with `FileOutcome::empties` instead).
+    pub fn optimize_many(&self, paths: &[PathBuf], options: &BatchOptions, stats: &mut PassStats) -> Vec<FileResult> {
+        paths.iter()
+            .map(|path| FileResult { path: path.clone(), outcome: self.optimize_one(path, options, stats) })
+            .collect()
+    }
+
+    fn optimize_one(&self, path: &Path, options: &BatchOptions, stats: &mut PassStats) -> Result<FileOutcome, GixError> {
+        let content = read_gitignore_file(path)?;
+        let original = parse_gitignore(&content)?;
+        let (optimized, _) = self.optimize_tracked(&original, stats)?;
+        let mut outcome = FileOutcome { optimized_content: content.clone(), original, optimized, content, written: false };
+        if outcome.removed() == 0 {
+            return Ok(outcome);
+        }
+
+        outcome.optimized_content = outcome.optimized.serialize(&WriteOptions::preserving(&outcome.original));
+        if options.dry_run {
+            return Ok(outcome);
+        }
+        if outcome.empties() && !options.allow_empty {
+            return Err(GixError::WouldBeEmpty(path.display().to_string()));
+        }
+        if let Some(policy) = &options.backup {
+            create_backup_in(path, policy)?;
+        }
+        write_gitignore_file(path, &outcome.optimized_content)?;
+        outcome.written = true;
+        Ok(outcome)
+    }
+
+    /// Optimize a file and move each negation next to the pattern it re-includes paths from
+    ///
+    /// A moved negation can leave a pattern redundant, and a removed pattern can free a negation
+    /// to move, so both steps repeat until neither changes the file.
+    pub fn optimize_grouped(&self, file: &GitignoreFile, paths: &[String], stats: &mut PassStats) -> Result<OptimizedFile, GixError> {
+        let mut optimized = self.optimize_in_tree(file, paths, stats)?;
+        loop {
+            let (grouped, moved) = group_negations(&optimized.file);
+            if moved.is_empty() {
+                optimized.report = OptimizationReport::describe(file, &optimized.file, &optimized.redundant, &optimized.folded, &optimized.moved, &optimized.kept_for_safety);
+                return Ok(optimized);
+            }
+            let again = self.optimize_in_tree(&grouped, paths, stats)?;
+            optimized.file = again.file;
+            optimized.redundant.extend(again.redundant);
+            optimized.folded.extend(again.folded);
+            // A pattern kept in an earlier round is kept again in each later one
+            let kept_before: Vec<String> = optimized.kept_for_safety.iter().map(|kept| kept.pattern.clone()).collect();
+            optimized.kept_for_safety.extend(again.kept_for_safety.into_iter().filter(|kept| !kept_before.contains(&kept.pattern)));
+            optimized.moved.extend(moved);
+        }
+    }
+
+    /// Analyze the patterns of a file
+    pub fn analyze(&self, file: &GitignoreFile) -> Result<GitignoreAnalysis, GixError> {
+        analyze_gitignore(file)
+    }
+
+    /// Report duplicates, conflicts, redundant and invalid patterns, and what plugins find
+    pub fn lint(&self, file: &GitignoreFile) -> Result<Vec<LintIssue>, GixError> {
+        let mut issues = lint_gitignore(file)?;
+        if !self.plugins.is_empty() {
+            issues.extend(self.plugins.lint(file));
+            issues.sort_by_key(|issue| issue.line_number);
+        }
+        Ok(issues)
+    }
+
+    /// Categorize a pattern, reusing the result for patterns seen before
+    pub fn categorize(&self, pattern: &str) -> PatternCategory {
+        if let Some(category) = self.categories.read().ok().and_then(|cache| cache.get(pattern).cloned()) {
+            return category;
+        }
+
+        let category = self.categorizer.primary_category(pattern);
+        if let Ok(mut cache) = self.categories.write() {
+            cache.insert(pattern.to_string(), category.clone());
+        }
+        category
+    }
+
+    /// Count patterns per category
+    pub fn category_summary(&self, patterns: &[String]) -> CategorySummary {
+        self.categorizer.prefetch(patterns);
+        let mut counts: HashMap<PatternCategory, usize> = HashMap::new();
+        for pattern in patterns {
+            *counts.entry(self.categorize(pattern)).or_default() += 1;
+        }
+
+        let mut summary = CategorySummary::new();
+        for (category, count) in counts {
+            summary.add_category(category, count);
+        }
+        summary
+    }
+
+    /// Add a generated comment above patterns that have none
+    pub fn generate_comments(&self, file: &GitignoreFile) -> GitignoreFile {
+        self.comment_generator.add_generated_comments(file)
+    }
+
+    /// Sort the patterns of each section without changing which paths are ignored
+    ///
+    /// Category order follows `organize`: kinds as configured, then category names; patterns
+    /// of the same category sort alphabetically. A negation sorts by the pattern it negates.
+    pub fn sort(&self, file: &GitignoreFile, order: SortOrder) -> GitignoreFile {
+        let alpha = |pattern: &str| pattern.trim_start_matches('!').to_string();
+        if order == SortOrder::Category {
+            self.categorizer.prefetch(file.entries.iter().filter_map(GitignoreEntry::comparison_pattern));
+        }
+        match order {
+            SortOrder::None => file.clone(),
+            SortOrder::Alpha => sort_sections(file, alpha),
+            SortOrder::Category => sort_sections(file, |pattern| {
+                let category = self.categorize(pattern.trim_start_matches('!'));
+                (category_rank(&category, &self.config.category_order), category.short_name(), alpha(pattern))
+            }),
+        }
+    }
+
+    /// Regroup patterns into one section per category, in the configured order
+    pub fn organize(&self, file: &GitignoreFile) -> Result<GitignoreFile, GixError> {
+        organize_gitignore_ordered(file, &self.categorizer, &self.comment_generator, &self.config.category_order)
+    }
+
+    /// Rewrite a file grouped by category, keeping each negation right after its partner where that is order-safe
+    pub fn organize_paired(&self, file: &GitignoreFile) -> Result<(GitignoreFile, Vec<MovedNegation>), GixError> {
+        organize_gitignore_paired(file, &self.categorizer, &self.comment_generator, &self.config.category_order)
+    }
+}
+
+impl Default for GixEngine {
+    fn default() -> Self {
+        Self::new(EngineConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_engine_is_send_and_sync() {
+        assert_send_sync::<GixEngine>();
+    }
+
+    #[test]
+    fn test_engine_shared_across_threads() {
+        let config = EngineConfig { mode: OptimizationMode::Advanced, offline: true, ..EngineConfig::default() };
+        let engine = Arc::new(GixEngine::new(config));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let engine = Arc::clone(&engine);
+                std::thread::spawn(move || {
+                    let file = engine.parse("*.log\nbuild/\nbuild/out.txt\n*.log").unwrap();
+                    let (optimized, redundant) = engine.optimize(&file).unwrap();
+                    (format!("{}", optimized), redundant.len())
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), ("*.log\nbuild/".to_string(), 1));
+        }
+    }
+
+    #[test]
+    fn test_categorize_is_cached() {
+        let engine = GixEngine::new(EngineConfig { offline: true, ..EngineConfig::default() });
+        let first = engine.categorize("local/");
+        assert_eq!(engine.categorize("local/"), first);
+        assert_eq!(engine.categories.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_optimize_many_keeps_going_past_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = ["a", "missing", "b", "c"].iter().map(|name| dir.path().join(name)).collect();
+        std::fs::write(&paths[0], "*.log\n*.log\n").unwrap();
+        std::fs::write(&paths[2], "target/\n").unwrap();
+        std::fs::write(&paths[3], "# only\n*.log\n").unwrap();
+
+        let engine = GixEngine::new(EngineConfig { offline: true, mode: OptimizationMode::Advanced, ..EngineConfig::default() });
+        let options = BatchOptions::default();
+        let results = engine.optimize_many(&paths, &options, &mut PassStats::default());
+
+        assert_eq!(results.iter().map(|result| &result.path).collect::<Vec<_>>(), paths.iter().collect::<Vec<_>>());
+        let first = results[0].outcome.as_ref().unwrap();
+        assert!(first.written);
+        assert_eq!(first.removed(), 1);
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "*.log\n");
+        assert!(matches!(results[1].outcome, Err(GixError::FileNotFound(_))));
+        assert!(!results[2].outcome.as_ref().unwrap().written);
+        assert!(results[3].outcome.is_ok());
+
+        std::fs::write(&paths[0], "*.log\n*.log\n").unwrap();
+        let dry_run = BatchOptions { dry_run: true, ..options };
+        let results = engine.optimize_many(&paths[..1], &dry_run, &mut PassStats::default());
+        assert_eq!(results[0].outcome.as_ref().unwrap().optimized_content, "*.log\n");
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "*.log\n*.log\n");
+    }
+}
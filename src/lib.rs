@@ -5,10 +5,13 @@
 
 pub mod cli;
 pub mod core;
+pub mod engine;
 pub mod models;
 pub mod utils;
 
 pub use models::errors::GixError;
 pub use models::gitignore::GitignoreFile;
 pub use core::parser::parse_gitignore;
-pub use core::optimizer::optimize_gitignore; 
\ No newline at end of file
+pub use core::optimizer::optimize_gitignore;
+pub use core::pipeline::{OptimizeResult, Optimizer, OptimizerBuilder};
+pub use engine::{BatchOptions, EngineConfig, FileOutcome, FileResult, GixEngine, OptimizedFile}; 
\ No newline at end of file
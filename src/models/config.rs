@@ -0,0 +1,511 @@
+use crate::core::organizer::DEFAULT_CATEGORY_ORDER;
+use crate::core::rules::{find_rule, Severity};
+use crate::models::{GixError, OptimizationMode, ReportFormat};
+use crate::utils::git::{repository_root, MetadataLocation};
+use clap::ValueEnum;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the config file read from the repository root
+pub const CONFIG_FILE_NAME: &str = "gix.toml";
+
+/// Settings read from `gix.toml`
+///
+/// Every setting is optional; command line flags override whatever is set here.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// Optimization mode used when `--mode` isn't given
+    pub mode: Option<OptimizationMode>,
+    /// Back up files before modifying them unless `--no-backup` is given
+    pub backup: Option<bool>,
+    /// Report format used when `--format` isn't given
+    pub format: Option<ReportFormat>,
+    /// Order of the sections written by `gix organize`, by category kind; unlisted kinds follow in the default order
+    pub category_order: Vec<String>,
+    /// Patterns optimization never removes, compared by their exact text (`keep` or `protect`)
+    pub keep: Vec<String>,
+    /// Where backups and the audit log go when `--metadata` isn't given
+    pub metadata: Option<MetadataLocation>,
+    /// Record writes in the history for `gix undo` unless `--no-history` is given; on when unset
+    pub history: Option<bool>,
+    /// Directory for backups when `--backup-dir` isn't given; relative paths are relative to the config file
+    pub backup_dir: Option<PathBuf>,
+    /// Backups kept of each file when `--backup-keep` isn't given
+    pub backup_keep: Option<usize>,
+    /// Treat patterns differing only in case as duplicates; unset follows git's `core.ignoreCase`
+    pub ignore_case: Option<bool>,
+    /// Severities `gix check` gives rules, by code or name, from the `allow`, `warn` and `deny` lists
+    pub rules: Vec<(String, Severity)>,
+    /// Commands run as plugins (see `CommandPlugin`); paths with a directory are relative to the config file
+    pub plugins: Vec<PathBuf>,
+    /// Plugins listed in a repository's `gix.toml`, run only with `--allow-plugins` since a checkout may not be trusted
+    pub repository_plugins: Vec<PathBuf>,
+}
+
+impl Config {
+    /// Parse a config file
+    ///
+    /// The format is a subset of TOML: `key = value` lines with strings, booleans and arrays
+    /// of strings (which may span lines), and `#` comments. Unknown keys are errors, so a
+    /// misspelled setting doesn't go unnoticed.
+    pub fn parse(text: &str) -> Result<Self, GixError> {
+        let mut config = Config::default();
+
+        for TomlSetting { line, table, key, value } in parse_settings(text)? {
+            let error = |message: String| GixError::ConfigError(format!("line {}: {}", line, message));
+            if let Some(table) = table {
+                return Err(error(format!("unknown table '[{}]'", table)));
+            }
+
+            match key.as_str() {
+                "mode" => {
+                    let name = parse_string(&value).ok_or_else(|| error("mode must be a string".to_string()))?;
+                    config.mode = Some(OptimizationMode::from_str(&name, true).map_err(|_| error(format!("unknown mode '{}'", name)))?);
+                }
+                "backup" => {
+                    config.backup = Some(parse_bool(&value).ok_or_else(|| error("backup must be true or false".to_string()))?);
+                }
+                "format" => {
+                    let name = parse_string(&value).ok_or_else(|| error("format must be a string".to_string()))?;
+                    config.format = Some(ReportFormat::from_str(&name, true).map_err(|_| error(format!("unknown format '{}'", name)))?);
+                }
+                "category_order" => {
+                    let kinds = parse_array(&value).ok_or_else(|| error("category_order must be an array of strings".to_string()))?;
+                    if let Some(unknown) = kinds.iter().find(|kind| !DEFAULT_CATEGORY_ORDER.contains(&kind.as_str())) {
+                        return Err(error(format!("unknown category kind '{}' (expected one of {})", unknown, DEFAULT_CATEGORY_ORDER.join(", "))));
+                    }
+                    config.category_order = kinds;
+                }
+                // `protect` is another name for `keep`; both lists apply
+                "keep" | "protect" => {
+                    config.keep.extend(parse_array(&value).ok_or_else(|| error(format!("{} must be an array of strings", key)))?);
+                }
+                "metadata" => {
+                    let name = parse_string(&value).ok_or_else(|| error("metadata must be a string".to_string()))?;
+                    config.metadata = Some(MetadataLocation::from_str(&name, true).map_err(|_| error(format!("unknown metadata location '{}' (expected worktree or git)", name)))?);
+                }
+                "history" => {
+                    config.history = Some(parse_bool(&value).ok_or_else(|| error("history must be true or false".to_string()))?);
+                }
+                "backup_dir" => {
+                    config.backup_dir = Some(PathBuf::from(parse_string(&value).ok_or_else(|| error("backup_dir must be a string".to_string()))?));
+                }
+                "backup_keep" => {
+                    config.backup_keep = Some(value.parse().map_err(|_| error("backup_keep must be a whole number".to_string()))?);
+                }
+                "ignore_case" => {
+                    config.ignore_case = Some(parse_bool(&value).ok_or_else(|| error("ignore_case must be true or false".to_string()))?);
+                }
+                "allow" | "warn" | "deny" => {
+                    let rules = parse_array(&value).ok_or_else(|| error(format!("{} must be an array of rule codes or names", key)))?;
+                    if let Some(unknown) = rules.iter().find(|rule| find_rule(rule).is_none()) {
+                        return Err(error(format!("unknown rule '{}'", unknown)));
+                    }
+                    let severity: Severity = key.parse()?;
+                    config.rules.extend(rules.into_iter().map(|rule| (rule, severity)));
+                }
+                "plugins" => {
+                    let commands = parse_array(&value).ok_or_else(|| error("plugins must be an array of commands".to_string()))?;
+                    config.plugins = commands.into_iter().map(PathBuf::from).collect();
+                }
+                _ => return Err(error(format!("unknown setting '{}'", key))),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Read and parse a config file, naming it in errors
+    pub fn read(path: &Path) -> Result<Self, GixError> {
+        let text = fs::read_to_string(path)?;
+        let mut config = Self::parse(&text).map_err(|e| match e {
+            GixError::ConfigError(message) => GixError::ConfigError(format!("{}: {}", path.display(), message)),
+            other => other,
+        })?;
+        if let (Some(dir), Some(parent)) = (&config.backup_dir, path.parent()) {
+            config.backup_dir = Some(parent.join(dir));
+        }
+        // A bare command name is looked up on PATH, as a shell would
+        if let Some(parent) = path.parent() {
+            for command in config.plugins.iter_mut().filter(|command| command.components().count() > 1) {
+                *command = parent.join(&*command);
+            }
+        }
+        Ok(config)
+    }
+
+    /// Read a repository's config file, its plugins kept apart in `repository_plugins`
+    pub fn read_repository(path: &Path) -> Result<Self, GixError> {
+        let mut config = Self::read(path)?;
+        config.repository_plugins = std::mem::take(&mut config.plugins);
+        Ok(config)
+    }
+
+    /// Load the config that applies to `dir`: the user's config, overridden by `gix.toml` at the repository root
+    pub fn load(dir: &Path) -> Result<Self, GixError> {
+        Self::load_layers(user_config_path().as_deref(), dir)
+    }
+
+    /// Load `user` (if it exists), overridden by `gix.toml` at the root of the repository containing `dir`
+    pub fn load_layers(user: Option<&Path>, dir: &Path) -> Result<Self, GixError> {
+        let mut config = match user.filter(|path| path.is_file()) {
+            Some(path) => Self::read(path)?,
+            None => Config::default(),
+        };
+        let repo = repository_root(dir).unwrap_or_else(|| dir.to_path_buf()).join(CONFIG_FILE_NAME);
+        if repo.is_file() {
+            config = config.overridden_by(Self::read_repository(&repo)?);
+        }
+        Ok(config)
+    }
+
+    /// Layer the `gix.toml` of each directory below `root`, down to and including `dir`, over this config
+    ///
+    /// Files nearer `dir` win. The file in `root` itself is left out, as this config is the one
+    /// loaded there; a `dir` outside `root` gets this config unchanged.
+    pub fn layered_below(&self, root: &Path, dir: &Path) -> Result<Config, GixError> {
+        let (Ok(root), Ok(dir)) = (root.canonicalize(), dir.canonicalize()) else {
+            return Ok(self.clone());
+        };
+        let Ok(relative) = dir.strip_prefix(&root) else {
+            return Ok(self.clone());
+        };
+
+        let mut config = self.clone();
+        let mut current = root.clone();
+        for component in relative.components() {
+            current.push(component);
+            let path = current.join(CONFIG_FILE_NAME);
+            if path.is_file() {
+                config = config.overridden_by(Self::read_repository(&path)?);
+            }
+        }
+        Ok(config)
+    }
+
+    /// Layer `other` over this config: settings `other` sets win, and both keep lists, rule severities and plugins apply
+    pub fn overridden_by(self, other: Config) -> Config {
+        let mut keep = self.keep;
+        for pattern in other.keep {
+            if !keep.contains(&pattern) {
+                keep.push(pattern);
+            }
+        }
+        let mut plugins = self.plugins;
+        for command in other.plugins {
+            if !plugins.contains(&command) {
+                plugins.push(command);
+            }
+        }
+        let mut repository_plugins = self.repository_plugins;
+        for command in other.repository_plugins {
+            if !repository_plugins.contains(&command) {
+                repository_plugins.push(command);
+            }
+        }
+        Config {
+            mode: other.mode.or(self.mode),
+            backup: other.backup.or(self.backup),
+            format: other.format.or(self.format),
+            category_order: if other.category_order.is_empty() { self.category_order } else { other.category_order },
+            keep,
+            metadata: other.metadata.or(self.metadata),
+            history: other.history.or(self.history),
+            backup_dir: other.backup_dir.or(self.backup_dir),
+            backup_keep: other.backup_keep.or(self.backup_keep),
+            ignore_case: other.ignore_case.or(self.ignore_case),
+            // Later severities win, so `other` overrides what this config says of the same rule
+            rules: self.rules.into_iter().chain(other.rules).collect(),
+            plugins,
+            repository_plugins,
+        }
+    }
+}
+
+/// Path of the user's config file, `$XDG_CONFIG_HOME/gix/config.toml` (`~/.config/gix/config.toml`)
+pub fn user_config_path() -> Option<PathBuf> {
+    user_config_path_from(env::var_os("XDG_CONFIG_HOME"), env::var_os("HOME"))
+}
+
+fn user_config_path_from(xdg_config_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let config_home = xdg_config_home
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("gix").join("config.toml"))
+}
+
+/// A `key = value` line of a TOML file, in the subset `Config::parse` describes
+pub(crate) struct TomlSetting {
+    /// Line the setting starts on, counting from 1
+    pub line: usize,
+    /// Name of the `[table]` the setting is in, if any
+    pub table: Option<String>,
+    /// The key, unquoted
+    pub key: String,
+    /// The value as written, an array spanning lines joined into one
+    pub value: String,
+}
+
+/// Split TOML text into its settings, leaving the values to be parsed by their keys
+pub(crate) fn parse_settings(text: &str) -> Result<Vec<TomlSetting>, GixError> {
+    let mut settings = Vec::new();
+    let mut table = None;
+    let mut lines = text.lines().enumerate();
+
+    while let Some((index, raw)) = lines.next() {
+        let error = |message: String| GixError::ConfigError(format!("line {}: {}", index + 1, message));
+        let line = strip_comment(raw).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            table = Some(name.trim().to_string());
+            continue;
+        }
+
+        let (key, value) = split_setting(line).ok_or_else(|| error("expected 'key = value'".to_string()))?;
+        let key = parse_key(key).ok_or_else(|| error(format!("quote the key '{}'", key)))?;
+        let mut value = value.to_string();
+        // Arrays may continue over the following lines until their closing bracket
+        while value.starts_with('[') && !value.ends_with(']') {
+            let (_, next) = lines.next().ok_or_else(|| error(format!("unclosed array for '{}'", key)))?;
+            value.push(' ');
+            value.push_str(strip_comment(next).trim());
+        }
+        settings.push(TomlSetting { line: index + 1, table: table.clone(), key, value });
+    }
+
+    Ok(settings)
+}
+
+/// Split a line at the `=` after its key, which may be quoted and contain one
+fn split_setting(line: &str) -> Option<(&str, &str)> {
+    let key_end = match line.chars().next()? {
+        quote @ ('"' | '\'') => line[1..].find(quote)? + 2,
+        _ => 0,
+    };
+    let equals = key_end + line[key_end..].find('=')?;
+    Some((line[..equals].trim(), line[equals + 1..].trim()))
+}
+
+/// Parse a bare key (letters, digits, `_` and `-`) or a quoted one
+fn parse_key(key: &str) -> Option<String> {
+    if key.starts_with(['"', '\'']) {
+        return parse_string(key);
+    }
+    let bare = !key.is_empty() && key.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-');
+    bare.then(|| key.to_string())
+}
+
+/// Cut a line at the first `#` outside a string
+fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (index, ch) in line.char_indices() {
+        match (quote, ch) {
+            _ if escaped => escaped = false,
+            (Some('"'), '\\') => escaped = true,
+            (Some(open), _) if ch == open => quote = None,
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '#') => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parse a basic string (`"..."` with `\"` and `\\` escapes) or a literal string (`'...'`)
+pub(crate) fn parse_string(value: &str) -> Option<String> {
+    if let Some(literal) = value.strip_prefix('\'').and_then(|rest| rest.strip_suffix('\'')) {
+        return (!literal.contains('\'')).then(|| literal.to_string());
+    }
+
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut parsed = String::new();
+    let mut chars = inner.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next()? {
+                'n' => parsed.push('\n'),
+                't' => parsed.push('\t'),
+                escaped @ ('"' | '\\') => parsed.push(escaped),
+                _ => return None,
+            },
+            '"' => return None,
+            _ => parsed.push(ch),
+        }
+    }
+    Some(parsed)
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse an array of strings, allowing a trailing comma
+pub(crate) fn parse_array(value: &str) -> Option<Vec<String>> {
+    let inner = value.strip_prefix('[')?.strip_suffix(']')?.trim();
+    let mut items = Vec::new();
+    let mut rest = inner;
+    while !rest.is_empty() {
+        let quote = rest.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        // Find the closing quote, skipping escaped ones in basic strings
+        let mut end = None;
+        let mut escaped = false;
+        for (index, ch) in rest.char_indices().skip(1) {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' if quote == '"' => escaped = true,
+                _ if ch == quote => {
+                    end = Some(index);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let end = end?;
+        items.push(parse_string(&rest[..=end])?);
+        rest = rest[end + 1..].trim_start();
+        rest = match rest.strip_prefix(',') {
+            Some(after) => after.trim_start(),
+            None if rest.is_empty() => rest,
+            None => return None,
+        };
+    }
+    Some(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(concat!(
+            "# gix settings\n",
+            "mode = \"advanced\"  # optimize harder\n",
+            "backup = true\n",
+            "format = 'github' # 'ci'\n",
+            "metadata = \"git\"\n",
+            "history = false\n",
+            "backup_dir = \"../backups\"\n",
+            "backup_keep = 3 # a few\n",
+            "ignore_case = true\n",
+            "allow = [\"GIX001\"]\n",
+            "warn = [\"conflict\"]\n",
+            "protect = [\"Cargo.lock\"]\n",
+            "plugins = [\"acme-gix\"]\n",
+            "category_order = [\"os\", \"language\"]\n",
+            "keep = [\n  \".env\",\n  \"\\\"quoted\\\" #name\", # trailing\n]\n",
+        )).unwrap();
+
+        assert_eq!(config, Config {
+            mode: Some(OptimizationMode::Advanced),
+            backup: Some(true),
+            format: Some(ReportFormat::Github),
+            category_order: vec!["os".to_string(), "language".to_string()],
+            keep: vec!["Cargo.lock".to_string(), ".env".to_string(), "\"quoted\" #name".to_string()],
+            metadata: Some(MetadataLocation::Git),
+            history: Some(false),
+            backup_dir: Some(PathBuf::from("../backups")),
+            backup_keep: Some(3),
+            ignore_case: Some(true),
+            rules: vec![("GIX001".to_string(), Severity::Allow), ("conflict".to_string(), Severity::Warn)],
+            plugins: vec![PathBuf::from("acme-gix")],
+            repository_plugins: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn test_parse_config_errors() {
+        let message = |text: &str| Config::parse(text).unwrap_err().to_string();
+        assert!(message("mode = \"turbo\"").contains("line 1: unknown mode 'turbo'"));
+        assert!(message("\nbakup = true").contains("line 2: unknown setting 'bakup'"));
+        assert!(message("backup = yes").contains("backup must be true or false"));
+        assert!(message("backup_keep = \"3\"").contains("backup_keep must be a whole number"));
+        assert!(message("ignore_case = 1").contains("ignore_case must be true or false"));
+        assert!(message("history = off").contains("history must be true or false"));
+        assert!(message("deny = [\"GIX999\"]").contains("unknown rule 'GIX999'"));
+        assert!(message("metadata = \"hidden\"").contains("unknown metadata location 'hidden'"));
+        assert!(message("category_order = [\"editors\"]").contains("unknown category kind 'editors'"));
+        assert!(message("keep = [\".env\"").contains("unclosed array"));
+        assert!(message("keep = [.env]").contains("keep must be an array of strings"));
+    }
+
+    #[test]
+    fn test_repository_config_overrides_user_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let user = dir.path().join("user.toml");
+        fs::write(&user, "mode = \"aggressive\"\nbackup = true\nkeep = [\".env\"]\n").unwrap();
+
+        assert_eq!(Config::load_layers(Some(&user), &dir.path().join("sub")).unwrap().mode, Some(OptimizationMode::Aggressive));
+
+        fs::write(dir.path().join(CONFIG_FILE_NAME), "mode = \"conservative\"\nkeep = [\"*.log\"]\n").unwrap();
+        let config = Config::load_layers(Some(&user), &dir.path().join("sub")).unwrap();
+        assert_eq!(config.mode, Some(OptimizationMode::Conservative));
+        assert_eq!(config.backup, Some(true));
+        assert_eq!(config.keep, vec![".env", "*.log"]);
+
+        assert_eq!(Config::load_layers(Some(&dir.path().join("missing.toml")), dir.path()).unwrap().backup, None);
+    }
+
+    #[test]
+    fn test_repository_plugins_kept_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let user = dir.path().join("user.toml");
+        fs::write(&user, "plugins = [\"acme-gix\"]\n").unwrap();
+        fs::write(dir.path().join(CONFIG_FILE_NAME), "plugins = [\"./tools/lint\"]\n").unwrap();
+        fs::write(dir.path().join("sub").join(CONFIG_FILE_NAME), "plugins = [\"evil\"]\n").unwrap();
+
+        let config = Config::load_layers(Some(&user), dir.path()).unwrap();
+        assert_eq!(config.plugins, vec![PathBuf::from("acme-gix")]);
+        assert_eq!(config.repository_plugins, vec![dir.path().join("./tools/lint")]);
+
+        let nested = config.layered_below(dir.path(), &dir.path().join("sub")).unwrap();
+        assert_eq!(nested.plugins, vec![PathBuf::from("acme-gix")]);
+        assert_eq!(nested.repository_plugins, vec![dir.path().join("./tools/lint"), PathBuf::from("evil")]);
+    }
+
+    #[test]
+    fn test_nested_config_layers() {
+        let dir = tempfile::tempdir().unwrap();
+        let services = dir.path().join("services").join("api");
+        fs::create_dir_all(&services).unwrap();
+        fs::create_dir_all(dir.path().join("legacy")).unwrap();
+        fs::write(dir.path().join(CONFIG_FILE_NAME), "mode = \"advanced\"\n").unwrap();
+        fs::write(dir.path().join("services").join(CONFIG_FILE_NAME), "mode = \"aggressive\"\nkeep = [\"*.pid\"]\n").unwrap();
+        fs::write(services.join(CONFIG_FILE_NAME), "backup = true\n").unwrap();
+        fs::write(dir.path().join("legacy").join(CONFIG_FILE_NAME), "mode = \"conservative\"\n").unwrap();
+        let root = Config { keep: vec![".env".to_string()], ..Config::default() };
+
+        let config = root.layered_below(dir.path(), &services).unwrap();
+        assert_eq!(config.mode, Some(OptimizationMode::Aggressive));
+        assert_eq!(config.backup, Some(true));
+        assert_eq!(config.keep, vec![".env", "*.pid"]);
+        assert_eq!(root.layered_below(dir.path(), &dir.path().join("legacy")).unwrap().mode, Some(OptimizationMode::Conservative));
+        assert_eq!(root.layered_below(dir.path(), dir.path()).unwrap(), root);
+        assert_eq!(root.layered_below(&services, dir.path()).unwrap(), root);
+    }
+
+    #[test]
+    fn test_user_config_path() {
+        let path = |xdg: Option<&str>, home: Option<&str>| user_config_path_from(xdg.map(OsString::from), home.map(OsString::from));
+        assert_eq!(path(Some("/xdg"), Some("/home/me")), Some(PathBuf::from("/xdg/gix/config.toml")));
+        assert_eq!(path(Some(""), Some("/home/me")), Some(PathBuf::from("/home/me/.config/gix/config.toml")));
+        assert_eq!(path(None, None), None);
+    }
+}
@@ -1,5 +1,16 @@
 use thiserror::Error;
 
+/// Exit status when nothing changed and no issue was found
+pub const EXIT_CLEAN: i32 = 0;
+/// Exit status when checks found issues, or with `--exit-code` when optimization changed a file
+pub const EXIT_CHANGES: i32 = 1;
+/// Exit status for invalid arguments or settings, or when gix refuses to go on (clap uses it for its own usage errors too)
+pub const EXIT_USAGE: i32 = 2;
+/// Exit status when a file, git or the network could not be read or written
+pub const EXIT_IO: i32 = 3;
+/// Exit status when a file gix reads could not be parsed or decoded
+pub const EXIT_PARSE: i32 = 4;
+
 #[derive(Debug, Error)]
 pub enum GixError {
     #[error("File not found: {0}")]
@@ -12,4 +23,128 @@ pub enum GixError {
     IoError(#[from] std::io::Error),
     #[error("Parse error: {0}")]
     ParseError(String),
-} 
\ No newline at end of file
+    #[error("Invalid config: {0}")]
+    ConfigError(String),
+    #[error("Invalid arguments: {0}")]
+    UsageError(String),
+    #[error("Check failed: {0} issue(s) found")]
+    CheckFailed(usize),
+    #[error("Conflicts found: {0} conflicting pattern pair(s)")]
+    ConflictsFound(usize),
+    #[error("File is empty: {0} (run `gix init --like <repo>` to start one)")]
+    EmptyFile(String),
+    #[error("File already exists: {0} (use --force to overwrite)")]
+    AlreadyExists(String),
+    #[error("Optimizing would leave no patterns in {0} (use --allow-empty to write it anyway, or delete the file)")]
+    WouldBeEmpty(String),
+    #[error("{failed} of {total} file(s) could not be optimized")]
+    BatchFailed { failed: usize, total: usize },
+    #[error("Unknown category: {0}")]
+    UnknownCategory(String),
+    #[error("Not idempotent: optimizing the result for {0} changes it again")]
+    NotIdempotent(String),
+    #[error("Unsafe rewrite: {0}")]
+    UnsafeRewrite(String),
+    #[error("Already covered: {0} (use --force to add it anyway)")]
+    AlreadyCovered(String),
+    #[error("Audit log broken: {0}")]
+    AuditLogBroken(String),
+    #[error("Not equivalent: {0} path(s) decided differently")]
+    NotEquivalent(usize),
+    #[error("Self-test failed: {0} path(s) decided differently from git")]
+    SelfTestFailed(usize),
+    #[error("Invalid plan: {0}")]
+    InvalidPlan(String),
+    #[error("Plan out of date: {0} changed since the plan was made")]
+    StalePlan(String),
+    #[error("Cannot undo: {0} changed since gix wrote it")]
+    ChangedSinceWrite(String),
+    #[error("Git error: {0}")]
+    GitError(String),
+    #[error("Network error: {0}")]
+    NetworkError(String),
+    #[error("Watch error: {0}")]
+    WatchError(String),
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+} 
+
+impl GixError {
+    /// Get the exit status gix ends with after this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            GixError::CheckFailed(_)
+            | GixError::ConflictsFound(_)
+            | GixError::BatchFailed { .. } => EXIT_CHANGES,
+            GixError::InvalidPattern(_)
+            | GixError::ConfigError(_)
+            | GixError::UsageError(_)
+            | GixError::UnknownCategory(_)
+            | GixError::AlreadyExists(_) => EXIT_USAGE,
+            // Refusals: gix won't write, or won't vouch for a result, without the user stepping in
+            GixError::EmptyFile(_)
+            | GixError::WouldBeEmpty(_)
+            | GixError::AlreadyCovered(_)
+            | GixError::StalePlan(_)
+            | GixError::ChangedSinceWrite(_)
+            | GixError::UnsafeRewrite(_)
+            | GixError::NotIdempotent(_)
+            | GixError::NotEquivalent(_)
+            | GixError::AuditLogBroken(_)
+            | GixError::SelfTestFailed(_) => EXIT_USAGE,
+            // Text that isn't valid UTF-8 couldn't be decoded, which is no fault of the disk
+            GixError::IoError(error) if error.kind() == std::io::ErrorKind::InvalidData => EXIT_PARSE,
+            GixError::FileNotFound(_)
+            | GixError::PermissionDenied(_)
+            | GixError::IoError(_)
+            | GixError::GitError(_)
+            | GixError::NetworkError(_)
+            | GixError::WatchError(_)
+            | GixError::ChecksumMismatch { .. } => EXIT_IO,
+            GixError::ParseError(_) | GixError::InvalidPlan(_) => EXIT_PARSE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        let file = || ".gitignore".to_string();
+        let cases = [
+            (GixError::CheckFailed(2), EXIT_CHANGES),
+            (GixError::ConflictsFound(1), EXIT_CHANGES),
+            (GixError::BatchFailed { failed: 1, total: 3 }, EXIT_CHANGES),
+            (GixError::InvalidPattern("[a".to_string()), EXIT_USAGE),
+            (GixError::ConfigError("line 1".to_string()), EXIT_USAGE),
+            (GixError::UsageError("--line 9".to_string()), EXIT_USAGE),
+            (GixError::UnknownCategory("editors".to_string()), EXIT_USAGE),
+            (GixError::AlreadyExists(file()), EXIT_USAGE),
+            (GixError::EmptyFile(file()), EXIT_USAGE),
+            (GixError::WouldBeEmpty(file()), EXIT_USAGE),
+            (GixError::AlreadyCovered("*.log".to_string()), EXIT_USAGE),
+            (GixError::StalePlan(file()), EXIT_USAGE),
+            (GixError::ChangedSinceWrite(file()), EXIT_USAGE),
+            (GixError::UnsafeRewrite(file()), EXIT_USAGE),
+            (GixError::NotIdempotent(file()), EXIT_USAGE),
+            (GixError::NotEquivalent(2), EXIT_USAGE),
+            (GixError::AuditLogBroken("line 3".to_string()), EXIT_USAGE),
+            (GixError::SelfTestFailed(1), EXIT_USAGE),
+            (GixError::FileNotFound(file()), EXIT_IO),
+            (GixError::PermissionDenied(file()), EXIT_IO),
+            (GixError::IoError(std::io::Error::other("disk full")), EXIT_IO),
+            (GixError::GitError("not a repository".to_string()), EXIT_IO),
+            (GixError::NetworkError("timed out".to_string()), EXIT_IO),
+            (GixError::WatchError("too many watches".to_string()), EXIT_IO),
+            (GixError::ChecksumMismatch { expected: "a".to_string(), actual: "b".to_string() }, EXIT_IO),
+            (GixError::IoError(std::io::Error::new(std::io::ErrorKind::InvalidData, "stream did not contain valid UTF-8")), EXIT_PARSE),
+            (GixError::ParseError("line 2".to_string()), EXIT_PARSE),
+            (GixError::InvalidPlan("missing version".to_string()), EXIT_PARSE),
+        ];
+        for (error, code) in cases {
+            assert_eq!(error.exit_code(), code, "{:?}", error);
+        }
+    }
+}
@@ -1,4 +1,64 @@
-use std::collections::HashMap;
+use crate::utils::patterns::GlobPattern;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Byte order mark some Windows editors put at the start of UTF-8 files
+pub const UTF8_BOM: char = '\u{feff}';
+
+/// Line ending used when writing a gitignore file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// Unix line endings (`\n`)
+    #[default]
+    Lf,
+    /// Windows line endings (`\r\n`)
+    CrLf,
+}
+
+impl LineEnding {
+    /// The characters that end a line
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// Detect the line ending used by most lines of `content`, `Lf` when it has none
+    pub fn detect(content: &str) -> Self {
+        let breaks = content.matches('\n').count();
+        let crlf = content.matches("\r\n").count();
+        if crlf * 2 > breaks { LineEnding::CrLf } else { LineEnding::Lf }
+    }
+}
+
+/// Options controlling how a gitignore file is rendered for writing
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WriteOptions {
+    /// Line ending placed between lines
+    pub line_ending: LineEnding,
+    /// Whether to end the last line with a line ending
+    pub trailing_newline: bool,
+    /// Text written as a comment block at the top of the file
+    pub banner: Option<String>,
+    /// Whether to start the file with a UTF-8 byte order mark
+    pub bom: bool,
+}
+
+impl WriteOptions {
+    /// Options that keep the encoding details recorded when `original` was parsed
+    ///
+    /// Serializing an untouched file with these options reproduces its content byte for byte,
+    /// unless it mixes line endings.
+    pub fn preserving(original: &GitignoreFile) -> Self {
+        Self {
+            line_ending: original.line_ending,
+            trailing_newline: original.trailing_newline,
+            bom: original.has_bom,
+            ..Self::default()
+        }
+    }
+}
 
 /// Represents the type of a gitignore entry
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -20,6 +80,10 @@ pub struct GitignoreEntry {
     pub entry_type: EntryType,
     /// Line number (1-indexed)
     pub line_number: usize,
+    /// Inline comment following the pattern, starting at the unescaped `#`
+    pub inline_comment: Option<String>,
+    /// The entry is inside a `# gix:off` ... `# gix:on` region, which nothing may modify
+    pub preserve: bool,
 }
 
 impl GitignoreEntry {
@@ -29,6 +93,8 @@ impl GitignoreEntry {
             original,
             entry_type,
             line_number,
+            inline_comment: None,
+            preserve: false,
         }
     }
 
@@ -54,6 +120,149 @@ impl GitignoreEntry {
             _ => None,
         }
     }
+
+    /// Get the pattern used to compare entries with each other
+    ///
+    /// Whitespace between a pattern and its inline comment only separates the two,
+    /// so `*.log # a` and `*.log\t# b` compare equal to `*.log`.
+    pub fn comparison_pattern(&self) -> Option<String> {
+        match &self.entry_type {
+            EntryType::Pattern(pattern) if self.inline_comment.is_some() => Some(pattern.trim_end().to_string()),
+            EntryType::Pattern(pattern) => Some(pattern.clone()),
+            _ => None,
+        }
+    }
+
+    /// Replace the pattern, keeping the line's leading whitespace and inline comment as they were
+    ///
+    /// `pattern` replaces the text `comparison_pattern` gives without its leading whitespace, so
+    /// rewriting `  *.LOG  # logs` to `*.log` gives `  *.log  # logs`. Comments and blank lines
+    /// are left alone, returning false.
+    pub fn rewrite_pattern(&mut self, pattern: &str) -> bool {
+        let EntryType::Pattern(old) = &self.entry_type else {
+            return false;
+        };
+        let start = old.len() - old.trim_start().len();
+        let end = match self.inline_comment {
+            Some(_) => old.trim_end().len().max(start),
+            None => old.len(),
+        };
+        let rewritten = format!("{}{}{}", &old[..start], pattern, &old[end..]);
+        self.original = format!("{}{}", rewritten, self.inline_comment.as_deref().unwrap_or_default());
+        self.entry_type = EntryType::Pattern(rewritten);
+        true
+    }
+
+    /// Get the span of the entry's pattern, or of the whole line for comments and blank lines
+    ///
+    /// Inline comments and the whitespace before them are left out, as in `comparison_pattern`.
+    pub fn span(&self) -> Span {
+        let text = self.comparison_pattern().unwrap_or_else(|| self.original.clone());
+        Span { line: self.line_number, start_column: 1, end_column: 1 + text.chars().count() }
+    }
+}
+
+/// Comment marking the pattern on the next line as protected from optimization
+pub const KEEP_MARKER: &str = "# gix:keep";
+
+/// Check if a comment is a `# gix:keep` marker
+pub fn is_keep_marker(comment: &str) -> bool {
+    comment.trim_start_matches('#').trim() == KEEP_MARKER.trim_start_matches('#').trim()
+}
+
+/// A header comment block and the entries following it, up to the next header
+///
+/// A header is a run of comments at the start of the file or after a blank line. Lines before
+/// the first header form a section without one.
+#[derive(Debug, Clone, Copy)]
+pub struct Section<'a> {
+    /// The comment lines heading the section, empty for lines before the first header
+    pub header: &'a [GitignoreEntry],
+    /// The entries after the header, including blank lines before the next section
+    pub entries: &'a [GitignoreEntry],
+}
+
+impl<'a> Section<'a> {
+    /// Name of the section: its first header line without the `#`
+    pub fn name(&self) -> Option<&'a str> {
+        self.header.first().map(|entry| entry.original.trim_start_matches('#').trim())
+    }
+
+    /// Line number of the first line of the section
+    pub fn start_line(&self) -> usize {
+        self.header.first().or(self.entries.first()).map_or(0, |entry| entry.line_number)
+    }
+
+    /// Pattern entries of the section
+    pub fn patterns(&self) -> impl Iterator<Item = &'a GitignoreEntry> {
+        self.entries.iter().filter(|entry| entry.is_pattern())
+    }
+}
+
+/// A range of characters on one line, locating a finding for editors and CI annotations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// Line number (1-indexed)
+    pub line: usize,
+    /// Column of the first character (1-indexed, counted in characters)
+    pub start_column: usize,
+    /// Column just past the last character
+    pub end_column: usize,
+}
+
+/// The pattern that decided whether a path is ignored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathMatch {
+    /// Whether the path is ignored
+    pub ignored: bool,
+    /// Line number of the deciding pattern (1-indexed)
+    pub line_number: usize,
+    /// The deciding pattern
+    pub pattern: String,
+    /// The parent directory the pattern matched, when it decided through a directory
+    pub via_directory: Option<String>,
+}
+
+/// The patterns of a gitignore file compiled for matching, from `GitignoreFile::matcher`
+#[derive(Debug, Clone)]
+pub struct GitignoreMatcher<'a> {
+    compiled: Vec<(&'a GitignoreEntry, GlobPattern)>,
+}
+
+impl GitignoreMatcher<'_> {
+    /// Decide whether a path is ignored, as `GitignoreFile::matches` does
+    pub fn matches(&self, path: &str, is_dir: bool) -> Option<PathMatch> {
+        let path = path.trim_start_matches("./").trim_start_matches('/');
+        let (path, is_dir) = match path.strip_suffix('/') {
+            Some(dir) => (dir, true),
+            None => (path, is_dir),
+        };
+        let mut decided = None;
+        for (end, _) in path.match_indices('/') {
+            let Some(mut verdict) = self.decide(&path[..end], true) else {
+                continue;
+            };
+            verdict.via_directory = Some(format!("{}/", &path[..end]));
+            if verdict.ignored {
+                return Some(verdict);
+            }
+            decided = Some(verdict);
+        }
+        self.decide(path, is_dir).or(decided)
+    }
+
+    /// Find the last pattern matching `path` itself, without looking at its parents
+    fn decide(&self, path: &str, is_dir: bool) -> Option<PathMatch> {
+        self.compiled.iter()
+            .rev()
+            .find(|(_, glob)| glob.matches_path(path, is_dir))
+            .map(|(entry, glob)| PathMatch {
+                ignored: !glob.negated,
+                line_number: entry.line_number,
+                pattern: entry.comparison_pattern().unwrap_or_default(),
+                via_directory: None,
+            })
+    }
 }
 
 /// Represents a complete .gitignore file
@@ -63,6 +272,12 @@ pub struct GitignoreFile {
     pub entries: Vec<GitignoreEntry>,
     /// Statistics about the file
     pub stats: FileStats,
+    /// Whether the parsed content started with a UTF-8 byte order mark
+    pub has_bom: bool,
+    /// Line ending used by most lines of the parsed content
+    pub line_ending: LineEnding,
+    /// Whether the parsed content ended with a line ending
+    pub trailing_newline: bool,
 }
 
 impl GitignoreFile {
@@ -71,15 +286,205 @@ impl GitignoreFile {
         Self {
             entries: Vec::new(),
             stats: FileStats::new(),
+            has_bom: false,
+            line_ending: LineEnding::Lf,
+            trailing_newline: false,
         }
     }
 
+    /// Take the encoding details recorded when `original` was parsed (BOM, line ending, trailing newline)
+    pub fn keep_format_of(&mut self, original: &GitignoreFile) {
+        self.has_bom = original.has_bom;
+        self.line_ending = original.line_ending;
+        self.trailing_newline = original.trailing_newline;
+    }
+
+    /// Get the span of the entry on a line, or an empty span at its start if there is none
+    pub fn span_of(&self, line_number: usize) -> Span {
+        self.entries.iter()
+            .find(|entry| entry.line_number == line_number)
+            .map_or(Span { line: line_number, start_column: 1, end_column: 1 }, GitignoreEntry::span)
+    }
+
     /// Add an entry to the file
     pub fn add_entry(&mut self, entry: GitignoreEntry) {
         self.stats.update(&entry);
         self.entries.push(entry);
     }
 
+    /// Append patterns in a new section headed by a comment, after a blank line
+    ///
+    /// Nothing is added when `patterns` is empty.
+    pub fn append_section(&mut self, header: &str, patterns: &[String]) {
+        if patterns.is_empty() {
+            return;
+        }
+
+        let mut lines = Vec::new();
+        if self.entries.last().is_some_and(|entry| !entry.is_blank()) {
+            lines.push((String::new(), EntryType::Blank));
+        }
+        lines.push((header.to_string(), EntryType::Comment(header.to_string())));
+        lines.extend(patterns.iter().map(|pattern| (pattern.clone(), EntryType::Pattern(pattern.clone()))));
+        for (line, entry_type) in lines {
+            let line_number = self.entries.len() + 1;
+            self.add_entry(GitignoreEntry::new(line, entry_type, line_number));
+        }
+    }
+
+    /// Add a pattern at the end of the section named `section` (see `Section::name`), or of the file
+    ///
+    /// The pattern goes after the last line of the section that isn't blank, so the blank lines
+    /// separating it from the next section stay where they are. A section that doesn't exist yet
+    /// is appended, headed `# <section>`. Entries are renumbered to their position in the file;
+    /// the line number of the new pattern is returned. The pattern is added as given, so check
+    /// it with `validate_pattern` first.
+    pub fn add_pattern(&mut self, pattern: &str, section: Option<&str>) -> usize {
+        let range = match section {
+            None => Some(0..self.entries.len()),
+            Some(name) => self.section_range(name),
+        };
+        let Some(range) = range else {
+            self.append_section(&format!("# {}", section.unwrap_or_default()), &[pattern.to_string()]);
+            self.renumber();
+            return self.entries.len();
+        };
+
+        let index = self.entries[range.clone()].iter()
+            .rposition(|entry| !entry.is_blank())
+            .map_or(range.start, |last| range.start + last + 1);
+        self.entries.insert(index, GitignoreEntry::new(pattern.to_string(), EntryType::Pattern(pattern.to_string()), 0));
+        self.renumber();
+        index + 1
+    }
+
+    /// Remove every occurrence of a pattern, compared as `comparison_pattern` gives it
+    ///
+    /// Occurrences in a `# gix:off` region or right below a `# gix:keep` marker stay. Comments
+    /// stay too, and a blank line left next to another blank line (or at the start or end of
+    /// the file) by the removal goes. Entries are renumbered to their position in the file; the
+    /// number of patterns removed is returned.
+    pub fn remove_pattern(&mut self, pattern: &str) -> usize {
+        let lines = self.editable_occurrences(pattern);
+        self.remove_lines(&lines)
+    }
+
+    /// Remove the patterns on the given lines, as `remove_pattern` does
+    ///
+    /// Lines that hold no pattern are left alone; the number of patterns removed is returned.
+    pub fn remove_lines(&mut self, line_numbers: &[usize]) -> usize {
+        let before = self.entries.len();
+        let mut removed = 0;
+        let mut after_removal = false;
+        let mut kept: Vec<GitignoreEntry> = Vec::with_capacity(before);
+        for entry in self.entries.drain(..) {
+            if entry.is_pattern() && line_numbers.contains(&entry.line_number) {
+                removed += 1;
+                after_removal = true;
+                continue;
+            }
+            let doubled = entry.is_blank() && kept.last().is_none_or(GitignoreEntry::is_blank);
+            if !(doubled && after_removal) {
+                kept.push(entry);
+            }
+            after_removal = after_removal && doubled;
+        }
+        while after_removal && kept.last().is_some_and(GitignoreEntry::is_blank) {
+            kept.pop();
+        }
+        self.entries = kept;
+        if self.entries.len() != before {
+            self.renumber();
+        }
+        removed
+    }
+
+    /// Replace every occurrence of a pattern with another, keeping leading whitespace and inline comments
+    ///
+    /// Occurrences are found as in `remove_pattern`, protected ones left alone, and rewritten
+    /// with `GitignoreEntry::rewrite_pattern`. Lines keep their numbers; the number of patterns
+    /// replaced is returned. `new` is written as given, so check it with `validate_pattern` first.
+    pub fn replace_pattern(&mut self, old: &str, new: &str) -> usize {
+        let lines = self.editable_occurrences(old);
+        for entry in self.entries.iter_mut().filter(|entry| lines.contains(&entry.line_number)) {
+            entry.rewrite_pattern(new);
+        }
+        lines.len()
+    }
+
+    /// Find the lines holding a pattern outside `# gix:off` regions and not marked `# gix:keep`
+    fn editable_occurrences(&self, pattern: &str) -> Vec<usize> {
+        let mut marked = false;
+        let mut lines = Vec::new();
+        for entry in &self.entries {
+            if !marked && !entry.preserve && entry.comparison_pattern().as_deref() == Some(pattern) {
+                lines.push(entry.line_number);
+            }
+            marked = matches!(&entry.entry_type, EntryType::Comment(comment) if is_keep_marker(comment));
+        }
+        lines
+    }
+
+    /// Find the entries of the first section with this name, header included
+    fn section_range(&self, name: &str) -> Option<std::ops::Range<usize>> {
+        let mut start = 0;
+        for section in self.sections() {
+            let len = section.header.len() + section.entries.len();
+            if section.name() == Some(name) {
+                return Some(start..start + len);
+            }
+            start += len;
+        }
+        None
+    }
+
+    /// Number the entries by their position and count them again, after entries were added or removed
+    fn renumber(&mut self) {
+        self.stats = FileStats::new();
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            entry.line_number = index + 1;
+            self.stats.update(entry);
+        }
+    }
+
+    /// Decide whether a path is ignored by this file, as git does, and which line decided it
+    ///
+    /// `path` is relative to the directory holding the file, with `/` separators. The last
+    /// matching pattern wins, and once a parent directory is ignored nothing below it can be
+    /// re-included. `None` means no pattern matches the path or its parents, so it is not ignored.
+    pub fn matches(&self, path: &str, is_dir: bool) -> Option<PathMatch> {
+        self.matcher().matches(path, is_dir)
+    }
+
+    /// Compile the patterns once, to decide many paths (see `matches`)
+    pub fn matcher(&self) -> GitignoreMatcher<'_> {
+        let compiled = self.entries.iter()
+            .filter_map(|entry| Some((entry, GlobPattern::parse(&entry.comparison_pattern()?))))
+            .collect();
+        GitignoreMatcher { compiled }
+    }
+
+    /// Split the file into sections, in file order (see `Section`)
+    pub fn sections(&self) -> Vec<Section<'_>> {
+        let starts_header = |index: usize| {
+            self.entries[index].is_comment() && (index == 0 || self.entries[index - 1].is_blank())
+        };
+        let mut starts: Vec<usize> = (0..self.entries.len()).filter(|&index| starts_header(index)).collect();
+        if starts.first() != Some(&0) && !self.entries.is_empty() {
+            starts.insert(0, 0);
+        }
+
+        starts.iter()
+            .enumerate()
+            .map(|(position, &start)| {
+                let end = starts.get(position + 1).copied().unwrap_or(self.entries.len());
+                let lines = &self.entries[start..end];
+                let header_len = if starts_header(start) { lines.iter().take_while(|entry| entry.is_comment()).count() } else { 0 };
+                Section { header: &lines[..header_len], entries: &lines[header_len..] }
+            })
+            .collect()
+    }
+
     /// Get all pattern entries
     pub fn patterns(&self) -> Vec<&GitignoreEntry> {
         self.entries.iter().filter(|e| e.is_pattern()).collect()
@@ -91,35 +496,216 @@ impl GitignoreFile {
     }
 
     /// Convert back to string representation
+    #[deprecated(note = "use `Display` (`format!(\"{}\", file)`) or `serialize(&WriteOptions)` instead")]
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(&self) -> String {
-        self.entries
-            .iter()
-            .map(|entry| entry.original.clone())
-            .collect::<Vec<_>>()
-            .join("\n")
+        self.serialize(&WriteOptions::default())
+    }
+
+    /// Render the file with the given line ending, trailing newline and banner settings
+    ///
+    /// Use `WriteOptions::preserving(&file)` to write it back the way it was read.
+    pub fn serialize(&self, options: &WriteOptions) -> String {
+        let mut lines: Vec<String> = Vec::new();
+
+        if let Some(banner) = &options.banner {
+            lines.extend(banner.lines().map(|line| {
+                if line.starts_with('#') {
+                    line.to_string()
+                } else if line.is_empty() {
+                    "#".to_string()
+                } else {
+                    format!("# {}", line)
+                }
+            }));
+            if !self.entries.is_empty() {
+                lines.push(String::new());
+            }
+        }
+
+        lines.extend(self.entries.iter().map(|entry| entry.original.clone()));
+
+        let newline = options.line_ending.as_str();
+        let mut content = lines.join(newline);
+        if options.trailing_newline && !lines.is_empty() {
+            content.push_str(newline);
+        }
+        if options.bom {
+            content.insert(0, UTF8_BOM);
+        }
+        content
     }
 
-    /// Find duplicate patterns
-    pub fn find_duplicates(&self) -> HashMap<String, Vec<usize>> {
-        let mut duplicates: HashMap<String, Vec<usize>> = HashMap::new();
+    /// Stable identifiers of the entries, aligned with `entries` (`None` for comments and blank lines)
+    ///
+    /// An identifier hashes the pattern with the first header comment of its section (see
+    /// `sections`), plus a counter for repeats within the section, so it survives edits elsewhere
+    /// in the file that shift line numbers, including comments added or reworded inside the section.
+    pub fn entry_ids(&self) -> Vec<Option<String>> {
+        let mut occurrences: HashMap<(Option<&str>, String), usize> = HashMap::new();
         
-        for entry in &self.entries {
-            if let Some(normalized) = entry.normalized_pattern() {
-                duplicates
-                    .entry(normalized)
-                    .or_insert_with(Vec::new)
-                    .push(entry.line_number);
+        self.sections().into_iter()
+            .flat_map(|section| {
+                let header = section.header.first().map(|comment| comment.original.trim());
+                section.header.iter().map(|_| None)
+                    .chain(section.entries.iter().map(move |entry| Some((header, entry))))
+            })
+            .map(|line| {
+                let (section, entry) = line?;
+                let pattern = entry.comparison_pattern()?;
+                let occurrence = occurrences.entry((section, pattern.clone())).or_insert(0);
+                *occurrence += 1;
+                let key = format!("{}\0{}\0{}", section.unwrap_or(""), pattern, occurrence);
+                Some(crate::utils::stable_hash(&key))
+            })
+            .collect()
+    }
+
+    /// Find duplicate patterns, ordered by the line of their first occurrence
+    ///
+    /// Each occurrence records the first header comment of its section (see `sections`).
+    pub fn find_duplicates(&self) -> DuplicateReport {
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut groups: Vec<DuplicateGroup> = Vec::new();
+        let entries = self.sections().into_iter().flat_map(|section| {
+            let header = section.header.first().map(|comment| comment.original.trim().to_string());
+            section.entries.iter().map(move |entry| (entry, header.clone()))
+        });
+        
+        for (entry, section) in entries {
+            if let Some(normalized) = entry.comparison_pattern() {
+                match index.get(&normalized) {
+                    Some(&position) => {
+                        let group = &mut groups[position];
+                        group.line_numbers.push(entry.line_number);
+                        group.sections.push(section);
+                        if !group.variants.contains(&entry.original) {
+                            group.variants.push(entry.original.clone());
+                        }
+                    }
+                    None => {
+                        index.insert(normalized.clone(), groups.len());
+                        groups.push(DuplicateGroup {
+                            pattern: normalized,
+                            line_numbers: vec![entry.line_number],
+                            variants: vec![entry.original.clone()],
+                            sections: vec![section],
+                        });
+                    }
+                }
             }
         }
 
-        // Keep only entries with more than one occurrence
-        duplicates.retain(|_, line_numbers| line_numbers.len() > 1);
-        duplicates
+        // Keep only patterns with more than one occurrence
+        groups.retain(|group| group.line_numbers.len() > 1);
+        DuplicateReport { groups }
+    }
+}
+
+/// A pattern that occurs more than once in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// The canonical pattern shared by all occurrences
+    pub pattern: String,
+    /// Line numbers of every occurrence, in file order
+    pub line_numbers: Vec<usize>,
+    /// Distinct original spellings of the line (e.g. with inline comments), in file order
+    pub variants: Vec<String>,
+    /// Header comment of the section of each occurrence, aligned with `line_numbers`
+    pub sections: Vec<Option<String>>,
+}
+
+impl DuplicateGroup {
+    /// Total number of occurrences
+    pub fn count(&self) -> usize {
+        self.line_numbers.len()
+    }
+    
+    /// Line number of the occurrence that is kept
+    pub fn first_line(&self) -> usize {
+        self.line_numbers[0]
+    }
+    
+    /// Header comment of the section holding the occurrence on `line_number`
+    pub fn section_of(&self, line_number: usize) -> Option<&str> {
+        let position = self.line_numbers.iter().position(|line| *line == line_number)?;
+        self.sections[position].as_deref()
+    }
+    
+    /// Describe an occurrence for people: `line 42 under '# Logs'`, or `line 42` outside any section
+    pub fn describe_line(&self, line_number: usize) -> String {
+        match self.section_of(line_number) {
+            Some(section) => format!("line {} under '{}'", line_number, section),
+            None => format!("line {}", line_number),
+        }
+    }
+}
+
+/// Duplicate patterns of a file, sorted by the line of their first occurrence
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DuplicateReport {
+    /// Duplicate groups in order of first occurrence
+    pub groups: Vec<DuplicateGroup>,
+}
+
+impl DuplicateReport {
+    /// Number of duplicated patterns
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+    
+    /// Check if no duplicates were found
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+    
+    /// Iterate over the duplicate groups in order
+    pub fn iter(&self) -> std::slice::Iter<'_, DuplicateGroup> {
+        self.groups.iter()
+    }
+    
+    /// Get the group for a canonical pattern
+    pub fn get(&self, pattern: &str) -> Option<&DuplicateGroup> {
+        self.groups.iter().find(|group| group.pattern == pattern)
+    }
+    
+    /// Check if a pattern is duplicated
+    pub fn contains(&self, pattern: &str) -> bool {
+        self.get(pattern).is_some()
+    }
+    
+    /// Drop occurrences on the given lines, keeping only groups that are still duplicated
+    pub fn excluding_lines(&self, lines: &HashSet<usize>) -> DuplicateReport {
+        let groups = self.groups.iter()
+            .filter_map(|group| {
+                let (line_numbers, sections): (Vec<usize>, Vec<Option<String>>) = group.line_numbers.iter()
+                    .copied()
+                    .zip(group.sections.iter().cloned())
+                    .filter(|(line, _)| !lines.contains(line))
+                    .unzip();
+                (line_numbers.len() > 1).then(|| DuplicateGroup { line_numbers, sections, ..group.clone() })
+            })
+            .collect();
+        DuplicateReport { groups }
+    }
+    
+    /// Number of redundant occurrences (all occurrences beyond the first)
+    pub fn redundant_lines(&self) -> usize {
+        self.groups.iter().map(|group| group.count() - 1).sum()
+    }
+}
+
+impl<'a> IntoIterator for &'a DuplicateReport {
+    type Item = &'a DuplicateGroup;
+    type IntoIter = std::slice::Iter<'a, DuplicateGroup>;
+    
+    fn into_iter(self) -> Self::IntoIter {
+        self.groups.iter()
     }
 }
 
 /// Statistics about a gitignore file
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FileStats {
     pub total_lines: usize,
     pub pattern_lines: usize,
@@ -149,6 +735,12 @@ impl FileStats {
     }
 }
 
+impl fmt::Display for GitignoreFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.serialize(&WriteOptions::default()))
+    }
+}
+
 impl Default for GitignoreFile {
     fn default() -> Self {
         Self::new()
@@ -200,6 +792,60 @@ mod tests {
         assert_eq!(entry.normalized_pattern(), None);
     }
 
+    #[test]
+    fn test_comparison_pattern_ignores_inline_comment_separator() {
+        let mut with_space = GitignoreEntry::new("*.log # a".to_string(), EntryType::Pattern("*.log ".to_string()), 1);
+        with_space.inline_comment = Some("# a".to_string());
+        let mut with_tab = GitignoreEntry::new("*.log\t# b".to_string(), EntryType::Pattern("*.log\t".to_string()), 2);
+        with_tab.inline_comment = Some("# b".to_string());
+        let trailing = GitignoreEntry::new("*.log ".to_string(), EntryType::Pattern("*.log ".to_string()), 3);
+
+        assert_eq!(with_space.comparison_pattern(), Some("*.log".to_string()));
+        assert_eq!(with_tab.comparison_pattern(), Some("*.log".to_string()));
+        assert_eq!(trailing.comparison_pattern(), Some("*.log ".to_string()));
+    }
+
+    #[test]
+    fn test_rewrite_pattern_keeps_indentation_and_inline_comment() {
+        let file = crate::core::parse_gitignore("  *.LOG\t # logs\n*.TMP\n# note").unwrap();
+        let mut entries = file.entries.clone();
+
+        assert!(entries[0].rewrite_pattern("*.log"));
+        assert_eq!(entries[0].original, "  *.log\t # logs");
+        assert_eq!(entries[0].comparison_pattern(), Some("  *.log".to_string()));
+        assert_eq!(entries[0].inline_comment.as_deref(), Some("# logs"));
+        assert!(entries[1].rewrite_pattern("*.tmp"));
+        assert_eq!(entries[1].original, "*.tmp");
+        assert!(!entries[2].rewrite_pattern("*.bak"));
+        assert_eq!(entries[2].original, "# note");
+    }
+
+    #[test]
+    fn test_sections() {
+        let file = crate::core::parse_gitignore("*.tmp\n\n# Python\n# Byte code\n*.pyc\n# inline note\n__pycache__/\n\n\n# Logs\n*.log").unwrap();
+        let sections = file.sections();
+
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0].name(), None);
+        assert_eq!(sections[0].entries.len(), 2);
+        assert_eq!(sections[1].name(), Some("Python"));
+        assert_eq!(sections[1].header.len(), 2);
+        assert_eq!(sections[1].start_line(), 3);
+        assert_eq!(sections[1].patterns().map(|entry| entry.original.as_str()).collect::<Vec<_>>(), vec!["*.pyc", "__pycache__/"]);
+        assert_eq!(sections[2].name(), Some("Logs"));
+        assert!(GitignoreFile::new().sections().is_empty());
+    }
+
+    #[test]
+    fn test_entry_spans() {
+        let file = crate::core::parse_gitignore("# Logs\n*.log  # noisy\nbüild/").unwrap();
+
+        assert_eq!(file.span_of(1), Span { line: 1, start_column: 1, end_column: 7 });
+        assert_eq!(file.span_of(2), Span { line: 2, start_column: 1, end_column: 6 });
+        assert_eq!(file.span_of(3), Span { line: 3, start_column: 1, end_column: 7 });
+        assert_eq!(file.span_of(9), Span { line: 9, start_column: 1, end_column: 1 });
+    }
+
     #[test]
     fn test_gitignore_file_creation() {
         let file = GitignoreFile::new();
@@ -253,11 +899,47 @@ mod tests {
 
         let duplicates = file.find_duplicates();
         assert_eq!(duplicates.len(), 1);
-        assert!(duplicates.contains_key("*.log"));
-        assert_eq!(duplicates["*.log"], vec![1, 2]);
+        assert!(duplicates.contains("*.log"));
+        assert_eq!(duplicates.get("*.log").unwrap().line_numbers, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_find_duplicates_is_ordered_by_first_line() {
+        let mut file = GitignoreFile::new();
+        let lines = ["# Build", "build/", "", "# Logs", "*.log", "*.log # again", "build/", "*.log"];
+        for (i, line) in lines.iter().enumerate() {
+            let entry_type = if line.starts_with('#') {
+                EntryType::Comment(line.to_string())
+            } else if line.is_empty() {
+                EntryType::Blank
+            } else {
+                EntryType::Pattern(line.split(" #").next().unwrap().to_string())
+            };
+            file.add_entry(GitignoreEntry::new(line.to_string(), entry_type, i + 1));
+        }
+
+        let duplicates = file.find_duplicates();
+        let patterns: Vec<&str> = duplicates.iter().map(|g| g.pattern.as_str()).collect();
+        assert_eq!(patterns, vec!["build/", "*.log"]);
+        
+        let logs = duplicates.get("*.log").unwrap();
+        assert_eq!(logs.count(), 3);
+        assert_eq!(logs.first_line(), 5);
+        assert_eq!(logs.variants, vec!["*.log".to_string(), "*.log # again".to_string()]);
+        assert_eq!(logs.sections, vec![Some("# Logs".to_string()); 3]);
+        assert_eq!(duplicates.get("build/").unwrap().section_of(2), Some("# Build"));
+        assert_eq!(duplicates.get("build/").unwrap().section_of(7), Some("# Logs"));
+        assert_eq!(duplicates.get("build/").unwrap().describe_line(7), "line 7 under '# Logs'");
+        assert_eq!(duplicates.redundant_lines(), 3);
+        
+        let remaining = duplicates.excluding_lines(&[7, 8].into_iter().collect());
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining.get("*.log").unwrap().line_numbers, vec![5, 6]);
+        assert_eq!(remaining.get("*.log").unwrap().sections.len(), 2);
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_to_string() {
         let mut file = GitignoreFile::new();
         file.add_entry(GitignoreEntry::new(
@@ -275,6 +957,152 @@ mod tests {
         assert_eq!(result, "*.log\n# Logs");
     }
 
+    #[test]
+    fn test_entry_ids_survive_line_shifts() {
+        let before = crate::core::parser::parse_gitignore("# Logs\n*.log\n*.log\n\n# Build\nbuild/").unwrap();
+        let after = crate::core::parser::parse_gitignore("# Added\nfoo\n\n# Logs\n*.log # again\n*.log\n\n# Build\nbuild/").unwrap();
+        let before_ids = before.entry_ids();
+        let after_ids = after.entry_ids();
+        
+        assert_eq!(before_ids[0], None);
+        assert_eq!(before_ids[1], after_ids[4]);
+        assert_eq!(before_ids[2], after_ids[5]);
+        assert_eq!(before_ids[5], after_ids[8]);
+        // Repeats within a section get distinct identifiers
+        assert_ne!(before_ids[1], before_ids[2]);
+    }
+
+    #[test]
+    fn test_entry_ids_follow_section_headers() {
+        let before = crate::core::parser::parse_gitignore("# Logs\n*.log\n*.log.1").unwrap();
+        let after = crate::core::parser::parse_gitignore("# Logs\n# Application logs\n*.log\n# Rotated\n*.log.1").unwrap();
+        let before_ids = before.entry_ids();
+        let after_ids = after.entry_ids();
+        
+        // Comments inside a section don't change the ids of its patterns
+        assert_eq!(after_ids[1], None);
+        assert_eq!(before_ids[1], after_ids[2]);
+        assert_eq!(before_ids[2], after_ids[4]);
+    }
+
+    #[test]
+    fn test_display_matches_default_content() {
+        let file = crate::core::parser::parse_gitignore("*.log\n# Logs\n\nbuild/").unwrap();
+        assert_eq!(format!("{}", file), "*.log\n# Logs\n\nbuild/");
+        assert_eq!(format!("{}", file), file.serialize(&WriteOptions::default()));
+    }
+
+    #[test]
+    fn test_matches_last_match_wins() {
+        let file = crate::core::parser::parse_gitignore("*.log\n!keep.log\nbuild/\n!build/keep.txt\nvendor/*\n!vendor/lib/").unwrap();
+        let decided = |path: &str, is_dir: bool| file.matches(path, is_dir).map(|found| (found.ignored, found.line_number));
+
+        assert_eq!(decided("app.log", false), Some((true, 1)));
+        assert_eq!(decided("logs/keep.log", false), Some((false, 2)));
+        assert_eq!(decided("src/main.rs", false), None);
+        assert_eq!(decided("build", true), Some((true, 3)));
+        assert_eq!(decided("build", false), None);
+        assert_eq!(decided("vendor/lib/", false), Some((false, 6)));
+        assert_eq!(decided("vendor/lib/a.c", false), Some((false, 6)));
+
+        // Nothing inside an ignored directory can be re-included
+        let found = file.matches("build/keep.txt", false).unwrap();
+        assert!(found.ignored);
+        assert_eq!(found.line_number, 3);
+        assert_eq!(found.via_directory.as_deref(), Some("build/"));
+    }
+
+    #[test]
+    fn test_append_section() {
+        let mut file = crate::core::parser::parse_gitignore("target/").unwrap();
+        file.append_section("# Synced", &[]);
+        assert_eq!(file.entries.len(), 1);
+
+        file.append_section("# Synced", &["**/.env".to_string()]);
+        assert_eq!(format!("{}", file), "target/\n\n# Synced\n**/.env");
+        assert_eq!(file.entries[3].line_number, 4);
+    }
+
+    #[test]
+    fn test_add_pattern() {
+        let mut file = crate::core::parser::parse_gitignore("*.tmp\n\n# Python\n*.pyc\n\n# Logs\n*.log").unwrap();
+        assert_eq!(file.add_pattern("__pycache__/", Some("Python")), 5);
+        assert_eq!(file.add_pattern("/dist", None), 9);
+        assert_eq!(file.add_pattern(".env", Some("Secrets")), 12);
+
+        assert_eq!(format!("{}", file), "*.tmp\n\n# Python\n*.pyc\n__pycache__/\n\n# Logs\n*.log\n/dist\n\n# Secrets\n.env");
+        assert_eq!(file.stats.pattern_lines, 6);
+        assert_eq!(file.stats.total_lines, 12);
+        assert!(file.entries.iter().enumerate().all(|(index, entry)| entry.line_number == index + 1));
+    }
+
+    #[test]
+    fn test_remove_and_replace_pattern() {
+        let mut file = crate::core::parser::parse_gitignore("*.log\n\n# Build\nbuild/\n\n# Logs\n*.log  # noisy\ndebug/").unwrap();
+        assert_eq!(file.replace_pattern("*.log", "*.log.*"), 2);
+        assert_eq!(file.entries[6].original, "*.log.*  # noisy");
+
+        assert_eq!(file.remove_pattern("*.log.*"), 2);
+        assert_eq!(file.remove_pattern("build/"), 1);
+        assert_eq!(format!("{}", file), "# Build\n\n# Logs\ndebug/");
+        assert_eq!(file.stats.pattern_lines, 1);
+        assert_eq!(file.entries[3].line_number, 4);
+        assert_eq!(file.remove_pattern("missing"), 0);
+    }
+
+    #[test]
+    fn test_remove_and_replace_pattern_leave_protected_lines() {
+        let content = "*.log\n# gix:keep\n*.log\n# gix:off\n*.log\n# gix:on\nbuild/";
+        let mut file = crate::core::parser::parse_gitignore(content).unwrap();
+        assert_eq!(file.replace_pattern("*.log", "*.txt"), 1);
+        assert_eq!(format!("{}", file), "*.txt\n# gix:keep\n*.log\n# gix:off\n*.log\n# gix:on\nbuild/");
+
+        assert_eq!(file.remove_pattern("*.log"), 0);
+        assert_eq!(file.remove_pattern("*.txt"), 1);
+        assert_eq!(format!("{}", file), "# gix:keep\n*.log\n# gix:off\n*.log\n# gix:on\nbuild/");
+    }
+
+    #[test]
+    fn test_serialize_with_options() {
+        let file = crate::core::parser::parse_gitignore("*.log\nbuild/").unwrap();
+        let options = WriteOptions {
+            line_ending: LineEnding::CrLf,
+            trailing_newline: true,
+            banner: Some("Generated by gix\n\n# Do not edit".to_string()),
+            bom: false,
+        };
+
+        assert_eq!(
+            file.serialize(&options),
+            "# Generated by gix\r\n#\r\n# Do not edit\r\n\r\n*.log\r\nbuild/\r\n"
+        );
+    }
+
+    #[test]
+    fn test_serialize_of_empty_file() {
+        let options = WriteOptions { trailing_newline: true, ..WriteOptions::default() };
+        assert_eq!(GitignoreFile::new().serialize(&options), "");
+    }
+
+    #[test]
+    fn test_serialize_round_trips_untouched_files() {
+        for content in ["*.log\nbuild/\n", "*.log\r\n\r\n# Build\r\nbuild/\r\n", "*.log\r\nbuild/", "\u{feff}*.log\n\n", "\n", ""] {
+            let file = crate::core::parser::parse_gitignore(content).unwrap();
+            assert_eq!(file.serialize(&WriteOptions::preserving(&file)), content);
+        }
+
+        let file = crate::core::parser::parse_gitignore("a\r\nb\r\nc\n").unwrap();
+        assert_eq!(file.line_ending, LineEnding::CrLf);
+        assert!(file.trailing_newline);
+    }
+
+    #[test]
+    fn test_serialize_preserving_bom() {
+        let file = crate::core::parser::parse_gitignore("\u{feff}# Logs\n*.log").unwrap();
+        assert_eq!(file.serialize(&WriteOptions::preserving(&file)), "\u{feff}# Logs\n*.log");
+        assert_eq!(format!("{}", file), "# Logs\n*.log");
+    }
+
     // Test cases from TEST_MATRIX.md
     #[test]
     fn test_tc01_exact_deduplication() {
@@ -292,7 +1120,7 @@ mod tests {
 
         let duplicates = file.find_duplicates();
         assert_eq!(duplicates.len(), 1);
-        assert_eq!(duplicates["*.log"], vec![1, 2]);
+        assert_eq!(duplicates.get("*.log").unwrap().line_numbers, vec![1, 2]);
     }
 
     #[test]
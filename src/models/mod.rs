@@ -1,5 +1,9 @@
+pub mod config;
 pub mod errors;
 pub mod gitignore;
+pub mod options;
 
-pub use errors::GixError;
-pub use gitignore::{GitignoreEntry, GitignoreFile, EntryType}; 
\ No newline at end of file
+pub use config::Config;
+pub use errors::{GixError, EXIT_CHANGES, EXIT_CLEAN, EXIT_IO, EXIT_PARSE, EXIT_USAGE};
+pub use options::{KeepOccurrence, OptimizationMode, ReportFormat, SortOrder};
+pub use gitignore::{GitignoreEntry, GitignoreFile, EntryType, DuplicateGroup, GitignoreMatcher, DuplicateReport, FileStats, LineEnding, PathMatch, Section, Span, WriteOptions, UTF8_BOM, KEEP_MARKER, is_keep_marker}; 
\ No newline at end of file
@@ -0,0 +1,85 @@
+use clap::ValueEnum;
+
+#[derive(Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OptimizationMode {
+    /// Standard optimization (remove duplicate patterns, preserve comments and blank lines)
+    Standard,
+    /// Aggressive optimization (also remove duplicate comments and limit blank lines)
+    Aggressive,
+    /// Conservative optimization (only remove exact duplicates)
+    Conservative,
+    /// Advanced optimization (use pattern analysis for better deduplication)
+    Advanced,
+}
+
+impl OptimizationMode {
+    /// Get the name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            OptimizationMode::Standard => "standard",
+            OptimizationMode::Aggressive => "aggressive",
+            OptimizationMode::Conservative => "conservative",
+            OptimizationMode::Advanced => "advanced",
+        }
+    }
+}
+
+/// How `--sort` orders the patterns of a section
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum SortOrder {
+    /// Keep the order of the file
+    #[default]
+    None,
+    /// Alphabetically, a negation sorting by the pattern it negates
+    Alpha,
+    /// By category, in the organize order, then alphabetically
+    Category,
+}
+
+impl SortOrder {
+    /// Get the name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortOrder::None => "none",
+            SortOrder::Alpha => "alpha",
+            SortOrder::Category => "category",
+        }
+    }
+}
+
+/// Which occurrence of a duplicated pattern `--keep` keeps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum KeepOccurrence {
+    /// The first, where the pattern first takes effect
+    #[default]
+    First,
+    /// The last, which may sit under a better section header or next to related negations
+    Last,
+}
+
+impl KeepOccurrence {
+    /// Get the name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeepOccurrence::First => "first",
+            KeepOccurrence::Last => "last",
+        }
+    }
+}
+
+/// Format used to report check results
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Human-readable text
+    Text,
+    /// JSON list of findings with stable identifiers, for tools tracking findings across runs
+    Json,
+    /// GitHub Actions workflow commands (inline annotations)
+    Github,
+    /// GitLab Code Quality report (JSON)
+    Gitlab,
+    /// Bitbucket Code Insights report and annotations (JSON)
+    Bitbucket,
+    /// SARIF 2.1.0 log, for GitHub code scanning and other SARIF consumers
+    Sarif,
+}
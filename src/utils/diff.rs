@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+
+/// Number of unchanged lines shown around each change
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// One line of an edit script
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit<'a> {
+    Keep(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Render a unified diff between two texts, or an empty string when they are identical
+///
+/// Lines keep their line ending, so a missing newline at the end of either text is reported
+/// with git's `\ No newline at end of file` marker.
+pub fn unified_diff(old_name: &str, new_name: &str, old: &str, new: &str, context: usize) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let edits = diff_lines(&old_lines, &new_lines);
+    if edits.iter().all(|edit| matches!(edit, Edit::Keep(_))) {
+        return String::new();
+    }
+
+    let mut output = format!("--- {}\n+++ {}\n", old_name, new_name);
+    // Line numbers where the next hunk starts in each file, counted from the edits before it
+    let (mut old_start, mut new_start, mut counted) = (0, 0, 0);
+    for (start, end) in hunk_ranges(&edits, context) {
+        old_start += edits[counted..start].iter().filter(|edit| !matches!(edit, Edit::Insert(_))).count();
+        new_start += edits[counted..start].iter().filter(|edit| !matches!(edit, Edit::Delete(_))).count();
+        counted = start;
+        let old_len = edits[start..end].iter().filter(|edit| !matches!(edit, Edit::Insert(_))).count();
+        let new_len = edits[start..end].iter().filter(|edit| !matches!(edit, Edit::Delete(_))).count();
+
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_len),
+            hunk_range(new_start, new_len)
+        ));
+        for edit in &edits[start..end] {
+            let (prefix, line) = match edit {
+                Edit::Keep(line) => (' ', line),
+                Edit::Delete(line) => ('-', line),
+                Edit::Insert(line) => ('+', line),
+            };
+            output.push(prefix);
+            output.push_str(line);
+            if !line.ends_with('\n') {
+                output.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    output
+}
+
+/// A change to one line, with line numbers of the old text (1-indexed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChange<'a> {
+    /// Line `line` goes
+    Delete { line: usize, text: &'a str },
+    /// A line is added after line `after` (0 for the start of the text)
+    Insert { after: usize, text: &'a str },
+}
+
+/// List the changes turning `old` into `new`, in order
+///
+/// Lines keep their line ending, so applying the changes gives back `new` byte for byte.
+pub fn line_changes<'a>(old: &'a str, new: &'a str) -> Vec<LineChange<'a>> {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let mut line = 0;
+    diff_lines(&old_lines, &new_lines).into_iter()
+        .filter_map(|edit| match edit {
+            Edit::Keep(_) => {
+                line += 1;
+                None
+            }
+            Edit::Delete(text) => {
+                line += 1;
+                Some(LineChange::Delete { line, text })
+            }
+            Edit::Insert(text) => Some(LineChange::Insert { after: line, text }),
+        })
+        .collect()
+}
+
+/// Format a hunk range as `start,len`, where an empty range points at the line before it
+fn hunk_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
+
+/// Group changes with their surrounding context, merging groups whose context overlaps
+fn hunk_ranges(edits: &[Edit], context: usize) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (index, edit) in edits.iter().enumerate() {
+        if matches!(edit, Edit::Keep(_)) {
+            continue;
+        }
+
+        let start = index.saturating_sub(context);
+        let end = (index + 1 + context).min(edits.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+    ranges
+}
+
+/// Compute an edit script, matching lines that occur once in both texts first (patience diff)
+///
+/// The lines between two such anchors are compared the same way in turn, and with Myers'
+/// algorithm once none are left.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Edit<'a>> {
+    let mut edits = Vec::with_capacity(old.len().max(new.len()));
+    diff_between_anchors(old, new, &mut edits);
+    edits
+}
+
+fn diff_between_anchors<'a>(old: &[&'a str], new: &[&'a str], edits: &mut Vec<Edit<'a>>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let (old_rest, new_rest) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+    edits.extend(old[..prefix].iter().map(|line| Edit::Keep(line)));
+
+    let anchors = unique_common_lines(old_rest, new_rest);
+    if anchors.is_empty() {
+        myers_diff(old_rest, new_rest, edits);
+    } else {
+        let (mut i, mut j) = (0, 0);
+        for (anchor_old, anchor_new) in anchors {
+            diff_between_anchors(&old_rest[i..anchor_old], &new_rest[j..anchor_new], edits);
+            edits.push(Edit::Keep(old_rest[anchor_old]));
+            (i, j) = (anchor_old + 1, anchor_new + 1);
+        }
+        diff_between_anchors(&old_rest[i..], &new_rest[j..], edits);
+    }
+
+    edits.extend(old[old.len() - suffix..].iter().map(|line| Edit::Keep(line)));
+}
+
+/// Find the longest run, in order in both texts, of lines that occur exactly once in each
+fn unique_common_lines(old: &[&str], new: &[&str]) -> Vec<(usize, usize)> {
+    // Occurrences in each text and the index of the last one
+    let mut counts: HashMap<&str, (usize, usize, usize, usize)> = HashMap::new();
+    for (index, line) in old.iter().enumerate() {
+        let count = counts.entry(line).or_default();
+        count.0 += 1;
+        count.2 = index;
+    }
+    for (index, line) in new.iter().enumerate() {
+        if let Some(count) = counts.get_mut(line) {
+            count.1 += 1;
+            count.3 = index;
+        }
+    }
+    let mut pairs: Vec<(usize, usize)> = counts.into_values()
+        .filter(|&(in_old, in_new, _, _)| in_old == 1 && in_new == 1)
+        .map(|(_, _, old_index, new_index)| (old_index, new_index))
+        .collect();
+    pairs.sort_unstable();
+
+    // Longest increasing run of new indices, by patience sorting: `piles` holds the pair ending
+    // the best run of each length, `previous` links each pair to the one before it in its run
+    let mut piles: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; pairs.len()];
+    for (index, &(_, new_index)) in pairs.iter().enumerate() {
+        let pile = piles.partition_point(|&top| pairs[top].1 < new_index);
+        previous[index] = pile.checked_sub(1).map(|before| piles[before]);
+        match piles.get_mut(pile) {
+            Some(top) => *top = index,
+            None => piles.push(index),
+        }
+    }
+    let mut run = Vec::with_capacity(piles.len());
+    let mut next = piles.last().copied();
+    while let Some(index) = next {
+        run.push(pairs[index]);
+        next = previous[index];
+    }
+    run.reverse();
+    run
+}
+
+/// Texts small enough to diff keeping Myers' whole trace, which takes quadratic memory
+const TRACE_LIMIT: usize = 1_000;
+
+/// Compute a shortest edit script with Myers' algorithm
+///
+/// Large texts are split at the middle snake of a shortest script and each half diffed in
+/// turn, which takes linear memory.
+fn myers_diff<'a>(old: &[&'a str], new: &[&'a str], edits: &mut Vec<Edit<'a>>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let (old_rest, new_rest) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+    edits.extend(old[..prefix].iter().map(|line| Edit::Keep(line)));
+
+    if old_rest.is_empty() || new_rest.is_empty() || old_rest.len() + new_rest.len() <= TRACE_LIMIT {
+        edits.extend(traced_myers_diff(old_rest, new_rest));
+    } else {
+        let (x, y, u, v) = middle_snake(old_rest, new_rest);
+        myers_diff(&old_rest[..x], &new_rest[..y], edits);
+        edits.extend(old_rest[x..u].iter().map(|line| Edit::Keep(line)));
+        myers_diff(&old_rest[u..], &new_rest[v..], edits);
+    }
+
+    edits.extend(old[old.len() - suffix..].iter().map(|line| Edit::Keep(line)));
+}
+
+/// Find the middle snake of a shortest edit script, as its start and end in both texts
+///
+/// Searches from both ends at once until the paths meet (Myers, section 4b). The texts must
+/// differ in their first and last lines.
+fn middle_snake(old: &[&str], new: &[&str]) -> (usize, usize, usize, usize) {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    // Furthest x reached on each diagonal k = x - y, from the start and (mirrored) from the end
+    let mut forward = vec![0isize; 2 * max as usize + 3];
+    let mut backward = vec![0isize; 2 * max as usize + 3];
+    let at = |k: isize| (k + offset) as usize;
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let (start_x, start_y) = (x, x - k);
+            let mut y = start_y;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+            let mirrored = delta - k;
+            if odd && (-(d - 1)..=d - 1).contains(&mirrored) && x + backward[at(mirrored)] >= n {
+                return (start_x as usize, start_y as usize, x as usize, y as usize);
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let (start_x, start_y) = (x, x - k);
+            let mut y = start_y;
+            while x < n && y < m && old[(n - 1 - x) as usize] == new[(m - 1 - y) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+            let mirrored = delta - k;
+            if !odd && (-d..=d).contains(&mirrored) && x + forward[at(mirrored)] >= n {
+                return ((n - x) as usize, (m - y) as usize, (n - start_x) as usize, (m - start_y) as usize);
+            }
+        }
+    }
+    unreachable!("the searches from both ends meet within (n + m) / 2 steps")
+}
+
+/// Compute a shortest edit script with Myers' algorithm, keeping the trace of every step
+fn traced_myers_diff<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Edit<'a>> {
+    let n = old.len() as isize;
+    let m = new.len() as isize;
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max as isize {
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            let index = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x;
+            if x >= n && y >= m {
+                trace.push(v.clone());
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+
+    // Walk the trace backwards to recover the edits
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize - 1).rev() {
+        let v = &trace[d as usize];
+        let k = x - y;
+        let index = (k + offset) as usize;
+        let previous_k = if k == -d || (k != d && v[index - 1] < v[index + 1]) { k + 1 } else { k - 1 };
+        let previous_x = v[(previous_k + offset) as usize];
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Keep(old[x as usize]));
+        }
+        if x == previous_x {
+            y -= 1;
+            edits.push(Edit::Insert(new[y as usize]));
+        } else {
+            x -= 1;
+            edits.push(Edit::Delete(old[x as usize]));
+        }
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        edits.push(Edit::Keep(old[x as usize]));
+    }
+
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_texts_have_no_diff() {
+        assert_eq!(unified_diff("a", "b", "*.log\nbuild/\n", "*.log\nbuild/\n", DEFAULT_CONTEXT), "");
+        assert_eq!(unified_diff("a", "b", "", "", DEFAULT_CONTEXT), "");
+    }
+
+    #[test]
+    fn test_removed_duplicate() {
+        let diff = unified_diff("a/.gitignore", "b/.gitignore", "*.log\nbuild/\n*.log\n", "*.log\nbuild/\n", DEFAULT_CONTEXT);
+        assert_eq!(diff, "--- a/.gitignore\n+++ b/.gitignore\n@@ -1,3 +1,2 @@\n *.log\n build/\n-*.log\n");
+    }
+
+    #[test]
+    fn test_line_changes() {
+        let changes = line_changes("*.log\nbuild/\n*.log\n", "# Logs\n*.log\nbuild/");
+        assert_eq!(changes, vec![
+            LineChange::Insert { after: 0, text: "# Logs\n" },
+            LineChange::Delete { line: 2, text: "build/\n" },
+            LineChange::Delete { line: 3, text: "*.log\n" },
+            LineChange::Insert { after: 3, text: "build/" },
+        ]);
+        assert!(line_changes("a\n", "a\n").is_empty());
+    }
+
+    #[test]
+    fn test_missing_final_newline() {
+        let diff = unified_diff("a", "b", "*.log\nbuild/\n", "*.log\nbuild/", DEFAULT_CONTEXT);
+        assert_eq!(diff, "--- a\n+++ b\n@@ -1,2 +1,2 @@\n *.log\n-build/\n+build/\n\\ No newline at end of file\n");
+    }
+
+    #[test]
+    fn test_separate_hunks() {
+        let old: String = (1..=20).map(|i| format!("line{}\n", i)).collect();
+        let new = old.replace("line2\n", "").replace("line18\n", "changed\n");
+        let diff = unified_diff("a", "b", &old, &new, DEFAULT_CONTEXT);
+
+        let headers: Vec<&str> = diff.lines().filter(|line| line.starts_with("@@")).collect();
+        assert_eq!(headers, vec!["@@ -1,5 +1,4 @@", "@@ -15,6 +14,6 @@"]);
+        assert!(diff.contains("-line18\n+changed\n"));
+    }
+
+    #[test]
+    fn test_moved_line() {
+        // Lines found once in both texts anchor the diff, so the moved line is the only change
+        let diff = unified_diff("a", "b", "1\n2\n3\n", "3\n1\n2\n", DEFAULT_CONTEXT);
+        assert_eq!(diff, "--- a\n+++ b\n@@ -1,3 +1,3 @@\n+3\n 1\n 2\n-3\n");
+    }
+
+    #[test]
+    fn test_large_texts_split_at_middle_snake() {
+        // Repeated lines leave no anchors, and the texts are past the size diffed with a full trace
+        let mut seed = 7u32;
+        let mut lines = |count: usize| -> Vec<String> {
+            (0..count).map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                format!("/build{}/", (seed >> 16) % 8)
+            }).collect()
+        };
+        let (old, new) = (lines(900), lines(700));
+        let (old, new): (Vec<&str>, Vec<&str>) = (old.iter().map(String::as_str).collect(), new.iter().map(String::as_str).collect());
+
+        let mut edits = Vec::new();
+        myers_diff(&old, &new, &mut edits);
+        let changed = |edits: &[Edit]| edits.iter().filter(|edit| !matches!(edit, Edit::Keep(_))).count();
+        assert_eq!(changed(&edits), changed(&traced_myers_diff(&old, &new)));
+
+        let kept_or = |keep_deleted: bool| -> Vec<&str> {
+            edits.iter().filter_map(|edit| match edit {
+                Edit::Keep(line) => Some(*line),
+                Edit::Delete(line) => keep_deleted.then_some(*line),
+                Edit::Insert(line) => (!keep_deleted).then_some(*line),
+            }).collect()
+        };
+        assert_eq!(kept_or(true), old);
+        assert_eq!(kept_or(false), new);
+    }
+
+    #[test]
+    fn test_insertions_into_empty_text() {
+        let diff = unified_diff("a", "b", "", "*.log\n", DEFAULT_CONTEXT);
+        assert_eq!(diff, "--- a\n+++ b\n@@ -0,0 +1 @@\n+*.log\n");
+    }
+}
@@ -0,0 +1,29 @@
+use crate::models::GixError;
+#[cfg(feature = "network")]
+use sha2::{Digest, Sha256};
+
+/// Fetch a text resource over HTTP(S)
+pub fn fetch_text(url: &str) -> Result<String, GixError> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|e| GixError::NetworkError(e.to_string()))?;
+    
+    response.into_string()
+        .map_err(|e| GixError::NetworkError(format!("{}: {}", url, e)))
+}
+
+/// Lowercase hex SHA-256 digest of the given bytes
+#[cfg(feature = "network")]
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(all(test, feature = "network"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(sha256_hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+}
@@ -1,6 +1,36 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::core::audit_log::format_timestamp;
 use crate::models::GixError;
+use crate::utils::git::{metadata_dir, repository_root, MetadataLocation};
+
+/// Number of backups of each file kept unless configured otherwise
+pub const DEFAULT_BACKUP_KEEP: usize = 10;
+
+/// Where backups go and how many of each file are kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupPolicy {
+    /// Where gix keeps metadata, which decides where backups go by default
+    pub location: MetadataLocation,
+    /// Directory for backups instead of the default; files inside a repository keep their path below it
+    pub dir: Option<PathBuf>,
+    /// Backups kept of each file, oldest deleted first; 0 keeps them all
+    pub keep: usize,
+}
+
+impl BackupPolicy {
+    /// Back up to the default place for `location`, keeping `DEFAULT_BACKUP_KEEP` backups of each file
+    pub fn new(location: MetadataLocation) -> Self {
+        Self { location, dir: None, keep: DEFAULT_BACKUP_KEEP }
+    }
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self::new(MetadataLocation::default())
+    }
+}
 
 /// Read a .gitignore file safely
 pub fn read_gitignore_file(path: &Path) -> Result<String, GixError> {
@@ -33,16 +63,107 @@ pub fn write_gitignore_file(path: &Path, content: &str) -> Result<(), GixError>
     })
 }
 
-/// Create a backup of the original .gitignore file
+/// Create a timestamped backup of the original .gitignore file next to it
 pub fn create_backup(path: &Path) -> Result<(), GixError> {
+    create_backup_in(path, &BackupPolicy::default())?;
+    Ok(())
+}
+
+/// Back up a file as `policy` says, returning the backup's path, or `None` if there is no file
+///
+/// Each backup is named `<base>.<timestamp>.backup` after `backup_base`, with the time in UTC
+/// to the millisecond, so earlier backups are never overwritten; beyond `policy.keep` backups
+/// of the file, the oldest are deleted.
+pub fn create_backup_in(path: &Path, policy: &BackupPolicy) -> Result<Option<PathBuf>, GixError> {
     if !path.exists() {
-        return Ok(()); // Nothing to backup
+        return Ok(None);
     }
-    
-    let backup_path = path.with_extension("backup");
-    fs::copy(path, &backup_path).map_err(GixError::IoError)?;
-    
-    Ok(())
+
+    let base = backup_base(path, policy);
+    let mut millis = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis());
+    let mut backup = backup_named(&base, millis);
+    while backup.exists() {
+        millis += 1;
+        backup = backup_named(&base, millis);
+    }
+    if let Some(parent) = backup.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(path, &backup).map_err(GixError::IoError)?;
+
+    if policy.keep > 0 {
+        let backups = list_backups(&base)?;
+        for old in &backups[..backups.len().saturating_sub(policy.keep)] {
+            fs::remove_file(old)?;
+        }
+    }
+    Ok(Some(backup))
+}
+
+/// Backups `create_backup_in` made of `path` under `policy`, oldest first
+pub fn backups_of(path: &Path, policy: &BackupPolicy) -> Result<Vec<PathBuf>, GixError> {
+    list_backups(&backup_base(path, policy))
+}
+
+/// Path the backups of `path` are named after
+///
+/// With `policy.dir`, or with metadata in the git directory, the backups mirror the file's path
+/// below that directory or `<git dir>/gix/backups/`; otherwise, and for files outside a
+/// repository without `policy.dir`, they sit next to the file.
+pub fn backup_base(path: &Path, policy: &BackupPolicy) -> PathBuf {
+    let beside = path.to_path_buf();
+    if policy.location == MetadataLocation::Worktree && policy.dir.is_none() {
+        return beside;
+    }
+
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let relative = repository_root(dir).and_then(|root| {
+        let relative = dir.canonicalize().ok()
+            .zip(path.file_name())
+            .and_then(|(dir, name)| path_relative_to(&root, &dir.join(name)))?;
+        Some((root, relative))
+    });
+    match (&policy.dir, relative) {
+        (Some(backup_dir), Some((_, relative))) => backup_dir.join(relative),
+        (Some(backup_dir), None) => path.file_name().map_or(beside, |name| backup_dir.join(name)),
+        (None, Some((root, relative))) => metadata_dir(&root, policy.location).join("backups").join(relative),
+        (None, None) => beside,
+    }
+}
+
+/// Name a backup of `base` taken at `millis` milliseconds since the Unix epoch
+fn backup_named(base: &Path, millis: u128) -> PathBuf {
+    let stamp = format_timestamp((millis / 1000) as u64).replace(['-', ':', 'Z'], "");
+    let mut name = base.as_os_str().to_os_string();
+    name.push(format!(".{}{:03}Z.backup", stamp, millis % 1000));
+    PathBuf::from(name)
+}
+
+/// Find the timestamped backups of `base`, oldest first
+fn list_backups(base: &Path) -> Result<Vec<PathBuf>, GixError> {
+    let (Some(dir), Some(name)) = (base.parent(), base.file_name().and_then(|name| name.to_str())) else {
+        return Ok(Vec::new());
+    };
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let is_stamp = |stamp: &str| stamp.len() == 19 && stamp.ends_with('Z') && stamp[..8].bytes().all(|byte| byte.is_ascii_digit());
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|backup| {
+            backup.file_name()
+                .and_then(|file_name| file_name.to_str())
+                .and_then(|file_name| file_name.strip_prefix(name)?.strip_prefix('.')?.strip_suffix(".backup"))
+                .is_some_and(is_stamp)
+        })
+        .collect();
+    backups.sort();
+    Ok(backups)
 }
 
 /// Check if a file is a .gitignore file
@@ -53,6 +174,54 @@ pub fn is_gitignore_file(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Express `path` relative to `base_dir` with `/` separators, as gitignore patterns see it
+///
+/// Returns `None` when the path is not strictly inside `base_dir`.
+pub fn path_relative_to(base_dir: &Path, path: &Path) -> Option<String> {
+    let base = normalize_path(base_dir);
+    let path = normalize_path(path);
+    let relative = path.strip_prefix(&base).ok()?;
+    
+    let components: Vec<String> = relative.components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if components.is_empty() {
+        None
+    } else {
+        Some(components.join("/"))
+    }
+}
+
+/// Directory where gix caches downloads, below the user's cache directory
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("gix"))
+}
+
+/// Make a path absolute and resolve `.` and `..` without touching the filesystem
+fn normalize_path(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+    
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,13 +273,50 @@ mod tests {
         let result = create_backup(temp_file.path());
         assert!(result.is_ok());
         
-        let backup_path = temp_file.path().with_extension("backup");
-        assert!(backup_path.exists());
+        let backups = backups_of(temp_file.path(), &BackupPolicy::default()).unwrap();
+        assert_eq!(backups.len(), 1);
         
-        let backup_content = fs::read_to_string(&backup_path).unwrap();
+        let backup_content = fs::read_to_string(&backups[0]).unwrap();
         assert_eq!(backup_content.trim(), content);
     }
 
+    #[test]
+    fn test_backup_in_git_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        let path = dir.path().join("src/.gitignore");
+        fs::write(&path, "*.log\n").unwrap();
+
+        let backup = create_backup_in(&path, &BackupPolicy::new(MetadataLocation::Git)).unwrap().unwrap();
+        assert!(backup.starts_with(dir.path().canonicalize().unwrap().join(".git/gix/backups/src")));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "*.log\n");
+        let worktree = BackupPolicy::new(MetadataLocation::Worktree);
+        assert!(backups_of(&path, &worktree).unwrap().is_empty());
+
+        assert_eq!(backup_base(&path, &worktree), path);
+        assert!(create_backup_in(&dir.path().join("missing"), &BackupPolicy::default()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_backups_rotate() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        let path = dir.path().join(".gitignore");
+        let policy = BackupPolicy { dir: Some(dir.path().join("backups")), keep: 2, ..BackupPolicy::default() };
+
+        let mut made = Vec::new();
+        for content in ["a\n", "b\n", "c\n"] {
+            fs::write(&path, content).unwrap();
+            made.push(create_backup_in(&path, &policy).unwrap().unwrap());
+        }
+        let name = made[0].file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with(".gitignore.") && name.ends_with("Z.backup"), "{}", name);
+        assert_eq!(backups_of(&path, &policy).unwrap(), made[1..]);
+        assert_eq!(fs::read_to_string(&made[2]).unwrap(), "c\n");
+        assert!(!made[0].exists());
+    }
+
     #[test]
     fn test_is_gitignore_file() {
         let gitignore_path = Path::new(".gitignore");
@@ -119,4 +325,13 @@ mod tests {
         assert!(is_gitignore_file(gitignore_path));
         assert!(!is_gitignore_file(other_path));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_path_relative_to() {
+        let base = Path::new("/repo");
+        assert_eq!(path_relative_to(base, Path::new("/repo/build/.gitignore")), Some("build/.gitignore".to_string()));
+        assert_eq!(path_relative_to(base, Path::new("/repo/./out/../build/x")), Some("build/x".to_string()));
+        assert_eq!(path_relative_to(base, Path::new("/elsewhere/.gitignore")), None);
+        assert_eq!(path_relative_to(base, Path::new("/repo")), None);
+    }
+}
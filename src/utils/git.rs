@@ -0,0 +1,357 @@
+use crate::models::{GixError, PathMatch};
+use clap::ValueEnum;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Find the git directory of the repository checked out at `worktree`
+///
+/// `.git` is either the git directory itself or, in linked worktrees and submodules, a file
+/// containing `gitdir: <path>`.
+pub fn resolve_git_dir(worktree: &Path) -> Option<PathBuf> {
+    let dot_git = worktree.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+
+    let content = fs::read_to_string(&dot_git).ok()?;
+    let target = content.lines().find_map(|line| line.strip_prefix("gitdir:"))?.trim();
+    let git_dir = worktree.join(target);
+    git_dir.is_dir().then_some(git_dir)
+}
+
+/// Find the directory shared by all worktrees of a repository, which holds `info/exclude`
+///
+/// Linked worktrees point at it through a `commondir` file; otherwise it is the git dir itself.
+pub fn common_git_dir(git_dir: &Path) -> PathBuf {
+    fs::read_to_string(git_dir.join("commondir"))
+        .ok()
+        .map(|content| git_dir.join(content.trim()))
+        .filter(|common| common.is_dir())
+        .unwrap_or_else(|| git_dir.to_path_buf())
+}
+
+/// Path of the repository-wide exclude file for the checkout at `worktree`, if it exists
+pub fn exclude_file(worktree: &Path) -> Option<PathBuf> {
+    let exclude = common_git_dir(&resolve_git_dir(worktree)?).join("info").join("exclude");
+    exclude.is_file().then_some(exclude)
+}
+
+/// Path of the user's global excludes file, if it exists
+///
+/// This is `core.excludesFile` from git's configuration, or git's default of
+/// `$XDG_CONFIG_HOME/git/ignore` (`~/.config/git/ignore`) when it is not set.
+pub fn global_excludes_file() -> Option<PathBuf> {
+    let configured = Command::new("git")
+        .args(["config", "--path", "--get", "core.excludesFile"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from);
+    let path = configured.or_else(|| default_global_excludes_file(env::var_os("XDG_CONFIG_HOME"), env::var_os("HOME")))?;
+    path.is_file().then_some(path)
+}
+
+/// Where git looks for global excludes when `core.excludesFile` is not set
+fn default_global_excludes_file(xdg_config_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let config_home = xdg_config_home
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("git").join("ignore"))
+}
+
+/// Read `core.ignoreCase` for the repository containing `dir`
+///
+/// Git sets it when the repository is created on a case-insensitive filesystem, and then
+/// matches ignore patterns without regard to case. `None` means `dir` is not in a repository
+/// or the setting is absent.
+pub fn core_ignore_case(dir: &Path) -> Option<bool> {
+    repository_root(dir)?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["config", "--bool", "--get", "core.ignorecase"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// List the files tracked by git below `dir`, relative to it, with `/` separators
+pub fn tracked_files(dir: &Path) -> Result<Vec<String>, GixError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["ls-files", "-z"])
+        .output()
+        .map_err(|e| GixError::GitError(format!("could not run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(GixError::GitError(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Find the root of the checkout containing `dir`: the nearest directory, `dir` included, with a `.git`
+pub fn repository_root(dir: &Path) -> Option<PathBuf> {
+    let dir = dir.canonicalize().ok()?;
+    dir.ancestors().find(|ancestor| ancestor.join(".git").exists()).map(Path::to_path_buf)
+}
+
+/// Name of the directory holding gix's backups and audit log
+pub const METADATA_DIR_NAME: &str = ".gix";
+
+/// Where gix keeps backups and its audit log
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum MetadataLocation {
+    /// `.gix/` at the repository root, with backups next to the files they copy
+    Worktree,
+    /// `gix/` inside the git directory, so nothing is written to the working tree; `.gix/` outside a repository
+    #[default]
+    Git,
+}
+
+impl MetadataLocation {
+    /// Get the name used on the command line and in gix.toml
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetadataLocation::Worktree => "worktree",
+            MetadataLocation::Git => "git",
+        }
+    }
+}
+
+/// Directory holding gix's metadata for the repository containing `dir` (or `dir` itself outside one)
+///
+/// Linked worktrees share the metadata directory of their main checkout, like `info/exclude`.
+pub fn metadata_dir(dir: &Path, location: MetadataLocation) -> PathBuf {
+    let root = repository_root(dir).unwrap_or_else(|| dir.to_path_buf());
+    let git_dir = match location {
+        MetadataLocation::Git => resolve_git_dir(&root),
+        MetadataLocation::Worktree => None,
+    };
+    match git_dir {
+        Some(git_dir) => common_git_dir(&git_dir).join("gix"),
+        None => root.join(METADATA_DIR_NAME),
+    }
+}
+
+/// Get the version of the installed git, as `git --version` prints it
+pub fn git_version() -> Result<String, GixError> {
+    let output = Command::new("git")
+        .arg("--version")
+        .output()
+        .map_err(|e| GixError::GitError(format!("could not run git: {}", e)))?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Build a git command for `dir` that ignores the user's and the system's configuration
+///
+/// Global excludes and settings such as `core.ignoreCase` would otherwise change its answers.
+fn isolated_git(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(dir)
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env_remove("GIT_CONFIG_GLOBAL");
+    command
+}
+
+/// Create an empty repository in `dir` for scratch work
+pub fn init_scratch_repository(dir: &Path) -> Result<(), GixError> {
+    let output = isolated_git(dir)
+        .args(["init", "--quiet"])
+        .output()
+        .map_err(|e| GixError::GitError(format!("could not run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(GixError::GitError(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    Ok(())
+}
+
+/// Ask `git check-ignore` which pattern decides each path, in the repository at `dir`
+///
+/// Paths are relative to `dir`, directories with a trailing `/`, and need not exist. Tracked
+/// files are treated like any other (`--no-index`); `None` means no pattern matches a path.
+pub fn check_ignore(dir: &Path, paths: &[String]) -> Result<Vec<Option<PathMatch>>, GixError> {
+    let mut child = isolated_git(dir)
+        .args(["check-ignore", "--no-index", "--verbose", "--non-matching", "--stdin", "-z"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| GixError::GitError(format!("could not run git: {}", e)))?;
+
+    // Write from another thread so a full output pipe can't stall git while it waits for input
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input: Vec<u8> = paths.iter().flat_map(|path| path.bytes().chain([0])).collect();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    writer.join().map_err(|_| GixError::GitError("could not write to git".to_string()))??;
+
+    // Exit status 1 only means that no path is ignored
+    if !matches!(output.status.code(), Some(0 | 1)) {
+        return Err(GixError::GitError(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+
+    // Each path gives four fields: source, line number, pattern and the path itself
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> = stdout.split('\0').collect();
+    Ok(fields.chunks_exact(4)
+        .map(|record| {
+            let line_number = record[1].parse().ok()?;
+            Some(PathMatch {
+                ignored: !record[2].starts_with('!'),
+                line_number,
+                pattern: record[2].to_string(),
+                via_directory: None,
+            })
+        })
+        .collect())
+}
+
+/// Make a shallow clone of the repository at `url` in `dest`, which must not exist yet
+pub fn clone_repository(url: &str, dest: &Path) -> Result<(), GixError> {
+    let output = Command::new("git")
+        .args(["clone", "--quiet", "--depth", "1", url])
+        .arg(dest)
+        .stdin(Stdio::null())
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| GixError::GitError(format!("could not run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(GixError::GitError(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    Ok(())
+}
+
+/// Check if a directory is the root of a repository nested in another, such as a submodule
+pub fn is_nested_repository(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repository_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join("src/app")).unwrap();
+
+        let root = dir.path().canonicalize().unwrap();
+        assert_eq!(repository_root(&dir.path().join("src/app")), Some(root.clone()));
+        assert_eq!(repository_root(dir.path()), Some(root));
+    }
+
+    #[test]
+    fn test_metadata_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        let root = dir.path().canonicalize().unwrap();
+
+        assert_eq!(metadata_dir(&dir.path().join("src"), MetadataLocation::Worktree), root.join(".gix"));
+        assert_eq!(metadata_dir(&dir.path().join("src"), MetadataLocation::Git), root.join(".git/gix"));
+
+        let outside = tempfile::tempdir().unwrap();
+        assert_eq!(metadata_dir(outside.path(), MetadataLocation::Git), outside.path().join(".gix"));
+    }
+
+    #[test]
+    fn test_check_ignore() {
+        let dir = tempfile::tempdir().unwrap();
+        init_scratch_repository(dir.path()).unwrap();
+        fs::write(dir.path().join(".gitignore"), "build/\n*.log\n!debug.log\n").unwrap();
+
+        let paths: Vec<String> = ["build/out.txt", "debug.log", "notes.txt"].iter().map(|path| path.to_string()).collect();
+        let decisions = check_ignore(dir.path(), &paths).unwrap();
+        assert_eq!(decisions.len(), 3);
+        assert!(decisions[0].as_ref().is_some_and(|decision| decision.ignored && decision.line_number == 1));
+        assert!(decisions[1].as_ref().is_some_and(|decision| !decision.ignored && decision.pattern == "!debug.log"));
+        assert_eq!(decisions[2], None);
+    }
+
+    #[test]
+    fn test_plain_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git/info")).unwrap();
+        fs::write(dir.path().join(".git/info/exclude"), "*.local\n").unwrap();
+
+        assert_eq!(resolve_git_dir(dir.path()), Some(dir.path().join(".git")));
+        assert_eq!(exclude_file(dir.path()), Some(dir.path().join(".git/info/exclude")));
+    }
+
+    #[test]
+    fn test_linked_worktree_uses_common_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main");
+        let linked = dir.path().join("linked");
+        fs::create_dir_all(main.join(".git/info")).unwrap();
+        fs::create_dir_all(main.join(".git/worktrees/linked")).unwrap();
+        fs::write(main.join(".git/info/exclude"), "*.local\n").unwrap();
+        fs::write(main.join(".git/worktrees/linked/commondir"), "../..\n").unwrap();
+        fs::create_dir_all(&linked).unwrap();
+        fs::write(linked.join(".git"), "gitdir: ../main/.git/worktrees/linked\n").unwrap();
+
+        let exclude = exclude_file(&linked).unwrap();
+        assert_eq!(fs::read_to_string(exclude).unwrap(), "*.local\n");
+    }
+
+    #[test]
+    fn test_default_global_excludes_file() {
+        assert_eq!(
+            default_global_excludes_file(Some("/xdg".into()), Some("/home/me".into())),
+            Some(PathBuf::from("/xdg/git/ignore"))
+        );
+        assert_eq!(
+            default_global_excludes_file(Some("".into()), Some("/home/me".into())),
+            Some(PathBuf::from("/home/me/.config/git/ignore"))
+        );
+        assert_eq!(default_global_excludes_file(None, None), None);
+    }
+
+    #[test]
+    fn test_tracked_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| Command::new("git").arg("-C").arg(dir.path()).args(args).output().unwrap();
+        git(&["init", "-q"]);
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("untracked.txt"), "").unwrap();
+        git(&["add", "src/main.rs"]);
+
+        assert_eq!(tracked_files(dir.path()).unwrap(), vec!["src/main.rs"]);
+        assert_eq!(tracked_files(&dir.path().join("src")).unwrap(), vec!["main.rs"]);
+    }
+
+    #[test]
+    fn test_tracked_files_outside_a_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(tracked_files(dir.path()), Err(GixError::GitError(_))));
+    }
+
+    #[test]
+    fn test_not_a_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(resolve_git_dir(dir.path()), None);
+        assert_eq!(exclude_file(dir.path()), None);
+        assert!(!is_nested_repository(dir.path()));
+    }
+}
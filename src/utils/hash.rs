@@ -0,0 +1,21 @@
+/// Hash a key to 16 hex digits that stay the same between builds, runs and platforms
+///
+/// FNV-1a is used because the std hasher is randomly seeded and may change between releases.
+pub fn stable_hash(key: &str) -> String {
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_hash() {
+        assert_eq!(stable_hash(""), "cbf29ce484222325");
+        assert_eq!(stable_hash("a"), "af63dc4c8601ec8c");
+        assert_ne!(stable_hash("*.log"), stable_hash("*.tmp"));
+    }
+}
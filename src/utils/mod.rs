@@ -1,5 +1,17 @@
+#[cfg(any(feature = "network", feature = "templates-remote"))]
+pub mod download;
+pub mod diff;
 pub mod file;
+pub mod git;
+pub mod hash;
+pub mod json;
 pub mod patterns;
+pub mod synthetic;
+pub mod tokens;
+pub mod walker;
+#[cfg(feature = "watch")]
+pub mod watch;
 
-pub use file::{read_gitignore_file, write_gitignore_file, create_backup};
+pub use file::{read_gitignore_file, write_gitignore_file, create_backup, create_backup_in, backups_of, backup_base, BackupPolicy, DEFAULT_BACKUP_KEEP, path_relative_to, cache_dir};
+pub use hash::stable_hash;
 pub use patterns::*; 
\ No newline at end of file
@@ -1,15 +1,334 @@
+use crate::utils::tokens::{match_segments, render, tokenize, Segment, Token};
+
+/// A gitignore pattern compiled for matching against paths
+///
+/// Paths are relative to the directory holding the `.gitignore` file and use `/` as the
+/// separator; a trailing `/` marks the path as a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobPattern {
+    /// The pattern re-includes what it matches (`!pattern`)
+    pub negated: bool,
+    /// The pattern only matches directories (`pattern/`)
+    pub directory_only: bool,
+    /// The pattern is matched against the full path rather than any basename
+    pub anchored: bool,
+    /// The glob itself, without negation, anchoring slash or trailing slash
+    pub body: String,
+    /// The tokens of the body, one segment per path component
+    pub segments: Vec<Segment>,
+}
+
+impl GlobPattern {
+    /// Compile a gitignore pattern line
+    pub fn parse(pattern: &str) -> Self {
+        let text = trim_unescaped_trailing_whitespace(pattern);
+        match text.strip_prefix('!') {
+            Some(rest) => Self::from_segments(true, tokenize(rest)),
+            None => Self::from_segments(false, tokenize(text)),
+        }
+    }
+
+    /// Compile a tokenized pattern (negation already removed)
+    pub fn from_segments(negated: bool, mut segments: Vec<Segment>) -> Self {
+        let directory_only = segments.len() > 1 && segments.last().is_some_and(Segment::is_empty);
+        if directory_only {
+            segments.pop();
+        }
+
+        // A slash at the start or in the middle anchors the pattern to this directory
+        let mut anchored = segments.len() > 1;
+        if anchored && segments[0].is_empty() {
+            segments.remove(0);
+        }
+
+        // "**/name" matches at any depth, which is what an unanchored "name" does
+        if segments.len() == 2 && segments[0].is_globstar() && !segments[1].is_empty() {
+            segments.remove(0);
+            anchored = false;
+        }
+
+        Self {
+            negated,
+            directory_only,
+            anchored,
+            body: render(&segments),
+            segments,
+        }
+    }
+
+    /// Check if the pattern matches exactly this path (not considering its parent directories)
+    pub fn matches_path(&self, path: &str, is_dir: bool) -> bool {
+        if self.body.is_empty() || (self.directory_only && !is_dir) {
+            return false;
+        }
+
+        let components: Vec<&str> = if self.anchored {
+            path.split('/').collect()
+        } else {
+            vec![path.rsplit('/').next().unwrap_or(path)]
+        };
+
+        match_segments(&self.segments, &components)
+    }
+
+    /// Check if the pattern applies to a path, either directly or through one of its parent directories
+    pub fn matches(&self, path: &str) -> bool {
+        let (path, is_dir) = split_path(path);
+
+        path.match_indices('/').any(|(i, _)| self.matches_path(&path[..i], true))
+            || self.matches_path(path, is_dir)
+    }
+
+    /// Check if the pattern has no wildcards, so it matches only its own text
+    pub fn is_literal(&self) -> bool {
+        self.segments.iter().all(Segment::is_literal)
+    }
+
+    /// The last path component of the body, as written
+    pub fn name(&self) -> String {
+        self.segments.last().map(Segment::to_string).unwrap_or_default()
+    }
+
+    /// Check if every path this pattern applies to is also matched by `self`
+    ///
+    /// This is conservative: `false` means coverage could not be proven, not that it is absent.
+    pub fn covers(&self, other: &GlobPattern) -> bool {
+        if self.body.is_empty() || other.body.is_empty() {
+            return false;
+        }
+        if self.directory_only && !other.directory_only {
+            return false;
+        }
+
+        // An unanchored pattern only ever sees the last path component
+        let target = match (self.anchored, other.anchored) {
+            (true, false) => return false,
+            (false, true) => &other.segments[other.segments.len() - 1..],
+            _ => &other.segments[..],
+        };
+
+        if self.segments == target {
+            return true;
+        }
+
+        // Matching the other glob as literal text is only sound when `self` uses plain `*`
+        // and every wildcard in the other glob expands to text without a slash
+        let tokens = |segments: &[Segment]| segments.iter().flat_map(|segment| segment.tokens.clone()).collect::<Vec<_>>();
+        let simple_self = tokens(&self.segments).iter().all(|token| matches!(token, Token::Literal(_) | Token::Star));
+        let simple_target = tokens(target).iter().all(|token| matches!(token, Token::Literal(_) | Token::Star | Token::Question));
+        let target_text: Vec<String> = target.iter().map(Segment::to_string).collect();
+        let target_text: Vec<&str> = target_text.iter().map(String::as_str).collect();
+        simple_self && simple_target && match_segments(&self.segments, &target_text)
+    }
+
+    /// Check if every path this pattern applies to lies inside a directory matched by `self`
+    pub fn covers_parent_of(&self, other: &GlobPattern) -> bool {
+        if !other.anchored {
+            return false;
+        }
+
+        (1..other.segments.len())
+            .map(|end| &other.segments[..end])
+            // "**" may stand for zero directories, so such a prefix is not a guaranteed parent
+            .take_while(|prefix| !prefix.iter().any(Segment::is_globstar))
+            .any(|prefix| self.covers(&GlobPattern {
+                negated: other.negated,
+                directory_only: true,
+                anchored: true,
+                body: render(prefix),
+                segments: prefix.to_vec(),
+            }))
+    }
+
+    /// Check if the two patterns might apply to a common path
+    ///
+    /// This is conservative: `false` means the patterns are provably disjoint.
+    pub fn may_overlap(&self, other: &GlobPattern) -> bool {
+        if self.body.is_empty() || other.body.is_empty() {
+            return false;
+        }
+        if self.witnesses().iter().any(|path| other.matches(path))
+            || other.witnesses().iter().any(|path| self.matches(path))
+        {
+            return true;
+        }
+
+        // An anchored literal matches only its witness directly, an unanchored one its name in
+        // any directory, which the other pattern's last component may match under its own prefix
+        if self.is_literal() || other.is_literal() {
+            return self.name_may_match(other) || other.name_may_match(self);
+        }
+
+        // Otherwise compare the literal text around the wildcards of the component both see
+        let last = |glob: &'_ GlobPattern| glob.segments.len() - 1;
+        let (first, second) = match (self.anchored, other.anchored) {
+            (true, true) | (false, false) => (&self.segments[..], &other.segments[..]),
+            (true, false) => (&self.segments[last(self)..], &other.segments[..]),
+            (false, true) => (&self.segments[..], &other.segments[last(other)..]),
+        };
+        let (first_prefix, first_suffix) = literal_affixes(first);
+        let (second_prefix, second_suffix) = literal_affixes(second);
+
+        (first_prefix.starts_with(&second_prefix) || second_prefix.starts_with(&first_prefix))
+            && (first_suffix.ends_with(&second_suffix) || second_suffix.ends_with(&first_suffix))
+    }
+
+    /// Check if this is an unanchored literal whose name the last component of `other` matches
+    fn name_may_match(&self, other: &GlobPattern) -> bool {
+        let name = instantiate(&self.segments, "");
+        !self.anchored && self.is_literal() && other.segments.last()
+            .is_some_and(|last| match_segments(std::slice::from_ref(last), &[name.as_str()]))
+    }
+
+    /// Sample paths this pattern applies to, used to detect overlapping patterns
+    pub fn witnesses(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+
+        for fill in ["", "x"] {
+            let path = instantiate(&self.segments, fill);
+            if path.is_empty() || paths.contains(&path) {
+                continue;
+            }
+            if !self.directory_only {
+                paths.push(path.clone());
+            }
+            paths.push(format!("{}/", path));
+        }
+
+        paths
+    }
+}
+
 /// Check if a pattern matches a file path
+///
+/// Negation is ignored; a trailing `/` on the path marks it as a directory, and a path inside
+/// a matched directory is matched as well.
 pub fn pattern_matches(pattern: &str, path: &str) -> bool {
-    // This is a simplified implementation
-    // In a real implementation, you would use proper glob pattern matching
-    pattern == path
+    GlobPattern::parse(pattern).matches(path)
 }
 
-/// Check if two patterns are conflicting
+/// Check if two patterns are conflicting, i.e. can match the same path so that their order matters
 pub fn patterns_conflict(pattern1: &str, pattern2: &str) -> bool {
-    // This is a simplified implementation
-    // In a real implementation, you would analyze pattern semantics
-    pattern1 == pattern2
+    GlobPattern::parse(pattern1).may_overlap(&GlobPattern::parse(pattern2))
+}
+
+/// Check if `general` matches every path that `specific` matches (negation is ignored)
+pub fn pattern_covers(general: &str, specific: &str) -> bool {
+    GlobPattern::parse(general).covers(&GlobPattern::parse(specific))
+}
+
+/// Check if a glob selects a pattern, matching the pattern's text as if it were a path
+///
+/// This picks patterns rather than files: `*.log*` selects `*.log`, `logs/*.log` and
+/// `npm-debug.log*`, and `.idea*` selects `.idea/`. The pattern's leading `!` or `/` is skipped.
+pub fn glob_selects(glob: &str, pattern: &str) -> bool {
+    let text = pattern.strip_prefix('!').unwrap_or(pattern);
+    GlobPattern::parse(glob).matches(text.strip_prefix('/').unwrap_or(text))
+}
+
+/// Decide whether a path is ignored by an ordered list of patterns, as git does
+///
+/// The last matching pattern wins, and nothing inside an ignored directory can be re-included.
+pub fn is_ignored<S: AsRef<str>>(patterns: &[S], path: &str) -> bool {
+    ignoring_pattern(patterns, path).is_some()
+}
+
+/// Find the pattern that makes a path ignored, as an index into `patterns`
+///
+/// That is the pattern ignoring the outermost ignored parent directory, if any, or else the
+/// last pattern matching the path itself. `None` means the path is not ignored.
+pub fn ignoring_pattern<S: AsRef<str>>(patterns: &[S], path: &str) -> Option<usize> {
+    let compiled: Vec<(usize, GlobPattern)> = patterns.iter()
+        .map(|pattern| pattern.as_ref())
+        .enumerate()
+        .filter(|(_, pattern)| !pattern.trim().is_empty() && !pattern.starts_with('#'))
+        .map(|(index, pattern)| (index, GlobPattern::parse(pattern)))
+        .collect();
+
+    let decide = |candidate: &str, is_dir: bool| {
+        compiled.iter()
+            .rev()
+            .find(|(_, pattern)| pattern.matches_path(candidate, is_dir))
+            .filter(|(_, pattern)| !pattern.negated)
+            .map(|(index, _)| *index)
+    };
+
+    let (path, is_dir) = split_path(path);
+    path.match_indices('/')
+        .find_map(|(i, _)| decide(&path[..i], true))
+        .or_else(|| decide(path, is_dir))
+}
+
+/// Literal text before the first and after the last wildcard, slashes included
+fn literal_affixes(segments: &[Segment]) -> (String, String) {
+    let mut tokens = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        if index > 0 {
+            tokens.push(Token::Literal('/'));
+        }
+        tokens.extend(segment.tokens.iter().cloned());
+    }
+
+    let prefix = tokens.iter().map_while(Token::literal).collect();
+    let suffix: Vec<char> = tokens.iter().rev().map_while(Token::literal).collect();
+    (prefix, suffix.into_iter().rev().collect())
+}
+
+/// Strip `./` and `/` prefixes and report whether the path names a directory
+fn split_path(path: &str) -> (&str, bool) {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    let path = path.trim_start_matches('/');
+    match path.strip_suffix('/') {
+        Some(dir) => (dir, true),
+        None => (path, false),
+    }
+}
+
+/// Drop trailing whitespace unless it is escaped with a backslash
+pub fn trim_unescaped_trailing_whitespace(pattern: &str) -> &str {
+    let trimmed = pattern.trim_end();
+    let backslashes = trimmed.chars().rev().take_while(|&ch| ch == '\\').count();
+
+    if backslashes % 2 == 1 && trimmed.len() < pattern.len() {
+        let escaped_len = pattern[trimmed.len()..].chars().next().map_or(0, char::len_utf8);
+        &pattern[..trimmed.len() + escaped_len]
+    } else {
+        trimmed
+    }
+}
+
+/// Build a concrete path matched by a glob, filling single stars with `fill`
+fn instantiate(segments: &[Segment], fill: &str) -> String {
+    let mut components = Vec::new();
+
+    for (index, segment) in segments.iter().enumerate() {
+        if segment.is_globstar() {
+            // "**/" may stand for no directories at all
+            if index == segments.len() - 1 {
+                components.push("x".to_string());
+            }
+            continue;
+        }
+
+        let mut component = String::new();
+        for token in &segment.tokens {
+            match token {
+                Token::Literal(ch) | Token::Escaped(ch) => component.push(*ch),
+                Token::Star | Token::Globstar => component.push_str(fill),
+                Token::Question => component.push('x'),
+                Token::Class(class) => {
+                    let member = "xa0_-.XA~"
+                        .chars()
+                        .chain((' '..='~').filter(|c| *c != '/'))
+                        .find(|&c| class.matches(c));
+                    component.push(member.unwrap_or('x'));
+                }
+            }
+        }
+        components.push(component);
+    }
+
+    components.join("/")
 }
 
 #[cfg(test)]
@@ -22,9 +341,159 @@ mod tests {
         assert!(!pattern_matches("*.log", "*.txt"));
     }
 
+    #[test]
+    fn test_glob_selects() {
+        for pattern in ["*.log", "logs/*.log", "npm-debug.log*", "!keep.log", "/debug.log"] {
+            assert!(glob_selects("*.log*", pattern), "{}", pattern);
+        }
+        assert!(glob_selects(".idea*", ".idea/"));
+        assert!(!glob_selects("*.log*", "*.txt"));
+        assert!(!glob_selects(".idea*", ".vscode/"));
+    }
+
     #[test]
     fn test_patterns_conflict() {
         assert!(patterns_conflict("*.log", "*.log"));
         assert!(!patterns_conflict("*.log", "*.txt"));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_star_matches_at_any_depth() {
+        assert!(pattern_matches("*.log", "foo/bar.log"));
+        assert!(pattern_matches("*.log", "debug.log"));
+        assert!(!pattern_matches("*.log", "foo/bar.log.txt"));
+    }
+
+    #[test]
+    fn test_star_does_not_cross_slashes() {
+        assert!(pattern_matches("src/*.rs", "src/main.rs"));
+        assert!(!pattern_matches("src/*.rs", "src/cli/args.rs"));
+    }
+
+    #[test]
+    fn test_question_mark_and_classes() {
+        assert!(pattern_matches("file?.txt", "file1.txt"));
+        assert!(!pattern_matches("file?.txt", "file10.txt"));
+        assert!(pattern_matches("*.py[co]", "module.pyc"));
+        assert!(!pattern_matches("*.py[co]", "module.pyx"));
+        assert!(pattern_matches("[!a]*", "build"));
+        assert!(!pattern_matches("[!a]*", "app"));
+        assert!(pattern_matches("v[0-9]", "v7"));
+        assert!(pattern_matches("[[:upper:]]*", "Makefile"));
+        assert!(pattern_matches("[]]", "]"));
+    }
+
+    #[test]
+    fn test_globstar() {
+        assert!(pattern_matches("**/node_modules", "a/b/node_modules"));
+        assert!(pattern_matches("**/node_modules", "node_modules"));
+        assert!(pattern_matches("logs/**", "logs/a/b.txt"));
+        assert!(!pattern_matches("logs/**", "logs"));
+        assert!(pattern_matches("a/**/b", "a/b"));
+        assert!(pattern_matches("a/**/b", "a/x/y/b"));
+        assert!(!pattern_matches("a/**/b", "x/a/b"));
+    }
+
+    #[test]
+    fn test_trailing_slash_only_matches_directories() {
+        assert!(pattern_matches("build/", "build/"));
+        assert!(pattern_matches("build/", "build/output.o"));
+        assert!(pattern_matches("build/", "src/build/x"));
+        assert!(!pattern_matches("build/", "build"));
+    }
+
+    #[test]
+    fn test_leading_slash_anchors() {
+        assert!(pattern_matches("/build", "build"));
+        assert!(!pattern_matches("/build", "src/build"));
+        assert!(pattern_matches("doc/frotz", "doc/frotz"));
+        assert!(!pattern_matches("doc/frotz", "a/doc/frotz"));
+    }
+
+    #[test]
+    fn test_escapes_and_trailing_spaces() {
+        assert!(pattern_matches("\\#notes", "#notes"));
+        assert!(pattern_matches("\\!important", "!important"));
+        assert!(pattern_matches("*.log  ", "a.log"));
+        assert!(pattern_matches("foo\\ ", "foo "));
+        assert!(!pattern_matches("foo\\ ", "foo"));
+    }
+
+    #[test]
+    fn test_negation_is_ignored_by_matching() {
+        assert!(pattern_matches("!debug.log", "logs/debug.log"));
+        assert!(GlobPattern::parse("!debug.log").negated);
+    }
+
+    #[test]
+    fn test_is_ignored_last_match_wins() {
+        let patterns = ["*.log", "!debug.log"];
+        assert!(is_ignored(&patterns, "app.log"));
+        assert!(!is_ignored(&patterns, "debug.log"));
+        assert!(!is_ignored(&patterns, "main.rs"));
+    }
+
+    #[test]
+    fn test_is_ignored_cannot_reinclude_inside_ignored_directory() {
+        assert!(is_ignored(&["build/", "!build/keep.txt"], "build/keep.txt"));
+        assert!(!is_ignored(&["build/*", "!build/keep.txt"], "build/keep.txt"));
+    }
+
+    #[test]
+    fn test_ignoring_pattern_index() {
+        let patterns = ["# Logs", "*.log", "!debug.log", "build/"];
+        assert_eq!(ignoring_pattern(&patterns, "app.log"), Some(1));
+        assert_eq!(ignoring_pattern(&patterns, "debug.log"), None);
+        assert_eq!(ignoring_pattern(&patterns, "build/debug.log"), Some(3));
+    }
+
+    #[test]
+    fn test_patterns_conflict_by_overlap() {
+        assert!(patterns_conflict("*.log", "!debug.log"));
+        assert!(patterns_conflict("build/", "!build"));
+        assert!(!patterns_conflict("build/", "!debug.log"));
+        assert!(!patterns_conflict("*.log", "!*.tmp"));
+        assert!(patterns_conflict("logs/*", "!b"));
+        assert!(patterns_conflict("logs/*.txt", "!b.txt"));
+    }
+
+    #[test]
+    fn test_may_overlap_is_conservative() {
+        let overlap = |a: &str, b: &str| GlobPattern::parse(a).may_overlap(&GlobPattern::parse(b));
+        // "ab" matches both, although neither pattern's sample paths show it
+        assert!(overlap("a*", "*b"));
+        assert!(overlap("!build/", "build/foo.txt"));
+        assert!(!overlap("*.log", "*.txt"));
+        assert!(!overlap("src/*.rs", "docs/*.rs"));
+        assert!(!overlap("build/", "debug.log"));
+        // An unanchored literal also matches its name inside directories an anchored glob names
+        assert!(overlap("b.txt", "logs/*.txt"));
+        assert!(overlap("logs/*", "!b"));
+        assert!(!overlap("b.log", "logs/*.txt"));
+        assert!(!overlap("/b.txt", "logs/*.txt"));
+    }
+
+    #[test]
+    fn test_covers_parent_of() {
+        let covers_parent = |a: &str, b: &str| GlobPattern::parse(a).covers_parent_of(&GlobPattern::parse(b));
+        assert!(covers_parent("build/", "build/foo.txt"));
+        assert!(covers_parent("build/", "/build/sub/"));
+        assert!(covers_parent("*.egg-info/", "pkg.egg-info/PKG-INFO"));
+        assert!(!covers_parent("build/", "src/foo.txt"));
+        assert!(covers_parent("a/", "a/**/b"));
+        assert!(!covers_parent("x/", "a/**/x/b"));
+    }
+
+    #[test]
+    fn test_pattern_covers() {
+        assert!(pattern_covers("*.log", "debug.log"));
+        assert!(pattern_covers("*.log", "logs/*.log"));
+        assert!(pattern_covers("build", "build/"));
+        assert!(pattern_covers("build", "/build"));
+        assert!(pattern_covers("node_modules/", "**/node_modules/"));
+        assert!(!pattern_covers("build/", "build"));
+        assert!(!pattern_covers("/build", "build"));
+        assert!(!pattern_covers("debug.log", "*.log"));
+        assert!(!pattern_covers("?.log", "*.log"));
+    }
+}
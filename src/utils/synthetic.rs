@@ -0,0 +1,73 @@
+use clap::ValueEnum;
+
+/// Number of negations in a synthetic file, whatever its size; real files have a handful
+const NEGATIONS: usize = 8;
+
+/// Size of a synthetic gitignore file for benchmarks (see `synthetic_gitignore`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FixtureSize {
+    /// 200 lines, about a hand-written project file
+    Small,
+    /// 2,000 lines, about a large monorepo file
+    Medium,
+    /// 20,000 lines, far beyond anything hand-written
+    Huge,
+}
+
+impl FixtureSize {
+    pub const ALL: [FixtureSize; 3] = [FixtureSize::Small, FixtureSize::Medium, FixtureSize::Huge];
+
+    /// Number of lines of a file of this size
+    pub fn lines(self) -> usize {
+        match self {
+            FixtureSize::Small => 200,
+            FixtureSize::Medium => 2_000,
+            FixtureSize::Huge => 20_000,
+        }
+    }
+
+    /// Name of the size, as given to `gix bench --size`
+    pub fn name(self) -> &'static str {
+        match self {
+            FixtureSize::Small => "small",
+            FixtureSize::Medium => "medium",
+            FixtureSize::Huge => "huge",
+        }
+    }
+}
+
+/// Generate a gitignore file of `lines` lines, the same every time
+///
+/// It has repeated section headers and patterns, equivalent spellings (`/build1/` and
+/// `build1`), literal paths, wildcards, globstars and a few negations, so every pass has
+/// work to do.
+pub fn synthetic_gitignore(lines: usize) -> String {
+    let negation_every = (lines / NEGATIONS).max(10);
+    (0..lines)
+        .map(|line| match line % 10 {
+            _ if line % negation_every == negation_every / 2 => format!("!dir{}/keep.txt\n", line % 300),
+            0 => format!("# Section {}\n", line % 97),
+            1 => "\n".to_string(),
+            2 | 3 => format!("dir{}/file{}.txt\n", line % 300, line % 900),
+            4 => format!("*.ext{}\n", line % 50),
+            5 => format!("/build{}/\n", line % 80),
+            6 => format!("build{}\n", line % 80),
+            7 => format!("**/cache{}\n", line % 200),
+            _ => format!("tmp{}.swp\n", line),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_gitignore() {
+        let content = synthetic_gitignore(FixtureSize::Medium.lines());
+        assert_eq!(content.lines().count(), 2_000);
+        assert_eq!(content, synthetic_gitignore(2_000));
+        assert_eq!(content.lines().filter(|line| line.starts_with('!')).count(), NEGATIONS);
+        assert!(content.starts_with("# Section 0\n\ndir2/file2.txt\n"));
+    }
+}
@@ -0,0 +1,334 @@
+use std::fmt;
+
+/// One element of a bracket expression
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClassItem {
+    /// A single character (`a`, `\]`)
+    Char(char),
+    /// An inclusive range (`0-9`)
+    Range(char, char),
+    /// A POSIX character class (`[:digit:]`), by name
+    Posix(String),
+}
+
+/// A bracket expression such as `[co]`, `[!a-z]` or `[[:upper:]]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CharClass {
+    /// The class matches characters it doesn't list (`[!...]` or `[^...]`)
+    pub negated: bool,
+    /// The listed characters, ranges and POSIX classes
+    pub items: Vec<ClassItem>,
+    /// The expression as written, brackets included
+    pub source: String,
+}
+
+impl CharClass {
+    /// Check if the class matches a character; it never matches the path separator
+    pub fn matches(&self, ch: char) -> bool {
+        let listed = self.items.iter().any(|item| match item {
+            ClassItem::Char(member) => *member == ch,
+            ClassItem::Range(low, high) => *low <= ch && ch <= *high,
+            ClassItem::Posix(name) => posix_class_matches(name, ch),
+        });
+        ch != '/' && listed != self.negated
+    }
+}
+
+/// A lexical element of a glob
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Token {
+    /// A character matching itself
+    Literal(char),
+    /// A character escaped with a backslash (`\#`, `\ `), matching itself
+    Escaped(char),
+    /// `*`: any run of characters within one path component
+    Star,
+    /// `?`: any single character except `/`
+    Question,
+    /// A bracket expression: one character from a set
+    Class(CharClass),
+    /// `**` as a whole path component: zero or more directories
+    Globstar,
+}
+
+impl Token {
+    /// Check if the token matches exactly one character
+    fn matches_char(&self, ch: char) -> bool {
+        match self {
+            Token::Literal(literal) | Token::Escaped(literal) => *literal == ch,
+            Token::Question => ch != '/',
+            Token::Class(class) => class.matches(ch),
+            Token::Star | Token::Globstar => false,
+        }
+    }
+
+    /// Check if the token stands for more than one string
+    pub fn is_wildcard(&self) -> bool {
+        !matches!(self, Token::Literal(_) | Token::Escaped(_))
+    }
+
+    /// Get the character a literal or escaped token stands for
+    pub fn literal(&self) -> Option<char> {
+        match self {
+            Token::Literal(ch) | Token::Escaped(ch) => Some(*ch),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Literal(ch) => write!(f, "{}", ch),
+            Token::Escaped(ch) => write!(f, "\\{}", ch),
+            Token::Star => f.write_str("*"),
+            Token::Question => f.write_str("?"),
+            Token::Class(class) => f.write_str(&class.source),
+            Token::Globstar => f.write_str("**"),
+        }
+    }
+}
+
+/// The tokens of one path component of a glob, between slashes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Segment {
+    /// Tokens in order
+    pub tokens: Vec<Token>,
+}
+
+impl Segment {
+    /// Check if the segment is `**`
+    pub fn is_globstar(&self) -> bool {
+        self.tokens == [Token::Globstar]
+    }
+
+    /// Check if the segment has no tokens, as before a leading or after a trailing slash
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Check if the segment matches only its own text
+    pub fn is_literal(&self) -> bool {
+        !self.tokens.iter().any(Token::is_wildcard)
+    }
+
+    /// Check if the segment matches a path component
+    pub fn matches(&self, component: &str) -> bool {
+        let text: Vec<char> = component.chars().collect();
+        match_tokens(&self.tokens, &text)
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.tokens.iter().try_for_each(|token| write!(f, "{}", token))
+    }
+}
+
+/// Split a glob into segments at its slashes and each segment into tokens
+///
+/// A leading slash gives an empty first segment and a trailing slash an empty last one; an
+/// unclosed `[` and a trailing lone backslash are literals. Runs of stars collapse to one
+/// `*`, except a run making up a whole segment, which is a `**`.
+pub fn tokenize(glob: &str) -> Vec<Segment> {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut segments = vec![Segment::default()];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let tokens = &mut segments.last_mut().expect("at least one segment").tokens;
+        match chars[i] {
+            '/' => {
+                segments.push(Segment::default());
+                i += 1;
+            }
+            '*' => {
+                let start = i;
+                while i < chars.len() && chars[i] == '*' {
+                    i += 1;
+                }
+                let whole_segment = tokens.is_empty() && (i == chars.len() || chars[i] == '/');
+                tokens.push(if i - start >= 2 && whole_segment { Token::Globstar } else { Token::Star });
+            }
+            '?' => {
+                tokens.push(Token::Question);
+                i += 1;
+            }
+            '[' if class_end(&chars, i).is_some() => {
+                let end = class_end(&chars, i).unwrap_or(i);
+                tokens.push(Token::Class(parse_class(&chars[i..=end])));
+                i = end + 1;
+            }
+            '\\' if i + 1 < chars.len() => {
+                tokens.push(Token::Escaped(chars[i + 1]));
+                i += 2;
+            }
+            literal => {
+                tokens.push(Token::Literal(literal));
+                i += 1;
+            }
+        }
+    }
+
+    segments
+}
+
+/// Render segments back to glob text, joined by slashes
+pub fn render(segments: &[Segment]) -> String {
+    segments.iter().map(Segment::to_string).collect::<Vec<_>>().join("/")
+}
+
+/// Match path components against segments with gitignore (wildmatch pathname) semantics
+///
+/// A `**` segment in the middle or at the start stands for zero or more directories, and a
+/// trailing one for everything below.
+pub fn match_segments(segments: &[Segment], components: &[&str]) -> bool {
+    match segments.split_first() {
+        None => components.is_empty(),
+        Some((first, [])) if first.is_globstar() => !components.is_empty(),
+        Some((first, rest)) if first.is_globstar() => {
+            (0..=components.len()).any(|skipped| match_segments(rest, &components[skipped..]))
+        }
+        Some((first, rest)) => match components.split_first() {
+            Some((component, remaining)) => first.matches(component) && match_segments(rest, remaining),
+            None => false,
+        },
+    }
+}
+
+fn match_tokens(tokens: &[Token], text: &[char]) -> bool {
+    match tokens.split_first() {
+        None => text.is_empty(),
+        Some((Token::Star, rest)) => (0..=text.len()).any(|taken| match_tokens(rest, &text[taken..])),
+        Some((token, rest)) => match text.split_first() {
+            Some((ch, remaining)) => token.matches_char(*ch) && match_tokens(rest, remaining),
+            None => false,
+        },
+    }
+}
+
+/// Find the index of the `]` closing the bracket expression that starts at `start`
+fn class_end(p: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if matches!(p.get(i), Some('!') | Some('^')) {
+        i += 1;
+    }
+    // A leading ']' is a literal member
+    if p.get(i) == Some(&']') {
+        i += 1;
+    }
+
+    while i < p.len() {
+        match p[i] {
+            ']' => return Some(i),
+            '\\' => i += 2,
+            '[' if p.get(i + 1) == Some(&':') => {
+                i = posix_class_end(p, i + 2).map_or(i + 1, |end| end + 2);
+            }
+            _ => i += 1,
+        }
+    }
+
+    None
+}
+
+/// Find the `:` of the `:]` closing a POSIX class name that starts at `start`
+fn posix_class_end(p: &[char], start: usize) -> Option<usize> {
+    (start..p.len().saturating_sub(1)).find(|&i| p[i] == ':' && p[i + 1] == ']')
+}
+
+/// Parse a whole bracket expression, brackets included
+fn parse_class(p: &[char]) -> CharClass {
+    let end = p.len() - 1;
+    let mut i = 1;
+    let negated = matches!(p.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut items = Vec::new();
+    while i < end {
+        if p[i] == '[' && p.get(i + 1) == Some(&':') {
+            if let Some(colon) = posix_class_end(p, i + 2) {
+                items.push(ClassItem::Posix(p[i + 2..colon].iter().collect()));
+                i = colon + 2;
+                continue;
+            }
+        }
+
+        let (low, after_low) = if p[i] == '\\' && i + 1 < end { (p[i + 1], i + 2) } else { (p[i], i + 1) };
+
+        if p.get(after_low) == Some(&'-') && after_low + 1 < end {
+            let (high, after_high) = if p[after_low + 1] == '\\' && after_low + 2 < end {
+                (p[after_low + 2], after_low + 3)
+            } else {
+                (p[after_low + 1], after_low + 2)
+            };
+            items.push(ClassItem::Range(low, high));
+            i = after_high;
+        } else {
+            items.push(ClassItem::Char(low));
+            i = after_low;
+        }
+    }
+
+    CharClass { negated, items, source: p.iter().collect() }
+}
+
+fn posix_class_matches(name: &str, ch: char) -> bool {
+    match name {
+        "alnum" => ch.is_ascii_alphanumeric(),
+        "alpha" => ch.is_ascii_alphabetic(),
+        "blank" => ch == ' ' || ch == '\t',
+        "cntrl" => ch.is_ascii_control(),
+        "digit" => ch.is_ascii_digit(),
+        "graph" => ch.is_ascii_graphic(),
+        "lower" => ch.is_ascii_lowercase(),
+        "print" => ch.is_ascii_graphic() || ch == ' ',
+        "punct" => ch.is_ascii_punctuation(),
+        "space" => ch.is_ascii_whitespace(),
+        "upper" => ch.is_ascii_uppercase(),
+        "xdigit" => ch.is_ascii_hexdigit(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_segments_and_tokens() {
+        let segments = tokenize("/src/**/*.py[co]");
+        assert_eq!(segments.len(), 4);
+        assert!(segments[0].is_empty());
+        assert_eq!(segments[1].tokens, "src".chars().map(Token::Literal).collect::<Vec<_>>());
+        assert!(segments[2].is_globstar());
+        assert_eq!(segments[3].tokens[0], Token::Star);
+        assert!(matches!(&segments[3].tokens[4], Token::Class(class) if class.items == [ClassItem::Char('c'), ClassItem::Char('o')]));
+        assert_eq!(render(&segments), "/src/**/*.py[co]");
+    }
+
+    #[test]
+    fn test_tokenize_escapes_stars_and_unclosed_classes() {
+        let [segment] = &tokenize("\\#a**b[x\\")[..] else { panic!("one segment expected") };
+        assert_eq!(segment.tokens, vec![
+            Token::Escaped('#'), Token::Literal('a'), Token::Star, Token::Literal('b'),
+            Token::Literal('['), Token::Literal('x'), Token::Literal('\\'),
+        ]);
+        assert!(!segment.is_literal());
+        assert!(tokenize("build/").last().is_some_and(Segment::is_empty));
+    }
+
+    #[test]
+    fn test_match_segments() {
+        let matches = |glob: &str, path: &str| match_segments(&tokenize(glob), &path.split('/').collect::<Vec<_>>());
+        assert!(matches("a/**/b", "a/b"));
+        assert!(matches("a/**/b", "a/x/y/b"));
+        assert!(!matches("logs/**", "logs"));
+        assert!(matches("logs/**", "logs/a/b.txt"));
+        assert!(matches("[!a]*", "build"));
+        assert!(matches("[[:digit:]-z]", "-"));
+        assert!(!matches("src/*.rs", "src/cli/args.rs"));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::core::parser::parse_gitignore;
+use crate::models::GixError;
+use crate::utils::file::read_gitignore_file;
+use crate::utils::git::{exclude_file, is_nested_repository};
+use crate::utils::patterns::is_ignored;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Options controlling which parts of a checkout a walk visits
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalkOptions {
+    /// Descend into submodules and other nested repositories instead of skipping them
+    pub include_submodules: bool,
+    /// Directories to skip, as gitignore-style globs relative to the walked root (e.g. `vendor`, `/third_party`)
+    pub exclude_paths: Vec<String>,
+}
+
+impl WalkOptions {
+    /// Check if a directory, relative to the walked root, was excluded
+    pub fn excludes(&self, dir: &str) -> bool {
+        !self.exclude_paths.is_empty() && is_ignored(&self.exclude_paths, &format!("{}/", dir))
+    }
+}
+
+/// A file found by a walk
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WalkedFile {
+    /// Path relative to the walked root, with `/` separators
+    pub path: String,
+    /// Location on disk
+    pub location: PathBuf,
+}
+
+/// Files found below a directory, each list sorted by path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepositoryFiles {
+    /// Files whose name was asked for
+    pub files: Vec<WalkedFile>,
+    /// The `info/exclude` of each visited checkout, labelled `.git/info/exclude` below its checkout
+    pub exclude_files: Vec<WalkedFile>,
+}
+
+/// Find files named one of `names` below `root`, skipping `.git` and directories ignored by a `.gitignore` above them
+///
+/// The repository's `info/exclude` applies to the whole checkout, resolved through the common
+/// git dir so linked worktrees use the file shared by all worktrees. Nested repositories such
+/// as submodules have their own ignore rules and are skipped unless `include_submodules` is set.
+pub fn find_files(root: &Path, names: &[&str], options: &WalkOptions) -> Result<RepositoryFiles, GixError> {
+    let mut walker = Walker { root, names, options, ignores: Vec::new(), found: RepositoryFiles::default() };
+    walker.checkout("")?;
+    walker.found.files.sort();
+    walker.found.exclude_files.sort();
+    Ok(walker.found)
+}
+
+/// List every file and directory below `root`, directories with a trailing `/`, sorted
+///
+/// Unlike `find_files` this descends into ignored directories, since the point is to see
+/// what patterns match; only `.git`, excluded paths and (unless `include_submodules` is set)
+/// nested repositories are skipped.
+pub fn list_paths(root: &Path, options: &WalkOptions) -> Result<Vec<String>, GixError> {
+    let mut paths = Vec::new();
+    list_dir(root, "", options, &mut paths)?;
+    paths.sort();
+    Ok(paths)
+}
+
+/// Collect the paths below `dir`
+fn list_dir(root: &Path, dir: &str, options: &WalkOptions, paths: &mut Vec<String>) -> Result<(), GixError> {
+    for entry in fs::read_dir(root.join(dir))?.filter_map(|entry| entry.ok()) {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = if dir.is_empty() { name.clone() } else { format!("{}/{}", dir, name) };
+
+        if !file_type.is_dir() {
+            paths.push(path);
+        } else if name != ".git" && !options.excludes(&path)
+            && (options.include_submodules || !is_nested_repository(&root.join(&path))) {
+            paths.push(format!("{}/", path));
+            list_dir(root, &path, options, paths)?;
+        }
+    }
+    Ok(())
+}
+
+/// State of a walk: the `.gitignore` patterns that apply to the current directory, by base directory
+struct Walker<'a> {
+    root: &'a Path,
+    names: &'a [&'a str],
+    options: &'a WalkOptions,
+    ignores: Vec<(String, Vec<String>)>,
+    found: RepositoryFiles,
+}
+
+impl Walker<'_> {
+    /// Walk the checkout at `prefix` with only its own rules, starting from its exclude file
+    fn checkout(&mut self, prefix: &str) -> Result<(), GixError> {
+        let outer = std::mem::take(&mut self.ignores);
+        if let Some(exclude) = exclude_file(&self.root.join(prefix)) {
+            self.ignores.push((prefix.to_string(), read_patterns(&exclude)?));
+            let path = if prefix.is_empty() { ".git/info/exclude".to_string() } else { format!("{}/.git/info/exclude", prefix) };
+            self.found.exclude_files.push(WalkedFile { path, location: exclude });
+        }
+        let result = self.dir(prefix);
+        self.ignores = outer;
+        result
+    }
+
+    /// Collect matching files below `dir`
+    fn dir(&mut self, dir: &str) -> Result<(), GixError> {
+        let join = |name: &str| if dir.is_empty() { name.to_string() } else { format!("{}/{}", dir, name) };
+
+        let gitignore = self.root.join(join(".gitignore"));
+        let pushed = gitignore.is_file();
+        if pushed {
+            // An unreadable file is still listed below, so whoever processes it reports the error
+            self.ignores.push((dir.to_string(), read_patterns(&gitignore).unwrap_or_default()));
+        }
+
+        let mut entries: Vec<(String, bool)> = fs::read_dir(self.root.join(dir))?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_type = entry.file_type().ok()?;
+                Some((entry.file_name().to_string_lossy().to_string(), file_type.is_dir()))
+            })
+            .collect();
+        entries.sort();
+
+        for (name, is_dir) in entries {
+            let path = join(&name);
+            if !is_dir {
+                if self.names.contains(&name.as_str()) {
+                    let location = self.root.join(&path);
+                    self.found.files.push(WalkedFile { path, location });
+                }
+            } else if name == ".git" || self.options.excludes(&path) || self.is_ignored(&path) {
+                continue;
+            } else if is_nested_repository(&self.root.join(&path)) {
+                // The enclosing repository's rules don't apply inside a nested one
+                if self.options.include_submodules {
+                    self.checkout(&path)?;
+                }
+            } else {
+                self.dir(&path)?;
+            }
+        }
+
+        if pushed {
+            self.ignores.pop();
+        }
+        Ok(())
+    }
+
+    /// Check a directory against every ignore file above it, each relative to its own directory
+    fn is_ignored(&self, dir: &str) -> bool {
+        self.ignores.iter().any(|(base, patterns)| {
+            let relative = if base.is_empty() { dir } else { &dir[base.len() + 1..] };
+            is_ignored(patterns, &format!("{}/", relative))
+        })
+    }
+}
+
+/// Read the patterns of an ignore file, without inline comments
+fn read_patterns(path: &Path) -> Result<Vec<String>, GixError> {
+    let file = parse_gitignore(&read_gitignore_file(path)?)?;
+    Ok(file.entries.iter().filter_map(|entry| entry.comparison_pattern()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(files: &[WalkedFile]) -> Vec<&str> {
+        files.iter().map(|file| file.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_find_files_skips_ignored_and_excluded_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        for sub in ["src", "target/debug", "vendor/lib", "app/cache"] {
+            fs::create_dir_all(dir.path().join(sub)).unwrap();
+        }
+        fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        fs::write(dir.path().join("src/.gitignore"), "*.tmp\n").unwrap();
+        fs::write(dir.path().join("target/debug/.gitignore"), "*\n").unwrap();
+        fs::write(dir.path().join("vendor/lib/.gitignore"), "*.o\n").unwrap();
+        fs::write(dir.path().join("app/.gitignore"), "cache/\n").unwrap();
+        fs::write(dir.path().join("app/cache/.gitignore"), "*\n").unwrap();
+
+        let options = WalkOptions { exclude_paths: vec!["vendor".to_string()], ..WalkOptions::default() };
+        let found = find_files(dir.path(), &[".gitignore"], &options).unwrap();
+        assert_eq!(paths(&found.files), vec![".gitignore", "app/.gitignore", "src/.gitignore"]);
+        assert_eq!(found.files[2].location, dir.path().join("src/.gitignore"));
+        assert!(found.exclude_files.is_empty());
+    }
+
+    #[test]
+    fn test_list_paths_includes_ignored_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::create_dir_all(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        fs::write(dir.path().join("target/debug/app"), "").unwrap();
+        fs::write(dir.path().join("vendor/lib.c"), "").unwrap();
+
+        let options = WalkOptions { exclude_paths: vec!["vendor".to_string()], ..WalkOptions::default() };
+        assert_eq!(list_paths(dir.path(), &options).unwrap(), vec![".gitignore", "target/", "target/debug/", "target/debug/app"]);
+    }
+
+    #[test]
+    fn test_find_files_in_submodules_uses_their_own_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git/info")).unwrap();
+        fs::write(dir.path().join(".git/info/exclude"), "build/\n").unwrap();
+        fs::create_dir_all(dir.path().join("sub/build")).unwrap();
+        fs::write(dir.path().join("sub/.git"), "gitdir: ../.git/modules/sub\n").unwrap();
+        fs::write(dir.path().join("sub/build/.gitignore"), "*\n").unwrap();
+
+        let found = find_files(dir.path(), &[".gitignore"], &WalkOptions::default()).unwrap();
+        assert!(found.files.is_empty());
+        assert_eq!(paths(&found.exclude_files), vec![".git/info/exclude"]);
+
+        let options = WalkOptions { include_submodules: true, ..WalkOptions::default() };
+        let found = find_files(dir.path(), &[".gitignore"], &options).unwrap();
+        assert_eq!(paths(&found.files), vec!["sub/build/.gitignore"]);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::models::GixError;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// Watches one ignore file, or every `.gitignore` below a directory, for changes
+///
+/// Editors often save in several steps (truncate, write, rename), so changes are reported
+/// only once the files have been quiet for the debounce delay, each file once per report.
+pub struct IgnoreFileWatcher {
+    root: PathBuf,
+    target: Option<PathBuf>,
+    debounce: Duration,
+    events: Receiver<notify::Result<Event>>,
+    // Dropping the watcher stops the events
+    _watcher: RecommendedWatcher,
+}
+
+impl IgnoreFileWatcher {
+    /// Watch the file at `path`
+    pub fn file(path: &Path, debounce: Duration) -> Result<Self, GixError> {
+        let name = path.file_name().ok_or_else(|| GixError::UsageError(format!("{} is not a file", path.display())))?;
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        // The directory is watched rather than the file, so a save that replaces the file is seen
+        let root = dir.canonicalize()?;
+        Self::start(root.clone(), Some(root.join(name)), RecursiveMode::NonRecursive, debounce)
+    }
+
+    /// Watch every `.gitignore` below `dir`, including those created later
+    pub fn recursive(dir: &Path, debounce: Duration) -> Result<Self, GixError> {
+        Self::start(dir.canonicalize()?, None, RecursiveMode::Recursive, debounce)
+    }
+
+    fn start(root: PathBuf, target: Option<PathBuf>, mode: RecursiveMode, debounce: Duration) -> Result<Self, GixError> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(|error| GixError::WatchError(error.to_string()))?;
+        watcher.watch(&root, mode).map_err(|error| GixError::WatchError(format!("{}: {}", root.display(), error)))?;
+        Ok(Self { root, target, debounce, events, _watcher: watcher })
+    }
+
+    /// The watched directory, canonicalized; for a single file, the directory holding it
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Wait until watched files change, then return them, sorted, once no change came for the debounce delay
+    ///
+    /// Files that were removed are included; the caller tells them apart by their absence.
+    pub fn next_changes(&self) -> Result<Vec<PathBuf>, GixError> {
+        let mut changed = BTreeSet::new();
+        let mut received = self.events.recv().map_err(|_| GixError::WatchError("the watcher stopped".to_string()))?;
+        loop {
+            let event = received.map_err(|error| GixError::WatchError(error.to_string()))?;
+            if !matches!(event.kind, EventKind::Access(_)) {
+                changed.extend(event.paths.into_iter().filter(|path| self.watches(path)));
+            }
+
+            received = match self.events.recv_timeout(self.debounce) {
+                Ok(next) => next,
+                Err(RecvTimeoutError::Timeout) if !changed.is_empty() => return Ok(changed.into_iter().collect()),
+                // Only unwatched files changed, so there is nothing to report yet
+                Err(RecvTimeoutError::Timeout) => self.events.recv().map_err(|_| GixError::WatchError("the watcher stopped".to_string()))?,
+                Err(RecvTimeoutError::Disconnected) => return Err(GixError::WatchError("the watcher stopped".to_string())),
+            };
+        }
+    }
+
+    /// Check if a changed path is one of the watched files
+    fn watches(&self, path: &Path) -> bool {
+        match &self.target {
+            Some(target) => path == target,
+            None => {
+                path.file_name().is_some_and(|name| name == ".gitignore")
+                    && path.strip_prefix(&self.root).is_ok_and(|relative| {
+                        !relative.components().any(|component| component == Component::Normal(".git".as_ref()))
+                    })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_recursive_watch_reports_gitignore_files_once() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        let watcher = IgnoreFileWatcher::recursive(dir.path(), Duration::from_millis(100)).unwrap();
+
+        fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.log\n*.log\n").unwrap();
+        fs::write(dir.path().join("sub/.gitignore"), "build/\n").unwrap();
+        fs::write(dir.path().join("sub/README.md"), "docs\n").unwrap();
+        fs::write(dir.path().join(".git/.gitignore"), "x\n").unwrap();
+
+        let root = dir.path().canonicalize().unwrap();
+        assert_eq!(watcher.next_changes().unwrap(), vec![root.join(".gitignore"), root.join("sub/.gitignore")]);
+    }
+
+    #[test]
+    fn test_file_watch_ignores_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.ignore");
+        let watcher = IgnoreFileWatcher::file(&path, Duration::from_millis(100)).unwrap();
+
+        fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        fs::write(&path, "*.tmp\n").unwrap();
+        assert_eq!(watcher.next_changes().unwrap(), vec![dir.path().canonicalize().unwrap().join("custom.ignore")]);
+    }
+}
@@ -0,0 +1,26 @@
+//! Keeps the minimal build small enough for containers and git hooks.
+//!
+//! Only enforced for the minimal build:
+//!   cargo test --profile minimal --no-default-features --test binary_size
+
+/// Upper bound for the stripped minimal binary
+///
+/// Raised from 1 MiB when the minimal build took on the checks every rewrite runs (negation
+/// semantics, plans, the audit log, `gix check` rules). Leaving them out would make the minimal
+/// build's rewrites less safe than the default build's, so the bound keeps some headroom instead.
+const MINIMAL_BINARY_LIMIT: u64 = 1536 * 1024;
+
+#[test]
+fn test_minimal_binary_size() {
+    if cfg!(debug_assertions) || cfg!(feature = "dataset") {
+        return;
+    }
+
+    let size = std::fs::metadata(env!("CARGO_BIN_EXE_gix")).unwrap().len();
+    assert!(
+        size <= MINIMAL_BINARY_LIMIT,
+        "minimal gix binary is {} bytes, limit is {} bytes",
+        size,
+        MINIMAL_BINARY_LIMIT
+    );
+}
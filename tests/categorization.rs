@@ -0,0 +1,80 @@
+//! Categorization accuracy against the embedded dataset.
+//!
+//! Every pattern in the corpus must land in its expected category; add a line here when a
+//! pattern is miscategorized, so the fix stays fixed.
+
+#![cfg(feature = "dataset")]
+
+use gix::core::PatternCategorizer;
+
+/// Patterns and the display name of the category each belongs to
+const CORPUS: &[(&str, &str)] = &[
+    // Written as in the dataset
+    ("*.pyc", "Language: Python"),
+    ("__pycache__/", "Language: Python"),
+    (".pytest_cache/", "Language: Python"),
+    (".venv/", "Language: Python"),
+    ("node_modules/", "Language: Node.js"),
+    (".eslintcache", "Language: Node.js"),
+    ("*.class", "Language: Java"),
+    ("Cargo.lock", "Language: Rust"),
+    ("go.work", "Language: Go"),
+    ("db.sqlite3", "Framework: Django"),
+    (".pnp.js", "Framework: React"),
+    (".vscode/", "Tool: VSCode"),
+    (".idea/", "Tool: IntelliJ"),
+    ("*.elc", "Tool: Emacs"),
+    (".DS_Store", "OS: macOS"),
+    ("Thumbs.db", "OS: Windows"),
+    (".directory", "OS: Linux"),
+    // Anchored or written differently, matching the same paths
+    ("/node_modules", "Language: Node.js"),
+    ("**/__pycache__/", "Language: Python"),
+    ("**/.DS_Store", "OS: macOS"),
+    ("/target/", "Language: Java"),
+    // Selected by a known glob
+    ("*.pyo", "Language: Python"),
+    ("npm-debug.log", "Language: Node.js"),
+    ("hs_err_pid123.log", "Language: Java"),
+    ("app.egg-info/", "Language: Python"),
+    (".Trash-1000", "OS: Linux"),
+    // Inside a known directory
+    (".vscode/launch.local.json", "Tool: VSCode"),
+    (".idea/workspace.xml", "Tool: IntelliJ"),
+    // Project-specific by name or directory
+    ("custom/", "Custom: Project-specific"),
+    ("local/", "Custom: Project-specific"),
+    ("config.local.json", "Custom: Project-specific"),
+    // Sharing letters with known patterns is not enough
+    ("log/", "Uncategorized"),
+    ("random.config", "Uncategorized"),
+    ("docs/_build/", "Uncategorized"),
+    ("coverage.xml", "Uncategorized"),
+    ("*.py", "Uncategorized"),
+    ("settings.json", "Uncategorized"),
+    ("random_file.txt", "Uncategorized"),
+];
+
+#[test]
+fn test_categorization_corpus() {
+    let categorizer = PatternCategorizer::new();
+    let wrong: Vec<String> = CORPUS.iter()
+        .filter_map(|(pattern, expected)| {
+            let actual = categorizer.primary_category(pattern).display_name();
+            (actual != *expected).then(|| format!("{}: expected {}, got {}", pattern, expected, actual))
+        })
+        .collect();
+    assert!(wrong.is_empty(), "{} of {} patterns miscategorized:\n{}", wrong.len(), CORPUS.len(), wrong.join("\n"));
+}
+
+#[test]
+fn test_confidence_follows_match_quality() {
+    let categorizer = PatternCategorizer::new();
+    let confidence = |pattern: &str| categorizer.categorize_pattern(pattern)[0].1;
+
+    assert_eq!(confidence("node_modules/"), 1.0);
+    assert!(confidence("/node_modules") > confidence("npm-debug.log"));
+    assert!(confidence("npm-debug.log") > confidence(".vscode/launch.local.json"));
+    assert!(confidence(".vscode/launch.local.json") > confidence("custom/"));
+    assert_eq!(confidence("random.config"), 0.0);
+}
@@ -434,4 +434,216 @@ build/
         assert_eq!(optimized.stats.comment_lines, 10, "Should preserve 10 comment lines");
         assert_eq!(optimized.stats.blank_lines, 7, "Should preserve 7 blank lines");
     }
-} 
\ No newline at end of file
+} 
+mod escape_tests {
+    use super::*;
+    use gix::core::{normalize_whitespace, PassStats, PatternAnalyzer};
+    use gix::utils::patterns::{pattern_matches, GlobPattern};
+
+    #[test]
+    fn should_keep_escaped_trailing_space() {
+        // Arrange: An escaped trailing space, followed by spaces git drops
+        let content = "foo\\   \nbar  \nbaz\\\\ \n";
+
+        // Act: Normalize patterns and whitespace
+        let analyzer = PatternAnalyzer::default();
+        let (normalized, fixes) = normalize_whitespace(&parse_gitignore(content).unwrap(), &mut PassStats::default());
+
+        // Assert: Only the escaped space survives; an escaped backslash escapes nothing
+        assert_eq!(analyzer.normalize_pattern("foo\\   "), "foo\\ ");
+        assert_eq!(analyzer.normalize_pattern("baz\\\\ "), "baz\\\\");
+        assert_eq!(format!("{}", normalized), "foo\\ \nbar\nbaz\\\\");
+        assert_eq!(fixes.len(), 3);
+        assert!(pattern_matches("foo\\ ", "foo "));
+        assert!(!pattern_matches("foo\\ ", "foo"));
+    }
+
+    #[test]
+    fn should_match_escaped_characters_literally() {
+        // Arrange: Escapes for the characters gitignore gives a meaning at the start of a line
+        let file = parse_gitignore("\\!important\n\\#notes\nback\\\\slash\n").unwrap();
+
+        // Act: Match each pattern against the literal path
+        let globs: Vec<GlobPattern> = file.entries.iter()
+            .filter_map(|entry| entry.comparison_pattern())
+            .map(|pattern| GlobPattern::parse(&pattern))
+            .collect();
+
+        // Assert: None is a negation or a comment, and each matches only the literal name
+        assert_eq!(globs.len(), 3);
+        assert!(globs.iter().all(|glob| !glob.negated));
+        assert!(globs[0].matches("!important"));
+        assert!(!globs[0].matches("important"));
+        assert!(globs[1].matches("#notes"));
+        assert!(globs[2].matches("back\\slash"));
+        assert!(!globs[2].matches("backslash"));
+    }
+}
+
+mod advanced_mode_tests {
+    use super::*;
+    use gix::core::{optimize_gitignore_advanced, PatternAnalyzer};
+    use std::process::Command;
+
+    /// Run the gix binary on a temporary .gitignore and return (output, rewritten content)
+    fn run_gix(content: &str, extra_args: &[&str]) -> (std::process::Output, String) {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join(".gitignore");
+        std::fs::write(&path, content).expect("Failed to write gitignore");
+
+        let output = Command::new(env!("CARGO_BIN_EXE_gix"))
+            .args(extra_args)
+            .arg(&path)
+            .output()
+            .expect("Failed to run gix");
+        let rewritten = std::fs::read_to_string(&path).expect("Failed to read gitignore");
+        (output, rewritten)
+    }
+
+    #[test]
+    fn should_remove_duplicates_and_covered_patterns() {
+        // Arrange: Duplicates plus patterns covered by broader ones
+        let content = "# Build\nbuild/\nbuild/output.o\n\n# Python\n*.pyc\n**/*.pyc\n*.pyc\n";
+
+        // Act: Optimize in advanced mode through the CLI
+        let (output, rewritten) = run_gix(content, &["--mode", "advanced"]);
+
+        // Assert: Only the broadest patterns survive, layout is kept
+        assert!(output.status.success());
+        assert_eq!(rewritten, "# Build\nbuild/\n\n# Python\n*.pyc\n");
+    }
+
+    #[test]
+    fn should_report_covering_patterns_in_verbose_mode() {
+        // Arrange
+        let content = "build/\nbuild/output.o\n";
+
+        // Act
+        let (output, _) = run_gix(content, &["--mode", "advanced", "--verbose"]);
+
+        // Assert: The mode is announced and each removal names its covering pattern
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("advanced optimization mode"));
+        assert!(stdout.contains("line 2: build/output.o (covered by 'build/' on line 1)"));
+    }
+
+    #[test]
+    fn should_report_covered_patterns_apart_from_duplicates() {
+        // Arrange
+        let content = "build/\nbuild/output.o\n*.log\n*.log\n";
+
+        // Act
+        let (output, _) = run_gix(content, &["--mode", "advanced"]);
+
+        // Assert: Only exact duplicates come with the promise that the same files are ignored
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Removed 1 duplicate line(s)"));
+        assert!(stdout.contains("Removed 1 pattern(s) covered by broader patterns"));
+        assert!(!stdout.contains("the same files are ignored"));
+
+        let (output, _) = run_gix("*.log\n*.log\n", &["--mode", "advanced"]);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("the same files are ignored"));
+    }
+
+    #[test]
+    fn should_keep_patterns_a_negation_depends_on() {
+        // Arrange: The last line re-ignores a file the negation re-included
+        let content = "*.log\n!debug.log\ndebug.log\n";
+
+        // Act
+        let (output, rewritten) = run_gix(content, &["--mode", "advanced"]);
+
+        // Assert: Nothing is removed
+        assert!(output.status.success());
+        assert_eq!(rewritten, "*.log\n!debug.log\ndebug.log\n");
+    }
+
+    #[test]
+    fn should_not_offer_findings_across_a_negation_as_safe_fixes() {
+        // Arrange: A negation sits between the first pattern and the one it covers
+        let content = "*.log\n!a.txt\nbuild/*.log\n*.tmp\ntmp/*.tmp\n";
+
+        // Act
+        let (output, rewritten) = run_gix(content, &["check"]);
+
+        // Assert: Only the finding with nothing in between is a safe fix
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("line 3: 'build/*.log' is covered by '*.log' on line 1 [GIX002 redundant, fix: behavior-may-change]"));
+        assert!(stdout.contains("line 5: 'tmp/*.tmp' is covered by '*.tmp' on line 4 [GIX002 redundant, fix: safe]"));
+        assert_eq!(rewritten, content);
+    }
+
+    #[test]
+    fn should_render_only_the_selected_analysis_sections() {
+        // Arrange
+        let content = "*.log\n!debug.log\nbuild/\n";
+
+        // Act
+        let (conflicts, rewritten) = run_gix(content, &["analyze", "--only", "conflicts"]);
+        let (negations, _) = run_gix(content, &["analyze", "--only", "negations", "--format", "json"]);
+
+        // Assert: Neither counts nor sections that weren't asked for are shown
+        let stdout = String::from_utf8_lossy(&conflicts.stdout);
+        assert!(stdout.contains("1 conflict(s):\n    *.log <-> !debug.log"));
+        assert!(!stdout.contains("Total patterns"));
+        assert!(!stdout.contains("Pattern features"));
+        assert_eq!(rewritten, content);
+        let json = String::from_utf8_lossy(&negations.stdout);
+        assert!(json.contains("\"patterns\": [\n    \"!debug.log\"\n  ]"));
+        assert!(!json.contains("total_patterns"));
+        assert!(!json.contains("conflicts"));
+    }
+
+    #[test]
+    fn should_leave_file_untouched_in_dry_run() {
+        let content = "build/\nbuild/output.o\n";
+        let (output, rewritten) = run_gix(content, &["--mode", "advanced", "--dry-run"]);
+
+        assert!(output.status.success());
+        assert_eq!(rewritten, content);
+    }
+
+    #[test]
+    fn should_tell_changes_from_failures_by_exit_code() {
+        // Arrange
+        let content = "*.log\n*.log\n";
+
+        // Act: Optimize twice with --exit-code, then read a file that doesn't exist and one that isn't UTF-8
+        let (changed, rewritten) = run_gix(content, &["--exit-code"]);
+        let (unchanged, _) = run_gix(&rewritten, &["--exit-code"]);
+        let missing = Command::new(env!("CARGO_BIN_EXE_gix"))
+            .arg("/nonexistent/.gitignore")
+            .output()
+            .expect("Failed to run gix");
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let latin1 = dir.path().join(".gitignore");
+        std::fs::write(&latin1, b"caf\xe9/\n").expect("Failed to write gitignore");
+        let undecodable = Command::new(env!("CARGO_BIN_EXE_gix"))
+            .arg(&latin1)
+            .output()
+            .expect("Failed to run gix");
+
+        // Assert: 1 for changes, 0 when already optimized, 3 for the IO error, 4 for the decode error
+        assert_eq!(changed.status.code(), Some(1));
+        assert_eq!(unchanged.status.code(), Some(0));
+        assert_eq!(missing.status.code(), Some(3));
+        assert_eq!(undecodable.status.code(), Some(4));
+    }
+
+    #[test]
+    fn should_match_library_advanced_optimization() {
+        // Arrange
+        let content = "*.log\nlogs/*.log\nnode_modules/\n**/node_modules/";
+        let file = parse_gitignore(content).expect("Failed to parse gitignore content");
+
+        // Act
+        let (optimized, redundant) = optimize_gitignore_advanced(&file, &PatternAnalyzer::safe())
+            .expect("Failed to optimize gitignore file");
+
+        // Assert
+        assert_entry_counts(&optimized, 2, 2);
+        assert_entry_exists(&optimized, "*.log");
+        assert_entry_exists(&optimized, "node_modules/");
+        assert_eq!(redundant.len(), 2);
+    }
+}
@@ -0,0 +1,77 @@
+//! Catches optimization passes turning quadratic again, with generous time limits.
+//!
+//! Only enforced for optimized builds:
+//!   cargo test --release --test performance
+
+use gix::cli::args::OptimizationMode;
+use gix::core::fold_character_classes;
+use gix::core::optimizer::PassStats;
+use gix::core::pattern_analyzer::PatternAnalyzer;
+use gix::utils::diff::{unified_diff, DEFAULT_CONTEXT};
+use gix::utils::synthetic::{synthetic_gitignore, FixtureSize};
+use gix::{parse_gitignore, EngineConfig, GixEngine};
+use std::time::{Duration, Instant};
+
+/// Run `work` and fail if it takes longer than `limit`
+fn assert_within(limit: Duration, what: &str, work: impl FnOnce()) {
+    let started = Instant::now();
+    work();
+    let elapsed = started.elapsed();
+    assert!(elapsed <= limit, "{} took {:?}, limit is {:?}", what, elapsed, limit);
+}
+
+#[test]
+fn test_huge_file_optimizes_in_time() {
+    if cfg!(debug_assertions) {
+        return;
+    }
+
+    let content = synthetic_gitignore(FixtureSize::Huge.lines());
+    let file = parse_gitignore(&content).unwrap();
+    for mode in [OptimizationMode::Conservative, OptimizationMode::Standard, OptimizationMode::Aggressive, OptimizationMode::Advanced] {
+        let engine = GixEngine::new(EngineConfig { mode: mode.clone(), offline: true, ..EngineConfig::default() });
+        assert_within(Duration::from_secs(5), mode.name(), || {
+            engine.optimize(&file).unwrap();
+        });
+    }
+}
+
+#[test]
+fn test_huge_file_conflicts_in_time() {
+    if cfg!(debug_assertions) {
+        return;
+    }
+
+    let file = parse_gitignore(&synthetic_gitignore(FixtureSize::Huge.lines())).unwrap();
+    let patterns: Vec<String> = file.entries.iter().filter_map(|entry| entry.comparison_pattern()).collect();
+    assert_within(Duration::from_secs(2), "conflict detection", || {
+        PatternAnalyzer::default().find_conflicts(&patterns);
+    });
+}
+
+#[test]
+fn test_huge_file_folds_in_time() {
+    if cfg!(debug_assertions) {
+        return;
+    }
+
+    let file = parse_gitignore(&synthetic_gitignore(FixtureSize::Huge.lines())).unwrap();
+    assert_within(Duration::from_secs(2), "character class folding", || {
+        fold_character_classes(&file, &[], &mut Vec::new(), &mut PassStats::default());
+    });
+}
+
+#[test]
+fn test_huge_file_diffs_in_time() {
+    if cfg!(debug_assertions) {
+        return;
+    }
+
+    // Folding rewrites most lines, so few are left to anchor the diff
+    let content = synthetic_gitignore(FixtureSize::Huge.lines());
+    let engine = GixEngine::new(EngineConfig { mode: OptimizationMode::Advanced, offline: true, ..EngineConfig::default() });
+    let optimized = format!("{}", engine.optimize(&parse_gitignore(&content).unwrap()).unwrap().0);
+    assert_within(Duration::from_secs(2), "diff", || {
+        unified_diff("a", "b", &content, &optimized, DEFAULT_CONTEXT);
+    });
+}
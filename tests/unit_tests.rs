@@ -396,8 +396,7 @@ BUILD/
         
         // Assert: Should handle gracefully (assuming parser accepts this)
         // This test demonstrates how to handle potential error cases
-        if parse_result.is_ok() {
-            let file = parse_result.unwrap();
+        if let Ok(file) = parse_result {
             let optimize_result = optimize_gitignore(&file);
             assert!(optimize_result.is_ok());
         }